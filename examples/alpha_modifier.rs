@@ -0,0 +1,208 @@
+//! Fades a window in and out with the alpha modifier, without redrawing its buffer.
+//!
+//! The opacity is updated on every frame callback, and the buffer is only drawn when the window is
+//! configured.
+//!
+//! ```text
+//! cargo run --example alpha_modifier
+//! ```
+
+use std::{convert::TryInto, f64::consts::PI, time::Instant};
+
+use smithay_client_toolkit::{
+    alpha_modifier::{AlphaModifierState, AlphaModifierSurface},
+    compositor::{CompositorHandler, CompositorState},
+    delegate_alpha_modifier, delegate_compositor, delegate_output, delegate_registry, delegate_shm,
+    delegate_xdg_shell, delegate_xdg_window,
+    output::{OutputHandler, OutputState},
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    shell::{
+        xdg::{
+            window::{Window, WindowConfigure, WindowDecorations, WindowHandler},
+            XdgShell, XdgShellHandler,
+        },
+        WaylandSurface,
+    },
+    shm::{slot::SlotPool, Shm, ShmHandler},
+};
+use wayland_client::{
+    globals::registry_queue_init,
+    protocol::{wl_output, wl_shm, wl_surface},
+    Connection, QueueHandle,
+};
+
+/// The time to fade out and back in, in seconds.
+const PERIOD: f64 = 3.0;
+
+fn main() {
+    env_logger::init();
+
+    let conn = Connection::connect_to_env().unwrap();
+
+    let (globals, mut event_queue) = registry_queue_init(&conn).unwrap();
+    let qh = event_queue.handle();
+
+    let compositor = CompositorState::bind(&globals, &qh).expect("wl_compositor not available");
+    let xdg_shell = XdgShell::bind(&globals, &qh).expect("xdg shell is not available");
+    let shm = Shm::bind(&globals, &qh).expect("wl shm is not available.");
+    let alpha_modifier_state =
+        AlphaModifierState::bind(&globals, &qh).expect("wp_alpha_modifier_v1 is not available");
+
+    let surface = compositor.create_surface(&qh);
+    let alpha_modifier = alpha_modifier_state.get_surface(&surface, &qh);
+    let window = xdg_shell.create_window(surface, WindowDecorations::RequestServer, &qh);
+    window.set_title("A fading window");
+    window.set_app_id("io.github.smithay.client-toolkit.AlphaModifier");
+    window.set_min_size(Some((256, 256)));
+    window.commit();
+
+    let pool = SlotPool::new(256 * 256 * 4, &shm).expect("Failed to create pool");
+
+    let mut fading = Fading {
+        registry_state: RegistryState::new(&globals),
+        output_state: OutputState::new(&globals, &qh),
+        shm,
+
+        exit: false,
+        pool,
+        width: 256,
+        height: 256,
+        start: Instant::now(),
+        alpha_modifier,
+        window,
+    };
+
+    loop {
+        event_queue.blocking_dispatch(&mut fading).unwrap();
+
+        if fading.exit {
+            println!("exiting example");
+            break;
+        }
+    }
+}
+
+struct Fading {
+    registry_state: RegistryState,
+    output_state: OutputState,
+    shm: Shm,
+
+    exit: bool,
+    pool: SlotPool,
+    width: u32,
+    height: u32,
+    start: Instant,
+    // Dropped before the window, since the alpha modifier must be destroyed before the surface.
+    alpha_modifier: AlphaModifierSurface,
+    window: Window,
+}
+
+impl Fading {
+    fn draw(&mut self, qh: &QueueHandle<Self>) {
+        let (width, height) = (self.width, self.height);
+        let (buffer, canvas) = self
+            .pool
+            .create_buffer(width as i32, height as i32, width as i32 * 4, wl_shm::Format::Xrgb8888)
+            .expect("create buffer");
+
+        canvas.chunks_exact_mut(4).for_each(|chunk| {
+            let array: &mut [u8; 4] = chunk.try_into().unwrap();
+            *array = 0xFF4080C0u32.to_le_bytes();
+        });
+
+        self.window.wl_surface().damage_buffer(0, 0, width as i32, height as i32);
+        buffer.attach_to(self.window.wl_surface()).expect("buffer attach");
+        self.fade(qh);
+    }
+
+    /// Update the opacity of the window, and commit it along with a request for the next frame.
+    fn fade(&self, qh: &QueueHandle<Self>) {
+        let phase = self.start.elapsed().as_secs_f64() / PERIOD * 2.0 * PI;
+        self.alpha_modifier.set_multiplier(0.5 + 0.5 * phase.cos());
+
+        self.window.request_frame(qh);
+        self.window.commit();
+    }
+}
+
+impl XdgShellHandler for Fading {}
+
+impl WindowHandler for Fading {
+    fn request_close(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &Window) {
+        self.exit = true;
+    }
+
+    fn configure(
+        &mut self,
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+        _: &Window,
+        configure: WindowConfigure,
+        _: u32,
+    ) {
+        self.width = configure.new_size.0.map(|w| w.get()).unwrap_or(256);
+        self.height = configure.new_size.1.map(|h| h.get()).unwrap_or(256);
+        self.draw(qh);
+    }
+}
+
+impl CompositorHandler for Fading {
+    fn scale_factor_changed(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &wl_surface::WlSurface,
+        _: i32,
+    ) {
+    }
+
+    fn transform_changed(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &wl_surface::WlSurface,
+        _: wl_output::Transform,
+    ) {
+    }
+
+    fn frame(&mut self, _: &Connection, qh: &QueueHandle<Self>, _: &wl_surface::WlSurface, _: u32) {
+        self.fade(qh);
+    }
+}
+
+impl OutputHandler for Fading {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+
+    fn update_output(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+
+    fn output_destroyed(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+}
+
+impl ShmHandler for Fading {
+    fn shm_state(&mut self) -> &mut Shm {
+        &mut self.shm
+    }
+}
+
+delegate_compositor!(Fading);
+delegate_output!(Fading);
+delegate_shm!(Fading);
+
+delegate_xdg_shell!(Fading);
+delegate_xdg_window!(Fading);
+delegate_alpha_modifier!(Fading);
+
+delegate_registry!(Fading);
+
+impl ProvidesRegistryState for Fading {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers![OutputState];
+}
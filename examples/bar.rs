@@ -0,0 +1,234 @@
+//! A status bar anchored to the top edge of an output, reserving space for itself with an exclusive
+//! zone so windows are not placed below it.
+//!
+//! The bar casts a shadow over the windows below it, which is made click-through with an input region.
+
+use std::convert::TryInto;
+
+use smithay_client_toolkit::{
+    compositor::{CompositorHandler, CompositorState},
+    delegate_compositor, delegate_layer, delegate_output, delegate_registry, delegate_shm,
+    output::{OutputHandler, OutputState},
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    shell::{
+        wlr_layer::{
+            Anchor, KeyboardInteractivity, Layer, LayerShell, LayerShellHandler, LayerSurface,
+            LayerSurfaceConfigure,
+        },
+        WaylandSurface,
+    },
+    shm::{slot::SlotPool, Shm, ShmHandler},
+};
+use wayland_client::{
+    globals::registry_queue_init,
+    protocol::{wl_output, wl_shm, wl_surface},
+    Connection, QueueHandle,
+};
+
+/// Height of the bar.
+const HEIGHT: u32 = 32;
+
+/// Height of the shadow below the bar.
+const SHADOW: u32 = 8;
+
+fn main() {
+    env_logger::init();
+
+    let conn = Connection::connect_to_env().unwrap();
+
+    let (globals, mut event_queue) = registry_queue_init(&conn).unwrap();
+    let qh = event_queue.handle();
+
+    let compositor = CompositorState::bind(&globals, &qh).expect("wl_compositor is not available");
+    let layer_shell = LayerShell::bind(&globals, &qh).expect("layer shell is not available");
+    let shm = Shm::bind(&globals, &qh).expect("wl_shm is not available");
+
+    let surface = compositor.create_surface(&qh);
+    // Without an output, the compositor places the bar on the output it prefers.
+    let layer = layer_shell.create_layer_surface(&qh, surface, Layer::Top, Some("bar"), None);
+    // Stretch the bar along the top edge: a width of 0 lets the compositor use the full width of the
+    // output, which is only allowed when anchored to both the left and right edges.
+    layer.set_anchor(Anchor::TOP | Anchor::LEFT | Anchor::RIGHT);
+    layer.set_size(0, HEIGHT + SHADOW);
+    // Ask the compositor to keep other surfaces out of the area covered by the bar, the shadow is
+    // drawn over them.
+    layer.set_exclusive_zone(HEIGHT as i32);
+    layer.set_keyboard_interactivity(KeyboardInteractivity::None);
+    // The compositor answers the initial commit with a configure containing the final size.
+    layer.commit();
+
+    let pool =
+        SlotPool::new((HEIGHT + SHADOW) as usize * 1920 * 4, &shm).expect("Failed to create pool");
+
+    let mut bar = Bar {
+        registry_state: RegistryState::new(&globals),
+        output_state: OutputState::new(&globals, &qh),
+        compositor,
+        shm,
+
+        exit: false,
+        pool,
+        width: 0,
+        layer,
+    };
+
+    loop {
+        event_queue.blocking_dispatch(&mut bar).unwrap();
+
+        if bar.exit {
+            println!("exiting example");
+            break;
+        }
+    }
+}
+
+struct Bar {
+    registry_state: RegistryState,
+    output_state: OutputState,
+    compositor: CompositorState,
+    shm: Shm,
+
+    exit: bool,
+    pool: SlotPool,
+    width: u32,
+    layer: LayerSurface,
+}
+
+impl Bar {
+    fn draw(&mut self) {
+        let width = self.width;
+        let height = HEIGHT + SHADOW;
+        let (buffer, canvas) = self
+            .pool
+            .create_buffer(width as i32, height as i32, width as i32 * 4, wl_shm::Format::Argb8888)
+            .expect("create buffer");
+
+        canvas.chunks_exact_mut(4).enumerate().for_each(|(index, chunk)| {
+            let y = index as u32 / width;
+            let color: u32 = if y < HEIGHT - 1 {
+                0xFF2E3440
+            } else if y == HEIGHT - 1 {
+                // A thin line separates the bar from the windows below.
+                0xFF5E81AC
+            } else {
+                // A shadow fading out, in premultiplied alpha.
+                let alpha = 0x60 * (HEIGHT + SHADOW - y) / (SHADOW + 1);
+                alpha << 24
+            };
+
+            let array: &mut [u8; 4] = chunk.try_into().unwrap();
+            *array = color.to_le_bytes();
+        });
+
+        // Only the bar takes input and is opaque, clicks on the shadow go to the windows below.
+        let bar = self.compositor.create_region().with_rect((0, 0, width as i32, HEIGHT as i32));
+        self.layer.set_input_region(Some(&bar));
+        self.layer.set_opaque_region(Some(&bar));
+
+        self.layer.wl_surface().damage_buffer(0, 0, width as i32, height as i32);
+        buffer.attach_to(self.layer.wl_surface()).expect("buffer attach");
+        self.layer.commit();
+    }
+}
+
+impl CompositorHandler for Bar {
+    fn scale_factor_changed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _new_factor: i32,
+    ) {
+    }
+
+    fn transform_changed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _new_transform: wl_output::Transform,
+    ) {
+    }
+
+    fn frame(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _time: u32,
+    ) {
+    }
+}
+
+impl OutputHandler for Bar {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+
+    fn update_output(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+
+    fn output_destroyed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+}
+
+impl LayerShellHandler for Bar {
+    fn closed(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _layer: &LayerSurface) {
+        // The output of the bar was removed, or the compositor closed it for another reason.
+        self.exit = true;
+    }
+
+    fn configure(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _layer: &LayerSurface,
+        configure: LayerSurfaceConfigure,
+        _serial: u32,
+    ) {
+        // The width is the width of the output, since the bar is anchored to both sides.
+        self.width = configure.new_size.0.max(1);
+        self.draw();
+    }
+}
+
+impl ShmHandler for Bar {
+    fn shm_state(&mut self) -> &mut Shm {
+        &mut self.shm
+    }
+}
+
+delegate_compositor!(Bar);
+delegate_output!(Bar);
+delegate_shm!(Bar);
+
+delegate_layer!(Bar);
+
+delegate_registry!(Bar);
+
+impl ProvidesRegistryState for Bar {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers![OutputState];
+}
@@ -0,0 +1,350 @@
+//! Draws a trivial titlebar and border to move and resize a window without server side decorations.
+//!
+//! Drag the titlebar to move the window, or its edges to resize it. Right-click the titlebar to open the
+//! window menu of the compositor. The button on the right of the titlebar toggles maximization, and is
+//! hidden if the compositor does not support maximizing windows.
+
+use std::convert::TryInto;
+
+use smithay_client_toolkit::reexports::csd_frame::WindowManagerCapabilities;
+use smithay_client_toolkit::{
+    compositor::{CompositorHandler, CompositorState},
+    delegate_compositor, delegate_output, delegate_pointer, delegate_registry, delegate_seat,
+    delegate_shm, delegate_xdg_shell, delegate_xdg_window,
+    output::{OutputHandler, OutputState},
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    seat::{
+        pointer::{PointerButton, PointerEvent, PointerEventKind, PointerHandler},
+        Capability, SeatHandler, SeatState,
+    },
+    shell::{
+        xdg::{
+            window::{resize_edge_at, Window, WindowConfigure, WindowDecorations, WindowHandler},
+            XdgShell, XdgShellHandler,
+        },
+        WaylandSurface,
+    },
+    shm::{slot::SlotPool, Shm, ShmHandler},
+};
+use wayland_client::{
+    globals::registry_queue_init,
+    protocol::{wl_output, wl_pointer, wl_seat, wl_shm, wl_surface},
+    Connection, QueueHandle,
+};
+
+/// Height of the titlebar.
+const TITLEBAR: u32 = 32;
+/// Width of the border in which the window can be resized.
+const BORDER: u32 = 6;
+/// Size of the maximize button, on the right of the titlebar.
+const BUTTON: u32 = 20;
+
+fn main() {
+    env_logger::init();
+
+    let conn = Connection::connect_to_env().unwrap();
+
+    let (globals, mut event_queue) = registry_queue_init(&conn).unwrap();
+    let qh = event_queue.handle();
+
+    let compositor = CompositorState::bind(&globals, &qh).expect("wl_compositor not available");
+    let xdg_shell = XdgShell::bind(&globals, &qh).expect("xdg shell is not available");
+    let shm = Shm::bind(&globals, &qh).expect("wl shm is not available.");
+
+    let surface = compositor.create_surface(&qh);
+    // Always draw our own decorations, even if the compositor could draw them.
+    let window = xdg_shell.create_window(surface, WindowDecorations::ClientOnly, &qh);
+    window.set_title("Client side decorations");
+    window.set_app_id("io.github.smithay.client-toolkit.ClientDecorations");
+    window.set_min_size(Some((128, 128)));
+    window.commit();
+
+    let pool = SlotPool::new(256 * 256 * 4, &shm).expect("Failed to create pool");
+
+    let mut client_decorations = ClientDecorations {
+        registry_state: RegistryState::new(&globals),
+        seat_state: SeatState::new(&globals, &qh),
+        output_state: OutputState::new(&globals, &qh),
+        shm,
+
+        exit: false,
+        pool,
+        width: 256,
+        height: 256,
+        maximized: false,
+        capabilities: WindowManagerCapabilities::all(),
+        window,
+        pointer: None,
+    };
+
+    loop {
+        event_queue.blocking_dispatch(&mut client_decorations).unwrap();
+
+        if client_decorations.exit {
+            println!("exiting example");
+            break;
+        }
+    }
+}
+
+struct ClientDecorations {
+    registry_state: RegistryState,
+    seat_state: SeatState,
+    output_state: OutputState,
+    shm: Shm,
+
+    exit: bool,
+    pool: SlotPool,
+    width: u32,
+    height: u32,
+    maximized: bool,
+    capabilities: WindowManagerCapabilities,
+    window: Window,
+    pointer: Option<(wl_seat::WlSeat, wl_pointer::WlPointer)>,
+}
+
+impl ClientDecorations {
+    /// Whether the position is over the maximize button, if it is shown.
+    fn over_maximize_button(&self, position: (f64, f64)) -> bool {
+        self.capabilities.contains(WindowManagerCapabilities::MAXIMIZE)
+            && over_button(self.width, position)
+    }
+
+    fn draw(&mut self) {
+        let show_button = self.capabilities.contains(WindowManagerCapabilities::MAXIMIZE);
+        let (width, height) = (self.width, self.height);
+        let (buffer, canvas) = self
+            .pool
+            .create_buffer(width as i32, height as i32, width as i32 * 4, wl_shm::Format::Argb8888)
+            .expect("create buffer");
+
+        canvas.chunks_exact_mut(4).enumerate().for_each(|(index, chunk)| {
+            let x = index as u32 % width;
+            let y = index as u32 / width;
+
+            let color: u32 = if show_button && over_button(width, (x as f64, y as f64)) {
+                0xFF808080
+            } else if x < BORDER || y < BORDER || x >= width - BORDER || y >= height - BORDER {
+                0xFF202020
+            } else if y < TITLEBAR {
+                0xFF404040
+            } else {
+                0xFFE0E0E0
+            };
+
+            let array: &mut [u8; 4] = chunk.try_into().unwrap();
+            *array = color.to_le_bytes();
+        });
+
+        self.window.wl_surface().damage_buffer(0, 0, width as i32, height as i32);
+        buffer.attach_to(self.window.wl_surface()).expect("buffer attach");
+        self.window.commit();
+    }
+}
+
+/// Whether the position is over the maximize button of a window of the given width.
+fn over_button(width: u32, (x, y): (f64, f64)) -> bool {
+    let left = width.saturating_sub(BORDER + BUTTON) as f64;
+    let top = ((TITLEBAR - BUTTON) / 2) as f64;
+    x >= left && x < left + BUTTON as f64 && y >= top && y < top + BUTTON as f64
+}
+
+impl CompositorHandler for ClientDecorations {
+    fn scale_factor_changed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _new_factor: i32,
+    ) {
+    }
+
+    fn transform_changed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _new_transform: wl_output::Transform,
+    ) {
+    }
+
+    fn frame(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _time: u32,
+    ) {
+    }
+}
+
+impl OutputHandler for ClientDecorations {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+
+    fn update_output(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+
+    fn output_destroyed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+}
+
+impl XdgShellHandler for ClientDecorations {}
+
+impl WindowHandler for ClientDecorations {
+    fn request_close(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &Window) {
+        self.exit = true;
+    }
+
+    fn configure(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _window: &Window,
+        configure: WindowConfigure,
+        _serial: u32,
+    ) {
+        // Pick our own size if the compositor does not, without going over the suggested bounds.
+        let (max_width, max_height) = configure.suggested_bounds.unwrap_or((256, 256));
+        self.width = configure.new_size.0.map_or(u32::min(256, max_width), |v| v.get());
+        self.height = configure.new_size.1.map_or(u32::min(256, max_height), |v| v.get());
+        self.maximized = configure.is_maximized();
+        self.capabilities = configure.capabilities;
+        self.draw();
+    }
+}
+
+impl SeatHandler for ClientDecorations {
+    fn seat_state(&mut self) -> &mut SeatState {
+        &mut self.seat_state
+    }
+
+    fn new_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_seat::WlSeat) {}
+
+    fn new_capability(
+        &mut self,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+        seat: wl_seat::WlSeat,
+        capability: Capability,
+    ) {
+        if capability == Capability::Pointer && self.pointer.is_none() {
+            let pointer = self.seat_state.get_pointer(qh, &seat).expect("Failed to create pointer");
+            self.pointer = Some((seat, pointer));
+        }
+    }
+
+    fn remove_capability(
+        &mut self,
+        _conn: &Connection,
+        _: &QueueHandle<Self>,
+        _: wl_seat::WlSeat,
+        capability: Capability,
+    ) {
+        if capability == Capability::Pointer {
+            if let Some((_, pointer)) = self.pointer.take() {
+                pointer.release();
+            }
+        }
+    }
+
+    fn remove_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_seat::WlSeat) {}
+}
+
+impl PointerHandler for ClientDecorations {
+    fn pointer_frame(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _pointer: &wl_pointer::WlPointer,
+        events: &[PointerEvent],
+    ) {
+        let seat = match &self.pointer {
+            Some((seat, _)) => seat.clone(),
+            None => return,
+        };
+
+        for event in events {
+            if &event.surface != self.window.wl_surface() {
+                continue;
+            }
+
+            if let PointerEventKind::Press { button, serial, .. } = event.kind {
+                let (x, y) = event.position;
+                let edge = resize_edge_at(event.position, self.width, self.height, BORDER);
+
+                match PointerButton::from(button) {
+                    PointerButton::Left => {
+                        if self.over_maximize_button(event.position) {
+                            if self.maximized {
+                                self.window.unset_maximized();
+                            } else {
+                                self.window.set_maximized();
+                            }
+                        } else if let Some(edge) = edge {
+                            self.window.resize(&seat, serial, edge);
+                        } else if y < TITLEBAR as f64 {
+                            self.window.move_(&seat, serial);
+                        }
+                    }
+                    PointerButton::Right
+                        if edge.is_none()
+                            && y < TITLEBAR as f64
+                            && self
+                                .capabilities
+                                .contains(WindowManagerCapabilities::WINDOW_MENU) =>
+                    {
+                        self.window.show_window_menu(&seat, serial, (x as i32, y as i32));
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+impl ShmHandler for ClientDecorations {
+    fn shm_state(&mut self) -> &mut Shm {
+        &mut self.shm
+    }
+}
+
+delegate_compositor!(ClientDecorations);
+delegate_output!(ClientDecorations);
+delegate_shm!(ClientDecorations);
+
+delegate_seat!(ClientDecorations);
+delegate_pointer!(ClientDecorations);
+
+delegate_xdg_shell!(ClientDecorations);
+delegate_xdg_window!(ClientDecorations);
+
+delegate_registry!(ClientDecorations);
+
+impl ProvidesRegistryState for ClientDecorations {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers![OutputState, SeatState];
+}
@@ -32,13 +32,13 @@ use smithay_client_toolkit::{
     registry_handlers,
     seat::{
         keyboard::{KeyEvent, KeyboardHandler, Keysym, Modifiers},
-        pointer::{PointerEvent, PointerEventKind, PointerHandler, BTN_LEFT},
+        pointer::{PointerButton, PointerEvent, PointerEventKind, PointerHandler},
         Capability, SeatHandler, SeatState,
     },
     shell::{
         xdg::{
             window::{Window, WindowConfigure, WindowDecorations, WindowHandler},
-            XdgShell,
+            XdgShell, XdgShellHandler,
         },
         WaylandSurface,
     },
@@ -254,6 +254,8 @@ impl OutputHandler for DataDeviceWindow {
     }
 }
 
+impl XdgShellHandler for DataDeviceWindow {}
+
 impl WindowHandler for DataDeviceWindow {
     fn request_close(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &Window) {
         self.exit = true;
@@ -464,7 +466,10 @@ impl PointerHandler for DataDeviceWindow {
             let surface = event.surface.clone();
 
             match event.kind {
-                Press { button, serial, .. } if button == BTN_LEFT && self.modifiers.ctrl => {
+                Press { button, serial, .. }
+                    if PointerButton::from(button) == PointerButton::Left
+                        && self.modifiers.ctrl =>
+                {
                     if let Some(seat) =
                         self.seat_objects.iter().find(|seat| seat.pointer.as_ref() == Some(pointer))
                     {
@@ -477,7 +482,9 @@ impl PointerHandler for DataDeviceWindow {
                         );
                     }
                 }
-                Press { button, serial, .. } if button == BTN_LEFT => {
+                Press { button, serial, .. }
+                    if PointerButton::from(button) == PointerButton::Left =>
+                {
                     if let Some(seat) =
                         self.seat_objects.iter().find(|seat| seat.pointer.as_ref() == Some(pointer))
                     {
@@ -564,11 +571,11 @@ impl DataDeviceWindow {
         self.window.wl_surface().damage_buffer(0, 0, self.width as i32, self.height as i32);
 
         // Request our next frame
-        self.window.wl_surface().frame(qh, self.window.wl_surface().clone());
+        self.window.request_frame(qh);
 
         // Attach and commit to present.
         buffer.attach_to(self.window.wl_surface()).expect("buffer attach");
-        self.window.wl_surface().commit();
+        self.window.commit();
     }
 }
 
@@ -0,0 +1,290 @@
+//! Animates a window at 30 frames per second, whatever the refresh rate of the output is.
+//!
+//! Instead of drawing on frame callbacks, a few frames are queued in advance. Each commit waits for
+//! the FIFO barrier of the previous one and targets its own time with commit timing, and presentation
+//! feedback tells when to queue the next frame.
+//!
+//! ```text
+//! cargo run --example fixed_rate
+//! ```
+
+use std::{convert::TryInto, time::Duration};
+
+use smithay_client_toolkit::{
+    commit_timing::{CommitTimer, CommitTimingState},
+    compositor::{CompositorHandler, CompositorState},
+    delegate_commit_timing, delegate_compositor, delegate_fifo, delegate_output,
+    delegate_presentation_time, delegate_registry, delegate_shm, delegate_xdg_shell,
+    delegate_xdg_window,
+    fifo::{Fifo, FifoState},
+    output::{OutputHandler, OutputState},
+    presentation_time::{PresentationHandler, PresentationState, PresentationTime, Presented},
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    shell::{
+        xdg::{
+            window::{Window, WindowConfigure, WindowDecorations, WindowHandler},
+            XdgShell, XdgShellHandler,
+        },
+        WaylandSurface,
+    },
+    shm::{slot::SlotPool, Shm, ShmHandler},
+};
+use wayland_client::{
+    globals::registry_queue_init,
+    protocol::{wl_output, wl_shm, wl_surface},
+    Connection, QueueHandle,
+};
+
+/// The duration of a frame at 30 frames per second.
+const FRAME_DURATION: Duration = Duration::from_nanos(1_000_000_000 / 30);
+
+/// The number of frames queued in advance.
+const QUEUE_DEPTH: u32 = 2;
+
+fn main() {
+    env_logger::init();
+
+    let conn = Connection::connect_to_env().unwrap();
+
+    let (globals, mut event_queue) = registry_queue_init(&conn).unwrap();
+    let qh = event_queue.handle();
+
+    let compositor = CompositorState::bind(&globals, &qh).expect("wl_compositor not available");
+    let xdg_shell = XdgShell::bind(&globals, &qh).expect("xdg shell is not available");
+    let shm = Shm::bind(&globals, &qh).expect("wl shm is not available.");
+    let presentation =
+        PresentationState::bind(&globals, &qh).expect("wp_presentation is not available");
+    let fifo_state = FifoState::bind(&globals, &qh).expect("wp_fifo_manager_v1 is not available");
+    let commit_timing_state = CommitTimingState::bind(&globals, &qh)
+        .expect("wp_commit_timing_manager_v1 is not available");
+
+    let surface = compositor.create_surface(&qh);
+    let fifo = fifo_state.get_fifo(&surface, &qh);
+    let commit_timer = commit_timing_state.get_timer(&surface, &qh);
+    let window = xdg_shell.create_window(surface, WindowDecorations::RequestServer, &qh);
+    window.set_title("30 frames per second");
+    window.set_app_id("io.github.smithay.client-toolkit.FixedRate");
+    window.set_min_size(Some((256, 256)));
+    window.commit();
+
+    let pool = SlotPool::new(256 * 256 * 4, &shm).expect("Failed to create pool");
+
+    let mut animation = Animation {
+        registry_state: RegistryState::new(&globals),
+        output_state: OutputState::new(&globals, &qh),
+        shm,
+        presentation,
+
+        exit: false,
+        pool,
+        width: 256,
+        height: 256,
+        start: None,
+        frame: 0,
+        done: 0,
+        fifo,
+        commit_timer,
+        window,
+    };
+
+    loop {
+        event_queue.blocking_dispatch(&mut animation).unwrap();
+
+        if animation.exit {
+            println!("exiting example");
+            break;
+        }
+    }
+}
+
+struct Animation {
+    registry_state: RegistryState,
+    output_state: OutputState,
+    shm: Shm,
+    presentation: PresentationState,
+
+    exit: bool,
+    pool: SlotPool,
+    width: u32,
+    height: u32,
+    /// The target time of the first frame.
+    start: Option<PresentationTime>,
+    /// The number of the next frame to queue.
+    frame: u64,
+    /// The number of frames that were presented or discarded.
+    done: u64,
+    // Dropped before the window, since they must be destroyed before the surface.
+    fifo: Fifo,
+    commit_timer: CommitTimer,
+    window: Window,
+}
+
+impl Animation {
+    /// The target time of a frame.
+    fn target_time(&self, frame: u64) -> Option<PresentationTime> {
+        self.start?.checked_add(FRAME_DURATION.checked_mul(frame.try_into().ok()?)?)
+    }
+
+    /// Queue frames until [`QUEUE_DEPTH`] frames are in flight.
+    fn queue_frames(&mut self, qh: &QueueHandle<Self>) {
+        while self.frame - self.done < u64::from(QUEUE_DEPTH) {
+            self.queue_frame(qh);
+        }
+    }
+
+    fn queue_frame(&mut self, qh: &QueueHandle<Self>) {
+        if self.start.is_none() {
+            // The clock is sent right after the global is bound, so it is known by the first
+            // configure.
+            let clock_id = self.presentation.clock_id().unwrap_or(libc::CLOCK_MONOTONIC as u32);
+            self.start = PresentationTime::now(clock_id);
+        }
+        let target = self.target_time(self.frame).expect("no target time for the frame");
+
+        let (width, height) = (self.width, self.height);
+        let (buffer, canvas) = self
+            .pool
+            .create_buffer(width as i32, height as i32, width as i32 * 4, wl_shm::Format::Xrgb8888)
+            .expect("create buffer");
+
+        // A bar sweeping across the window once per second.
+        let bar = (self.frame % 30) as u32 * width / 30;
+        canvas.chunks_exact_mut(4).enumerate().for_each(|(index, chunk)| {
+            let x = index as u32 % width;
+            let color: u32 =
+                if x >= bar && x < bar + width / 30 + 1 { 0xFFFFFFFF } else { 0xFF202020 };
+            chunk.copy_from_slice(&color.to_le_bytes());
+        });
+
+        let surface = self.window.wl_surface();
+        surface.damage_buffer(0, 0, width as i32, height as i32);
+        buffer.attach_to(surface).expect("buffer attach");
+
+        // Wait for the previous frame to be latched, and keep this one until it is latched as well.
+        self.fifo.wait_barrier();
+        self.fifo.set_barrier();
+        self.commit_timer.set_target_time(target);
+        self.presentation.feedback(surface, qh);
+        self.window.commit();
+
+        self.frame += 1;
+    }
+}
+
+impl PresentationHandler for Animation {
+    fn presentation_state(&mut self) -> &mut PresentationState {
+        &mut self.presentation
+    }
+
+    fn presented(
+        &mut self,
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+        _: &wl_surface::WlSurface,
+        presented: Presented,
+    ) {
+        // Feedback is delivered in the order of the commits.
+        let frame = self.done;
+        self.done += 1;
+
+        if frame % 30 == 0 {
+            let target = self.target_time(frame);
+            if let Some(late) = target.and_then(|target| presented.time.duration_since(target)) {
+                println!("Frame {frame} shown {late:?} after its target time");
+            }
+        }
+
+        self.queue_frames(qh);
+    }
+
+    fn discarded(&mut self, _: &Connection, qh: &QueueHandle<Self>, _: &wl_surface::WlSurface) {
+        self.done += 1;
+        self.queue_frames(qh);
+    }
+}
+
+impl XdgShellHandler for Animation {}
+
+impl WindowHandler for Animation {
+    fn request_close(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &Window) {
+        self.exit = true;
+    }
+
+    fn configure(
+        &mut self,
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+        _: &Window,
+        configure: WindowConfigure,
+        _: u32,
+    ) {
+        self.width = configure.new_size.0.map(|w| w.get()).unwrap_or(256);
+        self.height = configure.new_size.1.map(|h| h.get()).unwrap_or(256);
+
+        // Later configures are acknowledged by the next queued frame.
+        if self.start.is_none() {
+            self.queue_frames(qh);
+        }
+    }
+}
+
+impl CompositorHandler for Animation {
+    fn scale_factor_changed(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &wl_surface::WlSurface,
+        _: i32,
+    ) {
+    }
+
+    fn transform_changed(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &wl_surface::WlSurface,
+        _: wl_output::Transform,
+    ) {
+    }
+
+    fn frame(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &wl_surface::WlSurface, _: u32) {}
+}
+
+impl OutputHandler for Animation {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+
+    fn update_output(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+
+    fn output_destroyed(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+}
+
+impl ShmHandler for Animation {
+    fn shm_state(&mut self) -> &mut Shm {
+        &mut self.shm
+    }
+}
+
+delegate_compositor!(Animation);
+delegate_output!(Animation);
+delegate_shm!(Animation);
+
+delegate_xdg_shell!(Animation);
+delegate_xdg_window!(Animation);
+delegate_presentation_time!(Animation);
+delegate_fifo!(Animation);
+delegate_commit_timing!(Animation);
+
+delegate_registry!(Animation);
+
+impl ProvidesRegistryState for Animation {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers![OutputState];
+}
@@ -0,0 +1,124 @@
+//! Lists the toplevels of all clients, or activates one of them.
+//!
+//! This needs a compositor supporting wlr foreign toplevel management, such as sway:
+//!
+//! ```text
+//! cargo run --example foreign_toplevel
+//! cargo run --example foreign_toplevel -- <index>
+//! ```
+
+use std::env;
+
+use smithay_client_toolkit::{
+    delegate_foreign_toplevel, delegate_registry, delegate_seat,
+    foreign_toplevel::wlr::{ForeignToplevel, ForeignToplevelHandler, ForeignToplevelState},
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    seat::{Capability, SeatHandler, SeatState},
+};
+use wayland_client::{globals::registry_queue_init, protocol::wl_seat, Connection, QueueHandle};
+
+fn main() {
+    env_logger::init();
+
+    let index = env::args().nth(1).map(|index| index.parse::<usize>().expect("invalid index"));
+
+    let conn = Connection::connect_to_env().unwrap();
+
+    let (globals, mut event_queue) = registry_queue_init(&conn).unwrap();
+    let qh = event_queue.handle();
+
+    let mut list_toplevels = ListToplevels {
+        registry_state: RegistryState::new(&globals),
+        seat_state: SeatState::new(&globals, &qh),
+        foreign_toplevel_state: ForeignToplevelState::bind(&globals, &qh)
+            .expect("wlr foreign toplevel management is not available"),
+    };
+
+    // The compositor sends the toplevels and their metadata as soon as the global is bound.
+    event_queue.roundtrip(&mut list_toplevels).unwrap();
+
+    let toplevels = list_toplevels
+        .foreign_toplevel_state
+        .toplevels()
+        .cloned()
+        .collect::<Vec<ForeignToplevel>>();
+
+    match index {
+        Some(index) => {
+            let toplevel = toplevels.get(index).expect("no toplevel with this index");
+            let seat = list_toplevels.seat_state.seats().next().expect("no seat available");
+            toplevel.activate(&seat);
+            event_queue.roundtrip(&mut list_toplevels).unwrap();
+        }
+
+        None => {
+            println!("Toplevels:");
+
+            for (index, toplevel) in toplevels.iter().enumerate() {
+                if let Some(info) = toplevel.info() {
+                    println!("{index}: {} ({}) {:?}", info.title, info.app_id, info.state);
+                }
+            }
+        }
+    }
+}
+
+struct ListToplevels {
+    registry_state: RegistryState,
+    seat_state: SeatState,
+    foreign_toplevel_state: ForeignToplevelState,
+}
+
+impl ForeignToplevelHandler for ListToplevels {
+    fn foreign_toplevel_state(&mut self) -> &mut ForeignToplevelState {
+        &mut self.foreign_toplevel_state
+    }
+
+    fn new_toplevel(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &ForeignToplevel) {}
+
+    fn update_toplevel(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &ForeignToplevel) {}
+
+    fn toplevel_closed(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &ForeignToplevel) {}
+}
+
+impl SeatHandler for ListToplevels {
+    fn seat_state(&mut self) -> &mut SeatState {
+        &mut self.seat_state
+    }
+
+    fn new_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_seat::WlSeat) {}
+
+    fn new_capability(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: wl_seat::WlSeat,
+        _: Capability,
+    ) {
+    }
+
+    fn remove_capability(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: wl_seat::WlSeat,
+        _: Capability,
+    ) {
+    }
+
+    fn remove_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_seat::WlSeat) {}
+}
+
+delegate_seat!(ListToplevels);
+delegate_foreign_toplevel!(ListToplevels);
+
+delegate_registry!(ListToplevels);
+
+impl ProvidesRegistryState for ListToplevels {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers![SeatState];
+}
@@ -0,0 +1,162 @@
+//! Presents a colored buffer fullscreen on the default output of a kiosk compositor.
+//!
+//! This needs a compositor supporting the fullscreen shell, such as weston with
+//! `--shell=fullscreen-shell.so`:
+//!
+//! ```text
+//! cargo run --example fullscreen_shell
+//! ```
+
+use smithay_client_toolkit::{
+    compositor::{CompositorHandler, CompositorState},
+    delegate_compositor, delegate_fullscreen_shell, delegate_output, delegate_registry,
+    delegate_shm,
+    output::{OutputHandler, OutputState},
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    shell::fullscreen::{FullscreenShellHandler, FullscreenShellState, PresentMethod},
+    shm::{slot::SlotPool, Shm, ShmHandler},
+};
+use wayland_client::{
+    globals::registry_queue_init,
+    protocol::{wl_output, wl_shm, wl_surface},
+    Connection, QueueHandle,
+};
+
+fn main() {
+    env_logger::init();
+
+    let conn = Connection::connect_to_env().unwrap();
+
+    let (globals, mut event_queue) = registry_queue_init(&conn).unwrap();
+    let qh = event_queue.handle();
+
+    let compositor = CompositorState::bind(&globals, &qh).expect("wl_compositor is not available");
+    let shm = Shm::bind(&globals, &qh).expect("wl_shm is not available");
+
+    let mut kiosk = Kiosk {
+        registry_state: RegistryState::new(&globals),
+        output_state: OutputState::new(&globals, &qh),
+        fullscreen_shell_state: FullscreenShellState::bind(&globals, &qh)
+            .expect("fullscreen shell is not available"),
+        shm,
+    };
+
+    // Wait for the outputs to be known, to draw a buffer of the size of the default one.
+    event_queue.roundtrip(&mut kiosk).unwrap();
+
+    let (width, height) = kiosk
+        .output_state
+        .outputs()
+        .next()
+        .and_then(|output| kiosk.output_state.info(&output))
+        .and_then(|info| info.modes.iter().find(|mode| mode.current).map(|mode| mode.dimensions))
+        .unwrap_or((640, 480));
+
+    let mut pool =
+        SlotPool::new((width * height * 4) as usize, &kiosk.shm).expect("Failed to create pool");
+    let (buffer, canvas) = pool
+        .create_buffer(width, height, width * 4, wl_shm::Format::Xrgb8888)
+        .expect("Failed to create buffer");
+
+    // Teal, in little endian Xrgb8888.
+    for pixel in canvas.chunks_exact_mut(4) {
+        pixel.copy_from_slice(&[0x80, 0x80, 0x00, 0xFF]);
+    }
+
+    let surface = compositor.create_surface(&qh);
+    kiosk.fullscreen_shell_state.present_surface(Some(&surface), PresentMethod::Default, None);
+    buffer.attach_to(&surface).expect("buffer attach");
+    surface.damage_buffer(0, 0, width, height);
+    surface.commit();
+
+    loop {
+        event_queue.blocking_dispatch(&mut kiosk).unwrap();
+    }
+}
+
+struct Kiosk {
+    registry_state: RegistryState,
+    output_state: OutputState,
+    fullscreen_shell_state: FullscreenShellState,
+    shm: Shm,
+}
+
+impl FullscreenShellHandler for Kiosk {
+    fn fullscreen_shell_state(&mut self) -> &mut FullscreenShellState {
+        &mut self.fullscreen_shell_state
+    }
+
+    fn mode_successful(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &wl_surface::WlSurface,
+    ) {
+    }
+
+    fn mode_failed(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &wl_surface::WlSurface) {}
+
+    fn present_cancelled(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &wl_surface::WlSurface,
+    ) {
+    }
+}
+
+impl CompositorHandler for Kiosk {
+    fn scale_factor_changed(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &wl_surface::WlSurface,
+        _: i32,
+    ) {
+    }
+
+    fn transform_changed(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &wl_surface::WlSurface,
+        _: wl_output::Transform,
+    ) {
+    }
+
+    fn frame(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &wl_surface::WlSurface, _: u32) {}
+}
+
+impl OutputHandler for Kiosk {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+
+    fn update_output(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+
+    fn output_destroyed(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+}
+
+impl ShmHandler for Kiosk {
+    fn shm_state(&mut self) -> &mut Shm {
+        &mut self.shm
+    }
+}
+
+delegate_compositor!(Kiosk);
+delegate_output!(Kiosk);
+delegate_shm!(Kiosk);
+delegate_fullscreen_shell!(Kiosk);
+
+delegate_registry!(Kiosk);
+
+impl ProvidesRegistryState for Kiosk {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers![OutputState];
+}
@@ -0,0 +1,115 @@
+//! Warms the colors of all outputs until the program is stopped.
+//!
+//! This needs a compositor supporting wlr gamma control, such as sway:
+//!
+//! ```text
+//! cargo run --example gamma_control -- <temperature in kelvin>
+//! ```
+
+use std::env;
+
+use smithay_client_toolkit::{
+    delegate_gamma_control, delegate_output, delegate_registry,
+    gamma_control::{GammaControl, GammaControlHandler, GammaControlState, GammaRamps},
+    output::{OutputHandler, OutputState},
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+};
+use wayland_client::{globals::registry_queue_init, protocol::wl_output, Connection, QueueHandle};
+
+fn main() {
+    env_logger::init();
+
+    let temperature = env::args()
+        .nth(1)
+        .map(|temperature| temperature.parse::<u32>().expect("invalid temperature"))
+        .unwrap_or(4500);
+
+    let conn = Connection::connect_to_env().unwrap();
+
+    let (globals, mut event_queue) = registry_queue_init(&conn).unwrap();
+    let qh = event_queue.handle();
+
+    let mut night_light = NightLight {
+        registry_state: RegistryState::new(&globals),
+        output_state: OutputState::new(&globals, &qh),
+        gamma_control_state: GammaControlState::bind(&globals, &qh)
+            .expect("wlr gamma control is not available"),
+        temperature,
+        controls: Vec::new(),
+    };
+
+    println!("Setting the color temperature to {temperature} K, stop the program to restore it.");
+
+    loop {
+        event_queue.blocking_dispatch(&mut night_light).unwrap();
+    }
+}
+
+struct NightLight {
+    registry_state: RegistryState,
+    output_state: OutputState,
+    gamma_control_state: GammaControlState,
+    temperature: u32,
+    controls: Vec<GammaControl>,
+}
+
+impl GammaControlHandler for NightLight {
+    fn gamma_size(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        control: &GammaControl,
+        size: u32,
+    ) {
+        let ramps = GammaRamps::from_temperature(size, self.temperature, 1.0);
+        control.set_gamma(&ramps).expect("Failed to set the gamma ramps");
+    }
+
+    fn failed(&mut self, _: &Connection, _: &QueueHandle<Self>, control: &GammaControl) {
+        eprintln!("Failed to control the gamma of an output");
+        self.controls.retain(|c| c != control);
+    }
+}
+
+impl OutputHandler for NightLight {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(&mut self, _: &Connection, qh: &QueueHandle<Self>, output: wl_output::WlOutput) {
+        let control = self.gamma_control_state.get_gamma_control(&output, qh);
+        self.controls.push(control);
+    }
+
+    fn update_output(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+
+    fn output_destroyed(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        output: wl_output::WlOutput,
+    ) {
+        self.controls.retain(|control| {
+            if control.output() == &output {
+                control.destroy();
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+delegate_output!(NightLight);
+delegate_gamma_control!(NightLight);
+
+delegate_registry!(NightLight);
+
+impl ProvidesRegistryState for NightLight {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers![OutputState];
+}
@@ -9,13 +9,13 @@ use smithay_client_toolkit::{
     registry_handlers,
     seat::{
         keyboard::{KeyEvent, KeyboardHandler, Modifiers},
-        pointer::{PointerEvent, PointerEventKind, PointerHandler},
+        pointer::{PointerButton, PointerEvent, PointerEventKind, PointerHandler},
         Capability, SeatHandler, SeatState,
     },
     shell::{
         xdg::{
             window::{Window, WindowConfigure, WindowDecorations, WindowHandler},
-            XdgShell,
+            XdgShell, XdgShellHandler,
         },
         WaylandSurface,
     },
@@ -195,6 +195,8 @@ impl<T: Test + 'static> OutputHandler for SimpleWindow<T> {
     }
 }
 
+impl<T: Test + 'static> XdgShellHandler for SimpleWindow<T> {}
+
 impl<T: Test + 'static> WindowHandler for SimpleWindow<T> {
     fn request_close(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &Window) {
         self.exit = true;
@@ -358,11 +360,11 @@ impl<T: Test + 'static> PointerHandler for SimpleWindow<T> {
                 }
                 Motion { .. } => {}
                 Press { button, .. } => {
-                    println!("Press {:x} @ {:?}", button, event.position);
+                    println!("Press {:?} @ {:?}", PointerButton::from(button), event.position);
                     self.shift = self.shift.xor(Some(0));
                 }
                 Release { button, .. } => {
-                    println!("Release {:x} @ {:?}", button, event.position);
+                    println!("Release {:?} @ {:?}", PointerButton::from(button), event.position);
                 }
                 Axis { horizontal, vertical, .. } => {
                     println!("Scroll H:{horizontal:?}, V:{vertical:?}");
@@ -436,7 +438,7 @@ impl<T: Test + 'static> SimpleWindow<T> {
         self.window.wl_surface().damage_buffer(0, 0, self.width as i32, self.height as i32);
 
         // Request our next frame
-        self.window.wl_surface().frame(qh, self.window.wl_surface().clone());
+        self.window.request_frame(qh);
 
         // Attach and commit to present.
         buffer.attach_to(self.window.wl_surface()).expect("buffer attach");
@@ -10,7 +10,7 @@ use smithay_client_toolkit::{
     shell::{
         xdg::{
             window::{Window, WindowConfigure, WindowDecorations, WindowHandler},
-            XdgShell,
+            XdgShell, XdgShellHandler,
         },
         WaylandSurface,
     },
@@ -191,6 +191,8 @@ impl OutputHandler for State {
     }
 }
 
+impl XdgShellHandler for State {}
+
 impl WindowHandler for State {
     fn request_close(&mut self, _: &Connection, _: &QueueHandle<Self>, window: &Window) {
         self.windows.retain(|v| v.window != *window);
@@ -289,11 +291,11 @@ impl State {
             viewer.damaged = false;
 
             // Request our next frame
-            window.wl_surface().frame(qh, window.wl_surface().clone());
+            window.request_frame(qh);
 
             // Attach and commit to present.
             buffer.attach_to(window.wl_surface()).expect("buffer attach");
-            window.wl_surface().commit();
+            window.commit();
         }
     }
 }
@@ -10,7 +10,7 @@ use smithay_client_toolkit::{
     shell::{
         xdg::{
             window::{Window, WindowConfigure, WindowDecorations, WindowHandler},
-            XdgShell,
+            XdgShell, XdgShellHandler,
         },
         WaylandSurface,
     },
@@ -216,6 +216,8 @@ impl OutputHandler for State {
     }
 }
 
+impl XdgShellHandler for State {}
+
 impl WindowHandler for State {
     fn request_close(&mut self, _: &Connection, _: &QueueHandle<Self>, window: &Window) {
         self.windows.retain(|v| v.window != *window);
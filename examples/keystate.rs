@@ -0,0 +1,62 @@
+//! Prints the changes of the lock keys, using the KDE keystate protocol.
+
+use smithay_client_toolkit::{
+    delegate_keystate, delegate_registry,
+    globals::ProvidesBoundGlobal,
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    seat::keystate::{KeystateHandler, KeystateState, LockKey, LockState},
+};
+use wayland_client::{globals::registry_queue_init, Connection, QueueHandle};
+
+fn main() {
+    env_logger::init();
+
+    let conn = Connection::connect_to_env().unwrap();
+
+    let (globals, mut event_queue) = registry_queue_init(&conn).unwrap();
+    let qh = event_queue.handle();
+
+    let mut watcher = Watcher {
+        registry_state: RegistryState::new(&globals),
+        keystate_state: KeystateState::bind(&globals, &qh),
+    };
+
+    if watcher.keystate_state.bound_global().is_err() {
+        println!("org_kde_kwin_keystate is not available");
+        return;
+    }
+
+    loop {
+        event_queue.blocking_dispatch(&mut watcher).unwrap();
+    }
+}
+
+struct Watcher {
+    registry_state: RegistryState,
+    keystate_state: KeystateState,
+}
+
+impl KeystateHandler for Watcher {
+    fn lock_state_changed(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        key: LockKey,
+        state: LockState,
+    ) {
+        println!("{key:?}: {state:?}");
+    }
+}
+
+delegate_keystate!(Watcher);
+
+delegate_registry!(Watcher);
+
+impl ProvidesRegistryState for Watcher {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers!();
+}
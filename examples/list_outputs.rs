@@ -148,9 +148,12 @@ fn print_output(info: &OutputInfo) {
     if let Some((x, y)) = info.logical_position.as_ref() {
         println!("\tlogical x: {x}, y: {y}");
     }
-    if let Some((width, height)) = info.logical_size.as_ref() {
+    if let Some((width, height)) = info.logical_size() {
         println!("\tlogical width: {width}, height: {height}");
     }
+    if let Some(refresh_rate) = info.refresh_rate() {
+        println!("\trefresh rate: {}.{:03} Hz", refresh_rate / 1000, refresh_rate % 1000);
+    }
     println!("\tmodes:");
 
     for mode in &info.modes {
@@ -0,0 +1,170 @@
+//! Lists the heads of the compositor, or enables or disables one of them.
+//!
+//! This needs a compositor supporting wlr output management, such as sway:
+//!
+//! ```text
+//! cargo run --example output_management
+//! cargo run --example output_management -- <head name>
+//! ```
+
+use std::env;
+
+use smithay_client_toolkit::{
+    delegate_output_management, delegate_registry,
+    output_management::{
+        HeadInfo, OutputConfiguration, OutputHead, OutputManagementHandler, OutputManagementState,
+    },
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+};
+use wayland_client::{globals::registry_queue_init, Connection, QueueHandle};
+
+fn main() {
+    env_logger::init();
+
+    let name = env::args().nth(1);
+
+    let conn = Connection::connect_to_env().unwrap();
+
+    let (globals, mut event_queue) = registry_queue_init(&conn).unwrap();
+    let qh = event_queue.handle();
+
+    let mut output_management = OutputManagement {
+        registry_state: RegistryState::new(&globals),
+        output_management_state: OutputManagementState::bind(&globals, &qh)
+            .expect("wlr output management is not available"),
+        applied: None,
+    };
+
+    // The compositor sends the heads and their modes as soon as the global is bound.
+    event_queue.roundtrip(&mut output_management).unwrap();
+
+    let name = match name {
+        Some(name) => name,
+        None => {
+            for head in output_management.output_management_state.heads() {
+                print_head(&head.info().unwrap());
+            }
+            return;
+        }
+    };
+
+    loop {
+        let state = &output_management.output_management_state;
+        let serial = state.serial().expect("no heads were sent");
+        let configuration = state.create_configuration(serial, &qh).expect("no heads are listed");
+
+        for head in state.heads() {
+            let info = head.info().unwrap();
+            let enable = if info.name == name { !info.enabled } else { info.enabled };
+
+            if !enable {
+                configuration.disable_head(head).unwrap();
+                continue;
+            }
+
+            let configuration_head = configuration.enable_head(head, &qh).unwrap();
+            if !info.enabled {
+                // Enabled heads keep their state, newly enabled ones use their preferred mode.
+                if let Some(mode) = info.modes.iter().find(|mode| mode.preferred) {
+                    configuration_head.set_mode(mode);
+                }
+            }
+        }
+
+        configuration.apply().unwrap();
+
+        // Wait for the configuration to succeed, fail or be cancelled by a concurrent change, in which
+        // case it is created again from the new state.
+        output_management.applied = None;
+        while output_management.applied.is_none() {
+            event_queue.blocking_dispatch(&mut output_management).unwrap();
+        }
+
+        if output_management.applied == Some(true) {
+            break;
+        }
+    }
+}
+
+struct OutputManagement {
+    registry_state: RegistryState,
+    output_management_state: OutputManagementState,
+    /// Whether the last configuration was applied or failed, rather than cancelled.
+    applied: Option<bool>,
+}
+
+impl OutputManagementHandler for OutputManagement {
+    fn output_management_state(&mut self) -> &mut OutputManagementState {
+        &mut self.output_management_state
+    }
+
+    fn new_head(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &OutputHead) {}
+
+    fn update_head(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &OutputHead) {}
+
+    fn head_removed(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &OutputHead) {}
+
+    fn configuration_succeeded(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &OutputConfiguration,
+    ) {
+        println!("The configuration was applied");
+        self.applied = Some(true);
+    }
+
+    fn configuration_failed(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &OutputConfiguration,
+    ) {
+        eprintln!("The configuration could not be applied");
+        self.applied = Some(true);
+    }
+
+    fn configuration_cancelled(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &OutputConfiguration,
+    ) {
+        self.applied = Some(false);
+    }
+}
+
+delegate_output_management!(OutputManagement);
+
+delegate_registry!(OutputManagement);
+
+impl ProvidesRegistryState for OutputManagement {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers![];
+}
+
+fn print_head(info: &HeadInfo) {
+    println!("{} ({})", info.name, info.description);
+    println!("\tenabled: {}", info.enabled);
+    println!("\tphysical_size: {}×{}mm", info.physical_size.0, info.physical_size.1);
+
+    if info.enabled {
+        println!("\tx: {}, y: {}", info.position.0, info.position.1);
+        println!("\ttransform: {:?}", info.transform);
+        println!("\tscale: {}", info.scale);
+        println!("\tadaptive_sync: {}", info.adaptive_sync);
+    }
+
+    println!("\tmodes:");
+
+    for mode in &info.modes {
+        let refresh = mode.refresh.map(|refresh| refresh as f64 / 1000.0).unwrap_or(0.0);
+        let current = if info.current_mode.as_ref() == Some(mode) { " (current)" } else { "" };
+        let preferred = if mode.preferred { " (preferred)" } else { "" };
+        println!("\t\t{}×{} @ {refresh:.3} Hz{current}{preferred}", mode.size.0, mode.size.1);
+    }
+}
@@ -0,0 +1,185 @@
+//! Turns all outputs off when the seat is idle, and on again when it is used.
+//!
+//! This needs a compositor supporting wlr output power management and ext idle notify, such as sway:
+//!
+//! ```text
+//! cargo run --example output_power -- <timeout in seconds>
+//! ```
+
+use std::env;
+
+use smithay_client_toolkit::{
+    delegate_output, delegate_output_power, delegate_registry, delegate_seat,
+    output::{OutputHandler, OutputState},
+    output_power::{Mode, OutputPower, OutputPowerHandler, OutputPowerState},
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    seat::{Capability, SeatHandler, SeatState},
+};
+use wayland_client::{
+    globals::registry_queue_init,
+    protocol::{wl_output, wl_seat},
+    Connection, Dispatch, QueueHandle,
+};
+use wayland_protocols::ext::idle_notify::v1::client::{
+    ext_idle_notification_v1, ext_idle_notifier_v1,
+};
+
+fn main() {
+    env_logger::init();
+
+    let timeout = env::args()
+        .nth(1)
+        .map(|timeout| timeout.parse::<u32>().expect("invalid timeout"))
+        .unwrap_or(10);
+
+    let conn = Connection::connect_to_env().unwrap();
+
+    let (globals, mut event_queue) = registry_queue_init(&conn).unwrap();
+    let qh = event_queue.handle();
+
+    let idle_notifier: ext_idle_notifier_v1::ExtIdleNotifierV1 =
+        globals.bind(&qh, 1..=1, ()).expect("ext idle notify is not available");
+
+    let mut idle_blank = IdleBlank {
+        registry_state: RegistryState::new(&globals),
+        seat_state: SeatState::new(&globals, &qh),
+        output_state: OutputState::new(&globals, &qh),
+        output_power_state: OutputPowerState::bind(&globals, &qh)
+            .expect("wlr output power management is not available"),
+    };
+
+    event_queue.roundtrip(&mut idle_blank).unwrap();
+
+    let seat = idle_blank.seat_state.seats().next().expect("no seat available");
+    let _notification = idle_notifier.get_idle_notification(timeout * 1000, &seat, &qh, ());
+
+    println!("Turning the outputs off after {timeout} seconds of inactivity.");
+
+    loop {
+        event_queue.blocking_dispatch(&mut idle_blank).unwrap();
+    }
+}
+
+struct IdleBlank {
+    registry_state: RegistryState,
+    seat_state: SeatState,
+    output_state: OutputState,
+    output_power_state: OutputPowerState,
+}
+
+impl IdleBlank {
+    fn set_mode(&self, mode: Mode) {
+        for power in self.output_power_state.output_powers() {
+            power.set_mode(mode);
+        }
+    }
+}
+
+impl OutputPowerHandler for IdleBlank {
+    fn output_power_state(&mut self) -> &mut OutputPowerState {
+        &mut self.output_power_state
+    }
+
+    fn mode(&mut self, _: &Connection, _: &QueueHandle<Self>, power: &OutputPower, mode: Mode) {
+        let name = self.output_state.info(power.output()).and_then(|info| info.name);
+        println!("{} is {mode:?}", name.as_deref().unwrap_or("unknown output"));
+    }
+
+    fn failed(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &OutputPower) {
+        eprintln!("Failed to control the power mode of an output");
+    }
+}
+
+impl Dispatch<ext_idle_notifier_v1::ExtIdleNotifierV1, ()> for IdleBlank {
+    fn event(
+        _: &mut Self,
+        _: &ext_idle_notifier_v1::ExtIdleNotifierV1,
+        _: ext_idle_notifier_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        unreachable!("ext_idle_notifier_v1 has no events")
+    }
+}
+
+impl Dispatch<ext_idle_notification_v1::ExtIdleNotificationV1, ()> for IdleBlank {
+    fn event(
+        state: &mut Self,
+        _: &ext_idle_notification_v1::ExtIdleNotificationV1,
+        event: ext_idle_notification_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            ext_idle_notification_v1::Event::Idled => state.set_mode(Mode::Off),
+            ext_idle_notification_v1::Event::Resumed => state.set_mode(Mode::On),
+            _ => (),
+        }
+    }
+}
+
+impl OutputHandler for IdleBlank {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(&mut self, _: &Connection, qh: &QueueHandle<Self>, output: wl_output::WlOutput) {
+        self.output_power_state.get_output_power(&output, qh);
+    }
+
+    fn update_output(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+
+    fn output_destroyed(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        output: wl_output::WlOutput,
+    ) {
+        self.output_power_state.remove_output(&output);
+    }
+}
+
+impl SeatHandler for IdleBlank {
+    fn seat_state(&mut self) -> &mut SeatState {
+        &mut self.seat_state
+    }
+
+    fn new_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_seat::WlSeat) {}
+
+    fn new_capability(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: wl_seat::WlSeat,
+        _: Capability,
+    ) {
+    }
+
+    fn remove_capability(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: wl_seat::WlSeat,
+        _: Capability,
+    ) {
+    }
+
+    fn remove_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_seat::WlSeat) {}
+}
+
+delegate_output!(IdleBlank);
+delegate_seat!(IdleBlank);
+delegate_output_power!(IdleBlank);
+
+delegate_registry!(IdleBlank);
+
+impl ProvidesRegistryState for IdleBlank {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers![OutputState, SeatState];
+}
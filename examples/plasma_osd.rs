@@ -0,0 +1,250 @@
+//! Shows an on screen display, like the volume popup of KDE Plasma.
+//!
+//! This needs KWin, and only one client can bind Plasma shell, which is usually the Plasma desktop
+//! itself. KWin may also restrict Plasma shell to trusted clients.
+//!
+//! Clients which are not part of the Plasma desktop should use layer shell, as in the `simple_layer`
+//! example, which KWin also supports.
+
+use std::convert::TryInto;
+
+use smithay_client_toolkit::{
+    compositor::{CompositorHandler, CompositorState},
+    delegate_compositor, delegate_output, delegate_plasma_shell, delegate_registry, delegate_shm,
+    delegate_xdg_shell, delegate_xdg_window,
+    output::{OutputHandler, OutputState},
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    shell::{
+        plasma::plasma_shell::{PlasmaShellHandler, PlasmaShellState, PlasmaSurface, Role},
+        xdg::{
+            window::{Window, WindowConfigure, WindowDecorations, WindowHandler},
+            XdgShell, XdgShellHandler,
+        },
+        WaylandSurface,
+    },
+    shm::{slot::SlotPool, Shm, ShmHandler},
+};
+use wayland_client::{
+    globals::registry_queue_init,
+    protocol::{wl_output, wl_shm, wl_surface},
+    Connection, QueueHandle,
+};
+
+const WIDTH: u32 = 320;
+const HEIGHT: u32 = 80;
+
+fn main() {
+    env_logger::init();
+
+    let conn = Connection::connect_to_env().unwrap();
+
+    let (globals, mut event_queue) = registry_queue_init(&conn).unwrap();
+    let qh = event_queue.handle();
+
+    let compositor = CompositorState::bind(&globals, &qh).expect("wl_compositor not available");
+    let xdg_shell = XdgShell::bind(&globals, &qh).expect("xdg shell is not available");
+    let shm = Shm::bind(&globals, &qh).expect("wl shm is not available.");
+    let plasma_shell = PlasmaShellState::bind(&globals, &qh)
+        .expect("plasma shell is not available or already bound");
+
+    let surface = compositor.create_surface(&qh);
+    let window = xdg_shell.create_window(surface, WindowDecorations::None, &qh);
+    window.set_app_id("io.github.smithay.client-toolkit.PlasmaOsd");
+    window.set_min_size(Some((WIDTH, HEIGHT)));
+    window.set_max_size(Some((WIDTH, HEIGHT)));
+
+    // The role must be set before the initial commit. KWin centers on screen displays near the bottom
+    // of the screen, and keeps them above other windows without giving them focus.
+    let plasma_surface = plasma_shell.get_surface(window.wl_surface(), &qh);
+    plasma_surface.set_role(Role::Onscreendisplay);
+    plasma_surface.set_skip_taskbar(true);
+    plasma_surface.set_skip_switcher(true);
+
+    window.commit();
+
+    let pool = SlotPool::new((WIDTH * HEIGHT * 4) as usize, &shm).expect("Failed to create pool");
+
+    let mut plasma_osd = PlasmaOsd {
+        registry_state: RegistryState::new(&globals),
+        output_state: OutputState::new(&globals, &qh),
+        shm,
+
+        exit: false,
+        pool,
+        _plasma_surface: plasma_surface,
+        window,
+    };
+
+    loop {
+        event_queue.blocking_dispatch(&mut plasma_osd).unwrap();
+
+        if plasma_osd.exit {
+            println!("exiting example");
+            break;
+        }
+    }
+}
+
+struct PlasmaOsd {
+    registry_state: RegistryState,
+    output_state: OutputState,
+    shm: Shm,
+
+    exit: bool,
+    pool: SlotPool,
+    // Declared before the window, so it is destroyed before the surface.
+    _plasma_surface: PlasmaSurface,
+    window: Window,
+}
+
+impl PlasmaOsd {
+    fn draw(&mut self) {
+        let (buffer, canvas) = self
+            .pool
+            .create_buffer(WIDTH as i32, HEIGHT as i32, WIDTH as i32 * 4, wl_shm::Format::Argb8888)
+            .expect("create buffer");
+
+        // A translucent dark background with a bar filled to 70%, like a volume indicator.
+        canvas.chunks_exact_mut(4).enumerate().for_each(|(index, chunk)| {
+            let x = index as u32 % WIDTH;
+            let y = index as u32 / WIDTH;
+            let in_bar = (32..48).contains(&y) && (24..WIDTH - 24).contains(&x);
+            let filled = x < 24 + (WIDTH - 48) * 7 / 10;
+
+            let color: u32 = match (in_bar, filled) {
+                (true, true) => 0xFF3DAEE9,
+                (true, false) => 0xFF505050,
+                _ => 0xC0202020,
+            };
+
+            let array: &mut [u8; 4] = chunk.try_into().unwrap();
+            *array = color.to_le_bytes();
+        });
+
+        self.window.wl_surface().damage_buffer(0, 0, WIDTH as i32, HEIGHT as i32);
+        buffer.attach_to(self.window.wl_surface()).expect("buffer attach");
+        self.window.commit();
+    }
+}
+
+impl PlasmaShellHandler for PlasmaOsd {
+    fn auto_hidden_panel_hidden(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &PlasmaSurface,
+    ) {
+    }
+
+    fn auto_hidden_panel_shown(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &PlasmaSurface,
+    ) {
+    }
+}
+
+impl XdgShellHandler for PlasmaOsd {}
+
+impl WindowHandler for PlasmaOsd {
+    fn request_close(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &Window) {
+        self.exit = true;
+    }
+
+    fn configure(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _window: &Window,
+        _configure: WindowConfigure,
+        _serial: u32,
+    ) {
+        // The size is fixed, so the size suggested by the compositor is ignored.
+        self.draw();
+    }
+}
+
+impl CompositorHandler for PlasmaOsd {
+    fn scale_factor_changed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _new_factor: i32,
+    ) {
+    }
+
+    fn transform_changed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _new_transform: wl_output::Transform,
+    ) {
+    }
+
+    fn frame(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _time: u32,
+    ) {
+    }
+}
+
+impl OutputHandler for PlasmaOsd {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+
+    fn update_output(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+
+    fn output_destroyed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+}
+
+impl ShmHandler for PlasmaOsd {
+    fn shm_state(&mut self) -> &mut Shm {
+        &mut self.shm
+    }
+}
+
+delegate_compositor!(PlasmaOsd);
+delegate_output!(PlasmaOsd);
+delegate_shm!(PlasmaOsd);
+
+delegate_xdg_shell!(PlasmaOsd);
+delegate_xdg_window!(PlasmaOsd);
+delegate_plasma_shell!(PlasmaOsd);
+
+delegate_registry!(PlasmaOsd);
+
+impl ProvidesRegistryState for PlasmaOsd {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers![OutputState];
+}
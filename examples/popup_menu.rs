@@ -0,0 +1,403 @@
+//! Opens a context menu in an xdg popup when right-clicking the window.
+//!
+//! The popup grabs the pointer, so the compositor dismisses it when clicking outside of it.
+
+use std::convert::TryInto;
+
+use smithay_client_toolkit::reexports::protocols::xdg::shell::client::xdg_positioner::{
+    Anchor, ConstraintAdjustment, Gravity,
+};
+use smithay_client_toolkit::{
+    compositor::{CompositorHandler, CompositorState},
+    delegate_compositor, delegate_output, delegate_pointer, delegate_registry, delegate_seat,
+    delegate_shm, delegate_xdg_popup, delegate_xdg_shell, delegate_xdg_window,
+    output::{OutputHandler, OutputState},
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    seat::{
+        pointer::{PointerButton, PointerEvent, PointerEventKind, PointerHandler},
+        Capability, SeatHandler, SeatState,
+    },
+    shell::{
+        xdg::{
+            popup::{Popup, PopupConfigure, PopupHandler},
+            window::{Window, WindowConfigure, WindowDecorations, WindowHandler},
+            XdgPositioner, XdgShell, XdgShellHandler, XdgSurface,
+        },
+        WaylandSurface,
+    },
+    shm::{slot::SlotPool, Shm, ShmHandler},
+};
+use wayland_client::{
+    globals::registry_queue_init,
+    protocol::{wl_output, wl_pointer, wl_seat, wl_shm, wl_surface},
+    Connection, QueueHandle,
+};
+
+const ITEMS: [&str; 3] = ["Cut", "Copy", "Paste"];
+const ITEM_WIDTH: u32 = 120;
+const ITEM_HEIGHT: u32 = 24;
+
+fn main() {
+    env_logger::init();
+
+    let conn = Connection::connect_to_env().unwrap();
+
+    let (globals, mut event_queue) = registry_queue_init(&conn).unwrap();
+    let qh = event_queue.handle();
+
+    let compositor = CompositorState::bind(&globals, &qh).expect("wl_compositor not available");
+    let xdg_shell = XdgShell::bind(&globals, &qh).expect("xdg shell is not available");
+    let shm = Shm::bind(&globals, &qh).expect("wl shm is not available.");
+
+    let surface = compositor.create_surface(&qh);
+    let window = xdg_shell.create_window(surface, WindowDecorations::RequestServer, &qh);
+    window.set_title("Right-click for a menu");
+    window.set_app_id("io.github.smithay.client-toolkit.PopupMenu");
+    window.set_min_size(Some((256, 256)));
+    window.commit();
+
+    let pool = SlotPool::new(256 * 256 * 4, &shm).expect("Failed to create pool");
+
+    let mut popup_menu = PopupMenu {
+        registry_state: RegistryState::new(&globals),
+        seat_state: SeatState::new(&globals, &qh),
+        output_state: OutputState::new(&globals, &qh),
+        compositor,
+        xdg_shell,
+        shm,
+
+        exit: false,
+        pool,
+        width: 256,
+        height: 256,
+        window,
+        pointer: None,
+        menu: None,
+    };
+
+    loop {
+        event_queue.blocking_dispatch(&mut popup_menu).unwrap();
+
+        if popup_menu.exit {
+            println!("exiting example");
+            break;
+        }
+    }
+}
+
+struct Menu {
+    popup: Popup,
+    hovered: Option<usize>,
+}
+
+struct PopupMenu {
+    registry_state: RegistryState,
+    seat_state: SeatState,
+    output_state: OutputState,
+    compositor: CompositorState,
+    xdg_shell: XdgShell,
+    shm: Shm,
+
+    exit: bool,
+    pool: SlotPool,
+    width: u32,
+    height: u32,
+    window: Window,
+    pointer: Option<(wl_seat::WlSeat, wl_pointer::WlPointer)>,
+    menu: Option<Menu>,
+}
+
+impl PopupMenu {
+    /// Open the menu with its top left corner at the given position of the window.
+    fn open_menu(&mut self, qh: &QueueHandle<Self>, position: (f64, f64), serial: u32) {
+        let seat = match &self.pointer {
+            Some((seat, _)) => seat.clone(),
+            None => return,
+        };
+
+        let positioner = XdgPositioner::new(&self.xdg_shell)
+            .expect("create positioner")
+            .with_size(ITEM_WIDTH as i32, ITEM_HEIGHT as i32 * ITEMS.len() as i32)
+            .with_anchor_rect(position.0 as i32, position.1 as i32, 1, 1)
+            .with_anchor(Anchor::TopLeft)
+            .with_gravity(Gravity::BottomRight)
+            .with_constraint_adjustment(ConstraintAdjustment::FlipX | ConstraintAdjustment::FlipY);
+
+        let surface = self.compositor.create_surface(qh);
+        let popup = Popup::from_surface(
+            Some(self.window.xdg_surface()),
+            &positioner,
+            qh,
+            surface,
+            &self.xdg_shell,
+        )
+        .expect("create popup");
+        // The grab must be taken before the initial commit.
+        popup.grab(&seat, serial);
+        popup.commit();
+
+        self.menu = Some(Menu { popup, hovered: None });
+    }
+
+    fn draw_window(&mut self) {
+        let (width, height) = (self.width, self.height);
+        let (buffer, canvas) = self
+            .pool
+            .create_buffer(width as i32, height as i32, width as i32 * 4, wl_shm::Format::Argb8888)
+            .expect("create buffer");
+        fill(canvas, 0xFF336699);
+
+        self.window.wl_surface().damage_buffer(0, 0, width as i32, height as i32);
+        buffer.attach_to(self.window.wl_surface()).expect("buffer attach");
+        self.window.commit();
+    }
+
+    fn draw_menu(&mut self) {
+        let menu = match &self.menu {
+            Some(menu) => menu,
+            None => return,
+        };
+
+        let height = ITEM_HEIGHT * ITEMS.len() as u32;
+        let (buffer, canvas) = self
+            .pool
+            .create_buffer(
+                ITEM_WIDTH as i32,
+                height as i32,
+                ITEM_WIDTH as i32 * 4,
+                wl_shm::Format::Argb8888,
+            )
+            .expect("create buffer");
+
+        let item_len = (ITEM_WIDTH * ITEM_HEIGHT * 4) as usize;
+        for (index, item) in canvas.chunks_exact_mut(item_len).enumerate() {
+            let color = if menu.hovered == Some(index) { 0xFF6699CC } else { 0xFFDDDDDD };
+            fill(item, color);
+        }
+
+        let surface = menu.popup.wl_surface();
+        surface.damage_buffer(0, 0, ITEM_WIDTH as i32, height as i32);
+        buffer.attach_to(surface).expect("buffer attach");
+        menu.popup.commit();
+    }
+}
+
+fn fill(canvas: &mut [u8], color: u32) {
+    canvas.chunks_exact_mut(4).for_each(|chunk| {
+        let array: &mut [u8; 4] = chunk.try_into().unwrap();
+        *array = color.to_le_bytes();
+    });
+}
+
+impl CompositorHandler for PopupMenu {
+    fn scale_factor_changed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _new_factor: i32,
+    ) {
+    }
+
+    fn transform_changed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _new_transform: wl_output::Transform,
+    ) {
+    }
+
+    fn frame(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _time: u32,
+    ) {
+    }
+}
+
+impl OutputHandler for PopupMenu {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+
+    fn update_output(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+
+    fn output_destroyed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+}
+
+impl XdgShellHandler for PopupMenu {}
+
+impl WindowHandler for PopupMenu {
+    fn request_close(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &Window) {
+        self.exit = true;
+    }
+
+    fn configure(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _window: &Window,
+        configure: WindowConfigure,
+        _serial: u32,
+    ) {
+        self.width = configure.new_size.0.map(|v| v.get()).unwrap_or(256);
+        self.height = configure.new_size.1.map(|v| v.get()).unwrap_or(256);
+        self.draw_window();
+    }
+}
+
+impl PopupHandler for PopupMenu {
+    fn configure(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _popup: &Popup,
+        config: PopupConfigure,
+    ) {
+        println!("Menu configured at {:?} ({:?})", config.position, config.kind);
+        self.draw_menu();
+    }
+
+    fn done(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, popup: &Popup) {
+        // Dismissed by the compositor, usually because of a click outside of the menu.
+        if self.menu.as_ref().map(|menu| &menu.popup) == Some(popup) {
+            println!("Menu dismissed");
+            self.menu = None;
+        }
+    }
+}
+
+impl SeatHandler for PopupMenu {
+    fn seat_state(&mut self) -> &mut SeatState {
+        &mut self.seat_state
+    }
+
+    fn new_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_seat::WlSeat) {}
+
+    fn new_capability(
+        &mut self,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+        seat: wl_seat::WlSeat,
+        capability: Capability,
+    ) {
+        if capability == Capability::Pointer && self.pointer.is_none() {
+            let pointer = self.seat_state.get_pointer(qh, &seat).expect("Failed to create pointer");
+            self.pointer = Some((seat, pointer));
+        }
+    }
+
+    fn remove_capability(
+        &mut self,
+        _conn: &Connection,
+        _: &QueueHandle<Self>,
+        _: wl_seat::WlSeat,
+        capability: Capability,
+    ) {
+        if capability == Capability::Pointer {
+            if let Some((_, pointer)) = self.pointer.take() {
+                pointer.release();
+            }
+        }
+    }
+
+    fn remove_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_seat::WlSeat) {}
+}
+
+impl PointerHandler for PopupMenu {
+    fn pointer_frame(
+        &mut self,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+        _pointer: &wl_pointer::WlPointer,
+        events: &[PointerEvent],
+    ) {
+        for event in events {
+            let on_menu =
+                self.menu.as_ref().map(|menu| menu.popup.wl_surface()) == Some(&event.surface);
+            let item = (event.position.1 / ITEM_HEIGHT as f64) as usize;
+
+            match event.kind {
+                PointerEventKind::Motion { .. } | PointerEventKind::Enter { .. } if on_menu => {
+                    let menu = self.menu.as_mut().unwrap();
+                    if menu.hovered != Some(item) {
+                        menu.hovered = Some(item);
+                        self.draw_menu();
+                    }
+                }
+                PointerEventKind::Leave { .. } if on_menu => {
+                    self.menu.as_mut().unwrap().hovered = None;
+                    self.draw_menu();
+                }
+                PointerEventKind::Release { button, .. }
+                    if on_menu && PointerButton::from(button) == PointerButton::Left =>
+                {
+                    if let Some(name) = ITEMS.get(item) {
+                        println!("Selected {name}");
+                    }
+                    self.menu = None;
+                }
+                PointerEventKind::Press { button, serial, .. }
+                    if &event.surface == self.window.wl_surface()
+                        && PointerButton::from(button) == PointerButton::Right =>
+                {
+                    // Replace any menu that is already open.
+                    self.menu = None;
+                    self.open_menu(qh, event.position, serial);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl ShmHandler for PopupMenu {
+    fn shm_state(&mut self) -> &mut Shm {
+        &mut self.shm
+    }
+}
+
+delegate_compositor!(PopupMenu);
+delegate_output!(PopupMenu);
+delegate_shm!(PopupMenu);
+
+delegate_seat!(PopupMenu);
+delegate_pointer!(PopupMenu);
+
+delegate_xdg_shell!(PopupMenu);
+delegate_xdg_window!(PopupMenu);
+delegate_xdg_popup!(PopupMenu);
+
+delegate_registry!(PopupMenu);
+
+impl ProvidesRegistryState for PopupMenu {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers![OutputState, SeatState];
+}
@@ -0,0 +1,463 @@
+//! Opens a popup below a button in the top right corner of the window, and keeps it there while the
+//! window is resized.
+//!
+//! Click the button to open or close the popup. Each configure of the window repositions the popup
+//! against the new position of the button, and the popup is also reactive, so the compositor moves
+//! it along with the window on its own.
+//!
+//! ```text
+//! cargo run --example popup_reposition
+//! ```
+
+use std::convert::TryInto;
+
+use smithay_client_toolkit::reexports::protocols::xdg::shell::client::xdg_positioner::{
+    Anchor, ConstraintAdjustment, Gravity,
+};
+use smithay_client_toolkit::{
+    compositor::{CompositorHandler, CompositorState},
+    delegate_compositor, delegate_output, delegate_pointer, delegate_registry, delegate_seat,
+    delegate_shm, delegate_xdg_popup, delegate_xdg_shell, delegate_xdg_window,
+    output::{OutputHandler, OutputState},
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    seat::{
+        pointer::{PointerButton, PointerEvent, PointerEventKind, PointerHandler},
+        Capability, SeatHandler, SeatState,
+    },
+    shell::{
+        xdg::{
+            popup::{Popup, PopupConfigure, PopupHandler},
+            window::{Window, WindowConfigure, WindowDecorations, WindowHandler},
+            XdgPositioner, XdgShell, XdgShellHandler, XdgSurface,
+        },
+        WaylandSurface,
+    },
+    shm::{slot::SlotPool, Shm, ShmHandler},
+};
+use wayland_client::{
+    globals::registry_queue_init,
+    protocol::{wl_output, wl_pointer, wl_seat, wl_shm, wl_surface},
+    Connection, QueueHandle,
+};
+
+const BUTTON_WIDTH: u32 = 80;
+const BUTTON_HEIGHT: u32 = 24;
+const BUTTON_MARGIN: u32 = 8;
+const POPUP_WIDTH: u32 = 160;
+const POPUP_HEIGHT: u32 = 200;
+
+fn main() {
+    env_logger::init();
+
+    let conn = Connection::connect_to_env().unwrap();
+
+    let (globals, mut event_queue) = registry_queue_init(&conn).unwrap();
+    let qh = event_queue.handle();
+
+    let compositor = CompositorState::bind(&globals, &qh).expect("wl_compositor not available");
+    let xdg_shell = XdgShell::bind(&globals, &qh).expect("xdg shell is not available");
+    let shm = Shm::bind(&globals, &qh).expect("wl shm is not available.");
+
+    let surface = compositor.create_surface(&qh);
+    let window = xdg_shell.create_window(surface, WindowDecorations::RequestServer, &qh);
+    window.set_title("Resize the window with the popup open");
+    window.set_app_id("io.github.smithay.client-toolkit.PopupReposition");
+    window.set_min_size(Some((256, 256)));
+    window.commit();
+
+    let pool = SlotPool::new(256 * 256 * 4, &shm).expect("Failed to create pool");
+
+    let mut glued = Glued {
+        registry_state: RegistryState::new(&globals),
+        seat_state: SeatState::new(&globals, &qh),
+        output_state: OutputState::new(&globals, &qh),
+        compositor,
+        xdg_shell,
+        shm,
+
+        exit: false,
+        pool,
+        width: 256,
+        height: 256,
+        window,
+        pointer: None,
+        popup: None,
+        token: 0,
+    };
+
+    loop {
+        event_queue.blocking_dispatch(&mut glued).unwrap();
+
+        if glued.exit {
+            println!("exiting example");
+            break;
+        }
+    }
+}
+
+struct GluedPopup {
+    popup: Popup,
+    width: u32,
+    height: u32,
+}
+
+struct Glued {
+    registry_state: RegistryState,
+    seat_state: SeatState,
+    output_state: OutputState,
+    compositor: CompositorState,
+    xdg_shell: XdgShell,
+    shm: Shm,
+
+    exit: bool,
+    pool: SlotPool,
+    width: u32,
+    height: u32,
+    window: Window,
+    pointer: Option<wl_pointer::WlPointer>,
+    popup: Option<GluedPopup>,
+    /// The token of the last reposition request.
+    token: u32,
+}
+
+impl Glued {
+    /// The position of the button in the window.
+    fn button_rect(&self) -> (i32, i32, i32, i32) {
+        let x = self.width.saturating_sub(BUTTON_WIDTH + BUTTON_MARGIN);
+        (x as i32, BUTTON_MARGIN as i32, BUTTON_WIDTH as i32, BUTTON_HEIGHT as i32)
+    }
+
+    /// A positioner placing the popup below the button, for a window of the current size.
+    fn positioner(&self, parent_configure: Option<u32>) -> XdgPositioner {
+        let (x, y, width, height) = self.button_rect();
+        let positioner = XdgPositioner::new(&self.xdg_shell)
+            .expect("create positioner")
+            .with_size(POPUP_WIDTH as i32, POPUP_HEIGHT as i32)
+            .with_anchor_rect(x, y, width, height)
+            .with_anchor(Anchor::BottomRight)
+            .with_gravity(Gravity::BottomLeft)
+            // The compositor recomputes these adjustments for each reposition, so the popup may
+            // also change size.
+            .with_constraint_adjustment(
+                ConstraintAdjustment::SlideX
+                    | ConstraintAdjustment::FlipY
+                    | ConstraintAdjustment::ResizeY,
+            )
+            .with_reactive()
+            .with_parent_size(self.width as i32, self.height as i32);
+
+        match parent_configure {
+            Some(serial) => positioner.with_parent_configure(serial),
+            None => positioner,
+        }
+    }
+
+    fn open_popup(&mut self, qh: &QueueHandle<Self>) {
+        let positioner = self.positioner(None);
+        let surface = self.compositor.create_surface(qh);
+        let popup = Popup::from_surface(
+            Some(self.window.xdg_surface()),
+            &positioner,
+            qh,
+            surface,
+            &self.xdg_shell,
+        )
+        .expect("create popup");
+        popup.commit();
+
+        self.popup = Some(GluedPopup { popup, width: POPUP_WIDTH, height: POPUP_HEIGHT });
+    }
+
+    /// Move the popup below the button again, after the window changed size.
+    fn reposition_popup(&mut self, qh: &QueueHandle<Self>, serial: u32) {
+        let popup = match &self.popup {
+            Some(popup) => popup.popup.clone(),
+            None => return,
+        };
+
+        self.token = self.token.wrapping_add(1);
+        let positioner = self.positioner(Some(serial));
+        if !popup.reposition(&positioner, self.token) {
+            // The compositor cannot move existing popups, so open a new one instead.
+            self.popup = None;
+            self.open_popup(qh);
+        }
+    }
+
+    fn draw_window(&mut self) {
+        let (width, height) = (self.width, self.height);
+        let (x, y, button_width, button_height) = self.button_rect();
+        let (buffer, canvas) = self
+            .pool
+            .create_buffer(width as i32, height as i32, width as i32 * 4, wl_shm::Format::Argb8888)
+            .expect("create buffer");
+        fill(canvas, 0xFF336699);
+
+        for row in canvas
+            .chunks_exact_mut(width as usize * 4)
+            .skip(y as usize)
+            .take(button_height as usize)
+        {
+            let start = x as usize * 4;
+            let end = ((x + button_width) as usize * 4).min(row.len());
+            fill(&mut row[start..end], 0xFFDDDDDD);
+        }
+
+        self.window.wl_surface().damage_buffer(0, 0, width as i32, height as i32);
+        buffer.attach_to(self.window.wl_surface()).expect("buffer attach");
+        self.window.commit();
+    }
+
+    fn draw_popup(&mut self) {
+        let popup = match &self.popup {
+            Some(popup) => popup,
+            None => return,
+        };
+
+        let (width, height) = (popup.width, popup.height);
+        let (buffer, canvas) = self
+            .pool
+            .create_buffer(width as i32, height as i32, width as i32 * 4, wl_shm::Format::Argb8888)
+            .expect("create buffer");
+        fill(canvas, 0xFFEEEEEE);
+
+        let surface = popup.popup.wl_surface();
+        surface.damage_buffer(0, 0, width as i32, height as i32);
+        buffer.attach_to(surface).expect("buffer attach");
+        popup.popup.commit();
+    }
+}
+
+fn fill(canvas: &mut [u8], color: u32) {
+    canvas.chunks_exact_mut(4).for_each(|chunk| {
+        let array: &mut [u8; 4] = chunk.try_into().unwrap();
+        *array = color.to_le_bytes();
+    });
+}
+
+impl CompositorHandler for Glued {
+    fn scale_factor_changed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _new_factor: i32,
+    ) {
+    }
+
+    fn transform_changed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _new_transform: wl_output::Transform,
+    ) {
+    }
+
+    fn frame(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _time: u32,
+    ) {
+    }
+}
+
+impl OutputHandler for Glued {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+
+    fn update_output(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+
+    fn output_destroyed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+}
+
+impl XdgShellHandler for Glued {}
+
+impl WindowHandler for Glued {
+    fn request_close(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &Window) {
+        self.exit = true;
+    }
+
+    fn configure(
+        &mut self,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+        _window: &Window,
+        configure: WindowConfigure,
+        serial: u32,
+    ) {
+        let width = configure.new_size.0.map(|v| v.get()).unwrap_or(256);
+        let height = configure.new_size.1.map(|v| v.get()).unwrap_or(256);
+        let resized = (width, height) != (self.width, self.height);
+        self.width = width;
+        self.height = height;
+
+        if resized {
+            self.reposition_popup(qh, serial);
+        }
+        self.draw_window();
+    }
+}
+
+impl PopupHandler for Glued {
+    fn configure(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        popup: &Popup,
+        config: PopupConfigure,
+    ) {
+        let glued = match &mut self.popup {
+            Some(glued) if &glued.popup == popup => glued,
+            _ => return,
+        };
+
+        println!(
+            "Popup configured at {:?} with size {}x{} ({:?})",
+            config.position, config.width, config.height, config.kind
+        );
+        // Constraint adjustments may have resized the popup.
+        if config.width > 0 && config.height > 0 {
+            glued.width = config.width as u32;
+            glued.height = config.height as u32;
+        }
+        self.draw_popup();
+    }
+
+    fn done(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, popup: &Popup) {
+        if self.popup.as_ref().map(|glued| &glued.popup) == Some(popup) {
+            self.popup = None;
+        }
+    }
+
+    fn repositioned(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _popup: &Popup,
+        token: u32,
+    ) {
+        if token != self.token {
+            // A newer reposition request is still pending, its configure will follow.
+            println!("Skipping outdated reposition {token}");
+        }
+    }
+}
+
+impl SeatHandler for Glued {
+    fn seat_state(&mut self) -> &mut SeatState {
+        &mut self.seat_state
+    }
+
+    fn new_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_seat::WlSeat) {}
+
+    fn new_capability(
+        &mut self,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+        seat: wl_seat::WlSeat,
+        capability: Capability,
+    ) {
+        if capability == Capability::Pointer && self.pointer.is_none() {
+            let pointer = self.seat_state.get_pointer(qh, &seat).expect("Failed to create pointer");
+            self.pointer = Some(pointer);
+        }
+    }
+
+    fn remove_capability(
+        &mut self,
+        _conn: &Connection,
+        _: &QueueHandle<Self>,
+        _: wl_seat::WlSeat,
+        capability: Capability,
+    ) {
+        if capability == Capability::Pointer {
+            if let Some(pointer) = self.pointer.take() {
+                pointer.release();
+            }
+        }
+    }
+
+    fn remove_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_seat::WlSeat) {}
+}
+
+impl PointerHandler for Glued {
+    fn pointer_frame(
+        &mut self,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+        _pointer: &wl_pointer::WlPointer,
+        events: &[PointerEvent],
+    ) {
+        for event in events {
+            if &event.surface != self.window.wl_surface() {
+                continue;
+            }
+
+            if let PointerEventKind::Press { button, .. } = event.kind {
+                let (x, y, width, height) = self.button_rect();
+                let (px, py) = (event.position.0 as i32, event.position.1 as i32);
+                let on_button = px >= x && px < x + width && py >= y && py < y + height;
+
+                // Close the popup if it is open, or open it.
+                if on_button
+                    && PointerButton::from(button) == PointerButton::Left
+                    && self.popup.take().is_none()
+                {
+                    self.open_popup(qh);
+                }
+            }
+        }
+    }
+}
+
+impl ShmHandler for Glued {
+    fn shm_state(&mut self) -> &mut Shm {
+        &mut self.shm
+    }
+}
+
+delegate_compositor!(Glued);
+delegate_output!(Glued);
+delegate_shm!(Glued);
+
+delegate_seat!(Glued);
+delegate_pointer!(Glued);
+
+delegate_xdg_shell!(Glued);
+delegate_xdg_window!(Glued);
+delegate_xdg_popup!(Glued);
+
+delegate_registry!(Glued);
+
+impl ProvidesRegistryState for Glued {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers![OutputState, SeatState];
+}
@@ -18,7 +18,7 @@ use smithay_client_toolkit::{
     shell::{
         xdg::{
             window::{Window, WindowConfigure, WindowDecorations, WindowHandler},
-            XdgShell,
+            XdgShell, XdgShellHandler,
         },
         WaylandSurface,
     },
@@ -191,6 +191,8 @@ impl OutputHandler for SimpleWindow {
     }
 }
 
+impl XdgShellHandler for SimpleWindow {}
+
 impl WindowHandler for SimpleWindow {
     fn request_close(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &Window) {
         self.exit = true;
@@ -424,11 +426,11 @@ impl SimpleWindow {
             window.wl_surface().damage_buffer(0, 0, self.width as i32, self.height as i32);
 
             // Request our next frame
-            window.wl_surface().frame(qh, window.wl_surface().clone());
+            window.request_frame(qh);
 
             // Attach and commit to present.
             buffer.attach_to(window.wl_surface()).expect("buffer attach");
-            window.wl_surface().commit();
+            window.commit();
         }
     }
 
@@ -0,0 +1,173 @@
+//! Saves a screenshot of the first output as a PPM image.
+//!
+//! This needs a compositor supporting wlr screencopy, such as sway:
+//!
+//! ```text
+//! cargo run --example screencopy -- screenshot.ppm
+//! ```
+
+use std::{env, fs::File, io::Write, time::Duration};
+
+use smithay_client_toolkit::{
+    delegate_output, delegate_registry, delegate_screencopy, delegate_shm,
+    output::{OutputHandler, OutputState},
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    screencopy::{ScreencopyFrame, ScreencopyHandler, ScreencopyState, ShmBufferInfo},
+    shm::{slot::SlotPool, Shm, ShmHandler},
+};
+use wayland_client::{
+    globals::registry_queue_init,
+    protocol::{wl_output, wl_shm},
+    Connection, QueueHandle,
+};
+
+fn main() {
+    env_logger::init();
+
+    let path = env::args().nth(1).unwrap_or_else(|| "screenshot.ppm".to_owned());
+
+    let conn = Connection::connect_to_env().unwrap();
+
+    let (globals, mut event_queue) = registry_queue_init(&conn).unwrap();
+    let qh = event_queue.handle();
+
+    let shm = Shm::bind(&globals, &qh).expect("wl_shm is not available");
+    let pool = SlotPool::new(256 * 256 * 4, &shm).expect("Failed to create pool");
+
+    let mut screenshot = Screenshot {
+        registry_state: RegistryState::new(&globals),
+        output_state: OutputState::new(&globals, &qh),
+        shm,
+        screencopy_state: ScreencopyState::bind(&globals, &qh)
+            .expect("wlr screencopy is not available"),
+        pool,
+        done: false,
+    };
+
+    // Wait for the outputs to be known.
+    event_queue.roundtrip(&mut screenshot).unwrap();
+
+    let output = screenshot.output_state.outputs().next().expect("no output available");
+    let frame = screenshot.screencopy_state.capture_output(false, &output, &qh);
+
+    while !screenshot.done {
+        event_queue.blocking_dispatch(&mut screenshot).unwrap();
+    }
+
+    let info = frame.shm_buffer().unwrap();
+    let y_invert = frame.y_invert();
+    let canvas = match frame.canvas(&mut screenshot.pool) {
+        Some(canvas) => canvas,
+        None => return,
+    };
+
+    let mut file = File::create(&path).expect("Failed to create the image file");
+    write!(file, "P6\n{} {}\n255\n", info.width, info.height).unwrap();
+
+    let mut rows = canvas.chunks_exact(info.stride as usize).collect::<Vec<_>>();
+    if y_invert {
+        rows.reverse();
+    }
+
+    for row in rows {
+        let pixels = row[..info.width as usize * 4]
+            .chunks_exact(4)
+            .flat_map(|pixel| match info.format {
+                // Little endian, the bytes are blue, green, red, alpha.
+                wl_shm::Format::Argb8888 | wl_shm::Format::Xrgb8888 => {
+                    [pixel[2], pixel[1], pixel[0]]
+                }
+                // The bytes are red, green, blue, alpha.
+                _ => [pixel[0], pixel[1], pixel[2]],
+            })
+            .collect::<Vec<u8>>();
+        file.write_all(&pixels).unwrap();
+    }
+
+    println!("Saved the screenshot to {path}");
+}
+
+struct Screenshot {
+    registry_state: RegistryState,
+    output_state: OutputState,
+    shm: Shm,
+    screencopy_state: ScreencopyState,
+    pool: SlotPool,
+    done: bool,
+}
+
+impl ScreencopyHandler for Screenshot {
+    fn buffer(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &ScreencopyFrame,
+        buffer: ShmBufferInfo,
+    ) {
+        // Only 32 bit formats are converted by this example.
+        match buffer.format {
+            wl_shm::Format::Argb8888
+            | wl_shm::Format::Xrgb8888
+            | wl_shm::Format::Abgr8888
+            | wl_shm::Format::Xbgr8888 => (),
+            format => panic!("Unsupported screencopy format {format:?}"),
+        }
+    }
+
+    fn buffer_done(&mut self, _: &Connection, _: &QueueHandle<Self>, frame: &ScreencopyFrame) {
+        frame.capture_to_slotpool(&mut self.pool).expect("Failed to copy the frame");
+    }
+
+    fn flags(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &ScreencopyFrame, _: bool) {}
+
+    fn damage(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &ScreencopyFrame,
+        _: (u32, u32, u32, u32),
+    ) {
+    }
+
+    fn ready(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &ScreencopyFrame, _: Duration) {
+        self.done = true;
+    }
+
+    fn failed(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &ScreencopyFrame) {
+        eprintln!("The compositor failed to copy the output");
+        self.done = true;
+    }
+}
+
+impl OutputHandler for Screenshot {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+
+    fn update_output(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+
+    fn output_destroyed(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+}
+
+impl ShmHandler for Screenshot {
+    fn shm_state(&mut self) -> &mut Shm {
+        &mut self.shm
+    }
+}
+
+delegate_output!(Screenshot);
+delegate_shm!(Screenshot);
+delegate_screencopy!(Screenshot);
+
+delegate_registry!(Screenshot);
+
+impl ProvidesRegistryState for Screenshot {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers![OutputState];
+}
@@ -0,0 +1,74 @@
+//! Launches a client in a security context, as a sandbox launcher would.
+//!
+//! The client connects to the compositor through a dedicated socket, so the compositor knows its app
+//! id and may restrict the globals it exposes to it:
+//!
+//! ```text
+//! cargo run --example security_context -- <app id> <command> [args...]
+//! ```
+
+use std::{env, process::Command};
+
+use smithay_client_toolkit::{
+    delegate_registry, delegate_security_context,
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    security_context::SecurityContextState,
+};
+use wayland_client::{globals::registry_queue_init, Connection};
+
+fn main() {
+    env_logger::init();
+
+    let mut args = env::args().skip(1);
+    let app_id = args.next().expect("no app id given");
+    let command = args.next().expect("no command given");
+
+    let conn = Connection::connect_to_env().unwrap();
+
+    let (globals, mut event_queue) = registry_queue_init(&conn).unwrap();
+    let qh = event_queue.handle();
+
+    let mut launcher = Launcher { registry_state: RegistryState::new(&globals) };
+    let security_context_state = SecurityContextState::bind(&globals, &qh)
+        .expect("wp_security_context_manager_v1 is not available");
+
+    let instance_id = std::process::id().to_string();
+    let (context, socket) = security_context_state
+        .create_listener_socket(&format!("sctk-security-context-{instance_id}"), &qh)
+        .expect("failed to create the listening socket");
+    context.set_sandbox_engine("org.smithay.client-toolkit.example");
+    context.set_app_id(&app_id);
+    context.set_instance_id(&instance_id);
+    context.commit();
+
+    // Make sure the compositor listens before the client connects.
+    event_queue.roundtrip(&mut launcher).unwrap();
+
+    let status = Command::new(command)
+        .args(args)
+        .env("WAYLAND_DISPLAY", socket.path())
+        .status()
+        .expect("failed to launch the client");
+
+    println!("{app_id} exited with {status}");
+
+    // The compositor stops listening and the socket is removed.
+    drop(socket);
+}
+
+struct Launcher {
+    registry_state: RegistryState,
+}
+
+delegate_security_context!(Launcher);
+
+delegate_registry!(Launcher);
+
+impl ProvidesRegistryState for Launcher {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers![];
+}
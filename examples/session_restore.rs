@@ -0,0 +1,244 @@
+//! Shows a window whose position and size are restored by the compositor when the example is run
+//! again.
+//!
+//! The id of the session is stored in a file of the temporary directory. If the compositor does not
+//! support session management, the window is placed as usual.
+//!
+//! ```text
+//! cargo run --example session_restore --features unstable-protocols
+//! ```
+
+use std::{convert::TryInto, fs, path::PathBuf};
+
+use smithay_client_toolkit::{
+    compositor::{CompositorHandler, CompositorState},
+    delegate_compositor, delegate_output, delegate_registry, delegate_session_management,
+    delegate_shm, delegate_xdg_shell, delegate_xdg_window,
+    output::{OutputHandler, OutputState},
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    session_management::{Session, SessionHandler, SessionReason, SessionState, ToplevelSession},
+    shell::{
+        xdg::{
+            window::{Window, WindowConfigure, WindowDecorations, WindowHandler},
+            XdgShell, XdgShellHandler,
+        },
+        WaylandSurface,
+    },
+    shm::{slot::SlotPool, Shm, ShmHandler},
+};
+use wayland_client::{
+    globals::registry_queue_init,
+    protocol::{wl_output, wl_shm, wl_surface},
+    Connection, QueueHandle,
+};
+
+/// The name of the window in the session.
+const TOPLEVEL_NAME: &str = "main";
+
+fn session_file() -> PathBuf {
+    std::env::temp_dir().join("sctk-session-restore-example")
+}
+
+fn main() {
+    env_logger::init();
+
+    let conn = Connection::connect_to_env().unwrap();
+
+    let (globals, mut event_queue) = registry_queue_init(&conn).unwrap();
+    let qh = event_queue.handle();
+
+    let compositor = CompositorState::bind(&globals, &qh).expect("wl_compositor not available");
+    let xdg_shell = XdgShell::bind(&globals, &qh).expect("xdg shell is not available");
+    let shm = Shm::bind(&globals, &qh).expect("wl shm is not available.");
+    let session_state = SessionState::bind(&globals, &qh);
+
+    let surface = compositor.create_surface(&qh);
+    let window = xdg_shell.create_window(surface, WindowDecorations::RequestServer, &qh);
+    window.set_title("Move me, then run the example again");
+    window.set_app_id("io.github.smithay.client-toolkit.SessionRestore");
+    window.set_min_size(Some((256, 256)));
+
+    let previous_id = fs::read_to_string(session_file()).ok();
+    let (session, toplevel_session) =
+        match session_state.get_session(SessionReason::Launch, previous_id.as_deref(), &qh) {
+            Ok(session) => {
+                // The state of the window must be restored before its initial commit.
+                let toplevel_session = session.restore_toplevel(&window, TOPLEVEL_NAME, &qh);
+                (Some(session), Some(toplevel_session))
+            }
+            Err(err) => {
+                println!("Session management is not available: {err}");
+                (None, None)
+            }
+        };
+    window.commit();
+
+    let pool = SlotPool::new(256 * 256 * 4, &shm).expect("Failed to create pool");
+
+    let mut restore = Restore {
+        registry_state: RegistryState::new(&globals),
+        output_state: OutputState::new(&globals, &qh),
+        shm,
+
+        exit: false,
+        pool,
+        width: 256,
+        height: 256,
+        toplevel_session,
+        session,
+        window,
+    };
+
+    loop {
+        event_queue.blocking_dispatch(&mut restore).unwrap();
+
+        if restore.exit {
+            println!("exiting example");
+            break;
+        }
+    }
+}
+
+struct Restore {
+    registry_state: RegistryState,
+    output_state: OutputState,
+    shm: Shm,
+
+    exit: bool,
+    pool: SlotPool,
+    width: u32,
+    height: u32,
+    // Dropped before the window, since the toplevel session must be destroyed before the toplevel.
+    toplevel_session: Option<ToplevelSession>,
+    session: Option<Session>,
+    window: Window,
+}
+
+impl Restore {
+    fn draw(&mut self) {
+        let (width, height) = (self.width, self.height);
+        let (buffer, canvas) = self
+            .pool
+            .create_buffer(width as i32, height as i32, width as i32 * 4, wl_shm::Format::Argb8888)
+            .expect("create buffer");
+
+        canvas.chunks_exact_mut(4).for_each(|chunk| {
+            let array: &mut [u8; 4] = chunk.try_into().unwrap();
+            *array = 0xFF40A0C0u32.to_le_bytes();
+        });
+
+        self.window.wl_surface().damage_buffer(0, 0, width as i32, height as i32);
+        buffer.attach_to(self.window.wl_surface()).expect("buffer attach");
+        self.window.commit();
+    }
+}
+
+impl SessionHandler for Restore {
+    fn created(&mut self, _: &Connection, _: &QueueHandle<Self>, _: Session, id: String) {
+        println!("Created session {id}");
+        if let Err(err) = fs::write(session_file(), id) {
+            println!("Failed to store the session id: {err}");
+        }
+    }
+
+    fn restored(&mut self, _: &Connection, _: &QueueHandle<Self>, session: Session) {
+        println!("Restored session {}", session.id().unwrap_or_default());
+    }
+
+    fn replaced(&mut self, _: &Connection, _: &QueueHandle<Self>, _: Session) {
+        println!("The session was taken over by another client");
+        self.toplevel_session = None;
+        self.session = None;
+    }
+
+    fn toplevel_restored(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        toplevel_session: ToplevelSession,
+    ) {
+        if toplevel_session.window() == &self.window {
+            println!("Restored the state of the window");
+        }
+    }
+}
+
+impl XdgShellHandler for Restore {}
+
+impl WindowHandler for Restore {
+    fn request_close(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &Window) {
+        self.exit = true;
+    }
+
+    fn configure(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &Window,
+        configure: WindowConfigure,
+        _: u32,
+    ) {
+        self.width = configure.new_size.0.map(|w| w.get()).unwrap_or(256);
+        self.height = configure.new_size.1.map(|h| h.get()).unwrap_or(256);
+        self.draw();
+    }
+}
+
+impl CompositorHandler for Restore {
+    fn scale_factor_changed(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &wl_surface::WlSurface,
+        _: i32,
+    ) {
+    }
+
+    fn transform_changed(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &wl_surface::WlSurface,
+        _: wl_output::Transform,
+    ) {
+    }
+
+    fn frame(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &wl_surface::WlSurface, _: u32) {}
+}
+
+impl OutputHandler for Restore {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+
+    fn update_output(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+
+    fn output_destroyed(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+}
+
+impl ShmHandler for Restore {
+    fn shm_state(&mut self) -> &mut Shm {
+        &mut self.shm
+    }
+}
+
+delegate_compositor!(Restore);
+delegate_output!(Restore);
+delegate_shm!(Restore);
+
+delegate_xdg_shell!(Restore);
+delegate_xdg_window!(Restore);
+delegate_session_management!(Restore);
+
+delegate_registry!(Restore);
+
+impl ProvidesRegistryState for Restore {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers![OutputState];
+}
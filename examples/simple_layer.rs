@@ -11,7 +11,7 @@ use smithay_client_toolkit::{
     registry_handlers,
     seat::{
         keyboard::{KeyEvent, KeyboardHandler, Keysym, Modifiers},
-        pointer::{PointerEvent, PointerEventKind, PointerHandler},
+        pointer::{PointerButton, PointerEvent, PointerEventKind, PointerHandler},
         Capability, SeatHandler, SeatState,
     },
     shell::{
@@ -353,11 +353,11 @@ impl PointerHandler for SimpleLayer {
                 }
                 Motion { .. } => {}
                 Press { button, .. } => {
-                    println!("Press {:x} @ {:?}", button, event.position);
+                    println!("Press {:?} @ {:?}", PointerButton::from(button), event.position);
                     self.shift = self.shift.xor(Some(0));
                 }
                 Release { button, .. } => {
-                    println!("Release {:x} @ {:?}", button, event.position);
+                    println!("Release {:?} @ {:?}", PointerButton::from(button), event.position);
                 }
                 Axis { horizontal, vertical, .. } => {
                     println!("Scroll H:{horizontal:?}, V:{vertical:?}");
@@ -410,7 +410,7 @@ impl SimpleLayer {
         self.layer.wl_surface().damage_buffer(0, 0, width as i32, height as i32);
 
         // Request our next frame
-        self.layer.wl_surface().frame(qh, self.layer.wl_surface().clone());
+        self.layer.request_frame(qh);
 
         // Attach and commit to present.
         buffer.attach_to(self.layer.wl_surface()).expect("buffer attach");
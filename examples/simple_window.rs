@@ -1,25 +1,32 @@
 use std::{convert::TryInto, time::Duration};
 
 use smithay_client_toolkit::activation::RequestData;
-use smithay_client_toolkit::reexports::calloop::{EventLoop, LoopHandle};
+use smithay_client_toolkit::reexports::calloop::{
+    timer::{TimeoutAction, Timer},
+    EventLoop, LoopHandle,
+};
 use smithay_client_toolkit::reexports::calloop_wayland_source::WaylandSource;
 use smithay_client_toolkit::{
     activation::{ActivationHandler, ActivationState},
-    compositor::{CompositorHandler, CompositorState},
-    delegate_activation, delegate_compositor, delegate_keyboard, delegate_output, delegate_pointer,
-    delegate_registry, delegate_seat, delegate_shm, delegate_xdg_shell, delegate_xdg_window,
+    compositor::{CompositorHandler, CompositorState, SurfaceData},
+    delegate_activation, delegate_compositor, delegate_fractional_scale, delegate_keyboard,
+    delegate_output, delegate_pointer, delegate_presentation_time, delegate_registry,
+    delegate_seat, delegate_shm, delegate_tearing_control, delegate_viewporter, delegate_xdg_shell,
+    delegate_xdg_window,
+    fractional_scale::{FractionalScale, FractionalScaleHandler, FractionalScaleState},
     output::{OutputHandler, OutputState},
+    presentation_time::{PresentationHandler, PresentationState, PresentationTime, Presented},
     registry::{ProvidesRegistryState, RegistryState},
     registry_handlers,
     seat::{
         keyboard::{KeyEvent, KeyboardHandler, Keysym, Modifiers},
-        pointer::{PointerEvent, PointerEventKind, PointerHandler},
+        pointer::{PointerButton, PointerEvent, PointerEventKind, PointerHandler},
         Capability, SeatHandler, SeatState,
     },
     shell::{
         xdg::{
-            window::{Window, WindowConfigure, WindowDecorations, WindowHandler},
-            XdgShell,
+            window::{DecorationMode, Window, WindowConfigure, WindowDecorations, WindowHandler},
+            XdgShell, XdgShellHandler,
         },
         WaylandSurface,
     },
@@ -27,11 +34,13 @@ use smithay_client_toolkit::{
         slot::{Buffer, SlotPool},
         Shm, ShmHandler,
     },
+    tearing_control::{PresentationHint, TearingControl, TearingControlState},
+    viewporter::{Viewport, ViewporterState},
 };
 use wayland_client::{
     globals::registry_queue_init,
     protocol::{wl_keyboard, wl_output, wl_pointer, wl_seat, wl_shm, wl_surface},
-    Connection, QueueHandle,
+    Connection, Proxy, QueueHandle,
 };
 
 fn main() {
@@ -70,6 +79,24 @@ fn main() {
     window.set_app_id("io.github.smithay.client-toolkit.SimpleWindow");
     window.set_min_size(Some((256, 256)));
 
+    // On outputs with a fractional scale such as 1.25, the window stays crisp by drawing a buffer of
+    // the size of the window multiplied by the scale, which a viewport maps back to the window size.
+    let fractional_scale_state = FractionalScaleState::bind(&globals, &qh).ok();
+    let viewporter = ViewporterState::bind(&globals, &qh).ok();
+    let (fractional_scale, viewport) = match (&fractional_scale_state, &viewporter) {
+        (Some(fractional_scale_state), Some(viewporter)) => (
+            Some(fractional_scale_state.fractional_scaling(window.wl_surface(), &qh)),
+            Some(viewporter.get_viewport(window.wl_surface(), &qh)),
+        ),
+        _ => (None, None),
+    };
+
+    // Presentation feedback tells when frames are actually shown, to measure the refresh interval.
+    let presentation = PresentationState::bind(&globals, &qh).ok();
+
+    // Tearing control lets the compositor present frames without waiting for the vertical blank.
+    let tearing_control_state = TearingControlState::bind(&globals, &qh).ok();
+
     // In order for the window to be mapped, we need to perform an initial commit with no attached buffer.
     // For more info, see WaylandSurface::commit
     //
@@ -77,16 +104,34 @@ fn main() {
     // the correct options.
     window.commit();
 
-    // To request focus, we first need to request a token
     if let Some(activation) = xdg_activation.as_ref() {
-        activation.request_token(
-            &qh,
-            RequestData {
-                seat_and_serial: None,
-                surface: Some(window.wl_surface().clone()),
-                app_id: Some(String::from("io.github.smithay.client-toolkit.SimpleWindow")),
-            },
-        )
+        // A launcher may have passed a token for the first window of the application.
+        if let Some(token) = ActivationState::token_from_env() {
+            activation.activate::<SimpleWindow>(window.wl_surface(), token);
+        }
+
+        // To request focus later on, we first need to request a token. Switch to another window
+        // within 5 seconds to see this one being raised again.
+        let qh = qh.clone();
+        event_loop
+            .handle()
+            .insert_source(Timer::from_duration(Duration::from_secs(5)), move |_, _, app_data| {
+                if let Some(activation) = app_data.xdg_activation.as_ref() {
+                    println!("Requesting activation");
+                    activation.request_token(
+                        &qh,
+                        RequestData {
+                            seat_and_serial: None,
+                            surface: Some(app_data.window.wl_surface().clone()),
+                            app_id: Some(String::from(
+                                "io.github.smithay.client-toolkit.SimpleWindow",
+                            )),
+                        },
+                    )
+                }
+                TimeoutAction::Drop
+            })
+            .unwrap();
     }
 
     // We don't know how large the window will be yet, so lets assume the minimum size we suggested for the
@@ -101,13 +146,25 @@ fn main() {
         output_state: OutputState::new(&globals, &qh),
         shm,
         xdg_activation,
+        presentation,
+        tearing_control_state,
 
         exit: false,
         first_configure: true,
+        suspended: false,
+        fullscreen: false,
         pool,
         width: 256,
         height: 256,
+        scale: 1.,
+        fractional_scale,
+        viewport,
+        tearing_control: None,
+        last_presented: None,
+        presented_frames: 0,
+        presented_interval: Duration::ZERO,
         shift: None,
+        decoration_mode: DecorationMode::Client,
         buffer: None,
         window,
         keyboard: None,
@@ -133,13 +190,27 @@ struct SimpleWindow {
     output_state: OutputState,
     shm: Shm,
     xdg_activation: Option<ActivationState>,
+    presentation: Option<PresentationState>,
+    tearing_control_state: Option<TearingControlState>,
 
     exit: bool,
     first_configure: bool,
+    suspended: bool,
+    fullscreen: bool,
     pool: SlotPool,
     width: u32,
     height: u32,
+    scale: f64,
+    fractional_scale: Option<FractionalScale>,
+    // Dropped before the window, since the viewport must be destroyed before the surface.
+    viewport: Option<Viewport>,
+    // Also dropped before the window.
+    tearing_control: Option<TearingControl>,
+    last_presented: Option<PresentationTime>,
+    presented_frames: u32,
+    presented_interval: Duration,
     shift: Option<u32>,
+    decoration_mode: DecorationMode,
     buffer: Option<Buffer>,
     window: Window,
     keyboard: Option<wl_keyboard::WlKeyboard>,
@@ -176,7 +247,9 @@ impl CompositorHandler for SimpleWindow {
         _surface: &wl_surface::WlSurface,
         _time: u32,
     ) {
-        self.draw(conn, qh);
+        if !self.suspended {
+            self.draw(conn, qh);
+        }
     }
 }
 
@@ -210,6 +283,8 @@ impl OutputHandler for SimpleWindow {
     }
 }
 
+impl XdgShellHandler for SimpleWindow {}
+
 impl WindowHandler for SimpleWindow {
     fn request_close(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &Window) {
         self.exit = true;
@@ -228,15 +303,80 @@ impl WindowHandler for SimpleWindow {
         self.buffer = None;
         self.width = configure.new_size.0.map(|v| v.get()).unwrap_or(256);
         self.height = configure.new_size.1.map(|v| v.get()).unwrap_or(256);
+        self.decoration_mode = configure.decoration_mode;
 
-        // Initiate the first draw.
-        if self.first_configure {
+        // A suspended window is not visible, so the draw loop is stopped until it is resumed.
+        let resumed = self.suspended && !configure.is_suspended();
+        self.suspended = configure.is_suspended();
+        self.fullscreen = configure.is_fullscreen();
+
+        // Initiate the first draw, or restart drawing after being suspended.
+        if self.first_configure || resumed {
             self.first_configure = false;
             self.draw(conn, qh);
         }
     }
 }
 
+impl PresentationHandler for SimpleWindow {
+    fn presentation_state(&mut self) -> &mut PresentationState {
+        self.presentation.as_mut().unwrap()
+    }
+
+    fn presented(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        presented: Presented,
+    ) {
+        if let Some(interval) =
+            self.last_presented.and_then(|last| presented.time.duration_since(last))
+        {
+            self.presented_frames += 1;
+            self.presented_interval += interval;
+
+            // Print the average over a second or so, as individual intervals are noisy.
+            if self.presented_frames == 60 {
+                println!(
+                    "Measured refresh interval: {:?}, reported: {:?}",
+                    self.presented_interval / self.presented_frames,
+                    presented.refresh
+                );
+                self.presented_frames = 0;
+                self.presented_interval = Duration::ZERO;
+            }
+        }
+
+        self.last_presented = Some(presented.time);
+    }
+
+    fn discarded(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+    ) {
+    }
+}
+
+impl FractionalScaleHandler for SimpleWindow {
+    fn preferred_scale(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        scale: f64,
+    ) {
+        println!("Preferred scale: {scale}");
+        if self.fractional_scale.is_some() && self.scale != scale {
+            self.scale = scale;
+            // The next frame draws a buffer of the new size.
+            self.buffer = None;
+        }
+    }
+}
+
 impl ActivationHandler for SimpleWindow {
     type RequestData = RequestData;
 
@@ -342,12 +482,59 @@ impl KeyboardHandler for SimpleWindow {
     fn press_key(
         &mut self,
         _conn: &Connection,
-        _qh: &QueueHandle<Self>,
+        qh: &QueueHandle<Self>,
         _: &wl_keyboard::WlKeyboard,
         _: u32,
         event: KeyEvent,
     ) {
         println!("Key press: {event:?}");
+
+        if event.keysym == Keysym::f {
+            if self.fullscreen {
+                self.window.unset_fullscreen();
+            } else {
+                // Go fullscreen on the output the window is on, which is the one under the pointer
+                // as long as the pointer is over the window.
+                let output =
+                    self.window.wl_surface().data::<SurfaceData>().unwrap().outputs().next();
+                self.window.set_fullscreen(output.as_ref());
+            }
+        }
+
+        if event.keysym == Keysym::m {
+            self.window.set_minimized();
+        }
+
+        // Switch between vsync and tearing page flips. Frames are still drawn on frame callbacks.
+        if event.keysym == Keysym::t {
+            if self.tearing_control.take().is_some() {
+                // The window is presented with vsync again after the next commit.
+                println!("Presenting with vsync");
+            } else if let Some(tearing_control_state) = &self.tearing_control_state {
+                let tearing_control =
+                    tearing_control_state.get_tearing_control(self.window.wl_surface(), qh);
+                tearing_control.set_presentation_hint(PresentationHint::Async);
+                self.tearing_control = Some(tearing_control);
+                println!("Allowing tearing");
+            } else {
+                println!("Tearing control is not available");
+            }
+        }
+
+        // Switch between server and client side decorations.
+        if event.keysym == Keysym::d {
+            if !self.window.server_decorations_available() {
+                println!("Server side decorations are not available");
+                return;
+            }
+
+            let mode = match self.decoration_mode {
+                DecorationMode::Client => DecorationMode::Server,
+                DecorationMode::Server => DecorationMode::Client,
+            };
+            println!("Requesting {mode:?} side decorations");
+            self.window.request_decoration_mode(Some(mode));
+        }
     }
 
     fn release_key(
@@ -398,14 +585,22 @@ impl PointerHandler for SimpleWindow {
                 }
                 Motion { .. } => {}
                 Press { button, .. } => {
-                    println!("Press {:x} @ {:?}", button, event.position);
+                    println!("Press {:?} @ {:?}", PointerButton::from(button), event.position);
                     self.shift = self.shift.xor(Some(0));
                 }
                 Release { button, .. } => {
-                    println!("Release {:x} @ {:?}", button, event.position);
+                    println!("Release {:?} @ {:?}", PointerButton::from(button), event.position);
                 }
                 Axis { horizontal, vertical, .. } => {
                     println!("Scroll H:{horizontal:?}, V:{vertical:?}");
+                    if horizontal.value120 != 0 || vertical.value120 != 0 {
+                        // High-resolution wheels report fractions of a step.
+                        println!(
+                            "Wheel H:{:.3} steps, V:{:.3} steps",
+                            horizontal.value120 as f64 / 120.,
+                            vertical.value120 as f64 / 120.
+                        );
+                    }
                 }
             }
         }
@@ -420,9 +615,9 @@ impl ShmHandler for SimpleWindow {
 
 impl SimpleWindow {
     pub fn draw(&mut self, _conn: &Connection, qh: &QueueHandle<Self>) {
-        let width = self.width;
-        let height = self.height;
-        let stride = self.width as i32 * 4;
+        // The buffer is larger than the window on outputs with a fractional scale.
+        let (width, height) = self.window.buffer_size_for_logical((self.width, self.height));
+        let stride = width as i32 * 4;
 
         let buffer = self.buffer.get_or_insert_with(|| {
             self.pool
@@ -438,12 +633,7 @@ impl SimpleWindow {
                 // buffer, we need double-buffering.
                 let (second_buffer, canvas) = self
                     .pool
-                    .create_buffer(
-                        self.width as i32,
-                        self.height as i32,
-                        stride,
-                        wl_shm::Format::Argb8888,
-                    )
+                    .create_buffer(width as i32, height as i32, stride, wl_shm::Format::Argb8888)
                     .expect("create buffer");
                 *buffer = second_buffer;
                 canvas
@@ -453,15 +643,26 @@ impl SimpleWindow {
         // Draw to the window:
         {
             let shift = self.shift.unwrap_or(0);
+            // Without server side decorations, draw a trivial border so the window stands out.
+            let border = if self.decoration_mode == DecorationMode::Client { 4 } else { 0 };
             canvas.chunks_exact_mut(4).enumerate().for_each(|(index, chunk)| {
                 let x = ((index + shift as usize) % width as usize) as u32;
                 let y = (index / width as usize) as u32;
+                let (bx, by) = ((index % width as usize) as u32, y);
 
                 let a = 0xFF;
                 let r = u32::min(((width - x) * 0xFF) / width, ((height - y) * 0xFF) / height);
                 let g = u32::min((x * 0xFF) / width, ((height - y) * 0xFF) / height);
                 let b = u32::min(((width - x) * 0xFF) / width, (y * 0xFF) / height);
-                let color = (a << 24) + (r << 16) + (g << 8) + b;
+                let color = if bx < border
+                    || by < border
+                    || bx >= width - border
+                    || by >= height - border
+                {
+                    0xFF333333
+                } else {
+                    (a << 24) + (r << 16) + (g << 8) + b
+                };
 
                 let array: &mut [u8; 4] = chunk.try_into().unwrap();
                 *array = color.to_le_bytes();
@@ -473,10 +674,20 @@ impl SimpleWindow {
         }
 
         // Damage the entire window
-        self.window.wl_surface().damage_buffer(0, 0, self.width as i32, self.height as i32);
+        self.window.wl_surface().damage_buffer(0, 0, width as i32, height as i32);
+
+        // Present the buffer at the size of the window.
+        if let Some(viewport) = &self.viewport {
+            viewport.set_destination(Some((self.width, self.height))).unwrap();
+        }
 
         // Request our next frame
-        self.window.wl_surface().frame(qh, self.window.wl_surface().clone());
+        self.window.request_frame(qh);
+
+        // Learn when this frame is shown.
+        if let Some(presentation) = &self.presentation {
+            presentation.feedback(self.window.wl_surface(), qh);
+        }
 
         // Attach and commit to present.
         buffer.attach_to(self.window.wl_surface()).expect("buffer attach");
@@ -495,6 +706,10 @@ delegate_pointer!(SimpleWindow);
 delegate_xdg_shell!(SimpleWindow);
 delegate_xdg_window!(SimpleWindow);
 delegate_activation!(SimpleWindow);
+delegate_fractional_scale!(SimpleWindow);
+delegate_viewporter!(SimpleWindow);
+delegate_tearing_control!(SimpleWindow);
+delegate_presentation_time!(SimpleWindow);
 
 delegate_registry!(SimpleWindow);
 
@@ -0,0 +1,252 @@
+//! Shows a desynchronized subsurface animated at the refresh rate of the output, like a video
+//! player, while the window itself is only drawn when it is configured.
+//!
+//! ```text
+//! cargo run --example subsurface_video
+//! ```
+
+use std::convert::TryInto;
+
+use smithay_client_toolkit::{
+    compositor::{CompositorHandler, CompositorState},
+    delegate_compositor, delegate_output, delegate_registry, delegate_shm, delegate_subcompositor,
+    delegate_xdg_shell, delegate_xdg_window,
+    output::{OutputHandler, OutputState},
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    shell::{
+        xdg::{
+            window::{Window, WindowConfigure, WindowDecorations, WindowHandler},
+            XdgShell, XdgShellHandler,
+        },
+        WaylandSurface,
+    },
+    shm::{slot::SlotPool, Shm, ShmHandler},
+    subcompositor::{SubcompositorState, Subsurface},
+};
+use wayland_client::{
+    globals::registry_queue_init,
+    protocol::{wl_output, wl_shm, wl_surface},
+    Connection, QueueHandle,
+};
+
+const VIDEO_WIDTH: u32 = 160;
+const VIDEO_HEIGHT: u32 = 90;
+const VIDEO_POSITION: (i32, i32) = (48, 48);
+
+fn main() {
+    env_logger::init();
+
+    let conn = Connection::connect_to_env().unwrap();
+
+    let (globals, mut event_queue) = registry_queue_init(&conn).unwrap();
+    let qh = event_queue.handle();
+
+    let compositor = CompositorState::bind(&globals, &qh).expect("wl_compositor not available");
+    let subcompositor = SubcompositorState::bind(compositor.wl_compositor().clone(), &globals, &qh)
+        .expect("wl_subcompositor not available");
+    let xdg_shell = XdgShell::bind(&globals, &qh).expect("xdg shell is not available");
+    let shm = Shm::bind(&globals, &qh).expect("wl shm is not available.");
+
+    let surface = compositor.create_surface(&qh);
+    let window = xdg_shell.create_window(surface, WindowDecorations::RequestServer, &qh);
+    window.set_title("Subsurface video");
+    window.set_app_id("io.github.smithay.client-toolkit.SubsurfaceVideo");
+    window.set_min_size(Some((256, 256)));
+
+    let video = subcompositor.subsurface_from_surface(
+        compositor.create_surface(&qh),
+        window.wl_surface(),
+        &qh,
+    );
+    video.set_position(VIDEO_POSITION.0, VIDEO_POSITION.1);
+    // Show the frames of the video as soon as they are committed, without committing the window.
+    video.set_desync();
+    window.commit();
+
+    let pool = SlotPool::new(256 * 256 * 4, &shm).expect("Failed to create pool");
+
+    let mut player = Player {
+        registry_state: RegistryState::new(&globals),
+        output_state: OutputState::new(&globals, &qh),
+        shm,
+
+        exit: false,
+        pool,
+        width: 256,
+        height: 256,
+        configured: false,
+        frame: 0,
+        video,
+        window,
+    };
+
+    loop {
+        event_queue.blocking_dispatch(&mut player).unwrap();
+
+        if player.exit {
+            println!("exiting example");
+            break;
+        }
+    }
+}
+
+struct Player {
+    registry_state: RegistryState,
+    output_state: OutputState,
+    shm: Shm,
+
+    exit: bool,
+    pool: SlotPool,
+    width: u32,
+    height: u32,
+    configured: bool,
+    /// The number of frames of the video drawn so far.
+    frame: u32,
+    // Dropped before the window, since the subsurface must be destroyed before its parent.
+    video: Subsurface,
+    window: Window,
+}
+
+impl Player {
+    fn draw_window(&mut self) {
+        let (width, height) = (self.width, self.height);
+        let (buffer, canvas) = self
+            .pool
+            .create_buffer(width as i32, height as i32, width as i32 * 4, wl_shm::Format::Xrgb8888)
+            .expect("create buffer");
+
+        canvas.chunks_exact_mut(4).for_each(|chunk| {
+            let array: &mut [u8; 4] = chunk.try_into().unwrap();
+            *array = 0xFF303030u32.to_le_bytes();
+        });
+
+        self.window.wl_surface().damage_buffer(0, 0, width as i32, height as i32);
+        buffer.attach_to(self.window.wl_surface()).expect("buffer attach");
+        self.window.commit();
+    }
+
+    fn draw_video(&mut self, qh: &QueueHandle<Self>) {
+        let (buffer, canvas) = self
+            .pool
+            .create_buffer(
+                VIDEO_WIDTH as i32,
+                VIDEO_HEIGHT as i32,
+                VIDEO_WIDTH as i32 * 4,
+                wl_shm::Format::Xrgb8888,
+            )
+            .expect("create buffer");
+
+        // Colors cycling through the frames.
+        let shift = self.frame % 256;
+        canvas.chunks_exact_mut(4).enumerate().for_each(|(index, chunk)| {
+            let x = (index as u32 % VIDEO_WIDTH + shift) % 256;
+            let y = (index as u32 / VIDEO_WIDTH + shift) % 256;
+            let color = 0xFF000000 | (x << 16) | (y << 8) | (255 - shift);
+            let array: &mut [u8; 4] = chunk.try_into().unwrap();
+            *array = color.to_le_bytes();
+        });
+        self.frame = self.frame.wrapping_add(1);
+
+        let surface = self.video.wl_surface();
+        surface.damage_buffer(0, 0, VIDEO_WIDTH as i32, VIDEO_HEIGHT as i32);
+        buffer.attach_to(surface).expect("buffer attach");
+        self.video.request_frame(qh);
+        self.video.commit();
+    }
+}
+
+impl XdgShellHandler for Player {}
+
+impl WindowHandler for Player {
+    fn request_close(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &Window) {
+        self.exit = true;
+    }
+
+    fn configure(
+        &mut self,
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+        _: &Window,
+        configure: WindowConfigure,
+        _: u32,
+    ) {
+        self.width = configure.new_size.0.map(|w| w.get()).unwrap_or(256);
+        self.height = configure.new_size.1.map(|h| h.get()).unwrap_or(256);
+        self.draw_window();
+
+        // Start the video once the window is mapped.
+        if !self.configured {
+            self.configured = true;
+            self.draw_video(qh);
+        }
+    }
+}
+
+impl CompositorHandler for Player {
+    fn scale_factor_changed(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &wl_surface::WlSurface,
+        _: i32,
+    ) {
+    }
+
+    fn transform_changed(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &wl_surface::WlSurface,
+        _: wl_output::Transform,
+    ) {
+    }
+
+    fn frame(
+        &mut self,
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+        surface: &wl_surface::WlSurface,
+        _: u32,
+    ) {
+        if surface == self.video.wl_surface() {
+            self.draw_video(qh);
+        }
+    }
+}
+
+impl OutputHandler for Player {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+
+    fn update_output(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+
+    fn output_destroyed(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+}
+
+impl ShmHandler for Player {
+    fn shm_state(&mut self) -> &mut Shm {
+        &mut self.shm
+    }
+}
+
+delegate_compositor!(Player);
+delegate_subcompositor!(Player);
+delegate_output!(Player);
+delegate_shm!(Player);
+
+delegate_xdg_shell!(Player);
+delegate_xdg_window!(Player);
+
+delegate_registry!(Player);
+
+impl ProvidesRegistryState for Player {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers![OutputState];
+}
@@ -0,0 +1,535 @@
+//! An example editing a single line of text, using an input method through text-input-v3 when available.
+
+use smithay_client_toolkit::{
+    compositor::{CompositorHandler, CompositorState},
+    delegate_compositor, delegate_keyboard, delegate_output, delegate_registry, delegate_seat,
+    delegate_shm, delegate_text_input, delegate_xdg_shell, delegate_xdg_window,
+    output::{OutputHandler, OutputState},
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    seat::{
+        keyboard::{KeyEvent, KeyboardHandler, Keysym, Modifiers},
+        text_input::{Preedit, TextInput, TextInputHandler, TextInputState, TextInputUpdate},
+        Capability, SeatHandler, SeatState,
+    },
+    shell::{
+        xdg::{
+            window::{Window, WindowConfigure, WindowDecorations, WindowHandler},
+            XdgShell, XdgShellHandler,
+        },
+        WaylandSurface,
+    },
+    shm::{slot::SlotPool, Shm, ShmHandler},
+};
+use wayland_client::{
+    globals::registry_queue_init,
+    protocol::{wl_keyboard, wl_output, wl_seat, wl_shm, wl_surface},
+    Connection, QueueHandle,
+};
+use wayland_protocols::wp::text_input::zv3::client::zwp_text_input_v3::{
+    self, ChangeCause, ContentHint, ContentPurpose,
+};
+
+const WHITE: raqote::SolidSource = raqote::SolidSource { r: 255, g: 255, b: 255, a: 255 };
+const SOLID_BLACK: raqote::Source =
+    raqote::Source::Solid(raqote::SolidSource { r: 0, g: 0, b: 0, a: 255 });
+const SOLID_BLUE: raqote::Source =
+    raqote::Source::Solid(raqote::SolidSource { r: 0, g: 0, b: 192, a: 255 });
+const FONT_SIZE: f32 = 18.;
+const MARGIN: f32 = 8.;
+const BASELINE: f32 = 32.;
+
+fn main() {
+    env_logger::init();
+
+    let conn = Connection::connect_to_env().unwrap();
+
+    let (globals, mut event_queue) = registry_queue_init(&conn).unwrap();
+    let qh = event_queue.handle();
+
+    let font = font_kit::source::SystemSource::new()
+        .select_best_match(
+            &[font_kit::family_name::FamilyName::SansSerif],
+            &font_kit::properties::Properties::new(),
+        )
+        .unwrap()
+        .load()
+        .unwrap();
+
+    let compositor_state =
+        CompositorState::bind(&globals, &qh).expect("wl_compositor not available");
+    let xdg_shell_state = XdgShell::bind(&globals, &qh).expect("xdg shell not available");
+    let shm_state = Shm::bind(&globals, &qh).expect("wl_shm not available");
+    let text_input_state = TextInputState::bind(&globals, &qh);
+
+    let surface = compositor_state.create_surface(&qh);
+    let window = xdg_shell_state.create_window(surface, WindowDecorations::ServerDefault, &qh);
+    window.set_title("Text input");
+    window.set_app_id("io.github.smithay.client-toolkit.TextInput");
+    window.set_min_size(Some((256, 48)));
+    window.commit();
+
+    let mut editor = Editor {
+        registry_state: RegistryState::new(&globals),
+        seat_state: SeatState::new(&globals, &qh),
+        output_state: OutputState::new(&globals, &qh),
+        shm_state,
+        text_input_state,
+
+        exit: false,
+        width: 512,
+        height: 48,
+        window,
+        keyboard: None,
+        text_input: None,
+        text: String::new(),
+        cursor: 0,
+        preedit: None,
+        font,
+    };
+
+    while !editor.exit {
+        event_queue.blocking_dispatch(&mut editor).unwrap();
+    }
+}
+
+struct Editor {
+    registry_state: RegistryState,
+    seat_state: SeatState,
+    output_state: OutputState,
+    shm_state: Shm,
+    text_input_state: TextInputState,
+
+    exit: bool,
+    width: u32,
+    height: u32,
+    window: Window,
+    keyboard: Option<wl_keyboard::WlKeyboard>,
+    text_input: Option<TextInput>,
+    text: String,
+    /// Byte offset of the cursor in `text`.
+    cursor: usize,
+    preedit: Option<Preedit>,
+    font: font_kit::loaders::freetype::Font,
+}
+
+impl CompositorHandler for Editor {
+    fn scale_factor_changed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _new_factor: i32,
+    ) {
+        // Not needed for this example.
+    }
+
+    fn transform_changed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _new_transform: wl_output::Transform,
+    ) {
+        // Not needed for this example.
+    }
+
+    fn frame(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _time: u32,
+    ) {
+        // Only redrawn when the text changes.
+    }
+}
+
+impl OutputHandler for Editor {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+
+    fn update_output(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+
+    fn output_destroyed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+}
+
+impl XdgShellHandler for Editor {}
+
+impl WindowHandler for Editor {
+    fn request_close(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &Window) {
+        self.exit = true;
+    }
+
+    fn configure(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _window: &Window,
+        configure: WindowConfigure,
+        _serial: u32,
+    ) {
+        self.width = configure.new_size.0.map(|v| v.get()).unwrap_or(512);
+        self.height = configure.new_size.1.map(|v| v.get()).unwrap_or(48);
+        self.draw();
+    }
+}
+
+impl SeatHandler for Editor {
+    fn seat_state(&mut self) -> &mut SeatState {
+        &mut self.seat_state
+    }
+
+    fn new_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_seat::WlSeat) {}
+
+    fn new_capability(
+        &mut self,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+        seat: wl_seat::WlSeat,
+        capability: Capability,
+    ) {
+        if capability == Capability::Keyboard && self.keyboard.is_none() {
+            self.keyboard = Some(
+                self.seat_state.get_keyboard(qh, &seat, None).expect("Failed to create keyboard"),
+            );
+
+            match self.text_input_state.get_text_input(&seat, qh) {
+                Ok(text_input) => self.text_input = Some(text_input),
+                Err(_) => println!("Compositor does not support text-input-v3"),
+            }
+        }
+    }
+
+    fn remove_capability(
+        &mut self,
+        _conn: &Connection,
+        _: &QueueHandle<Self>,
+        _: wl_seat::WlSeat,
+        capability: Capability,
+    ) {
+        if capability == Capability::Keyboard {
+            if let Some(keyboard) = self.keyboard.take() {
+                keyboard.release();
+            }
+            self.text_input = None;
+        }
+    }
+
+    fn remove_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_seat::WlSeat) {}
+}
+
+impl KeyboardHandler for Editor {
+    fn enter(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &wl_keyboard::WlKeyboard,
+        _: &wl_surface::WlSurface,
+        _: u32,
+        _: &[u32],
+        _: &[Keysym],
+    ) {
+    }
+
+    fn leave(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &wl_keyboard::WlKeyboard,
+        _: &wl_surface::WlSurface,
+        _: u32,
+    ) {
+    }
+
+    fn press_key(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _: &wl_keyboard::WlKeyboard,
+        _: u32,
+        event: KeyEvent,
+    ) {
+        match event.keysym {
+            Keysym::BackSpace => {
+                if let Some((index, _)) = self.text[..self.cursor].char_indices().next_back() {
+                    self.text.replace_range(index..self.cursor, "");
+                    self.cursor = index;
+                }
+            }
+            Keysym::Delete => {
+                if let Some(c) = self.text[self.cursor..].chars().next() {
+                    self.text.replace_range(self.cursor..self.cursor + c.len_utf8(), "");
+                }
+            }
+            Keysym::Left => {
+                if let Some((index, _)) = self.text[..self.cursor].char_indices().next_back() {
+                    self.cursor = index;
+                }
+            }
+            Keysym::Right => {
+                if let Some(c) = self.text[self.cursor..].chars().next() {
+                    self.cursor += c.len_utf8();
+                }
+            }
+            Keysym::Escape => {
+                self.exit = true;
+                return;
+            }
+            _ => match event.utf8 {
+                Some(text) if !text.chars().any(char::is_control) => {
+                    self.text.insert_str(self.cursor, &text);
+                    self.cursor += text.len();
+                }
+                _ => return,
+            },
+        }
+
+        self.send_state(ChangeCause::Other);
+        self.draw();
+    }
+
+    fn release_key(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &wl_keyboard::WlKeyboard,
+        _: u32,
+        _: KeyEvent,
+    ) {
+    }
+
+    fn update_modifiers(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &wl_keyboard::WlKeyboard,
+        _serial: u32,
+        _modifiers: Modifiers,
+        _layout: u32,
+    ) {
+    }
+}
+
+impl TextInputHandler for Editor {
+    fn enter(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _text_input: &zwp_text_input_v3::ZwpTextInputV3,
+        surface: &wl_surface::WlSurface,
+    ) {
+        if self.window.wl_surface() != surface {
+            return;
+        }
+
+        if let Some(text_input) = self.text_input.as_ref() {
+            text_input.enable();
+            text_input.set_content_type(ContentHint::None, ContentPurpose::Normal);
+        }
+        self.send_state(ChangeCause::Other);
+    }
+
+    fn leave(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _text_input: &zwp_text_input_v3::ZwpTextInputV3,
+        _surface: &wl_surface::WlSurface,
+    ) {
+        if let Some(text_input) = self.text_input.as_ref() {
+            text_input.disable();
+            text_input.commit();
+        }
+        self.preedit = None;
+        self.draw();
+    }
+
+    fn done(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _text_input: &zwp_text_input_v3::ZwpTextInputV3,
+        update: TextInputUpdate,
+    ) {
+        if let Some(delete) = update.delete_surrounding_text {
+            let start = floor_char_boundary(
+                &self.text,
+                self.cursor.saturating_sub(delete.before_length as usize),
+            );
+            let end = floor_char_boundary(&self.text, self.cursor + delete.after_length as usize);
+            self.text.replace_range(start..end, "");
+            self.cursor = start;
+        }
+
+        if let Some(text) = update.commit_string {
+            self.text.insert_str(self.cursor, &text);
+            self.cursor += text.len();
+        }
+
+        self.preedit = update.preedit;
+
+        // State may only be sent once the compositor caught up with our latest commit.
+        if update.is_current {
+            self.send_state(ChangeCause::InputMethod);
+        }
+        self.draw();
+    }
+}
+
+impl ShmHandler for Editor {
+    fn shm_state(&mut self) -> &mut Shm {
+        &mut self.shm_state
+    }
+}
+
+impl Editor {
+    fn send_state(&self, cause: ChangeCause) {
+        let text_input = match self.text_input.as_ref() {
+            Some(text_input) if text_input.focus().is_some() => text_input,
+            _ => return,
+        };
+
+        text_input.set_surrounding_text(self.text.clone(), self.cursor as i32, self.cursor as i32);
+        text_input.set_text_change_cause(cause);
+        let x = MARGIN + self.text_width(&self.text[..self.cursor]);
+        text_input.set_cursor_rectangle(
+            x as i32,
+            (BASELINE - FONT_SIZE) as i32,
+            1,
+            FONT_SIZE as i32,
+        );
+        text_input.commit();
+    }
+
+    fn text_width(&self, text: &str) -> f32 {
+        let units_per_em = self.font.metrics().units_per_em as f32;
+        text.chars()
+            .filter_map(|c| self.font.glyph_for_char(c))
+            .filter_map(|glyph| self.font.advance(glyph).ok())
+            .map(|advance| advance.x() * FONT_SIZE / units_per_em)
+            .sum()
+    }
+
+    fn draw(&mut self) {
+        let width = self.width;
+        let height = self.height;
+        let stride = width as i32 * 4;
+
+        let mut pool = SlotPool::new(width as usize * height as usize * 4, &self.shm_state)
+            .expect("Failed to create pool");
+
+        let buffer = pool
+            .create_buffer(width as i32, height as i32, stride, wl_shm::Format::Xrgb8888)
+            .expect("create buffer")
+            .0;
+
+        let mut dt = raqote::DrawTarget::from_backing(
+            width as i32,
+            height as i32,
+            bytemuck::cast_slice_mut(pool.canvas(&buffer).unwrap()),
+        );
+        dt.clear(WHITE);
+
+        let (before, after) = self.text.split_at(self.cursor);
+        let mut x = MARGIN;
+        let options = raqote::DrawOptions::new();
+
+        dt.draw_text(
+            &self.font,
+            FONT_SIZE,
+            before,
+            raqote::Point::new(x, BASELINE),
+            &SOLID_BLACK,
+            &options,
+        );
+        x += self.text_width(before);
+
+        let mut cursor_x = x;
+        if let Some(preedit) = self.preedit.as_ref() {
+            dt.draw_text(
+                &self.font,
+                FONT_SIZE,
+                &preedit.text,
+                raqote::Point::new(x, BASELINE),
+                &SOLID_BLUE,
+                &options,
+            );
+            let preedit_width = self.text_width(&preedit.text);
+            dt.fill_rect(x, BASELINE + 2., preedit_width, 1., &SOLID_BLUE, &options);
+
+            if let Some((begin, _)) = preedit.cursor {
+                let begin = floor_char_boundary(&preedit.text, begin.max(0) as usize);
+                cursor_x = x + self.text_width(&preedit.text[..begin]);
+            }
+            x += preedit_width;
+        }
+
+        dt.draw_text(
+            &self.font,
+            FONT_SIZE,
+            after,
+            raqote::Point::new(x, BASELINE),
+            &SOLID_BLACK,
+            &options,
+        );
+        dt.fill_rect(cursor_x, BASELINE - FONT_SIZE, 1., FONT_SIZE + 4., &SOLID_BLACK, &options);
+
+        // Damage the entire window
+        self.window.wl_surface().damage_buffer(0, 0, width as i32, height as i32);
+
+        // Attach and commit to present.
+        buffer.attach_to(self.window.wl_surface()).expect("buffer attach");
+        self.window.wl_surface().commit();
+    }
+}
+
+/// Clamp a byte offset to the closest char boundary at or before it.
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+delegate_compositor!(Editor);
+delegate_output!(Editor);
+delegate_shm!(Editor);
+
+delegate_seat!(Editor);
+delegate_keyboard!(Editor);
+delegate_text_input!(Editor);
+
+delegate_xdg_shell!(Editor);
+delegate_xdg_window!(Editor);
+
+delegate_registry!(Editor);
+
+impl ProvidesRegistryState for Editor {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+    registry_handlers![OutputState, SeatState,];
+}
@@ -21,8 +21,8 @@ use smithay_client_toolkit::{
     seat::{
         keyboard::{KeyEvent, KeyboardHandler, Keysym, Modifiers},
         pointer::{
-            CursorIcon, PointerData, PointerEvent, PointerEventKind, PointerHandler, ThemeSpec,
-            ThemedPointer,
+            CursorIcon, PointerButton, PointerData, PointerEvent, PointerEventKind, PointerHandler,
+            ThemeSpec, ThemedPointer,
         },
         Capability, SeatHandler, SeatState,
     },
@@ -30,7 +30,7 @@ use smithay_client_toolkit::{
         xdg::{
             fallback_frame::FallbackFrame,
             window::{DecorationMode, Window, WindowConfigure, WindowDecorations, WindowHandler},
-            XdgShell, XdgSurface,
+            XdgShell, XdgShellHandler, XdgSurface,
         },
         WaylandSurface,
     },
@@ -186,12 +186,17 @@ struct SimpleWindow {
 impl CompositorHandler for SimpleWindow {
     fn scale_factor_changed(
         &mut self,
-        _conn: &Connection,
+        conn: &Connection,
         _qh: &QueueHandle<Self>,
-        _surface: &wl_surface::WlSurface,
+        surface: &wl_surface::WlSurface,
         _new_factor: i32,
     ) {
-        // Not needed for this example.
+        // Show the cursor at the size of the new output.
+        if let Some(themed_pointer) = self.themed_pointer.as_ref() {
+            if themed_pointer.surface() == surface {
+                let _ = themed_pointer.reload(conn);
+            }
+        }
     }
 
     fn transform_changed(
@@ -245,6 +250,8 @@ impl OutputHandler for SimpleWindow {
     }
 }
 
+impl XdgShellHandler for SimpleWindow {}
+
 impl WindowHandler for SimpleWindow {
     fn request_close(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &Window) {
         self.exit = true;
@@ -509,9 +516,9 @@ impl PointerHandler for SimpleWindow {
                 Press { button, serial, time } | Release { button, serial, time } => {
                     let pressed = matches!(event.kind, Press { .. });
                     if &event.surface != self.window.wl_surface() {
-                        let click = match button {
-                            0x110 => FrameClick::Normal,
-                            0x111 => FrameClick::Alternate,
+                        let click = match PointerButton::from(button) {
+                            PointerButton::Left => FrameClick::Normal,
+                            PointerButton::Right => FrameClick::Alternate,
                             _ => continue,
                         };
 
@@ -634,11 +641,11 @@ impl SimpleWindow {
         self.window.wl_surface().damage_buffer(0, 0, width as i32, height as i32);
 
         // Request our next frame
-        self.window.wl_surface().frame(qh, self.window.wl_surface().clone());
+        self.window.request_frame(qh);
 
         // Attach and commit to present.
         buffer.attach_to(self.window.wl_surface()).expect("buffer attach");
-        self.window.wl_surface().commit();
+        self.window.commit();
     }
 }
 
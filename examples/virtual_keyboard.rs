@@ -0,0 +1,109 @@
+//! Types "hello " into the focused client using a virtual keyboard.
+
+use std::{thread, time::Duration};
+
+use smithay_client_toolkit::{
+    delegate_registry, delegate_seat, delegate_virtual_keyboard,
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    seat::{
+        keyboard::{Keysym, RMLVO},
+        virtual_keyboard::VirtualKeyboardState,
+        Capability, SeatHandler, SeatState,
+    },
+};
+use wayland_client::{
+    globals::registry_queue_init,
+    protocol::{wl_keyboard, wl_seat},
+    Connection, QueueHandle,
+};
+
+fn main() {
+    env_logger::init();
+
+    let conn = Connection::connect_to_env().unwrap();
+
+    let (globals, mut event_queue) = registry_queue_init(&conn).unwrap();
+    let qh = event_queue.handle();
+
+    let mut typer = Typer {
+        registry_state: RegistryState::new(&globals),
+        seat_state: SeatState::new(&globals, &qh),
+        virtual_keyboard_state: VirtualKeyboardState::bind(&globals, &qh),
+    };
+
+    event_queue.roundtrip(&mut typer).unwrap();
+
+    let seat = typer.seat_state.seats().next().expect("no seat available");
+    let mut keyboard = typer
+        .virtual_keyboard_state
+        .create_virtual_keyboard(&seat, &qh)
+        .expect("zwp_virtual_keyboard_manager_v1 not available");
+
+    // Use the default keymap of the system.
+    let rmlvo = RMLVO { rules: None, model: None, layout: None, variant: None, options: None };
+    keyboard.set_keymap_from_rmlvo(rmlvo).expect("failed to set keymap");
+    event_queue.roundtrip(&mut typer).unwrap();
+
+    // Give some time to focus another client.
+    println!("Typing \"hello \" in 3 seconds");
+    thread::sleep(Duration::from_secs(3));
+
+    keyboard.type_str(0, "hello").expect("failed to type");
+
+    // Keys can also be sent one by one, looking up their code in the keymap.
+    let space = keyboard
+        .keycode_for_keysym(Keysym::space)
+        .unwrap()
+        .expect("no key produces a space in the keymap");
+    keyboard.key(0, space, wl_keyboard::KeyState::Pressed).unwrap();
+    keyboard.key(0, space, wl_keyboard::KeyState::Released).unwrap();
+    event_queue.roundtrip(&mut typer).unwrap();
+}
+
+struct Typer {
+    registry_state: RegistryState,
+    seat_state: SeatState,
+    virtual_keyboard_state: VirtualKeyboardState,
+}
+
+impl SeatHandler for Typer {
+    fn seat_state(&mut self) -> &mut SeatState {
+        &mut self.seat_state
+    }
+
+    fn new_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_seat::WlSeat) {}
+
+    fn new_capability(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: wl_seat::WlSeat,
+        _: Capability,
+    ) {
+    }
+
+    fn remove_capability(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: wl_seat::WlSeat,
+        _: Capability,
+    ) {
+    }
+
+    fn remove_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_seat::WlSeat) {}
+}
+
+delegate_seat!(Typer);
+delegate_virtual_keyboard!(Typer);
+
+delegate_registry!(Typer);
+
+impl ProvidesRegistryState for Typer {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers!(SeatState);
+}
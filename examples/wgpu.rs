@@ -13,7 +13,7 @@ use smithay_client_toolkit::{
     shell::{
         xdg::{
             window::{Window, WindowConfigure, WindowDecorations, WindowHandler},
-            XdgShell,
+            XdgShell, XdgShellHandler,
         },
         WaylandSurface,
     },
@@ -201,6 +201,8 @@ impl OutputHandler for Wgpu {
     }
 }
 
+impl XdgShellHandler for Wgpu {}
+
 impl WindowHandler for Wgpu {
     fn request_close(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &Window) {
         self.exit = true;
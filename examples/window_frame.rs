@@ -0,0 +1,307 @@
+//! Shows a window with client side decorations from the frame module, if the compositor does not draw
+//! server side decorations.
+//!
+//! Drag the titlebar to move the window, or its borders to resize it, and use the buttons to
+//! minimize, maximize or close it.
+//!
+//! ```text
+//! cargo run --example window_frame --features frame
+//! ```
+
+use std::{convert::TryInto, num::NonZeroU32, sync::Arc};
+
+use smithay_client_toolkit::{
+    compositor::{CompositorHandler, CompositorState},
+    delegate_compositor, delegate_output, delegate_pointer, delegate_registry, delegate_seat,
+    delegate_shm, delegate_subcompositor, delegate_xdg_shell, delegate_xdg_window,
+    output::{OutputHandler, OutputState},
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    seat::{
+        pointer::{
+            CursorIcon, PointerData, PointerEvent, PointerEventKind, PointerHandler, ThemeSpec,
+            ThemedPointer,
+        },
+        Capability, SeatHandler, SeatState,
+    },
+    shell::{
+        xdg::{
+            frame::{FrameRequest, FrameTheme, WindowFrame},
+            window::{Window, WindowConfigure, WindowDecorations, WindowHandler},
+            XdgShell, XdgShellHandler,
+        },
+        WaylandSurface,
+    },
+    shm::{slot::SlotPool, Shm, ShmHandler},
+    subcompositor::SubcompositorState,
+};
+use wayland_client::{
+    globals::registry_queue_init,
+    protocol::{wl_output, wl_pointer, wl_seat, wl_shm, wl_surface},
+    Connection, Proxy, QueueHandle,
+};
+
+fn main() {
+    env_logger::init();
+
+    let conn = Connection::connect_to_env().unwrap();
+
+    let (globals, mut event_queue) = registry_queue_init(&conn).unwrap();
+    let qh = event_queue.handle();
+
+    let compositor = CompositorState::bind(&globals, &qh).expect("wl_compositor not available");
+    let subcompositor = SubcompositorState::bind(compositor.wl_compositor().clone(), &globals, &qh)
+        .expect("wl_subcompositor not available");
+    let xdg_shell = XdgShell::bind(&globals, &qh).expect("xdg shell is not available");
+    let shm = Shm::bind(&globals, &qh).expect("wl shm is not available.");
+
+    let surface = compositor.create_surface(&qh);
+    // Prefer server side decorations, the frame is only shown if the compositor refuses them.
+    let window = xdg_shell.create_window(surface, WindowDecorations::RequestServer, &qh);
+    window.set_title("Window frame");
+    window.set_app_id("io.github.smithay.client-toolkit.WindowFrame");
+    window.set_min_size(Some((256, 256)));
+    window.commit();
+
+    // A blue frame.
+    let theme = FrameTheme {
+        active: 0xFF2A4A7A,
+        inactive: 0xFF1E2A3A,
+        button_icon: 0xFFE0E0E0,
+        button_hover: 0xFF4A6A9A,
+    };
+    let frame = WindowFrame::new(&window, &shm, Arc::new(subcompositor), qh.clone(), theme)
+        .expect("failed to create the window frame");
+
+    let pool = SlotPool::new(256 * 256 * 4, &shm).expect("Failed to create pool");
+
+    let mut framed = Framed {
+        registry_state: RegistryState::new(&globals),
+        seat_state: SeatState::new(&globals, &qh),
+        output_state: OutputState::new(&globals, &qh),
+        compositor,
+        shm,
+
+        exit: false,
+        pool,
+        width: NonZeroU32::new(256).unwrap(),
+        height: NonZeroU32::new(256).unwrap(),
+        themed_pointer: None,
+        frame,
+        window,
+    };
+
+    loop {
+        event_queue.blocking_dispatch(&mut framed).unwrap();
+
+        if framed.exit {
+            println!("exiting example");
+            break;
+        }
+    }
+}
+
+struct Framed {
+    registry_state: RegistryState,
+    seat_state: SeatState,
+    output_state: OutputState,
+    compositor: CompositorState,
+    shm: Shm,
+
+    exit: bool,
+    pool: SlotPool,
+    width: NonZeroU32,
+    height: NonZeroU32,
+    themed_pointer: Option<ThemedPointer>,
+    // Dropped before the window, since it draws in subsurfaces of the window.
+    frame: WindowFrame<Self>,
+    window: Window,
+}
+
+impl Framed {
+    fn draw(&mut self) {
+        let (width, height) = (self.width.get(), self.height.get());
+        let (buffer, canvas) = self
+            .pool
+            .create_buffer(width as i32, height as i32, width as i32 * 4, wl_shm::Format::Xrgb8888)
+            .expect("create buffer");
+
+        canvas.chunks_exact_mut(4).for_each(|chunk| {
+            let array: &mut [u8; 4] = chunk.try_into().unwrap();
+            *array = 0xFFE8E8E8u32.to_le_bytes();
+        });
+
+        // The frame is drawn first, so that it is resized along with the window.
+        self.frame.draw();
+
+        self.window.wl_surface().damage_buffer(0, 0, width as i32, height as i32);
+        buffer.attach_to(self.window.wl_surface()).expect("buffer attach");
+        self.window.commit();
+    }
+}
+
+impl XdgShellHandler for Framed {}
+
+impl WindowHandler for Framed {
+    fn request_close(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &Window) {
+        self.exit = true;
+    }
+
+    fn configure(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &Window,
+        configure: WindowConfigure,
+        _: u32,
+    ) {
+        println!("Decoration mode: {:?}", configure.decoration_mode);
+        (self.width, self.height) = self.frame.configure(&configure, (self.width, self.height));
+        self.draw();
+    }
+}
+
+impl PointerHandler for Framed {
+    fn pointer_frame(
+        &mut self,
+        conn: &Connection,
+        _: &QueueHandle<Self>,
+        pointer: &wl_pointer::WlPointer,
+        events: &[PointerEvent],
+    ) {
+        let seat = pointer.data::<PointerData>().unwrap().seat().clone();
+
+        for event in events {
+            if &event.surface == self.window.wl_surface() {
+                if let PointerEventKind::Enter { .. } = event.kind {
+                    self.set_cursor(conn, CursorIcon::Default);
+                }
+                continue;
+            }
+
+            match self.frame.handle_pointer_event(&seat, event) {
+                Some(FrameRequest::Close) => self.exit = true,
+                Some(FrameRequest::Cursor(icon)) => self.set_cursor(conn, icon),
+                None => {}
+            }
+        }
+
+        // Show the hovered button.
+        if self.frame.draw() {
+            self.window.commit();
+        }
+    }
+}
+
+impl Framed {
+    fn set_cursor(&self, conn: &Connection, icon: CursorIcon) {
+        if let Some(themed_pointer) = &self.themed_pointer {
+            let _ = themed_pointer.set_cursor(conn, icon);
+        }
+    }
+}
+
+impl SeatHandler for Framed {
+    fn seat_state(&mut self) -> &mut SeatState {
+        &mut self.seat_state
+    }
+
+    fn new_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_seat::WlSeat) {}
+
+    fn new_capability(
+        &mut self,
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+        seat: wl_seat::WlSeat,
+        capability: Capability,
+    ) {
+        if capability == Capability::Pointer && self.themed_pointer.is_none() {
+            let surface = self.compositor.create_surface(qh);
+            let themed_pointer = self
+                .seat_state
+                .get_pointer_with_theme(qh, &seat, self.shm.wl_shm(), surface, ThemeSpec::default())
+                .expect("Failed to create pointer");
+            self.themed_pointer = Some(themed_pointer);
+        }
+    }
+
+    fn remove_capability(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: wl_seat::WlSeat,
+        capability: Capability,
+    ) {
+        if capability == Capability::Pointer {
+            if let Some(themed_pointer) = self.themed_pointer.take() {
+                themed_pointer.pointer().release();
+            }
+        }
+    }
+
+    fn remove_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_seat::WlSeat) {}
+}
+
+impl CompositorHandler for Framed {
+    fn scale_factor_changed(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        surface: &wl_surface::WlSurface,
+        new_factor: i32,
+    ) {
+        if surface == self.window.wl_surface() {
+            self.frame.set_scale_factor(new_factor.into());
+            self.draw();
+        }
+    }
+
+    fn transform_changed(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &wl_surface::WlSurface,
+        _: wl_output::Transform,
+    ) {
+    }
+
+    fn frame(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &wl_surface::WlSurface, _: u32) {}
+}
+
+impl OutputHandler for Framed {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+
+    fn update_output(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+
+    fn output_destroyed(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+}
+
+impl ShmHandler for Framed {
+    fn shm_state(&mut self) -> &mut Shm {
+        &mut self.shm
+    }
+}
+
+delegate_compositor!(Framed);
+delegate_subcompositor!(Framed);
+delegate_output!(Framed);
+delegate_shm!(Framed);
+delegate_seat!(Framed);
+delegate_pointer!(Framed);
+
+delegate_xdg_shell!(Framed);
+delegate_xdg_window!(Framed);
+
+delegate_registry!(Framed);
+
+impl ProvidesRegistryState for Framed {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers![OutputState, SeatState];
+}
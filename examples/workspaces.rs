@@ -0,0 +1,233 @@
+//! Prints the workspace layout whenever it changes, and switches workspaces like a pager.
+//!
+//! Type the number of a workspace, as printed in the layout, and press enter to activate it.
+//!
+//! ```text
+//! cargo run --example workspaces
+//! ```
+
+use std::{io::BufRead, thread, time::Duration};
+
+use smithay_client_toolkit::{
+    delegate_output, delegate_registry, delegate_workspace,
+    output::{OutputHandler, OutputState},
+    reexports::{
+        calloop::{
+            channel::{self, Channel},
+            EventLoop,
+        },
+        calloop_wayland_source::WaylandSource,
+    },
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    workspace::{Workspace, WorkspaceGroup, WorkspaceHandler, WorkspaceState},
+};
+use wayland_client::{globals::registry_queue_init, protocol::wl_output, Connection, QueueHandle};
+
+fn main() {
+    env_logger::init();
+
+    let conn = Connection::connect_to_env().unwrap();
+
+    let (globals, event_queue) = registry_queue_init(&conn).unwrap();
+    let qh = event_queue.handle();
+
+    let mut event_loop: EventLoop<Pager> =
+        EventLoop::try_new().expect("Failed to initialize the event loop!");
+    WaylandSource::new(conn.clone(), event_queue).insert(event_loop.handle()).unwrap();
+
+    let mut pager = Pager {
+        registry_state: RegistryState::new(&globals),
+        output_state: OutputState::new(&globals, &qh),
+        workspace_state: WorkspaceState::bind(&globals, &qh)
+            .expect("ext workspace is not available"),
+        changed: false,
+    };
+
+    // Read the numbers typed on stdin, and activate the matching workspaces.
+    let (sender, numbers): (_, Channel<usize>) = channel::channel();
+    thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            match line.trim().parse() {
+                Ok(number) => {
+                    if sender.send(number).is_err() {
+                        break;
+                    }
+                }
+                Err(_) => eprintln!("Not a workspace number: {line}"),
+            }
+        }
+    });
+    event_loop
+        .handle()
+        .insert_source(numbers, |event, _, pager| {
+            if let channel::Event::Msg(number) = event {
+                pager.activate(number);
+            }
+        })
+        .unwrap();
+
+    loop {
+        event_loop.dispatch(Duration::from_millis(100), &mut pager).unwrap();
+
+        // Print the layout once for all the changes sent together.
+        if std::mem::take(&mut pager.changed) {
+            pager.print_layout();
+        }
+
+        if pager.workspace_state.is_finished() {
+            break;
+        }
+    }
+}
+
+struct Pager {
+    registry_state: RegistryState,
+    output_state: OutputState,
+    workspace_state: WorkspaceState,
+    changed: bool,
+}
+
+impl Pager {
+    /// The workspaces, numbered in the order they are printed.
+    fn numbered_workspaces(&self) -> Vec<Workspace> {
+        let mut workspaces = Vec::new();
+
+        for group in self.workspace_state.groups() {
+            workspaces.extend(group.info().into_iter().flat_map(|info| info.workspaces));
+        }
+
+        // Workspaces without a group are printed last.
+        for workspace in self.workspace_state.workspaces() {
+            if !workspaces.contains(workspace) {
+                workspaces.push(workspace.clone());
+            }
+        }
+
+        workspaces
+    }
+
+    fn print_layout(&self) {
+        let workspaces = self.numbered_workspaces();
+        let mut printed_group = None;
+
+        for (number, workspace) in workspaces.iter().enumerate() {
+            let Some(info) = workspace.info() else { continue };
+
+            if info.group != printed_group || number == 0 {
+                match &info.group {
+                    Some(group) => println!("Group on {}:", self.group_outputs(group)),
+                    None => println!("Without group:"),
+                }
+                printed_group = info.group.clone();
+            }
+
+            println!(
+                "  {number}: {}{}{}",
+                info.name,
+                if info.coordinates.is_empty() {
+                    String::new()
+                } else {
+                    format!(" {:?}", info.coordinates)
+                },
+                if info.is_active() { " (active)" } else { "" },
+            );
+        }
+
+        println!();
+    }
+
+    fn group_outputs(&self, group: &WorkspaceGroup) -> String {
+        let outputs = group.info().map(|info| info.outputs).unwrap_or_default();
+        if outputs.is_empty() {
+            return "no output".to_owned();
+        }
+
+        outputs
+            .iter()
+            .map(|output| {
+                self.output_state
+                    .info(output)
+                    .and_then(|info| info.name)
+                    .unwrap_or_else(|| "unknown output".to_owned())
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn activate(&self, number: usize) {
+        match self.numbered_workspaces().get(number) {
+            Some(workspace) => {
+                workspace.activate();
+                self.workspace_state.commit();
+            }
+            None => eprintln!("No workspace {number}"),
+        }
+    }
+}
+
+impl WorkspaceHandler for Pager {
+    fn workspace_state(&mut self) -> &mut WorkspaceState {
+        &mut self.workspace_state
+    }
+
+    fn new_workspace_group(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &WorkspaceGroup) {
+        self.changed = true;
+    }
+
+    fn update_workspace_group(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &WorkspaceGroup,
+    ) {
+        self.changed = true;
+    }
+
+    fn workspace_group_removed(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &WorkspaceGroup,
+    ) {
+        self.changed = true;
+    }
+
+    fn new_workspace(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &Workspace) {
+        self.changed = true;
+    }
+
+    fn update_workspace(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &Workspace) {
+        self.changed = true;
+    }
+
+    fn workspace_removed(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &Workspace) {
+        self.changed = true;
+    }
+}
+
+impl OutputHandler for Pager {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+
+    fn update_output(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+
+    fn output_destroyed(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+}
+
+delegate_output!(Pager);
+delegate_workspace!(Pager);
+
+delegate_registry!(Pager);
+
+impl ProvidesRegistryState for Pager {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers![OutputState];
+}
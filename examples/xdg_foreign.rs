@@ -0,0 +1,279 @@
+//! Makes a window of one process the parent of a window of another process.
+//!
+//! Run the example without arguments to open a window and print its exported handle, then run it again
+//! with the handle as argument to open a child window of the first one:
+//!
+//! ```text
+//! cargo run --example xdg_foreign
+//! cargo run --example xdg_foreign -- <handle>
+//! ```
+//!
+//! Handles exported by other applications can be used as well, such as the handles passed to XDG
+//! desktop portals as parent window, after removing their `wayland:` prefix.
+
+use std::{convert::TryInto, env};
+
+use smithay_client_toolkit::{
+    compositor::{CompositorHandler, CompositorState},
+    delegate_compositor, delegate_output, delegate_registry, delegate_shm, delegate_xdg_foreign,
+    delegate_xdg_shell, delegate_xdg_window,
+    output::{OutputHandler, OutputState},
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    shell::{
+        xdg::{
+            foreign::{ExportedToplevel, ForeignHandler, ForeignState, ImportedToplevel},
+            window::{Window, WindowConfigure, WindowDecorations, WindowHandler},
+            XdgShell, XdgShellHandler,
+        },
+        WaylandSurface,
+    },
+    shm::{slot::SlotPool, Shm, ShmHandler},
+};
+use wayland_client::{
+    globals::registry_queue_init,
+    protocol::{wl_output, wl_shm, wl_surface},
+    Connection, QueueHandle,
+};
+
+fn main() {
+    env_logger::init();
+
+    let handle = env::args().nth(1);
+
+    let conn = Connection::connect_to_env().unwrap();
+
+    let (globals, mut event_queue) = registry_queue_init(&conn).unwrap();
+    let qh = event_queue.handle();
+
+    let compositor = CompositorState::bind(&globals, &qh).expect("wl_compositor not available");
+    let xdg_shell = XdgShell::bind(&globals, &qh).expect("xdg shell is not available");
+    let shm = Shm::bind(&globals, &qh).expect("wl shm is not available.");
+    let foreign = ForeignState::new(&globals, &qh);
+
+    let surface = compositor.create_surface(&qh);
+    let window = xdg_shell.create_window(surface, WindowDecorations::RequestServer, &qh);
+    window.set_app_id("io.github.smithay.client-toolkit.XdgForeign");
+    window.set_min_size(Some((256, 256)));
+
+    let imported = match handle {
+        // Parent the window before its initial commit, so it is mapped as a child.
+        Some(handle) => {
+            window.set_title("xdg-foreign child");
+            let imported = foreign
+                .import_toplevel(handle, &qh)
+                .expect("xdg foreign importer is not available");
+            imported.set_parent_of(window.wl_surface());
+            Some(imported)
+        }
+
+        None => {
+            window.set_title("xdg-foreign parent");
+            None
+        }
+    };
+
+    window.commit();
+
+    let pool = SlotPool::new(256 * 256 * 4, &shm).expect("Failed to create pool");
+
+    let mut xdg_foreign = XdgForeign {
+        registry_state: RegistryState::new(&globals),
+        output_state: OutputState::new(&globals, &qh),
+        shm,
+        foreign,
+
+        exit: false,
+        first_configure: true,
+        pool,
+        width: 256,
+        height: 256,
+        exported: None,
+        imported,
+        window,
+    };
+
+    loop {
+        event_queue.blocking_dispatch(&mut xdg_foreign).unwrap();
+
+        if xdg_foreign.exit {
+            println!("exiting example");
+            break;
+        }
+    }
+}
+
+struct XdgForeign {
+    registry_state: RegistryState,
+    output_state: OutputState,
+    shm: Shm,
+    foreign: ForeignState,
+
+    exit: bool,
+    first_configure: bool,
+    pool: SlotPool,
+    width: u32,
+    height: u32,
+    exported: Option<ExportedToplevel>,
+    imported: Option<ImportedToplevel>,
+    window: Window,
+}
+
+impl XdgForeign {
+    fn draw(&mut self) {
+        let (width, height) = (self.width, self.height);
+        let (buffer, canvas) = self
+            .pool
+            .create_buffer(width as i32, height as i32, width as i32 * 4, wl_shm::Format::Argb8888)
+            .expect("create buffer");
+
+        // The parent is blue and the child is green.
+        let color: u32 = if self.imported.is_some() { 0xFF40A040 } else { 0xFF4060C0 };
+        canvas.chunks_exact_mut(4).for_each(|chunk| {
+            let array: &mut [u8; 4] = chunk.try_into().unwrap();
+            *array = color.to_le_bytes();
+        });
+
+        self.window.wl_surface().damage_buffer(0, 0, width as i32, height as i32);
+        buffer.attach_to(self.window.wl_surface()).expect("buffer attach");
+        self.window.commit();
+    }
+}
+
+impl ForeignHandler for XdgForeign {
+    fn exported(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _exported: &ExportedToplevel,
+        handle: String,
+    ) {
+        println!("Exported the window, open a child window with:");
+        println!("cargo run --example xdg_foreign -- {handle}");
+    }
+
+    fn imported_destroyed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        imported: &ImportedToplevel,
+    ) {
+        println!("The parent window {} is gone", imported.handle());
+        self.imported = None;
+    }
+}
+
+impl XdgShellHandler for XdgForeign {}
+
+impl WindowHandler for XdgForeign {
+    fn request_close(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &Window) {
+        self.exit = true;
+    }
+
+    fn configure(
+        &mut self,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+        _window: &Window,
+        configure: WindowConfigure,
+        _serial: u32,
+    ) {
+        self.width = configure.new_size.0.map(|w| w.get()).unwrap_or(256);
+        self.height = configure.new_size.1.map(|h| h.get()).unwrap_or(256);
+
+        // Only a mapped toplevel can be exported.
+        if self.first_configure && self.imported.is_none() {
+            self.exported = Some(
+                self.foreign
+                    .export_toplevel(self.window.wl_surface(), qh)
+                    .expect("xdg foreign exporter is not available"),
+            );
+        }
+
+        self.first_configure = false;
+        self.draw();
+    }
+}
+
+impl CompositorHandler for XdgForeign {
+    fn scale_factor_changed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _new_factor: i32,
+    ) {
+    }
+
+    fn transform_changed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _new_transform: wl_output::Transform,
+    ) {
+    }
+
+    fn frame(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        _time: u32,
+    ) {
+    }
+}
+
+impl OutputHandler for XdgForeign {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+
+    fn update_output(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+
+    fn output_destroyed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _output: wl_output::WlOutput,
+    ) {
+    }
+}
+
+impl ShmHandler for XdgForeign {
+    fn shm_state(&mut self) -> &mut Shm {
+        &mut self.shm
+    }
+}
+
+delegate_compositor!(XdgForeign);
+delegate_output!(XdgForeign);
+delegate_shm!(XdgForeign);
+
+delegate_xdg_shell!(XdgForeign);
+delegate_xdg_window!(XdgForeign);
+delegate_xdg_foreign!(XdgForeign);
+
+delegate_registry!(XdgForeign);
+
+impl ProvidesRegistryState for XdgForeign {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers![OutputState];
+}
@@ -87,6 +87,21 @@ impl ActivationState {
         Ok(ActivationState { xdg_activation })
     }
 
+    /// Take the activation token passed by the launcher of this application, if any.
+    ///
+    /// Launchers pass a token in the `XDG_ACTIVATION_TOKEN` environment variable, which may be used
+    /// once with [`Self::activate`] to focus the first window of the application. The variable is
+    /// removed from the environment so the token is not inherited by child processes.
+    pub fn token_from_env() -> Option<String> {
+        let token = std::env::var("XDG_ACTIVATION_TOKEN").ok()?;
+        std::env::remove_var("XDG_ACTIVATION_TOKEN");
+        if token.is_empty() {
+            None
+        } else {
+            Some(token)
+        }
+    }
+
     /// Activate a surface with the provided token.
     pub fn activate<D>(&self, surface: &wl_surface::WlSurface, token: String) {
         self.xdg_activation.activate(token, surface)
@@ -159,13 +174,15 @@ where
 {
     fn event(
         state: &mut D,
-        _proxy: &xdg_activation_token_v1::XdgActivationTokenV1,
+        proxy: &xdg_activation_token_v1::XdgActivationTokenV1,
         event: <xdg_activation_token_v1::XdgActivationTokenV1 as Proxy>::Event,
         data: &R,
         _conn: &wayland_client::Connection,
         _qhandle: &QueueHandle<D>,
     ) {
         if let xdg_activation_token_v1::Event::Done { token } = event {
+            // The token object is of no further use once the token has been issued.
+            proxy.destroy();
             state.new_token(token, data);
         }
     }
@@ -173,25 +190,25 @@ where
 
 #[macro_export]
 macro_rules! delegate_activation {
-   ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
-        $crate::reexports::client::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty:
+   ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
             [
                 $crate::reexports::protocols::xdg::activation::v1::client::xdg_activation_v1::XdgActivationV1: $crate::globals::GlobalData
             ] => $crate::activation::ActivationState
         );
-        $crate::reexports::client::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty:
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
             [
                 $crate::reexports::protocols::xdg::activation::v1::client::xdg_activation_token_v1::XdgActivationTokenV1: $crate::activation::RequestData
             ] => $crate::activation::ActivationState
         );
     };
-   ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty, $data: ty) => {
-        $crate::reexports::client::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty:
+   ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty, $data: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
             [
                 $crate::reexports::protocols::xdg::activation::v1::client::xdg_activation_v1::XdgActivationV1: $crate::globals::GlobalData
             ] => $crate::activation::ActivationState
         );
-        $crate::reexports::client::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty:
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
             [
                 $crate::reexports::protocols::xdg::activation::v1::client::xdg_activation_token_v1::XdgActivationTokenV1: $data
             ] => $crate::activation::ActivationState
@@ -0,0 +1,218 @@
+//! ## Alpha modifier
+//!
+//! The `wp_alpha_modifier_v1` global lets clients multiply the alpha channel of a whole surface, for
+//! example to fade out a window, without drawing new buffers.
+//!
+//! The multiplier is applied on top of the alpha channel of the buffer, so it also works for buffers
+//! in formats without alpha.
+
+use std::sync::{Arc, Weak};
+
+use wayland_client::{
+    globals::{BindError, GlobalList},
+    protocol::wl_surface,
+    Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols::wp::alpha_modifier::v1::client::{
+    wp_alpha_modifier_surface_v1, wp_alpha_modifier_v1,
+};
+
+use crate::{
+    compositor::SurfaceData,
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+};
+
+/// The alpha modifier global.
+#[derive(Debug)]
+pub struct AlphaModifierState {
+    alpha_modifier: wp_alpha_modifier_v1::WpAlphaModifierV1,
+}
+
+impl AlphaModifierState {
+    /// Binds the `wp_alpha_modifier_v1` global.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support the alpha modifier.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State: Dispatch<wp_alpha_modifier_v1::WpAlphaModifierV1, GlobalData> + 'static,
+    {
+        let alpha_modifier = globals.bind(qh, 1..=1, GlobalData)?;
+        Ok(Self { alpha_modifier })
+    }
+
+    /// Get the alpha modifier of a surface.
+    ///
+    /// If the surface was created with [`SurfaceData`], the alpha modifier is stored in it, and this
+    /// returns the same alpha modifier until all its clones are dropped.
+    ///
+    /// # Protocol errors
+    ///
+    /// Only one alpha modifier may exist for a surface at a time.
+    pub fn get_surface<D>(
+        &self,
+        surface: &wl_surface::WlSurface,
+        qh: &QueueHandle<D>,
+    ) -> AlphaModifierSurface
+    where
+        D: Dispatch<
+                wp_alpha_modifier_surface_v1::WpAlphaModifierSurfaceV1,
+                AlphaModifierSurfaceData,
+            > + 'static,
+    {
+        let surface_data = surface.data::<SurfaceData>();
+
+        if let Some(alpha_modifier) = surface_data
+            .and_then(SurfaceData::alpha_modifier)
+            .and_then(|alpha_modifier| AlphaModifierSurface::from_alpha_modifier(&alpha_modifier))
+        {
+            return alpha_modifier;
+        }
+
+        let inner = Arc::new_cyclic(|weak| {
+            let alpha_modifier = self.alpha_modifier.get_surface(
+                surface,
+                qh,
+                AlphaModifierSurfaceData { inner: weak.clone() },
+            );
+
+            AlphaModifierSurfaceInner { alpha_modifier, surface: surface.clone() }
+        });
+
+        if let Some(surface_data) = surface_data {
+            surface_data.set_alpha_modifier(Some(inner.alpha_modifier.clone()));
+        }
+
+        AlphaModifierSurface(inner)
+    }
+}
+
+impl ProvidesBoundGlobal<wp_alpha_modifier_v1::WpAlphaModifierV1, 1> for AlphaModifierState {
+    fn bound_global(&self) -> Result<wp_alpha_modifier_v1::WpAlphaModifierV1, GlobalError> {
+        Ok(self.alpha_modifier.clone())
+    }
+}
+
+#[derive(Debug)]
+struct AlphaModifierSurfaceInner {
+    alpha_modifier: wp_alpha_modifier_surface_v1::WpAlphaModifierSurfaceV1,
+    surface: wl_surface::WlSurface,
+}
+
+impl Drop for AlphaModifierSurfaceInner {
+    fn drop(&mut self) {
+        if let Some(surface_data) = self.surface.data::<SurfaceData>() {
+            surface_data.set_alpha_modifier(None);
+        }
+        self.alpha_modifier.destroy();
+    }
+}
+
+/// The alpha modifier of a surface.
+///
+/// The alpha modifier is destroyed when the last clone is dropped, which must happen before the
+/// surface is destroyed. The surface is then fully opaque again after its next commit.
+#[derive(Debug, Clone)]
+pub struct AlphaModifierSurface(Arc<AlphaModifierSurfaceInner>);
+
+impl AlphaModifierSurface {
+    pub fn from_alpha_modifier(
+        alpha_modifier: &wp_alpha_modifier_surface_v1::WpAlphaModifierSurfaceV1,
+    ) -> Option<Self> {
+        alpha_modifier
+            .data::<AlphaModifierSurfaceData>()
+            .and_then(|data| data.inner.upgrade())
+            .map(Self)
+    }
+
+    pub fn alpha_modifier(&self) -> &wp_alpha_modifier_surface_v1::WpAlphaModifierSurfaceV1 {
+        &self.0.alpha_modifier
+    }
+
+    /// The surface of this alpha modifier.
+    pub fn wl_surface(&self) -> &wl_surface::WlSurface {
+        &self.0.surface
+    }
+
+    /// Set the factor the alpha channel of the surface is multiplied by.
+    ///
+    /// The factor is clamped between `0.0`, fully transparent, and `1.0`, unchanged.
+    ///
+    /// This state is double buffered and applied on the next commit of the surface.
+    pub fn set_multiplier(&self, factor: f64) {
+        self.0.alpha_modifier.set_multiplier(multiplier_to_fixed(factor));
+    }
+
+    /// Leave the alpha channel of the surface unchanged, as if no alpha modifier existed.
+    ///
+    /// This state is double buffered and applied on the next commit of the surface.
+    pub fn unset(&self) {
+        self.0.alpha_modifier.set_multiplier(u32::MAX);
+    }
+}
+
+impl PartialEq for AlphaModifierSurface {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.alpha_modifier == other.0.alpha_modifier
+    }
+}
+
+/// Convert a factor to the protocol representation, where [`u32::MAX`] is `1.0`.
+fn multiplier_to_fixed(factor: f64) -> u32 {
+    // NaN is mapped to 0 by the conversion.
+    (factor.clamp(0.0, 1.0) * u32::MAX as f64).round() as u32
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct AlphaModifierSurfaceData {
+    inner: Weak<AlphaModifierSurfaceInner>,
+}
+
+impl<D> Dispatch<wp_alpha_modifier_v1::WpAlphaModifierV1, GlobalData, D> for AlphaModifierState
+where
+    D: Dispatch<wp_alpha_modifier_v1::WpAlphaModifierV1, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &wp_alpha_modifier_v1::WpAlphaModifierV1,
+        _: wp_alpha_modifier_v1::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("wp_alpha_modifier_v1 has no events")
+    }
+}
+
+impl<D>
+    Dispatch<wp_alpha_modifier_surface_v1::WpAlphaModifierSurfaceV1, AlphaModifierSurfaceData, D>
+    for AlphaModifierState
+where
+    D: Dispatch<wp_alpha_modifier_surface_v1::WpAlphaModifierSurfaceV1, AlphaModifierSurfaceData>,
+{
+    fn event(
+        _: &mut D,
+        _: &wp_alpha_modifier_surface_v1::WpAlphaModifierSurfaceV1,
+        _: wp_alpha_modifier_surface_v1::Event,
+        _: &AlphaModifierSurfaceData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("wp_alpha_modifier_surface_v1 has no events")
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_alpha_modifier {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::alpha_modifier::v1::client::wp_alpha_modifier_v1::WpAlphaModifierV1: $crate::globals::GlobalData
+        ] => $crate::alpha_modifier::AlphaModifierState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::alpha_modifier::v1::client::wp_alpha_modifier_surface_v1::WpAlphaModifierSurfaceV1: $crate::alpha_modifier::AlphaModifierSurfaceData
+        ] => $crate::alpha_modifier::AlphaModifierState);
+    };
+}
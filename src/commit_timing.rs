@@ -0,0 +1,214 @@
+//! ## Commit timing
+//!
+//! The `wp_commit_timing_manager_v1` global lets clients attach a target time to a commit. The
+//! compositor holds the commit back until the output refresh closest to that time, so a video can be
+//! shown at its own frame rate whatever the refresh rate of the output is.
+//!
+//! Target times use the clock of the presentation time protocol, given by
+//! [`PresentationState::clock_id`](crate::presentation_time::PresentationState::clock_id), and are
+//! best computed from the times reported by
+//! [`PresentationHandler::presented`](crate::presentation_time::PresentationHandler::presented):
+//! presentation feedback tells when the previous commits were actually shown and the refresh
+//! duration of the output, and commit timing tells when the next ones should be.
+//!
+//! Timed commits are usually combined with [`fifo`](crate::fifo) barriers, so that a queued commit
+//! is not replaced by a later one before it was shown.
+
+use std::sync::{Arc, Weak};
+
+use wayland_client::{
+    globals::{BindError, GlobalList},
+    protocol::wl_surface,
+    Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols::wp::commit_timing::v1::client::{
+    wp_commit_timer_v1, wp_commit_timing_manager_v1,
+};
+
+use crate::{
+    compositor::SurfaceData,
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+    presentation_time::PresentationTime,
+};
+
+/// The commit timing manager global.
+#[derive(Debug)]
+pub struct CommitTimingState {
+    manager: wp_commit_timing_manager_v1::WpCommitTimingManagerV1,
+}
+
+impl CommitTimingState {
+    /// Binds the `wp_commit_timing_manager_v1` global.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support commit timing.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State: Dispatch<wp_commit_timing_manager_v1::WpCommitTimingManagerV1, GlobalData> + 'static,
+    {
+        let manager = globals.bind(qh, 1..=1, GlobalData)?;
+        Ok(Self { manager })
+    }
+
+    /// Get the commit timer of a surface.
+    ///
+    /// If the surface was created with [`SurfaceData`], the timer is stored in it, and this returns
+    /// the same timer until all its clones are dropped.
+    ///
+    /// # Protocol errors
+    ///
+    /// Only one commit timer may exist for a surface at a time.
+    pub fn get_timer<D>(&self, surface: &wl_surface::WlSurface, qh: &QueueHandle<D>) -> CommitTimer
+    where
+        D: Dispatch<wp_commit_timer_v1::WpCommitTimerV1, CommitTimerData> + 'static,
+    {
+        let surface_data = surface.data::<SurfaceData>();
+
+        if let Some(timer) = surface_data
+            .and_then(SurfaceData::commit_timer)
+            .and_then(|timer| CommitTimer::from_commit_timer(&timer))
+        {
+            return timer;
+        }
+
+        let inner = Arc::new_cyclic(|weak| {
+            let timer =
+                self.manager.get_timer(surface, qh, CommitTimerData { inner: weak.clone() });
+            CommitTimerInner { timer, surface: surface.clone() }
+        });
+
+        if let Some(surface_data) = surface_data {
+            surface_data.set_commit_timer(Some(inner.timer.clone()));
+        }
+
+        CommitTimer(inner)
+    }
+}
+
+impl ProvidesBoundGlobal<wp_commit_timing_manager_v1::WpCommitTimingManagerV1, 1>
+    for CommitTimingState
+{
+    fn bound_global(
+        &self,
+    ) -> Result<wp_commit_timing_manager_v1::WpCommitTimingManagerV1, GlobalError> {
+        Ok(self.manager.clone())
+    }
+}
+
+#[derive(Debug)]
+struct CommitTimerInner {
+    timer: wp_commit_timer_v1::WpCommitTimerV1,
+    surface: wl_surface::WlSurface,
+}
+
+impl Drop for CommitTimerInner {
+    fn drop(&mut self) {
+        if let Some(surface_data) = self.surface.data::<SurfaceData>() {
+            surface_data.set_commit_timer(None);
+        }
+        self.timer.destroy();
+    }
+}
+
+/// The commit timer of a surface.
+///
+/// The timer is destroyed when the last clone is dropped, which should happen before the surface is
+/// destroyed, since the timer can no longer be used afterwards. Commits already made keep their
+/// target time.
+#[derive(Debug, Clone)]
+pub struct CommitTimer(Arc<CommitTimerInner>);
+
+impl CommitTimer {
+    pub fn from_commit_timer(timer: &wp_commit_timer_v1::WpCommitTimerV1) -> Option<Self> {
+        timer.data::<CommitTimerData>().and_then(|data| data.inner.upgrade()).map(Self)
+    }
+
+    pub fn commit_timer(&self) -> &wp_commit_timer_v1::WpCommitTimerV1 {
+        &self.0.timer
+    }
+
+    /// The surface of this commit timer.
+    pub fn wl_surface(&self) -> &wl_surface::WlSurface {
+        &self.0.surface
+    }
+
+    /// Set the target time of the next commit of the surface.
+    ///
+    /// The time must be in the clock of
+    /// [`PresentationState::clock_id`](crate::presentation_time::PresentationState::clock_id).
+    ///
+    /// # Protocol errors
+    ///
+    /// Only one target time may be set for a commit.
+    pub fn set_target_time(&self, time: PresentationTime) {
+        let since_epoch = time.since_epoch();
+        let secs = since_epoch.as_secs();
+        self.set_timestamp((secs >> 32) as u32, secs as u32, since_epoch.subsec_nanos());
+    }
+
+    /// Set the target time of the next commit of the surface, as a raw timestamp.
+    ///
+    /// This is [`CommitTimer::set_target_time`] with the seconds split in their high and low 32 bits.
+    pub fn set_timestamp(&self, tv_sec_hi: u32, tv_sec_lo: u32, tv_nsec: u32) {
+        self.0.timer.set_timestamp(tv_sec_hi, tv_sec_lo, tv_nsec);
+    }
+}
+
+impl PartialEq for CommitTimer {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.timer == other.0.timer
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct CommitTimerData {
+    inner: Weak<CommitTimerInner>,
+}
+
+impl<D> Dispatch<wp_commit_timing_manager_v1::WpCommitTimingManagerV1, GlobalData, D>
+    for CommitTimingState
+where
+    D: Dispatch<wp_commit_timing_manager_v1::WpCommitTimingManagerV1, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &wp_commit_timing_manager_v1::WpCommitTimingManagerV1,
+        _: wp_commit_timing_manager_v1::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("wp_commit_timing_manager_v1 has no events")
+    }
+}
+
+impl<D> Dispatch<wp_commit_timer_v1::WpCommitTimerV1, CommitTimerData, D> for CommitTimingState
+where
+    D: Dispatch<wp_commit_timer_v1::WpCommitTimerV1, CommitTimerData>,
+{
+    fn event(
+        _: &mut D,
+        _: &wp_commit_timer_v1::WpCommitTimerV1,
+        _: wp_commit_timer_v1::Event,
+        _: &CommitTimerData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("wp_commit_timer_v1 has no events")
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_commit_timing {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::commit_timing::v1::client::wp_commit_timing_manager_v1::WpCommitTimingManagerV1: $crate::globals::GlobalData
+        ] => $crate::commit_timing::CommitTimingState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::commit_timing::v1::client::wp_commit_timer_v1::WpCommitTimerV1: $crate::commit_timing::CommitTimerData
+        ] => $crate::commit_timing::CommitTimingState);
+    };
+}
@@ -7,6 +7,7 @@ use std::sync::{
 };
 
 use wayland_client::{
+    backend::ObjectId,
     globals::{BindError, GlobalList},
     protocol::{
         wl_callback, wl_compositor, wl_output, wl_region,
@@ -14,6 +15,14 @@ use wayland_client::{
     },
     Connection, Dispatch, Proxy, QueueHandle, WEnum,
 };
+use wayland_protocols::wp::{
+    alpha_modifier::v1::client::wp_alpha_modifier_surface_v1::WpAlphaModifierSurfaceV1,
+    commit_timing::v1::client::wp_commit_timer_v1::WpCommitTimerV1,
+    content_type::v1::client::wp_content_type_v1::WpContentTypeV1,
+    fifo::v1::client::wp_fifo_v1::WpFifoV1,
+    tearing_control::v1::client::wp_tearing_control_v1::WpTearingControlV1,
+    viewporter::client::wp_viewport::WpViewport,
+};
 
 use crate::{
     error::GlobalError,
@@ -23,6 +32,9 @@ use crate::{
 
 pub trait CompositorHandler: Sized {
     /// The surface has either been moved into or out of an output and the output has a different scale factor.
+    ///
+    /// With `wl_surface` version 6 or later, this is instead called when the compositor sends a
+    /// different preferred buffer scale for the surface.
     fn scale_factor_changed(
         &mut self,
         conn: &Connection,
@@ -32,6 +44,9 @@ pub trait CompositorHandler: Sized {
     );
 
     /// The surface has either been moved into or out of an output and the output has different transform.
+    ///
+    /// With `wl_surface` version 6 or later, this is instead called when the compositor sends a
+    /// different preferred buffer transform for the surface.
     fn transform_changed(
         &mut self,
         conn: &Connection,
@@ -47,9 +62,11 @@ pub trait CompositorHandler: Sized {
     /// to that surface until the callback completes.  See the
     /// [`WlSurface::frame`](wl_surface::WlSurface::frame) request for more details.
     ///
-    /// This function will be called if you request a frame callback by passing the surface itself
-    /// as the userdata (`surface.frame(&queue, &surface)`); you can also implement [`Dispatch`]
-    /// for other values to more easily dispatch rendering for specific surface types.
+    /// This function will be called if you request a frame callback with
+    /// [`WaylandSurface::request_frame`](crate::shell::WaylandSurface::request_frame), or by passing
+    /// the surface itself as the userdata (`surface.frame(&queue, &surface)`); you can also
+    /// implement [`Dispatch`] for other values to more easily dispatch rendering for specific
+    /// surface types.
     fn frame(
         &mut self,
         conn: &Connection,
@@ -57,6 +74,41 @@ pub trait CompositorHandler: Sized {
         surface: &wl_surface::WlSurface,
         time: u32,
     );
+
+    /// The surface has entered an output.
+    ///
+    /// This is called after the output is added to [`SurfaceData::outputs`], and before
+    /// [`scale_factor_changed`](Self::scale_factor_changed) and
+    /// [`transform_changed`](Self::transform_changed) if entering the output changes the scale
+    /// factor or transform of the surface.
+    fn surface_enter(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        surface: &wl_surface::WlSurface,
+        output: &wl_output::WlOutput,
+    ) {
+        let _ = (conn, qh, surface, output);
+    }
+
+    /// The surface has left an output.
+    ///
+    /// This is also called when the global of an output the surface is inside is removed, before
+    /// [`OutputHandler::output_destroyed`].
+    ///
+    /// This is called after the output is removed from [`SurfaceData::outputs`], and before
+    /// [`scale_factor_changed`](Self::scale_factor_changed) and
+    /// [`transform_changed`](Self::transform_changed) if leaving the output changes the scale
+    /// factor or transform of the surface.
+    fn surface_leave(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        surface: &wl_surface::WlSurface,
+        output: &wl_output::WlOutput,
+    ) {
+        let _ = (conn, qh, surface, output);
+    }
 }
 
 pub trait SurfaceDataExt: Send + Sync {
@@ -114,6 +166,11 @@ impl CompositorState {
     {
         self.wl_compositor.create_surface(qh, data)
     }
+
+    /// Create an empty region, see [`Region`].
+    pub fn create_region(&self) -> Region {
+        Region::new(self).expect("wl_compositor is always bound")
+    }
 }
 
 /// Data associated with a [`WlSurface`](wl_surface::WlSurface).
@@ -122,11 +179,6 @@ pub struct SurfaceData {
     /// The scale factor of the output with the highest scale factor.
     pub(crate) scale_factor: AtomicI32,
 
-    /// Parent surface used when creating subsurfaces.
-    ///
-    /// For top-level surfaces this is always `None`.
-    pub(crate) parent_surface: Option<WlSurface>,
-
     /// The inner mutable storage.
     inner: Mutex<SurfaceDataInner>,
 }
@@ -136,12 +188,14 @@ impl SurfaceData {
     pub fn new(parent_surface: Option<WlSurface>, scale_factor: i32) -> Self {
         Self {
             scale_factor: AtomicI32::new(scale_factor),
-            parent_surface,
-            inner: Default::default(),
+            inner: Mutex::new(SurfaceDataInner { parent_surface, ..Default::default() }),
         }
     }
 
     /// The scale factor of the output with the highest scale factor.
+    ///
+    /// With `wl_surface` version 6 or later, this is the scale preferred by the compositor
+    /// instead. [`CompositorHandler::scale_factor_changed`] is only called when this changes.
     pub fn scale_factor(&self) -> i32 {
         self.scale_factor.load(Ordering::Relaxed)
     }
@@ -151,18 +205,214 @@ impl SurfaceData {
         self.inner.lock().unwrap().transform
     }
 
+    /// The buffer scale set with
+    /// [`WaylandSurface::set_buffer_scale`](crate::shell::WaylandSurface::set_buffer_scale).
+    pub fn buffer_scale(&self) -> i32 {
+        self.inner.lock().unwrap().buffer_scale
+    }
+
+    pub(crate) fn set_buffer_scale(&self, scale: i32) {
+        self.inner.lock().unwrap().buffer_scale = scale;
+    }
+
+    /// The buffer transform set with
+    /// [`WaylandSurface::set_buffer_transform`](crate::shell::WaylandSurface::set_buffer_transform).
+    pub fn buffer_transform(&self) -> wl_output::Transform {
+        self.inner.lock().unwrap().buffer_transform
+    }
+
+    pub(crate) fn set_buffer_transform(&self, transform: wl_output::Transform) {
+        self.inner.lock().unwrap().buffer_transform = transform;
+    }
+
+    /// The buffer scale preferred by the compositor for this surface.
+    ///
+    /// This is [`None`] until the compositor sends one, which requires `wl_surface` version 6.
+    /// Once it is sent, it is used for [`scale_factor`](Self::scale_factor) instead of the scale
+    /// derived from the outputs of the surface.
+    pub fn preferred_buffer_scale(&self) -> Option<i32> {
+        self.inner.lock().unwrap().preferred_buffer_scale
+    }
+
+    /// The buffer transform preferred by the compositor for this surface.
+    ///
+    /// This is [`None`] until the compositor sends one, which requires `wl_surface` version 6.
+    /// Once it is sent, it is used for [`transform`](Self::transform) instead of the transform
+    /// derived from the outputs of the surface.
+    pub fn preferred_buffer_transform(&self) -> Option<wl_output::Transform> {
+        self.inner.lock().unwrap().preferred_buffer_transform
+    }
+
+    /// Whether a frame callback was requested with
+    /// [`WaylandSurface::request_frame`](crate::shell::WaylandSurface::request_frame) since the last
+    /// [`WaylandSurface::commit`](crate::shell::WaylandSurface::commit), and has not fired yet.
+    pub fn has_pending_frame(&self) -> bool {
+        self.inner.lock().unwrap().frame_callback.is_some()
+    }
+
+    /// Requests a frame callback, unless one was already requested for the next commit.
+    pub(crate) fn request_frame<D>(
+        &self,
+        surface: &wl_surface::WlSurface,
+        qh: &QueueHandle<D>,
+    ) -> bool
+    where
+        D: Dispatch<wl_callback::WlCallback, wl_surface::WlSurface> + 'static,
+    {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.frame_callback.is_some() {
+            return false;
+        }
+
+        inner.frame_callback = Some(surface.frame(qh, surface.clone()));
+        true
+    }
+
+    /// Forgets the frame callback requested for a commit, so the next commit can get its own.
+    pub(crate) fn frame_committed(&self) {
+        self.inner.lock().unwrap().frame_callback = None;
+    }
+
     /// The parent surface used for this surface.
     ///
     /// The surface is `Some` for primarily for subsurfaces,
     /// since they must have a parent surface.
-    pub fn parent_surface(&self) -> Option<&WlSurface> {
-        self.parent_surface.as_ref()
+    pub fn parent_surface(&self) -> Option<WlSurface> {
+        self.inner.lock().unwrap().parent_surface.clone()
+    }
+
+    pub(crate) fn set_parent_surface(&self, parent_surface: Option<WlSurface>) {
+        self.inner.lock().unwrap().parent_surface = parent_surface;
+    }
+
+    /// Track a subsurface of this surface, so it follows the scale factor and transform of this
+    /// surface.
+    pub(crate) fn add_child(&self, child: &WlSurface) {
+        self.inner.lock().unwrap().children.push(child.id());
+    }
+
+    pub(crate) fn remove_child(&self, child: &WlSurface) {
+        self.inner.lock().unwrap().children.retain(|id| id != &child.id());
     }
 
     /// The outputs the surface is currently inside.
+    ///
+    /// Outputs are removed when the compositor sends a leave event, or when their global is
+    /// removed.
     pub fn outputs(&self) -> impl Iterator<Item = wl_output::WlOutput> {
         self.inner.lock().unwrap().outputs.clone().into_iter()
     }
+
+    /// The latest fractional scale preferred by the compositor for this surface.
+    ///
+    /// This is [`None`] unless the scale is requested with
+    /// [`FractionalScaleState`](crate::fractional_scale::FractionalScaleState).
+    pub fn fractional_scale(&self) -> Option<f64> {
+        self.inner.lock().unwrap().fractional_scale
+    }
+
+    pub(crate) fn set_fractional_scale(&self, scale: f64) {
+        self.inner.lock().unwrap().fractional_scale = Some(scale);
+    }
+
+    /// The viewport of this surface.
+    ///
+    /// This is [`None`] unless a viewport was created with
+    /// [`ViewporterState::get_viewport`](crate::viewporter::ViewporterState::get_viewport) and is still
+    /// alive.
+    pub fn viewport(&self) -> Option<WpViewport> {
+        self.inner.lock().unwrap().viewport.clone()
+    }
+
+    pub(crate) fn set_viewport(&self, viewport: Option<WpViewport>) {
+        self.inner.lock().unwrap().viewport = viewport;
+    }
+
+    /// The tearing control of this surface.
+    ///
+    /// This is [`None`] unless a tearing control was created with
+    /// [`TearingControlState::get_tearing_control`](crate::tearing_control::TearingControlState::get_tearing_control)
+    /// and is still alive.
+    pub fn tearing_control(&self) -> Option<WpTearingControlV1> {
+        self.inner.lock().unwrap().tearing_control.clone()
+    }
+
+    pub(crate) fn set_tearing_control(&self, tearing_control: Option<WpTearingControlV1>) {
+        self.inner.lock().unwrap().tearing_control = tearing_control;
+    }
+
+    /// The content type object of this surface.
+    ///
+    /// This is [`None`] unless one was created with
+    /// [`ContentTypeState::get_surface_content_type`](crate::content_type::ContentTypeState::get_surface_content_type)
+    /// and is still alive.
+    pub fn content_type(&self) -> Option<WpContentTypeV1> {
+        self.inner.lock().unwrap().content_type.clone()
+    }
+
+    pub(crate) fn set_content_type(&self, content_type: Option<WpContentTypeV1>) {
+        self.inner.lock().unwrap().content_type = content_type;
+    }
+
+    /// The alpha modifier of this surface.
+    ///
+    /// This is [`None`] unless an alpha modifier was created with
+    /// [`AlphaModifierState::get_surface`](crate::alpha_modifier::AlphaModifierState::get_surface)
+    /// and is still alive.
+    pub fn alpha_modifier(&self) -> Option<WpAlphaModifierSurfaceV1> {
+        self.inner.lock().unwrap().alpha_modifier.clone()
+    }
+
+    pub(crate) fn set_alpha_modifier(&self, alpha_modifier: Option<WpAlphaModifierSurfaceV1>) {
+        self.inner.lock().unwrap().alpha_modifier = alpha_modifier;
+    }
+
+    /// The FIFO object of this surface.
+    ///
+    /// This is [`None`] unless one was created with
+    /// [`FifoState::get_fifo`](crate::fifo::FifoState::get_fifo) and is still alive.
+    pub fn fifo(&self) -> Option<WpFifoV1> {
+        self.inner.lock().unwrap().fifo.clone()
+    }
+
+    pub(crate) fn set_fifo(&self, fifo: Option<WpFifoV1>) {
+        self.inner.lock().unwrap().fifo = fifo;
+    }
+
+    /// The commit timer of this surface.
+    ///
+    /// This is [`None`] unless a commit timer was created with
+    /// [`CommitTimingState::get_timer`](crate::commit_timing::CommitTimingState::get_timer) and is
+    /// still alive.
+    pub fn commit_timer(&self) -> Option<WpCommitTimerV1> {
+        self.inner.lock().unwrap().commit_timer.clone()
+    }
+
+    pub(crate) fn set_commit_timer(&self, commit_timer: Option<WpCommitTimerV1>) {
+        self.inner.lock().unwrap().commit_timer = commit_timer;
+    }
+
+    /// Convert surface-local coordinates, as in pointer, touch and drag and drop events, to buffer
+    /// coordinates.
+    ///
+    /// This assumes the buffers of the surface are drawn at [`SurfaceData::fractional_scale`] if it is
+    /// known, or at [`SurfaceData::scale_factor`] otherwise. The buffer transform is not applied.
+    pub fn to_buffer_coordinates(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        let scale = self.coordinate_scale();
+        (x * scale, y * scale)
+    }
+
+    /// Convert buffer coordinates to surface-local coordinates.
+    ///
+    /// This is the inverse of [`SurfaceData::to_buffer_coordinates`].
+    pub fn to_surface_coordinates(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        let scale = self.coordinate_scale();
+        (x / scale, y / scale)
+    }
+
+    fn coordinate_scale(&self) -> f64 {
+        self.fractional_scale().unwrap_or_else(|| self.scale_factor() as f64)
+    }
 }
 
 impl Default for SurfaceData {
@@ -173,19 +423,83 @@ impl Default for SurfaceData {
 
 #[derive(Debug)]
 struct SurfaceDataInner {
+    /// Parent surface used when creating subsurfaces.
+    ///
+    /// For top-level surfaces this is always `None`.
+    parent_surface: Option<WlSurface>,
+
+    /// The subsurfaces of this surface.
+    ///
+    /// Only their ids are stored, since the children keep a handle to their parent.
+    children: Vec<ObjectId>,
+
     /// The transform of the given surface.
     transform: wl_output::Transform,
 
+    /// The buffer scale preferred by the compositor.
+    preferred_buffer_scale: Option<i32>,
+
+    /// The buffer scale set by the client.
+    buffer_scale: i32,
+
+    /// The buffer transform set by the client.
+    buffer_transform: wl_output::Transform,
+
+    /// The buffer transform preferred by the compositor.
+    preferred_buffer_transform: Option<wl_output::Transform>,
+
     /// The outputs the surface is currently inside.
     outputs: Vec<wl_output::WlOutput>,
 
+    /// The frame callback requested for the next commit, until the commit or until it fires.
+    frame_callback: Option<wl_callback::WlCallback>,
+
     /// A handle to the OutputInfo callback that dispatches scale updates.
     watcher: Option<ScaleWatcherHandle>,
+
+    /// The latest preferred fractional scale.
+    fractional_scale: Option<f64>,
+
+    /// The viewport of the surface.
+    viewport: Option<WpViewport>,
+
+    /// The tearing control of the surface.
+    tearing_control: Option<WpTearingControlV1>,
+
+    /// The content type object of the surface.
+    content_type: Option<WpContentTypeV1>,
+
+    /// The alpha modifier of the surface.
+    alpha_modifier: Option<WpAlphaModifierSurfaceV1>,
+
+    /// The FIFO object of the surface.
+    fifo: Option<WpFifoV1>,
+
+    /// The commit timer of the surface.
+    commit_timer: Option<WpCommitTimerV1>,
 }
 
 impl Default for SurfaceDataInner {
     fn default() -> Self {
-        Self { transform: wl_output::Transform::Normal, outputs: Vec::new(), watcher: None }
+        Self {
+            parent_surface: None,
+            children: Vec::new(),
+            transform: wl_output::Transform::Normal,
+            preferred_buffer_scale: None,
+            preferred_buffer_transform: None,
+            buffer_scale: 1,
+            buffer_transform: wl_output::Transform::Normal,
+            outputs: Vec::new(),
+            frame_callback: None,
+            watcher: None,
+            fractional_scale: None,
+            viewport: None,
+            tearing_control: None,
+            content_type: None,
+            alpha_modifier: None,
+            fifo: None,
+            commit_timer: None,
+        }
     }
 }
 
@@ -243,27 +557,27 @@ impl Drop for Surface {
 
 #[macro_export]
 macro_rules! delegate_compositor {
-    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
-        $crate::delegate_compositor!(@{ $(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty }; surface: []);
-        $crate::delegate_compositor!(@{ $(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty }; surface-only: $crate::compositor::SurfaceData);
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::delegate_compositor!(@{ $(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })? }; surface: []);
+        $crate::delegate_compositor!(@{ $(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })? }; surface-only: $crate::compositor::SurfaceData);
     };
-    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty, surface: [$($surface: ty),*$(,)?]) => {
-        $crate::delegate_compositor!(@{ $(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty }; surface: [ $($surface),* ]);
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty, surface: [$($surface: ty),*$(,)?] $(where $($bound:tt)+)?) => {
+        $crate::delegate_compositor!(@{ $(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })? }; surface: [ $($surface),* ]);
     };
     (@{$($ty:tt)*}; surface: []) => {
-        $crate::reexports::client::delegate_dispatch!($($ty)*:
+        $crate::__delegate_dispatch!($($ty)*:
             [
                 $crate::reexports::client::protocol::wl_compositor::WlCompositor: $crate::globals::GlobalData
             ] => $crate::compositor::CompositorState
         );
-        $crate::reexports::client::delegate_dispatch!($($ty)*:
+        $crate::__delegate_dispatch!($($ty)*:
             [
                 $crate::reexports::client::protocol::wl_callback::WlCallback: $crate::reexports::client::protocol::wl_surface::WlSurface
             ] => $crate::compositor::CompositorState
         );
     };
     (@{$($ty:tt)*}; surface-only: $surface:ty) => {
-        $crate::reexports::client::delegate_dispatch!($($ty)*:
+        $crate::__delegate_dispatch!($($ty)*:
             [
                     $crate::reexports::client::protocol::wl_surface::WlSurface: $surface
             ] => $crate::compositor::CompositorState
@@ -295,27 +609,41 @@ where
 
         match event {
             wl_surface::Event::Enter { output } => {
-                inner.outputs.push(output);
+                if inner.outputs.contains(&output) {
+                    return;
+                }
+                inner.outputs.push(output.clone());
+                drop(inner);
+                state.surface_enter(conn, qh, surface, &output);
             }
             wl_surface::Event::Leave { output } => {
+                let count = inner.outputs.len();
                 inner.outputs.retain(|o| o != &output);
+                if inner.outputs.len() == count {
+                    return;
+                }
+                drop(inner);
+                state.surface_leave(conn, qh, surface, &output);
             }
             wl_surface::Event::PreferredBufferScale { factor } => {
-                let current_scale = data.scale_factor.load(Ordering::Relaxed);
+                inner.preferred_buffer_scale = Some(factor);
                 drop(inner);
-                data.scale_factor.store(factor, Ordering::Relaxed);
+                let current_scale = data.scale_factor.swap(factor, Ordering::Relaxed);
                 if current_scale != factor {
                     state.scale_factor_changed(conn, qh, surface, factor);
+                    update_children::<D, U>(state, conn, qh, data, Some(factor), None);
                 }
                 return;
             }
             wl_surface::Event::PreferredBufferTransform { transform } => {
                 // Only handle known values.
                 if let WEnum::Value(transform) = transform {
+                    inner.preferred_buffer_transform = Some(transform);
                     let old_transform = std::mem::replace(&mut inner.transform, transform);
                     drop(inner);
                     if old_transform != transform {
                         state.transform_changed(conn, qh, surface, transform);
+                        update_children::<D, U>(state, conn, qh, data, None, Some(transform));
                     }
                 }
                 return;
@@ -323,28 +651,44 @@ where
             _ => unreachable!(),
         }
 
-        // NOTE: with v6 we don't need any special handling of the scale factor, everything
-        // was handled from the above, so return.
-        if surface.version() >= 6 {
-            return;
-        }
-
+        let mut inner = data.inner.lock().unwrap();
         inner.watcher.get_or_insert_with(|| {
             // Avoid storing the WlSurface inside the closure as that would create a reference
             // cycle.  Instead, store the ID and re-create the proxy.
             let id = surface.id();
-            OutputState::add_scale_watcher(state, move |state, conn, qh, _| {
+            OutputState::add_scale_watcher(state, move |state, conn, qh, output| {
                 let id = id.clone();
                 if let Ok(surface) = wl_surface::WlSurface::from_id(conn, id) {
                     if let Some(data) = surface.data::<U>() {
                         let data = data.surface_data();
-                        let inner = data.inner.lock().unwrap();
-                        dispatch_surface_state_updates(state, conn, qh, &surface, data, inner);
+                        let mut inner = data.inner.lock().unwrap();
+
+                        // The compositor does not send a leave event for an output whose global
+                        // was removed, so forget about it here.
+                        if inner.outputs.contains(output)
+                            && !state.output_state().outputs().any(|o| &o == output)
+                        {
+                            inner.outputs.retain(|o| o != output);
+                            drop(inner);
+                            state.surface_leave(conn, qh, &surface, output);
+                            inner = data.inner.lock().unwrap();
+                        }
+
+                        // NOTE: with v6 the scale factor and transform are sent by the compositor.
+                        if surface.version() < 6 {
+                            dispatch_surface_state_updates(state, conn, qh, &surface, data, inner);
+                        }
                     }
                 }
             })
         });
 
+        // NOTE: with v6 we don't need any special handling of the scale factor, everything
+        // was handled from the above, so return.
+        if surface.version() >= 6 {
+            return;
+        }
+
         dispatch_surface_state_updates(state, conn, qh, surface, data, inner);
     }
 }
@@ -378,6 +722,10 @@ fn dispatch_surface_state_updates<D, U>(
         Some(props) => props,
     };
 
+    // The values preferred by the compositor win over the ones derived from the outputs.
+    let factor = inner.preferred_buffer_scale.unwrap_or(factor);
+    let transform = inner.preferred_buffer_transform.unwrap_or(transform);
+
     data.scale_factor.store(factor, Ordering::Relaxed);
     let old_transform = mem::replace(&mut inner.transform, transform);
     // Drop the mutex before we send of any events.
@@ -390,10 +738,101 @@ fn dispatch_surface_state_updates<D, U>(
     if transform != old_transform {
         state.transform_changed(conn, qh, surface, transform);
     }
+
+    update_children::<D, U>(
+        state,
+        conn,
+        qh,
+        data,
+        Some(factor).filter(|&factor| factor != current_scale),
+        Some(transform).filter(|&transform| transform != old_transform),
+    );
+}
+
+/// Apply a new scale factor or transform of a surface to its subsurfaces.
+///
+/// The compositor may not send these to a subsurface, for example before it is mapped, so the
+/// subsurfaces follow their parent until they get their own.
+fn update_children<D, U>(
+    state: &mut D,
+    conn: &Connection,
+    qh: &QueueHandle<D>,
+    data: &SurfaceData,
+    scale_factor: Option<i32>,
+    transform: Option<wl_output::Transform>,
+) where
+    D: Dispatch<wl_surface::WlSurface, U> + CompositorHandler + OutputHandler + 'static,
+    U: SurfaceDataExt + 'static,
+{
+    if scale_factor.is_none() && transform.is_none() {
+        return;
+    }
+
+    let children = {
+        let mut inner = data.inner.lock().unwrap();
+        // Forget the subsurfaces that were destroyed.
+        inner.children.retain(|id| wl_surface::WlSurface::from_id(conn, id.clone()).is_ok());
+        inner.children.clone()
+    };
+
+    for id in children {
+        let Ok(child) = wl_surface::WlSurface::from_id(conn, id) else { continue };
+        let Some(child_data) = child.data::<U>() else { continue };
+        let child_data = child_data.surface_data();
+
+        // Children with their own preferred values keep them.
+        let (scale_factor, transform) = {
+            let mut child_inner = child_data.inner.lock().unwrap();
+            let scale_factor = scale_factor.filter(|&factor| {
+                child_inner.preferred_buffer_scale.is_none()
+                    && child_data.scale_factor.swap(factor, Ordering::Relaxed) != factor
+            });
+            let transform = transform.filter(|&transform| {
+                child_inner.preferred_buffer_transform.is_none()
+                    && mem::replace(&mut child_inner.transform, transform) != transform
+            });
+            (scale_factor, transform)
+        };
+
+        if let Some(factor) = scale_factor {
+            state.scale_factor_changed(conn, qh, &child, factor);
+        }
+        if let Some(transform) = transform {
+            state.transform_changed(conn, qh, &child, transform);
+        }
+
+        update_children::<D, U>(state, conn, qh, child_data, scale_factor, transform);
+    }
+}
+
+/// A rectangle, in surface coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rect {
+    pub fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self { x, y, width, height }
+    }
+}
+
+impl From<(i32, i32, i32, i32)> for Rect {
+    fn from((x, y, width, height): (i32, i32, i32, i32)) -> Self {
+        Self { x, y, width, height }
+    }
 }
 
 /// A trivial wrapper around a [`WlRegion`][wl_region::WlRegion].
 ///
+/// Regions are used to set the input and opaque regions of a surface, with
+/// [`WaylandSurface::set_input_region`](crate::shell::WaylandSurface::set_input_region) and
+/// [`WaylandSurface::set_opaque_region`](crate::shell::WaylandSurface::set_opaque_region). The surface
+/// copies the region when it is set, so the region may be dropped right away.
+///
 /// This destroys the region on drop.
 #[derive(Debug)]
 pub struct Region(wl_region::WlRegion);
@@ -414,6 +853,21 @@ impl Region {
             .map(Region)
     }
 
+    /// Create a region covering the union of the given rectangles.
+    pub fn from_rects(
+        compositor: &impl ProvidesBoundGlobal<
+            wl_compositor::WlCompositor,
+            { CompositorState::API_VERSION_MAX },
+        >,
+        rects: impl IntoIterator<Item = impl Into<Rect>>,
+    ) -> Result<Region, GlobalError> {
+        let region = Region::new(compositor)?;
+        for rect in rects {
+            region.add_rect(rect.into());
+        }
+        Ok(region)
+    }
+
     pub fn add(&self, x: i32, y: i32, width: i32, height: i32) {
         self.0.add(x, y, width, height)
     }
@@ -422,6 +876,28 @@ impl Region {
         self.0.subtract(x, y, width, height)
     }
 
+    /// Add a rectangle to the region.
+    pub fn add_rect(&self, rect: Rect) {
+        self.add(rect.x, rect.y, rect.width, rect.height)
+    }
+
+    /// Remove a rectangle from the region.
+    pub fn subtract_rect(&self, rect: Rect) {
+        self.subtract(rect.x, rect.y, rect.width, rect.height)
+    }
+
+    /// Add a rectangle to the region, for chaining.
+    pub fn with_rect(self, rect: impl Into<Rect>) -> Self {
+        self.add_rect(rect.into());
+        self
+    }
+
+    /// Remove a rectangle from the region, for chaining.
+    pub fn without_rect(self, rect: impl Into<Rect>) -> Self {
+        self.subtract_rect(rect.into());
+        self
+    }
+
     pub fn wl_region(&self) -> &wl_region::WlRegion {
         &self.0
     }
@@ -476,7 +952,7 @@ where
 {
     fn event(
         state: &mut D,
-        _: &wl_callback::WlCallback,
+        callback: &wl_callback::WlCallback,
         event: wl_callback::Event,
         surface: &wl_surface::WlSurface,
         conn: &Connection,
@@ -484,6 +960,14 @@ where
     ) {
         match event {
             wl_callback::Event::Done { callback_data } => {
+                // Clear the pending callback first, so a new one can be requested from the handler.
+                // It is still set if the surface was committed without `WaylandSurface::commit`.
+                if let Some(data) = surface.data::<SurfaceData>() {
+                    let mut inner = data.inner.lock().unwrap();
+                    if inner.frame_callback.as_ref() == Some(callback) {
+                        inner.frame_callback = None;
+                    }
+                }
                 state.frame(conn, qh, surface, callback_data);
             }
 
@@ -491,3 +975,129 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use wayland_backend::{protocol::Argument, server::ObjectId};
+    use wayland_client::{
+        protocol::{wl_compositor::WlCompositor, wl_output::WlOutput, wl_surface::WlSurface},
+        Proxy,
+    };
+
+    use super::SurfaceData;
+    use crate::testing::{Call, TestClient, TestServer};
+
+    /// A client with a surface and the server side of two outputs, of scale 1 and 2.
+    fn connect(compositor_version: u32) -> (TestServer, TestClient, WlSurface, Vec<ObjectId>) {
+        let mut server = TestServer::new();
+        server.create_global(WlCompositor::interface(), compositor_version);
+        server.create_global(WlOutput::interface(), 4);
+        server.create_global(WlOutput::interface(), 4);
+        let mut client = server.connect();
+        client.roundtrip();
+
+        let outputs = server.objects("wl_output");
+        server.send_output_info(&outputs[0], (0, 0), (1920, 1080), 1);
+        server.send_output_info(&outputs[1], (1920, 0), (3840, 2160), 2);
+        let surface = client.create_surface();
+        client.state.take_calls();
+        (server, client, surface, outputs)
+    }
+
+    /// Sends `wl_surface.enter` or `wl_surface.leave` and returns the resulting calls.
+    fn send(
+        server: &mut TestServer,
+        client: &mut TestClient,
+        surface: &WlSurface,
+        event: &str,
+        output: &ObjectId,
+    ) -> Vec<Call> {
+        server.send(&server.object(surface), event, vec![Argument::Object(output.clone())]);
+        client.roundtrip();
+        client.state.take_calls()
+    }
+
+    #[test]
+    fn enter_leave() {
+        let (mut server, mut client, surface, outputs) = connect(5);
+        let data = surface.data::<SurfaceData>().unwrap();
+
+        let calls = send(&mut server, &mut client, &surface, "enter", &outputs[0]);
+        assert!(matches!(calls[..], [Call::SurfaceEnter(..)]));
+        // The scale factor changes after the surface entered the output.
+        let calls = send(&mut server, &mut client, &surface, "enter", &outputs[1]);
+        assert!(matches!(calls[..], [Call::SurfaceEnter(..), Call::ScaleFactorChanged(_, 2)]));
+        assert_eq!(data.outputs().count(), 2);
+
+        // Entering an output twice is ignored.
+        assert!(send(&mut server, &mut client, &surface, "enter", &outputs[1]).is_empty());
+        assert_eq!(data.outputs().count(), 2);
+
+        let calls = send(&mut server, &mut client, &surface, "leave", &outputs[1]);
+        match &calls[..] {
+            [Call::SurfaceLeave(leave_surface, output), Call::ScaleFactorChanged(_, 1)] => {
+                assert_eq!(leave_surface, &surface);
+                assert_eq!(server.object(output), outputs[1]);
+            }
+            calls => panic!("unexpected calls {:?}", calls),
+        }
+        // Leaving an output the surface is not on is ignored.
+        assert!(send(&mut server, &mut client, &surface, "leave", &outputs[1]).is_empty());
+
+        // The last scale factor is kept once the surface is on no output.
+        let calls = send(&mut server, &mut client, &surface, "leave", &outputs[0]);
+        assert!(matches!(calls[..], [Call::SurfaceLeave(..)]));
+        assert_eq!(data.outputs().count(), 0);
+        assert_eq!(data.scale_factor(), 1);
+    }
+
+    #[test]
+    fn enter_leave_preferred_scale() {
+        // From version 6 the scale factor is only changed by the compositor.
+        let (mut server, mut client, surface, outputs) = connect(6);
+
+        let calls = send(&mut server, &mut client, &surface, "enter", &outputs[1]);
+        assert!(matches!(calls[..], [Call::SurfaceEnter(..)]));
+
+        server.send(&server.object(&surface), "preferred_buffer_scale", vec![Argument::Int(2)]);
+        client.roundtrip();
+        assert!(matches!(client.state.take_calls()[..], [Call::ScaleFactorChanged(_, 2)]));
+
+        let calls = send(&mut server, &mut client, &surface, "leave", &outputs[1]);
+        assert!(matches!(calls[..], [Call::SurfaceLeave(..)]));
+        assert_eq!(surface.data::<SurfaceData>().unwrap().scale_factor(), 2);
+    }
+
+    #[test]
+    fn buffer_coordinates_integer_scale() {
+        let data = SurfaceData::new(None, 1);
+        assert_eq!(data.to_buffer_coordinates((10.5, 20.25)), (10.5, 20.25));
+        assert_eq!(data.to_surface_coordinates((10.5, 20.25)), (10.5, 20.25));
+
+        let data = SurfaceData::new(None, 2);
+        assert_eq!(data.to_buffer_coordinates((10.5, 20.25)), (21., 40.5));
+        assert_eq!(data.to_surface_coordinates((21., 40.5)), (10.5, 20.25));
+    }
+
+    #[test]
+    fn buffer_coordinates_fractional_scale() {
+        // The fractional scale is used instead of the integer scale once it is known.
+        let data = SurfaceData::new(None, 2);
+        data.set_fractional_scale(1.25);
+        assert_eq!(data.to_buffer_coordinates((10., 20.)), (12.5, 25.));
+        assert_eq!(data.to_surface_coordinates((12.5, 25.)), (10., 20.));
+        assert_eq!(data.to_buffer_coordinates((-8., 0.)), (-10., 0.));
+    }
+
+    #[test]
+    fn buffer_coordinates_roundtrip() {
+        for scale in [1., 2., 1.25] {
+            let data = SurfaceData::new(None, 1);
+            data.set_fractional_scale(scale);
+            for position in [(0., 0.), (0.5, 3.75), (799.25, 451.5)] {
+                let buffer = data.to_buffer_coordinates(position);
+                assert_eq!(data.to_surface_coordinates(buffer), position);
+            }
+        }
+    }
+}
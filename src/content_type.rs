@@ -0,0 +1,227 @@
+//! ## Content type
+//!
+//! The `wp_content_type_manager_v1` global lets clients describe the kind of content a surface
+//! shows, such as a video or a game. The compositor may use this to adapt how the surface is
+//! presented, for example by enabling adaptive sync or picking a different scaling filter.
+//!
+//! For windows, [`Window::set_content_type`](crate::shell::xdg::window::Window::set_content_type)
+//! keeps the content type alongside the window.
+
+use std::sync::{Arc, Weak};
+
+use wayland_client::{
+    globals::{BindError, GlobalList},
+    protocol::wl_surface,
+    Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols::wp::content_type::v1::client::{
+    wp_content_type_manager_v1, wp_content_type_v1,
+};
+
+use crate::{
+    compositor::SurfaceData,
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+};
+
+/// The kind of content shown by a surface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContentType {
+    /// Still images, which should be shown as accurately as possible.
+    Photo,
+    /// Videos, which may benefit from a refresh rate matching their frame rate.
+    Video,
+    /// Games, for which latency matters the most.
+    Game,
+}
+
+impl From<ContentType> for wp_content_type_v1::Type {
+    fn from(content_type: ContentType) -> Self {
+        match content_type {
+            ContentType::Photo => wp_content_type_v1::Type::Photo,
+            ContentType::Video => wp_content_type_v1::Type::Video,
+            ContentType::Game => wp_content_type_v1::Type::Game,
+        }
+    }
+}
+
+/// The content type manager global.
+#[derive(Debug)]
+pub struct ContentTypeState {
+    manager: wp_content_type_manager_v1::WpContentTypeManagerV1,
+}
+
+impl ContentTypeState {
+    /// Binds the `wp_content_type_manager_v1` global.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support content types.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State: Dispatch<wp_content_type_manager_v1::WpContentTypeManagerV1, GlobalData> + 'static,
+    {
+        let manager = globals.bind(qh, 1..=1, GlobalData)?;
+        Ok(Self { manager })
+    }
+
+    /// Get the content type object of a surface.
+    ///
+    /// If the surface was created with [`SurfaceData`], the object is stored in it, and this returns
+    /// the same object until all its clones are dropped.
+    ///
+    /// # Protocol errors
+    ///
+    /// Only one content type object may exist for a surface at a time.
+    pub fn get_surface_content_type<D>(
+        &self,
+        surface: &wl_surface::WlSurface,
+        qh: &QueueHandle<D>,
+    ) -> SurfaceContentType
+    where
+        D: Dispatch<wp_content_type_v1::WpContentTypeV1, ContentTypeData> + 'static,
+    {
+        let surface_data = surface.data::<SurfaceData>();
+
+        if let Some(content_type) = surface_data
+            .and_then(SurfaceData::content_type)
+            .and_then(|content_type| SurfaceContentType::from_content_type(&content_type))
+        {
+            return content_type;
+        }
+
+        let inner = Arc::new_cyclic(|weak| {
+            let content_type = self.manager.get_surface_content_type(
+                surface,
+                qh,
+                ContentTypeData { inner: weak.clone() },
+            );
+
+            SurfaceContentTypeInner { content_type, surface: surface.clone() }
+        });
+
+        if let Some(surface_data) = surface_data {
+            surface_data.set_content_type(Some(inner.content_type.clone()));
+        }
+
+        SurfaceContentType(inner)
+    }
+}
+
+impl ProvidesBoundGlobal<wp_content_type_manager_v1::WpContentTypeManagerV1, 1>
+    for ContentTypeState
+{
+    fn bound_global(
+        &self,
+    ) -> Result<wp_content_type_manager_v1::WpContentTypeManagerV1, GlobalError> {
+        Ok(self.manager.clone())
+    }
+}
+
+#[derive(Debug)]
+struct SurfaceContentTypeInner {
+    content_type: wp_content_type_v1::WpContentTypeV1,
+    surface: wl_surface::WlSurface,
+}
+
+impl Drop for SurfaceContentTypeInner {
+    fn drop(&mut self) {
+        if let Some(surface_data) = self.surface.data::<SurfaceData>() {
+            surface_data.set_content_type(None);
+        }
+        self.content_type.destroy();
+    }
+}
+
+/// The content type of a surface.
+///
+/// The object is destroyed when the last clone is dropped, which must happen before the surface is
+/// destroyed. The surface then has no content type after its next commit.
+#[derive(Debug, Clone)]
+pub struct SurfaceContentType(Arc<SurfaceContentTypeInner>);
+
+impl SurfaceContentType {
+    pub fn from_content_type(content_type: &wp_content_type_v1::WpContentTypeV1) -> Option<Self> {
+        content_type.data::<ContentTypeData>().and_then(|data| data.inner.upgrade()).map(Self)
+    }
+
+    pub fn content_type(&self) -> &wp_content_type_v1::WpContentTypeV1 {
+        &self.0.content_type
+    }
+
+    /// The surface of this content type.
+    pub fn wl_surface(&self) -> &wl_surface::WlSurface {
+        &self.0.surface
+    }
+
+    /// Set the kind of content shown by the surface.
+    ///
+    /// This state is double buffered and applied on the next commit of the surface.
+    pub fn set_content_type(&self, content_type: ContentType) {
+        self.0.content_type.set_content_type(content_type.into());
+    }
+
+    /// Remove the content type of the surface.
+    ///
+    /// This state is double buffered and applied on the next commit of the surface.
+    pub fn unset(&self) {
+        self.0.content_type.set_content_type(wp_content_type_v1::Type::None);
+    }
+}
+
+impl PartialEq for SurfaceContentType {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.content_type == other.0.content_type
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct ContentTypeData {
+    inner: Weak<SurfaceContentTypeInner>,
+}
+
+impl<D> Dispatch<wp_content_type_manager_v1::WpContentTypeManagerV1, GlobalData, D>
+    for ContentTypeState
+where
+    D: Dispatch<wp_content_type_manager_v1::WpContentTypeManagerV1, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &wp_content_type_manager_v1::WpContentTypeManagerV1,
+        _: wp_content_type_manager_v1::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("wp_content_type_manager_v1 has no events")
+    }
+}
+
+impl<D> Dispatch<wp_content_type_v1::WpContentTypeV1, ContentTypeData, D> for ContentTypeState
+where
+    D: Dispatch<wp_content_type_v1::WpContentTypeV1, ContentTypeData>,
+{
+    fn event(
+        _: &mut D,
+        _: &wp_content_type_v1::WpContentTypeV1,
+        _: wp_content_type_v1::Event,
+        _: &ContentTypeData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("wp_content_type_v1 has no events")
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_content_type {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::content_type::v1::client::wp_content_type_manager_v1::WpContentTypeManagerV1: $crate::globals::GlobalData
+        ] => $crate::content_type::ContentTypeState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::content_type::v1::client::wp_content_type_v1::WpContentTypeV1: $crate::content_type::ContentTypeData
+        ] => $crate::content_type::ContentTypeState);
+    };
+}
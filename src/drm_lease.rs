@@ -0,0 +1,590 @@
+//! ## DRM lease
+//!
+//! The `wp_drm_lease_device_v1` globals let clients such as VR runtimes lease connectors away from the
+//! compositor, to drive a display directly through DRM. There is a global for each DRM device, which
+//! advertises the connectors available for leasing.
+//!
+//! A lease is requested with a [`DrmLeaseRequest`] created by [`DrmLeaseDevice::create_lease_request`].
+//! Once granted, the DRM fd of the lease is passed to [`DrmLeaseHandler::lease_fd`].
+
+use std::{
+    os::unix::io::OwnedFd,
+    sync::{Arc, Mutex},
+};
+
+use wayland_client::{
+    event_created_child, globals::GlobalList, Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols::wp::drm_lease::v1::client::{
+    wp_drm_lease_connector_v1, wp_drm_lease_device_v1, wp_drm_lease_request_v1, wp_drm_lease_v1,
+};
+
+use crate::{
+    globals::GlobalData,
+    registry::{ProvidesRegistryState, RegistryHandler},
+};
+
+/// Handler for DRM lease devices and the leases requested from them.
+pub trait DrmLeaseHandler: Sized {
+    fn drm_lease_state(&mut self) -> &mut DrmLeaseState;
+
+    /// A DRM lease device is advertised, and its DRM fd and connectors are known.
+    fn new_device(&mut self, conn: &Connection, qh: &QueueHandle<Self>, device: &DrmLeaseDevice);
+
+    /// Connectors were added to or withdrawn from a device.
+    fn update_device(&mut self, conn: &Connection, qh: &QueueHandle<Self>, device: &DrmLeaseDevice);
+
+    /// A device is no longer advertised.
+    ///
+    /// Leases which were already granted stay valid.
+    fn remove_device(&mut self, conn: &Connection, qh: &QueueHandle<Self>, device: &DrmLeaseDevice);
+
+    /// A lease was granted, with the DRM fd giving access to the leased connectors.
+    fn lease_fd(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        lease: &DrmLease,
+        fd: OwnedFd,
+    );
+
+    /// A granted lease was revoked by the compositor.
+    ///
+    /// The lease is destroyed after this returns.
+    fn lease_finished(&mut self, conn: &Connection, qh: &QueueHandle<Self>, lease: &DrmLease);
+
+    /// A lease could not be granted, because the compositor denied it or one of its connectors was
+    /// withdrawn while it was pending.
+    ///
+    /// The lease is destroyed after this returns.
+    fn lease_failed(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        lease: &DrmLease,
+        error: DrmLeaseError,
+    );
+}
+
+/// The DRM lease device globals.
+#[derive(Debug)]
+pub struct DrmLeaseState {
+    devices: Vec<DrmLeaseDevice>,
+}
+
+impl DrmLeaseState {
+    /// Binds all the `wp_drm_lease_device_v1` globals.
+    ///
+    /// Devices advertised later are bound through [`RegistryHandler`].
+    pub fn new<D>(global_list: &GlobalList, qh: &QueueHandle<D>) -> Self
+    where
+        D: Dispatch<wp_drm_lease_device_v1::WpDrmLeaseDeviceV1, DrmLeaseDeviceData> + 'static,
+    {
+        let devices = global_list.contents().with_list(|globals| {
+            crate::registry::bind_all(
+                global_list.registry(),
+                globals,
+                qh,
+                1..=1,
+                DrmLeaseDeviceData::new,
+            )
+            .expect("failed to bind global")
+        });
+
+        Self { devices: devices.into_iter().map(DrmLeaseDevice).collect() }
+    }
+
+    /// The advertised devices.
+    ///
+    /// A device is only usable once [`DrmLeaseHandler::new_device`] was called for it.
+    pub fn devices(&self) -> impl Iterator<Item = &DrmLeaseDevice> {
+        self.devices.iter()
+    }
+}
+
+/// An error that may occur when requesting a lease.
+#[derive(Debug, thiserror::Error)]
+pub enum DrmLeaseError {
+    /// The lease request has no connectors.
+    #[error("the lease request has no connectors")]
+    EmptyLease,
+
+    /// The connector is not advertised by the device the lease is requested from.
+    #[error("the connector belongs to another device")]
+    WrongDevice,
+
+    /// The connector was already added to the lease request.
+    #[error("the connector was already requested")]
+    DuplicateConnector,
+
+    /// The connector was withdrawn by the compositor.
+    #[error("the connector was withdrawn")]
+    ConnectorWithdrawn,
+
+    /// The compositor did not grant the lease.
+    #[error("the lease was denied")]
+    Denied,
+}
+
+/// A DRM device whose connectors may be leased.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DrmLeaseDevice(wp_drm_lease_device_v1::WpDrmLeaseDeviceV1);
+
+impl DrmLeaseDevice {
+    pub fn device(&self) -> &wp_drm_lease_device_v1::WpDrmLeaseDeviceV1 {
+        &self.0
+    }
+
+    /// The DRM fd of the device, opened without master privileges, which may be used to query the
+    /// connectors.
+    pub fn drm_fd(&self) -> Option<Arc<OwnedFd>> {
+        self.data().inner.lock().unwrap().drm_fd.clone()
+    }
+
+    /// The connectors available for leasing.
+    pub fn connectors(&self) -> Vec<DrmLeaseConnector> {
+        self.data().inner.lock().unwrap().connectors.clone()
+    }
+
+    /// Start building a lease request for some of the connectors of this device.
+    pub fn create_lease_request(&self) -> DrmLeaseRequest {
+        DrmLeaseRequest { device: self.clone(), connectors: Vec::new() }
+    }
+
+    fn data(&self) -> &DrmLeaseDeviceData {
+        self.0.data::<DrmLeaseDeviceData>().unwrap()
+    }
+}
+
+/// A connector which may be leased.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DrmLeaseConnector(wp_drm_lease_connector_v1::WpDrmLeaseConnectorV1);
+
+impl DrmLeaseConnector {
+    pub fn connector(&self) -> &wp_drm_lease_connector_v1::WpDrmLeaseConnectorV1 {
+        &self.0
+    }
+
+    /// The properties of the connector, or [`None`] until the compositor sent them or once the
+    /// connector was withdrawn and destroyed.
+    pub fn info(&self) -> Option<DrmConnectorInfo> {
+        self.data()?.inner.lock().unwrap().current.clone()
+    }
+
+    /// Whether the compositor withdrew the connector, so it can no longer be leased.
+    pub fn withdrawn(&self) -> bool {
+        match self.data() {
+            Some(data) => data.inner.lock().unwrap().withdrawn,
+            None => true,
+        }
+    }
+
+    fn data(&self) -> Option<&DrmLeaseConnectorData> {
+        self.0.data::<DrmLeaseConnectorData>()
+    }
+}
+
+/// The properties of a connector.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DrmConnectorInfo {
+    /// The name of the connector, such as `DP-1`.
+    pub name: String,
+    pub description: String,
+    /// The DRM object id of the connector.
+    pub connector_id: u32,
+}
+
+/// Builder for a lease of connectors of a device.
+#[derive(Debug)]
+pub struct DrmLeaseRequest {
+    device: DrmLeaseDevice,
+    connectors: Vec<DrmLeaseConnector>,
+}
+
+impl DrmLeaseRequest {
+    /// Add a connector to the lease.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the connector is not advertised by the device, was already
+    /// added or was withdrawn.
+    pub fn request_connector(
+        &mut self,
+        connector: &DrmLeaseConnector,
+    ) -> Result<(), DrmLeaseError> {
+        if connector.withdrawn() {
+            return Err(DrmLeaseError::ConnectorWithdrawn);
+        }
+
+        if self.connectors.contains(connector) {
+            return Err(DrmLeaseError::DuplicateConnector);
+        }
+
+        if !self.device.data().inner.lock().unwrap().connectors.contains(connector) {
+            return Err(DrmLeaseError::WrongDevice);
+        }
+
+        self.connectors.push(connector.clone());
+        Ok(())
+    }
+
+    /// Submit the lease request.
+    ///
+    /// [`DrmLeaseHandler::lease_fd`] or [`DrmLeaseHandler::lease_failed`] will be invoked when the
+    /// lease is granted or not.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if no connector was added, or if one of them was withdrawn
+    /// since it was added.
+    pub fn submit<D>(self, qh: &QueueHandle<D>) -> Result<DrmLease, DrmLeaseError>
+    where
+        D: Dispatch<wp_drm_lease_request_v1::WpDrmLeaseRequestV1, GlobalData>
+            + Dispatch<wp_drm_lease_v1::WpDrmLeaseV1, DrmLeaseData>
+            + 'static,
+    {
+        if self.connectors.is_empty() {
+            return Err(DrmLeaseError::EmptyLease);
+        }
+
+        if self.connectors.iter().any(DrmLeaseConnector::withdrawn) {
+            return Err(DrmLeaseError::ConnectorWithdrawn);
+        }
+
+        let request = self.device.0.create_lease_request(qh, GlobalData);
+        for connector in &self.connectors {
+            request.request_connector(&connector.0);
+        }
+
+        let data = DrmLeaseData {
+            device: self.device.clone(),
+            connectors: self.connectors,
+            granted: Mutex::new(false),
+        };
+        let lease = DrmLease(request.submit(qh, data));
+        self.device.data().inner.lock().unwrap().pending_leases.push(lease.clone());
+        Ok(lease)
+    }
+}
+
+/// A lease of connectors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DrmLease(wp_drm_lease_v1::WpDrmLeaseV1);
+
+impl DrmLease {
+    pub fn lease(&self) -> &wp_drm_lease_v1::WpDrmLeaseV1 {
+        &self.0
+    }
+
+    /// The device the connectors are leased from.
+    pub fn device(&self) -> &DrmLeaseDevice {
+        &self.data().device
+    }
+
+    /// The leased connectors.
+    pub fn connectors(&self) -> &[DrmLeaseConnector] {
+        &self.data().connectors
+    }
+
+    /// Whether the lease was granted.
+    pub fn granted(&self) -> bool {
+        *self.data().granted.lock().unwrap()
+    }
+
+    /// Revoke the lease, giving the connectors back to the compositor.
+    pub fn destroy(&self) {
+        self.0.destroy();
+    }
+
+    fn data(&self) -> &DrmLeaseData {
+        self.0.data::<DrmLeaseData>().unwrap()
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct DrmLeaseDeviceData {
+    name: u32,
+    inner: Mutex<DrmLeaseDeviceInner>,
+}
+
+impl DrmLeaseDeviceData {
+    fn new(name: u32) -> Self {
+        Self { name, inner: Mutex::new(DrmLeaseDeviceInner::default()) }
+    }
+}
+
+#[derive(Debug, Default)]
+struct DrmLeaseDeviceInner {
+    drm_fd: Option<Arc<OwnedFd>>,
+    connectors: Vec<DrmLeaseConnector>,
+    /// The leases submitted from this device which were not granted yet.
+    pending_leases: Vec<DrmLease>,
+    /// Whether the first `done` event was received.
+    done: bool,
+}
+
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct DrmLeaseConnectorData {
+    inner: Mutex<DrmLeaseConnectorInner>,
+}
+
+#[derive(Debug, Default)]
+struct DrmLeaseConnectorInner {
+    pending: DrmConnectorInfo,
+    current: Option<DrmConnectorInfo>,
+    withdrawn: bool,
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct DrmLeaseData {
+    device: DrmLeaseDevice,
+    connectors: Vec<DrmLeaseConnector>,
+    granted: Mutex<bool>,
+}
+
+impl DrmLeaseData {
+    fn remove_pending(&self, lease: &DrmLease) {
+        // The device may already be released.
+        if let Some(device_data) = self.device.0.data::<DrmLeaseDeviceData>() {
+            device_data.inner.lock().unwrap().pending_leases.retain(|l| l != lease);
+        }
+    }
+}
+
+impl<D> Dispatch<wp_drm_lease_device_v1::WpDrmLeaseDeviceV1, DrmLeaseDeviceData, D>
+    for DrmLeaseState
+where
+    D: Dispatch<wp_drm_lease_device_v1::WpDrmLeaseDeviceV1, DrmLeaseDeviceData>
+        + Dispatch<wp_drm_lease_connector_v1::WpDrmLeaseConnectorV1, DrmLeaseConnectorData>
+        + DrmLeaseHandler
+        + 'static,
+{
+    event_created_child!(D, wp_drm_lease_device_v1::WpDrmLeaseDeviceV1, [
+        wp_drm_lease_device_v1::EVT_CONNECTOR_OPCODE => (wp_drm_lease_connector_v1::WpDrmLeaseConnectorV1, Default::default())
+    ]);
+
+    fn event(
+        state: &mut D,
+        proxy: &wp_drm_lease_device_v1::WpDrmLeaseDeviceV1,
+        event: wp_drm_lease_device_v1::Event,
+        data: &DrmLeaseDeviceData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        match event {
+            wp_drm_lease_device_v1::Event::DrmFd { fd } => {
+                data.inner.lock().unwrap().drm_fd = Some(Arc::new(fd));
+            }
+
+            wp_drm_lease_device_v1::Event::Connector { id } => {
+                data.inner.lock().unwrap().connectors.push(DrmLeaseConnector(id));
+            }
+
+            wp_drm_lease_device_v1::Event::Done => {
+                let device = DrmLeaseDevice(proxy.clone());
+
+                let (first_done, withdrawn, failed) = {
+                    let mut inner = data.inner.lock().unwrap();
+                    let (withdrawn, connectors) = inner
+                        .connectors
+                        .drain(..)
+                        .partition::<Vec<_>, _>(DrmLeaseConnector::withdrawn);
+                    inner.connectors = connectors;
+
+                    // The compositor may never answer a lease request whose connectors were
+                    // withdrawn, so it is failed rather than left pending.
+                    let (failed, pending) =
+                        inner.pending_leases.drain(..).partition::<Vec<_>, _>(|lease| {
+                            lease.connectors().iter().any(DrmLeaseConnector::withdrawn)
+                        });
+                    inner.pending_leases = pending;
+
+                    (!std::mem::replace(&mut inner.done, true), withdrawn, failed)
+                };
+
+                for lease in failed {
+                    state.lease_failed(conn, qh, &lease, DrmLeaseError::ConnectorWithdrawn);
+                    lease.0.destroy();
+                }
+
+                for connector in withdrawn {
+                    connector.0.destroy();
+                }
+
+                if first_done {
+                    state.new_device(conn, qh, &device);
+                } else {
+                    state.update_device(conn, qh, &device);
+                }
+            }
+
+            wp_drm_lease_device_v1::Event::Released => {
+                // The device was already removed from the state when it was released.
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<wp_drm_lease_connector_v1::WpDrmLeaseConnectorV1, DrmLeaseConnectorData, D>
+    for DrmLeaseState
+where
+    D: Dispatch<wp_drm_lease_connector_v1::WpDrmLeaseConnectorV1, DrmLeaseConnectorData>
+        + DrmLeaseHandler,
+{
+    fn event(
+        _: &mut D,
+        _: &wp_drm_lease_connector_v1::WpDrmLeaseConnectorV1,
+        event: wp_drm_lease_connector_v1::Event,
+        data: &DrmLeaseConnectorData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        let mut inner = data.inner.lock().unwrap();
+
+        match event {
+            wp_drm_lease_connector_v1::Event::Name { name } => {
+                inner.pending.name = name;
+            }
+
+            wp_drm_lease_connector_v1::Event::Description { description } => {
+                inner.pending.description = description;
+            }
+
+            wp_drm_lease_connector_v1::Event::ConnectorId { connector_id } => {
+                inner.pending.connector_id = connector_id;
+            }
+
+            wp_drm_lease_connector_v1::Event::Done => {
+                inner.current = Some(inner.pending.clone());
+            }
+
+            wp_drm_lease_connector_v1::Event::Withdrawn => {
+                // The connector is removed from its device and destroyed on the next `done` event of
+                // the device.
+                inner.withdrawn = true;
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<wp_drm_lease_request_v1::WpDrmLeaseRequestV1, GlobalData, D> for DrmLeaseState
+where
+    D: Dispatch<wp_drm_lease_request_v1::WpDrmLeaseRequestV1, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &wp_drm_lease_request_v1::WpDrmLeaseRequestV1,
+        _: wp_drm_lease_request_v1::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("wp_drm_lease_request_v1 has no events")
+    }
+}
+
+impl<D> Dispatch<wp_drm_lease_v1::WpDrmLeaseV1, DrmLeaseData, D> for DrmLeaseState
+where
+    D: Dispatch<wp_drm_lease_v1::WpDrmLeaseV1, DrmLeaseData> + DrmLeaseHandler,
+{
+    fn event(
+        state: &mut D,
+        proxy: &wp_drm_lease_v1::WpDrmLeaseV1,
+        event: wp_drm_lease_v1::Event,
+        data: &DrmLeaseData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let lease = DrmLease(proxy.clone());
+
+        match event {
+            wp_drm_lease_v1::Event::LeaseFd { leased_fd } => {
+                *data.granted.lock().unwrap() = true;
+                data.remove_pending(&lease);
+                state.lease_fd(conn, qh, &lease, leased_fd);
+            }
+
+            wp_drm_lease_v1::Event::Finished => {
+                if *data.granted.lock().unwrap() {
+                    state.lease_finished(conn, qh, &lease);
+                } else {
+                    data.remove_pending(&lease);
+                    state.lease_failed(conn, qh, &lease, DrmLeaseError::Denied);
+                }
+
+                proxy.destroy();
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> RegistryHandler<D> for DrmLeaseState
+where
+    D: Dispatch<wp_drm_lease_device_v1::WpDrmLeaseDeviceV1, DrmLeaseDeviceData>
+        + DrmLeaseHandler
+        + ProvidesRegistryState
+        + 'static,
+{
+    fn new_global(
+        state: &mut D,
+        _: &Connection,
+        qh: &QueueHandle<D>,
+        name: u32,
+        interface: &str,
+        _: u32,
+    ) {
+        if interface == wp_drm_lease_device_v1::WpDrmLeaseDeviceV1::interface().name {
+            let device = state
+                .registry()
+                .bind_specific(qh, name, 1..=1, DrmLeaseDeviceData::new(name))
+                .expect("failed to bind global");
+
+            state.drm_lease_state().devices.push(DrmLeaseDevice(device));
+        }
+    }
+
+    fn remove_global(
+        state: &mut D,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+        name: u32,
+        interface: &str,
+    ) {
+        if interface == wp_drm_lease_device_v1::WpDrmLeaseDeviceV1::interface().name {
+            let devices = &mut state.drm_lease_state().devices;
+            if let Some(idx) = devices.iter().position(|device| device.data().name == name) {
+                let device = devices.remove(idx);
+                state.remove_device(conn, qh, &device);
+                device.0.release();
+            }
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_drm_lease {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::drm_lease::v1::client::wp_drm_lease_device_v1::WpDrmLeaseDeviceV1: $crate::drm_lease::DrmLeaseDeviceData
+        ] => $crate::drm_lease::DrmLeaseState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::drm_lease::v1::client::wp_drm_lease_connector_v1::WpDrmLeaseConnectorV1: $crate::drm_lease::DrmLeaseConnectorData
+        ] => $crate::drm_lease::DrmLeaseState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::drm_lease::v1::client::wp_drm_lease_request_v1::WpDrmLeaseRequestV1: $crate::globals::GlobalData
+        ] => $crate::drm_lease::DrmLeaseState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::drm_lease::v1::client::wp_drm_lease_v1::WpDrmLeaseV1: $crate::drm_lease::DrmLeaseData
+        ] => $crate::drm_lease::DrmLeaseState);
+    };
+}
@@ -1,3 +1,5 @@
+use std::ops::RangeInclusive;
+
 /// An error that may occur when creating objects using a global.
 #[derive(Debug, thiserror::Error)]
 pub enum GlobalError {
@@ -8,4 +10,61 @@ pub enum GlobalError {
     /// A compositor global was available, but did not support the given minimum version
     #[error("the '{name}' global does not support interface version {required} (using version {available})")]
     InvalidVersion { name: &'static str, required: u32, available: u32 },
+
+    /// A compositor global was advertised, but with a version outside of the range that was requested
+    /// when binding it
+    #[error(
+        "the '{name}' global is advertised with version {advertised}, but versions {}..={} were requested",
+        .requested.start(),
+        .requested.end()
+    )]
+    UnsupportedVersion { name: &'static str, requested: RangeInclusive<u32>, advertised: u32 },
+
+    /// A compositor global is advertised, but was not bound yet
+    #[error("the '{0}' global was not bound yet")]
+    NotBound(&'static str),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GlobalError;
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            GlobalError::MissingGlobal("wl_shm").to_string(),
+            "the 'wl_shm' global was not available"
+        );
+        assert_eq!(
+            GlobalError::InvalidVersion { name: "wl_seat", required: 5, available: 3 }.to_string(),
+            "the 'wl_seat' global does not support interface version 5 (using version 3)"
+        );
+        assert_eq!(
+            GlobalError::NotBound("wl_output").to_string(),
+            "the 'wl_output' global was not bound yet"
+        );
+    }
+
+    #[test]
+    fn display_unsupported_version() {
+        let error = GlobalError::UnsupportedVersion {
+            name: "wp_viewporter",
+            requested: 2..=4,
+            advertised: 1,
+        };
+        assert_eq!(
+            error.to_string(),
+            "the 'wp_viewporter' global is advertised with version 1, but versions 2..=4 were requested"
+        );
+
+        let error = GlobalError::UnsupportedVersion {
+            name: "xdg_wm_base",
+            requested: 1..=1,
+            advertised: 6,
+        };
+        assert_eq!(
+            error.to_string(),
+            "the 'xdg_wm_base' global is advertised with version 6, but versions 1..=1 were requested"
+        );
+    }
 }
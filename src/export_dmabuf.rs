@@ -0,0 +1,242 @@
+//! ## wlr export dmabuf
+//!
+//! The `zwlr_export_dmabuf_manager_v1` global exports the frames of an output as dmabufs, without
+//! copying them, which lets screen recorders feed them directly to a hardware encoder.
+//!
+//! A frame is requested with [`ExportDmabufState::capture_output`]. Its metadata and dmabuf objects are
+//! collected into a [`DmabufFrame`], which is passed to [`ExportDmabufHandler::ready`]. To capture
+//! continuously, the next frame is requested from there.
+
+use std::{os::unix::io::OwnedFd, sync::Mutex, time::Duration};
+
+use wayland_client::{
+    globals::{BindError, GlobalList},
+    protocol::wl_output,
+    Connection, Dispatch, QueueHandle, WEnum,
+};
+use wayland_protocols::wp::linux_dmabuf::zv1::client::zwp_linux_buffer_params_v1;
+use wayland_protocols_wlr::export_dmabuf::v1::client::{
+    zwlr_export_dmabuf_frame_v1, zwlr_export_dmabuf_manager_v1,
+};
+
+use crate::globals::GlobalData;
+
+pub use zwlr_export_dmabuf_frame_v1::CancelReason;
+
+/// Handler for exported dmabuf frames.
+pub trait ExportDmabufHandler: Sized {
+    /// A frame of an output was exported.
+    ///
+    /// The dmabufs are closed when the frame is dropped.
+    fn ready(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        output: &wl_output::WlOutput,
+        frame: DmabufFrame,
+    );
+
+    /// A frame of an output could not be exported.
+    ///
+    /// With [`CancelReason::Temporary`] or [`CancelReason::Resizing`], the next frame may be requested
+    /// again.
+    fn cancel(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        output: &wl_output::WlOutput,
+        reason: WEnum<CancelReason>,
+    );
+}
+
+/// The export dmabuf manager global.
+#[derive(Debug)]
+pub struct ExportDmabufState {
+    manager: zwlr_export_dmabuf_manager_v1::ZwlrExportDmabufManagerV1,
+}
+
+impl ExportDmabufState {
+    /// Binds the `zwlr_export_dmabuf_manager_v1` global.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support wlr export dmabuf.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State: Dispatch<zwlr_export_dmabuf_manager_v1::ZwlrExportDmabufManagerV1, GlobalData>
+            + 'static,
+    {
+        let manager = globals.bind(qh, 1..=1, GlobalData)?;
+        Ok(Self { manager })
+    }
+
+    /// Request the next frame of an output, with or without the cursor drawn on top of it.
+    pub fn capture_output<D>(
+        &self,
+        overlay_cursor: bool,
+        output: &wl_output::WlOutput,
+        qh: &QueueHandle<D>,
+    ) -> zwlr_export_dmabuf_frame_v1::ZwlrExportDmabufFrameV1
+    where
+        D: Dispatch<zwlr_export_dmabuf_frame_v1::ZwlrExportDmabufFrameV1, ExportDmabufFrameData>
+            + 'static,
+    {
+        let data = ExportDmabufFrameData { output: output.clone(), frame: Mutex::new(None) };
+        self.manager.capture_output(overlay_cursor.into(), output, qh, data)
+    }
+}
+
+/// An exported frame, which owns its dmabufs.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct DmabufFrame {
+    /// The size of the frame in pixels.
+    pub size: (u32, u32),
+    /// The position of the frame in the dmabufs.
+    pub offset: (u32, u32),
+    /// The DRM fourcc format.
+    pub format: u32,
+    pub modifier: u64,
+    /// The properties of the dmabufs, such as whether they are vertically flipped.
+    pub buffer_flags: zwp_linux_buffer_params_v1::Flags,
+    /// Whether the dmabufs are only valid until the next frame is exported.
+    pub transient: bool,
+    /// The dmabuf objects, with the planes they contain.
+    pub objects: Vec<DmabufObject>,
+    /// The time the frame was presented at, in the `CLOCK_MONOTONIC` clock.
+    pub presentation_time: Duration,
+}
+
+/// A dmabuf object of an exported frame.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct DmabufObject {
+    pub fd: OwnedFd,
+    /// The size of the object in bytes.
+    pub size: u32,
+    /// The offset of the plane in the object.
+    pub offset: u32,
+    /// The distance in bytes between the beginning of a row of the plane and the next one.
+    pub stride: u32,
+    pub plane_index: u32,
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct ExportDmabufFrameData {
+    output: wl_output::WlOutput,
+    /// The frame being received, [`None`] until its metadata is known.
+    frame: Mutex<Option<DmabufFrame>>,
+}
+
+impl<D> Dispatch<zwlr_export_dmabuf_manager_v1::ZwlrExportDmabufManagerV1, GlobalData, D>
+    for ExportDmabufState
+where
+    D: Dispatch<zwlr_export_dmabuf_manager_v1::ZwlrExportDmabufManagerV1, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &zwlr_export_dmabuf_manager_v1::ZwlrExportDmabufManagerV1,
+        _: zwlr_export_dmabuf_manager_v1::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("zwlr_export_dmabuf_manager_v1 has no events")
+    }
+}
+
+impl<D> Dispatch<zwlr_export_dmabuf_frame_v1::ZwlrExportDmabufFrameV1, ExportDmabufFrameData, D>
+    for ExportDmabufState
+where
+    D: Dispatch<zwlr_export_dmabuf_frame_v1::ZwlrExportDmabufFrameV1, ExportDmabufFrameData>
+        + ExportDmabufHandler,
+{
+    fn event(
+        state: &mut D,
+        proxy: &zwlr_export_dmabuf_frame_v1::ZwlrExportDmabufFrameV1,
+        event: zwlr_export_dmabuf_frame_v1::Event,
+        data: &ExportDmabufFrameData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        match event {
+            zwlr_export_dmabuf_frame_v1::Event::Frame {
+                width,
+                height,
+                offset_x,
+                offset_y,
+                buffer_flags,
+                flags,
+                format,
+                mod_high,
+                mod_low,
+                num_objects,
+            } => {
+                let transient = u32::from(flags)
+                    & u32::from(zwlr_export_dmabuf_frame_v1::Flags::Transient)
+                    != 0;
+
+                *data.frame.lock().unwrap() = Some(DmabufFrame {
+                    size: (width, height),
+                    offset: (offset_x, offset_y),
+                    format,
+                    modifier: (u64::from(mod_high) << 32) | u64::from(mod_low),
+                    buffer_flags: zwp_linux_buffer_params_v1::Flags::from_bits_truncate(
+                        buffer_flags,
+                    ),
+                    transient,
+                    objects: Vec::with_capacity(num_objects as usize),
+                    presentation_time: Duration::ZERO,
+                });
+            }
+
+            zwlr_export_dmabuf_frame_v1::Event::Object {
+                index: _,
+                fd,
+                size,
+                offset,
+                stride,
+                plane_index,
+            } => {
+                // The objects are always sent after the frame metadata. If not, the fd is closed.
+                if let Some(frame) = data.frame.lock().unwrap().as_mut() {
+                    frame.objects.push(DmabufObject { fd, size, offset, stride, plane_index });
+                }
+            }
+
+            zwlr_export_dmabuf_frame_v1::Event::Ready { tv_sec_hi, tv_sec_lo, tv_nsec } => {
+                proxy.destroy();
+
+                let frame = data.frame.lock().unwrap().take();
+                if let Some(mut frame) = frame {
+                    let secs = (u64::from(tv_sec_hi) << 32) | u64::from(tv_sec_lo);
+                    frame.presentation_time = Duration::new(secs, tv_nsec);
+                    state.ready(conn, qh, &data.output, frame);
+                }
+            }
+
+            zwlr_export_dmabuf_frame_v1::Event::Cancel { reason } => {
+                proxy.destroy();
+
+                // Close the dmabufs which were already received.
+                data.frame.lock().unwrap().take();
+                state.cancel(conn, qh, &data.output, reason);
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_export_dmabuf {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_wlr::export_dmabuf::v1::client::zwlr_export_dmabuf_manager_v1::ZwlrExportDmabufManagerV1: $crate::globals::GlobalData
+        ] => $crate::export_dmabuf::ExportDmabufState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_wlr::export_dmabuf::v1::client::zwlr_export_dmabuf_frame_v1::ZwlrExportDmabufFrameV1: $crate::export_dmabuf::ExportDmabufFrameData
+        ] => $crate::export_dmabuf::ExportDmabufState);
+    };
+}
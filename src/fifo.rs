@@ -0,0 +1,193 @@
+//! ## FIFO
+//!
+//! The `wp_fifo_manager_v1` global gives the commits of a surface first in, first out semantics:
+//! a commit can set a barrier, and a later commit can wait for that barrier to be cleared, which
+//! happens when the content is latched by the compositor for an output refresh. Together, they queue
+//! commits so that each one is shown for at least one refresh, instead of replacing the previous
+//! commit before it was ever shown.
+//!
+//! Unlike frame callbacks, waiting for a barrier does not block the client, which may queue several
+//! commits in advance. It is often combined with [`commit_timing`](crate::commit_timing) to also
+//! choose when each commit is shown.
+
+use std::sync::{Arc, Weak};
+
+use wayland_client::{
+    globals::{BindError, GlobalList},
+    protocol::wl_surface,
+    Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols::wp::fifo::v1::client::{wp_fifo_manager_v1, wp_fifo_v1};
+
+use crate::{
+    compositor::SurfaceData,
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+};
+
+/// The FIFO manager global.
+#[derive(Debug)]
+pub struct FifoState {
+    manager: wp_fifo_manager_v1::WpFifoManagerV1,
+}
+
+impl FifoState {
+    /// Binds the `wp_fifo_manager_v1` global.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support FIFO.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State: Dispatch<wp_fifo_manager_v1::WpFifoManagerV1, GlobalData> + 'static,
+    {
+        let manager = globals.bind(qh, 1..=1, GlobalData)?;
+        Ok(Self { manager })
+    }
+
+    /// Get the FIFO object of a surface.
+    ///
+    /// If the surface was created with [`SurfaceData`], the object is stored in it, and this returns
+    /// the same object until all its clones are dropped.
+    ///
+    /// # Protocol errors
+    ///
+    /// Only one FIFO object may exist for a surface at a time.
+    pub fn get_fifo<D>(&self, surface: &wl_surface::WlSurface, qh: &QueueHandle<D>) -> Fifo
+    where
+        D: Dispatch<wp_fifo_v1::WpFifoV1, FifoData> + 'static,
+    {
+        let surface_data = surface.data::<SurfaceData>();
+
+        if let Some(fifo) =
+            surface_data.and_then(SurfaceData::fifo).and_then(|fifo| Fifo::from_fifo(&fifo))
+        {
+            return fifo;
+        }
+
+        let inner = Arc::new_cyclic(|weak| {
+            let fifo = self.manager.get_fifo(surface, qh, FifoData { inner: weak.clone() });
+            FifoInner { fifo, surface: surface.clone() }
+        });
+
+        if let Some(surface_data) = surface_data {
+            surface_data.set_fifo(Some(inner.fifo.clone()));
+        }
+
+        Fifo(inner)
+    }
+}
+
+impl ProvidesBoundGlobal<wp_fifo_manager_v1::WpFifoManagerV1, 1> for FifoState {
+    fn bound_global(&self) -> Result<wp_fifo_manager_v1::WpFifoManagerV1, GlobalError> {
+        Ok(self.manager.clone())
+    }
+}
+
+#[derive(Debug)]
+struct FifoInner {
+    fifo: wp_fifo_v1::WpFifoV1,
+    surface: wl_surface::WlSurface,
+}
+
+impl Drop for FifoInner {
+    fn drop(&mut self) {
+        if let Some(surface_data) = self.surface.data::<SurfaceData>() {
+            surface_data.set_fifo(None);
+        }
+        self.fifo.destroy();
+    }
+}
+
+/// The FIFO object of a surface.
+///
+/// The object is destroyed when the last clone is dropped, which should happen before the surface is
+/// destroyed, since the object can no longer be used afterwards. Barriers already set or waited for
+/// still apply to the commits that were already made.
+#[derive(Debug, Clone)]
+pub struct Fifo(Arc<FifoInner>);
+
+impl Fifo {
+    pub fn from_fifo(fifo: &wp_fifo_v1::WpFifoV1) -> Option<Self> {
+        fifo.data::<FifoData>().and_then(|data| data.inner.upgrade()).map(Self)
+    }
+
+    pub fn fifo(&self) -> &wp_fifo_v1::WpFifoV1 {
+        &self.0.fifo
+    }
+
+    /// The surface of this FIFO object.
+    pub fn wl_surface(&self) -> &wl_surface::WlSurface {
+        &self.0.surface
+    }
+
+    /// Set a barrier on the next commit of the surface.
+    ///
+    /// The barrier is cleared once the content of that commit is latched for an output refresh.
+    pub fn set_barrier(&self) {
+        self.0.fifo.set_barrier();
+    }
+
+    /// Make the next commit of the surface wait until the current barrier, if any, is cleared.
+    ///
+    /// The commit is applied immediately if there is no barrier.
+    pub fn wait_barrier(&self) {
+        self.0.fifo.wait_barrier();
+    }
+}
+
+impl PartialEq for Fifo {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.fifo == other.0.fifo
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct FifoData {
+    inner: Weak<FifoInner>,
+}
+
+impl<D> Dispatch<wp_fifo_manager_v1::WpFifoManagerV1, GlobalData, D> for FifoState
+where
+    D: Dispatch<wp_fifo_manager_v1::WpFifoManagerV1, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &wp_fifo_manager_v1::WpFifoManagerV1,
+        _: wp_fifo_manager_v1::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("wp_fifo_manager_v1 has no events")
+    }
+}
+
+impl<D> Dispatch<wp_fifo_v1::WpFifoV1, FifoData, D> for FifoState
+where
+    D: Dispatch<wp_fifo_v1::WpFifoV1, FifoData>,
+{
+    fn event(
+        _: &mut D,
+        _: &wp_fifo_v1::WpFifoV1,
+        _: wp_fifo_v1::Event,
+        _: &FifoData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("wp_fifo_v1 has no events")
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_fifo {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::fifo::v1::client::wp_fifo_manager_v1::WpFifoManagerV1: $crate::globals::GlobalData
+        ] => $crate::fifo::FifoState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::fifo::v1::client::wp_fifo_v1::WpFifoV1: $crate::fifo::FifoData
+        ] => $crate::fifo::FifoState);
+    };
+}
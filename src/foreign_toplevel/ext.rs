@@ -0,0 +1,266 @@
+//! ## ext foreign toplevel list
+//!
+//! The `ext_foreign_toplevel_list_v1` global lists the toplevels of all clients, each with a stable
+//! identifier. Unlike [`wlr`](super::wlr), the list is read-only.
+//!
+//! [`ExtForeignToplevelListState`] keeps track of the toplevels. Their metadata is applied atomically
+//! when the compositor is done sending a change, and reported to [`ExtForeignToplevelListHandler`].
+
+use std::sync::Mutex;
+
+use wayland_client::{
+    event_created_child,
+    globals::{BindError, GlobalList},
+    Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols::ext::foreign_toplevel_list::v1::client::{
+    ext_foreign_toplevel_handle_v1, ext_foreign_toplevel_list_v1,
+};
+
+use crate::globals::GlobalData;
+
+use super::ToplevelInfo;
+
+/// Handler for toplevels listed by the ext foreign toplevel list.
+pub trait ExtForeignToplevelListHandler: Sized {
+    fn ext_foreign_toplevel_list_state(&mut self) -> &mut ExtForeignToplevelListState;
+
+    /// A toplevel was mapped, and its initial metadata is known.
+    fn new_toplevel(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        toplevel: &ExtForeignToplevel,
+    );
+
+    /// The metadata of a toplevel changed.
+    fn update_toplevel(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        toplevel: &ExtForeignToplevel,
+    );
+
+    /// A toplevel was unmapped.
+    ///
+    /// This is also called for all toplevels when the compositor stops sending toplevels, after
+    /// [`ExtForeignToplevelListState::stop`] or when the global is removed.
+    fn toplevel_closed(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        toplevel: &ExtForeignToplevel,
+    );
+}
+
+/// The ext foreign toplevel list global and the toplevels it lists.
+#[derive(Debug)]
+pub struct ExtForeignToplevelListState {
+    /// [`None`] once the compositor stopped sending toplevels.
+    list: Option<ext_foreign_toplevel_list_v1::ExtForeignToplevelListV1>,
+    toplevels: Vec<ExtForeignToplevel>,
+}
+
+impl ExtForeignToplevelListState {
+    /// Binds the `ext_foreign_toplevel_list_v1` global, after which the compositor starts sending the
+    /// toplevels.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support the ext foreign toplevel
+    /// list.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State:
+            Dispatch<ext_foreign_toplevel_list_v1::ExtForeignToplevelListV1, GlobalData> + 'static,
+    {
+        let list = globals.bind(qh, 1..=1, GlobalData)?;
+        Ok(Self { list: Some(list), toplevels: Vec::new() })
+    }
+
+    /// The mapped toplevels whose initial metadata is known.
+    pub fn toplevels(&self) -> impl Iterator<Item = &ExtForeignToplevel> {
+        self.toplevels.iter().filter(|toplevel| toplevel.info().is_some())
+    }
+
+    /// Whether the compositor stopped sending toplevels, after which all toplevels were closed.
+    pub fn is_finished(&self) -> bool {
+        self.list.is_none()
+    }
+
+    /// Request the compositor to stop sending toplevels.
+    ///
+    /// Events that are in flight are still delivered, then all remaining toplevels are closed.
+    pub fn stop(&self) {
+        if let Some(list) = &self.list {
+            list.stop();
+        }
+    }
+}
+
+/// A toplevel of another client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtForeignToplevel(ext_foreign_toplevel_handle_v1::ExtForeignToplevelHandleV1);
+
+impl ExtForeignToplevel {
+    pub fn handle(&self) -> &ext_foreign_toplevel_handle_v1::ExtForeignToplevelHandleV1 {
+        &self.0
+    }
+
+    /// The metadata of the toplevel, or [`None`] until the compositor sent it.
+    ///
+    /// The [`identifier`](ToplevelInfo::identifier) is always set.
+    pub fn info(&self) -> Option<ToplevelInfo> {
+        self.data().inner.lock().unwrap().current.clone()
+    }
+
+    fn data(&self) -> &ExtForeignToplevelData {
+        self.0.data::<ExtForeignToplevelData>().unwrap()
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct ExtForeignToplevelData {
+    inner: Mutex<ExtForeignToplevelInner>,
+}
+
+#[derive(Debug, Default)]
+struct ExtForeignToplevelInner {
+    /// The metadata applied by the last done event.
+    current: Option<ToplevelInfo>,
+    /// The metadata received since the last done event.
+    pending: Option<ToplevelInfo>,
+}
+
+impl ExtForeignToplevelInner {
+    fn pending(&mut self) -> &mut ToplevelInfo {
+        let current = &self.current;
+        self.pending.get_or_insert_with(|| current.clone().unwrap_or_else(ToplevelInfo::new))
+    }
+}
+
+impl<D> Dispatch<ext_foreign_toplevel_list_v1::ExtForeignToplevelListV1, GlobalData, D>
+    for ExtForeignToplevelListState
+where
+    D: Dispatch<ext_foreign_toplevel_list_v1::ExtForeignToplevelListV1, GlobalData>
+        + Dispatch<ext_foreign_toplevel_handle_v1::ExtForeignToplevelHandleV1, ExtForeignToplevelData>
+        + ExtForeignToplevelListHandler
+        + 'static,
+{
+    event_created_child!(D, ext_foreign_toplevel_list_v1::ExtForeignToplevelListV1, [
+        ext_foreign_toplevel_list_v1::EVT_TOPLEVEL_OPCODE => (ext_foreign_toplevel_handle_v1::ExtForeignToplevelHandleV1, Default::default())
+    ]);
+
+    fn event(
+        state: &mut D,
+        list: &ext_foreign_toplevel_list_v1::ExtForeignToplevelListV1,
+        event: ext_foreign_toplevel_list_v1::Event,
+        _: &GlobalData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        match event {
+            ext_foreign_toplevel_list_v1::Event::Toplevel { toplevel } => {
+                state
+                    .ext_foreign_toplevel_list_state()
+                    .toplevels
+                    .push(ExtForeignToplevel(toplevel));
+            }
+
+            ext_foreign_toplevel_list_v1::Event::Finished => {
+                let list_state = state.ext_foreign_toplevel_list_state();
+                list_state.list = None;
+                let toplevels = std::mem::take(&mut list_state.toplevels);
+
+                // The handles are independent of the list, but no longer useful.
+                for toplevel in toplevels {
+                    if toplevel.info().is_some() {
+                        state.toplevel_closed(conn, qh, &toplevel);
+                    }
+                    toplevel.0.destroy();
+                }
+
+                list.destroy();
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D>
+    Dispatch<ext_foreign_toplevel_handle_v1::ExtForeignToplevelHandleV1, ExtForeignToplevelData, D>
+    for ExtForeignToplevelListState
+where
+    D: Dispatch<ext_foreign_toplevel_handle_v1::ExtForeignToplevelHandleV1, ExtForeignToplevelData>
+        + ExtForeignToplevelListHandler,
+{
+    fn event(
+        state: &mut D,
+        handle: &ext_foreign_toplevel_handle_v1::ExtForeignToplevelHandleV1,
+        event: ext_foreign_toplevel_handle_v1::Event,
+        data: &ExtForeignToplevelData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let toplevel = ExtForeignToplevel(handle.clone());
+        let mut inner = data.inner.lock().unwrap();
+
+        match event {
+            ext_foreign_toplevel_handle_v1::Event::Identifier { identifier } => {
+                inner.pending().identifier = Some(identifier);
+            }
+
+            ext_foreign_toplevel_handle_v1::Event::Title { title } => {
+                inner.pending().title = title;
+            }
+
+            ext_foreign_toplevel_handle_v1::Event::AppId { app_id } => {
+                inner.pending().app_id = app_id;
+            }
+
+            ext_foreign_toplevel_handle_v1::Event::Done => {
+                let pending = match inner.pending.take() {
+                    Some(pending) => pending,
+                    None => return,
+                };
+
+                let is_new = inner.current.is_none();
+                inner.current = Some(pending);
+                drop(inner);
+
+                if is_new {
+                    state.new_toplevel(conn, qh, &toplevel);
+                } else {
+                    state.update_toplevel(conn, qh, &toplevel);
+                }
+            }
+
+            ext_foreign_toplevel_handle_v1::Event::Closed => {
+                let was_announced = inner.current.is_some();
+                drop(inner);
+
+                state.ext_foreign_toplevel_list_state().toplevels.retain(|t| t != &toplevel);
+                if was_announced {
+                    state.toplevel_closed(conn, qh, &toplevel);
+                }
+                handle.destroy();
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_ext_foreign_toplevel_list {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::ext::foreign_toplevel_list::v1::client::ext_foreign_toplevel_list_v1::ExtForeignToplevelListV1: $crate::globals::GlobalData
+        ] => $crate::foreign_toplevel::ext::ExtForeignToplevelListState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::ext::foreign_toplevel_list::v1::client::ext_foreign_toplevel_handle_v1::ExtForeignToplevelHandleV1: $crate::foreign_toplevel::ext::ExtForeignToplevelData
+        ] => $crate::foreign_toplevel::ext::ExtForeignToplevelListState);
+    };
+}
@@ -0,0 +1,52 @@
+//! ## Toplevels of other clients
+//!
+//! Taskbars, docks and window switchers list the toplevels of other clients. This is done with the
+//! wlroots protocol, which can also manage them, see [`wlr`], or with the read-only standard protocol
+//! compositors are moving to, see [`ext`].
+//!
+//! Both report the metadata of a toplevel as a [`ToplevelInfo`], so an application can support them
+//! with the same code.
+
+use bitflags::bitflags;
+use wayland_client::protocol::wl_output;
+
+pub mod ext;
+pub mod wlr;
+
+bitflags! {
+    /// The state of a toplevel of another client.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct ToplevelState: u32 {
+        const MAXIMIZED = 1;
+        const MINIMIZED = 1 << 1;
+        /// The toplevel has keyboard focus.
+        const ACTIVATED = 1 << 2;
+        const FULLSCREEN = 1 << 3;
+    }
+}
+
+/// The cached metadata of a toplevel of another client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ToplevelInfo {
+    /// The stable identifier of the toplevel, or [`None`] if the protocol does not provide one.
+    pub identifier: Option<String>,
+    pub title: String,
+    pub app_id: String,
+    /// Always empty for protocols which do not report the state.
+    pub state: ToplevelState,
+    /// The outputs the toplevel is visible on, always empty for protocols which do not report them.
+    pub outputs: Vec<wl_output::WlOutput>,
+}
+
+impl ToplevelInfo {
+    pub(crate) fn new() -> Self {
+        Self {
+            identifier: None,
+            title: String::new(),
+            app_id: String::new(),
+            state: ToplevelState::empty(),
+            outputs: Vec::new(),
+        }
+    }
+}
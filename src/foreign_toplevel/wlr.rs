@@ -0,0 +1,368 @@
+//! ## wlr foreign toplevel management
+//!
+//! The `zwlr_foreign_toplevel_manager_v1` global lists the toplevels of all clients, and lets the
+//! client activate, maximize, minimize or close them.
+//!
+//! [`ForeignToplevelState`] keeps track of the toplevels. Their metadata is applied atomically when the
+//! compositor is done sending a change, and reported to [`ForeignToplevelHandler`].
+
+use std::{convert::TryInto, sync::Mutex};
+
+use wayland_client::{
+    event_created_child,
+    globals::{BindError, GlobalList},
+    protocol::{wl_output, wl_seat, wl_surface},
+    Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::{
+    zwlr_foreign_toplevel_handle_v1, zwlr_foreign_toplevel_manager_v1,
+};
+
+use crate::globals::GlobalData;
+
+use super::{ToplevelInfo, ToplevelState};
+
+/// Handler for toplevels listed by wlr foreign toplevel management.
+pub trait ForeignToplevelHandler: Sized {
+    fn foreign_toplevel_state(&mut self) -> &mut ForeignToplevelState;
+
+    /// A toplevel was mapped, and its initial metadata is known.
+    fn new_toplevel(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        toplevel: &ForeignToplevel,
+    );
+
+    /// The metadata of a toplevel changed.
+    fn update_toplevel(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        toplevel: &ForeignToplevel,
+    );
+
+    /// A toplevel was unmapped.
+    ///
+    /// This is also called for all toplevels when the compositor stops sending toplevels, after
+    /// [`ForeignToplevelState::stop`] or when the global is removed.
+    fn toplevel_closed(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        toplevel: &ForeignToplevel,
+    );
+}
+
+/// The wlr foreign toplevel manager global and the toplevels it lists.
+#[derive(Debug)]
+pub struct ForeignToplevelState {
+    /// [`None`] once the compositor stopped sending toplevels.
+    manager: Option<zwlr_foreign_toplevel_manager_v1::ZwlrForeignToplevelManagerV1>,
+    toplevels: Vec<ForeignToplevel>,
+}
+
+impl ForeignToplevelState {
+    /// Binds the `zwlr_foreign_toplevel_manager_v1` global, after which the compositor starts sending
+    /// the toplevels.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support wlr foreign toplevel
+    /// management.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State: Dispatch<zwlr_foreign_toplevel_manager_v1::ZwlrForeignToplevelManagerV1, GlobalData>
+            + 'static,
+    {
+        let manager = globals.bind(qh, 1..=3, GlobalData)?;
+        Ok(Self { manager: Some(manager), toplevels: Vec::new() })
+    }
+
+    /// The mapped toplevels whose initial metadata is known.
+    pub fn toplevels(&self) -> impl Iterator<Item = &ForeignToplevel> {
+        self.toplevels.iter().filter(|toplevel| toplevel.info().is_some())
+    }
+
+    /// Whether the compositor stopped sending toplevels, after which all toplevels were closed.
+    pub fn is_finished(&self) -> bool {
+        self.manager.is_none()
+    }
+
+    /// Request the compositor to stop sending toplevels.
+    ///
+    /// Events that are in flight are still delivered, then all remaining toplevels are closed.
+    pub fn stop(&self) {
+        if let Some(manager) = &self.manager {
+            manager.stop();
+        }
+    }
+}
+
+/// A toplevel of another client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignToplevel(zwlr_foreign_toplevel_handle_v1::ZwlrForeignToplevelHandleV1);
+
+impl ForeignToplevel {
+    pub fn handle(&self) -> &zwlr_foreign_toplevel_handle_v1::ZwlrForeignToplevelHandleV1 {
+        &self.0
+    }
+
+    /// The metadata of the toplevel, or [`None`] until the compositor sent it.
+    pub fn info(&self) -> Option<ToplevelInfo> {
+        self.data().inner.lock().unwrap().current.clone()
+    }
+
+    /// The toplevel this toplevel is a child of, such as the main window of a dialog.
+    pub fn parent(&self) -> Option<ForeignToplevel> {
+        self.data().inner.lock().unwrap().current_parent.clone()
+    }
+
+    /// Request to activate the toplevel, giving it the keyboard focus of a seat.
+    pub fn activate(&self, seat: &wl_seat::WlSeat) {
+        self.0.activate(seat);
+    }
+
+    /// Request to maximize or unmaximize the toplevel.
+    pub fn set_maximized(&self, maximized: bool) {
+        if maximized {
+            self.0.set_maximized();
+        } else {
+            self.0.unset_maximized();
+        }
+    }
+
+    /// Request to minimize or unminimize the toplevel.
+    pub fn set_minimized(&self, minimized: bool) {
+        if minimized {
+            self.0.set_minimized();
+        } else {
+            self.0.unset_minimized();
+        }
+    }
+
+    /// Request to make the toplevel fullscreen, on an output or on an output chosen by the compositor.
+    ///
+    /// Does nothing if the compositor only supports version 1 of the protocol.
+    pub fn set_fullscreen(&self, output: Option<&wl_output::WlOutput>) {
+        if self.0.version() >= 2 {
+            self.0.set_fullscreen(output);
+        }
+    }
+
+    /// Request the toplevel to leave fullscreen.
+    ///
+    /// Does nothing if the compositor only supports version 1 of the protocol.
+    pub fn unset_fullscreen(&self) {
+        if self.0.version() >= 2 {
+            self.0.unset_fullscreen();
+        }
+    }
+
+    /// Request to close the toplevel.
+    ///
+    /// The client owning the toplevel may ignore this, or ask the user first.
+    pub fn close(&self) {
+        self.0.close();
+    }
+
+    /// Set the area of a surface of this client which represents the toplevel, such as its taskbar
+    /// entry, relative to the surface. The compositor may use it as the target of the minimize animation.
+    pub fn set_rectangle(
+        &self,
+        surface: &wl_surface::WlSurface,
+        (x, y, width, height): (i32, i32, i32, i32),
+    ) {
+        self.0.set_rectangle(surface, x, y, width, height);
+    }
+
+    fn data(&self) -> &ForeignToplevelData {
+        self.0.data::<ForeignToplevelData>().unwrap()
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct ForeignToplevelData {
+    inner: Mutex<ForeignToplevelInner>,
+}
+
+#[derive(Debug, Default)]
+struct ForeignToplevelInner {
+    /// The metadata applied by the last done event.
+    current: Option<ToplevelInfo>,
+    current_parent: Option<ForeignToplevel>,
+    /// The metadata received since the last done event.
+    pending: Option<ToplevelInfo>,
+    pending_parent: Option<Option<ForeignToplevel>>,
+}
+
+impl ForeignToplevelInner {
+    fn pending(&mut self) -> &mut ToplevelInfo {
+        let current = &self.current;
+        self.pending.get_or_insert_with(|| current.clone().unwrap_or_else(ToplevelInfo::new))
+    }
+}
+
+impl<D> Dispatch<zwlr_foreign_toplevel_manager_v1::ZwlrForeignToplevelManagerV1, GlobalData, D>
+    for ForeignToplevelState
+where
+    D: Dispatch<zwlr_foreign_toplevel_manager_v1::ZwlrForeignToplevelManagerV1, GlobalData>
+        + Dispatch<zwlr_foreign_toplevel_handle_v1::ZwlrForeignToplevelHandleV1, ForeignToplevelData>
+        + ForeignToplevelHandler
+        + 'static,
+{
+    event_created_child!(D, zwlr_foreign_toplevel_manager_v1::ZwlrForeignToplevelManagerV1, [
+        zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE => (zwlr_foreign_toplevel_handle_v1::ZwlrForeignToplevelHandleV1, Default::default())
+    ]);
+
+    fn event(
+        state: &mut D,
+        _: &zwlr_foreign_toplevel_manager_v1::ZwlrForeignToplevelManagerV1,
+        event: zwlr_foreign_toplevel_manager_v1::Event,
+        _: &GlobalData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        match event {
+            zwlr_foreign_toplevel_manager_v1::Event::Toplevel { toplevel } => {
+                state.foreign_toplevel_state().toplevels.push(ForeignToplevel(toplevel));
+            }
+
+            zwlr_foreign_toplevel_manager_v1::Event::Finished => {
+                let foreign_toplevel_state = state.foreign_toplevel_state();
+                foreign_toplevel_state.manager = None;
+                let toplevels = std::mem::take(&mut foreign_toplevel_state.toplevels);
+
+                // The handles are independent of the manager, but no longer useful.
+                for toplevel in toplevels {
+                    if toplevel.info().is_some() {
+                        state.toplevel_closed(conn, qh, &toplevel);
+                    }
+                    toplevel.0.destroy();
+                }
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D>
+    Dispatch<zwlr_foreign_toplevel_handle_v1::ZwlrForeignToplevelHandleV1, ForeignToplevelData, D>
+    for ForeignToplevelState
+where
+    D: Dispatch<zwlr_foreign_toplevel_handle_v1::ZwlrForeignToplevelHandleV1, ForeignToplevelData>
+        + ForeignToplevelHandler,
+{
+    fn event(
+        state: &mut D,
+        handle: &zwlr_foreign_toplevel_handle_v1::ZwlrForeignToplevelHandleV1,
+        event: zwlr_foreign_toplevel_handle_v1::Event,
+        data: &ForeignToplevelData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let toplevel = ForeignToplevel(handle.clone());
+        let mut inner = data.inner.lock().unwrap();
+
+        match event {
+            zwlr_foreign_toplevel_handle_v1::Event::Title { title } => {
+                inner.pending().title = title;
+            }
+
+            zwlr_foreign_toplevel_handle_v1::Event::AppId { app_id } => {
+                inner.pending().app_id = app_id;
+            }
+
+            zwlr_foreign_toplevel_handle_v1::Event::OutputEnter { output } => {
+                let outputs = &mut inner.pending().outputs;
+                if !outputs.contains(&output) {
+                    outputs.push(output);
+                }
+            }
+
+            zwlr_foreign_toplevel_handle_v1::Event::OutputLeave { output } => {
+                inner.pending().outputs.retain(|o| o != &output);
+            }
+
+            zwlr_foreign_toplevel_handle_v1::Event::State { state: states } => {
+                // The states are encoded as u32 of native endian. Unknown states from newer protocol
+                // versions are skipped.
+                inner.pending().state = states
+                    .chunks_exact(4)
+                    .map(|chunk| u32::from_ne_bytes(chunk.try_into().unwrap()))
+                    .flat_map(zwlr_foreign_toplevel_handle_v1::State::try_from)
+                    .fold(ToplevelState::empty(), |acc, state| {
+                        acc | match state {
+                            zwlr_foreign_toplevel_handle_v1::State::Maximized => {
+                                ToplevelState::MAXIMIZED
+                            }
+                            zwlr_foreign_toplevel_handle_v1::State::Minimized => {
+                                ToplevelState::MINIMIZED
+                            }
+                            zwlr_foreign_toplevel_handle_v1::State::Activated => {
+                                ToplevelState::ACTIVATED
+                            }
+                            zwlr_foreign_toplevel_handle_v1::State::Fullscreen => {
+                                ToplevelState::FULLSCREEN
+                            }
+                            _ => ToplevelState::empty(),
+                        }
+                    });
+            }
+
+            zwlr_foreign_toplevel_handle_v1::Event::Parent { parent } => {
+                inner.pending();
+                inner.pending_parent = Some(parent.map(ForeignToplevel));
+            }
+
+            zwlr_foreign_toplevel_handle_v1::Event::Done => {
+                let pending = match inner.pending.take() {
+                    Some(pending) => pending,
+                    // A toplevel may be announced without any metadata.
+                    None if inner.current.is_none() => ToplevelInfo::new(),
+                    None => return,
+                };
+
+                let is_new = inner.current.is_none();
+                inner.current = Some(pending);
+                if let Some(parent) = inner.pending_parent.take() {
+                    inner.current_parent = parent;
+                }
+                drop(inner);
+
+                if is_new {
+                    state.new_toplevel(conn, qh, &toplevel);
+                } else {
+                    state.update_toplevel(conn, qh, &toplevel);
+                }
+            }
+
+            zwlr_foreign_toplevel_handle_v1::Event::Closed => {
+                let was_announced = inner.current.is_some();
+                drop(inner);
+
+                state.foreign_toplevel_state().toplevels.retain(|t| t != &toplevel);
+                if was_announced {
+                    state.toplevel_closed(conn, qh, &toplevel);
+                }
+                handle.destroy();
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_foreign_toplevel {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_wlr::foreign_toplevel::v1::client::zwlr_foreign_toplevel_manager_v1::ZwlrForeignToplevelManagerV1: $crate::globals::GlobalData
+        ] => $crate::foreign_toplevel::wlr::ForeignToplevelState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_wlr::foreign_toplevel::v1::client::zwlr_foreign_toplevel_handle_v1::ZwlrForeignToplevelHandleV1: $crate::foreign_toplevel::wlr::ForeignToplevelData
+        ] => $crate::foreign_toplevel::wlr::ForeignToplevelState);
+    };
+}
@@ -0,0 +1,183 @@
+//! ## Fractional scaling
+//!
+//! The `wp_fractional_scale_manager_v1` global lets the compositor tell a surface its preferred scale
+//! as a fraction, such as 1.25 or 1.5, where [`CompositorHandler::scale_factor_changed`] only reports
+//! integer scales.
+//!
+//! A fractional scale can not be expressed with `wl_surface::set_buffer_scale`. Instead, the client
+//! keeps the buffer scale at 1, draws a buffer of the logical size multiplied by the scale, rounded
+//! to the nearest integer, and uses a `wp_viewport` with the logical size as destination so the
+//! compositor maps the buffer back to the logical size of the surface.
+//!
+//! [`CompositorHandler::scale_factor_changed`]: crate::compositor::CompositorHandler::scale_factor_changed
+
+use wayland_client::{
+    globals::{BindError, GlobalList},
+    protocol::wl_surface,
+    Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols::wp::fractional_scale::v1::client::{
+    wp_fractional_scale_manager_v1, wp_fractional_scale_v1,
+};
+
+use crate::{
+    compositor::SurfaceData,
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+};
+
+/// The denominator of the scales sent by the compositor.
+const SCALE_DENOMINATOR: f64 = 120.;
+
+/// Handler for fractional scale changes.
+pub trait FractionalScaleHandler: Sized {
+    /// The compositor prefers the surface to be drawn at a new scale.
+    ///
+    /// This is also called once after [`FractionalScaleState::fractional_scaling`], with the initial
+    /// scale of the surface.
+    fn preferred_scale(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        surface: &wl_surface::WlSurface,
+        scale: f64,
+    );
+}
+
+/// The fractional scale global.
+#[derive(Debug)]
+pub struct FractionalScaleState {
+    manager: wp_fractional_scale_manager_v1::WpFractionalScaleManagerV1,
+}
+
+impl FractionalScaleState {
+    /// Binds the `wp_fractional_scale_manager_v1` global.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support fractional scaling.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State: Dispatch<wp_fractional_scale_manager_v1::WpFractionalScaleManagerV1, GlobalData>
+            + 'static,
+    {
+        let manager = globals.bind(qh, 1..=1, GlobalData)?;
+        Ok(Self { manager })
+    }
+
+    /// Start receiving the preferred fractional scale of a surface.
+    ///
+    /// The scale is delivered to [`FractionalScaleHandler::preferred_scale`] and, if the surface was
+    /// created with [`SurfaceData`], is also available from [`SurfaceData::fractional_scale`].
+    ///
+    /// # Protocol errors
+    ///
+    /// Only one [`FractionalScale`] may exist for a surface at a time.
+    pub fn fractional_scaling<D>(
+        &self,
+        surface: &wl_surface::WlSurface,
+        qh: &QueueHandle<D>,
+    ) -> FractionalScale
+    where
+        D: Dispatch<wp_fractional_scale_v1::WpFractionalScaleV1, FractionalScaleData> + 'static,
+    {
+        let data = FractionalScaleData { surface: surface.clone() };
+        FractionalScale(self.manager.get_fractional_scale(surface, qh, data))
+    }
+}
+
+impl ProvidesBoundGlobal<wp_fractional_scale_manager_v1::WpFractionalScaleManagerV1, 1>
+    for FractionalScaleState
+{
+    fn bound_global(
+        &self,
+    ) -> Result<wp_fractional_scale_manager_v1::WpFractionalScaleManagerV1, GlobalError> {
+        Ok(self.manager.clone())
+    }
+}
+
+/// The fractional scale object of a surface.
+///
+/// The scale of the surface is no longer reported once this is dropped.
+#[derive(Debug)]
+pub struct FractionalScale(wp_fractional_scale_v1::WpFractionalScaleV1);
+
+impl FractionalScale {
+    pub fn fractional_scale(&self) -> &wp_fractional_scale_v1::WpFractionalScaleV1 {
+        &self.0
+    }
+
+    /// The surface this scale is reported for.
+    pub fn surface(&self) -> &wl_surface::WlSurface {
+        &self.0.data::<FractionalScaleData>().unwrap().surface
+    }
+}
+
+impl Drop for FractionalScale {
+    fn drop(&mut self) {
+        self.0.destroy();
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct FractionalScaleData {
+    surface: wl_surface::WlSurface,
+}
+
+impl<D> Dispatch<wp_fractional_scale_manager_v1::WpFractionalScaleManagerV1, GlobalData, D>
+    for FractionalScaleState
+where
+    D: Dispatch<wp_fractional_scale_manager_v1::WpFractionalScaleManagerV1, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &wp_fractional_scale_manager_v1::WpFractionalScaleManagerV1,
+        _: wp_fractional_scale_manager_v1::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("wp_fractional_scale_manager_v1 has no events")
+    }
+}
+
+impl<D> Dispatch<wp_fractional_scale_v1::WpFractionalScaleV1, FractionalScaleData, D>
+    for FractionalScaleState
+where
+    D: Dispatch<wp_fractional_scale_v1::WpFractionalScaleV1, FractionalScaleData>
+        + FractionalScaleHandler,
+{
+    fn event(
+        state: &mut D,
+        _: &wp_fractional_scale_v1::WpFractionalScaleV1,
+        event: wp_fractional_scale_v1::Event,
+        data: &FractionalScaleData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        match event {
+            wp_fractional_scale_v1::Event::PreferredScale { scale } => {
+                let scale = scale as f64 / SCALE_DENOMINATOR;
+                if let Some(surface_data) = data.surface.data::<SurfaceData>() {
+                    surface_data.set_fractional_scale(scale);
+                }
+                state.preferred_scale(conn, qh, &data.surface, scale);
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_fractional_scale {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::fractional_scale::v1::client::wp_fractional_scale_manager_v1::WpFractionalScaleManagerV1: $crate::globals::GlobalData
+        ] => $crate::fractional_scale::FractionalScaleState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::fractional_scale::v1::client::wp_fractional_scale_v1::WpFractionalScaleV1: $crate::fractional_scale::FractionalScaleData
+        ] => $crate::fractional_scale::FractionalScaleState);
+    };
+}
@@ -0,0 +1,298 @@
+//! ## wlr gamma control
+//!
+//! The `zwlr_gamma_control_manager_v1` global lets night light and color calibration tools set the
+//! gamma ramps of outputs.
+//!
+//! The gamma ramps of an output are controlled through a [`GammaControl`] created with
+//! [`GammaControlState::get_gamma_control`]. Once the compositor reported the size of the ramps to
+//! [`GammaControlHandler::gamma_size`], they are set with [`GammaControl::set_gamma`]. The ramps are
+//! restored by the compositor when the gamma control is destroyed or the client disconnects.
+
+use std::{
+    fs::File,
+    io::{self, Seek, SeekFrom, Write},
+    os::unix::io::AsFd,
+    sync::Mutex,
+};
+
+use wayland_client::{
+    globals::{BindError, GlobalList},
+    protocol::wl_output,
+    Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols_wlr::gamma_control::v1::client::{
+    zwlr_gamma_control_manager_v1, zwlr_gamma_control_v1,
+};
+
+use crate::{globals::GlobalData, shm::raw::RawPool};
+
+/// Handler for gamma controls.
+pub trait GammaControlHandler: Sized {
+    /// The number of entries each gamma ramp of the output must have.
+    fn gamma_size(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        control: &GammaControl,
+        size: u32,
+    );
+
+    /// The gamma control is no longer valid, for example because another client controls the gamma
+    /// of the output or the output was removed.
+    ///
+    /// The gamma control is destroyed after this returns.
+    fn failed(&mut self, conn: &Connection, qh: &QueueHandle<Self>, control: &GammaControl);
+}
+
+/// The gamma control manager global.
+#[derive(Debug)]
+pub struct GammaControlState {
+    manager: zwlr_gamma_control_manager_v1::ZwlrGammaControlManagerV1,
+}
+
+impl GammaControlState {
+    /// Binds the `zwlr_gamma_control_manager_v1` global.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support wlr gamma control.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State: Dispatch<zwlr_gamma_control_manager_v1::ZwlrGammaControlManagerV1, GlobalData>
+            + 'static,
+    {
+        let manager = globals.bind(qh, 1..=1, GlobalData)?;
+        Ok(Self { manager })
+    }
+
+    /// Take control of the gamma ramps of an output.
+    pub fn get_gamma_control<D>(
+        &self,
+        output: &wl_output::WlOutput,
+        qh: &QueueHandle<D>,
+    ) -> GammaControl
+    where
+        D: Dispatch<zwlr_gamma_control_v1::ZwlrGammaControlV1, GammaControlData> + 'static,
+    {
+        let data = GammaControlData { output: output.clone(), inner: Default::default() };
+        GammaControl(self.manager.get_gamma_control(output, qh, data))
+    }
+}
+
+/// An error that may occur when setting gamma ramps.
+#[derive(Debug, thiserror::Error)]
+pub enum GammaControlError {
+    /// Error while uploading the gamma ramps.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    /// The size of the gamma ramps is not known yet.
+    #[error("the gamma size of the output is not known yet")]
+    UnknownSize,
+
+    /// The gamma ramps do not have the size of the gamma ramps of the output.
+    #[error("the gamma ramps have {len} entries, but the output expects {size}")]
+    InvalidSize { len: usize, size: u32 },
+
+    /// The gamma control is no longer valid.
+    #[error("the gamma control failed")]
+    Failed,
+}
+
+/// The gamma control of an output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GammaControl(zwlr_gamma_control_v1::ZwlrGammaControlV1);
+
+impl GammaControl {
+    pub fn gamma_control(&self) -> &zwlr_gamma_control_v1::ZwlrGammaControlV1 {
+        &self.0
+    }
+
+    /// The output whose gamma is controlled.
+    pub fn output(&self) -> &wl_output::WlOutput {
+        &self.data().output
+    }
+
+    /// The number of entries each gamma ramp must have, or [`None`] until the compositor sent it.
+    pub fn gamma_size(&self) -> Option<u32> {
+        self.data().inner.lock().unwrap().gamma_size
+    }
+
+    /// Set the gamma ramps of the output.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the gamma size is not known yet or does not match the
+    /// ramps, if the gamma control failed, or if the ramps could not be uploaded.
+    pub fn set_gamma(&self, ramps: &GammaRamps) -> Result<(), GammaControlError> {
+        let inner = self.data().inner.lock().unwrap();
+        if inner.failed {
+            return Err(GammaControlError::Failed);
+        }
+
+        let size = inner.gamma_size.ok_or(GammaControlError::UnknownSize)?;
+        for ramp in [&ramps.red, &ramps.green, &ramps.blue] {
+            if ramp.len() != size as usize {
+                return Err(GammaControlError::InvalidSize { len: ramp.len(), size });
+            }
+        }
+
+        // The ramps are read as native endian u16, red then green then blue, from the start of the
+        // file.
+        let mut file = File::from(RawPool::create_shm_fd()?);
+        for ramp in [&ramps.red, &ramps.green, &ramps.blue] {
+            let bytes = ramp.iter().flat_map(|value| value.to_ne_bytes()).collect::<Vec<u8>>();
+            file.write_all(&bytes)?;
+        }
+        file.flush()?;
+        file.seek(SeekFrom::Start(0))?;
+
+        self.0.set_gamma(file.as_fd());
+        Ok(())
+    }
+
+    /// Release the control, which restores the original gamma ramps of the output.
+    pub fn destroy(&self) {
+        self.0.destroy();
+    }
+
+    fn data(&self) -> &GammaControlData {
+        self.0.data::<GammaControlData>().unwrap()
+    }
+}
+
+/// The red, green and blue gamma ramps of an output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GammaRamps {
+    pub red: Vec<u16>,
+    pub green: Vec<u16>,
+    pub blue: Vec<u16>,
+}
+
+impl GammaRamps {
+    /// Linear ramps, which leave the colors unchanged.
+    pub fn identity(size: u32) -> Self {
+        Self::from_temperature(size, 6500, 1.0)
+    }
+
+    /// Ramps tinting the output like white light of a color temperature in kelvin, dimmed by a
+    /// brightness between `0.0` and `1.0`.
+    ///
+    /// 6500 K is neutral, lower temperatures are warmer and higher ones are cooler.
+    pub fn from_temperature(size: u32, temperature: u32, brightness: f64) -> Self {
+        let (red, green, blue) = white_point(temperature);
+        let brightness = brightness.clamp(0.0, 1.0);
+        let ramp = |factor: f64| {
+            (0..size)
+                .map(|i| {
+                    let value = if size > 1 { f64::from(i) / f64::from(size - 1) } else { 1.0 };
+                    (value * factor * brightness * f64::from(u16::MAX)).round() as u16
+                })
+                .collect()
+        };
+
+        Self { red: ramp(red), green: ramp(green), blue: ramp(blue) }
+    }
+}
+
+/// The color of a black body at a temperature in kelvin, relative to the one at 6500 K.
+fn white_point(temperature: u32) -> (f64, f64, f64) {
+    // Approximation of the black body color in sRGB by Tanner Helland.
+    fn approximate(temperature: f64) -> (f64, f64, f64) {
+        let t = temperature / 100.0;
+        let red =
+            if t <= 66.0 { 255.0 } else { 329.698_727_446 * (t - 60.0).powf(-0.133_204_759_2) };
+        let green = if t <= 66.0 {
+            99.470_802_586_1 * t.ln() - 161.119_568_166_1
+        } else {
+            288.122_169_528_3 * (t - 60.0).powf(-0.075_514_849_2)
+        };
+        let blue = if t >= 66.0 {
+            255.0
+        } else if t <= 19.0 {
+            0.0
+        } else {
+            138.517_731_223_1 * (t - 10.0).ln() - 305.044_792_730_7
+        };
+
+        (red.clamp(0.0, 255.0), green.clamp(0.0, 255.0), blue.clamp(0.0, 255.0))
+    }
+
+    let (red, green, blue) = approximate(f64::from(temperature.clamp(1000, 25000)));
+    let (white_red, white_green, white_blue) = approximate(6500.0);
+    ((red / white_red).min(1.0), (green / white_green).min(1.0), (blue / white_blue).min(1.0))
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct GammaControlData {
+    output: wl_output::WlOutput,
+    inner: Mutex<GammaControlInner>,
+}
+
+#[derive(Debug, Default)]
+struct GammaControlInner {
+    gamma_size: Option<u32>,
+    failed: bool,
+}
+
+impl<D> Dispatch<zwlr_gamma_control_manager_v1::ZwlrGammaControlManagerV1, GlobalData, D>
+    for GammaControlState
+where
+    D: Dispatch<zwlr_gamma_control_manager_v1::ZwlrGammaControlManagerV1, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &zwlr_gamma_control_manager_v1::ZwlrGammaControlManagerV1,
+        _: zwlr_gamma_control_manager_v1::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("zwlr_gamma_control_manager_v1 has no events")
+    }
+}
+
+impl<D> Dispatch<zwlr_gamma_control_v1::ZwlrGammaControlV1, GammaControlData, D>
+    for GammaControlState
+where
+    D: Dispatch<zwlr_gamma_control_v1::ZwlrGammaControlV1, GammaControlData> + GammaControlHandler,
+{
+    fn event(
+        state: &mut D,
+        proxy: &zwlr_gamma_control_v1::ZwlrGammaControlV1,
+        event: zwlr_gamma_control_v1::Event,
+        data: &GammaControlData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let control = GammaControl(proxy.clone());
+
+        match event {
+            zwlr_gamma_control_v1::Event::GammaSize { size } => {
+                data.inner.lock().unwrap().gamma_size = Some(size);
+                state.gamma_size(conn, qh, &control, size);
+            }
+
+            zwlr_gamma_control_v1::Event::Failed => {
+                data.inner.lock().unwrap().failed = true;
+                state.failed(conn, qh, &control);
+                proxy.destroy();
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_gamma_control {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_wlr::gamma_control::v1::client::zwlr_gamma_control_manager_v1::ZwlrGammaControlManagerV1: $crate::globals::GlobalData
+        ] => $crate::gamma_control::GammaControlState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_wlr::gamma_control::v1::client::zwlr_gamma_control_v1::ZwlrGammaControlV1: $crate::gamma_control::GammaControlData
+        ] => $crate::gamma_control::GammaControlState);
+    };
+}
@@ -25,6 +25,11 @@ use wayland_client::Proxy;
 /// earlier versions of the protocol or use [`ProvidesBoundGlobal::with_min_version`] to produce an
 /// error in this case.
 ///
+/// `API_COMPAT_VERSION` is the oldest API a caller relies on, not the version that was bound. A
+/// caller that can make use of later versions when available, for example to send a request added
+/// in a later version, should require the lowest compatible version and check
+/// [`ProvidesBoundGlobal::version`] at runtime.
+///
 /// It is permitted to implement `ProvidesBoundGlobal` for versions that are higher than the
 /// maximum version you bind.  When rustc gains the ability to constrain const parameters with
 /// integer bounds (`where API_COMPAT_VERSION >= 5`), implementations of this trait should be
@@ -32,6 +37,12 @@ use wayland_client::Proxy;
 /// updates be done in lock-step.
 pub trait ProvidesBoundGlobal<I: Proxy, const API_COMPAT_VERSION: u32> {
     fn bound_global(&self) -> Result<I, GlobalError>;
+
+    /// The version the global was actually bound with.
+    fn version(&self) -> Result<u32, GlobalError> {
+        self.bound_global().map(|proxy| proxy.version())
+    }
+
     fn with_min_version(&self, version: u32) -> Result<I, GlobalError> {
         let proxy = self.bound_global()?;
         if proxy.version() < version {
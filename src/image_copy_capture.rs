@@ -0,0 +1,650 @@
+//! ## ext image copy capture
+//!
+//! The `ext_image_copy_capture_manager_v1` global is the standard successor of
+//! [`screencopy`](crate::screencopy). It captures an image capture source, which is created from an
+//! output or from a toplevel listed by [`ext`](crate::foreign_toplevel::ext) foreign toplevel list.
+//!
+//! A [`CaptureSession`] is created with [`ImageCopyCaptureState::create_output_session`] or
+//! [`ImageCopyCaptureState::create_toplevel_session`]. The compositor describes the buffers it can copy
+//! into with [`ImageCopyCaptureHandler::buffer_constraints`], which is called again whenever they
+//! change. Each frame is then captured with [`CaptureSession::create_frame`], into a shm buffer
+//! allocated with [`CaptureFrame::capture_to_slotpool`] or into any other buffer, such as a dmabuf
+//! created with [`DmabufParams`](crate::dmabuf::DmabufParams), with [`CaptureFrame::capture`].
+
+use std::{convert::TryInto, sync::Mutex, time::Duration};
+
+use wayland_client::{
+    globals::{BindError, GlobalList},
+    protocol::{wl_buffer, wl_output, wl_shm},
+    Connection, Dispatch, Proxy, QueueHandle, WEnum,
+};
+use wayland_protocols::ext::{
+    image_capture_source::v1::client::{
+        ext_foreign_toplevel_image_capture_source_manager_v1, ext_image_capture_source_v1,
+        ext_output_image_capture_source_manager_v1,
+    },
+    image_copy_capture::v1::client::{
+        ext_image_copy_capture_frame_v1, ext_image_copy_capture_manager_v1,
+        ext_image_copy_capture_session_v1,
+    },
+};
+
+use crate::{
+    dmabuf::dev_t,
+    error::GlobalError,
+    foreign_toplevel::ext::ExtForeignToplevel,
+    globals::GlobalData,
+    shm::slot::{Buffer, CreateBufferError, SlotPool},
+};
+
+pub use ext_image_copy_capture_frame_v1::FailureReason;
+
+/// Handler for image copy capture sessions and frames.
+pub trait ImageCopyCaptureHandler: Sized {
+    /// The buffers the compositor can copy the frames of a session into are known or changed.
+    ///
+    /// Frames must only be captured after this was called once. Buffers allocated for the previous
+    /// constraints should be reallocated.
+    fn buffer_constraints(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        session: &CaptureSession,
+        constraints: &BufferConstraints,
+    );
+
+    /// The session stopped, for example because the captured toplevel was closed.
+    ///
+    /// No more frames can be captured, the session is destroyed after this returns.
+    fn session_stopped(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        session: &CaptureSession,
+    );
+
+    /// A frame was copied into its buffer.
+    fn frame_ready(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        frame: &CaptureFrame,
+        info: FrameInfo,
+    );
+
+    /// A frame could not be copied.
+    ///
+    /// With [`FailureReason::BufferConstraints`], the buffer should be reallocated before capturing the
+    /// next frame.
+    fn frame_failed(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        frame: &CaptureFrame,
+        reason: WEnum<FailureReason>,
+    );
+}
+
+/// The image copy capture global and the image capture source globals.
+#[derive(Debug)]
+pub struct ImageCopyCaptureState {
+    manager: ext_image_copy_capture_manager_v1::ExtImageCopyCaptureManagerV1,
+    output_source_manager:
+        Option<ext_output_image_capture_source_manager_v1::ExtOutputImageCaptureSourceManagerV1>,
+    toplevel_source_manager: Option<
+        ext_foreign_toplevel_image_capture_source_manager_v1::ExtForeignToplevelImageCaptureSourceManagerV1,
+    >,
+}
+
+impl ImageCopyCaptureState {
+    /// Binds the `ext_image_copy_capture_manager_v1` global, and the output and toplevel image
+    /// capture source globals if they are available.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support image copy capture.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State: Dispatch<ext_image_copy_capture_manager_v1::ExtImageCopyCaptureManagerV1, GlobalData>
+            + Dispatch<
+                ext_output_image_capture_source_manager_v1::ExtOutputImageCaptureSourceManagerV1,
+                GlobalData,
+            > + Dispatch<
+                ext_foreign_toplevel_image_capture_source_manager_v1::ExtForeignToplevelImageCaptureSourceManagerV1,
+                GlobalData,
+            > + 'static,
+    {
+        let manager = globals.bind(qh, 1..=1, GlobalData)?;
+        let output_source_manager = globals.bind(qh, 1..=1, GlobalData).ok();
+        let toplevel_source_manager = globals.bind(qh, 1..=1, GlobalData).ok();
+        Ok(Self { manager, output_source_manager, toplevel_source_manager })
+    }
+
+    /// Create a session capturing an output, with or without the cursor drawn on top of it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support output image capture
+    /// sources.
+    pub fn create_output_session<D>(
+        &self,
+        output: &wl_output::WlOutput,
+        paint_cursors: bool,
+        qh: &QueueHandle<D>,
+    ) -> Result<CaptureSession, GlobalError>
+    where
+        D: Dispatch<ext_image_capture_source_v1::ExtImageCaptureSourceV1, GlobalData>
+            + Dispatch<
+                ext_image_copy_capture_session_v1::ExtImageCopyCaptureSessionV1,
+                CaptureSessionData,
+            > + 'static,
+    {
+        let manager = self
+            .output_source_manager
+            .as_ref()
+            .ok_or(GlobalError::MissingGlobal("ext_output_image_capture_source_manager_v1"))?;
+        let source = manager.create_source(output, qh, GlobalData);
+        Ok(self.create_session(source, paint_cursors, qh))
+    }
+
+    /// Create a session capturing a toplevel of another client, with or without the cursor drawn on
+    /// top of it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support toplevel image capture
+    /// sources.
+    pub fn create_toplevel_session<D>(
+        &self,
+        toplevel: &ExtForeignToplevel,
+        paint_cursors: bool,
+        qh: &QueueHandle<D>,
+    ) -> Result<CaptureSession, GlobalError>
+    where
+        D: Dispatch<ext_image_capture_source_v1::ExtImageCaptureSourceV1, GlobalData>
+            + Dispatch<
+                ext_image_copy_capture_session_v1::ExtImageCopyCaptureSessionV1,
+                CaptureSessionData,
+            > + 'static,
+    {
+        let manager = self.toplevel_source_manager.as_ref().ok_or(GlobalError::MissingGlobal(
+            "ext_foreign_toplevel_image_capture_source_manager_v1",
+        ))?;
+        let source = manager.create_source(toplevel.handle(), qh, GlobalData);
+        Ok(self.create_session(source, paint_cursors, qh))
+    }
+
+    fn create_session<D>(
+        &self,
+        source: ext_image_capture_source_v1::ExtImageCaptureSourceV1,
+        paint_cursors: bool,
+        qh: &QueueHandle<D>,
+    ) -> CaptureSession
+    where
+        D: Dispatch<
+                ext_image_copy_capture_session_v1::ExtImageCopyCaptureSessionV1,
+                CaptureSessionData,
+            > + 'static,
+    {
+        let options = if paint_cursors {
+            ext_image_copy_capture_manager_v1::Options::PaintCursors
+        } else {
+            ext_image_copy_capture_manager_v1::Options::empty()
+        };
+        let session =
+            self.manager.create_session(&source, options, qh, CaptureSessionData::default());
+        // The session keeps capturing the source once it is created.
+        source.destroy();
+        CaptureSession(session)
+    }
+}
+
+/// The buffers the compositor can copy the frames of a session into.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BufferConstraints {
+    /// The size the buffers must have, as `(width, height)`.
+    pub size: (u32, u32),
+    /// The supported shm formats.
+    pub shm_formats: Vec<wl_shm::Format>,
+    /// The device dmabufs must be allocated on, or [`None`] if dmabufs are not supported.
+    pub dmabuf_device: Option<dev_t>,
+    /// The supported dmabuf formats, as DRM fourcc codes with their supported modifiers.
+    pub dmabuf_formats: Vec<(u32, Vec<u64>)>,
+}
+
+/// The metadata of a frame that was copied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FrameInfo {
+    /// The transform to apply to the buffer to display it as the source is displayed.
+    pub transform: WEnum<wl_output::Transform>,
+    /// The regions of the buffer that changed since the last frame, as `(x, y, width, height)`.
+    pub damage: Vec<(i32, i32, i32, i32)>,
+    /// The time the frame was presented at, in the `CLOCK_MONOTONIC` clock.
+    pub presentation_time: Option<Duration>,
+}
+
+/// An error that may occur when capturing a frame into a buffer of a [`SlotPool`].
+#[derive(Debug, thiserror::Error)]
+pub enum CaptureError {
+    /// The buffer constraints of the session are not known yet.
+    #[error("The buffer constraints of the session are not known yet")]
+    NoConstraints,
+
+    /// The compositor did not advertise a shm format with 32 bits per pixel.
+    #[error("The compositor did not advertise a supported shm format")]
+    NoShmFormat,
+
+    /// The frame was already captured.
+    #[error("The frame was already captured")]
+    AlreadyCaptured,
+
+    /// The buffer could not be allocated.
+    #[error(transparent)]
+    CreateBuffer(#[from] CreateBufferError),
+}
+
+/// A session capturing an output or a toplevel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureSession(ext_image_copy_capture_session_v1::ExtImageCopyCaptureSessionV1);
+
+impl CaptureSession {
+    pub fn session(&self) -> &ext_image_copy_capture_session_v1::ExtImageCopyCaptureSessionV1 {
+        &self.0
+    }
+
+    /// The buffer constraints of the session, or [`None`] until the compositor sent them.
+    pub fn buffer_constraints(&self) -> Option<BufferConstraints> {
+        self.data().inner.lock().unwrap().current.clone()
+    }
+
+    /// Whether the session stopped, after which no frames can be captured.
+    pub fn is_stopped(&self) -> bool {
+        self.data().inner.lock().unwrap().stopped
+    }
+
+    /// Create a frame to capture the next image of the source.
+    ///
+    /// Only one frame may exist at a time per session.
+    pub fn create_frame<D>(&self, qh: &QueueHandle<D>) -> CaptureFrame
+    where
+        D: Dispatch<ext_image_copy_capture_frame_v1::ExtImageCopyCaptureFrameV1, CaptureFrameData>
+            + 'static,
+    {
+        let data = CaptureFrameData { session: self.clone(), inner: Default::default() };
+        CaptureFrame(self.0.create_frame(qh, data))
+    }
+
+    /// Stop the session.
+    pub fn destroy(&self) {
+        self.0.destroy();
+    }
+
+    fn data(&self) -> &CaptureSessionData {
+        self.0.data::<CaptureSessionData>().unwrap()
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct CaptureSessionData {
+    inner: Mutex<CaptureSessionInner>,
+}
+
+#[derive(Debug, Default)]
+struct CaptureSessionInner {
+    /// The constraints applied by the last done event.
+    current: Option<BufferConstraints>,
+    /// The constraints received since the last done event.
+    pending: BufferConstraints,
+    stopped: bool,
+}
+
+/// A frame being captured.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureFrame(ext_image_copy_capture_frame_v1::ExtImageCopyCaptureFrameV1);
+
+impl CaptureFrame {
+    pub fn frame(&self) -> &ext_image_copy_capture_frame_v1::ExtImageCopyCaptureFrameV1 {
+        &self.0
+    }
+
+    /// The session the frame belongs to.
+    pub fn session(&self) -> &CaptureSession {
+        &self.data().session
+    }
+
+    /// Capture the frame into a buffer matching the constraints of the session.
+    ///
+    /// The whole buffer is marked as damaged, so the compositor copies all of it.
+    pub fn capture(&self, buffer: &wl_buffer::WlBuffer) {
+        self.0.attach_buffer(buffer);
+        self.0.damage_buffer(0, 0, i32::MAX, i32::MAX);
+        self.0.capture();
+    }
+
+    /// Capture the frame into a buffer matching the constraints of the session, which only needs to
+    /// be updated in the given regions as `(x, y, width, height)`, for example because the buffer is
+    /// reused from the previous frame.
+    pub fn capture_with_damage(
+        &self,
+        buffer: &wl_buffer::WlBuffer,
+        damage: &[(i32, i32, i32, i32)],
+    ) {
+        self.0.attach_buffer(buffer);
+        for &(x, y, width, height) in damage {
+            self.0.damage_buffer(x, y, width, height);
+        }
+        self.0.capture();
+    }
+
+    /// Allocate a shm buffer matching the constraints of the session from a pool, and capture the frame
+    /// into it.
+    ///
+    /// Only shm formats with 32 bits per pixel are used. Once the frame is ready, its content is
+    /// available with [`CaptureFrame::canvas`].
+    pub fn capture_to_slotpool(&self, pool: &mut SlotPool) -> Result<(), CaptureError> {
+        let constraints = self.session().buffer_constraints().ok_or(CaptureError::NoConstraints)?;
+        let format = constraints
+            .shm_formats
+            .iter()
+            .copied()
+            .find(|format| {
+                matches!(
+                    format,
+                    wl_shm::Format::Argb8888
+                        | wl_shm::Format::Xrgb8888
+                        | wl_shm::Format::Abgr8888
+                        | wl_shm::Format::Xbgr8888
+                )
+            })
+            .ok_or(CaptureError::NoShmFormat)?;
+
+        let mut inner = self.data().inner.lock().unwrap();
+        if inner.buffer.is_some() {
+            return Err(CaptureError::AlreadyCaptured);
+        }
+
+        let (width, height) = (constraints.size.0 as i32, constraints.size.1 as i32);
+        let (buffer, _) = pool.create_buffer(width, height, width * 4, format)?;
+        self.capture(buffer.wl_buffer());
+        inner.buffer = Some(buffer);
+        Ok(())
+    }
+
+    /// The content of the buffer allocated by [`CaptureFrame::capture_to_slotpool`].
+    ///
+    /// This is only meaningful after [`ImageCopyCaptureHandler::frame_ready`] was called.
+    pub fn canvas<'pool>(&self, pool: &'pool mut SlotPool) -> Option<&'pool mut [u8]> {
+        self.data().inner.lock().unwrap().buffer.as_ref()?.canvas(pool)
+    }
+
+    /// Take the buffer allocated by [`CaptureFrame::capture_to_slotpool`], for example to reuse it for
+    /// the next frame with [`CaptureFrame::capture_with_damage`].
+    pub fn take_buffer(&self) -> Option<Buffer> {
+        self.data().inner.lock().unwrap().buffer.take()
+    }
+
+    fn data(&self) -> &CaptureFrameData {
+        self.0.data::<CaptureFrameData>().unwrap()
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct CaptureFrameData {
+    session: CaptureSession,
+    inner: Mutex<CaptureFrameInner>,
+}
+
+#[derive(Debug)]
+struct CaptureFrameInner {
+    transform: WEnum<wl_output::Transform>,
+    damage: Vec<(i32, i32, i32, i32)>,
+    presentation_time: Option<Duration>,
+    buffer: Option<Buffer>,
+}
+
+impl Default for CaptureFrameInner {
+    fn default() -> Self {
+        Self {
+            transform: WEnum::Value(wl_output::Transform::Normal),
+            damage: Vec::new(),
+            presentation_time: None,
+            buffer: None,
+        }
+    }
+}
+
+impl<D> Dispatch<ext_image_copy_capture_manager_v1::ExtImageCopyCaptureManagerV1, GlobalData, D>
+    for ImageCopyCaptureState
+where
+    D: Dispatch<ext_image_copy_capture_manager_v1::ExtImageCopyCaptureManagerV1, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &ext_image_copy_capture_manager_v1::ExtImageCopyCaptureManagerV1,
+        _: ext_image_copy_capture_manager_v1::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("ext_image_copy_capture_manager_v1 has no events")
+    }
+}
+
+impl<D>
+    Dispatch<
+        ext_output_image_capture_source_manager_v1::ExtOutputImageCaptureSourceManagerV1,
+        GlobalData,
+        D,
+    > for ImageCopyCaptureState
+where
+    D: Dispatch<
+        ext_output_image_capture_source_manager_v1::ExtOutputImageCaptureSourceManagerV1,
+        GlobalData,
+    >,
+{
+    fn event(
+        _: &mut D,
+        _: &ext_output_image_capture_source_manager_v1::ExtOutputImageCaptureSourceManagerV1,
+        _: ext_output_image_capture_source_manager_v1::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("ext_output_image_capture_source_manager_v1 has no events")
+    }
+}
+
+impl<D>
+    Dispatch<
+        ext_foreign_toplevel_image_capture_source_manager_v1::ExtForeignToplevelImageCaptureSourceManagerV1,
+        GlobalData,
+        D,
+    > for ImageCopyCaptureState
+where
+    D: Dispatch<
+        ext_foreign_toplevel_image_capture_source_manager_v1::ExtForeignToplevelImageCaptureSourceManagerV1,
+        GlobalData,
+    >,
+{
+    fn event(
+        _: &mut D,
+        _: &ext_foreign_toplevel_image_capture_source_manager_v1::ExtForeignToplevelImageCaptureSourceManagerV1,
+        _: ext_foreign_toplevel_image_capture_source_manager_v1::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("ext_foreign_toplevel_image_capture_source_manager_v1 has no events")
+    }
+}
+
+impl<D> Dispatch<ext_image_capture_source_v1::ExtImageCaptureSourceV1, GlobalData, D>
+    for ImageCopyCaptureState
+where
+    D: Dispatch<ext_image_capture_source_v1::ExtImageCaptureSourceV1, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &ext_image_capture_source_v1::ExtImageCaptureSourceV1,
+        _: ext_image_capture_source_v1::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("ext_image_capture_source_v1 has no events")
+    }
+}
+
+impl<D>
+    Dispatch<ext_image_copy_capture_session_v1::ExtImageCopyCaptureSessionV1, CaptureSessionData, D>
+    for ImageCopyCaptureState
+where
+    D: Dispatch<
+            ext_image_copy_capture_session_v1::ExtImageCopyCaptureSessionV1,
+            CaptureSessionData,
+        > + ImageCopyCaptureHandler,
+{
+    fn event(
+        state: &mut D,
+        proxy: &ext_image_copy_capture_session_v1::ExtImageCopyCaptureSessionV1,
+        event: ext_image_copy_capture_session_v1::Event,
+        data: &CaptureSessionData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let session = CaptureSession(proxy.clone());
+        let mut inner = data.inner.lock().unwrap();
+
+        match event {
+            ext_image_copy_capture_session_v1::Event::BufferSize { width, height } => {
+                inner.pending.size = (width, height);
+            }
+
+            ext_image_copy_capture_session_v1::Event::ShmFormat { format } => match format {
+                WEnum::Value(format) => inner.pending.shm_formats.push(format),
+                WEnum::Unknown(raw) => {
+                    log::debug!(target: "sctk", "Unknown image copy capture shm format {:x}", raw);
+                }
+            },
+
+            ext_image_copy_capture_session_v1::Event::DmabufDevice { device } => {
+                inner.pending.dmabuf_device =
+                    Some(dev_t::from_ne_bytes(device.try_into().unwrap()));
+            }
+
+            ext_image_copy_capture_session_v1::Event::DmabufFormat { format, modifiers } => {
+                let modifiers = modifiers
+                    .chunks_exact(8)
+                    .map(|modifier| u64::from_ne_bytes(modifier.try_into().unwrap()))
+                    .collect();
+                inner.pending.dmabuf_formats.push((format, modifiers));
+            }
+
+            ext_image_copy_capture_session_v1::Event::Done => {
+                // The constraints are sent again in full whenever they change.
+                let constraints = std::mem::take(&mut inner.pending);
+                inner.current = Some(constraints.clone());
+                drop(inner);
+
+                state.buffer_constraints(conn, qh, &session, &constraints);
+            }
+
+            ext_image_copy_capture_session_v1::Event::Stopped => {
+                inner.stopped = true;
+                drop(inner);
+
+                state.session_stopped(conn, qh, &session);
+                proxy.destroy();
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ext_image_copy_capture_frame_v1::ExtImageCopyCaptureFrameV1, CaptureFrameData, D>
+    for ImageCopyCaptureState
+where
+    D: Dispatch<ext_image_copy_capture_frame_v1::ExtImageCopyCaptureFrameV1, CaptureFrameData>
+        + ImageCopyCaptureHandler,
+{
+    fn event(
+        state: &mut D,
+        proxy: &ext_image_copy_capture_frame_v1::ExtImageCopyCaptureFrameV1,
+        event: ext_image_copy_capture_frame_v1::Event,
+        data: &CaptureFrameData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let frame = CaptureFrame(proxy.clone());
+        let mut inner = data.inner.lock().unwrap();
+
+        match event {
+            ext_image_copy_capture_frame_v1::Event::Transform { transform } => {
+                inner.transform = transform;
+            }
+
+            ext_image_copy_capture_frame_v1::Event::Damage { x, y, width, height } => {
+                inner.damage.push((x, y, width, height));
+            }
+
+            ext_image_copy_capture_frame_v1::Event::PresentationTime {
+                tv_sec_hi,
+                tv_sec_lo,
+                tv_nsec,
+            } => {
+                let secs = (u64::from(tv_sec_hi) << 32) | u64::from(tv_sec_lo);
+                inner.presentation_time = Some(Duration::new(secs, tv_nsec));
+            }
+
+            ext_image_copy_capture_frame_v1::Event::Ready => {
+                let info = FrameInfo {
+                    transform: inner.transform,
+                    damage: std::mem::take(&mut inner.damage),
+                    presentation_time: inner.presentation_time,
+                };
+                drop(inner);
+
+                state.frame_ready(conn, qh, &frame, info);
+                proxy.destroy();
+            }
+
+            ext_image_copy_capture_frame_v1::Event::Failed { reason } => {
+                drop(inner);
+
+                state.frame_failed(conn, qh, &frame, reason);
+                proxy.destroy();
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_image_copy_capture {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::ext::image_copy_capture::v1::client::ext_image_copy_capture_manager_v1::ExtImageCopyCaptureManagerV1: $crate::globals::GlobalData
+        ] => $crate::image_copy_capture::ImageCopyCaptureState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::ext::image_capture_source::v1::client::ext_output_image_capture_source_manager_v1::ExtOutputImageCaptureSourceManagerV1: $crate::globals::GlobalData
+        ] => $crate::image_copy_capture::ImageCopyCaptureState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::ext::image_capture_source::v1::client::ext_foreign_toplevel_image_capture_source_manager_v1::ExtForeignToplevelImageCaptureSourceManagerV1: $crate::globals::GlobalData
+        ] => $crate::image_copy_capture::ImageCopyCaptureState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::ext::image_capture_source::v1::client::ext_image_capture_source_v1::ExtImageCaptureSourceV1: $crate::globals::GlobalData
+        ] => $crate::image_copy_capture::ImageCopyCaptureState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::ext::image_copy_capture::v1::client::ext_image_copy_capture_session_v1::ExtImageCopyCaptureSessionV1: $crate::image_copy_capture::CaptureSessionData
+        ] => $crate::image_copy_capture::ImageCopyCaptureState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::ext::image_copy_capture::v1::client::ext_image_copy_capture_frame_v1::ExtImageCopyCaptureFrameV1: $crate::image_copy_capture::CaptureFrameData
+        ] => $crate::image_copy_capture::ImageCopyCaptureState);
+    };
+}
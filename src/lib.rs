@@ -15,20 +15,46 @@ pub mod reexports {
     pub use wayland_client as client;
     pub use wayland_csd_frame as csd_frame;
     pub use wayland_protocols as protocols;
+    pub use wayland_protocols_misc as protocols_misc;
+    pub use wayland_protocols_plasma as protocols_plasma;
     pub use wayland_protocols_wlr as protocols_wlr;
 }
 
 pub mod activation;
+pub mod alpha_modifier;
+pub mod commit_timing;
 pub mod compositor;
+pub mod content_type;
 pub mod data_device_manager;
 pub mod dmabuf;
+pub mod drm_lease;
 pub mod error;
+pub mod export_dmabuf;
+pub mod fifo;
+pub mod foreign_toplevel;
+pub mod fractional_scale;
+pub mod gamma_control;
 pub mod globals;
+pub mod image_copy_capture;
 pub mod output;
+pub mod output_management;
+pub mod output_power;
+pub mod presentation_time;
 pub mod primary_selection;
 pub mod registry;
+pub mod screencopy;
 pub mod seat;
+pub mod security_context;
 pub mod session_lock;
+#[cfg(feature = "unstable-protocols")]
+pub mod session_management;
 pub mod shell;
 pub mod shm;
 pub mod subcompositor;
+pub mod system_bell;
+pub mod tearing_control;
+pub mod viewporter;
+pub mod workspace;
+
+#[cfg(test)]
+mod testing;
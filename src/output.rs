@@ -1,5 +1,6 @@
 use std::{
     any::Any,
+    collections::HashMap,
     fmt::{self, Display, Formatter},
     sync::{Arc, Mutex, Weak},
 };
@@ -16,7 +17,7 @@ use wayland_protocols::xdg::xdg_output::zv1::client::{
 
 use crate::{
     globals::GlobalData,
-    registry::{GlobalProxy, ProvidesRegistryState, RegistryHandler},
+    registry::{GlobalProxy, MultiGlobal, ProvidesRegistryState, RegistryHandler},
 };
 
 /// Simplified event handler for [`wl_output::WlOutput`].
@@ -25,6 +26,9 @@ pub trait OutputHandler: Sized {
     fn output_state(&mut self) -> &mut OutputState;
 
     /// A new output has been advertised.
+    ///
+    /// If the compositor supports the xdg-output protocol, this is only called once the logical
+    /// position and size of the output are known.
     fn new_output(
         &mut self,
         conn: &Connection,
@@ -33,6 +37,9 @@ pub trait OutputHandler: Sized {
     );
 
     /// An existing output has changed.
+    ///
+    /// All changes to the info of the output sent together, over both the wl_output and xdg-output
+    /// protocols, are applied before this is called.
     fn update_output(
         &mut self,
         conn: &Connection,
@@ -40,9 +47,62 @@ pub trait OutputHandler: Sized {
         output: wl_output::WlOutput,
     );
 
+    /// The current mode of an existing output has changed, such as its resolution or refresh rate.
+    ///
+    /// This is called after [`OutputHandler::update_output`], with the info of the output before the
+    /// change. The new info is available from [`OutputState::info`].
+    fn mode_changed(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        output: wl_output::WlOutput,
+        previous: &OutputInfo,
+    ) {
+        let _ = (conn, qh, output, previous);
+    }
+
+    /// The scale factor of an existing output has changed.
+    ///
+    /// This is called after [`OutputHandler::mode_changed`], with the info of the output before the
+    /// change. The new info is available from [`OutputState::info`].
+    fn scale_changed(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        output: wl_output::WlOutput,
+        previous: &OutputInfo,
+    ) {
+        let _ = (conn, qh, output, previous);
+    }
+
+    /// The geometry of an existing output has changed.
+    ///
+    /// This covers the location, physical size and transform of the output, as well as its logical
+    /// position and size from the xdg-output protocol.
+    ///
+    /// This is called after [`OutputHandler::scale_changed`], with the info of the output before the
+    /// change. The new info is available from [`OutputState::info`].
+    fn geometry_changed(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        output: wl_output::WlOutput,
+        previous: &OutputInfo,
+    ) {
+        let _ = (conn, qh, output, previous);
+    }
+
     /// An output is no longer advertised.
     ///
-    /// The info passed to this function was the state of the output before destruction.
+    /// This is the last callback for this output. It is called after the output is removed from
+    /// [`OutputState::outputs`] and after the surfaces which were on the output left it, with
+    /// [`CompositorHandler::surface_leave`](crate::compositor::CompositorHandler::surface_leave)
+    /// and any resulting scale factor change. While it is called, the last info of the output is
+    /// still available from [`OutputState::info`], for example to move surfaces to another output.
+    /// Afterwards the output is released and events still sent for it are ignored.
+    ///
+    /// This is not called for an output removed before [`OutputHandler::new_output`] was called
+    /// for it.
     fn output_destroyed(
         &mut self,
         conn: &Connection,
@@ -108,7 +168,9 @@ type ScaleWatcherFn =
 /// ```
 pub struct OutputState {
     xdg: GlobalProxy<ZxdgOutputManagerV1>,
-    outputs: Vec<OutputInner>,
+    outputs: MultiGlobal<wl_output::WlOutput>,
+    /// The xdg_output and info of each output, by the name of its `wl_output` global.
+    inner: HashMap<u32, OutputInner>,
     callbacks: Vec<Weak<ScaleWatcherFn>>,
 }
 
@@ -117,6 +179,7 @@ impl fmt::Debug for OutputState {
         fmt.debug_struct("OutputState")
             .field("xdg", &self.xdg)
             .field("outputs", &self.outputs)
+            .field("inner", &self.inner)
             .field("callbacks", &self.callbacks.len())
             .finish()
     }
@@ -140,23 +203,16 @@ impl OutputState {
         global_list: &GlobalList,
         qh: &QueueHandle<D>,
     ) -> OutputState {
-        let (outputs, xdg) = global_list.contents().with_list(|globals| {
-            let outputs: Vec<wl_output::WlOutput> = crate::registry::bind_all(
-                global_list.registry(),
-                globals,
-                qh,
-                1..=4,
-                OutputData::new,
-            )
-            .expect("Failed to bind global");
-            let xdg =
-                crate::registry::bind_one(global_list.registry(), globals, qh, 1..=3, GlobalData)
-                    .into();
-            (outputs, xdg)
+        let outputs =
+            MultiGlobal::<wl_output::WlOutput>::bind_all(global_list, qh, 1..=4, OutputData::new)
+                .expect("Failed to bind global");
+        let xdg = global_list.contents().with_list(|globals| {
+            crate::registry::bind_one(global_list.registry(), globals, qh, 1..=3, GlobalData).into()
         });
 
-        let mut output_state = OutputState { xdg, outputs: vec![], callbacks: vec![] };
-        for wl_output in outputs {
+        let mut output_state =
+            OutputState { xdg, outputs, inner: HashMap::new(), callbacks: vec![] };
+        for wl_output in output_state.outputs() {
             output_state.setup(wl_output, qh);
         }
         output_state
@@ -164,7 +220,7 @@ impl OutputState {
 
     /// Returns an iterator over all outputs.
     pub fn outputs(&self) -> impl Iterator<Item = wl_output::WlOutput> {
-        self.outputs.iter().map(|output| &output.wl_output).cloned().collect::<Vec<_>>().into_iter()
+        self.outputs.iter().cloned().collect::<Vec<_>>().into_iter()
     }
 
     /// Returns information about an output.
@@ -172,10 +228,57 @@ impl OutputState {
     /// This may be none if the output has been destroyed or the compositor has not sent information about the
     /// output yet.
     pub fn info(&self, output: &wl_output::WlOutput) -> Option<OutputInfo> {
+        self.current_info(output).cloned()
+    }
+
+    /// The info applied by the last update of an output.
+    ///
+    /// This is looked up from the global name of the output rather than from `self.outputs`, so the
+    /// info is still available while [`OutputHandler::output_destroyed`] is called.
+    fn current_info(&self, output: &wl_output::WlOutput) -> Option<&OutputInfo> {
+        let name = output.data::<OutputData>()?.global_name();
+        self.inner.get(&name)?.current_info.as_ref()
+    }
+
+    /// Returns the output with the given name, such as `DP-1`.
+    ///
+    /// Outputs only have a name with version 4 of `wl_output` or version 2 of `zxdg_output_v1`.
+    pub fn find_by_name(&self, name: &str) -> Option<wl_output::WlOutput> {
+        self.outputs
+            .iter()
+            .find(|output| {
+                self.current_info(output).and_then(|info| info.name.as_deref()) == Some(name)
+            })
+            .cloned()
+    }
+
+    /// Returns the output bound from the `wl_output` global with the given name.
+    ///
+    /// This is the numeric name advertised by the registry, which some protocols use to refer to
+    /// outputs. It is also available as [`OutputInfo::id`] and [`OutputData::global_name`].
+    pub fn find_by_global_name(&self, global_name: u32) -> Option<wl_output::WlOutput> {
+        self.outputs.get_by_name(global_name).cloned()
+    }
+
+    /// Returns the output containing the given point in global compositor space.
+    ///
+    /// The logical geometry of the outputs from the xdg-output protocol is used when available.
+    /// Otherwise the geometry is computed from the location advertised by `wl_output` and
+    /// [`OutputInfo::logical_size`], which may not match the actual layout of the outputs, for
+    /// example with fractional scales. Outputs whose size is unknown are skipped, and the first
+    /// matching output is returned if outputs overlap.
+    pub fn output_at_logical_point(&self, (x, y): (i32, i32)) -> Option<wl_output::WlOutput> {
         self.outputs
             .iter()
-            .find(|inner| &inner.wl_output == output)
-            .and_then(|inner| inner.current_info.clone())
+            .find(|output| {
+                let Some(info) = self.current_info(output) else { return false };
+                let (output_x, output_y) = info.logical_position.unwrap_or(info.location);
+                let Some((width, height)) = info.logical_size() else { return false };
+
+                (output_x..output_x + width).contains(&x)
+                    && (output_y..output_y + height).contains(&y)
+            })
+            .cloned()
     }
 
     pub fn add_scale_watcher<F, D>(data: &mut D, f: F) -> ScaleWatcherHandle
@@ -194,6 +297,7 @@ impl OutputState {
         rv
     }
 
+    /// Starts tracking the info of an output, which must already be in `self.outputs`.
     fn setup<D>(&mut self, wl_output: wl_output::WlOutput, qh: &QueueHandle<D>)
     where
         D: Dispatch<zxdg_output_v1::ZxdgOutputV1, OutputData> + 'static,
@@ -215,8 +319,6 @@ impl OutputState {
         };
 
         let inner = OutputInner {
-            name,
-            wl_output,
             xdg_output,
             just_created: true,
             // wl_output::done was added in version 2.
@@ -224,11 +326,12 @@ impl OutputState {
             current_info: if version > 1 { None } else { Some(OutputInfo::new(name)) },
 
             pending_info,
-            pending_wl: true,
+            // As above, a version 1 output will not send wl_output::done.
+            pending_wl: version > 1,
             pending_xdg,
         };
 
-        self.outputs.push(inner);
+        self.inner.insert(name, inner);
     }
 }
 
@@ -240,6 +343,11 @@ impl OutputData {
         OutputData(Arc::new(Mutex::new(OutputInfo::new(name))))
     }
 
+    /// The name of the `wl_output` global the output was bound from.
+    pub fn global_name(&self) -> u32 {
+        self.0.lock().unwrap().id
+    }
+
     /// Get the output scale factor.
     pub fn scale_factor(&self) -> i32 {
         let guard = self.0.lock().unwrap();
@@ -360,10 +468,14 @@ pub struct OutputInfo {
     /// Possible modes for an output.
     pub modes: Vec<Mode>,
 
-    /// Logical position in global compositor space
+    /// Logical position in global compositor space, after scaling and transformation.
+    ///
+    /// This is [`None`] if the compositor does not support the xdg-output protocol.
     pub logical_position: Option<(i32, i32)>,
 
-    /// Logical size in global compositor space
+    /// Logical size in global compositor space, after scaling and transformation.
+    ///
+    /// This is [`None`] if the compositor does not support the xdg-output protocol.
     pub logical_size: Option<(i32, i32)>,
 
     /// The name of the this output as advertised by the surface.
@@ -392,14 +504,14 @@ pub struct OutputInfo {
 
 #[macro_export]
 macro_rules! delegate_output {
-    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
-        $crate::reexports::client::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
             $crate::reexports::client::protocol::wl_output::WlOutput: $crate::output::OutputData
         ] => $crate::output::OutputState);
-        $crate::reexports::client::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
             $crate::reexports::protocols::xdg::xdg_output::zv1::client::zxdg_output_manager_v1::ZxdgOutputManagerV1: $crate::globals::GlobalData
         ] => $crate::output::OutputState);
-        $crate::reexports::client::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
             $crate::reexports::protocols::xdg::xdg_output::zv1::client::zxdg_output_v1::ZxdgOutputV1: $crate::output::OutputData
         ] => $crate::output::OutputState);
     };
@@ -417,12 +529,10 @@ where
         conn: &Connection,
         qh: &QueueHandle<D>,
     ) {
-        let inner = state
-            .output_state()
-            .outputs
-            .iter_mut()
-            .find(|inner| &inner.wl_output == output)
-            .expect("Received event for dead output");
+        let Some(inner) = state.output_state().inner.get_mut(&data.global_name()) else {
+            // Events may still arrive for an output whose global was just removed.
+            return;
+        };
 
         match event {
             wl_output::Event::Geometry {
@@ -507,31 +617,8 @@ where
             }
 
             wl_output::Event::Done => {
-                let info = inner.pending_info.clone();
-                inner.current_info = Some(info.clone());
                 inner.pending_wl = false;
-
-                // Set the user data, see if we need to run scale callbacks
-                let run_callbacks = data.set(info);
-
-                // Don't call `new_output` until we have xdg output info
-                if !inner.pending_xdg {
-                    if inner.just_created {
-                        inner.just_created = false;
-                        state.new_output(conn, qh, output.clone());
-                    } else {
-                        state.update_output(conn, qh, output.clone());
-                    }
-                }
-
-                if run_callbacks {
-                    let callbacks = state.output_state().callbacks.clone();
-                    for cb in callbacks {
-                        if let Some(cb) = cb.upgrade() {
-                            cb(state, conn, qh, output);
-                        }
-                    }
-                }
+                apply_pending_info(state, conn, qh, output, data);
             }
 
             _ => unreachable!(),
@@ -557,7 +644,7 @@ where
 
 impl<D> Dispatch<zxdg_output_v1::ZxdgOutputV1, OutputData, D> for OutputState
 where
-    D: Dispatch<zxdg_output_v1::ZxdgOutputV1, OutputData> + OutputHandler,
+    D: Dispatch<zxdg_output_v1::ZxdgOutputV1, OutputData> + OutputHandler + 'static,
 {
     fn event(
         state: &mut D,
@@ -567,12 +654,14 @@ where
         conn: &Connection,
         qh: &QueueHandle<D>,
     ) {
-        let inner = state
-            .output_state()
-            .outputs
-            .iter_mut()
-            .find(|inner| inner.xdg_output.as_ref() == Some(output))
-            .expect("Received event for dead output");
+        let name = data.global_name();
+        let output_state = state.output_state();
+        let (Some(wl_output), Some(inner)) =
+            (output_state.outputs.get_by_name(name).cloned(), output_state.inner.get_mut(&name))
+        else {
+            // Events may still arrive for an output whose global was just removed.
+            return;
+        };
 
         // zxdg_output_v1::done is deprecated in version 3. So we only need
         // to wait for wl_output::done, once we get any xdg output info.
@@ -594,7 +683,7 @@ where
                 }
             }
             zxdg_output_v1::Event::Name { name } => {
-                if inner.wl_output.version() < 4 {
+                if wl_output.version() < 4 {
                     inner.pending_info.name = Some(name);
                 }
                 if output.version() < 3 {
@@ -603,7 +692,7 @@ where
             }
 
             zxdg_output_v1::Event::Description { description } => {
-                if inner.wl_output.version() < 4 {
+                if wl_output.version() < 4 {
                     inner.pending_info.description = Some(description);
                 }
                 if output.version() < 3 {
@@ -614,32 +703,87 @@ where
             zxdg_output_v1::Event::Done => {
                 // This event is deprecated starting in version 3, wl_output::done should be sent instead.
                 if output.version() < 3 {
-                    let info = inner.pending_info.clone();
-                    inner.current_info = Some(info.clone());
                     inner.pending_xdg = false;
+                    apply_pending_info(state, conn, qh, &wl_output, data);
+                }
+            }
 
-                    // Set the user data
-                    data.set(info);
+            _ => unreachable!(),
+        }
+    }
+}
 
-                    let pending_wl = inner.pending_wl;
-                    let just_created = inner.just_created;
-                    let output = inner.wl_output.clone();
+/// Make the pending info of an output current, once neither the wl_output nor the xdg_output info is
+/// still being sent, so handlers never see an output with only part of its info updated.
+fn apply_pending_info<D>(
+    state: &mut D,
+    conn: &Connection,
+    qh: &QueueHandle<D>,
+    output: &wl_output::WlOutput,
+    data: &OutputData,
+) where
+    D: OutputHandler + 'static,
+{
+    let Some(inner) = state.output_state().inner.get_mut(&data.global_name()) else {
+        return;
+    };
 
-                    if just_created {
-                        inner.just_created = false;
-                    }
+    if inner.pending_wl || inner.pending_xdg {
+        return;
+    }
 
-                    if !pending_wl {
-                        if just_created {
-                            state.new_output(conn, qh, output);
-                        } else {
-                            state.update_output(conn, qh, output);
-                        }
-                    }
-                }
-            }
+    let info = inner.pending_info.clone();
+    let previous = inner.current_info.replace(info.clone());
+    let just_created = std::mem::replace(&mut inner.just_created, false);
 
-            _ => unreachable!(),
+    // Compare with the info applied by the previous update, not with the pending info.
+    let changes = previous.filter(|_| !just_created).map(|previous| {
+        let mode = |info: &OutputInfo| {
+            info.current_mode().map(|mode| (mode.dimensions, mode.refresh_rate))
+        };
+        let geometry = |info: &OutputInfo| {
+            (
+                info.location,
+                info.physical_size,
+                info.transform,
+                info.logical_position,
+                info.logical_size,
+            )
+        };
+
+        let mode_changed = mode(&previous) != mode(&info);
+        let scale_changed = previous.scale_factor != info.scale_factor;
+        let geometry_changed = geometry(&previous) != geometry(&info);
+        (previous, mode_changed, scale_changed, geometry_changed)
+    });
+
+    // Set the user data, see if we need to run scale callbacks
+    let run_callbacks = data.set(info);
+
+    if just_created {
+        state.new_output(conn, qh, output.clone());
+    } else {
+        state.update_output(conn, qh, output.clone());
+    }
+
+    if let Some((previous, mode_changed, scale_changed, geometry_changed)) = changes {
+        if mode_changed {
+            state.mode_changed(conn, qh, output.clone(), &previous);
+        }
+        if scale_changed {
+            state.scale_changed(conn, qh, output.clone(), &previous);
+        }
+        if geometry_changed {
+            state.geometry_changed(conn, qh, output.clone(), &previous);
+        }
+    }
+
+    if run_callbacks {
+        let callbacks = state.output_state().callbacks.clone();
+        for cb in callbacks {
+            if let Some(cb) = cb.upgrade() {
+                cb(state, conn, qh, output);
+            }
         }
     }
 }
@@ -662,10 +806,11 @@ where
         _version: u32,
     ) {
         if interface == "wl_output" {
-            let output = data
+            let output: wl_output::WlOutput = data
                 .registry()
                 .bind_specific(qh, name, 1..=4, OutputData::new(name))
                 .expect("Failed to bind global");
+            data.output_state().outputs.add(name, output.clone());
             data.output_state().setup(output, qh);
         }
     }
@@ -678,22 +823,35 @@ where
         interface: &str,
     ) {
         if interface == "wl_output" {
-            let output = data
-                .output_state()
-                .outputs
-                .iter()
-                .position(|o| o.name == name)
-                .expect("Removed non-existing output");
-
-            let wl_output = data.output_state().outputs[output].wl_output.clone();
-            data.output_destroyed(conn, qh, wl_output);
-
-            let output = data.output_state().outputs.remove(output);
-            if let Some(xdg_output) = &output.xdg_output {
+            // The output may not have been bound if it was removed right after being advertised.
+            let Some(wl_output) = data.output_state().outputs.remove(name) else {
+                return;
+            };
+
+            // Let the surfaces which were on this output leave it and update their scale factor.
+            let callbacks = data.output_state().callbacks.clone();
+            for cb in callbacks {
+                if let Some(cb) = cb.upgrade() {
+                    cb(data, conn, qh, &wl_output);
+                }
+            }
+
+            // An output removed before its info was complete was never announced by new_output.
+            let announced = matches!(
+                data.output_state().inner.get(&name),
+                Some(inner) if !inner.just_created
+            );
+            if announced {
+                data.output_destroyed(conn, qh, wl_output.clone());
+            }
+
+            // Events still sent for the output are ignored once it is removed from `inner`.
+            let output = data.output_state().inner.remove(&name);
+            if let Some(xdg_output) = output.and_then(|output| output.xdg_output) {
                 xdg_output.destroy();
             }
-            if output.wl_output.version() >= 3 {
-                output.wl_output.release();
+            if wl_output.version() >= 3 {
+                wl_output.release();
             }
         }
     }
@@ -717,6 +875,40 @@ impl OutputInfo {
             description: None,
         }
     }
+
+    /// The current mode of the output, if the compositor advertised one.
+    pub fn current_mode(&self) -> Option<&Mode> {
+        self.modes.iter().find(|mode| mode.current)
+    }
+
+    /// The refresh rate of the current mode, in millihertz.
+    ///
+    /// This is [`None`] if there is no current mode, or if its refresh rate is unknown.
+    pub fn refresh_rate(&self) -> Option<i32> {
+        self.current_mode().map(|mode| mode.refresh_rate).filter(|&refresh_rate| refresh_rate > 0)
+    }
+
+    /// The size of the output in global compositor space, after scaling and transformation.
+    ///
+    /// This is the size advertised by the xdg-output protocol if available. Otherwise it is computed
+    /// from the current mode, the scale factor and the transform of the output, which does not
+    /// account for a fractional scale of the output.
+    pub fn logical_size(&self) -> Option<(i32, i32)> {
+        if let Some(logical_size) = self.logical_size {
+            return Some(logical_size);
+        }
+
+        let (width, height) = self.current_mode()?.dimensions;
+        let scale_factor = self.scale_factor.max(1);
+        let (width, height) = (width / scale_factor, height / scale_factor);
+
+        match self.transform {
+            Transform::_90 | Transform::_270 | Transform::Flipped90 | Transform::Flipped270 => {
+                Some((height, width))
+            }
+            _ => Some((width, height)),
+        }
+    }
 }
 
 impl OutputData {
@@ -733,9 +925,6 @@ impl OutputData {
 
 #[derive(Debug)]
 struct OutputInner {
-    /// The name of the wl_output global.
-    name: u32,
-    wl_output: wl_output::WlOutput,
     xdg_output: Option<zxdg_output_v1::ZxdgOutputV1>,
     /// Whether this output was just created and has not an event yet.
     just_created: bool,
@@ -745,3 +934,239 @@ struct OutputInner {
     pending_wl: bool,
     pending_xdg: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use wayland_backend::{protocol::Argument, server::ObjectId};
+    use wayland_client::{
+        protocol::{wl_compositor::WlCompositor, wl_output::WlOutput},
+        Proxy,
+    };
+
+    use wayland_protocols::xdg::xdg_output::zv1::client::zxdg_output_manager_v1::ZxdgOutputManagerV1;
+
+    use crate::testing::{Call, TestClient, TestServer};
+
+    /// A client with one output whose initial info was sent, and the server side of the output.
+    fn connect(xdg_output: bool) -> (TestServer, TestClient, ObjectId) {
+        let mut server = TestServer::new();
+        server.create_global(WlOutput::interface(), 4);
+        if xdg_output {
+            server.create_global(ZxdgOutputManagerV1::interface(), 3);
+        }
+        let mut client = server.connect();
+        client.roundtrip();
+
+        let output = server.last_object("wl_output");
+        if xdg_output {
+            let xdg_output = server.last_object("zxdg_output_v1");
+            server.send(&xdg_output, "logical_position", vec![Argument::Int(0), Argument::Int(0)]);
+            let size = vec![Argument::Int(1920), Argument::Int(1080)];
+            server.send(&xdg_output, "logical_size", size);
+        }
+        server.send_output_info(&output, (0, 0), (1920, 1080), 1);
+        client.roundtrip();
+        assert!(matches!(client.state.take_calls()[..], [Call::NewOutput(_)]));
+        (server, client, output)
+    }
+
+    #[test]
+    fn update_without_change() {
+        let (mut server, mut client, output) = connect(false);
+
+        server.send_output_info(&output, (0, 0), (1920, 1080), 1);
+        client.roundtrip();
+        assert!(matches!(client.state.take_calls()[..], [Call::UpdateOutput(_)]));
+    }
+
+    #[test]
+    fn mode_changed() {
+        let (mut server, mut client, output) = connect(false);
+
+        server.send_output_info(&output, (0, 0), (2560, 1440), 1);
+        client.roundtrip();
+        match &client.state.take_calls()[..] {
+            [Call::UpdateOutput(_), Call::ModeChanged(wl_output, previous)] => {
+                let mode = previous.current_mode().unwrap();
+                assert_eq!(mode.dimensions, (1920, 1080));
+                let info = client.state.output_state.info(wl_output).unwrap();
+                assert_eq!(info.current_mode().unwrap().dimensions, (2560, 1440));
+            }
+            calls => panic!("unexpected calls {:?}", calls),
+        }
+    }
+
+    #[test]
+    fn scale_changed() {
+        let (mut server, mut client, output) = connect(false);
+
+        server.send_output_info(&output, (0, 0), (1920, 1080), 2);
+        client.roundtrip();
+        match &client.state.take_calls()[..] {
+            [Call::UpdateOutput(_), Call::ScaleChanged(wl_output, previous)] => {
+                assert_eq!(previous.scale_factor, 1);
+                assert_eq!(client.state.output_state.info(wl_output).unwrap().scale_factor, 2);
+            }
+            calls => panic!("unexpected calls {:?}", calls),
+        }
+    }
+
+    #[test]
+    fn geometry_changed() {
+        let (mut server, mut client, output) = connect(false);
+
+        server.send_output_info(&output, (1920, 0), (1920, 1080), 1);
+        client.roundtrip();
+        match &client.state.take_calls()[..] {
+            [Call::UpdateOutput(_), Call::GeometryChanged(wl_output, previous)] => {
+                assert_eq!(previous.location, (0, 0));
+                assert_eq!(client.state.output_state.info(wl_output).unwrap().location, (1920, 0));
+            }
+            calls => panic!("unexpected calls {:?}", calls),
+        }
+    }
+
+    #[test]
+    fn geometry_changed_xdg_output() {
+        let (mut server, mut client, output) = connect(true);
+        let xdg_output = server.last_object("zxdg_output_v1");
+
+        // The logical geometry changes alone, and applies with the next wl_output.done.
+        server.send(&xdg_output, "logical_size", vec![Argument::Int(1280), Argument::Int(720)]);
+        client.roundtrip();
+        assert!(client.state.take_calls().is_empty());
+        server.send(&output, "done", vec![]);
+        client.roundtrip();
+        match &client.state.take_calls()[..] {
+            [Call::UpdateOutput(_), Call::GeometryChanged(_, previous)] => {
+                assert_eq!(previous.logical_size, Some((1920, 1080)));
+            }
+            calls => panic!("unexpected calls {:?}", calls),
+        }
+    }
+
+    #[test]
+    fn all_changed() {
+        let (mut server, mut client, output) = connect(false);
+
+        server.send_output_info(&output, (1920, 0), (2560, 1440), 2);
+        client.roundtrip();
+        match &client.state.take_calls()[..] {
+            [Call::UpdateOutput(_), Call::ModeChanged(_, mode), Call::ScaleChanged(_, scale), Call::GeometryChanged(_, geometry)] =>
+            {
+                // Every callback gets the info from before the whole update.
+                for previous in [mode, scale, geometry] {
+                    assert_eq!(previous.current_mode().unwrap().dimensions, (1920, 1080));
+                    assert_eq!(previous.scale_factor, 1);
+                    assert_eq!(previous.location, (0, 0));
+                }
+            }
+            calls => panic!("unexpected calls {:?}", calls),
+        }
+    }
+
+    #[test]
+    fn destroyed_after_surface_leave() {
+        let mut server = TestServer::new();
+        server.create_global(WlCompositor::interface(), 5);
+        server.create_global(WlOutput::interface(), 4);
+        let global = server.create_global(WlOutput::interface(), 4);
+        let mut client = server.connect();
+        client.roundtrip();
+
+        let outputs = server.objects("wl_output");
+        server.send_output_info(&outputs[0], (0, 0), (1920, 1080), 1);
+        server.send_output_info(&outputs[1], (1920, 0), (3840, 2160), 2);
+        let surface = client.create_surface();
+        for output in outputs {
+            server.send(&server.object(&surface), "enter", vec![Argument::Object(output)]);
+        }
+        client.roundtrip();
+        assert!(matches!(
+            client.state.take_calls()[..],
+            [
+                Call::NewOutput(_),
+                Call::NewOutput(_),
+                Call::SurfaceEnter(..),
+                Call::SurfaceEnter(..),
+                Call::ScaleFactorChanged(_, 2)
+            ]
+        ));
+        server.take_requests();
+
+        // The compositor does not send a leave event when the output is removed.
+        server.disable_global(global.clone());
+        client.roundtrip();
+        let wl_output = match &client.state.take_calls()[..] {
+            [Call::SurfaceLeave(leave_surface, leave_output), Call::ScaleFactorChanged(scale_surface, 1), Call::OutputDestroyed(output, Some(info))] =>
+            {
+                assert_eq!(leave_surface, &surface);
+                assert_eq!(scale_surface, &surface);
+                assert_eq!(leave_output, output);
+                assert_eq!(info.scale_factor, 2);
+                output.clone()
+            }
+            calls => panic!("unexpected calls {:?}", calls),
+        };
+        assert_eq!(server.take_request_names(), ["wl_output.release"]);
+        assert!(client.state.output_state.info(&wl_output).is_none());
+        assert_eq!(client.state.output_state.outputs().count(), 1);
+        assert_eq!(client.state.output_state.inner.len(), 1);
+
+        server.remove_global(global);
+        client.roundtrip();
+        assert!(client.state.take_calls().is_empty());
+    }
+
+    #[test]
+    fn removed_before_announced() {
+        let mut server = TestServer::new();
+        let global = server.create_global(WlOutput::interface(), 4);
+        let mut client = server.connect();
+        client.roundtrip();
+        server.take_requests();
+
+        // The output is removed before its info is complete, so it was never announced.
+        server.disable_global(global);
+        client.roundtrip();
+        assert!(client.state.take_calls().is_empty());
+        assert_eq!(server.take_request_names(), ["wl_output.release"]);
+        assert!(client.state.output_state.inner.is_empty());
+    }
+
+    #[test]
+    fn rapid_add_remove() {
+        let mut server = TestServer::new();
+        let mut client = server.connect();
+        client.roundtrip();
+
+        for i in 0..10 {
+            let global = server.create_global(WlOutput::interface(), 4);
+            // Every other output is removed before the client could even bind it.
+            if i % 2 == 0 {
+                client.roundtrip();
+                let output = server.last_object("wl_output");
+                server.send_output_info(&output, (0, 0), (1920, 1080), 1);
+            }
+            server.disable_global(global.clone());
+            client.roundtrip();
+            server.remove_global(global);
+        }
+        client.roundtrip();
+
+        let calls = client.state.take_calls();
+        assert_eq!(calls.len(), 10);
+        for pair in calls.chunks(2) {
+            match pair {
+                [Call::NewOutput(new), Call::OutputDestroyed(destroyed, Some(_))] => {
+                    assert_eq!(new, destroyed)
+                }
+                calls => panic!("unexpected calls {:?}", calls),
+            }
+        }
+        let releases = server.take_request_names();
+        assert_eq!(releases.iter().filter(|name| *name == "wl_output.release").count(), 10);
+        assert_eq!(client.state.output_state.outputs().count(), 0);
+        assert!(client.state.output_state.inner.is_empty());
+    }
+}
@@ -0,0 +1,741 @@
+//! ## wlr output management
+//!
+//! The `zwlr_output_manager_v1` global lists the heads of the compositor, which are the connected
+//! displays whether they are enabled or not, with their modes. Display settings tools use it to
+//! change the mode, position, scale and transform of heads, or to enable and disable them.
+//!
+//! [`OutputManagementState`] keeps track of the heads. Changes of all heads are applied atomically when
+//! the compositor is done sending them, and reported to [`OutputManagementHandler`].
+//!
+//! A new configuration is created with [`OutputManagementState::create_configuration`] from the serial
+//! of the last change, and must configure every head. If the heads changed since then, the
+//! configuration is cancelled, and should be created again from the new state.
+
+use std::sync::Mutex;
+
+use wayland_client::{
+    event_created_child,
+    globals::{BindError, GlobalList},
+    protocol::wl_output,
+    Connection, Dispatch, Proxy, QueueHandle, WEnum,
+};
+use wayland_protocols_wlr::output_management::v1::client::{
+    zwlr_output_configuration_head_v1, zwlr_output_configuration_v1, zwlr_output_head_v1,
+    zwlr_output_manager_v1, zwlr_output_mode_v1,
+};
+
+use crate::globals::GlobalData;
+
+/// Handler for heads and configurations of wlr output management.
+pub trait OutputManagementHandler: Sized {
+    fn output_management_state(&mut self) -> &mut OutputManagementState;
+
+    /// A head was connected, and its initial state is known.
+    fn new_head(&mut self, conn: &Connection, qh: &QueueHandle<Self>, head: &OutputHead);
+
+    /// The state of a head changed.
+    fn update_head(&mut self, conn: &Connection, qh: &QueueHandle<Self>, head: &OutputHead);
+
+    /// A head was disconnected.
+    ///
+    /// This is also called for all heads when the compositor stops sending heads, after
+    /// [`OutputManagementState::stop`] or when the global is removed.
+    fn head_removed(&mut self, conn: &Connection, qh: &QueueHandle<Self>, head: &OutputHead);
+
+    /// A configuration was applied, or passed the test.
+    fn configuration_succeeded(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        configuration: &OutputConfiguration,
+    );
+
+    /// A configuration could not be applied, or failed the test.
+    fn configuration_failed(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        configuration: &OutputConfiguration,
+    );
+
+    /// A configuration was cancelled because the heads changed since it was created.
+    ///
+    /// It may be created again from the new [`OutputManagementState::serial`].
+    fn configuration_cancelled(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        configuration: &OutputConfiguration,
+    );
+}
+
+/// The wlr output manager global and the heads it lists.
+#[derive(Debug)]
+pub struct OutputManagementState {
+    /// [`None`] once the compositor stopped sending heads.
+    manager: Option<zwlr_output_manager_v1::ZwlrOutputManagerV1>,
+    heads: Vec<OutputHead>,
+    serial: Option<u32>,
+}
+
+impl OutputManagementState {
+    /// Binds the `zwlr_output_manager_v1` global, after which the compositor starts sending the heads.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support wlr output management.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State: Dispatch<zwlr_output_manager_v1::ZwlrOutputManagerV1, GlobalData> + 'static,
+    {
+        let manager = globals.bind(qh, 1..=4, GlobalData)?;
+        Ok(Self { manager: Some(manager), heads: Vec::new(), serial: None })
+    }
+
+    /// The heads whose initial state is known.
+    pub fn heads(&self) -> impl Iterator<Item = &OutputHead> {
+        self.heads.iter().filter(|head| head.info().is_some())
+    }
+
+    /// The serial of the last change of the heads, or [`None`] until the initial state is known.
+    pub fn serial(&self) -> Option<u32> {
+        self.serial
+    }
+
+    /// Whether the compositor stopped sending heads, after which all heads were removed.
+    pub fn is_finished(&self) -> bool {
+        self.manager.is_none()
+    }
+
+    /// Request the compositor to stop sending heads.
+    pub fn stop(&self) {
+        if let Some(manager) = &self.manager {
+            manager.stop();
+        }
+    }
+
+    /// Create a configuration of the heads, from the serial of the state it is based on.
+    ///
+    /// Returns [`None`] if the compositor stopped sending heads.
+    pub fn create_configuration<D>(
+        &self,
+        serial: u32,
+        qh: &QueueHandle<D>,
+    ) -> Option<OutputConfiguration>
+    where
+        D: Dispatch<
+                zwlr_output_configuration_v1::ZwlrOutputConfigurationV1,
+                OutputConfigurationData,
+            > + 'static,
+    {
+        let manager = self.manager.as_ref()?;
+        let data = OutputConfigurationData {
+            serial,
+            inner: Mutex::new(OutputConfigurationInner {
+                unconfigured: self.heads().map(|head| head.0.clone()).collect(),
+                used: false,
+            }),
+        };
+        Some(OutputConfiguration(manager.create_configuration(serial, qh, data)))
+    }
+}
+
+/// A head, which is a display connected to the compositor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputHead(zwlr_output_head_v1::ZwlrOutputHeadV1);
+
+impl OutputHead {
+    pub fn head(&self) -> &zwlr_output_head_v1::ZwlrOutputHeadV1 {
+        &self.0
+    }
+
+    /// The state of the head, or [`None`] until the compositor sent it.
+    pub fn info(&self) -> Option<HeadInfo> {
+        self.data().inner.lock().unwrap().current.clone()
+    }
+
+    fn data(&self) -> &OutputHeadData {
+        self.0.data::<OutputHeadData>().unwrap()
+    }
+}
+
+/// The cached state of a head.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct HeadInfo {
+    /// The name of the head, such as `DP-1`, which is also the name of its `wl_output`.
+    pub name: String,
+    pub description: String,
+    /// The physical size in millimeters, or `(0, 0)` if it is unknown.
+    pub physical_size: (i32, i32),
+    pub make: Option<String>,
+    pub model: Option<String>,
+    pub serial_number: Option<String>,
+    /// The modes supported by the head.
+    pub modes: Vec<ModeInfo>,
+    /// Whether the head is enabled, in which case it is exposed as a `wl_output`.
+    pub enabled: bool,
+    /// The current mode, or [`None`] if the head is disabled or uses a custom mode.
+    pub current_mode: Option<ModeInfo>,
+    /// The position in the global compositor space, only meaningful if the head is enabled.
+    pub position: (i32, i32),
+    pub transform: wl_output::Transform,
+    pub scale: f64,
+    pub adaptive_sync: bool,
+}
+
+impl HeadInfo {
+    fn new() -> Self {
+        Self {
+            name: String::new(),
+            description: String::new(),
+            physical_size: (0, 0),
+            make: None,
+            model: None,
+            serial_number: None,
+            modes: Vec::new(),
+            enabled: false,
+            current_mode: None,
+            position: (0, 0),
+            transform: wl_output::Transform::Normal,
+            scale: 1.0,
+            adaptive_sync: false,
+        }
+    }
+}
+
+/// A mode supported by a head.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ModeInfo {
+    pub mode: zwlr_output_mode_v1::ZwlrOutputModeV1,
+    /// The size in physical pixels.
+    pub size: (i32, i32),
+    /// The refresh rate in mHz, or [`None`] if it is unknown.
+    pub refresh: Option<i32>,
+    pub preferred: bool,
+}
+
+/// An error that may occur when building a configuration.
+#[derive(Debug, thiserror::Error)]
+pub enum OutputConfigurationError {
+    /// The head was already enabled or disabled in this configuration, or is not known.
+    #[error("the head was already configured or is not known")]
+    AlreadyConfigured,
+
+    /// Not all heads were enabled or disabled in this configuration.
+    #[error("not all heads were configured")]
+    UnconfiguredHeads,
+
+    /// The configuration was already applied or tested.
+    #[error("the configuration was already used")]
+    AlreadyUsed,
+}
+
+/// A configuration of all heads, which is applied or tested at once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputConfiguration(zwlr_output_configuration_v1::ZwlrOutputConfigurationV1);
+
+impl OutputConfiguration {
+    pub fn configuration(&self) -> &zwlr_output_configuration_v1::ZwlrOutputConfigurationV1 {
+        &self.0
+    }
+
+    /// The serial of the state the configuration is based on.
+    pub fn serial(&self) -> u32 {
+        self.data().serial
+    }
+
+    /// Enable a head, and configure its state.
+    pub fn enable_head<D>(
+        &self,
+        head: &OutputHead,
+        qh: &QueueHandle<D>,
+    ) -> Result<OutputConfigurationHead, OutputConfigurationError>
+    where
+        D: Dispatch<zwlr_output_configuration_head_v1::ZwlrOutputConfigurationHeadV1, GlobalData>
+            + 'static,
+    {
+        self.configure(head)?;
+        Ok(OutputConfigurationHead(self.0.enable_head(&head.0, qh, GlobalData)))
+    }
+
+    /// Disable a head.
+    pub fn disable_head(&self, head: &OutputHead) -> Result<(), OutputConfigurationError> {
+        self.configure(head)?;
+        self.0.disable_head(&head.0);
+        Ok(())
+    }
+
+    /// Apply the configuration, after which it can no longer be changed.
+    pub fn apply(&self) -> Result<(), OutputConfigurationError> {
+        self.use_configuration()?;
+        self.0.apply();
+        Ok(())
+    }
+
+    /// Test whether the configuration could be applied, after which it can no longer be changed.
+    pub fn test(&self) -> Result<(), OutputConfigurationError> {
+        self.use_configuration()?;
+        self.0.test();
+        Ok(())
+    }
+
+    fn configure(&self, head: &OutputHead) -> Result<(), OutputConfigurationError> {
+        let mut inner = self.data().inner.lock().unwrap();
+        if inner.used {
+            return Err(OutputConfigurationError::AlreadyUsed);
+        }
+
+        let len = inner.unconfigured.len();
+        inner.unconfigured.retain(|h| h != &head.0);
+        if inner.unconfigured.len() == len {
+            return Err(OutputConfigurationError::AlreadyConfigured);
+        }
+
+        Ok(())
+    }
+
+    fn use_configuration(&self) -> Result<(), OutputConfigurationError> {
+        let mut inner = self.data().inner.lock().unwrap();
+        if inner.used {
+            return Err(OutputConfigurationError::AlreadyUsed);
+        }
+        if !inner.unconfigured.is_empty() {
+            return Err(OutputConfigurationError::UnconfiguredHeads);
+        }
+
+        inner.used = true;
+        Ok(())
+    }
+
+    fn data(&self) -> &OutputConfigurationData {
+        self.0.data::<OutputConfigurationData>().unwrap()
+    }
+}
+
+/// The state of a head enabled by a configuration.
+///
+/// Properties which are not set keep their current value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputConfigurationHead(
+    zwlr_output_configuration_head_v1::ZwlrOutputConfigurationHeadV1,
+);
+
+impl OutputConfigurationHead {
+    pub fn configuration_head(
+        &self,
+    ) -> &zwlr_output_configuration_head_v1::ZwlrOutputConfigurationHeadV1 {
+        &self.0
+    }
+
+    /// Use one of the modes of the head.
+    pub fn set_mode(&self, mode: &ModeInfo) {
+        self.0.set_mode(&mode.mode);
+    }
+
+    /// Use a custom mode, with a refresh rate in mHz or 0 to let the compositor choose it.
+    pub fn set_custom_mode(&self, width: i32, height: i32, refresh: i32) {
+        self.0.set_custom_mode(width, height, refresh);
+    }
+
+    /// Set the position in the global compositor space.
+    pub fn set_position(&self, x: i32, y: i32) {
+        self.0.set_position(x, y);
+    }
+
+    pub fn set_transform(&self, transform: wl_output::Transform) {
+        self.0.set_transform(transform);
+    }
+
+    pub fn set_scale(&self, scale: f64) {
+        self.0.set_scale(scale);
+    }
+
+    /// Enable or disable adaptive sync.
+    ///
+    /// Does nothing if the compositor supports a version of the protocol older than 4.
+    pub fn set_adaptive_sync(&self, adaptive_sync: bool) {
+        if self.0.version() >= 4 {
+            self.0.set_adaptive_sync(if adaptive_sync {
+                zwlr_output_head_v1::AdaptiveSyncState::Enabled
+            } else {
+                zwlr_output_head_v1::AdaptiveSyncState::Disabled
+            });
+        }
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct OutputHeadData {
+    inner: Mutex<OutputHeadInner>,
+}
+
+#[derive(Debug, Default)]
+struct OutputHeadInner {
+    /// The state applied by the last done event.
+    current: Option<HeadInfo>,
+    /// The state received until now, whose modes are taken from `modes` when applied.
+    pending: Option<HeadInfo>,
+    modes: Vec<zwlr_output_mode_v1::ZwlrOutputModeV1>,
+    current_mode: Option<zwlr_output_mode_v1::ZwlrOutputModeV1>,
+}
+
+impl OutputHeadInner {
+    fn pending(&mut self) -> &mut HeadInfo {
+        self.pending.get_or_insert_with(HeadInfo::new)
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct OutputModeData {
+    inner: Mutex<OutputModeInner>,
+}
+
+#[derive(Debug, Default)]
+struct OutputModeInner {
+    size: (i32, i32),
+    refresh: Option<i32>,
+    preferred: bool,
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct OutputConfigurationData {
+    serial: u32,
+    inner: Mutex<OutputConfigurationInner>,
+}
+
+#[derive(Debug)]
+struct OutputConfigurationInner {
+    /// The heads which were neither enabled nor disabled yet.
+    unconfigured: Vec<zwlr_output_head_v1::ZwlrOutputHeadV1>,
+    used: bool,
+}
+
+impl<D> Dispatch<zwlr_output_manager_v1::ZwlrOutputManagerV1, GlobalData, D>
+    for OutputManagementState
+where
+    D: Dispatch<zwlr_output_manager_v1::ZwlrOutputManagerV1, GlobalData>
+        + Dispatch<zwlr_output_head_v1::ZwlrOutputHeadV1, OutputHeadData>
+        + OutputManagementHandler
+        + 'static,
+{
+    event_created_child!(D, zwlr_output_manager_v1::ZwlrOutputManagerV1, [
+        zwlr_output_manager_v1::EVT_HEAD_OPCODE => (zwlr_output_head_v1::ZwlrOutputHeadV1, Default::default())
+    ]);
+
+    fn event(
+        state: &mut D,
+        _: &zwlr_output_manager_v1::ZwlrOutputManagerV1,
+        event: zwlr_output_manager_v1::Event,
+        _: &GlobalData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        match event {
+            zwlr_output_manager_v1::Event::Head { head } => {
+                state.output_management_state().heads.push(OutputHead(head));
+            }
+
+            zwlr_output_manager_v1::Event::Done { serial } => {
+                let management_state = state.output_management_state();
+                management_state.serial = Some(serial);
+
+                // The state of the modes is only known by the heads once all changes are done.
+                let mut new = Vec::new();
+                let mut updated = Vec::new();
+                for head in &management_state.heads {
+                    let mut inner = head.data().inner.lock().unwrap();
+                    let mut info = match inner.pending.clone() {
+                        Some(info) => info,
+                        None => continue,
+                    };
+
+                    info.modes = inner.modes.iter().map(mode_info).collect();
+                    info.current_mode = inner
+                        .current_mode
+                        .as_ref()
+                        .and_then(|mode| info.modes.iter().find(|m| &m.mode == mode).cloned());
+
+                    match &inner.current {
+                        None => new.push(head.clone()),
+                        Some(current) if current != &info => updated.push(head.clone()),
+                        Some(_) => continue,
+                    }
+                    inner.current = Some(info);
+                }
+
+                for head in new {
+                    state.new_head(conn, qh, &head);
+                }
+                for head in updated {
+                    state.update_head(conn, qh, &head);
+                }
+            }
+
+            zwlr_output_manager_v1::Event::Finished => {
+                let management_state = state.output_management_state();
+                management_state.manager = None;
+                management_state.serial = None;
+                let heads = std::mem::take(&mut management_state.heads);
+
+                for head in heads {
+                    if head.info().is_some() {
+                        state.head_removed(conn, qh, &head);
+                    }
+                }
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+fn mode_info(mode: &zwlr_output_mode_v1::ZwlrOutputModeV1) -> ModeInfo {
+    let inner = mode.data::<OutputModeData>().unwrap().inner.lock().unwrap();
+    ModeInfo {
+        mode: mode.clone(),
+        size: inner.size,
+        refresh: inner.refresh,
+        preferred: inner.preferred,
+    }
+}
+
+impl<D> Dispatch<zwlr_output_head_v1::ZwlrOutputHeadV1, OutputHeadData, D> for OutputManagementState
+where
+    D: Dispatch<zwlr_output_head_v1::ZwlrOutputHeadV1, OutputHeadData>
+        + Dispatch<zwlr_output_mode_v1::ZwlrOutputModeV1, OutputModeData>
+        + OutputManagementHandler
+        + 'static,
+{
+    event_created_child!(D, zwlr_output_head_v1::ZwlrOutputHeadV1, [
+        zwlr_output_head_v1::EVT_MODE_OPCODE => (zwlr_output_mode_v1::ZwlrOutputModeV1, Default::default())
+    ]);
+
+    fn event(
+        state: &mut D,
+        proxy: &zwlr_output_head_v1::ZwlrOutputHeadV1,
+        event: zwlr_output_head_v1::Event,
+        data: &OutputHeadData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let mut inner = data.inner.lock().unwrap();
+
+        match event {
+            zwlr_output_head_v1::Event::Name { name } => {
+                inner.pending().name = name;
+            }
+
+            zwlr_output_head_v1::Event::Description { description } => {
+                inner.pending().description = description;
+            }
+
+            zwlr_output_head_v1::Event::PhysicalSize { width, height } => {
+                inner.pending().physical_size = (width, height);
+            }
+
+            zwlr_output_head_v1::Event::Make { make } => {
+                inner.pending().make = Some(make);
+            }
+
+            zwlr_output_head_v1::Event::Model { model } => {
+                inner.pending().model = Some(model);
+            }
+
+            zwlr_output_head_v1::Event::SerialNumber { serial_number } => {
+                inner.pending().serial_number = Some(serial_number);
+            }
+
+            zwlr_output_head_v1::Event::Mode { mode } => {
+                inner.pending();
+                inner.modes.push(mode);
+            }
+
+            zwlr_output_head_v1::Event::Enabled { enabled } => {
+                let enabled = enabled != 0;
+                inner.pending().enabled = enabled;
+                if !enabled {
+                    inner.current_mode = None;
+                }
+            }
+
+            zwlr_output_head_v1::Event::CurrentMode { mode } => {
+                inner.pending();
+                inner.current_mode = Some(mode);
+            }
+
+            zwlr_output_head_v1::Event::Position { x, y } => {
+                inner.pending().position = (x, y);
+            }
+
+            zwlr_output_head_v1::Event::Transform { transform } => match transform {
+                WEnum::Value(transform) => inner.pending().transform = transform,
+                WEnum::Unknown(raw) => {
+                    log::warn!(target: "sctk", "Unknown output transform {:x}", raw);
+                }
+            },
+
+            zwlr_output_head_v1::Event::Scale { scale } => {
+                inner.pending().scale = scale;
+            }
+
+            zwlr_output_head_v1::Event::AdaptiveSync { state: adaptive_sync } => {
+                inner.pending().adaptive_sync =
+                    adaptive_sync == WEnum::Value(zwlr_output_head_v1::AdaptiveSyncState::Enabled);
+            }
+
+            zwlr_output_head_v1::Event::Finished => {
+                let was_announced = inner.current.is_some();
+                let modes = std::mem::take(&mut inner.modes);
+                drop(inner);
+
+                let head = OutputHead(proxy.clone());
+                state.output_management_state().heads.retain(|h| h != &head);
+                if was_announced {
+                    state.head_removed(conn, qh, &head);
+                }
+
+                if proxy.version() >= 3 {
+                    for mode in modes {
+                        mode.release();
+                    }
+                    proxy.release();
+                }
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<zwlr_output_mode_v1::ZwlrOutputModeV1, OutputModeData, D> for OutputManagementState
+where
+    D: Dispatch<zwlr_output_mode_v1::ZwlrOutputModeV1, OutputModeData> + OutputManagementHandler,
+{
+    fn event(
+        state: &mut D,
+        proxy: &zwlr_output_mode_v1::ZwlrOutputModeV1,
+        event: zwlr_output_mode_v1::Event,
+        data: &OutputModeData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        let mut inner = data.inner.lock().unwrap();
+
+        match event {
+            zwlr_output_mode_v1::Event::Size { width, height } => {
+                inner.size = (width, height);
+            }
+
+            zwlr_output_mode_v1::Event::Refresh { refresh } => {
+                inner.refresh = Some(refresh);
+            }
+
+            zwlr_output_mode_v1::Event::Preferred => {
+                inner.preferred = true;
+            }
+
+            zwlr_output_mode_v1::Event::Finished => {
+                drop(inner);
+
+                // The mode is removed from its head, which is applied by the next done event.
+                for head in &state.output_management_state().heads {
+                    let mut inner = head.data().inner.lock().unwrap();
+                    if inner.modes.contains(proxy) {
+                        inner.modes.retain(|mode| mode != proxy);
+                        if inner.current_mode.as_ref() == Some(proxy) {
+                            inner.current_mode = None;
+                        }
+                        break;
+                    }
+                }
+
+                if proxy.version() >= 3 {
+                    proxy.release();
+                }
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D>
+    Dispatch<zwlr_output_configuration_v1::ZwlrOutputConfigurationV1, OutputConfigurationData, D>
+    for OutputManagementState
+where
+    D: Dispatch<zwlr_output_configuration_v1::ZwlrOutputConfigurationV1, OutputConfigurationData>
+        + OutputManagementHandler,
+{
+    fn event(
+        state: &mut D,
+        proxy: &zwlr_output_configuration_v1::ZwlrOutputConfigurationV1,
+        event: zwlr_output_configuration_v1::Event,
+        _: &OutputConfigurationData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let configuration = OutputConfiguration(proxy.clone());
+
+        match event {
+            zwlr_output_configuration_v1::Event::Succeeded => {
+                state.configuration_succeeded(conn, qh, &configuration);
+            }
+
+            zwlr_output_configuration_v1::Event::Failed => {
+                state.configuration_failed(conn, qh, &configuration);
+            }
+
+            zwlr_output_configuration_v1::Event::Cancelled => {
+                state.configuration_cancelled(conn, qh, &configuration);
+            }
+
+            _ => unreachable!(),
+        }
+
+        // A configuration is only used once.
+        proxy.destroy();
+    }
+}
+
+impl<D> Dispatch<zwlr_output_configuration_head_v1::ZwlrOutputConfigurationHeadV1, GlobalData, D>
+    for OutputManagementState
+where
+    D: Dispatch<zwlr_output_configuration_head_v1::ZwlrOutputConfigurationHeadV1, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &zwlr_output_configuration_head_v1::ZwlrOutputConfigurationHeadV1,
+        _: zwlr_output_configuration_head_v1::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("zwlr_output_configuration_head_v1 has no events")
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_output_management {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_wlr::output_management::v1::client::zwlr_output_manager_v1::ZwlrOutputManagerV1: $crate::globals::GlobalData
+        ] => $crate::output_management::OutputManagementState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_wlr::output_management::v1::client::zwlr_output_head_v1::ZwlrOutputHeadV1: $crate::output_management::OutputHeadData
+        ] => $crate::output_management::OutputManagementState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_wlr::output_management::v1::client::zwlr_output_mode_v1::ZwlrOutputModeV1: $crate::output_management::OutputModeData
+        ] => $crate::output_management::OutputManagementState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_wlr::output_management::v1::client::zwlr_output_configuration_v1::ZwlrOutputConfigurationV1: $crate::output_management::OutputConfigurationData
+        ] => $crate::output_management::OutputManagementState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_wlr::output_management::v1::client::zwlr_output_configuration_head_v1::ZwlrOutputConfigurationHeadV1: $crate::globals::GlobalData
+        ] => $crate::output_management::OutputManagementState);
+    };
+}
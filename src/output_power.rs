@@ -0,0 +1,208 @@
+//! ## wlr output power management
+//!
+//! The `zwlr_output_power_manager_v1` global lets idle daemons turn outputs off and on again, without
+//! changing the configuration of the outputs.
+//!
+//! The power mode of an output is controlled through an [`OutputPower`] created with
+//! [`OutputPowerState::get_output_power`]. Its mode is reported to [`OutputPowerHandler::mode`]
+//! whenever it changes.
+
+use std::sync::Mutex;
+
+use wayland_client::{
+    globals::{BindError, GlobalList},
+    protocol::wl_output,
+    Connection, Dispatch, Proxy, QueueHandle, WEnum,
+};
+use wayland_protocols_wlr::output_power_management::v1::client::{
+    zwlr_output_power_manager_v1, zwlr_output_power_v1,
+};
+
+use crate::globals::GlobalData;
+
+pub use zwlr_output_power_v1::Mode;
+
+/// Handler for the power mode of outputs.
+pub trait OutputPowerHandler: Sized {
+    fn output_power_state(&mut self) -> &mut OutputPowerState;
+
+    /// The power mode of an output is known or changed.
+    fn mode(&mut self, conn: &Connection, qh: &QueueHandle<Self>, power: &OutputPower, mode: Mode);
+
+    /// The power mode of an output can no longer be controlled, for example because the output was
+    /// removed or another client controls it.
+    ///
+    /// The output power is destroyed after this returns.
+    fn failed(&mut self, conn: &Connection, qh: &QueueHandle<Self>, power: &OutputPower);
+}
+
+/// The output power manager global, and the output powers created from it.
+#[derive(Debug)]
+pub struct OutputPowerState {
+    manager: zwlr_output_power_manager_v1::ZwlrOutputPowerManagerV1,
+    powers: Vec<OutputPower>,
+}
+
+impl OutputPowerState {
+    /// Binds the `zwlr_output_power_manager_v1` global.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support wlr output power
+    /// management.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State:
+            Dispatch<zwlr_output_power_manager_v1::ZwlrOutputPowerManagerV1, GlobalData> + 'static,
+    {
+        let manager = globals.bind(qh, 1..=1, GlobalData)?;
+        Ok(Self { manager, powers: Vec::new() })
+    }
+
+    /// Take control of the power mode of an output.
+    ///
+    /// The same output power is returned if it already exists for this output.
+    pub fn get_output_power<D>(
+        &mut self,
+        output: &wl_output::WlOutput,
+        qh: &QueueHandle<D>,
+    ) -> OutputPower
+    where
+        D: Dispatch<zwlr_output_power_v1::ZwlrOutputPowerV1, OutputPowerData> + 'static,
+    {
+        if let Some(power) = self.output_power(output) {
+            return power.clone();
+        }
+
+        let data = OutputPowerData { output: output.clone(), mode: Mutex::new(None) };
+        let power = OutputPower(self.manager.get_output_power(output, qh, data));
+        self.powers.push(power.clone());
+        power
+    }
+
+    /// The output power of an output, if it was created.
+    pub fn output_power(&self, output: &wl_output::WlOutput) -> Option<&OutputPower> {
+        self.powers.iter().find(|power| power.output() == output)
+    }
+
+    /// The output powers which were created.
+    pub fn output_powers(&self) -> impl Iterator<Item = &OutputPower> {
+        self.powers.iter()
+    }
+
+    /// Release the control of the power mode of an output, for example when the output is destroyed.
+    pub fn remove_output(&mut self, output: &wl_output::WlOutput) {
+        self.powers.retain(|power| {
+            if power.output() == output {
+                power.0.destroy();
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+/// The control of the power mode of an output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputPower(zwlr_output_power_v1::ZwlrOutputPowerV1);
+
+impl OutputPower {
+    pub fn output_power(&self) -> &zwlr_output_power_v1::ZwlrOutputPowerV1 {
+        &self.0
+    }
+
+    /// The output whose power mode is controlled.
+    pub fn output(&self) -> &wl_output::WlOutput {
+        &self.data().output
+    }
+
+    /// The current power mode, or [`None`] until the compositor sent it.
+    pub fn mode(&self) -> Option<Mode> {
+        *self.data().mode.lock().unwrap()
+    }
+
+    /// Request to turn the output on or off.
+    pub fn set_mode(&self, mode: Mode) {
+        self.0.set_mode(mode);
+    }
+
+    fn data(&self) -> &OutputPowerData {
+        self.0.data::<OutputPowerData>().unwrap()
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct OutputPowerData {
+    output: wl_output::WlOutput,
+    mode: Mutex<Option<Mode>>,
+}
+
+impl<D> Dispatch<zwlr_output_power_manager_v1::ZwlrOutputPowerManagerV1, GlobalData, D>
+    for OutputPowerState
+where
+    D: Dispatch<zwlr_output_power_manager_v1::ZwlrOutputPowerManagerV1, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &zwlr_output_power_manager_v1::ZwlrOutputPowerManagerV1,
+        _: zwlr_output_power_manager_v1::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("zwlr_output_power_manager_v1 has no events")
+    }
+}
+
+impl<D> Dispatch<zwlr_output_power_v1::ZwlrOutputPowerV1, OutputPowerData, D> for OutputPowerState
+where
+    D: Dispatch<zwlr_output_power_v1::ZwlrOutputPowerV1, OutputPowerData> + OutputPowerHandler,
+{
+    fn event(
+        state: &mut D,
+        proxy: &zwlr_output_power_v1::ZwlrOutputPowerV1,
+        event: zwlr_output_power_v1::Event,
+        data: &OutputPowerData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let power = OutputPower(proxy.clone());
+
+        match event {
+            zwlr_output_power_v1::Event::Mode { mode } => {
+                let mode = match mode {
+                    WEnum::Value(mode) => mode,
+                    WEnum::Unknown(raw) => {
+                        log::warn!(target: "sctk", "Unknown output power mode {:x}", raw);
+                        return;
+                    }
+                };
+
+                *data.mode.lock().unwrap() = Some(mode);
+                state.mode(conn, qh, &power, mode);
+            }
+
+            zwlr_output_power_v1::Event::Failed => {
+                state.output_power_state().powers.retain(|p| p != &power);
+                state.failed(conn, qh, &power);
+                proxy.destroy();
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_output_power {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_wlr::output_power_management::v1::client::zwlr_output_power_manager_v1::ZwlrOutputPowerManagerV1: $crate::globals::GlobalData
+        ] => $crate::output_power::OutputPowerState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_wlr::output_power_management::v1::client::zwlr_output_power_v1::ZwlrOutputPowerV1: $crate::output_power::OutputPowerData
+        ] => $crate::output_power::OutputPowerState);
+    };
+}
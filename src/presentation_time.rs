@@ -0,0 +1,278 @@
+//! ## Presentation time
+//!
+//! Frame callbacks only tell a client when it is a good time to draw. The `wp_presentation` global
+//! reports when the content of a commit was actually shown on an output, with the refresh duration of
+//! that output, which is needed for audio and video synchronization and precise frame pacing.
+//!
+//! Feedback is requested for the next commit of a surface with [`PresentationState::feedback`], and
+//! delivered to [`PresentationHandler::presented`] or [`PresentationHandler::discarded`].
+
+use std::{sync::Mutex, time::Duration};
+
+use wayland_client::{
+    globals::{BindError, GlobalList},
+    protocol::{wl_output, wl_surface},
+    Connection, Dispatch, QueueHandle, WEnum,
+};
+use wayland_protocols::wp::presentation_time::client::{wp_presentation, wp_presentation_feedback};
+
+use crate::{
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+};
+
+pub use wp_presentation_feedback::Kind;
+
+/// Handler for presentation feedback.
+pub trait PresentationHandler: Sized {
+    fn presentation_state(&mut self) -> &mut PresentationState;
+
+    /// The content of the commit the feedback was requested for was shown.
+    fn presented(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        surface: &wl_surface::WlSurface,
+        presented: Presented,
+    );
+
+    /// The content of the commit the feedback was requested for was never shown, for example because it
+    /// was replaced by a later commit before the next refresh.
+    fn discarded(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        surface: &wl_surface::WlSurface,
+    );
+}
+
+/// The presentation global.
+#[derive(Debug)]
+pub struct PresentationState {
+    presentation: wp_presentation::WpPresentation,
+    clock_id: Option<u32>,
+}
+
+impl PresentationState {
+    /// Binds the `wp_presentation` global.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support presentation time.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State:
+            Dispatch<wp_presentation::WpPresentation, GlobalData> + PresentationHandler + 'static,
+    {
+        let presentation = globals.bind(qh, 1..=1, GlobalData)?;
+        Ok(Self { presentation, clock_id: None })
+    }
+
+    /// The id of the clock used for presentation timestamps, as passed to `clock_gettime`.
+    ///
+    /// This is sent by the compositor right after the global is bound, and is [`None`] until then.
+    pub fn clock_id(&self) -> Option<u32> {
+        self.clock_id
+    }
+
+    /// Request presentation feedback for the next commit of a surface.
+    ///
+    /// The feedback is delivered to [`PresentationHandler::presented`] or
+    /// [`PresentationHandler::discarded`], after which the feedback object is destroyed.
+    pub fn feedback<D>(
+        &self,
+        surface: &wl_surface::WlSurface,
+        qh: &QueueHandle<D>,
+    ) -> wp_presentation_feedback::WpPresentationFeedback
+    where
+        D: Dispatch<wp_presentation_feedback::WpPresentationFeedback, PresentationFeedbackData>
+            + 'static,
+    {
+        let data = PresentationFeedbackData { surface: surface.clone(), output: Mutex::new(None) };
+        self.presentation.feedback(surface, qh, data)
+    }
+}
+
+impl ProvidesBoundGlobal<wp_presentation::WpPresentation, 1> for PresentationState {
+    fn bound_global(&self) -> Result<wp_presentation::WpPresentation, GlobalError> {
+        Ok(self.presentation.clone())
+    }
+}
+
+/// The feedback of a commit that was shown.
+#[derive(Debug, Clone)]
+pub struct Presented {
+    /// The time at which the content was shown, in the clock of the compositor.
+    pub time: PresentationTime,
+
+    /// The duration until the next refresh of the output, or [`None`] if it is unknown, such as for
+    /// outputs with a variable refresh rate.
+    pub refresh: Option<Duration>,
+
+    /// The vertical retrace counter of the output.
+    ///
+    /// This is only meaningful if [`Kind::Vsync`] is set.
+    pub seq: u64,
+
+    /// How the content was shown.
+    pub flags: Kind,
+
+    /// The output the content was shown on, if it is bound by the client.
+    pub output: Option<wl_output::WlOutput>,
+}
+
+/// A timestamp of the presentation clock.
+///
+/// This acts like an [`Instant`](std::time::Instant) for the clock chosen by the compositor, which may
+/// not be the clock used by the standard library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PresentationTime {
+    clock_id: u32,
+    time: Duration,
+}
+
+impl PresentationTime {
+    /// The current time of a clock, or [`None`] if the clock is not supported by the system.
+    pub fn now(clock_id: u32) -> Option<Self> {
+        let mut ts = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+        // SAFETY: ts is a valid timespec to write to.
+        if unsafe { libc::clock_gettime(clock_id as libc::clockid_t, &mut ts) } != 0 {
+            return None;
+        }
+
+        let time = Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32);
+        Some(Self { clock_id, time })
+    }
+
+    /// The id of the clock of this timestamp.
+    pub fn clock_id(&self) -> u32 {
+        self.clock_id
+    }
+
+    /// The time since the epoch of the clock, which depends on the clock.
+    pub fn since_epoch(&self) -> Duration {
+        self.time
+    }
+
+    /// The time elapsed from an earlier timestamp to this one.
+    ///
+    /// Returns [`None`] if the timestamps use different clocks, or if `earlier` is later than this
+    /// timestamp.
+    pub fn duration_since(&self, earlier: PresentationTime) -> Option<Duration> {
+        if self.clock_id != earlier.clock_id {
+            return None;
+        }
+
+        self.time.checked_sub(earlier.time)
+    }
+
+    /// The timestamp a duration after this one, in the same clock, or [`None`] if it overflows.
+    pub fn checked_add(&self, duration: Duration) -> Option<PresentationTime> {
+        Some(Self { clock_id: self.clock_id, time: self.time.checked_add(duration)? })
+    }
+
+    /// The time elapsed since this timestamp, or [`None`] if the clock is not supported.
+    pub fn elapsed(&self) -> Option<Duration> {
+        Self::now(self.clock_id)?.duration_since(*self)
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct PresentationFeedbackData {
+    surface: wl_surface::WlSurface,
+    output: Mutex<Option<wl_output::WlOutput>>,
+}
+
+impl<D> Dispatch<wp_presentation::WpPresentation, GlobalData, D> for PresentationState
+where
+    D: Dispatch<wp_presentation::WpPresentation, GlobalData> + PresentationHandler,
+{
+    fn event(
+        state: &mut D,
+        _: &wp_presentation::WpPresentation,
+        event: wp_presentation::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        match event {
+            wp_presentation::Event::ClockId { clk_id } => {
+                state.presentation_state().clock_id = Some(clk_id);
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<wp_presentation_feedback::WpPresentationFeedback, PresentationFeedbackData, D>
+    for PresentationState
+where
+    D: Dispatch<wp_presentation_feedback::WpPresentationFeedback, PresentationFeedbackData>
+        + PresentationHandler,
+{
+    fn event(
+        state: &mut D,
+        _: &wp_presentation_feedback::WpPresentationFeedback,
+        event: wp_presentation_feedback::Event,
+        data: &PresentationFeedbackData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        match event {
+            wp_presentation_feedback::Event::SyncOutput { output } => {
+                *data.output.lock().unwrap() = Some(output);
+            }
+
+            wp_presentation_feedback::Event::Presented {
+                tv_sec_hi,
+                tv_sec_lo,
+                tv_nsec,
+                refresh,
+                seq_hi,
+                seq_lo,
+                flags,
+            } => {
+                // The clock is always sent before any feedback, the fallback is the clock most
+                // compositors use.
+                let clock_id =
+                    state.presentation_state().clock_id.unwrap_or(libc::CLOCK_MONOTONIC as u32);
+                let secs = (u64::from(tv_sec_hi) << 32) | u64::from(tv_sec_lo);
+                let time = PresentationTime { clock_id, time: Duration::new(secs, tv_nsec) };
+                let flags = match flags {
+                    WEnum::Value(flags) => flags,
+                    WEnum::Unknown(raw) => Kind::from_bits_truncate(raw),
+                };
+
+                let presented = Presented {
+                    time,
+                    refresh: (refresh != 0).then(|| Duration::from_nanos(refresh.into())),
+                    seq: (u64::from(seq_hi) << 32) | u64::from(seq_lo),
+                    flags,
+                    output: data.output.lock().unwrap().take(),
+                };
+
+                state.presented(conn, qh, &data.surface, presented);
+            }
+
+            wp_presentation_feedback::Event::Discarded => {
+                state.discarded(conn, qh, &data.surface);
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_presentation_time {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::presentation_time::client::wp_presentation::WpPresentation: $crate::globals::GlobalData
+        ] => $crate::presentation_time::PresentationState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::presentation_time::client::wp_presentation_feedback::WpPresentationFeedback: $crate::presentation_time::PresentationFeedbackData
+        ] => $crate::presentation_time::PresentationState);
+    };
+}
@@ -96,6 +96,9 @@ where
     /// initial enumeration of globals. It is primarily useful for multi-instance globals such as
     /// `wl_output` and `wl_seat`.
     ///
+    /// The global is already listed by [`RegistryState::globals`] when this is called, so it can be
+    /// bound with [`RegistryState::bind_specific`].
+    ///
     /// The default implementation does nothing.
     fn new_global(
         data: &mut D,
@@ -110,6 +113,9 @@ where
 
     /// Called when a global has been destroyed by the compositor.
     ///
+    /// The global is no longer listed by [`RegistryState::globals`] when this is called. Objects
+    /// bound from the global remain valid, but should be destroyed or released.
+    ///
     /// The default implementation does nothing.
     fn remove_global(
         data: &mut D,
@@ -176,9 +182,9 @@ impl RegistryState {
         &self.registry
     }
 
-    /// Returns an iterator over all globals.
+    /// Returns an iterator over all globals, with their name, interface and version.
     ///
-    /// This list may change if the compositor adds or removes globals after initial
+    /// This list is updated when the compositor adds or removes globals after initial
     /// enumeration.
     ///
     /// No guarantees are provided about the ordering of the globals in this iterator.
@@ -196,6 +202,15 @@ impl RegistryState {
         self.globals.iter().filter(move |g| g.interface == interface)
     }
 
+    /// Returns whether a global implementing the given interface is advertised with at least the
+    /// given version.
+    ///
+    /// This may be used to check whether an optional protocol is supported before binding it, or to
+    /// report which protocols the compositor lacks.
+    pub fn contains(&self, interface: &str, min_version: u32) -> bool {
+        self.globals_by_interface(interface).any(|global| global.version >= min_version)
+    }
+
     /// Binds a global, returning a new object associated with the global.
     ///
     /// This should not be used to bind globals that have multiple instances such as `wl_output`;
@@ -254,6 +269,8 @@ impl RegistryState {
     }
 
     /// Binds all globals with a given interface.
+    ///
+    /// See [`MultiGlobal::bind_all`] to also keep track of the names of the globals.
     pub fn bind_all<I, D, U, F>(
         &self,
         qh: &QueueHandle<D>,
@@ -295,10 +312,63 @@ impl RegistryState {
 ///     }
 /// }
 /// ```
+///
+/// ## Generic state
+///
+/// This macro and all the other `delegate_*` macros of this crate accept the generic parameters of the
+/// state before its type, prefixed with `@`. Each parameter may be bounded by single token bounds, such
+/// as traits in scope and lifetimes, joined with `+`. Other bounds, such as traits with generic
+/// arguments, go in a where clause after all the other arguments of the macro. The last argument of
+/// [`delegate_simple`](crate::delegate_simple) is an expression, so its where clause is preceded by a
+/// comma.
+///
+/// [`registry_handlers`](crate::registry_handlers) is used inside the `impl` block and does not need the
+/// generic parameters.
+///
+/// ```
+/// use smithay_client_toolkit::{
+///     delegate_registry, delegate_shm,
+///     registry::{ProvidesRegistryState, RegistryState},
+///     registry_handlers,
+///     shm::{Shm, ShmHandler},
+/// };
+///
+/// trait Backend {
+///     type Buffer;
+/// }
+///
+/// struct App<B, T> {
+///     registry_state: RegistryState,
+///     shm_state: Shm,
+///     backend: B,
+///     buffer: Option<T>,
+/// }
+///
+/// delegate_shm!(@<B: Backend + 'static, T: 'static> App<B, T>);
+/// delegate_registry!(@<B, T> App<B, T> where B: Backend<Buffer = T> + 'static, T: 'static);
+///
+/// impl<B: Backend + 'static, T: 'static> ShmHandler for App<B, T> {
+///     fn shm_state(&mut self) -> &mut Shm {
+///         &mut self.shm_state
+///     }
+/// }
+///
+/// impl<B, T> ProvidesRegistryState for App<B, T>
+/// where
+///     B: Backend<Buffer = T> + 'static,
+///     T: 'static,
+/// {
+///     fn registry(&mut self) -> &mut RegistryState {
+///         &mut self.registry_state
+///     }
+///
+///     registry_handlers![];
+/// }
+/// ```
 #[macro_export]
 macro_rules! delegate_registry {
-    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
-        $crate::reexports::client::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty:
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
             [
                 $crate::reexports::client::protocol::wl_registry::WlRegistry: $crate::reexports::client::globals::GlobalListContents
             ]  => $crate::registry::RegistryState
@@ -306,6 +376,58 @@ macro_rules! delegate_registry {
     };
 }
 
+/// Implements [`Dispatch`] for a state by delegating it to another type.
+///
+/// This is [`delegate_dispatch!`](wayland_client::delegate_dispatch), extended with an optional
+/// where clause in braces after the state, and a list of interfaces. It is used by the `delegate_*`
+/// macros of this crate to support the syntax described in [`delegate_registry`].
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __delegate_dispatch {
+    (@list $head: tt [$($interface: ty: $udata: ty),*] => $dispatch_to: ty) => {
+        $(
+            $crate::__delegate_dispatch!(@impl $head $interface: $udata => $dispatch_to);
+        )*
+    };
+    (@impl { $(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where { $($bound:tt)* })? }
+        $interface: ty: $udata: ty => $dispatch_to: ty
+    ) => {
+        impl$(< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $crate::reexports::client::Dispatch<$interface, $udata> for $ty
+        $(where $($bound)*)?
+        {
+            fn event(
+                state: &mut Self,
+                proxy: &$interface,
+                event: <$interface as $crate::reexports::client::Proxy>::Event,
+                data: &$udata,
+                conn: &$crate::reexports::client::Connection,
+                qhandle: &$crate::reexports::client::QueueHandle<Self>,
+            ) {
+                <$dispatch_to as $crate::reexports::client::Dispatch<$interface, $udata, Self>>::event(
+                    state, proxy, event, data, conn, qhandle,
+                )
+            }
+
+            fn event_created_child(
+                opcode: u16,
+                qhandle: &$crate::reexports::client::QueueHandle<Self>,
+            ) -> ::std::sync::Arc<dyn $crate::reexports::client::backend::ObjectData> {
+                <$dispatch_to as $crate::reexports::client::Dispatch<$interface, $udata, Self>>::event_created_child(
+                    opcode, qhandle,
+                )
+            }
+        }
+    };
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $bounds: tt)?:
+        [$($interface: ty: $udata: ty),* $(,)?] => $dispatch_to: ty
+    ) => {
+        $crate::__delegate_dispatch!(
+            @list { $(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where $bounds)? }
+            [$($interface: $udata),*] => $dispatch_to
+        );
+    };
+}
+
 impl<D> Dispatch<wl_registry::WlRegistry, GlobalListContents, D> for RegistryState
 where
     D: Dispatch<wl_registry::WlRegistry, GlobalListContents> + ProvidesRegistryState,
@@ -345,6 +467,23 @@ where
 pub enum GlobalProxy<I> {
     /// The requested global was not present after a complete enumeration.
     NotPresent,
+    /// The requested global was advertised, but with a version outside of the requested range.
+    UnsupportedVersion {
+        /// The range of versions that was requested.
+        requested: std::ops::RangeInclusive<u32>,
+        /// The highest version the global is advertised with.
+        advertised: u32,
+    },
+    /// The requested global is advertised, but is only bound on first use, see
+    /// [`GlobalProxy::get_or_bind`].
+    Lazy {
+        /// The registry the global is advertised on.
+        registry: wl_registry::WlRegistry,
+        /// The name of the global.
+        name: u32,
+        /// The version the global will be bound with.
+        version: u32,
+    },
     /// The cached global.
     Bound(I),
 }
@@ -358,6 +497,134 @@ impl<I> From<Result<I, BindError>> for GlobalProxy<I> {
     }
 }
 
+impl<I: Proxy + 'static> GlobalProxy<I> {
+    /// Binds a global, keeping track of why it could not be bound.
+    ///
+    /// Unlike converting the result of [`GlobalList::bind`], this remembers the advertised version
+    /// of a global that does not support the requested versions, which is then reported by
+    /// [`GlobalError::UnsupportedVersion`].
+    pub fn bind<D, U>(
+        globals: &GlobalList,
+        qh: &QueueHandle<D>,
+        version: std::ops::RangeInclusive<u32>,
+        udata: U,
+    ) -> Self
+    where
+        D: Dispatch<I, U> + 'static,
+        U: Send + Sync + 'static,
+    {
+        match globals.bind(qh, version.clone(), udata) {
+            Ok(proxy) => GlobalProxy::Bound(proxy),
+            Err(BindError::UnsupportedVersion) => {
+                let advertised = globals.contents().with_list(|list| {
+                    list.iter()
+                        .filter(|global| global.interface == I::interface().name)
+                        .map(|global| global.version)
+                        .max()
+                        .unwrap_or(0)
+                });
+                GlobalProxy::UnsupportedVersion { requested: version, advertised }
+            }
+            Err(BindError::NotPresent) => GlobalProxy::NotPresent,
+        }
+    }
+
+    /// Finds a global without binding it yet.
+    ///
+    /// The global is bound by [`GlobalProxy::get_or_bind`] when it is first needed, which avoids
+    /// creating objects for optional protocols that may never be used. Until then,
+    /// [`GlobalProxy::get`] returns [`GlobalError::NotBound`].
+    ///
+    /// A global advertised later is picked up by [`GlobalProxy::global_added`], and a global removed
+    /// before it is bound is forgotten by [`GlobalProxy::global_removed`]. States using this should
+    /// call both from their [`RegistryHandler`], like
+    /// [`KdeBlurManagerState`](crate::shell::plasma::blur::KdeBlurManagerState) does.
+    pub fn lazy(globals: &GlobalList, version: std::ops::RangeInclusive<u32>) -> Self {
+        let iface = I::interface();
+        if *version.end() > iface.version {
+            // This is a panic because it's a compile-time programmer error, not a runtime error.
+            panic!("Maximum version ({}) of {} was higher than the proxy's maximum version ({}); outdated wayland XML files?",
+                version.end(), iface.name, iface.version);
+        }
+
+        globals.contents().with_list(|list| {
+            match list.iter().find(|global| global.interface == iface.name) {
+                Some(global) => {
+                    Self::found(globals.registry(), global.name, global.version, version)
+                }
+                None => GlobalProxy::NotPresent,
+            }
+        })
+    }
+
+    /// Picks up a global advertised after [`GlobalProxy::lazy`], from
+    /// [`RegistryHandler::new_global`].
+    ///
+    /// A missing global of this interface is then bound on first use, or reported as
+    /// [`GlobalError::UnsupportedVersion`] if its version is outside of `version`. A global that is
+    /// already found or bound is kept.
+    pub fn global_added(
+        &mut self,
+        registry: &wl_registry::WlRegistry,
+        name: u32,
+        interface: &str,
+        advertised: u32,
+        version: std::ops::RangeInclusive<u32>,
+    ) {
+        if interface != I::interface().name
+            || matches!(self, GlobalProxy::Lazy { .. } | GlobalProxy::Bound(_))
+        {
+            return;
+        }
+
+        *self = Self::found(registry, name, advertised, version);
+    }
+
+    /// The state of an advertised global which is not bound yet.
+    fn found(
+        registry: &wl_registry::WlRegistry,
+        name: u32,
+        advertised: u32,
+        version: std::ops::RangeInclusive<u32>,
+    ) -> Self {
+        if advertised < *version.start() {
+            return GlobalProxy::UnsupportedVersion { requested: version, advertised };
+        }
+
+        GlobalProxy::Lazy {
+            registry: registry.clone(),
+            name,
+            version: advertised.min(*version.end()),
+        }
+    }
+
+    /// Returns the global, binding it first if it was found with [`GlobalProxy::lazy`].
+    pub fn get_or_bind<D, U>(&mut self, qh: &QueueHandle<D>, udata: U) -> Result<&I, GlobalError>
+    where
+        D: Dispatch<I, U> + 'static,
+        U: Send + Sync + 'static,
+    {
+        if let GlobalProxy::Lazy { registry, name, version } = self {
+            let proxy = registry.bind(*name, *version, qh, udata);
+            log::debug!(target: "sctk", "Bound new global [{}] {} v{}", name, I::interface().name, version);
+            *self = GlobalProxy::Bound(proxy);
+        }
+
+        self.get()
+    }
+
+    /// Forgets a global found with [`GlobalProxy::lazy`] if it is removed before being bound, from
+    /// [`RegistryHandler::remove_global`].
+    ///
+    /// Afterwards the global is reported as missing. A global that is already bound is kept, since
+    /// its objects stay valid until they are destroyed.
+    pub fn global_removed(&mut self, name: u32) {
+        if matches!(self, GlobalProxy::Lazy { name: lazy_name, .. } if *lazy_name == name) {
+            *self = GlobalProxy::NotPresent;
+        }
+    }
+}
+
 impl<I: Proxy> GlobalProxy<I> {
     pub fn get(&self) -> Result<&I, GlobalError> {
         self.with_min_version(0)
@@ -376,6 +643,14 @@ impl<I: Proxy> GlobalProxy<I> {
                     Ok(proxy)
                 }
             }
+            GlobalProxy::UnsupportedVersion { requested, advertised } => {
+                Err(GlobalError::UnsupportedVersion {
+                    name: I::interface().name,
+                    requested: requested.clone(),
+                    advertised: *advertised,
+                })
+            }
+            GlobalProxy::Lazy { .. } => Err(GlobalError::NotBound(I::interface().name)),
             GlobalProxy::NotPresent => Err(GlobalError::MissingGlobal(I::interface().name)),
         }
     }
@@ -422,14 +697,119 @@ where
     }
 }
 
+/// All the bound instances of a global which may be advertised several times, such as `wl_seat`.
+///
+/// Each proxy is stored with the name of the global it was bound from, so it can be found when the
+/// global is removed, see [`RegistryHandler::remove_global`].
+#[derive(Debug)]
+pub struct MultiGlobal<I> {
+    globals: Vec<(u32, I)>,
+}
+
+impl<I> Default for MultiGlobal<I> {
+    fn default() -> Self {
+        Self { globals: Vec::new() }
+    }
+}
+
+impl<I: Proxy + 'static> MultiGlobal<I> {
+    /// Creates an empty container.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Binds all instances of the global advertised during initial enumeration.
+    ///
+    /// The user data of each instance is created by `make_udata` from the name of its global.
+    pub fn bind_all<D, U, F>(
+        globals: &GlobalList,
+        qh: &QueueHandle<D>,
+        version: std::ops::RangeInclusive<u32>,
+        make_udata: F,
+    ) -> Result<Self, BindError>
+    where
+        D: Dispatch<I, U> + 'static,
+        F: FnMut(u32) -> U,
+        U: Send + Sync + 'static,
+    {
+        let globals = globals.contents().with_list(|list| {
+            bind_all_with_names(globals.registry(), list, qh, version, make_udata)
+        })?;
+        Ok(Self { globals })
+    }
+
+    /// Adds an instance bound from the global with the given name, usually in
+    /// [`RegistryHandler::new_global`].
+    pub fn add(&mut self, name: u32, proxy: I) {
+        self.globals.push((name, proxy));
+    }
+
+    /// Removes the instance bound from the global with the given name, usually in
+    /// [`RegistryHandler::remove_global`].
+    ///
+    /// The proxy is returned so it can be destroyed or released.
+    pub fn remove(&mut self, name: u32) -> Option<I> {
+        let position = self.globals.iter().position(|(global_name, _)| *global_name == name)?;
+        Some(self.globals.remove(position).1)
+    }
+
+    /// Returns the instance bound from the global with the given name.
+    pub fn get_by_name(&self, name: u32) -> Option<&I> {
+        self.globals.iter().find(|(global_name, _)| *global_name == name).map(|(_, proxy)| proxy)
+    }
+
+    /// Returns the name of the global the proxy was bound from.
+    pub fn name_of(&self, proxy: &I) -> Option<u32> {
+        self.globals.iter().find(|(_, p)| p.id() == proxy.id()).map(|(name, _)| *name)
+    }
+
+    /// Returns whether the proxy is one of the instances.
+    pub fn contains(&self, proxy: &I) -> bool {
+        self.name_of(proxy).is_some()
+    }
+
+    /// Returns an iterator over the instances, in the order they were bound.
+    pub fn iter(&self) -> impl Iterator<Item = &I> + '_ {
+        self.globals.iter().map(|(_, proxy)| proxy)
+    }
+
+    /// Returns the number of instances.
+    pub fn len(&self) -> usize {
+        self.globals.len()
+    }
+
+    /// Returns whether there are no instances.
+    pub fn is_empty(&self) -> bool {
+        self.globals.is_empty()
+    }
+}
+
 /// Binds all globals with a given interface.
 pub(crate) fn bind_all<I, D, U, F>(
     registry: &wl_registry::WlRegistry,
     globals: &[Global],
     qh: &QueueHandle<D>,
     version: std::ops::RangeInclusive<u32>,
-    mut make_udata: F,
+    make_udata: F,
 ) -> Result<Vec<I>, BindError>
+where
+    D: Dispatch<I, U> + 'static,
+    I: Proxy + 'static,
+    F: FnMut(u32) -> U,
+    U: Send + Sync + 'static,
+{
+    let proxies = bind_all_with_names(registry, globals, qh, version, make_udata)?;
+    Ok(proxies.into_iter().map(|(_, proxy)| proxy).collect())
+}
+
+/// Binds all globals with a given interface, along with the names of the globals.
+fn bind_all_with_names<I, D, U, F>(
+    registry: &wl_registry::WlRegistry,
+    globals: &[Global],
+    qh: &QueueHandle<D>,
+    version: std::ops::RangeInclusive<u32>,
+    mut make_udata: F,
+) -> Result<Vec<(u32, I)>, BindError>
 where
     D: Dispatch<I, U> + 'static,
     I: Proxy + 'static,
@@ -455,7 +835,7 @@ where
         let proxy = registry.bind(global.name, version, qh, udata);
         log::debug!(target: "sctk", "Bound new global [{}] {} v{}", global.name, iface.name, version);
 
-        rv.push(proxy);
+        rv.push((global.name, proxy));
     }
     Ok(rv)
 }
@@ -502,8 +882,8 @@ where
 
 #[macro_export]
 macro_rules! delegate_simple {
-    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty:ty, $iface:ty, $max:expr) => {
-        $crate::reexports::client::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [ $iface: () ]
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty:ty, $iface:ty, $max:expr $(, where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [ $iface: () ]
             => $crate::registry::SimpleGlobal<$iface, $max>
         );
     };
@@ -511,6 +891,10 @@ macro_rules! delegate_simple {
 
 /// A helper macro for implementing [`ProvidesRegistryState`].
 ///
+/// Globals added or removed after initial enumeration are passed to
+/// [`RegistryHandler::new_global`] and [`RegistryHandler::remove_global`] of each of the listed
+/// types, in the order they are listed.
+///
 /// See [`delegate_registry`] for an example.
 #[macro_export]
 macro_rules! registry_handlers {
@@ -541,3 +925,271 @@ macro_rules! registry_handlers {
         }
     }
 }
+
+/// Compile tests of the `delegate_*` macros with a generic state, whose bounds need where clauses.
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+
+    #[cfg(feature = "xkbcommon")]
+    use wayland_client::protocol::wl_keyboard::WlKeyboard;
+    use wayland_client::{
+        protocol::{
+            wl_compositor::WlCompositor,
+            wl_output::{Transform, WlOutput},
+            wl_pointer::WlPointer,
+            wl_seat::WlSeat,
+            wl_shm::WlShm,
+            wl_surface::WlSurface,
+        },
+        Connection, Dispatch, QueueHandle,
+    };
+    use wayland_protocols::wp::viewporter::client::wp_viewporter::WpViewporter;
+    use wayland_protocols_plasma::blur::client::org_kde_kwin_blur_manager::OrgKdeKwinBlurManager;
+
+    use super::{ProvidesRegistryState, RegistryState};
+    use crate::{
+        compositor::{CompositorHandler, SurfaceData},
+        delegate_compositor, delegate_kde_blur, delegate_output, delegate_pointer, delegate_seat,
+        delegate_shm,
+        globals::GlobalData,
+        output::{OutputData, OutputHandler, OutputState},
+        seat::{
+            pointer::{PointerData, PointerEvent, PointerHandler},
+            Capability, SeatData, SeatHandler, SeatState,
+        },
+        shm::{Shm, ShmHandler},
+    };
+    #[cfg(feature = "xkbcommon")]
+    use crate::{
+        delegate_keyboard,
+        seat::keyboard::{KeyEvent, KeyboardData, KeyboardHandler, Keysym, Modifiers},
+    };
+
+    trait Backend {
+        type Buffer;
+    }
+
+    struct TestBackend;
+
+    impl Backend for TestBackend {
+        type Buffer = u32;
+    }
+
+    struct App<B, T> {
+        registry_state: RegistryState,
+        output_state: OutputState,
+        seat_state: SeatState,
+        shm: Shm,
+        backend: PhantomData<(B, T)>,
+    }
+
+    delegate_registry!(@<B, T> App<B, T> where B: Backend<Buffer = T> + 'static, T: 'static);
+    delegate_shm!(@<B, T> App<B, T> where B: Backend<Buffer = T> + 'static, T: 'static);
+    delegate_compositor!(@<B, T> App<B, T>, surface: [SurfaceData] where B: Backend<Buffer = T> + 'static, T: 'static);
+    delegate_output!(@<B, T> App<B, T> where B: Backend<Buffer = T> + 'static, T: 'static);
+    delegate_seat!(@<B, T> App<B, T> where B: Backend<Buffer = T> + 'static, T: 'static);
+    delegate_pointer!(@<B, T> App<B, T>, pointer: [PointerData] where B: Backend<Buffer = T> + 'static, T: 'static);
+    #[cfg(feature = "xkbcommon")]
+    delegate_keyboard!(@<B, T> App<B, T>, keyboard: [KeyboardData<App<B, T>>] where B: Backend<Buffer = T> + 'static, T: 'static);
+    delegate_kde_blur!(@<B, T> App<B, T> where B: Backend<Buffer = T> + 'static, T: 'static);
+    delegate_simple!(@<B, T> App<B, T>, WpViewporter, 1, where B: Backend<Buffer = T> + 'static, T: 'static);
+
+    impl<B, T> ProvidesRegistryState for App<B, T>
+    where
+        B: Backend<Buffer = T> + 'static,
+        T: 'static,
+    {
+        fn registry(&mut self) -> &mut RegistryState {
+            &mut self.registry_state
+        }
+
+        registry_handlers!(OutputState, SeatState);
+    }
+
+    impl<B, T> ShmHandler for App<B, T>
+    where
+        B: Backend<Buffer = T> + 'static,
+        T: 'static,
+    {
+        fn shm_state(&mut self) -> &mut Shm {
+            &mut self.shm
+        }
+    }
+
+    impl<B, T> CompositorHandler for App<B, T>
+    where
+        B: Backend<Buffer = T> + 'static,
+        T: 'static,
+    {
+        fn scale_factor_changed(
+            &mut self,
+            _: &Connection,
+            _: &QueueHandle<Self>,
+            _: &WlSurface,
+            _: i32,
+        ) {
+        }
+
+        fn transform_changed(
+            &mut self,
+            _: &Connection,
+            _: &QueueHandle<Self>,
+            _: &WlSurface,
+            _: Transform,
+        ) {
+        }
+
+        fn frame(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &WlSurface, _: u32) {}
+    }
+
+    impl<B, T> OutputHandler for App<B, T>
+    where
+        B: Backend<Buffer = T> + 'static,
+        T: 'static,
+    {
+        fn output_state(&mut self) -> &mut OutputState {
+            &mut self.output_state
+        }
+
+        fn new_output(&mut self, _: &Connection, _: &QueueHandle<Self>, _: WlOutput) {}
+
+        fn update_output(&mut self, _: &Connection, _: &QueueHandle<Self>, _: WlOutput) {}
+
+        fn output_destroyed(&mut self, _: &Connection, _: &QueueHandle<Self>, _: WlOutput) {}
+    }
+
+    impl<B, T> SeatHandler for App<B, T>
+    where
+        B: Backend<Buffer = T> + 'static,
+        T: 'static,
+    {
+        fn seat_state(&mut self) -> &mut SeatState {
+            &mut self.seat_state
+        }
+
+        fn new_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: WlSeat) {}
+
+        fn new_capability(
+            &mut self,
+            _: &Connection,
+            _: &QueueHandle<Self>,
+            _: WlSeat,
+            _: Capability,
+        ) {
+        }
+
+        fn remove_capability(
+            &mut self,
+            _: &Connection,
+            _: &QueueHandle<Self>,
+            _: WlSeat,
+            _: Capability,
+        ) {
+        }
+
+        fn remove_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: WlSeat) {}
+    }
+
+    impl<B, T> PointerHandler for App<B, T>
+    where
+        B: Backend<Buffer = T> + 'static,
+        T: 'static,
+    {
+        fn pointer_frame(
+            &mut self,
+            _: &Connection,
+            _: &QueueHandle<Self>,
+            _: &WlPointer,
+            _: &[PointerEvent],
+        ) {
+        }
+    }
+
+    #[cfg(feature = "xkbcommon")]
+    impl<B, T> KeyboardHandler for App<B, T>
+    where
+        B: Backend<Buffer = T> + 'static,
+        T: 'static,
+    {
+        fn enter(
+            &mut self,
+            _: &Connection,
+            _: &QueueHandle<Self>,
+            _: &WlKeyboard,
+            _: &WlSurface,
+            _: u32,
+            _: &[u32],
+            _: &[Keysym],
+        ) {
+        }
+
+        fn leave(
+            &mut self,
+            _: &Connection,
+            _: &QueueHandle<Self>,
+            _: &WlKeyboard,
+            _: &WlSurface,
+            _: u32,
+        ) {
+        }
+
+        fn press_key(
+            &mut self,
+            _: &Connection,
+            _: &QueueHandle<Self>,
+            _: &WlKeyboard,
+            _: u32,
+            _: KeyEvent,
+        ) {
+        }
+
+        fn release_key(
+            &mut self,
+            _: &Connection,
+            _: &QueueHandle<Self>,
+            _: &WlKeyboard,
+            _: u32,
+            _: KeyEvent,
+        ) {
+        }
+
+        fn update_modifiers(
+            &mut self,
+            _: &Connection,
+            _: &QueueHandle<Self>,
+            _: &WlKeyboard,
+            _: u32,
+            _: Modifiers,
+            _: u32,
+        ) {
+        }
+    }
+
+    fn assert_dispatch<D>()
+    where
+        D: Dispatch<WlCompositor, GlobalData>
+            + Dispatch<WlSurface, SurfaceData>
+            + Dispatch<WlShm, GlobalData>
+            + Dispatch<WlOutput, OutputData>
+            + Dispatch<WlSeat, SeatData>
+            + Dispatch<WlPointer, PointerData>
+            + Dispatch<OrgKdeKwinBlurManager, GlobalData>
+            + Dispatch<WpViewporter, ()>,
+    {
+    }
+
+    #[cfg(feature = "xkbcommon")]
+    fn assert_keyboard_dispatch<D>()
+    where
+        D: Dispatch<WlKeyboard, KeyboardData<D>>,
+    {
+    }
+
+    #[test]
+    fn generic_state() {
+        assert_dispatch::<App<TestBackend, u32>>();
+        #[cfg(feature = "xkbcommon")]
+        assert_keyboard_dispatch::<App<TestBackend, u32>>();
+    }
+}
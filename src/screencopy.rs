@@ -0,0 +1,379 @@
+//! ## wlr screencopy
+//!
+//! The `zwlr_screencopy_manager_v1` global lets screenshot and screen recording tools copy the content
+//! of an output, or of a region of an output, into a buffer of the client.
+//!
+//! A capture is started with [`ScreencopyState::capture_output`], after which the compositor describes
+//! the buffers it can copy into. Once [`ScreencopyHandler::buffer_done`] is called, a matching buffer is
+//! passed to [`ScreencopyFrame::copy`], or allocated from a [`SlotPool`] with
+//! [`ScreencopyFrame::capture_to_slotpool`]. The result is reported to [`ScreencopyHandler::ready`] or
+//! [`ScreencopyHandler::failed`].
+
+use std::{sync::Mutex, time::Duration};
+
+use wayland_client::{
+    globals::{BindError, GlobalList},
+    protocol::{wl_buffer, wl_output, wl_shm},
+    Connection, Dispatch, Proxy, QueueHandle, WEnum,
+};
+use wayland_protocols_wlr::screencopy::v1::client::{
+    zwlr_screencopy_frame_v1, zwlr_screencopy_manager_v1,
+};
+
+use crate::{
+    globals::GlobalData,
+    shm::slot::{Buffer, CreateBufferError, SlotPool},
+};
+
+/// Handler for screencopy frames.
+pub trait ScreencopyHandler: Sized {
+    /// The compositor can copy the frame into a shm buffer with this format and size.
+    fn buffer(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        frame: &ScreencopyFrame,
+        buffer: ShmBufferInfo,
+    );
+
+    /// All buffer types were described, and the frame can be copied.
+    ///
+    /// This is called right after [`ScreencopyHandler::buffer`] if the compositor only supports
+    /// versions 1 or 2 of the protocol.
+    fn buffer_done(&mut self, conn: &Connection, qh: &QueueHandle<Self>, frame: &ScreencopyFrame);
+
+    /// Whether the copied frame is vertically flipped. This is sent before the frame is ready.
+    fn flags(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        frame: &ScreencopyFrame,
+        y_invert: bool,
+    );
+
+    /// A region of the frame changed since the last copy, as `(x, y, width, height)` in buffer
+    /// coordinates.
+    ///
+    /// This is only sent for frames copied with [`ScreencopyFrame::copy_with_damage`].
+    fn damage(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        frame: &ScreencopyFrame,
+        damage: (u32, u32, u32, u32),
+    );
+
+    /// The frame was copied into the buffer.
+    ///
+    /// `time` is the time the frame was presented at, in the `CLOCK_MONOTONIC` clock.
+    fn ready(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        frame: &ScreencopyFrame,
+        time: Duration,
+    );
+
+    /// The frame could not be copied, for example because the buffer does not match the advertised
+    /// ones or the output was removed.
+    fn failed(&mut self, conn: &Connection, qh: &QueueHandle<Self>, frame: &ScreencopyFrame);
+}
+
+/// The screencopy manager global.
+#[derive(Debug)]
+pub struct ScreencopyState {
+    manager: zwlr_screencopy_manager_v1::ZwlrScreencopyManagerV1,
+}
+
+impl ScreencopyState {
+    /// Binds the `zwlr_screencopy_manager_v1` global.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support wlr screencopy.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State: Dispatch<zwlr_screencopy_manager_v1::ZwlrScreencopyManagerV1, GlobalData> + 'static,
+    {
+        let manager = globals.bind(qh, 1..=3, GlobalData)?;
+        Ok(Self { manager })
+    }
+
+    /// Start capturing the content of an output, with or without the cursor drawn on top of it.
+    pub fn capture_output<D>(
+        &self,
+        overlay_cursor: bool,
+        output: &wl_output::WlOutput,
+        qh: &QueueHandle<D>,
+    ) -> ScreencopyFrame
+    where
+        D: Dispatch<zwlr_screencopy_frame_v1::ZwlrScreencopyFrameV1, ScreencopyFrameData> + 'static,
+    {
+        let frame = self.manager.capture_output(
+            overlay_cursor.into(),
+            output,
+            qh,
+            ScreencopyFrameData::default(),
+        );
+        ScreencopyFrame(frame)
+    }
+
+    /// Start capturing a region of an output, given as `(x, y, width, height)` in the logical
+    /// coordinates of the output.
+    pub fn capture_output_region<D>(
+        &self,
+        overlay_cursor: bool,
+        output: &wl_output::WlOutput,
+        (x, y, width, height): (i32, i32, i32, i32),
+        qh: &QueueHandle<D>,
+    ) -> ScreencopyFrame
+    where
+        D: Dispatch<zwlr_screencopy_frame_v1::ZwlrScreencopyFrameV1, ScreencopyFrameData> + 'static,
+    {
+        let frame = self.manager.capture_output_region(
+            overlay_cursor.into(),
+            output,
+            x,
+            y,
+            width,
+            height,
+            qh,
+            ScreencopyFrameData::default(),
+        );
+        ScreencopyFrame(frame)
+    }
+}
+
+/// A shm buffer the compositor can copy a frame into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShmBufferInfo {
+    pub format: wl_shm::Format,
+    pub width: u32,
+    pub height: u32,
+    /// The distance in bytes between the beginning of a row and the next one.
+    pub stride: u32,
+}
+
+/// A dmabuf buffer the compositor can copy a frame into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DmabufBufferInfo {
+    /// The DRM fourcc format.
+    pub format: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// An error that may occur when copying a frame into a buffer of a [`SlotPool`].
+#[derive(Debug, thiserror::Error)]
+pub enum ScreencopyError {
+    /// The compositor did not advertise a shm buffer for the frame.
+    #[error("The compositor did not advertise a shm buffer")]
+    NoShmBuffer,
+
+    /// The frame was already copied.
+    #[error("The frame was already copied")]
+    AlreadyCopied,
+
+    /// The buffer could not be allocated.
+    #[error(transparent)]
+    CreateBuffer(#[from] CreateBufferError),
+}
+
+/// A frame being captured.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScreencopyFrame(zwlr_screencopy_frame_v1::ZwlrScreencopyFrameV1);
+
+impl ScreencopyFrame {
+    pub fn frame(&self) -> &zwlr_screencopy_frame_v1::ZwlrScreencopyFrameV1 {
+        &self.0
+    }
+
+    /// The shm buffer the compositor can copy the frame into, or [`None`] until it was advertised.
+    pub fn shm_buffer(&self) -> Option<ShmBufferInfo> {
+        self.data().inner.lock().unwrap().shm_buffer
+    }
+
+    /// The dmabuf buffer the compositor can copy the frame into, if it advertised one.
+    ///
+    /// Compositors only advertise dmabuf buffers since version 3 of the protocol.
+    pub fn dmabuf_buffer(&self) -> Option<DmabufBufferInfo> {
+        self.data().inner.lock().unwrap().dmabuf_buffer
+    }
+
+    /// Whether the copied frame is vertically flipped.
+    pub fn y_invert(&self) -> bool {
+        self.data().inner.lock().unwrap().y_invert
+    }
+
+    /// Copy the frame into a buffer matching one of the advertised buffers.
+    pub fn copy(&self, buffer: &wl_buffer::WlBuffer) {
+        self.0.copy(buffer);
+    }
+
+    /// Copy the frame into a buffer once the content of the output changes, reporting the changed
+    /// regions to [`ScreencopyHandler::damage`].
+    ///
+    /// This copies immediately if the compositor only supports version 1 of the protocol.
+    pub fn copy_with_damage(&self, buffer: &wl_buffer::WlBuffer) {
+        if self.0.version() >= 2 {
+            self.0.copy_with_damage(buffer);
+        } else {
+            self.0.copy(buffer);
+        }
+    }
+
+    /// Allocate a buffer matching the advertised shm buffer from a pool, and copy the frame into it.
+    ///
+    /// Once the frame is ready, its content is available with [`ScreencopyFrame::canvas`].
+    pub fn capture_to_slotpool(&self, pool: &mut SlotPool) -> Result<(), ScreencopyError> {
+        let mut inner = self.data().inner.lock().unwrap();
+        if inner.buffer.is_some() {
+            return Err(ScreencopyError::AlreadyCopied);
+        }
+
+        let info = inner.shm_buffer.ok_or(ScreencopyError::NoShmBuffer)?;
+        let (buffer, _) = pool.create_buffer(
+            info.width as i32,
+            info.height as i32,
+            info.stride as i32,
+            info.format,
+        )?;
+        self.0.copy(buffer.wl_buffer());
+        inner.buffer = Some(buffer);
+        Ok(())
+    }
+
+    /// The content of the buffer allocated by [`ScreencopyFrame::capture_to_slotpool`].
+    ///
+    /// This is only meaningful after [`ScreencopyHandler::ready`] was called.
+    pub fn canvas<'pool>(&self, pool: &'pool mut SlotPool) -> Option<&'pool mut [u8]> {
+        self.data().inner.lock().unwrap().buffer.as_ref()?.canvas(pool)
+    }
+
+    /// Take the buffer allocated by [`ScreencopyFrame::capture_to_slotpool`], for example to attach it
+    /// to a surface.
+    pub fn take_buffer(&self) -> Option<Buffer> {
+        self.data().inner.lock().unwrap().buffer.take()
+    }
+
+    fn data(&self) -> &ScreencopyFrameData {
+        self.0.data::<ScreencopyFrameData>().unwrap()
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct ScreencopyFrameData {
+    inner: Mutex<ScreencopyFrameInner>,
+}
+
+#[derive(Debug, Default)]
+struct ScreencopyFrameInner {
+    shm_buffer: Option<ShmBufferInfo>,
+    dmabuf_buffer: Option<DmabufBufferInfo>,
+    y_invert: bool,
+    buffer: Option<Buffer>,
+}
+
+impl<D> Dispatch<zwlr_screencopy_manager_v1::ZwlrScreencopyManagerV1, GlobalData, D>
+    for ScreencopyState
+where
+    D: Dispatch<zwlr_screencopy_manager_v1::ZwlrScreencopyManagerV1, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &zwlr_screencopy_manager_v1::ZwlrScreencopyManagerV1,
+        _: zwlr_screencopy_manager_v1::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("zwlr_screencopy_manager_v1 has no events")
+    }
+}
+
+impl<D> Dispatch<zwlr_screencopy_frame_v1::ZwlrScreencopyFrameV1, ScreencopyFrameData, D>
+    for ScreencopyState
+where
+    D: Dispatch<zwlr_screencopy_frame_v1::ZwlrScreencopyFrameV1, ScreencopyFrameData>
+        + ScreencopyHandler,
+{
+    fn event(
+        state: &mut D,
+        proxy: &zwlr_screencopy_frame_v1::ZwlrScreencopyFrameV1,
+        event: zwlr_screencopy_frame_v1::Event,
+        data: &ScreencopyFrameData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let frame = ScreencopyFrame(proxy.clone());
+
+        match event {
+            zwlr_screencopy_frame_v1::Event::Buffer { format, width, height, stride } => {
+                let format = match format {
+                    WEnum::Value(format) => format,
+                    WEnum::Unknown(raw) => {
+                        log::warn!(target: "sctk", "Unknown screencopy shm format {:x}", raw);
+                        return;
+                    }
+                };
+
+                let info = ShmBufferInfo { format, width, height, stride };
+                data.inner.lock().unwrap().shm_buffer = Some(info);
+                state.buffer(conn, qh, &frame, info);
+
+                // Before version 3, the buffer event is the only description of the buffers.
+                if proxy.version() < 3 {
+                    state.buffer_done(conn, qh, &frame);
+                }
+            }
+
+            zwlr_screencopy_frame_v1::Event::LinuxDmabuf { format, width, height } => {
+                data.inner.lock().unwrap().dmabuf_buffer =
+                    Some(DmabufBufferInfo { format, width, height });
+            }
+
+            zwlr_screencopy_frame_v1::Event::BufferDone => {
+                state.buffer_done(conn, qh, &frame);
+            }
+
+            zwlr_screencopy_frame_v1::Event::Flags { flags } => {
+                let y_invert = match flags {
+                    WEnum::Value(flags) => flags.contains(zwlr_screencopy_frame_v1::Flags::YInvert),
+                    WEnum::Unknown(raw) => raw & 1 != 0,
+                };
+                data.inner.lock().unwrap().y_invert = y_invert;
+                state.flags(conn, qh, &frame, y_invert);
+            }
+
+            zwlr_screencopy_frame_v1::Event::Damage { x, y, width, height } => {
+                state.damage(conn, qh, &frame, (x, y, width, height));
+            }
+
+            zwlr_screencopy_frame_v1::Event::Ready { tv_sec_hi, tv_sec_lo, tv_nsec } => {
+                let secs = (u64::from(tv_sec_hi) << 32) | u64::from(tv_sec_lo);
+                state.ready(conn, qh, &frame, Duration::new(secs, tv_nsec));
+                proxy.destroy();
+            }
+
+            zwlr_screencopy_frame_v1::Event::Failed => {
+                state.failed(conn, qh, &frame);
+                proxy.destroy();
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_screencopy {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_wlr::screencopy::v1::client::zwlr_screencopy_manager_v1::ZwlrScreencopyManagerV1: $crate::globals::GlobalData
+        ] => $crate::screencopy::ScreencopyState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_wlr::screencopy::v1::client::zwlr_screencopy_frame_v1::ZwlrScreencopyFrameV1: $crate::screencopy::ScreencopyFrameData
+        ] => $crate::screencopy::ScreencopyState);
+    };
+}
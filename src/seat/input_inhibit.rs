@@ -0,0 +1,165 @@
+//! Inhibition of the input of other clients, for lock screens.
+//!
+//! This uses the wlroots specific `zwlr_input_inhibit_manager_v1` protocol. Lock screens should prefer
+//! [`SessionLockState`](crate::session_lock::SessionLockState), which is designed for this purpose and
+//! also hides the content of the outputs, and fall back to this protocol on compositors which do not
+//! support `ext_session_lock_manager_v1`.
+
+use std::sync::{Arc, Weak};
+
+use wayland_client::{globals::GlobalList, Connection, Dispatch, QueueHandle};
+use wayland_protocols_wlr::input_inhibitor::v1::client::{
+    zwlr_input_inhibit_manager_v1, zwlr_input_inhibitor_v1,
+};
+
+use crate::{
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+    registry::GlobalProxy,
+};
+
+/// An error that may occur when inhibiting input.
+#[derive(Debug, thiserror::Error)]
+pub enum InputInhibitError {
+    /// The `zwlr_input_inhibit_manager_v1` global is not bound.
+    #[error(transparent)]
+    Global(#[from] GlobalError),
+
+    /// An inhibitor created by this state is still alive.
+    ///
+    /// The compositor allows a single inhibitor at a time, and creating another one is a protocol error.
+    #[error("input is already inhibited")]
+    AlreadyInhibited,
+}
+
+#[derive(Debug)]
+pub struct InputInhibitState {
+    input_inhibit_manager: GlobalProxy<zwlr_input_inhibit_manager_v1::ZwlrInputInhibitManagerV1>,
+    inhibitor: Weak<InputInhibitorInner>,
+}
+
+impl InputInhibitState {
+    /// Bind `zwlr_input_inhibit_manager_v1` global, if it exists
+    pub fn bind<D>(globals: &GlobalList, qh: &QueueHandle<D>) -> Self
+    where
+        D: Dispatch<zwlr_input_inhibit_manager_v1::ZwlrInputInhibitManagerV1, GlobalData> + 'static,
+    {
+        let input_inhibit_manager = GlobalProxy::bind(globals, qh, 1..=1, GlobalData);
+        Self { input_inhibit_manager, inhibitor: Weak::new() }
+    }
+
+    /// Stop the compositor from sending input events to other clients.
+    ///
+    /// Input is inhibited until the returned [`InputInhibitor`] and all its clones are dropped.
+    ///
+    /// ## Errors
+    ///
+    /// This will return [`InputInhibitError::AlreadyInhibited`] if an inhibitor created by this state is
+    /// still alive. The compositor raises a protocol error if another client holds an inhibitor, which can
+    /// not be detected beforehand.
+    pub fn inhibit<D>(&mut self, qh: &QueueHandle<D>) -> Result<InputInhibitor, InputInhibitError>
+    where
+        D: Dispatch<zwlr_input_inhibitor_v1::ZwlrInputInhibitorV1, GlobalData> + 'static,
+    {
+        let manager = self.input_inhibit_manager.get()?;
+
+        if self.inhibitor.strong_count() > 0 {
+            return Err(InputInhibitError::AlreadyInhibited);
+        }
+
+        let inhibitor = manager.get_inhibitor(qh, GlobalData);
+        let inner = Arc::new(InputInhibitorInner { inhibitor });
+        self.inhibitor = Arc::downgrade(&inner);
+
+        Ok(InputInhibitor(inner))
+    }
+
+    /// Whether an inhibitor created by this state is alive.
+    pub fn is_inhibited(&self) -> bool {
+        self.inhibitor.strong_count() > 0
+    }
+}
+
+impl ProvidesBoundGlobal<zwlr_input_inhibit_manager_v1::ZwlrInputInhibitManagerV1, 1>
+    for InputInhibitState
+{
+    fn bound_global(
+        &self,
+    ) -> Result<zwlr_input_inhibit_manager_v1::ZwlrInputInhibitManagerV1, GlobalError> {
+        self.input_inhibit_manager.get().cloned()
+    }
+}
+
+#[derive(Debug)]
+struct InputInhibitorInner {
+    inhibitor: zwlr_input_inhibitor_v1::ZwlrInputInhibitorV1,
+}
+
+impl Drop for InputInhibitorInner {
+    fn drop(&mut self) {
+        self.inhibitor.destroy();
+    }
+}
+
+/// An inhibitor of the input of other clients.
+///
+/// The inhibitor is destroyed when the last clone is dropped, which gives the input back to the other
+/// clients.
+#[must_use]
+#[derive(Debug, Clone)]
+pub struct InputInhibitor(Arc<InputInhibitorInner>);
+
+impl InputInhibitor {
+    pub fn inhibitor(&self) -> &zwlr_input_inhibitor_v1::ZwlrInputInhibitorV1 {
+        &self.0.inhibitor
+    }
+}
+
+impl<D> Dispatch<zwlr_input_inhibit_manager_v1::ZwlrInputInhibitManagerV1, GlobalData, D>
+    for InputInhibitState
+where
+    D: Dispatch<zwlr_input_inhibit_manager_v1::ZwlrInputInhibitManagerV1, GlobalData>,
+{
+    fn event(
+        _data: &mut D,
+        _manager: &zwlr_input_inhibit_manager_v1::ZwlrInputInhibitManagerV1,
+        _event: zwlr_input_inhibit_manager_v1::Event,
+        _: &GlobalData,
+        _conn: &Connection,
+        _qh: &QueueHandle<D>,
+    ) {
+        unreachable!()
+    }
+}
+
+impl<D> Dispatch<zwlr_input_inhibitor_v1::ZwlrInputInhibitorV1, GlobalData, D> for InputInhibitState
+where
+    D: Dispatch<zwlr_input_inhibitor_v1::ZwlrInputInhibitorV1, GlobalData>,
+{
+    fn event(
+        _data: &mut D,
+        _inhibitor: &zwlr_input_inhibitor_v1::ZwlrInputInhibitorV1,
+        _event: zwlr_input_inhibitor_v1::Event,
+        _: &GlobalData,
+        _conn: &Connection,
+        _qh: &QueueHandle<D>,
+    ) {
+        unreachable!()
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_input_inhibit {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols_wlr::input_inhibitor::v1::client::zwlr_input_inhibit_manager_v1::ZwlrInputInhibitManagerV1: $crate::globals::GlobalData
+            ] => $crate::seat::input_inhibit::InputInhibitState
+        );
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols_wlr::input_inhibitor::v1::client::zwlr_input_inhibitor_v1::ZwlrInputInhibitorV1: $crate::globals::GlobalData
+            ] => $crate::seat::input_inhibit::InputInhibitState
+        );
+    };
+}
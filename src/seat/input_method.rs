@@ -0,0 +1,652 @@
+use std::sync::{Arc, Mutex};
+
+use wayland_client::{
+    globals::GlobalList,
+    protocol::{wl_seat, wl_surface},
+    Connection, Dispatch, Proxy, QueueHandle, WEnum,
+};
+use wayland_protocols::wp::text_input::zv3::client::zwp_text_input_v3::{
+    ChangeCause, ContentHint, ContentPurpose,
+};
+use wayland_protocols_misc::zwp_input_method_v2::client::{
+    zwp_input_method_manager_v2, zwp_input_method_v2, zwp_input_popup_surface_v2,
+};
+
+use crate::{
+    compositor::Surface,
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+    registry::GlobalProxy,
+    shell::WaylandSurface,
+};
+
+#[cfg(feature = "xkbcommon")]
+pub use grab::{KeyboardGrab, KeyboardGrabHandler};
+
+#[derive(Debug)]
+pub struct InputMethodState {
+    input_method_manager: GlobalProxy<zwp_input_method_manager_v2::ZwpInputMethodManagerV2>,
+}
+
+impl InputMethodState {
+    /// Bind `zwp_input_method_manager_v2` global, if it exists
+    pub fn bind<D>(globals: &GlobalList, qh: &QueueHandle<D>) -> Self
+    where
+        D: Dispatch<zwp_input_method_manager_v2::ZwpInputMethodManagerV2, GlobalData> + 'static,
+    {
+        let input_method_manager = GlobalProxy::bind(globals, qh, 1..=1, GlobalData);
+        Self { input_method_manager }
+    }
+
+    /// Create an input method for a seat.
+    ///
+    /// Only one input method may exist per seat. If another client already provides the input method for
+    /// the seat, [`InputMethodHandler::unavailable`] is called.
+    pub fn get_input_method<D>(
+        &self,
+        seat: &wl_seat::WlSeat,
+        qh: &QueueHandle<D>,
+    ) -> Result<InputMethod, GlobalError>
+    where
+        D: Dispatch<zwp_input_method_v2::ZwpInputMethodV2, InputMethodData> + 'static,
+    {
+        let udata = InputMethodData { seat: seat.clone(), inner: Mutex::new(Default::default()) };
+        let input_method = self.input_method_manager.get()?.get_input_method(seat, qh, udata);
+        Ok(InputMethod { input_method })
+    }
+}
+
+impl ProvidesBoundGlobal<zwp_input_method_manager_v2::ZwpInputMethodManagerV2, 1>
+    for InputMethodState
+{
+    fn bound_global(
+        &self,
+    ) -> Result<zwp_input_method_manager_v2::ZwpInputMethodManagerV2, GlobalError> {
+        self.input_method_manager.get().cloned()
+    }
+}
+
+/// An input method for a seat.
+///
+/// Requests sending text to the focused text input are double-buffered and only take effect once
+/// [`InputMethod::commit`] is called. The input method is destroyed on drop.
+#[derive(Debug)]
+pub struct InputMethod {
+    input_method: zwp_input_method_v2::ZwpInputMethodV2,
+}
+
+impl InputMethod {
+    pub fn input_method(&self) -> &zwp_input_method_v2::ZwpInputMethodV2 {
+        &self.input_method
+    }
+
+    pub fn seat(&self) -> &wl_seat::WlSeat {
+        &self.data().seat
+    }
+
+    /// Whether the input method is active, as of the latest `done` event.
+    pub fn is_active(&self) -> bool {
+        self.data().is_active()
+    }
+
+    /// Insert text at the cursor of the focused text input.
+    pub fn commit_string(&self, text: String) {
+        self.input_method.commit_string(text);
+    }
+
+    /// Set the pre-edit text shown at the cursor of the focused text input.
+    ///
+    /// `cursor_begin` and `cursor_end` are byte offsets into `text`. Both should be -1 to hide the cursor.
+    pub fn set_preedit_string(&self, text: String, cursor_begin: i32, cursor_end: i32) {
+        self.input_method.set_preedit_string(text, cursor_begin, cursor_end);
+    }
+
+    /// Delete text around the cursor of the focused text input, in bytes.
+    pub fn delete_surrounding_text(&self, before_length: u32, after_length: u32) {
+        self.input_method.delete_surrounding_text(before_length, after_length);
+    }
+
+    /// Apply the pending changes to the focused text input.
+    ///
+    /// The serial is the number of `done` events received so far, as required by the protocol.
+    pub fn commit(&self) {
+        let serial = self.data().inner.lock().unwrap().done_count;
+        self.input_method.commit(serial);
+    }
+
+    /// Create a popup surface shown next to the cursor of the focused text input.
+    ///
+    /// The popup is only visible while the input method is active.
+    pub fn get_input_popup_surface<D>(
+        &self,
+        surface: impl Into<Surface>,
+        qh: &QueueHandle<D>,
+    ) -> InputPopupSurface
+    where
+        D: Dispatch<zwp_input_popup_surface_v2::ZwpInputPopupSurfaceV2, InputPopupSurfaceData>
+            + 'static,
+    {
+        let surface = surface.into();
+        let udata = InputPopupSurfaceData { text_input_rectangle: Mutex::new((0, 0, 0, 0)) };
+        let popup = self.input_method.get_input_popup_surface(surface.wl_surface(), qh, udata);
+
+        InputPopupSurface(Arc::new(InputPopupSurfaceInner { popup, surface }))
+    }
+
+    fn data(&self) -> &InputMethodData {
+        self.input_method.data::<InputMethodData>().unwrap()
+    }
+}
+
+impl Drop for InputMethod {
+    fn drop(&mut self) {
+        self.input_method.destroy();
+    }
+}
+
+#[derive(Debug)]
+struct InputPopupSurfaceInner {
+    popup: zwp_input_popup_surface_v2::ZwpInputPopupSurfaceV2,
+    surface: Surface,
+}
+
+impl Drop for InputPopupSurfaceInner {
+    fn drop(&mut self) {
+        // The role object must be destroyed before the surface.
+        self.popup.destroy();
+    }
+}
+
+/// A surface with the input popup role.
+///
+/// The popup is destroyed when the last clone is dropped.
+#[derive(Debug, Clone)]
+pub struct InputPopupSurface(Arc<InputPopupSurfaceInner>);
+
+impl PartialEq for InputPopupSurface {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl InputPopupSurface {
+    pub fn popup(&self) -> &zwp_input_popup_surface_v2::ZwpInputPopupSurfaceV2 {
+        &self.0.popup
+    }
+
+    /// The area of the text input's cursor, relative to the popup surface.
+    ///
+    /// This is `(x, y, width, height)`.
+    pub fn text_input_rectangle(&self) -> (i32, i32, i32, i32) {
+        *self.0.popup.data::<InputPopupSurfaceData>().unwrap().text_input_rectangle.lock().unwrap()
+    }
+}
+
+impl WaylandSurface for InputPopupSurface {
+    fn wl_surface(&self) -> &wl_surface::WlSurface {
+        self.0.surface.wl_surface()
+    }
+}
+
+pub trait InputMethodHandler: Sized {
+    /// A text input was focused and enabled, so the input method should start sending text.
+    ///
+    /// This is called when the `done` event applying the activation is received, before the other
+    /// callbacks for the same `done` event.
+    fn activate(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        input_method: &zwp_input_method_v2::ZwpInputMethodV2,
+    );
+
+    /// The text input was disabled or lost focus.
+    fn deactivate(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        input_method: &zwp_input_method_v2::ZwpInputMethodV2,
+    );
+
+    /// The text around the cursor of the text input changed.
+    ///
+    /// `cursor` and `anchor` are byte offsets into `text`, and `cause` tells whether the change was made by
+    /// the input method.
+    #[allow(clippy::too_many_arguments)]
+    fn surrounding_text(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        input_method: &zwp_input_method_v2::ZwpInputMethodV2,
+        text: String,
+        cursor: u32,
+        anchor: u32,
+        cause: ChangeCause,
+    );
+
+    /// The type of content edited by the text input changed.
+    fn content_type(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        input_method: &zwp_input_method_v2::ZwpInputMethodV2,
+        hint: ContentHint,
+        purpose: ContentPurpose,
+    );
+
+    /// All the state sent by the compositor since the previous `done` event has been applied.
+    fn done(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        input_method: &zwp_input_method_v2::ZwpInputMethodV2,
+    );
+
+    /// The input method can not be used, for example because another input method exists for the seat.
+    ///
+    /// The input method should be dropped.
+    fn unavailable(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        input_method: &zwp_input_method_v2::ZwpInputMethodV2,
+    );
+
+    /// The position of the text input's cursor relative to an input popup surface changed.
+    ///
+    /// This does nothing by default.
+    #[allow(clippy::too_many_arguments)]
+    fn text_input_rectangle(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _popup: &zwp_input_popup_surface_v2::ZwpInputPopupSurfaceV2,
+        _x: i32,
+        _y: i32,
+        _width: i32,
+        _height: i32,
+    ) {
+    }
+}
+
+#[derive(Debug)]
+pub struct InputMethodData {
+    seat: wl_seat::WlSeat,
+    inner: Mutex<InputMethodDataInner>,
+}
+
+impl InputMethodData {
+    pub fn seat(&self) -> &wl_seat::WlSeat {
+        &self.seat
+    }
+
+    /// Whether the input method is active, as of the latest `done` event.
+    pub fn is_active(&self) -> bool {
+        self.inner.lock().unwrap().active
+    }
+}
+
+#[derive(Debug, Default)]
+struct InputMethodDataInner {
+    active: bool,
+    done_count: u32,
+    pending: PendingState,
+}
+
+#[derive(Debug, Default)]
+struct PendingState {
+    active: Option<bool>,
+    surrounding_text: Option<(String, u32, u32)>,
+    text_change_cause: Option<ChangeCause>,
+    content_type: Option<(ContentHint, ContentPurpose)>,
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct InputPopupSurfaceData {
+    text_input_rectangle: Mutex<(i32, i32, i32, i32)>,
+}
+
+impl<D> Dispatch<zwp_input_method_manager_v2::ZwpInputMethodManagerV2, GlobalData, D>
+    for InputMethodState
+where
+    D: Dispatch<zwp_input_method_manager_v2::ZwpInputMethodManagerV2, GlobalData>,
+{
+    fn event(
+        _data: &mut D,
+        _manager: &zwp_input_method_manager_v2::ZwpInputMethodManagerV2,
+        _event: zwp_input_method_manager_v2::Event,
+        _: &GlobalData,
+        _conn: &Connection,
+        _qh: &QueueHandle<D>,
+    ) {
+        unreachable!()
+    }
+}
+
+impl<D> Dispatch<zwp_input_method_v2::ZwpInputMethodV2, InputMethodData, D> for InputMethodState
+where
+    D: Dispatch<zwp_input_method_v2::ZwpInputMethodV2, InputMethodData> + InputMethodHandler,
+{
+    fn event(
+        data: &mut D,
+        input_method: &zwp_input_method_v2::ZwpInputMethodV2,
+        event: zwp_input_method_v2::Event,
+        udata: &InputMethodData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let mut inner = udata.inner.lock().unwrap();
+
+        match event {
+            zwp_input_method_v2::Event::Activate => {
+                // Activation resets the state of the text input.
+                inner.pending = PendingState { active: Some(true), ..Default::default() };
+            }
+            zwp_input_method_v2::Event::Deactivate => {
+                inner.pending.active = Some(false);
+            }
+            zwp_input_method_v2::Event::SurroundingText { text, cursor, anchor } => {
+                inner.pending.surrounding_text = Some((text, cursor, anchor));
+            }
+            zwp_input_method_v2::Event::TextChangeCause { cause } => {
+                inner.pending.text_change_cause = match cause {
+                    WEnum::Value(cause) => Some(cause),
+                    WEnum::Unknown(value) => {
+                        log::warn!(target: "sctk", "{}: unknown text change cause 0x{:x}", input_method.id(), value);
+                        None
+                    }
+                };
+            }
+            zwp_input_method_v2::Event::ContentType { hint, purpose } => {
+                let hint = match hint {
+                    WEnum::Value(hint) => hint,
+                    WEnum::Unknown(value) => ContentHint::from_bits_truncate(value),
+                };
+                let purpose = match purpose {
+                    WEnum::Value(purpose) => purpose,
+                    WEnum::Unknown(_) => ContentPurpose::Normal,
+                };
+                inner.pending.content_type = Some((hint, purpose));
+            }
+            zwp_input_method_v2::Event::Done => {
+                let pending = std::mem::take(&mut inner.pending);
+                inner.done_count = inner.done_count.wrapping_add(1);
+                if let Some(active) = pending.active {
+                    inner.active = active;
+                }
+                drop(inner);
+
+                match pending.active {
+                    Some(true) => data.activate(conn, qh, input_method),
+                    Some(false) => data.deactivate(conn, qh, input_method),
+                    None => (),
+                }
+
+                if let Some((text, cursor, anchor)) = pending.surrounding_text {
+                    let cause = pending.text_change_cause.unwrap_or(ChangeCause::InputMethod);
+                    data.surrounding_text(conn, qh, input_method, text, cursor, anchor, cause);
+                }
+
+                if let Some((hint, purpose)) = pending.content_type {
+                    data.content_type(conn, qh, input_method, hint, purpose);
+                }
+
+                data.done(conn, qh, input_method);
+            }
+            zwp_input_method_v2::Event::Unavailable => {
+                drop(inner);
+                data.unavailable(conn, qh, input_method);
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<zwp_input_popup_surface_v2::ZwpInputPopupSurfaceV2, InputPopupSurfaceData, D>
+    for InputMethodState
+where
+    D: Dispatch<zwp_input_popup_surface_v2::ZwpInputPopupSurfaceV2, InputPopupSurfaceData>
+        + InputMethodHandler,
+{
+    fn event(
+        data: &mut D,
+        popup: &zwp_input_popup_surface_v2::ZwpInputPopupSurfaceV2,
+        event: zwp_input_popup_surface_v2::Event,
+        udata: &InputPopupSurfaceData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        match event {
+            zwp_input_popup_surface_v2::Event::TextInputRectangle { x, y, width, height } => {
+                *udata.text_input_rectangle.lock().unwrap() = (x, y, width, height);
+                data.text_input_rectangle(conn, qh, popup, x, y, width, height);
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[cfg(feature = "xkbcommon")]
+mod grab {
+    use std::num::NonZeroU32;
+
+    use wayland_client::{protocol::wl_keyboard, Connection, Dispatch, Proxy, QueueHandle, WEnum};
+    use wayland_protocols_misc::zwp_input_method_v2::client::zwp_input_method_keyboard_grab_v2;
+
+    use super::{InputMethod, InputMethodState};
+    use crate::seat::keyboard::{KeyEvent, KeyboardData, Keymap, Modifiers, RepeatInfo};
+
+    impl InputMethod {
+        /// Grab the keyboard of the seat, so key events are sent to the input method instead of the focused
+        /// client.
+        ///
+        /// Keys are interpreted using the keymap sent by the compositor in the same way as
+        /// [`KeyboardHandler`](crate::seat::keyboard::KeyboardHandler) does. The grab is released on drop.
+        pub fn grab_keyboard<D>(&self, qh: &QueueHandle<D>) -> KeyboardGrab
+        where
+            D: Dispatch<
+                    zwp_input_method_keyboard_grab_v2::ZwpInputMethodKeyboardGrabV2,
+                    KeyboardData<()>,
+                > + 'static,
+        {
+            let udata = KeyboardData::new(self.seat().clone());
+            KeyboardGrab { grab: self.input_method.grab_keyboard(qh, udata) }
+        }
+    }
+
+    /// A keyboard grab created by an input method.
+    #[derive(Debug)]
+    pub struct KeyboardGrab {
+        grab: zwp_input_method_keyboard_grab_v2::ZwpInputMethodKeyboardGrabV2,
+    }
+
+    impl KeyboardGrab {
+        pub fn grab(&self) -> &zwp_input_method_keyboard_grab_v2::ZwpInputMethodKeyboardGrabV2 {
+            &self.grab
+        }
+    }
+
+    impl Drop for KeyboardGrab {
+        fn drop(&mut self) {
+            self.grab.release();
+        }
+    }
+
+    /// Handler trait for keyboard input grabbed by an input method.
+    ///
+    /// This mirrors [`KeyboardHandler`](crate::seat::keyboard::KeyboardHandler), except that a grab has no
+    /// focus.
+    pub trait KeyboardGrabHandler: Sized {
+        /// A key has been pressed on the keyboard.
+        fn press_key(
+            &mut self,
+            conn: &Connection,
+            qh: &QueueHandle<Self>,
+            grab: &zwp_input_method_keyboard_grab_v2::ZwpInputMethodKeyboardGrabV2,
+            serial: u32,
+            event: KeyEvent,
+        );
+
+        /// A key has been released.
+        fn release_key(
+            &mut self,
+            conn: &Connection,
+            qh: &QueueHandle<Self>,
+            grab: &zwp_input_method_keyboard_grab_v2::ZwpInputMethodKeyboardGrabV2,
+            serial: u32,
+            event: KeyEvent,
+        );
+
+        /// Keyboard modifiers have been updated.
+        fn update_modifiers(
+            &mut self,
+            conn: &Connection,
+            qh: &QueueHandle<Self>,
+            grab: &zwp_input_method_keyboard_grab_v2::ZwpInputMethodKeyboardGrabV2,
+            serial: u32,
+            modifiers: Modifiers,
+            layout: u32,
+        );
+
+        /// The keyboard has updated the rate and delay between repeating key inputs.
+        ///
+        /// This function does nothing by default.
+        fn update_repeat_info(
+            &mut self,
+            _conn: &Connection,
+            _qh: &QueueHandle<Self>,
+            _grab: &zwp_input_method_keyboard_grab_v2::ZwpInputMethodKeyboardGrabV2,
+            _info: RepeatInfo,
+        ) {
+        }
+
+        /// Keyboard keymap has been updated.
+        ///
+        /// This function does nothing by default.
+        fn update_keymap(
+            &mut self,
+            _conn: &Connection,
+            _qh: &QueueHandle<Self>,
+            _grab: &zwp_input_method_keyboard_grab_v2::ZwpInputMethodKeyboardGrabV2,
+            _keymap: Keymap<'_>,
+        ) {
+        }
+    }
+
+    impl<D>
+        Dispatch<
+            zwp_input_method_keyboard_grab_v2::ZwpInputMethodKeyboardGrabV2,
+            KeyboardData<()>,
+            D,
+        > for InputMethodState
+    where
+        D: Dispatch<
+                zwp_input_method_keyboard_grab_v2::ZwpInputMethodKeyboardGrabV2,
+                KeyboardData<()>,
+            > + KeyboardGrabHandler,
+    {
+        fn event(
+            data: &mut D,
+            grab: &zwp_input_method_keyboard_grab_v2::ZwpInputMethodKeyboardGrabV2,
+            event: zwp_input_method_keyboard_grab_v2::Event,
+            udata: &KeyboardData<()>,
+            conn: &Connection,
+            qh: &QueueHandle<D>,
+        ) {
+            match event {
+                zwp_input_method_keyboard_grab_v2::Event::Keymap { format, fd, size } => {
+                    match format {
+                        WEnum::Value(wl_keyboard::KeymapFormat::XkbV1) => {
+                            if let Some(keymap) = udata.load_keymap(fd, size) {
+                                data.update_keymap(conn, qh, grab, Keymap(&keymap));
+                            }
+                        }
+                        _ => log::warn!(target: "sctk", "non-xkb compatible keymap"),
+                    }
+                }
+
+                zwp_input_method_keyboard_grab_v2::Event::Key { serial, time, key, state } => {
+                    match state {
+                        WEnum::Value(wl_keyboard::KeyState::Pressed) => {
+                            if let Some(event) = udata.key_event(serial, time, key, true) {
+                                data.press_key(conn, qh, grab, serial, event);
+                            }
+                        }
+                        WEnum::Value(wl_keyboard::KeyState::Released) => {
+                            if let Some(event) = udata.key_event(serial, time, key, false) {
+                                data.release_key(conn, qh, grab, serial, event);
+                            }
+                        }
+                        _ => {
+                            log::warn!(target: "sctk", "{}: compositor sends invalid key state: {:?}", grab.id(), state);
+                        }
+                    }
+                }
+
+                zwp_input_method_keyboard_grab_v2::Event::Modifiers {
+                    serial,
+                    mods_depressed,
+                    mods_latched,
+                    mods_locked,
+                    group,
+                } => {
+                    if let Some(modifiers) =
+                        udata.update_mask(mods_depressed, mods_latched, mods_locked, group)
+                    {
+                        data.update_modifiers(conn, qh, grab, serial, modifiers, group);
+                    }
+                }
+
+                zwp_input_method_keyboard_grab_v2::Event::RepeatInfo { rate, delay } => {
+                    let info = if rate != 0 {
+                        RepeatInfo::Repeat {
+                            rate: NonZeroU32::new(rate as u32).unwrap(),
+                            delay: delay as u32,
+                        }
+                    } else {
+                        RepeatInfo::Disable
+                    };
+                    udata.store_repeat_info(info);
+                    data.update_repeat_info(conn, qh, grab, info);
+                }
+
+                _ => unreachable!(),
+            }
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_input_method {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols_misc::zwp_input_method_v2::client::zwp_input_method_manager_v2::ZwpInputMethodManagerV2: $crate::globals::GlobalData
+            ] => $crate::seat::input_method::InputMethodState
+        );
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols_misc::zwp_input_method_v2::client::zwp_input_method_v2::ZwpInputMethodV2: $crate::seat::input_method::InputMethodData
+            ] => $crate::seat::input_method::InputMethodState
+        );
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols_misc::zwp_input_method_v2::client::zwp_input_popup_surface_v2::ZwpInputPopupSurfaceV2: $crate::seat::input_method::InputPopupSurfaceData
+            ] => $crate::seat::input_method::InputMethodState
+        );
+    };
+}
+
+/// Delegates keyboard grabs created by [`InputMethod::grab_keyboard`] to [`InputMethodState`].
+#[cfg(feature = "xkbcommon")]
+#[macro_export]
+macro_rules! delegate_input_method_keyboard_grab {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols_misc::zwp_input_method_v2::client::zwp_input_method_keyboard_grab_v2::ZwpInputMethodKeyboardGrabV2: $crate::seat::keyboard::KeyboardData<()>
+            ] => $crate::seat::input_method::InputMethodState
+        );
+    };
+}
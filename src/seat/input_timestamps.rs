@@ -0,0 +1,193 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use wayland_client::{
+    globals::GlobalList,
+    protocol::{wl_pointer, wl_touch},
+    Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols::wp::input_timestamps::zv1::client::{
+    zwp_input_timestamps_manager_v1, zwp_input_timestamps_v1,
+};
+
+use crate::{
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+    registry::GlobalProxy,
+};
+
+#[cfg(feature = "xkbcommon")]
+use super::keyboard::KeyboardDataExt;
+use super::{pointer::PointerDataExt, touch::TouchDataExt};
+
+#[derive(Debug)]
+pub struct InputTimestampsState {
+    input_timestamps_manager:
+        GlobalProxy<zwp_input_timestamps_manager_v1::ZwpInputTimestampsManagerV1>,
+}
+
+impl InputTimestampsState {
+    /// Bind `zwp_input_timestamps_manager_v1` global, if it exists
+    pub fn bind<D>(globals: &GlobalList, qh: &QueueHandle<D>) -> Self
+    where
+        D: Dispatch<zwp_input_timestamps_manager_v1::ZwpInputTimestampsManagerV1, GlobalData>
+            + 'static,
+    {
+        let input_timestamps_manager = GlobalProxy::bind(globals, qh, 1..=1, GlobalData);
+        Self { input_timestamps_manager }
+    }
+
+    /// Subscribe to high-resolution timestamps for the key events of a keyboard.
+    ///
+    /// The timestamps are reported in [`KeyEvent::timestamp`](super::keyboard::KeyEvent::timestamp)
+    /// until the returned object is destroyed.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the user data of the keyboard is not `U`.
+    #[cfg(feature = "xkbcommon")]
+    pub fn get_keyboard_timestamps<D, U>(
+        &self,
+        keyboard: &wayland_client::protocol::wl_keyboard::WlKeyboard,
+        qh: &QueueHandle<D>,
+    ) -> Result<zwp_input_timestamps_v1::ZwpInputTimestampsV1, GlobalError>
+    where
+        D: Dispatch<zwp_input_timestamps_v1::ZwpInputTimestampsV1, InputTimestampsData> + 'static,
+        U: KeyboardDataExt + 'static,
+    {
+        let udata = keyboard.data::<U>().expect("unexpected keyboard user data");
+        let udata = InputTimestampsData { timestamp: udata.keyboard_data().timestamp.clone() };
+        Ok(self.input_timestamps_manager.get()?.get_keyboard_timestamps(keyboard, qh, udata))
+    }
+
+    /// Subscribe to high-resolution timestamps for the events of a pointer.
+    ///
+    /// The timestamps are reported in [`PointerEvent::timestamp`](super::pointer::PointerEvent::timestamp)
+    /// until the returned object is destroyed.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the user data of the pointer is not `U`.
+    pub fn get_pointer_timestamps<D, U>(
+        &self,
+        pointer: &wl_pointer::WlPointer,
+        qh: &QueueHandle<D>,
+    ) -> Result<zwp_input_timestamps_v1::ZwpInputTimestampsV1, GlobalError>
+    where
+        D: Dispatch<zwp_input_timestamps_v1::ZwpInputTimestampsV1, InputTimestampsData> + 'static,
+        U: PointerDataExt + 'static,
+    {
+        let udata = pointer.data::<U>().expect("unexpected pointer user data");
+        let udata = InputTimestampsData { timestamp: udata.pointer_data().timestamp.clone() };
+        Ok(self.input_timestamps_manager.get()?.get_pointer_timestamps(pointer, qh, udata))
+    }
+
+    /// Subscribe to high-resolution timestamps for the events of a touch device.
+    ///
+    /// The timestamps are reported in [`TouchPoint::timestamp`](super::touch::TouchPoint::timestamp)
+    /// until the returned object is destroyed.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the user data of the touch device is not `U`.
+    pub fn get_touch_timestamps<D, U>(
+        &self,
+        touch: &wl_touch::WlTouch,
+        qh: &QueueHandle<D>,
+    ) -> Result<zwp_input_timestamps_v1::ZwpInputTimestampsV1, GlobalError>
+    where
+        D: Dispatch<zwp_input_timestamps_v1::ZwpInputTimestampsV1, InputTimestampsData> + 'static,
+        U: TouchDataExt + 'static,
+    {
+        let udata = touch.data::<U>().expect("unexpected touch user data");
+        let udata = InputTimestampsData { timestamp: udata.touch_data().timestamp.clone() };
+        Ok(self.input_timestamps_manager.get()?.get_touch_timestamps(touch, qh, udata))
+    }
+}
+
+impl ProvidesBoundGlobal<zwp_input_timestamps_manager_v1::ZwpInputTimestampsManagerV1, 1>
+    for InputTimestampsState
+{
+    fn bound_global(
+        &self,
+    ) -> Result<zwp_input_timestamps_manager_v1::ZwpInputTimestampsManagerV1, GlobalError> {
+        self.input_timestamps_manager.get().cloned()
+    }
+}
+
+/// The latest high-resolution timestamp of an input device, not yet attached to an event.
+///
+/// The compositor sends the timestamp right before the event it describes, so it is shared between the
+/// timestamps object and the user data of the input device, which takes it when the event arrives.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct PendingTimestamp(Arc<Mutex<Option<Duration>>>);
+
+impl PendingTimestamp {
+    pub(crate) fn take(&self) -> Option<Duration> {
+        self.0.lock().unwrap().take()
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct InputTimestampsData {
+    timestamp: PendingTimestamp,
+}
+
+impl<D> Dispatch<zwp_input_timestamps_manager_v1::ZwpInputTimestampsManagerV1, GlobalData, D>
+    for InputTimestampsState
+where
+    D: Dispatch<zwp_input_timestamps_manager_v1::ZwpInputTimestampsManagerV1, GlobalData>,
+{
+    fn event(
+        _data: &mut D,
+        _manager: &zwp_input_timestamps_manager_v1::ZwpInputTimestampsManagerV1,
+        _event: zwp_input_timestamps_manager_v1::Event,
+        _: &GlobalData,
+        _conn: &Connection,
+        _qh: &QueueHandle<D>,
+    ) {
+        unreachable!()
+    }
+}
+
+impl<D> Dispatch<zwp_input_timestamps_v1::ZwpInputTimestampsV1, InputTimestampsData, D>
+    for InputTimestampsState
+where
+    D: Dispatch<zwp_input_timestamps_v1::ZwpInputTimestampsV1, InputTimestampsData>,
+{
+    fn event(
+        _data: &mut D,
+        _timestamps: &zwp_input_timestamps_v1::ZwpInputTimestampsV1,
+        event: zwp_input_timestamps_v1::Event,
+        udata: &InputTimestampsData,
+        _conn: &Connection,
+        _qh: &QueueHandle<D>,
+    ) {
+        match event {
+            zwp_input_timestamps_v1::Event::Timestamp { tv_sec_hi, tv_sec_lo, tv_nsec } => {
+                let secs = (tv_sec_hi as u64) << 32 | tv_sec_lo as u64;
+                *udata.timestamp.0.lock().unwrap() = Some(Duration::new(secs, tv_nsec));
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_input_timestamps {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols::wp::input_timestamps::zv1::client::zwp_input_timestamps_manager_v1::ZwpInputTimestampsManagerV1: $crate::globals::GlobalData
+            ] => $crate::seat::input_timestamps::InputTimestampsState
+        );
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols::wp::input_timestamps::zv1::client::zwp_input_timestamps_v1::ZwpInputTimestampsV1: $crate::seat::input_timestamps::InputTimestampsData
+            ] => $crate::seat::input_timestamps::InputTimestampsState
+        );
+    };
+}
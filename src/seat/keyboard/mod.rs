@@ -1,9 +1,13 @@
 use std::{
+    collections::hash_map::DefaultHasher,
     convert::TryInto,
     env,
     fmt::Debug,
+    hash::{Hash, Hasher},
     marker::PhantomData,
+    mem,
     num::NonZeroU32,
+    os::unix::io::OwnedFd,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc, Mutex,
@@ -14,8 +18,8 @@ use std::{
 #[doc(inline)]
 pub use xkeysym::{KeyCode, Keysym};
 
-#[cfg(feature = "calloop")]
-use calloop::timer::{TimeoutAction, Timer};
+use memmap2::MmapOptions;
+
 use wayland_client::{
     protocol::{wl_keyboard, wl_seat, wl_surface},
     Connection, Dispatch, Proxy, QueueHandle, WEnum,
@@ -26,7 +30,7 @@ use xkbcommon::xkb;
 #[cfg(feature = "calloop")]
 use repeat::{RepeatData, RepeatedKey};
 
-use super::{Capability, SeatError, SeatHandler, SeatState};
+use super::{input_timestamps::PendingTimestamp, Capability, SeatError, SeatHandler, SeatState};
 
 #[cfg(feature = "calloop")]
 pub mod repeat;
@@ -95,10 +99,9 @@ impl SeatState {
         D: Dispatch<wl_keyboard::WlKeyboard, U> + SeatHandler + KeyboardHandler + 'static,
         U: KeyboardDataExt + 'static,
     {
-        let inner =
-            self.seats.iter().find(|inner| &inner.seat == seat).ok_or(SeatError::DeadObject)?;
+        let data = self.seat_data(seat).ok_or(SeatError::DeadObject)?;
 
-        if !inner.data.has_keyboard.load(Ordering::SeqCst) {
+        if !data.has_keyboard.load(Ordering::SeqCst) {
             return Err(SeatError::UnsupportedCapability(Capability::Keyboard).into());
         }
 
@@ -108,13 +111,78 @@ impl SeatState {
 
 /// Wrapper around a libxkbcommon keymap
 #[allow(missing_debug_implementations)]
-pub struct Keymap<'a>(&'a xkb::Keymap);
+pub struct Keymap<'a>(pub(crate) &'a xkb::Keymap);
 
 impl<'a> Keymap<'a> {
     /// Get keymap as string in text format. The keymap should always be valid.
     pub fn as_string(&self) -> String {
         self.0.get_as_string(xkb::KEYMAP_FORMAT_TEXT_V1)
     }
+
+    /// The raw code of the first key producing the keysym, in any layout and level.
+    pub fn keycode_for_keysym(&self, keysym: Keysym) -> Option<u32> {
+        keycode_for_keysym(self.0, keysym)
+    }
+
+    /// The keysyms produced by the key with the given raw code, in all layouts and levels.
+    pub fn keysyms_for_keycode(&self, raw_code: u32) -> Vec<Keysym> {
+        keysyms_for_keycode(self.0, raw_code)
+    }
+}
+
+fn keycode_for_keysym(keymap: &xkb::Keymap, keysym: Keysym) -> Option<u32> {
+    (keymap.min_keycode().raw()..=keymap.max_keycode().raw())
+        .find(|&code| key_keysyms(keymap, KeyCode::new(code)).any(|sym| sym == keysym))
+        // Keycodes of the xkb keymap are offset by 8 from raw key codes.
+        .and_then(|code| code.checked_sub(8))
+}
+
+fn keysyms_for_keycode(keymap: &xkb::Keymap, raw_code: u32) -> Vec<Keysym> {
+    let mut keysyms = Vec::new();
+    for keysym in key_keysyms(keymap, KeyCode::new(raw_code.saturating_add(8))) {
+        if !keysyms.contains(&keysym) {
+            keysyms.push(keysym);
+        }
+    }
+    keysyms
+}
+
+/// All the keysyms of a key, by layout and level.
+fn key_keysyms(keymap: &xkb::Keymap, keycode: KeyCode) -> impl Iterator<Item = Keysym> + '_ {
+    (0..keymap.num_layouts_for_key(keycode)).flat_map(move |layout| {
+        (0..keymap.num_levels_for_key(keycode, layout)).flat_map(move |level| {
+            keymap.key_get_syms_by_level(keycode, layout, level).iter().copied()
+        })
+    })
+}
+
+/// The keymap as sent by the compositor, before any processing by libxkbcommon.
+#[derive(Debug)]
+pub struct RawKeymap<'a> {
+    format: WEnum<wl_keyboard::KeymapFormat>,
+    data: &'a [u8],
+}
+
+impl<'a> RawKeymap<'a> {
+    /// The format of the keymap.
+    pub fn format(&self) -> WEnum<wl_keyboard::KeymapFormat> {
+        self.format
+    }
+
+    /// The keymap data, borrowed from the memory shared by the compositor.
+    ///
+    /// This is empty if the compositor did not send any keymap data.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// The keymap data as a string, without the trailing nul byte.
+    ///
+    /// Returns [`None`] if the data is not valid UTF-8.
+    pub fn as_str(&self) -> Option<&'a str> {
+        let data = self.data.strip_suffix(b"\0").unwrap_or(self.data);
+        std::str::from_utf8(data).ok()
+    }
 }
 
 /// Handler trait for keyboard input.
@@ -125,8 +193,12 @@ pub trait KeyboardHandler: Sized {
     ///
     /// When called, you may assume the specified surface has keyboard focus.
     ///
-    /// When a keyboard enters a surface, the `raw` and `keysym` fields indicate which keys are currently
-    /// pressed.
+    /// When a keyboard enters a surface, the `raw` and `keysyms` fields indicate which keys are currently
+    /// pressed. `raw` holds the raw key codes, as in [`KeyEvent::raw_code`], and `keysyms` holds the symbol
+    /// of each of these keys, in the same order, resolved with the current keymap and modifiers. If no
+    /// keymap was loaded yet, the symbols are [`Keysym::NoSymbol`].
+    ///
+    /// No text is produced for the keys which are already held, and they are not repeated.
     #[allow(clippy::too_many_arguments)]
     fn enter(
         &mut self,
@@ -165,6 +237,24 @@ pub trait KeyboardHandler: Sized {
         event: KeyEvent,
     );
 
+    /// A key press has been repeated.
+    ///
+    /// This is called by the key repeat of keyboards created with
+    /// [`SeatState::get_keyboard_with_repeat_handler`]. The serial of the event is the one of the
+    /// original key press.
+    ///
+    /// By default this calls [`KeyboardHandler::press_key`].
+    #[cfg(feature = "calloop")]
+    fn repeat_key(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        keyboard: &wl_keyboard::WlKeyboard,
+        event: KeyEvent,
+    ) {
+        self.press_key(conn, qh, keyboard, event.serial, event);
+    }
+
     /// A key has been released.
     ///
     /// This stops the key from being repeated if the key is the last key which was pressed.
@@ -180,7 +270,7 @@ pub trait KeyboardHandler: Sized {
     /// Keyboard modifiers have been updated.
     ///
     /// This happens when one of the modifier keys, such as "Shift", "Control" or "Alt" is pressed or
-    /// released.
+    /// released, or when a lock such as "Caps Lock" is toggled.
     fn update_modifiers(
         &mut self,
         conn: &Connection,
@@ -191,10 +281,25 @@ pub trait KeyboardHandler: Sized {
         layout: u32,
     );
 
+    /// The effective layout or the state of the keyboard LEDs changed.
+    ///
+    /// This is called after [`update_modifiers`](KeyboardHandler::update_modifiers) when the compositor
+    /// switches the layout or toggles a lock, and after a new keymap is loaded.
+    ///
+    /// This function does nothing by default.
+    fn update_layout(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _keyboard: &wl_keyboard::WlKeyboard,
+        _layout: Layout,
+    ) {
+    }
+
     /// The keyboard has updated the rate and delay between repeating key inputs.
     ///
-    /// This function does nothing by default but is provided if a repeat mechanism outside of calloop is\
-    /// used.
+    /// This function does nothing by default but is provided if a repeat mechanism outside of calloop is
+    /// used. The latest value is also available through [`KeyboardData::repeat_info`].
     fn update_repeat_info(
         &mut self,
         _conn: &Connection,
@@ -204,13 +309,32 @@ pub trait KeyboardHandler: Sized {
     ) {
     }
 
+    /// The compositor has sent a keymap.
+    ///
+    /// This is called for every keymap event, including ones using [`KeymapFormat::NoKeymap`], before the
+    /// keymap is loaded into libxkbcommon. Use this if you need the keymap exactly as provided by the
+    /// compositor, for example to forward it to another process.
+    ///
+    /// This function does nothing by default.
+    ///
+    /// [`KeymapFormat::NoKeymap`]: wl_keyboard::KeymapFormat::NoKeymap
+    fn raw_keymap(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _keyboard: &wl_keyboard::WlKeyboard,
+        _keymap: RawKeymap<'_>,
+    ) {
+    }
+
     /// Keyboard keymap has been updated.
     ///
     /// `keymap.as_string()` can be used get the keymap as a string. It cannot be exposed directly
     /// as an `xkbcommon::xkb::Keymap` due to the fact xkbcommon uses non-thread-safe reference
     /// counting. But can be used to create an independent `Keymap`.
     ///
-    /// This is called after the default handler for keymap changes and does nothing by default.
+    /// This is called after the default handler for keymap changes and does nothing by default. It is
+    /// not called when the compositor sends the keymap currently in use again.
     fn update_keymap(
         &mut self,
         _conn: &Connection,
@@ -222,7 +346,7 @@ pub trait KeyboardHandler: Sized {
 }
 
 /// The rate at which a pressed key is repeated.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RepeatInfo {
     /// Keys will be repeated at the specified rate and delay.
     Repeat {
@@ -243,20 +367,55 @@ pub struct KeyEvent {
     /// Time at which the keypress occurred.
     pub time: u32,
 
+    /// Serial of the key event.
+    ///
+    /// Repeated key events reuse the serial of the original key press, so it may be used for requests
+    /// such as setting the selection.
+    pub serial: u32,
+
     /// The raw value of the key.
+    ///
+    /// This is the evdev key code, as sent by the compositor.
     pub raw_code: u32,
 
+    /// The xkb key code of the key, which is offset by 8 from the evdev key code.
+    ///
+    /// This is the key code to use with the keymap.
+    pub keycode: KeyCode,
+
     /// The interpreted symbol of the key.
     ///
     /// This corresponds to one of the assoiated values on the [`Keysym`] type.
     pub keysym: Keysym,
 
+    /// High-resolution timestamp of the key event.
+    ///
+    /// This is only set for keyboards subscribed with
+    /// [`InputTimestampsState::get_keyboard_timestamps`](crate::seat::input_timestamps::InputTimestampsState::get_keyboard_timestamps).
+    /// Repeated key events advance the timestamp of the original key press.
+    pub timestamp: Option<Duration>,
+
     /// UTF-8 interpretation of the entered text.
     ///
-    /// This will always be [`None`] on release events.
+    /// This will always be [`None`] on release events and on presses which are part of an unfinished
+    /// compose sequence, such as a dead key. The completed sequence contains the composed text.
     pub utf8: Option<String>,
 }
 
+/// Get the name of a keysym as used in xkb keymaps, such as `XF86AudioPlay`.
+///
+/// Unlike [`Keysym::name`], the name is stable and suited for display or configuration files. This
+/// returns [`None`] if the keysym is invalid.
+pub fn keysym_name(keysym: Keysym) -> Option<String> {
+    // libxkbcommon fails on keysyms outside of the 29 bit range.
+    if keysym.raw() > 0x1fff_ffff {
+        return None;
+    }
+
+    let name = xkb::keysym_get_name(keysym);
+    (!name.is_empty()).then_some(name)
+}
+
 /// The state of keyboard modifiers
 ///
 /// Each field of this indicates whether a specified modifier is active.
@@ -274,6 +433,8 @@ pub struct Modifiers {
     pub shift: bool,
 
     /// The "Caps lock" key
+    ///
+    /// This is true while Caps Lock is locked, not while the key is held.
     pub caps_lock: bool,
 
     /// The "logo" key
@@ -284,7 +445,35 @@ pub struct Modifiers {
     pub logo: bool,
 
     /// The "Num lock" key
+    ///
+    /// This is true while Num Lock is locked, not while the key is held.
+    pub num_lock: bool,
+}
+
+/// The state of the keyboard LEDs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Leds {
+    /// The "Caps lock" LED
+    pub caps_lock: bool,
+
+    /// The "Num lock" LED
     pub num_lock: bool,
+
+    /// The "Scroll lock" LED
+    pub scroll_lock: bool,
+}
+
+/// The effective layout of a keyboard and the state of its LEDs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Layout {
+    /// Index of the layout in the keymap.
+    pub index: u32,
+
+    /// Name of the layout given by the keymap, such as "English (US)".
+    pub name: Option<String>,
+
+    /// State of the keyboard LEDs.
+    pub leds: Leds,
 }
 
 /// The RMLVO description of a keymap
@@ -320,10 +509,18 @@ pub struct KeyboardData<T> {
     /// If the user manually specified the RMLVO to use.
     user_specified_rmlvo: bool,
     xkb_state: Mutex<Option<xkb::State>>,
+    /// Hash of the last keymap sent by the compositor, to skip parsing it again when it is resent.
+    keymap_hash: Mutex<Option<u64>>,
     xkb_compose: Mutex<Option<xkb::compose::State>>,
+    compose_enabled: AtomicBool,
+    release_keys_on_leave: AtomicBool,
+    pressed_keys: Mutex<Vec<u32>>,
     #[cfg(feature = "calloop")]
     repeat_data: Arc<Mutex<Option<RepeatData<T>>>>,
     focus: Mutex<Option<wl_surface::WlSurface>>,
+    layout: Mutex<Option<Layout>>,
+    repeat_info: Mutex<Option<RepeatInfo>>,
+    pub(crate) timestamp: PendingTimestamp,
     _phantom_data: PhantomData<T>,
 }
 
@@ -335,15 +532,15 @@ impl<T> Debug for KeyboardData<T> {
 
 #[macro_export]
 macro_rules! delegate_keyboard {
-    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
-        $crate::reexports::client::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty:
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
             [
                 $crate::reexports::client::protocol::wl_keyboard::WlKeyboard: $crate::seat::keyboard::KeyboardData<$ty>
             ] => $crate::seat::SeatState
         );
     };
-    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty, keyboard: [$($udata:ty),* $(,)?]) => {
-        $crate::reexports::client::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty:
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty, keyboard: [$($udata:ty),* $(,)?] $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
             [
                 $(
                     $crate::reexports::client::protocol::wl_keyboard::WlKeyboard: $udata,
@@ -366,11 +563,18 @@ impl<T> KeyboardData<T> {
             first_event: AtomicBool::new(false),
             xkb_context: Mutex::new(xkb_context),
             xkb_state: Mutex::new(None),
+            keymap_hash: Mutex::new(None),
             user_specified_rmlvo: false,
             xkb_compose: Mutex::new(None),
+            compose_enabled: AtomicBool::new(true),
+            release_keys_on_leave: AtomicBool::new(false),
+            pressed_keys: Mutex::new(Vec::new()),
             #[cfg(feature = "calloop")]
             repeat_data: Arc::new(Mutex::new(None)),
             focus: Mutex::new(None),
+            layout: Mutex::new(None),
+            repeat_info: Mutex::new(None),
+            timestamp: Default::default(),
             _phantom_data: PhantomData,
         };
 
@@ -406,11 +610,18 @@ impl<T> KeyboardData<T> {
             first_event: AtomicBool::new(false),
             xkb_context: Mutex::new(xkb_context),
             xkb_state: Mutex::new(xkb_state),
+            keymap_hash: Mutex::new(None),
             user_specified_rmlvo: true,
             xkb_compose: Mutex::new(None),
+            compose_enabled: AtomicBool::new(true),
+            release_keys_on_leave: AtomicBool::new(false),
+            pressed_keys: Mutex::new(Vec::new()),
             #[cfg(feature = "calloop")]
             repeat_data: Arc::new(Mutex::new(None)),
             focus: Mutex::new(None),
+            layout: Mutex::new(None),
+            repeat_info: Mutex::new(None),
+            timestamp: Default::default(),
             _phantom_data: PhantomData,
         };
 
@@ -444,6 +655,254 @@ impl<T> KeyboardData<T> {
         }
     }
 
+    /// Enables or disables composition of key sequences, such as dead keys.
+    ///
+    /// Composition is enabled by default if a compose table is available for the current locale. When
+    /// disabled, [`KeyEvent::utf8`] contains the text produced by the key alone.
+    pub fn set_compose_enabled(&self, enabled: bool) {
+        self.compose_enabled.store(enabled, Ordering::SeqCst);
+        self.reset_compose();
+    }
+
+    /// Enables or disables release events for the keys still pressed when the keyboard leaves a surface.
+    ///
+    /// The compositor does not send release events for keys held while the focus changes. When enabled,
+    /// [`KeyboardHandler::release_key`] is called for those keys before [`KeyboardHandler::leave`],
+    /// using the serial of the leave event. This is disabled by default.
+    pub fn set_release_keys_on_leave(&self, enabled: bool) {
+        self.release_keys_on_leave.store(enabled, Ordering::SeqCst);
+    }
+
+    /// The raw codes of the keys currently pressed on the focused surface.
+    ///
+    /// The keys are updated before the [`KeyboardHandler`] callbacks run, and cleared when the keyboard
+    /// leaves the surface.
+    pub fn pressed_keys(&self) -> Vec<u32> {
+        self.pressed_keys.lock().unwrap().clone()
+    }
+
+    /// The raw code of the first key producing the keysym in the current keymap, in any layout and level.
+    ///
+    /// The result changes when the keymap changes, and is [`None`] until a keymap is loaded.
+    pub fn keycode_for_keysym(&self, keysym: Keysym) -> Option<u32> {
+        let state = self.xkb_state.lock().unwrap();
+        keycode_for_keysym(&state.as_ref()?.get_keymap(), keysym)
+    }
+
+    /// The keysyms produced by the key with the given raw code in the current keymap, in all layouts
+    /// and levels.
+    ///
+    /// The result changes when the keymap changes, and is empty until a keymap is loaded.
+    pub fn keysyms_for_keycode(&self, raw_code: u32) -> Vec<Keysym> {
+        let state = self.xkb_state.lock().unwrap();
+        match state.as_ref() {
+            Some(state) => keysyms_for_keycode(&state.get_keymap(), raw_code),
+            None => Vec::new(),
+        }
+    }
+
+    /// Whether the key with the given raw code is currently pressed on the focused surface.
+    pub fn is_pressed(&self, raw_code: u32) -> bool {
+        self.pressed_keys.lock().unwrap().contains(&raw_code)
+    }
+
+    /// Whether a key producing the given keysym with the current modifiers is pressed on the focused
+    /// surface.
+    ///
+    /// This always returns false until a keymap is loaded.
+    pub fn is_keysym_pressed(&self, keysym: Keysym) -> bool {
+        let state_guard = self.xkb_state.lock().unwrap();
+        let state = match state_guard.as_ref() {
+            Some(state) => state,
+            None => return false,
+        };
+
+        self.pressed_keys
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|&key| state.key_get_one_sym(KeyCode::new(key + 8)) == keysym)
+    }
+
+    /// Returns whether composition of key sequences is enabled.
+    pub fn compose_enabled(&self) -> bool {
+        self.compose_enabled.load(Ordering::SeqCst)
+    }
+
+    fn reset_compose(&self) {
+        if let Some(compose) = self.xkb_compose.lock().unwrap().as_mut() {
+            compose.reset();
+        }
+    }
+
+    /// Feeds a pressed key through the compose state machine and returns the resulting text.
+    fn key_utf8(&self, state: &xkb::State, keycode: KeyCode, keysym: Keysym) -> Option<String> {
+        let mut compose = self.xkb_compose.lock().unwrap();
+
+        match compose.as_mut().filter(|_| self.compose_enabled()) {
+            Some(compose) => match compose.feed(keysym) {
+                xkb::FeedResult::Ignored => None,
+                xkb::FeedResult::Accepted => match compose.status() {
+                    xkb::Status::Composed => compose.utf8(),
+                    xkb::Status::Nothing => Some(state.key_get_utf8(keycode)),
+                    _ => None,
+                },
+            },
+
+            // No compose
+            None => Some(state.key_get_utf8(keycode)),
+        }
+    }
+
+    /// Records the hash of a keymap sent by the compositor.
+    ///
+    /// Returns true if the keymap is the one currently loaded, in which case it does not need to be
+    /// parsed again.
+    fn keymap_unchanged(&self, hash: Option<u64>) -> bool {
+        let mut keymap_hash = self.keymap_hash.lock().unwrap();
+        let unchanged =
+            hash.is_some() && *keymap_hash == hash && self.xkb_state.lock().unwrap().is_some();
+        *keymap_hash = hash;
+
+        if unchanged {
+            log::debug!(target: "sctk", "keymap unchanged, skipped parsing it");
+        }
+
+        unchanged
+    }
+
+    /// Loads a keymap sent by the compositor and resets the xkb state to use it.
+    ///
+    /// Returns [`None`] if the keymap is invalid or if the keymap was specified by the user, in which case
+    /// keymap updates are ignored.
+    pub(crate) fn load_keymap(&self, fd: OwnedFd, size: u32) -> Option<xkb::Keymap> {
+        if self.user_specified_rmlvo {
+            // state is locked, ignore keymap updates
+            return None;
+        }
+
+        let context = self.xkb_context.lock().unwrap();
+
+        // 0.5.0-beta.0 does not mark this function as unsafe but upstream rightly makes
+        // this function unsafe.
+        //
+        // Version 7 of wl_keyboard requires the file descriptor to be mapped using
+        // MAP_PRIVATE. xkbcommon-rs does mmap the file descriptor properly.
+        //
+        // SAFETY:
+        // - wayland-client guarantees we have received a valid file descriptor.
+        #[allow(unused_unsafe)] // Upstream release will change this
+        match unsafe {
+            xkb::Keymap::new_from_fd(
+                &context,
+                fd,
+                size as usize,
+                xkb::KEYMAP_FORMAT_TEXT_V1,
+                xkb::COMPILE_NO_FLAGS,
+            )
+        } {
+            Ok(Some(keymap)) => {
+                *self.xkb_state.lock().unwrap() = Some(xkb::State::new(&keymap));
+                Some(keymap)
+            }
+
+            Ok(None) => {
+                log::error!(target: "sctk", "invalid keymap");
+                None
+            }
+
+            Err(err) => {
+                log::error!(target: "sctk", "{}", err);
+                None
+            }
+        }
+    }
+
+    /// Interprets a raw key press or release using the current keymap.
+    ///
+    /// Returns [`None`] if no keymap was loaded yet.
+    pub(crate) fn key_event(
+        &self,
+        serial: u32,
+        time: u32,
+        key: u32,
+        pressed: bool,
+    ) -> Option<KeyEvent> {
+        let state_guard = self.xkb_state.lock().unwrap();
+        let state = state_guard.as_ref()?;
+
+        // We must add 8 to the keycode for any functions we pass the raw keycode into per
+        // wl_keyboard protocol.
+        let keycode = KeyCode::new(key + 8);
+        let keysym = state.key_get_one_sym(keycode);
+        let utf8 = if pressed { self.key_utf8(state, keycode, keysym) } else { None };
+
+        Some(KeyEvent { time, serial, raw_code: key, keycode, keysym, timestamp: None, utf8 })
+    }
+
+    /// Applies serialized modifier state sent by the compositor.
+    ///
+    /// Returns the new modifiers, or [`None`] if no keymap was loaded yet.
+    pub(crate) fn update_mask(
+        &self,
+        mods_depressed: u32,
+        mods_latched: u32,
+        mods_locked: u32,
+        group: u32,
+    ) -> Option<Modifiers> {
+        let mut guard = self.xkb_state.lock().unwrap();
+        let state = guard.as_mut()?;
+        let _ = state.update_mask(mods_depressed, mods_latched, mods_locked, 0, 0, group);
+        drop(guard);
+
+        Some(self.update_modifiers())
+    }
+
+    /// The latest repeat info sent by the compositor, if any.
+    ///
+    /// When the seat is older than version 4, this holds the default repeat info once the first event
+    /// is received.
+    pub fn repeat_info(&self) -> Option<RepeatInfo> {
+        *self.repeat_info.lock().unwrap()
+    }
+
+    pub(crate) fn store_repeat_info(&self, info: RepeatInfo) {
+        *self.repeat_info.lock().unwrap() = Some(info);
+    }
+
+    /// The effective layout and LED state, as last reported to
+    /// [`KeyboardHandler::update_layout`].
+    pub fn layout(&self) -> Option<Layout> {
+        self.layout.lock().unwrap().clone()
+    }
+
+    /// Computes the effective layout, returning it if it changed since the last call.
+    pub(crate) fn update_layout(&self) -> Option<Layout> {
+        let guard = self.xkb_state.lock().unwrap();
+        let state = guard.as_ref()?;
+
+        let index = state.serialize_layout(xkb::STATE_LAYOUT_EFFECTIVE);
+        let name = state.get_keymap().layout_get_name(index).to_owned();
+        let layout = Layout {
+            index,
+            name: (!name.is_empty()).then_some(name),
+            leds: Leds {
+                caps_lock: state.led_name_is_active(xkb::LED_NAME_CAPS),
+                num_lock: state.led_name_is_active(xkb::LED_NAME_NUM),
+                scroll_lock: state.led_name_is_active(xkb::LED_NAME_SCROLL),
+            },
+        };
+        drop(guard);
+
+        let mut current = self.layout.lock().unwrap();
+        if current.as_ref() == Some(&layout) {
+            None
+        } else {
+            *current = Some(layout.clone());
+            Some(layout)
+        }
+    }
+
     fn update_modifiers(&self) -> Modifiers {
         let guard = self.xkb_state.lock().unwrap();
         let state = guard.as_ref().unwrap();
@@ -452,9 +911,9 @@ impl<T> KeyboardData<T> {
             ctrl: state.mod_name_is_active(xkb::MOD_NAME_CTRL, xkb::STATE_MODS_EFFECTIVE),
             alt: state.mod_name_is_active(xkb::MOD_NAME_ALT, xkb::STATE_MODS_EFFECTIVE),
             shift: state.mod_name_is_active(xkb::MOD_NAME_SHIFT, xkb::STATE_MODS_EFFECTIVE),
-            caps_lock: state.mod_name_is_active(xkb::MOD_NAME_CAPS, xkb::STATE_MODS_EFFECTIVE),
+            caps_lock: state.mod_name_is_active(xkb::MOD_NAME_CAPS, xkb::STATE_MODS_LOCKED),
             logo: state.mod_name_is_active(xkb::MOD_NAME_LOGO, xkb::STATE_MODS_EFFECTIVE),
-            num_lock: state.mod_name_is_active(xkb::MOD_NAME_NUM, xkb::STATE_MODS_EFFECTIVE),
+            num_lock: state.mod_name_is_active(xkb::MOD_NAME_NUM, xkb::STATE_MODS_LOCKED),
         }
     }
 }
@@ -495,19 +954,40 @@ where
         // The compositor has no way to tell clients if the seat is not version 4 or above.
         // In this case, send a synthetic repeat info event using the default repeat values used by the X
         // server.
-        if keyboard.version() < 4 && udata.first_event.load(Ordering::SeqCst) {
-            udata.first_event.store(true, Ordering::SeqCst);
-
-            data.update_repeat_info(
-                conn,
-                qh,
-                keyboard,
-                RepeatInfo::Repeat { rate: NonZeroU32::new(200).unwrap(), delay: 200 },
-            );
+        if keyboard.version() < 4 && !udata.first_event.swap(true, Ordering::SeqCst) {
+            let info = RepeatInfo::Repeat { rate: NonZeroU32::new(200).unwrap(), delay: 200 };
+            udata.store_repeat_info(info);
+            data.update_repeat_info(conn, qh, keyboard, info);
         }
 
         match event {
             wl_keyboard::Event::Keymap { format, fd, size } => {
+                // Version 7 of wl_keyboard requires the file descriptor to be mapped using MAP_PRIVATE.
+                //
+                // SAFETY: wayland-client guarantees we have received a valid file descriptor and the
+                // compositor must not modify the keymap once it was sent.
+                let mapping = (size != 0)
+                    .then(|| unsafe {
+                        MmapOptions::new().len(size as usize).map_copy_read_only(&fd)
+                    })
+                    .transpose()
+                    .unwrap_or_else(|err| {
+                        log::error!(target: "sctk", "failed to map keymap: {}", err);
+                        None
+                    });
+                data.raw_keymap(
+                    conn,
+                    qh,
+                    keyboard,
+                    RawKeymap { format, data: mapping.as_deref().unwrap_or_default() },
+                );
+                let hash = mapping.as_deref().map(|data| {
+                    let mut hasher = DefaultHasher::new();
+                    data.hash(&mut hasher);
+                    hasher.finish()
+                });
+                drop(mapping);
+
                 match format {
                     WEnum::Value(format) => match format {
                         wl_keyboard::KeymapFormat::NoKeymap => {
@@ -515,47 +995,17 @@ where
                         }
 
                         wl_keyboard::KeymapFormat::XkbV1 => {
-                            if udata.user_specified_rmlvo {
-                                // state is locked, ignore keymap updates
+                            // Some compositors resend the same keymap, for example on VT switches.
+                            if udata.keymap_unchanged(hash) {
                                 return;
                             }
 
-                            let context = udata.xkb_context.lock().unwrap();
-
-                            // 0.5.0-beta.0 does not mark this function as unsafe but upstream rightly makes
-                            // this function unsafe.
-                            //
-                            // Version 7 of wl_keyboard requires the file descriptor to be mapped using
-                            // MAP_PRIVATE. xkbcommon-rs does mmap the file descriptor properly.
-                            //
-                            // SAFETY:
-                            // - wayland-client guarantees we have received a valid file descriptor.
-                            #[allow(unused_unsafe)] // Upstream release will change this
-                            match unsafe {
-                                xkb::Keymap::new_from_fd(
-                                    &context,
-                                    fd,
-                                    size as usize,
-                                    xkb::KEYMAP_FORMAT_TEXT_V1,
-                                    xkb::COMPILE_NO_FLAGS,
-                                )
-                            } {
-                                Ok(Some(keymap)) => {
-                                    let state = xkb::State::new(&keymap);
-                                    {
-                                        let mut state_guard = udata.xkb_state.lock().unwrap();
-                                        *state_guard = Some(state);
-                                    }
-                                    data.update_keymap(conn, qh, keyboard, Keymap(&keymap));
-                                }
-
-                                Ok(None) => {
-                                    log::error!(target: "sctk", "invalid keymap");
-                                }
+                            if let Some(keymap) = udata.load_keymap(fd, size) {
+                                data.update_keymap(conn, qh, keyboard, Keymap(&keymap));
+                            }
 
-                                Err(err) => {
-                                    log::error!(target: "sctk", "{}", err);
-                                }
+                            if let Some(layout) = udata.update_layout() {
+                                data.update_layout(conn, qh, keyboard, layout);
                             }
                         }
 
@@ -569,39 +1019,31 @@ where
             }
 
             wl_keyboard::Event::Enter { serial, surface, keys } => {
-                let state_guard = udata.xkb_state.lock().unwrap();
+                // Keys are encoded as an array of u32
+                let raw = keys
+                    .chunks_exact(4)
+                    .flat_map(TryInto::<[u8; 4]>::try_into)
+                    .map(u32::from_ne_bytes)
+                    .collect::<Vec<_>>();
 
-                if let Some(guard) = state_guard.as_ref() {
-                    // Keysyms are encoded as an array of u32
-                    let raw = keys
-                        .chunks_exact(4)
-                        .flat_map(TryInto::<[u8; 4]>::try_into)
-                        .map(u32::from_le_bytes)
-                        .collect::<Vec<_>>();
-
-                    let keysyms = raw
-                        .iter()
-                        .copied()
+                let state_guard = udata.xkb_state.lock().unwrap();
+                let keysyms = raw
+                    .iter()
+                    .map(|&raw| match state_guard.as_ref() {
                         // We must add 8 to the keycode for any functions we pass the raw keycode into per
                         // wl_keyboard protocol.
-                        .map(|raw| guard.key_get_one_sym(KeyCode::new(raw + 8)))
-                        .collect::<Vec<_>>();
-
-                    // Drop guard before calling user code.
-                    drop(state_guard);
-
-                    data.enter(
-                        conn,
-                        qh,
-                        keyboard,
-                        &surface,
-                        serial,
-                        &raw,
-                        bytemuck::cast_slice(&keysyms),
-                    );
-                }
+                        Some(state) => state.key_get_one_sym(KeyCode::new(raw + 8)),
+                        None => Keysym::NoSymbol,
+                    })
+                    .collect::<Vec<_>>();
+
+                // Drop guard before calling user code.
+                drop(state_guard);
+
+                *udata.pressed_keys.lock().unwrap() = raw.clone();
+                *udata.focus.lock().unwrap() = Some(surface.clone());
 
-                *udata.focus.lock().unwrap() = Some(surface);
+                data.enter(conn, qh, keyboard, &surface, serial, &raw, &keysyms);
             }
 
             wl_keyboard::Event::Leave { serial, surface } => {
@@ -610,7 +1052,27 @@ where
                 #[cfg(feature = "calloop")]
                 {
                     if let Some(repeat_data) = udata.repeat_data.lock().unwrap().as_mut() {
-                        repeat_data.current_repeat.take();
+                        repeat_data.stop();
+                    }
+                }
+
+                // A compose sequence can not continue on another surface.
+                udata.reset_compose();
+
+                // No release is sent for keys held while leaving, forget them and the modifiers they
+                // hold. The compositor sends the current modifiers on the next enter.
+                let pressed_keys = mem::take(&mut *udata.pressed_keys.lock().unwrap());
+                if let Some(state) = udata.xkb_state.lock().unwrap().as_mut() {
+                    let locked = state.serialize_mods(xkb::STATE_MODS_LOCKED);
+                    let group = state.serialize_layout(xkb::STATE_LAYOUT_EFFECTIVE);
+                    let _ = state.update_mask(0, 0, locked, 0, 0, group);
+                }
+
+                if udata.release_keys_on_leave.load(Ordering::SeqCst) {
+                    for key in pressed_keys {
+                        if let Some(event) = udata.key_event(serial, 0, key, false) {
+                            data.release_key(conn, qh, keyboard, serial, event);
+                        }
                     }
                 }
 
@@ -621,37 +1083,18 @@ where
 
             wl_keyboard::Event::Key { serial, time, key, state } => match state {
                 WEnum::Value(state) => {
-                    let state_guard = udata.xkb_state.lock().unwrap();
+                    let pressed = state == wl_keyboard::KeyState::Pressed;
 
-                    if let Some(guard) = state_guard.as_ref() {
-                        // We must add 8 to the keycode for any functions we pass the raw keycode into per
-                        // wl_keyboard protocol.
-                        let keycode = KeyCode::new(key + 8);
-                        let keysym = guard.key_get_one_sym(keycode);
-                        let utf8 = if state == wl_keyboard::KeyState::Pressed {
-                            let mut compose = udata.xkb_compose.lock().unwrap();
-
-                            match compose.as_mut() {
-                                Some(compose) => match compose.feed(keysym) {
-                                    xkb::FeedResult::Ignored => None,
-                                    xkb::FeedResult::Accepted => match compose.status() {
-                                        xkb::Status::Composed => compose.utf8(),
-                                        xkb::Status::Nothing => Some(guard.key_get_utf8(keycode)),
-                                        _ => None,
-                                    },
-                                },
-
-                                // No compose
-                                None => Some(guard.key_get_utf8(keycode)),
-                            }
-                        } else {
-                            None
-                        };
-
-                        // Drop guard before calling user code.
-                        drop(state_guard);
+                    {
+                        let mut pressed_keys = udata.pressed_keys.lock().unwrap();
+                        pressed_keys.retain(|&k| k != key);
+                        if pressed {
+                            pressed_keys.push(key);
+                        }
+                    }
 
-                        let event = KeyEvent { time, raw_code: key, keysym, utf8 };
+                    if let Some(mut event) = udata.key_event(serial, time, key, pressed) {
+                        event.timestamp = udata.timestamp.take();
 
                         match state {
                             wl_keyboard::KeyState::Released => {
@@ -666,7 +1109,7 @@ where
                                                 .as_ref()
                                                 .map(|r| r.key.raw_code)
                                         {
-                                            repeat_data.current_repeat = None;
+                                            repeat_data.stop();
                                         }
                                     }
                                 }
@@ -679,22 +1122,16 @@ where
                                     if let Some(repeat_data) =
                                         udata.repeat_data.lock().unwrap().as_mut()
                                     {
-                                        let loop_handle = &mut repeat_data.loop_handle;
                                         let state_guard = udata.xkb_state.lock().unwrap();
                                         let key_repeats = state_guard
                                             .as_ref()
                                             .map(|guard| {
-                                                guard
-                                                    .get_keymap()
-                                                    .key_repeats(KeyCode::new(event.raw_code + 8))
+                                                guard.get_keymap().key_repeats(event.keycode)
                                             })
                                             .unwrap_or_default();
-                                        if key_repeats {
-                                            // Cancel the previous timer / repeat.
-                                            if let Some(token) = repeat_data.repeat_token.take() {
-                                                loop_handle.remove(token);
-                                            }
+                                        drop(state_guard);
 
+                                        if key_repeats {
                                             let surface = udata
                                                 .focus
                                                 .lock()
@@ -703,60 +1140,17 @@ where
                                                 .cloned()
                                                 .expect("wl_keyboard::key with no focused surface");
 
-                                            // Update the current repeat key.
+                                            // Update the current repeat key and restart the timer.
                                             repeat_data.current_repeat.replace(RepeatedKey {
                                                 key: event.clone(),
                                                 is_first: true,
                                                 surface,
                                             });
-
-                                            let (delay, rate) = match repeat_data.repeat_info {
-                                                RepeatInfo::Disable => return,
-                                                RepeatInfo::Repeat { delay, rate } => (delay, rate),
-                                            };
-                                            let gap = Duration::from_micros(
-                                                1_000_000 / rate.get() as u64,
-                                            );
-                                            let timer = Timer::from_duration(
-                                                Duration::from_millis(delay as u64),
+                                            repeat_data.arm(
+                                                &udata.repeat_data,
+                                                keyboard,
+                                                udata.repeat_info(),
                                             );
-                                            let repeat_data2 = udata.repeat_data.clone();
-
-                                            // Start the timer.
-                                            let kbd = keyboard.clone();
-                                            if let Ok(token) = loop_handle.insert_source(
-                                                timer,
-                                                move |_, _, state| {
-                                                    let mut repeat_data =
-                                                        repeat_data2.lock().unwrap();
-                                                    let repeat_data = match repeat_data.as_mut() {
-                                                        Some(repeat_data) => repeat_data,
-                                                        None => return TimeoutAction::Drop,
-                                                    };
-
-                                                    let callback = &mut repeat_data.callback;
-                                                    let key = &mut repeat_data.current_repeat;
-                                                    if key.is_none() {
-                                                        return TimeoutAction::Drop;
-                                                    }
-                                                    let key = key.as_mut().unwrap();
-                                                    // If surface was closed while focused, no `Leave`
-                                                    // event occurred.
-                                                    if !key.surface.is_alive() {
-                                                        return TimeoutAction::Drop;
-                                                    }
-                                                    key.key.time += if key.is_first {
-                                                        key.is_first = false;
-                                                        delay
-                                                    } else {
-                                                        gap.as_millis() as u32
-                                                    };
-                                                    callback(state, &kbd, key.key.clone());
-                                                    TimeoutAction::ToDuration(gap)
-                                                },
-                                            ) {
-                                                repeat_data.repeat_token = Some(token);
-                                            }
                                         }
                                     }
                                 }
@@ -795,28 +1189,7 @@ where
                 if let Some(repeat_data) = udata.repeat_data.lock().unwrap().as_mut() {
                     if let Some(mut event) = repeat_data.current_repeat.take() {
                         // Apply new modifiers to get new utf8.
-                        event.key.utf8 = {
-                            let mut compose = udata.xkb_compose.lock().unwrap();
-
-                            match compose.as_mut() {
-                                Some(compose) => match compose.feed(event.key.keysym) {
-                                    xkb::FeedResult::Ignored => None,
-                                    xkb::FeedResult::Accepted => match compose.status() {
-                                        xkb::Status::Composed => compose.utf8(),
-                                        xkb::Status::Nothing => Some(
-                                            state
-                                                .key_get_utf8(KeyCode::new(event.key.raw_code + 8)),
-                                        ),
-                                        _ => None,
-                                    },
-                                },
-
-                                // No compose.
-                                None => {
-                                    Some(state.key_get_utf8(KeyCode::new(event.key.raw_code + 8)))
-                                }
-                            }
-                        };
+                        event.key.utf8 = udata.key_utf8(state, event.key.keycode, event.key.keysym);
 
                         // Update the stored event.
                         repeat_data.current_repeat = Some(event);
@@ -829,6 +1202,10 @@ where
                 // Always issue the modifiers update for the user.
                 let modifiers = udata.update_modifiers();
                 data.update_modifiers(conn, qh, keyboard, serial, modifiers, group);
+
+                if let Some(layout) = udata.update_layout() {
+                    data.update_layout(conn, qh, keyboard, layout);
+                }
             }
 
             wl_keyboard::Event::RepeatInfo { rate, delay } => {
@@ -841,10 +1218,14 @@ where
                     RepeatInfo::Disable
                 };
 
+                udata.store_repeat_info(info);
+
                 #[cfg(feature = "calloop")]
                 {
                     if let Some(repeat_data) = udata.repeat_data.lock().unwrap().as_mut() {
-                        repeat_data.repeat_info = info;
+                        // Retime a key which is currently held, this also stops the timer if repeat
+                        // was disabled.
+                        repeat_data.arm(&udata.repeat_data, keyboard, Some(info));
                     }
                 }
                 data.update_repeat_info(conn, qh, keyboard, info);
@@ -854,3 +1235,271 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use wayland_backend::{protocol::Argument, server::ObjectId};
+    use wayland_client::{
+        protocol::{
+            wl_compositor::WlCompositor,
+            wl_keyboard::WlKeyboard,
+            wl_seat::{self, WlSeat},
+        },
+        Proxy,
+    };
+    use xkbcommon::xkb;
+
+    use super::{KeyCode, KeyEvent, KeyboardData, KeyboardDataExt, Keysym, RMLVO};
+    use crate::testing::{Call, TestClient, TestServer, TestState};
+
+    const COMPOSE: &str = "<dead_acute> <e> : \"\u{e9}\" eacute\n";
+
+    const KEY_A: u32 = 30;
+    const KEY_LEFTSHIFT: u32 = 42;
+    /// The modifier mask of Shift in the `us` layout.
+    const SHIFT: u32 = 1;
+
+    fn us() -> RMLVO {
+        RMLVO { rules: None, model: None, layout: Some("us".into()), variant: None, options: None }
+    }
+
+    fn keyboard(server: &mut TestServer) -> KeyboardData<TestState> {
+        let client = server.connect();
+        let seat = WlSeat::inert(client.conn.backend().downgrade());
+        let keyboard = KeyboardData::from_rmlvo(seat, us()).unwrap();
+
+        let context = keyboard.xkb_context.lock().unwrap();
+        let table = xkb::compose::Table::new_from_buffer(
+            &context,
+            COMPOSE,
+            "C",
+            xkb::compose::FORMAT_TEXT_V1,
+            xkb::compose::COMPILE_NO_FLAGS,
+        )
+        .unwrap();
+        *keyboard.xkb_compose.lock().unwrap() =
+            Some(xkb::compose::State::new(&table, xkb::compose::STATE_NO_FLAGS));
+        drop(context);
+
+        keyboard
+    }
+
+    /// Feeds a keysym, using the key of `e` for the text produced without composition.
+    fn feed(keyboard: &KeyboardData<TestState>, keysym: Keysym) -> Option<String> {
+        let state = keyboard.xkb_state.lock().unwrap();
+        keyboard.key_utf8(state.as_ref().unwrap(), KeyCode::new(18 + 8), keysym)
+    }
+
+    #[test]
+    fn compose_dead_key() {
+        let mut server = TestServer::new();
+        let keyboard = keyboard(&mut server);
+
+        assert_eq!(feed(&keyboard, Keysym::dead_acute), None);
+        assert_eq!(feed(&keyboard, Keysym::e).as_deref(), Some("\u{e9}"));
+        // Keys outside of a sequence produce their own text.
+        assert_eq!(feed(&keyboard, Keysym::e).as_deref(), Some("e"));
+    }
+
+    #[test]
+    fn compose_reset() {
+        let mut server = TestServer::new();
+        let keyboard = keyboard(&mut server);
+
+        assert_eq!(feed(&keyboard, Keysym::dead_acute), None);
+        keyboard.reset_compose();
+        assert_eq!(feed(&keyboard, Keysym::e).as_deref(), Some("e"));
+    }
+
+    #[test]
+    fn compose_disabled() {
+        let mut server = TestServer::new();
+        let keyboard = keyboard(&mut server);
+
+        keyboard.set_compose_enabled(false);
+        assert_eq!(feed(&keyboard, Keysym::dead_acute).as_deref(), Some("e"));
+        assert_eq!(feed(&keyboard, Keysym::e).as_deref(), Some("e"));
+
+        keyboard.set_compose_enabled(true);
+        assert_eq!(feed(&keyboard, Keysym::dead_acute), None);
+        assert_eq!(feed(&keyboard, Keysym::e).as_deref(), Some("\u{e9}"));
+    }
+
+    /// A keyboard with the `us` layout and a surface it can enter.
+    struct Keyboard {
+        server: TestServer,
+        client: TestClient,
+        keyboard: WlKeyboard,
+        surface: ObjectId,
+        serial: u32,
+    }
+
+    impl Keyboard {
+        fn new() -> Self {
+            let mut server = TestServer::new();
+            server.create_global(WlCompositor::interface(), 6);
+            server.create_global(WlSeat::interface(), 9);
+            let mut client = server.connect();
+            let seat = client.seat(&mut server, wl_seat::Capability::Keyboard);
+            let keyboard =
+                client.state.seat_state.get_keyboard(&client.qh, &seat, Some(us())).unwrap();
+            let surface = client.create_surface();
+            let surface = server.object(&surface);
+            Keyboard { server, client, keyboard, surface, serial: 0 }
+        }
+
+        fn send(&mut self, event: &str, args: Vec<Argument<ObjectId, i32>>) {
+            self.serial += 1;
+            let mut all_args = vec![Argument::Uint(self.serial)];
+            all_args.extend(args);
+            let keyboard = self.server.object(&self.keyboard);
+            self.server.send(&keyboard, event, all_args);
+        }
+
+        fn enter(&mut self, keys: &[u32]) {
+            let keys = keys.iter().flat_map(|key| key.to_ne_bytes()).collect();
+            let surface = Argument::Object(self.surface.clone());
+            self.send("enter", vec![surface, Argument::Array(Box::new(keys))]);
+        }
+
+        fn leave(&mut self) {
+            self.send("leave", vec![Argument::Object(self.surface.clone())]);
+        }
+
+        fn key(&mut self, key: u32, pressed: bool) {
+            let state = Argument::Uint(pressed.into());
+            self.send("key", vec![Argument::Uint(0), Argument::Uint(key), state]);
+        }
+
+        fn modifiers(&mut self, depressed: u32) {
+            let args = vec![depressed, 0, 0, 0].into_iter().map(Argument::Uint).collect();
+            self.send("modifiers", args);
+        }
+
+        fn calls(&mut self) -> Vec<Call> {
+            self.client.roundtrip();
+            self.client.state.take_calls()
+        }
+
+        fn data(&self) -> &KeyboardData<TestState> {
+            self.keyboard.data::<KeyboardData<TestState>>().unwrap().keyboard_data()
+        }
+    }
+
+    fn pressed(calls: &[Call]) -> Vec<&KeyEvent> {
+        calls
+            .iter()
+            .filter_map(|call| match call {
+                Call::PressKey(event) => Some(event),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn modifier_held_across_leave() {
+        let mut keyboard = Keyboard::new();
+        keyboard.enter(&[]);
+        keyboard.key(KEY_LEFTSHIFT, true);
+        keyboard.modifiers(SHIFT);
+        keyboard.key(KEY_A, true);
+        let calls = keyboard.calls();
+        assert_eq!(pressed(&calls)[1].keysym, Keysym::A);
+
+        // Focus moves to another client while Shift and A are held, so no release is sent for them.
+        keyboard.leave();
+        let calls = keyboard.calls();
+        assert!(matches!(calls[..], [Call::KeyboardLeave]), "{:?}", calls);
+        assert!(keyboard.data().pressed_keys().is_empty());
+
+        // Shift was released while the focus was elsewhere.
+        keyboard.enter(&[]);
+        keyboard.key(KEY_A, true);
+        let calls = keyboard.calls();
+        assert!(matches!(&calls[0], Call::KeyboardEnter(raw, _) if raw.is_empty()));
+        let press = pressed(&calls)[0];
+        assert_eq!(press.keysym, Keysym::a);
+        assert_eq!(press.utf8.as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn key_held_across_leave() {
+        let mut keyboard = Keyboard::new();
+        keyboard.enter(&[]);
+        keyboard.key(KEY_A, true);
+        keyboard.calls();
+
+        keyboard.leave();
+        keyboard.enter(&[KEY_A]);
+        let calls = keyboard.calls();
+        assert!(matches!(
+            &calls[..],
+            [Call::KeyboardLeave, Call::KeyboardEnter(raw, keysyms)]
+                if raw == &[KEY_A] && keysyms == &[Keysym::a]
+        ));
+        assert_eq!(keyboard.data().pressed_keys(), [KEY_A]);
+
+        keyboard.key(KEY_A, false);
+        keyboard.key(KEY_A, true);
+        let calls = keyboard.calls();
+        assert!(matches!(&calls[..], [Call::ReleaseKey(_), Call::PressKey(_)]));
+        assert_eq!(pressed(&calls)[0].utf8.as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn release_keys_on_leave() {
+        let mut keyboard = Keyboard::new();
+        keyboard.data().set_release_keys_on_leave(true);
+        keyboard.enter(&[]);
+        keyboard.key(KEY_LEFTSHIFT, true);
+        keyboard.key(KEY_A, true);
+        keyboard.calls();
+
+        keyboard.leave();
+        let calls = keyboard.calls();
+        let released: Vec<_> = calls
+            .iter()
+            .filter_map(|call| match call {
+                Call::ReleaseKey(event) => Some(event.raw_code),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(released, [KEY_LEFTSHIFT, KEY_A]);
+        assert!(matches!(calls.last(), Some(Call::KeyboardLeave)));
+    }
+
+    #[test]
+    fn pressed_keys() {
+        let mut keyboard = Keyboard::new();
+        keyboard.enter(&[]);
+        keyboard.key(KEY_A, true);
+        keyboard.calls();
+        assert!(keyboard.data().is_pressed(KEY_A));
+        assert!(keyboard.data().is_keysym_pressed(Keysym::a));
+        assert!(!keyboard.data().is_pressed(KEY_LEFTSHIFT));
+
+        // Keysyms are resolved with the current modifiers.
+        keyboard.key(KEY_LEFTSHIFT, true);
+        keyboard.modifiers(SHIFT);
+        keyboard.calls();
+        assert_eq!(keyboard.data().pressed_keys(), [KEY_A, KEY_LEFTSHIFT]);
+        assert!(keyboard.data().is_keysym_pressed(Keysym::A));
+        assert!(!keyboard.data().is_keysym_pressed(Keysym::a));
+
+        keyboard.key(KEY_A, false);
+        keyboard.calls();
+        assert!(!keyboard.data().is_pressed(KEY_A));
+        assert!(!keyboard.data().is_keysym_pressed(Keysym::A));
+        assert!(keyboard.data().is_pressed(KEY_LEFTSHIFT));
+
+        keyboard.leave();
+        keyboard.calls();
+        assert!(!keyboard.data().is_pressed(KEY_LEFTSHIFT));
+        assert!(!keyboard.data().is_keysym_pressed(Keysym::Shift_L));
+
+        keyboard.enter(&[KEY_A]);
+        keyboard.calls();
+        assert!(keyboard.data().is_pressed(KEY_A));
+        assert!(keyboard.data().is_keysym_pressed(Keysym::a));
+    }
+}
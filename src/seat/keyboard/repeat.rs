@@ -1,12 +1,18 @@
-use std::sync::atomic::Ordering;
+use std::{
+    sync::{atomic::Ordering, Arc, Mutex},
+    time::Duration,
+};
 
-use calloop::{LoopHandle, RegistrationToken};
+use calloop::{
+    timer::{TimeoutAction, Timer},
+    LoopHandle, RegistrationToken,
+};
 use wayland_client::{
     protocol::{
         wl_keyboard::{self, WlKeyboard},
         wl_seat, wl_surface,
     },
-    Dispatch, QueueHandle,
+    Connection, Dispatch, Proxy, QueueHandle,
 };
 
 use super::{
@@ -26,12 +32,93 @@ pub type RepeatCallback<T> = Box<dyn FnMut(&mut T, &WlKeyboard, KeyEvent) + 'sta
 
 pub(crate) struct RepeatData<T> {
     pub(crate) current_repeat: Option<RepeatedKey>,
-    pub(crate) repeat_info: RepeatInfo,
     pub(crate) loop_handle: LoopHandle<'static, T>,
     pub(crate) callback: RepeatCallback<T>,
     pub(crate) repeat_token: Option<RegistrationToken>,
 }
 
+impl<T: 'static> RepeatData<T> {
+    /// Stops the current repeat, if any, and cancels the timer driving it.
+    pub(crate) fn stop(&mut self) {
+        self.current_repeat = None;
+        self.cancel_timer();
+    }
+
+    fn cancel_timer(&mut self) {
+        if let Some(token) = self.repeat_token.take() {
+            self.loop_handle.remove(token);
+        }
+    }
+
+    /// (Re)starts the timer for the current repeat using the latest repeat info.
+    ///
+    /// Keys are not repeated until the compositor sent the repeat info.
+    ///
+    /// Any previously running timer is cancelled, so this is used both when a new key is pressed and when the
+    /// repeat info changes while a key is held.
+    pub(crate) fn arm(
+        &mut self,
+        repeat_data: &Arc<Mutex<Option<RepeatData<T>>>>,
+        keyboard: &WlKeyboard,
+        repeat_info: Option<RepeatInfo>,
+    ) {
+        self.cancel_timer();
+
+        let is_first = match self.current_repeat.as_ref() {
+            Some(current) => current.is_first,
+            None => return,
+        };
+
+        let (delay, rate) = match repeat_info {
+            Some(RepeatInfo::Repeat { delay, rate }) => (delay, rate),
+            Some(RepeatInfo::Disable) | None => return,
+        };
+        let gap = Duration::from_micros(1_000_000 / rate.get() as u64);
+        let timer =
+            Timer::from_duration(if is_first { Duration::from_millis(delay as u64) } else { gap });
+        let repeat_data = repeat_data.clone();
+        let kbd = keyboard.clone();
+
+        let result = self.loop_handle.insert_source(timer, move |_, _, state| {
+            let mut repeat_data = repeat_data.lock().unwrap();
+            let repeat_data = match repeat_data.as_mut() {
+                Some(repeat_data) => repeat_data,
+                None => return TimeoutAction::Drop,
+            };
+
+            let key = match repeat_data.current_repeat.as_mut() {
+                // If surface was closed while focused, no `Leave` event occurred.
+                Some(key) if key.surface.is_alive() => key,
+                _ => {
+                    repeat_data.current_repeat = None;
+                    repeat_data.repeat_token = None;
+                    return TimeoutAction::Drop;
+                }
+            };
+
+            let elapsed = if key.is_first {
+                key.is_first = false;
+                delay
+            } else {
+                gap.as_millis() as u32
+            };
+            key.key.time += elapsed;
+            key.key.timestamp = key
+                .key
+                .timestamp
+                .map(|timestamp| timestamp + Duration::from_millis(elapsed as u64));
+            let event = key.key.clone();
+            (repeat_data.callback)(state, &kbd, event);
+            TimeoutAction::ToDuration(gap)
+        });
+
+        match result {
+            Ok(token) => self.repeat_token = Some(token),
+            Err(err) => log::error!(target: "sctk", "failed to start key repeat timer: {}", err),
+        }
+    }
+}
+
 impl<T> Drop for RepeatData<T> {
     fn drop(&mut self) {
         if let Some(token) = self.repeat_token.take() {
@@ -45,6 +132,9 @@ impl SeatState {
     ///
     /// This function returns an [`EventSource`] that indicates when a key press is going to repeat.
     ///
+    /// The repeat timer follows the rate and delay advertised by the compositor. Repetition stops when the key
+    /// is released, when the keyboard leaves the focused surface or when the compositor disables key repeat.
+    ///
     /// This keyboard implementation uses libxkbcommon for the keymap.
     ///
     /// Typically the compositor will provide a keymap, but you may specify your own keymap using the `rmlvo`
@@ -75,6 +165,35 @@ impl SeatState {
         self.get_keyboard_with_repeat_with_data(qh, seat, udata, loop_handle, callback)
     }
 
+    /// Creates a keyboard from a seat, delivering repeated keys to [`KeyboardHandler::repeat_key`].
+    ///
+    /// This works like [`SeatState::get_keyboard_with_repeat`], for applications whose event loop state
+    /// is the handler of the keyboard.
+    ///
+    /// ## Errors
+    ///
+    /// This will return [`SeatError::UnsupportedCapability`] if the seat does not support a keyboard.
+    pub fn get_keyboard_with_repeat_handler<D>(
+        &mut self,
+        qh: &QueueHandle<D>,
+        seat: &wl_seat::WlSeat,
+        rmlvo: Option<RMLVO>,
+        loop_handle: LoopHandle<'static, D>,
+    ) -> Result<wl_keyboard::WlKeyboard, KeyboardError>
+    where
+        D: Dispatch<wl_keyboard::WlKeyboard, KeyboardData<D>> + KeyboardHandler + 'static,
+    {
+        let repeat_qh = qh.clone();
+        let callback: RepeatCallback<D> = Box::new(move |state, keyboard, event| {
+            if let Some(backend) = keyboard.backend().upgrade() {
+                let conn = Connection::from_backend(backend);
+                state.repeat_key(&conn, &repeat_qh, keyboard, event);
+            }
+        });
+
+        self.get_keyboard_with_repeat(qh, seat, rmlvo, loop_handle, callback)
+    }
+
     /// Creates a keyboard from a seat.
     ///
     /// This function returns an [`EventSource`] that indicates when a key press is going to repeat.
@@ -101,17 +220,15 @@ impl SeatState {
         D: Dispatch<wl_keyboard::WlKeyboard, U> + KeyboardHandler + 'static,
         U: KeyboardDataExt + 'static,
     {
-        let inner =
-            self.seats.iter().find(|inner| &inner.seat == seat).ok_or(SeatError::DeadObject)?;
+        let data = self.seat_data(seat).ok_or(SeatError::DeadObject)?;
 
-        if !inner.data.has_keyboard.load(Ordering::SeqCst) {
+        if !data.has_keyboard.load(Ordering::SeqCst) {
             return Err(SeatError::UnsupportedCapability(Capability::Keyboard).into());
         }
 
         let kbd_data = udata.keyboard_data_mut();
         kbd_data.repeat_data.lock().unwrap().replace(RepeatData {
             current_repeat: None,
-            repeat_info: RepeatInfo::Disable,
             loop_handle: loop_handle.clone(),
             callback,
             repeat_token: None,
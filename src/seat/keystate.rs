@@ -0,0 +1,124 @@
+use wayland_client::{globals::GlobalList, Connection, Dispatch, QueueHandle};
+use wayland_protocols_plasma::keystate::client::org_kde_kwin_keystate::{self, OrgKdeKwinKeystate};
+
+use crate::{
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+    registry::GlobalProxy,
+};
+
+/// A lock key reported by the KDE keystate protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LockKey {
+    CapsLock,
+    NumLock,
+    ScrollLock,
+}
+
+/// The state of a lock key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LockState {
+    Unlocked,
+    Latched,
+    Locked,
+}
+
+/// State of the `org_kde_kwin_keystate` global.
+///
+/// This is a KDE specific protocol, which lets clients track the lock keys without keyboard focus.
+#[derive(Debug)]
+pub struct KeystateState {
+    keystate: GlobalProxy<OrgKdeKwinKeystate>,
+}
+
+impl KeystateState {
+    /// Bind `org_kde_kwin_keystate` global, if it exists.
+    ///
+    /// The current state of all lock keys is requested right away, and delivered to
+    /// [`KeystateHandler::lock_state_changed`].
+    pub fn bind<D>(globals: &GlobalList, qh: &QueueHandle<D>) -> Self
+    where
+        D: Dispatch<OrgKdeKwinKeystate, GlobalData> + 'static,
+    {
+        // Version 5 adds modifier keys, which are already covered by `wl_keyboard`.
+        let keystate = GlobalProxy::bind(globals, qh, 1..=4, GlobalData);
+        if let Ok(keystate) = keystate.get() {
+            keystate.fetchStates();
+        }
+        Self { keystate }
+    }
+
+    /// Request the state of all lock keys again.
+    pub fn fetch_states(&self) -> Result<(), GlobalError> {
+        self.keystate.get()?.fetchStates();
+        Ok(())
+    }
+}
+
+impl ProvidesBoundGlobal<OrgKdeKwinKeystate, 4> for KeystateState {
+    fn bound_global(&self) -> Result<OrgKdeKwinKeystate, GlobalError> {
+        self.keystate.get().cloned()
+    }
+}
+
+pub trait KeystateHandler: Sized {
+    /// The state of a lock key changed, or was requested with [`KeystateState::fetch_states`].
+    fn lock_state_changed(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        key: LockKey,
+        state: LockState,
+    );
+}
+
+impl<D> Dispatch<OrgKdeKwinKeystate, GlobalData, D> for KeystateState
+where
+    D: Dispatch<OrgKdeKwinKeystate, GlobalData> + KeystateHandler,
+{
+    fn event(
+        data: &mut D,
+        _: &OrgKdeKwinKeystate,
+        event: org_kde_kwin_keystate::Event,
+        _: &GlobalData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        match event {
+            org_kde_kwin_keystate::Event::StateChanged { key, state } => {
+                let key = match key {
+                    0 => LockKey::CapsLock,
+                    1 => LockKey::NumLock,
+                    2 => LockKey::ScrollLock,
+                    _ => {
+                        log::warn!(target: "sctk", "unknown keystate key {key}");
+                        return;
+                    }
+                };
+                let state = match state {
+                    0 => LockState::Unlocked,
+                    1 => LockState::Latched,
+                    2 => LockState::Locked,
+                    _ => {
+                        log::warn!(target: "sctk", "unknown keystate state {state}");
+                        return;
+                    }
+                };
+
+                data.lock_state_changed(conn, qh, key, state);
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_keystate {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols_plasma::keystate::client::org_kde_kwin_keystate::OrgKdeKwinKeystate: $crate::globals::GlobalData
+            ] => $crate::seat::keystate::KeystateState
+        );
+    };
+}
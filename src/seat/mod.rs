@@ -16,15 +16,25 @@ use crate::reexports::protocols::wp::cursor_shape::v1::client::wp_cursor_shape_m
 use crate::{
     compositor::SurfaceDataExt,
     globals::GlobalData,
-    registry::{ProvidesRegistryState, RegistryHandler},
+    registry::{MultiGlobal, ProvidesRegistryState, RegistryHandler},
 };
 
+pub mod input_inhibit;
+pub mod input_method;
+pub mod input_timestamps;
 #[cfg(feature = "xkbcommon")]
 pub mod keyboard;
+pub mod keystate;
 pub mod pointer;
 pub mod pointer_constraints;
+pub mod pointer_gestures;
+pub mod pointer_warp;
 pub mod relative_pointer;
+pub mod shortcuts_inhibit;
+pub mod tablet;
+pub mod text_input;
 pub mod touch;
+pub mod virtual_keyboard;
 
 use pointer::cursor_shape::CursorShapeManager;
 use pointer::{PointerData, PointerDataExt, PointerHandler, ThemeSpec, ThemedPointer, Themes};
@@ -63,8 +73,7 @@ pub enum SeatError {
 
 #[derive(Debug)]
 pub struct SeatState {
-    // (name, seat)
-    seats: Vec<SeatInner>,
+    seats: MultiGlobal<wl_seat::WlSeat>,
     cursor_shape_manager_state: CursorShapeManagerState,
 }
 
@@ -80,60 +89,62 @@ impl SeatState {
         global_list: &GlobalList,
         qh: &QueueHandle<D>,
     ) -> SeatState {
-        let (seats, cursor_shape_manager) = global_list.contents().with_list(|globals| {
-            let global = globals
+        let cursor_shape_manager = global_list.contents().with_list(|globals| {
+            globals
                 .iter()
                 .find(|global| global.interface == WpCursorShapeManagerV1::interface().name)
                 .map(|global| CursorShapeManagerState::Pending {
                     registry: global_list.registry().clone(),
                     global: global.clone(),
                 })
-                .unwrap_or(CursorShapeManagerState::NotPresent);
-
-            (
-                crate::registry::bind_all(global_list.registry(), globals, qh, 1..=7, |id| {
-                    SeatData {
-                        has_keyboard: Arc::new(AtomicBool::new(false)),
-                        has_pointer: Arc::new(AtomicBool::new(false)),
-                        has_touch: Arc::new(AtomicBool::new(false)),
-                        name: Arc::new(Mutex::new(None)),
-                        id,
-                    }
-                })
-                .expect("failed to bind global"),
-                global,
-            )
+                .unwrap_or(CursorShapeManagerState::NotPresent)
         });
 
-        let mut state =
-            SeatState { seats: vec![], cursor_shape_manager_state: cursor_shape_manager };
-
-        for seat in seats {
-            let data = seat.data::<SeatData>().unwrap().clone();
+        let seats = MultiGlobal::bind_all(global_list, qh, 1..=9, |_| SeatData {
+            has_keyboard: Arc::new(AtomicBool::new(false)),
+            has_pointer: Arc::new(AtomicBool::new(false)),
+            has_touch: Arc::new(AtomicBool::new(false)),
+            name: Arc::new(Mutex::new(None)),
+        })
+        .expect("failed to bind global");
 
-            state.seats.push(SeatInner { seat: seat.clone(), data });
-        }
-        state
+        SeatState { seats, cursor_shape_manager_state: cursor_shape_manager }
     }
 
     /// Returns an iterator over all the seats.
     pub fn seats(&self) -> impl Iterator<Item = wl_seat::WlSeat> {
-        self.seats.iter().map(|inner| inner.seat.clone()).collect::<Vec<_>>().into_iter()
+        self.seats.iter().cloned().collect::<Vec<_>>().into_iter()
+    }
+
+    /// The data of a seat, if it is still advertised.
+    fn seat_data(&self, seat: &wl_seat::WlSeat) -> Option<&SeatData> {
+        self.seats.iter().find(|s| *s == seat).and_then(|seat| seat.data::<SeatData>())
+    }
+
+    /// Returns the capabilities currently supported by a seat.
+    ///
+    /// This will return [`None`] if the seat is dead.
+    pub fn capabilities(&self, seat: &wl_seat::WlSeat) -> Option<wl_seat::Capability> {
+        self.info(seat).map(|info| info.capabilities)
     }
 
     /// Returns information about a seat.
     ///
     /// This will return [`None`] if the seat is dead.
     pub fn info(&self, seat: &wl_seat::WlSeat) -> Option<SeatInfo> {
-        self.seats.iter().find(|inner| &inner.seat == seat).map(|inner| {
-            let name = inner.data.name.lock().unwrap().clone();
-
-            SeatInfo {
-                name,
-                has_keyboard: inner.data.has_keyboard.load(Ordering::SeqCst),
-                has_pointer: inner.data.has_pointer.load(Ordering::SeqCst),
-                has_touch: inner.data.has_touch.load(Ordering::SeqCst),
-            }
+        self.seat_data(seat).map(|data| {
+            let name = data.name.lock().unwrap().clone();
+
+            let has_keyboard = data.has_keyboard.load(Ordering::SeqCst);
+            let has_pointer = data.has_pointer.load(Ordering::SeqCst);
+            let has_touch = data.has_touch.load(Ordering::SeqCst);
+
+            let mut capabilities = wl_seat::Capability::empty();
+            capabilities.set(wl_seat::Capability::Keyboard, has_keyboard);
+            capabilities.set(wl_seat::Capability::Pointer, has_pointer);
+            capabilities.set(wl_seat::Capability::Touch, has_touch);
+
+            SeatInfo { name, capabilities, has_keyboard, has_pointer, has_touch }
         })
     }
 
@@ -202,10 +213,9 @@ impl SeatState {
         D: Dispatch<wl_pointer::WlPointer, U> + PointerHandler + 'static,
         U: PointerDataExt + 'static,
     {
-        let inner =
-            self.seats.iter().find(|inner| &inner.seat == seat).ok_or(SeatError::DeadObject)?;
+        let data = self.seat_data(seat).ok_or(SeatError::DeadObject)?;
 
-        if !inner.data.has_pointer.load(Ordering::SeqCst) {
+        if !data.has_pointer.load(Ordering::SeqCst) {
             return Err(SeatError::UnsupportedCapability(Capability::Pointer));
         }
 
@@ -236,10 +246,9 @@ impl SeatState {
         S: SurfaceDataExt + 'static,
         U: PointerDataExt + 'static,
     {
-        let inner =
-            self.seats.iter().find(|inner| &inner.seat == seat).ok_or(SeatError::DeadObject)?;
+        let data = self.seat_data(seat).ok_or(SeatError::DeadObject)?;
 
-        if !inner.data.has_pointer.load(Ordering::SeqCst) {
+        if !data.has_pointer.load(Ordering::SeqCst) {
             return Err(SeatError::UnsupportedCapability(Capability::Pointer));
         }
 
@@ -271,6 +280,7 @@ impl SeatState {
             shm: shm.clone(),
             surface,
             shape_device,
+            current_cursor: Mutex::new(None),
             _marker: std::marker::PhantomData,
             _surface_data: std::marker::PhantomData,
         })
@@ -307,10 +317,9 @@ impl SeatState {
         D: Dispatch<wl_touch::WlTouch, U> + TouchHandler + 'static,
         U: TouchDataExt + 'static,
     {
-        let inner =
-            self.seats.iter().find(|inner| &inner.seat == seat).ok_or(SeatError::DeadObject)?;
+        let data = self.seat_data(seat).ok_or(SeatError::DeadObject)?;
 
-        if !inner.data.has_touch.load(Ordering::SeqCst) {
+        if !data.has_touch.load(Ordering::SeqCst) {
             return Err(SeatError::UnsupportedCapability(Capability::Touch));
         }
 
@@ -351,8 +360,22 @@ pub trait SeatHandler: Sized {
 
     /// A seat has been removed.
     ///
-    /// The seat is destroyed and all capability objects created from it are invalid.
+    /// The seat is still alive during this call, so the keyboards, pointers and touch objects created from it
+    /// should be released here. The seat is destroyed afterwards.
     fn remove_seat(&mut self, conn: &Connection, qh: &QueueHandle<Self>, seat: wl_seat::WlSeat);
+
+    /// The name of the seat was received.
+    ///
+    /// The name is also available through [`SeatState::info`].
+    fn name(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        seat: wl_seat::WlSeat,
+        name: String,
+    ) {
+        let _ = (conn, qh, seat, name);
+    }
 }
 
 /// Description of a seat.
@@ -362,6 +385,9 @@ pub struct SeatInfo {
     /// The name of the seat.
     pub name: Option<String>,
 
+    /// The capabilities currently supported by the seat.
+    pub capabilities: wl_seat::Capability,
+
     /// Does the seat support a keyboard.
     pub has_keyboard: bool,
 
@@ -414,13 +440,12 @@ pub struct SeatData {
     has_pointer: Arc<AtomicBool>,
     has_touch: Arc<AtomicBool>,
     name: Arc<Mutex<Option<String>>>,
-    id: u32,
 }
 
 #[macro_export]
 macro_rules! delegate_seat {
-    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
-        $crate::reexports::client::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty:
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
             [
                 $crate::reexports::client::protocol::wl_seat::WlSeat: $crate::seat::SeatData
             ] => $crate::seat::SeatState
@@ -428,12 +453,6 @@ macro_rules! delegate_seat {
     };
 }
 
-#[derive(Debug)]
-struct SeatInner {
-    seat: wl_seat::WlSeat,
-    data: SeatData,
-}
-
 impl<D> Dispatch<wl_seat::WlSeat, SeatData, D> for SeatState
 where
     D: Dispatch<wl_seat::WlSeat, SeatData> + SeatHandler,
@@ -491,7 +510,8 @@ where
             }
 
             wl_seat::Event::Name { name } => {
-                *data.name.lock().unwrap() = Some(name);
+                *data.name.lock().unwrap() = Some(name.clone());
+                state.name(conn, qh, seat.clone(), name);
             }
 
             _ => unreachable!(),
@@ -517,20 +537,17 @@ where
                 .bind_specific(
                     qh,
                     name,
-                    1..=7,
+                    1..=9,
                     SeatData {
                         has_keyboard: Arc::new(AtomicBool::new(false)),
                         has_pointer: Arc::new(AtomicBool::new(false)),
                         has_touch: Arc::new(AtomicBool::new(false)),
                         name: Arc::new(Mutex::new(None)),
-                        id: name,
                     },
                 )
                 .expect("failed to bind global");
 
-            let data = seat.data::<SeatData>().unwrap().clone();
-
-            state.seat_state().seats.push(SeatInner { seat: seat.clone(), data });
+            state.seat_state().seats.add(name, seat.clone());
             state.new_seat(conn, qh, seat);
         }
     }
@@ -543,12 +560,13 @@ where
         interface: &str,
     ) {
         if interface == wl_seat::WlSeat::interface().name {
-            if let Some(seat) = state.seat_state().seats.iter().find(|inner| inner.data.id == name)
-            {
-                let seat = seat.seat.clone();
+            if let Some(seat) = state.seat_state().seats.get_by_name(name).cloned() {
+                state.remove_seat(conn, qh, seat.clone());
+                state.seat_state().seats.remove(name);
 
-                state.remove_seat(conn, qh, seat);
-                state.seat_state().seats.retain(|inner| inner.data.id != name);
+                if seat.version() >= 5 {
+                    seat.release();
+                }
             }
         }
     }
@@ -7,6 +7,7 @@ use crate::reexports::client::{Connection, Dispatch, Proxy, QueueHandle};
 use crate::reexports::protocols::wp::cursor_shape::v1::client::wp_cursor_shape_device_v1::Shape;
 use crate::reexports::protocols::wp::cursor_shape::v1::client::wp_cursor_shape_device_v1::WpCursorShapeDeviceV1;
 use crate::reexports::protocols::wp::cursor_shape::v1::client::wp_cursor_shape_manager_v1::WpCursorShapeManagerV1;
+use crate::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_tool_v2::ZwpTabletToolV2;
 
 #[derive(Debug)]
 pub struct CursorShapeManager {
@@ -40,6 +41,20 @@ impl CursorShapeManager {
         self.cursor_shape_manager.get_pointer(pointer, queue_handle, GlobalData)
     }
 
+    /// Creates a cursor shape device for a tablet tool.
+    ///
+    /// The shape set on this device is used while the tool is in proximity of a surface.
+    pub fn get_shape_device_for_tablet_tool<State>(
+        &self,
+        tool: &ZwpTabletToolV2,
+        queue_handle: &QueueHandle<State>,
+    ) -> WpCursorShapeDeviceV1
+    where
+        State: Dispatch<WpCursorShapeDeviceV1, GlobalData> + 'static,
+    {
+        self.cursor_shape_manager.get_tablet_tool_v2(tool, queue_handle, GlobalData)
+    }
+
     pub fn inner(&self) -> &WpCursorShapeManagerV1 {
         &self.cursor_shape_manager
     }
@@ -77,7 +92,10 @@ where
     }
 }
 
-pub(crate) fn cursor_icon_to_shape(cursor_icon: CursorIcon) -> Shape {
+/// Converts a [`CursorIcon`] to the matching cursor shape.
+///
+/// Icons without an equivalent shape are mapped to [`Shape::Default`].
+pub fn cursor_icon_to_shape(cursor_icon: CursorIcon) -> Shape {
     match cursor_icon {
         CursorIcon::Default => Shape::Default,
         CursorIcon::ContextMenu => Shape::ContextMenu,
@@ -2,6 +2,7 @@ use std::{
     collections::{hash_map::Entry, HashMap},
     env, iter, mem,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
 use wayland_backend::{client::InvalidId, smallvec::SmallVec};
@@ -14,7 +15,7 @@ use wayland_client::{
     },
     Connection, Dispatch, Proxy, QueueHandle, WEnum,
 };
-use wayland_cursor::{Cursor, CursorTheme};
+use wayland_cursor::{Cursor, CursorImageBuffer, CursorTheme};
 use wayland_protocols::wp::cursor_shape::v1::client::wp_cursor_shape_device_v1::WpCursorShapeDeviceV1;
 
 use crate::{
@@ -22,7 +23,7 @@ use crate::{
     error::GlobalError,
 };
 
-use super::SeatState;
+use super::{input_timestamps::PendingTimestamp, SeatState};
 
 #[doc(inline)]
 pub use cursor_icon::{CursorIcon, ParseError as CursorIconParseError};
@@ -46,6 +47,69 @@ pub const BTN_FORWARD: u32 = 0x115;
 pub const BTN_BACK: u32 = 0x116;
 pub const BTN_TASK: u32 = 0x117;
 
+/// A pointer button, identified from its raw code in [`PointerEventKind::Press`] and
+/// [`PointerEventKind::Release`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PointerButton {
+    /// [`BTN_LEFT`]
+    Left,
+    /// [`BTN_RIGHT`]
+    Right,
+    /// [`BTN_MIDDLE`]
+    Middle,
+    /// [`BTN_SIDE`], often used as "back".
+    Side,
+    /// [`BTN_EXTRA`], often used as "forward".
+    Extra,
+    /// [`BTN_FORWARD`]
+    Forward,
+    /// [`BTN_BACK`]
+    Back,
+    /// [`BTN_TASK`]
+    Task,
+    /// Any other button, with its raw code.
+    Other(u32),
+}
+
+impl PointerButton {
+    /// The raw code of the button, as sent by the compositor.
+    pub fn into_raw(self) -> u32 {
+        match self {
+            PointerButton::Left => BTN_LEFT,
+            PointerButton::Right => BTN_RIGHT,
+            PointerButton::Middle => BTN_MIDDLE,
+            PointerButton::Side => BTN_SIDE,
+            PointerButton::Extra => BTN_EXTRA,
+            PointerButton::Forward => BTN_FORWARD,
+            PointerButton::Back => BTN_BACK,
+            PointerButton::Task => BTN_TASK,
+            PointerButton::Other(raw) => raw,
+        }
+    }
+}
+
+impl From<u32> for PointerButton {
+    fn from(raw: u32) -> Self {
+        match raw {
+            BTN_LEFT => PointerButton::Left,
+            BTN_RIGHT => PointerButton::Right,
+            BTN_MIDDLE => PointerButton::Middle,
+            BTN_SIDE => PointerButton::Side,
+            BTN_EXTRA => PointerButton::Extra,
+            BTN_FORWARD => PointerButton::Forward,
+            BTN_BACK => PointerButton::Back,
+            BTN_TASK => PointerButton::Task,
+            raw => PointerButton::Other(raw),
+        }
+    }
+}
+
+impl From<PointerButton> for u32 {
+    fn from(button: PointerButton) -> Self {
+        button.into_raw()
+    }
+}
+
 /// Describes a scroll along one axis
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
 pub struct AxisScroll {
@@ -58,10 +122,22 @@ pub struct AxisScroll {
     /// source.
     pub discrete: i32,
 
+    /// The high-resolution scroll measured in fractions of a step, where 120 is a full step.
+    ///
+    /// This is synthesized from [`discrete`](Self::discrete) when the compositor does not support
+    /// `wl_pointer` version 8. Like `discrete`, it is zero for continuous sources.
+    pub value120: i32,
+
     /// The scroll was stopped.
     ///
     /// Generally this is encountered when hardware indicates the end of some continuous scrolling.
     pub stop: bool,
+
+    /// The direction of the scroll relative to the physical motion, such as the movement of the fingers.
+    ///
+    /// An inverted direction means natural scrolling is enabled. This is only reported by compositors
+    /// supporting `wl_pointer` version 9.
+    pub relative_direction: Option<wl_pointer::AxisRelativeDirection>,
 }
 
 impl AxisScroll {
@@ -73,7 +149,9 @@ impl AxisScroll {
     fn merge(&mut self, other: &Self) {
         self.absolute += other.absolute;
         self.discrete += other.discrete;
+        self.value120 += other.value120;
         self.stop |= other.stop;
+        self.relative_direction = self.relative_direction.or(other.relative_direction);
     }
 }
 
@@ -83,13 +161,25 @@ pub struct PointerEvent {
     pub surface: WlSurface,
     pub position: (f64, f64),
     pub kind: PointerEventKind,
+    /// High-resolution timestamp of the event.
+    ///
+    /// This is only set for motion, button and axis events of pointers subscribed with
+    /// [`InputTimestampsState::get_pointer_timestamps`](crate::seat::input_timestamps::InputTimestampsState::get_pointer_timestamps).
+    pub timestamp: Option<Duration>,
 }
 
 #[derive(Debug, Clone)]
 pub enum PointerEventKind {
+    /// The pointer entered the surface.
+    ///
+    /// The serial is also available afterwards through [`PointerData::latest_enter_serial`].
     Enter {
         serial: u32,
     },
+    /// The pointer left the surface.
+    ///
+    /// When the pointer moves from one surface to another, the leave and enter events are part of the
+    /// same frame and delivered in order.
     Leave {
         serial: u32,
     },
@@ -114,6 +204,19 @@ pub enum PointerEventKind {
     },
 }
 
+impl PointerEventKind {
+    /// The button of a [`Press`](PointerEventKind::Press) or [`Release`](PointerEventKind::Release)
+    /// event.
+    pub fn button(&self) -> Option<PointerButton> {
+        match self {
+            PointerEventKind::Press { button, .. } | PointerEventKind::Release { button, .. } => {
+                Some(PointerButton::from(*button))
+            }
+            _ => None,
+        }
+    }
+}
+
 pub trait PointerHandler: Sized {
     /// One or more pointer events are available.
     ///
@@ -134,11 +237,12 @@ pub trait PointerHandler: Sized {
 pub struct PointerData {
     seat: WlSeat,
     pub(crate) inner: Mutex<PointerDataInner>,
+    pub(crate) timestamp: PendingTimestamp,
 }
 
 impl PointerData {
     pub fn new(seat: WlSeat) -> Self {
-        Self { seat, inner: Default::default() }
+        Self { seat, inner: Default::default(), timestamp: Default::default() }
     }
 
     /// The seat associated with this pointer.
@@ -147,10 +251,17 @@ impl PointerData {
     }
 
     /// Serial from the latest [`PointerEventKind::Enter`] event.
+    ///
+    /// The serial is kept after the pointer leaves the surface.
     pub fn latest_enter_serial(&self) -> Option<u32> {
         self.inner.lock().unwrap().latest_enter
     }
 
+    /// The surface the pointer is currently over, if any.
+    pub fn focused_surface(&self) -> Option<WlSurface> {
+        self.inner.lock().unwrap().surface.clone()
+    }
+
     /// Serial from the latest button [`PointerEventKind::Press`] and
     /// [`PointerEventKind::Release`] events.
     pub fn latest_button_serial(&self) -> Option<u32> {
@@ -170,27 +281,27 @@ impl PointerDataExt for PointerData {
 
 #[macro_export]
 macro_rules! delegate_pointer {
-    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
-        $crate::delegate_pointer!(@{ $(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty }; pointer: []);
-        $crate::delegate_pointer!(@{ $(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty }; pointer-only: $crate::seat::pointer::PointerData);
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::delegate_pointer!(@{ $(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })? }; pointer: []);
+        $crate::delegate_pointer!(@{ $(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })? }; pointer-only: $crate::seat::pointer::PointerData);
     };
-    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty, pointer: [$($pointer_data:ty),* $(,)?]) => {
-        $crate::delegate_pointer!(@{ $(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty }; pointer: [ $($pointer_data),* ]);
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty, pointer: [$($pointer_data:ty),* $(,)?] $(where $($bound:tt)+)?) => {
+        $crate::delegate_pointer!(@{ $(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })? }; pointer: [ $($pointer_data),* ]);
     };
     (@{$($ty:tt)*}; pointer: []) => {
-        $crate::reexports::client::delegate_dispatch!($($ty)*:
+        $crate::__delegate_dispatch!($($ty)*:
             [
                 $crate::reexports::protocols::wp::cursor_shape::v1::client::wp_cursor_shape_manager_v1::WpCursorShapeManagerV1: $crate::globals::GlobalData
             ] => $crate::seat::pointer::cursor_shape::CursorShapeManager
         );
-        $crate::reexports::client::delegate_dispatch!($($ty)*:
+        $crate::__delegate_dispatch!($($ty)*:
             [
                 $crate::reexports::protocols::wp::cursor_shape::v1::client::wp_cursor_shape_device_v1::WpCursorShapeDeviceV1: $crate::globals::GlobalData
             ] => $crate::seat::pointer::cursor_shape::CursorShapeManager
         );
     };
     (@{$($ty:tt)*}; pointer-only: $pointer_data:ty) => {
-        $crate::reexports::client::delegate_dispatch!($($ty)*:
+        $crate::__delegate_dispatch!($($ty)*:
             [
                 $crate::reexports::client::protocol::wl_pointer::WlPointer: $pointer_data
             ] => $crate::seat::SeatState
@@ -217,6 +328,9 @@ pub(crate) struct PointerDataInner {
 
     /// The serial of the latest enter event for the pointer
     pub(crate) latest_btn: Option<u32>,
+
+    /// High-resolution scroll not yet reported as a discrete step, per axis (horizontal, vertical).
+    pub(crate) value120_remainder: (i32, i32),
 }
 
 impl<D, U> Dispatch<WlPointer, U, D> for SeatState
@@ -335,10 +449,12 @@ where
                     match axis {
                         wl_pointer::Axis::VerticalScroll => {
                             vertical.discrete = discrete;
+                            vertical.value120 = discrete * 120;
                         }
 
                         wl_pointer::Axis::HorizontalScroll => {
                             horizontal.discrete = discrete;
+                            horizontal.value120 = discrete * 120;
                         }
 
                         _ => unreachable!(),
@@ -353,6 +469,71 @@ where
                 }
             },
 
+            // Replaces AxisDiscrete since version 8.
+            wl_pointer::Event::AxisValue120 { axis, value120 } => match axis {
+                WEnum::Value(axis) => {
+                    let (mut horizontal, mut vertical) = <(AxisScroll, AxisScroll)>::default();
+                    let (scroll, remainder) = match axis {
+                        wl_pointer::Axis::VerticalScroll => {
+                            (&mut vertical, &mut guard.value120_remainder.1)
+                        }
+                        wl_pointer::Axis::HorizontalScroll => {
+                            (&mut horizontal, &mut guard.value120_remainder.0)
+                        }
+                        _ => unreachable!(),
+                    };
+
+                    // Report a discrete step every time a full step is accumulated, restarting when
+                    // the scroll direction changes.
+                    if remainder.signum() == -value120.signum() {
+                        *remainder = 0;
+                    }
+                    *remainder += value120;
+                    scroll.value120 = value120;
+                    scroll.discrete = *remainder / 120;
+                    *remainder %= 120;
+
+                    PointerEventKind::Axis { time: 0, horizontal, vertical, source: None }
+                }
+
+                WEnum::Unknown(unknown) => {
+                    log::warn!(target: "sctk", "{}: invalid pointer axis: {:x}", pointer.id(), unknown);
+                    return;
+                }
+            },
+
+            wl_pointer::Event::AxisRelativeDirection { axis, direction } => {
+                let direction = match direction {
+                    WEnum::Value(direction) => direction,
+                    WEnum::Unknown(unknown) => {
+                        log::warn!(target: "sctk", "{}: invalid axis relative direction: {:x}", pointer.id(), unknown);
+                        return;
+                    }
+                };
+
+                match axis {
+                    WEnum::Value(axis) => {
+                        let (mut horizontal, mut vertical) = <(AxisScroll, AxisScroll)>::default();
+                        match axis {
+                            wl_pointer::Axis::VerticalScroll => {
+                                vertical.relative_direction = Some(direction);
+                            }
+                            wl_pointer::Axis::HorizontalScroll => {
+                                horizontal.relative_direction = Some(direction);
+                            }
+                            _ => unreachable!(),
+                        };
+
+                        PointerEventKind::Axis { time: 0, horizontal, vertical, source: None }
+                    }
+
+                    WEnum::Unknown(unknown) => {
+                        log::warn!(target: "sctk", "{}: invalid pointer axis: {:x}", pointer.id(), unknown);
+                        return;
+                    }
+                }
+            }
+
             wl_pointer::Event::Frame => {
                 let pending = mem::take(&mut guard.pending);
                 drop(guard);
@@ -374,27 +555,39 @@ where
             }
         };
 
-        let event = PointerEvent { surface, position: guard.position, kind };
+        let event = PointerEvent {
+            surface,
+            position: guard.position,
+            kind,
+            timestamp: udata.timestamp.take(),
+        };
 
         if pointer.version() < 5 {
             drop(guard);
             // No Frame events, send right away
             data.pointer_frame(conn, qh, pointer, &[event]);
         } else {
-            // Merge a new Axis event with the previous event to create an event with more
-            // information and potentially diagonal scrolling.
+            // Merge a new Axis event with the previous Axis event of the frame to create a single event
+            // with more information and potentially diagonal scrolling. Values along the same axis are
+            // summed, as a frame may contain several axis events for one axis.
+            let previous_axis = guard.pending.iter_mut().rev().find(|pending| {
+                matches!(pending.kind, PointerEventKind::Axis { .. })
+                    && pending.surface == event.surface
+            });
             if let (
                 Some(PointerEvent {
                     kind:
                         PointerEventKind::Axis { time: ot, horizontal: oh, vertical: ov, source: os },
+                    timestamp: ots,
                     ..
                 }),
                 PointerEvent {
                     kind:
                         PointerEventKind::Axis { time: nt, horizontal: nh, vertical: nv, source: ns },
+                    timestamp: nts,
                     ..
                 },
-            ) = (guard.pending.last_mut(), &event)
+            ) = (previous_axis, &event)
             {
                 // A time of 0 is "don't know", so avoid using it if possible.
                 if *ot == 0 {
@@ -403,6 +596,7 @@ where
                 oh.merge(nh);
                 ov.merge(nv);
                 *os = os.or(*ns);
+                *ots = ots.or(*nts);
                 return;
             }
 
@@ -421,6 +615,8 @@ pub struct ThemedPointer<U = PointerData, S = SurfaceData> {
     /// The surface owned by the cursor to present the icon.
     pub(super) surface: WlSurface,
     pub(super) shape_device: Option<WpCursorShapeDeviceV1>,
+    /// The cursor loaded from the theme currently shown, if any.
+    pub(super) current_cursor: Mutex<Option<ThemeCursor>>,
     pub(super) _marker: std::marker::PhantomData<U>,
     pub(super) _surface_data: std::marker::PhantomData<S>,
 }
@@ -429,6 +625,9 @@ impl<U: PointerDataExt + 'static, S: SurfaceDataExt + 'static> ThemedPointer<U,
     /// Set the cursor to the given [`CursorIcon`].
     ///
     /// The cursor icon should be reloaded on every [`PointerEventKind::Enter`] event.
+    ///
+    /// When the cursor is loaded from the theme and is animated, only its first frame is shown until
+    /// [`ThemedPointer::advance`] is called.
     pub fn set_cursor(&self, conn: &Connection, icon: CursorIcon) -> Result<(), PointerThemeError> {
         let serial = match self
             .pointer
@@ -439,6 +638,9 @@ impl<U: PointerDataExt + 'static, S: SurfaceDataExt + 'static> ThemedPointer<U,
             None => return Err(PointerThemeError::MissingEnterSerial),
         };
 
+        // Forget the previous cursor, stopping its animation.
+        *self.current_cursor.lock().unwrap() = None;
+
         if let Some(shape_device) = self.shape_device.as_ref() {
             shape_device.set_shape(serial, cursor_icon_to_shape(icon));
             Ok(())
@@ -463,30 +665,15 @@ impl<U: PointerDataExt + 'static, S: SurfaceDataExt + 'static> ThemedPointer<U,
                 .get_cursor(conn, cursor_icon_name, scale as u32, &self.shm)
                 .map_err(PointerThemeError::InvalidId)?
             {
-                let image = &cursor[0];
-                let (w, h) = image.dimensions();
-                let (hx, hy) = image.hotspot();
-
-                self.surface.set_buffer_scale(scale);
-                self.surface.attach(Some(image), 0, 0);
-
-                if self.surface.version() >= 4 {
-                    self.surface.damage_buffer(0, 0, w as i32, h as i32);
-                } else {
-                    // Fallback for the old old surface.
-                    self.surface.damage(0, 0, w as i32 / scale, h as i32 / scale);
-                }
+                self.attach_image(&cursor[0], scale, serial);
 
-                // Commit the surface to place the cursor image in the compositor's memory.
-                self.surface.commit();
-
-                // Set the pointer surface to change the pointer.
-                self.pointer.set_cursor(
+                *self.current_cursor.lock().unwrap() = Some(ThemeCursor {
+                    name: cursor_icon_name.to_string(),
                     serial,
-                    Some(&self.surface),
-                    hx as i32 / scale,
-                    hy as i32 / scale,
-                );
+                    scale,
+                    elapsed: Duration::ZERO,
+                    frame: 0,
+                });
 
                 return Ok(());
             }
@@ -495,12 +682,118 @@ impl<U: PointerDataExt + 'static, S: SurfaceDataExt + 'static> ThemedPointer<U,
         Err(PointerThemeError::CursorNotFound)
     }
 
+    /// Attach a cursor image to the cursor surface and set it as the cursor.
+    fn attach_image(&self, image: &CursorImageBuffer, scale: i32, serial: u32) {
+        let (w, h) = image.dimensions();
+        let (hx, hy) = image.hotspot();
+
+        self.surface.set_buffer_scale(scale);
+        self.surface.attach(Some(image), 0, 0);
+
+        if self.surface.version() >= 4 {
+            self.surface.damage_buffer(0, 0, w as i32, h as i32);
+        } else {
+            // Fallback for the old old surface.
+            self.surface.damage(0, 0, w as i32 / scale, h as i32 / scale);
+        }
+
+        // Commit the surface to place the cursor image in the compositor's memory.
+        self.surface.commit();
+
+        // Set the pointer surface to change the pointer.
+        self.pointer.set_cursor(serial, Some(&self.surface), hx as i32 / scale, hy as i32 / scale);
+    }
+
+    /// Advance the animation of the current cursor by `elapsed`.
+    ///
+    /// The frame shown is updated when needed, and reloaded if the scale factor of the cursor surface
+    /// changed. This returns the time until the next frame, or [`None`] if the current cursor is not
+    /// animated, in which case there is no need to call this until the cursor is set again.
+    pub fn advance(
+        &self,
+        conn: &Connection,
+        elapsed: Duration,
+    ) -> Result<Option<Duration>, PointerThemeError> {
+        let mut current_cursor = self.current_cursor.lock().unwrap();
+        let current = match current_cursor.as_mut() {
+            Some(current) => current,
+            None => return Ok(None),
+        };
+
+        let scale = self.surface.data::<S>().unwrap().surface_data().scale_factor();
+        let mut themes = self.themes.lock().unwrap();
+        let cursor = themes
+            .get_cursor(conn, &current.name, scale as u32, &self.shm)
+            .map_err(PointerThemeError::InvalidId)?
+            .ok_or(PointerThemeError::CursorNotFound)?;
+
+        let total = (0..cursor.image_count()).map(|i| cursor[i].delay() as u128).sum::<u128>();
+        if cursor.image_count() < 2 || total == 0 {
+            if scale != current.scale {
+                self.attach_image(&cursor[0], scale, current.serial);
+                current.scale = scale;
+            }
+            return Ok(None);
+        }
+
+        // Wrap the time around the length of the animation.
+        let mut millis = (current.elapsed + elapsed).as_millis() % total;
+        current.elapsed = Duration::from_millis(millis as u64);
+
+        let mut frame = 0;
+        while millis >= cursor[frame].delay() as u128 {
+            millis -= cursor[frame].delay() as u128;
+            frame += 1;
+        }
+        let remaining = cursor[frame].delay() as u64 - millis as u64;
+
+        if frame != current.frame || scale != current.scale {
+            self.attach_image(&cursor[frame], scale, current.serial);
+            current.frame = frame;
+            current.scale = scale;
+        }
+
+        Ok(Some(Duration::from_millis(remaining)))
+    }
+
+    /// Reload the cursor loaded from the theme if the scale factor of the cursor surface changed.
+    ///
+    /// This should be called from [`CompositorHandler::scale_factor_changed`] for the
+    /// [surface](ThemedPointer::surface) of the pointer, so the cursor is shown at the size of the new
+    /// output. The cursor is set again with the serial used to set it, so no new enter event is needed.
+    ///
+    /// [`CompositorHandler::scale_factor_changed`]: crate::compositor::CompositorHandler::scale_factor_changed
+    pub fn reload(&self, conn: &Connection) -> Result<(), PointerThemeError> {
+        let mut current_cursor = self.current_cursor.lock().unwrap();
+        let current = match current_cursor.as_mut() {
+            Some(current) => current,
+            None => return Ok(()),
+        };
+
+        let scale = self.surface.data::<S>().unwrap().surface_data().scale_factor();
+        if scale == current.scale {
+            return Ok(());
+        }
+
+        let mut themes = self.themes.lock().unwrap();
+        let cursor = themes
+            .get_cursor(conn, &current.name, scale as u32, &self.shm)
+            .map_err(PointerThemeError::InvalidId)?
+            .ok_or(PointerThemeError::CursorNotFound)?;
+
+        let frame = current.frame.min(cursor.image_count() - 1);
+        self.attach_image(&cursor[frame], scale, current.serial);
+        current.scale = scale;
+        Ok(())
+    }
+
     /// Hide the cursor by providing empty surface for it.
     ///
     /// The cursor should be hidden on every [`PointerEventKind::Enter`] event.
     pub fn hide_cursor(&self) -> Result<(), PointerThemeError> {
         let data = self.pointer.data::<U>();
         if let Some(serial) = data.and_then(|data| data.pointer_data().latest_enter_serial()) {
+            *self.current_cursor.lock().unwrap() = None;
             self.pointer.set_cursor(serial, None, 0, 0);
             Ok(())
         } else {
@@ -581,6 +874,19 @@ pub enum PointerThemeError {
     MissingEnterSerial,
 }
 
+/// State of the cursor of a [`ThemedPointer`] loaded from the theme.
+#[derive(Debug)]
+pub(super) struct ThemeCursor {
+    /// Name of the cursor in the theme.
+    name: String,
+    serial: u32,
+    /// Scale the current frame was loaded at.
+    scale: i32,
+    /// Time into the animation.
+    elapsed: Duration,
+    frame: usize,
+}
+
 #[derive(Debug)]
 pub(crate) struct Themes {
     name: String,
@@ -634,3 +940,269 @@ impl Themes {
         Ok(theme.get_cursor(name))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use wayland_backend::{protocol::Argument, server::ObjectId};
+    use wayland_client::{
+        protocol::{wl_compositor::WlCompositor, wl_pointer, wl_seat},
+        Proxy,
+    };
+
+    use super::{AxisScroll, PointerEvent, PointerEventKind};
+    use crate::testing::{Call, TestClient, TestServer};
+
+    /// A pointer which entered a surface.
+    struct Pointer {
+        server: TestServer,
+        client: TestClient,
+        pointer: ObjectId,
+    }
+
+    impl Pointer {
+        fn new(version: u32) -> Self {
+            let mut server = TestServer::new();
+            server.create_global(WlCompositor::interface(), 6);
+            server.create_global(wl_seat::WlSeat::interface(), version);
+            let mut client = server.connect();
+            let seat = client.seat(&mut server, wl_seat::Capability::Pointer);
+            let pointer = client.state.seat_state.get_pointer(&client.qh, &seat).unwrap();
+            let surface = client.create_surface();
+
+            let pointer = server.object(&pointer);
+            let surface = server.object(&surface);
+            let mut this = Pointer { server, client, pointer };
+            this.send(
+                "enter",
+                vec![Argument::Uint(1), Argument::Object(surface), fixed(0.), fixed(0.)],
+            );
+            if version >= 5 {
+                this.frame();
+            }
+            this.frames();
+            this
+        }
+
+        fn send(&mut self, event: &str, args: Vec<Argument<ObjectId, i32>>) {
+            self.server.send(&self.pointer, event, args);
+        }
+
+        fn axis(&mut self, axis: wl_pointer::Axis, value: f64) {
+            self.send("axis", vec![Argument::Uint(0), Argument::Uint(axis.into()), fixed(value)]);
+        }
+
+        fn axis_value120(&mut self, axis: wl_pointer::Axis, value120: i32) {
+            self.send("axis_value120", vec![Argument::Uint(axis.into()), Argument::Int(value120)]);
+        }
+
+        fn axis_discrete(&mut self, axis: wl_pointer::Axis, discrete: i32) {
+            self.send("axis_discrete", vec![Argument::Uint(axis.into()), Argument::Int(discrete)]);
+        }
+
+        fn axis_stop(&mut self, axis: wl_pointer::Axis) {
+            self.send("axis_stop", vec![Argument::Uint(0), Argument::Uint(axis.into())]);
+        }
+
+        fn motion(&mut self, x: f64, y: f64) {
+            self.send("motion", vec![Argument::Uint(0), fixed(x), fixed(y)]);
+        }
+
+        fn relative_direction(
+            &mut self,
+            axis: wl_pointer::Axis,
+            direction: wl_pointer::AxisRelativeDirection,
+        ) {
+            let args = vec![Argument::Uint(axis.into()), Argument::Uint(direction.into())];
+            self.send("axis_relative_direction", args);
+        }
+
+        fn frame(&mut self) {
+            self.send("frame", Vec::new());
+        }
+
+        /// The frames delivered to the handler since this was last called.
+        fn frames(&mut self) -> Vec<Vec<PointerEvent>> {
+            self.client.roundtrip();
+            self.client
+                .state
+                .take_calls()
+                .into_iter()
+                .filter_map(|call| match call {
+                    Call::PointerFrame(events) => Some(events),
+                    _ => None,
+                })
+                .collect()
+        }
+
+        /// The axes of the single axis event delivered since this was last called.
+        fn axis_event(&mut self) -> (AxisScroll, AxisScroll) {
+            let frames = self.frames();
+            match &frames[..] {
+                [events] => match &events[..] {
+                    [PointerEvent {
+                        kind: PointerEventKind::Axis { horizontal, vertical, .. },
+                        ..
+                    }] => (*horizontal, *vertical),
+                    _ => panic!("expected a single axis event, got {:?}", events),
+                },
+                _ => panic!("expected a single frame, got {:?}", frames),
+            }
+        }
+    }
+
+    fn fixed(value: f64) -> Argument<ObjectId, i32> {
+        Argument::Fixed((value * 256.) as i32)
+    }
+
+    #[test]
+    fn relative_direction() {
+        let mut pointer = Pointer::new(9);
+
+        pointer.relative_direction(
+            wl_pointer::Axis::VerticalScroll,
+            wl_pointer::AxisRelativeDirection::Inverted,
+        );
+        pointer.axis(wl_pointer::Axis::VerticalScroll, 10.);
+        pointer.frame();
+        let (horizontal, vertical) = pointer.axis_event();
+        assert_eq!(vertical.relative_direction, Some(wl_pointer::AxisRelativeDirection::Inverted));
+        assert_eq!(vertical.absolute, 10.);
+        assert!(horizontal.is_none());
+    }
+
+    #[test]
+    fn relative_direction_per_axis() {
+        let mut pointer = Pointer::new(9);
+
+        pointer.relative_direction(
+            wl_pointer::Axis::VerticalScroll,
+            wl_pointer::AxisRelativeDirection::Identical,
+        );
+        pointer.axis(wl_pointer::Axis::VerticalScroll, 10.);
+        pointer.relative_direction(
+            wl_pointer::Axis::HorizontalScroll,
+            wl_pointer::AxisRelativeDirection::Inverted,
+        );
+        pointer.axis(wl_pointer::Axis::HorizontalScroll, -5.);
+        pointer.frame();
+        let (horizontal, vertical) = pointer.axis_event();
+        assert_eq!(vertical.relative_direction, Some(wl_pointer::AxisRelativeDirection::Identical));
+        assert_eq!(
+            horizontal.relative_direction,
+            Some(wl_pointer::AxisRelativeDirection::Inverted)
+        );
+        assert_eq!((horizontal.absolute, vertical.absolute), (-5., 10.));
+    }
+
+    #[test]
+    fn relative_direction_not_kept_across_frames() {
+        let mut pointer = Pointer::new(9);
+
+        pointer.relative_direction(
+            wl_pointer::Axis::VerticalScroll,
+            wl_pointer::AxisRelativeDirection::Inverted,
+        );
+        pointer.axis(wl_pointer::Axis::VerticalScroll, 10.);
+        pointer.frame();
+        pointer.axis_event();
+
+        pointer.axis(wl_pointer::Axis::VerticalScroll, 10.);
+        pointer.frame();
+        let (_, vertical) = pointer.axis_event();
+        assert_eq!(vertical.relative_direction, None);
+    }
+
+    #[test]
+    fn relative_direction_unsupported() {
+        let mut pointer = Pointer::new(8);
+
+        pointer.axis(wl_pointer::Axis::VerticalScroll, 10.);
+        pointer.frame();
+        let (horizontal, vertical) = pointer.axis_event();
+        assert_eq!(vertical.relative_direction, None);
+        assert_eq!(horizontal.relative_direction, None);
+    }
+
+    #[test]
+    fn merge_axes() {
+        let mut pointer = Pointer::new(8);
+
+        pointer.axis(wl_pointer::Axis::VerticalScroll, 10.);
+        pointer.axis_value120(wl_pointer::Axis::VerticalScroll, 120);
+        pointer.axis(wl_pointer::Axis::HorizontalScroll, -4.);
+        pointer.axis_value120(wl_pointer::Axis::HorizontalScroll, -60);
+        pointer.frame();
+        let (horizontal, vertical) = pointer.axis_event();
+        assert_eq!((vertical.absolute, vertical.value120, vertical.discrete), (10., 120, 1));
+        assert_eq!((horizontal.absolute, horizontal.value120, horizontal.discrete), (-4., -60, 0));
+    }
+
+    #[test]
+    fn merge_duplicate_axis() {
+        let mut pointer = Pointer::new(8);
+
+        pointer.axis_value120(wl_pointer::Axis::VerticalScroll, 60);
+        pointer.axis(wl_pointer::Axis::VerticalScroll, 5.);
+        pointer.axis_value120(wl_pointer::Axis::VerticalScroll, 60);
+        pointer.axis(wl_pointer::Axis::VerticalScroll, 5.5);
+        pointer.axis_stop(wl_pointer::Axis::VerticalScroll);
+        pointer.frame();
+        let (horizontal, vertical) = pointer.axis_event();
+        assert_eq!((vertical.absolute, vertical.value120, vertical.discrete), (10.5, 120, 1));
+        assert!(vertical.stop);
+        assert!(horizontal.is_none());
+    }
+
+    #[test]
+    fn merge_discrete() {
+        let mut pointer = Pointer::new(7);
+
+        pointer.axis_discrete(wl_pointer::Axis::VerticalScroll, 1);
+        pointer.axis(wl_pointer::Axis::VerticalScroll, 10.);
+        pointer.axis_discrete(wl_pointer::Axis::VerticalScroll, 2);
+        pointer.axis(wl_pointer::Axis::VerticalScroll, 20.);
+        pointer.frame();
+        let (_, vertical) = pointer.axis_event();
+        assert_eq!((vertical.absolute, vertical.value120, vertical.discrete), (30., 360, 3));
+    }
+
+    #[test]
+    fn merge_axes_around_other_events() {
+        let mut pointer = Pointer::new(8);
+
+        pointer.axis(wl_pointer::Axis::VerticalScroll, 10.);
+        pointer.motion(4., 2.);
+        pointer.axis(wl_pointer::Axis::VerticalScroll, 10.);
+        pointer.axis(wl_pointer::Axis::HorizontalScroll, 3.);
+        pointer.frame();
+        let frames = pointer.frames();
+        assert_eq!(frames.len(), 1);
+        match &frames[0][..] {
+            [PointerEvent { kind: PointerEventKind::Axis { horizontal, vertical, .. }, .. }, PointerEvent { kind: PointerEventKind::Motion { .. }, position, .. }] =>
+            {
+                assert_eq!((horizontal.absolute, vertical.absolute), (3., 20.));
+                assert_eq!(*position, (4., 2.));
+            }
+            events => panic!("expected an axis and a motion event, got {:?}", events),
+        }
+    }
+
+    #[test]
+    fn axes_without_frames() {
+        let mut pointer = Pointer::new(4);
+
+        pointer.axis(wl_pointer::Axis::VerticalScroll, 10.);
+        pointer.axis(wl_pointer::Axis::VerticalScroll, 5.);
+        let frames = pointer.frames();
+        let vertical: Vec<_> = frames
+            .iter()
+            .map(|events| match &events[..] {
+                [PointerEvent { kind: PointerEventKind::Axis { vertical, .. }, .. }] => {
+                    vertical.absolute
+                }
+                _ => panic!("expected a single axis event, got {:?}", events),
+            })
+            .collect();
+        assert_eq!(vertical, [10., 5.]);
+    }
+}
@@ -0,0 +1,490 @@
+use wayland_client::{
+    globals::GlobalList,
+    protocol::{wl_pointer, wl_surface},
+    Connection, Dispatch, QueueHandle,
+};
+use wayland_protocols::wp::pointer_gestures::zv1::client::{
+    zwp_pointer_gesture_hold_v1, zwp_pointer_gesture_pinch_v1, zwp_pointer_gesture_swipe_v1,
+    zwp_pointer_gestures_v1,
+};
+
+use crate::{error::GlobalError, globals::GlobalData, registry::GlobalProxy};
+
+/// A kind of pointer gesture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PointerGestureKind {
+    Swipe,
+    Pinch,
+    /// Requires version 3 of `zwp_pointer_gestures_v1`.
+    Hold,
+}
+
+impl PointerGestureKind {
+    /// The version of `zwp_pointer_gestures_v1` introducing this gesture.
+    pub fn since(self) -> u32 {
+        match self {
+            PointerGestureKind::Swipe | PointerGestureKind::Pinch => 1,
+            PointerGestureKind::Hold => 3,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PointerGesturesState {
+    pointer_gestures: GlobalProxy<zwp_pointer_gestures_v1::ZwpPointerGesturesV1>,
+}
+
+impl PointerGesturesState {
+    /// Bind `zwp_pointer_gestures_v1` global, if it exists
+    pub fn bind<D>(globals: &GlobalList, qh: &QueueHandle<D>) -> Self
+    where
+        D: Dispatch<zwp_pointer_gestures_v1::ZwpPointerGesturesV1, GlobalData> + 'static,
+    {
+        let pointer_gestures = GlobalProxy::bind(globals, qh, 1..=3, GlobalData);
+        Self { pointer_gestures }
+    }
+
+    /// Whether swipe and pinch gestures are available.
+    pub fn is_available(&self) -> bool {
+        self.pointer_gestures.get().is_ok()
+    }
+
+    /// Whether hold gestures are available.
+    ///
+    /// Hold gestures require version 3 of `zwp_pointer_gestures_v1`.
+    pub fn supports_hold(&self) -> bool {
+        self.supports(PointerGestureKind::Hold)
+    }
+
+    /// Whether the bound global supports the given kind of gesture.
+    pub fn supports(&self, kind: PointerGestureKind) -> bool {
+        self.pointer_gestures.with_min_version(kind.since()).is_ok()
+    }
+
+    /// The kinds of gestures supported by the bound global.
+    ///
+    /// This is empty if the compositor does not support pointer gestures.
+    pub fn supported_gestures(&self) -> Vec<PointerGestureKind> {
+        [PointerGestureKind::Swipe, PointerGestureKind::Pinch, PointerGestureKind::Hold]
+            .into_iter()
+            .filter(|kind| self.supports(*kind))
+            .collect()
+    }
+
+    /// Create a swipe gesture object for the pointer.
+    pub fn get_swipe_gesture<D>(
+        &self,
+        pointer: &wl_pointer::WlPointer,
+        qh: &QueueHandle<D>,
+    ) -> Result<zwp_pointer_gesture_swipe_v1::ZwpPointerGestureSwipeV1, GlobalError>
+    where
+        D: Dispatch<zwp_pointer_gesture_swipe_v1::ZwpPointerGestureSwipeV1, PointerGestureData>
+            + 'static,
+    {
+        let udata = PointerGestureData { wl_pointer: pointer.clone() };
+        Ok(self.pointer_gestures.get()?.get_swipe_gesture(pointer, qh, udata))
+    }
+
+    /// Create a pinch gesture object for the pointer.
+    pub fn get_pinch_gesture<D>(
+        &self,
+        pointer: &wl_pointer::WlPointer,
+        qh: &QueueHandle<D>,
+    ) -> Result<zwp_pointer_gesture_pinch_v1::ZwpPointerGesturePinchV1, GlobalError>
+    where
+        D: Dispatch<zwp_pointer_gesture_pinch_v1::ZwpPointerGesturePinchV1, PointerGestureData>
+            + 'static,
+    {
+        let udata = PointerGestureData { wl_pointer: pointer.clone() };
+        Ok(self.pointer_gestures.get()?.get_pinch_gesture(pointer, qh, udata))
+    }
+
+    /// Create a hold gesture object for the pointer.
+    ///
+    /// This returns [`GlobalError::InvalidVersion`] if the compositor does not support version 3 of
+    /// `zwp_pointer_gestures_v1`.
+    pub fn get_hold_gesture<D>(
+        &self,
+        pointer: &wl_pointer::WlPointer,
+        qh: &QueueHandle<D>,
+    ) -> Result<zwp_pointer_gesture_hold_v1::ZwpPointerGestureHoldV1, GlobalError>
+    where
+        D: Dispatch<zwp_pointer_gesture_hold_v1::ZwpPointerGestureHoldV1, PointerGestureData>
+            + 'static,
+    {
+        let udata = PointerGestureData { wl_pointer: pointer.clone() };
+        Ok(self
+            .pointer_gestures
+            .with_min_version(PointerGestureKind::Hold.since())?
+            .get_hold_gesture(pointer, qh, udata))
+    }
+}
+
+pub trait PointerGesturesHandler: Sized {
+    /// A multi-finger swipe gesture has begun on a surface.
+    #[allow(clippy::too_many_arguments)]
+    fn swipe_begin(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        swipe: &zwp_pointer_gesture_swipe_v1::ZwpPointerGestureSwipeV1,
+        pointer: &wl_pointer::WlPointer,
+        serial: u32,
+        time: u32,
+        surface: &wl_surface::WlSurface,
+        fingers: u32,
+    );
+
+    /// The fingers of a swipe gesture have moved by `(dx, dy)` in surface coordinates.
+    #[allow(clippy::too_many_arguments)]
+    fn swipe_update(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        swipe: &zwp_pointer_gesture_swipe_v1::ZwpPointerGestureSwipeV1,
+        pointer: &wl_pointer::WlPointer,
+        time: u32,
+        dx: f64,
+        dy: f64,
+    );
+
+    /// A swipe gesture has ended.
+    ///
+    /// If `cancelled` is true, the gesture was not performed and should be reverted.
+    #[allow(clippy::too_many_arguments)]
+    fn swipe_end(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        swipe: &zwp_pointer_gesture_swipe_v1::ZwpPointerGestureSwipeV1,
+        pointer: &wl_pointer::WlPointer,
+        serial: u32,
+        time: u32,
+        cancelled: bool,
+    );
+
+    /// A multi-finger pinch gesture has begun on a surface.
+    #[allow(clippy::too_many_arguments)]
+    fn pinch_begin(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        pinch: &zwp_pointer_gesture_pinch_v1::ZwpPointerGesturePinchV1,
+        pointer: &wl_pointer::WlPointer,
+        serial: u32,
+        time: u32,
+        surface: &wl_surface::WlSurface,
+        fingers: u32,
+    );
+
+    /// The fingers of a pinch gesture have moved.
+    ///
+    /// `(dx, dy)` is the motion of the logical center of the gesture in surface coordinates, `scale` is the
+    /// absolute scale compared to the start of the gesture and `rotation` is the angle in degrees, clockwise,
+    /// relative to the previous update.
+    #[allow(clippy::too_many_arguments)]
+    fn pinch_update(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        pinch: &zwp_pointer_gesture_pinch_v1::ZwpPointerGesturePinchV1,
+        pointer: &wl_pointer::WlPointer,
+        time: u32,
+        dx: f64,
+        dy: f64,
+        scale: f64,
+        rotation: f64,
+    );
+
+    /// A pinch gesture has ended.
+    ///
+    /// If `cancelled` is true, the gesture was not performed and should be reverted.
+    #[allow(clippy::too_many_arguments)]
+    fn pinch_end(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        pinch: &zwp_pointer_gesture_pinch_v1::ZwpPointerGesturePinchV1,
+        pointer: &wl_pointer::WlPointer,
+        serial: u32,
+        time: u32,
+        cancelled: bool,
+    );
+
+    /// Fingers have been placed on the device without moving, beginning a hold gesture.
+    ///
+    /// This does nothing by default.
+    #[allow(clippy::too_many_arguments)]
+    fn hold_begin(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _hold: &zwp_pointer_gesture_hold_v1::ZwpPointerGestureHoldV1,
+        _pointer: &wl_pointer::WlPointer,
+        _serial: u32,
+        _time: u32,
+        _surface: &wl_surface::WlSurface,
+        _fingers: u32,
+    ) {
+    }
+
+    /// A hold gesture has ended.
+    ///
+    /// If `cancelled` is true, the fingers moved or another gesture began, otherwise the fingers were lifted.
+    ///
+    /// This does nothing by default.
+    #[allow(clippy::too_many_arguments)]
+    fn hold_end(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _hold: &zwp_pointer_gesture_hold_v1::ZwpPointerGestureHoldV1,
+        _pointer: &wl_pointer::WlPointer,
+        _serial: u32,
+        _time: u32,
+        _cancelled: bool,
+    ) {
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct PointerGestureData {
+    wl_pointer: wl_pointer::WlPointer,
+}
+
+impl<D> Dispatch<zwp_pointer_gestures_v1::ZwpPointerGesturesV1, GlobalData, D>
+    for PointerGesturesState
+where
+    D: Dispatch<zwp_pointer_gestures_v1::ZwpPointerGesturesV1, GlobalData> + PointerGesturesHandler,
+{
+    fn event(
+        _data: &mut D,
+        _manager: &zwp_pointer_gestures_v1::ZwpPointerGesturesV1,
+        _event: zwp_pointer_gestures_v1::Event,
+        _: &GlobalData,
+        _conn: &Connection,
+        _qh: &QueueHandle<D>,
+    ) {
+        unreachable!()
+    }
+}
+
+impl<D> Dispatch<zwp_pointer_gesture_swipe_v1::ZwpPointerGestureSwipeV1, PointerGestureData, D>
+    for PointerGesturesState
+where
+    D: Dispatch<zwp_pointer_gesture_swipe_v1::ZwpPointerGestureSwipeV1, PointerGestureData>
+        + PointerGesturesHandler,
+{
+    fn event(
+        data: &mut D,
+        swipe: &zwp_pointer_gesture_swipe_v1::ZwpPointerGestureSwipeV1,
+        event: zwp_pointer_gesture_swipe_v1::Event,
+        udata: &PointerGestureData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let pointer = &udata.wl_pointer;
+        match event {
+            zwp_pointer_gesture_swipe_v1::Event::Begin { serial, time, surface, fingers } => {
+                data.swipe_begin(conn, qh, swipe, pointer, serial, time, &surface, fingers)
+            }
+            zwp_pointer_gesture_swipe_v1::Event::Update { time, dx, dy } => {
+                data.swipe_update(conn, qh, swipe, pointer, time, dx, dy)
+            }
+            zwp_pointer_gesture_swipe_v1::Event::End { serial, time, cancelled } => {
+                data.swipe_end(conn, qh, swipe, pointer, serial, time, cancelled != 0)
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<zwp_pointer_gesture_pinch_v1::ZwpPointerGesturePinchV1, PointerGestureData, D>
+    for PointerGesturesState
+where
+    D: Dispatch<zwp_pointer_gesture_pinch_v1::ZwpPointerGesturePinchV1, PointerGestureData>
+        + PointerGesturesHandler,
+{
+    fn event(
+        data: &mut D,
+        pinch: &zwp_pointer_gesture_pinch_v1::ZwpPointerGesturePinchV1,
+        event: zwp_pointer_gesture_pinch_v1::Event,
+        udata: &PointerGestureData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let pointer = &udata.wl_pointer;
+        match event {
+            zwp_pointer_gesture_pinch_v1::Event::Begin { serial, time, surface, fingers } => {
+                data.pinch_begin(conn, qh, pinch, pointer, serial, time, &surface, fingers)
+            }
+            zwp_pointer_gesture_pinch_v1::Event::Update { time, dx, dy, scale, rotation } => {
+                data.pinch_update(conn, qh, pinch, pointer, time, dx, dy, scale, rotation)
+            }
+            zwp_pointer_gesture_pinch_v1::Event::End { serial, time, cancelled } => {
+                data.pinch_end(conn, qh, pinch, pointer, serial, time, cancelled != 0)
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<zwp_pointer_gesture_hold_v1::ZwpPointerGestureHoldV1, PointerGestureData, D>
+    for PointerGesturesState
+where
+    D: Dispatch<zwp_pointer_gesture_hold_v1::ZwpPointerGestureHoldV1, PointerGestureData>
+        + PointerGesturesHandler,
+{
+    fn event(
+        data: &mut D,
+        hold: &zwp_pointer_gesture_hold_v1::ZwpPointerGestureHoldV1,
+        event: zwp_pointer_gesture_hold_v1::Event,
+        udata: &PointerGestureData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let pointer = &udata.wl_pointer;
+        match event {
+            zwp_pointer_gesture_hold_v1::Event::Begin { serial, time, surface, fingers } => {
+                data.hold_begin(conn, qh, hold, pointer, serial, time, &surface, fingers)
+            }
+            zwp_pointer_gesture_hold_v1::Event::End { serial, time, cancelled } => {
+                data.hold_end(conn, qh, hold, pointer, serial, time, cancelled != 0)
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_pointer_gestures {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::pointer_gestures::zv1::client::zwp_pointer_gestures_v1::ZwpPointerGesturesV1: $crate::globals::GlobalData
+        ] => $crate::seat::pointer_gestures::PointerGesturesState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::pointer_gestures::zv1::client::zwp_pointer_gesture_swipe_v1::ZwpPointerGestureSwipeV1: $crate::seat::pointer_gestures::PointerGestureData
+        ] => $crate::seat::pointer_gestures::PointerGesturesState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::pointer_gestures::zv1::client::zwp_pointer_gesture_pinch_v1::ZwpPointerGesturePinchV1: $crate::seat::pointer_gestures::PointerGestureData
+        ] => $crate::seat::pointer_gestures::PointerGesturesState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::pointer_gestures::zv1::client::zwp_pointer_gesture_hold_v1::ZwpPointerGestureHoldV1: $crate::seat::pointer_gestures::PointerGestureData
+        ] => $crate::seat::pointer_gestures::PointerGesturesState);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use wayland_backend::protocol::Argument;
+    use wayland_client::{
+        protocol::{wl_compositor::WlCompositor, wl_pointer::WlPointer, wl_seat},
+        Proxy,
+    };
+    use wayland_protocols::wp::pointer_gestures::zv1::client::zwp_pointer_gestures_v1::ZwpPointerGesturesV1;
+
+    use super::PointerGestureKind;
+    use crate::{
+        error::GlobalError,
+        testing::{Call, TestClient, TestServer},
+    };
+
+    fn connect(version: Option<u32>) -> (TestServer, TestClient, WlPointer) {
+        let mut server = TestServer::new();
+        server.create_global(WlCompositor::interface(), 6);
+        server.create_global(wl_seat::WlSeat::interface(), 9);
+        if let Some(version) = version {
+            server.create_global(ZwpPointerGesturesV1::interface(), version);
+        }
+        let mut client = server.connect();
+        let seat = client.seat(&mut server, wl_seat::Capability::Pointer);
+        let pointer = client.state.seat_state.get_pointer(&client.qh, &seat).unwrap();
+        client.roundtrip();
+        server.take_requests();
+        (server, client, pointer)
+    }
+
+    #[test]
+    fn since() {
+        assert_eq!(PointerGestureKind::Swipe.since(), 1);
+        assert_eq!(PointerGestureKind::Pinch.since(), 1);
+        assert_eq!(PointerGestureKind::Hold.since(), 3);
+    }
+
+    #[test]
+    fn supported_gestures() {
+        for version in 1..=3 {
+            let (_server, client, _) = connect(Some(version));
+            let gestures = &client.state.pointer_gestures;
+            assert!(gestures.is_available());
+            assert!(gestures.supports(PointerGestureKind::Swipe));
+            assert!(gestures.supports(PointerGestureKind::Pinch));
+            assert_eq!(gestures.supports(PointerGestureKind::Hold), version == 3);
+            assert_eq!(gestures.supports_hold(), version == 3);
+        }
+
+        let (_server, client, _) = connect(Some(3));
+        assert_eq!(
+            client.state.pointer_gestures.supported_gestures(),
+            [PointerGestureKind::Swipe, PointerGestureKind::Pinch, PointerGestureKind::Hold]
+        );
+    }
+
+    #[test]
+    fn no_gestures() {
+        let (mut server, client, pointer) = connect(None);
+        let gestures = &client.state.pointer_gestures;
+        assert!(!gestures.is_available());
+        assert!(gestures.supported_gestures().is_empty());
+        assert!(gestures.get_swipe_gesture(&pointer, &client.qh).is_err());
+        assert!(gestures.get_hold_gesture(&pointer, &client.qh).is_err());
+        assert!(server.take_requests().is_empty());
+    }
+
+    #[test]
+    fn hold_unsupported() {
+        let (mut server, mut client, pointer) = connect(Some(2));
+
+        let result = client.state.pointer_gestures.get_hold_gesture(&pointer, &client.qh);
+        assert!(matches!(
+            result,
+            Err(GlobalError::InvalidVersion { required: 3, available: 2, .. })
+        ));
+        client.roundtrip();
+        assert!(server.take_requests().is_empty());
+
+        // Swipe and pinch gestures are still available.
+        client.state.pointer_gestures.get_swipe_gesture(&pointer, &client.qh).unwrap();
+        client.roundtrip();
+        assert_eq!(server.take_request_names(), ["zwp_pointer_gestures_v1.get_swipe_gesture"]);
+    }
+
+    #[test]
+    fn hold() {
+        let (mut server, mut client, pointer) = connect(Some(3));
+        let surface = client.create_surface();
+        let hold = client.state.pointer_gestures.get_hold_gesture(&pointer, &client.qh).unwrap();
+        client.roundtrip();
+        assert!(server
+            .take_request_names()
+            .contains(&"zwp_pointer_gestures_v1.get_hold_gesture".to_string()));
+
+        let hold = server.object(&hold);
+        let surface = Argument::Object(server.object(&surface));
+        server.send(
+            &hold,
+            "begin",
+            vec![Argument::Uint(1), Argument::Uint(0), surface, Argument::Uint(3)],
+        );
+        server.send(&hold, "end", vec![Argument::Uint(2), Argument::Uint(0), Argument::Int(1)]);
+        client.roundtrip();
+        assert!(matches!(
+            client.state.take_calls()[..],
+            [
+                Call::GestureBegin(PointerGestureKind::Hold, 3),
+                Call::GestureEnd(PointerGestureKind::Hold, true)
+            ]
+        ));
+    }
+}
@@ -0,0 +1,106 @@
+use wayland_client::{
+    globals::GlobalList,
+    protocol::{wl_pointer, wl_surface},
+    Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols::wp::pointer_warp::v1::client::wp_pointer_warp_v1;
+
+use crate::{
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+    registry::GlobalProxy,
+};
+
+use super::pointer::PointerData;
+
+/// An error that may occur when warping the pointer.
+#[derive(Debug, thiserror::Error)]
+pub enum PointerWarpError {
+    /// The compositor does not support `wp_pointer_warp_v1`.
+    #[error(transparent)]
+    Global(#[from] GlobalError),
+
+    /// No serial was given, and the pointer has not entered any surface.
+    #[error("the pointer has not entered a surface")]
+    NoEnterSerial,
+}
+
+#[derive(Debug)]
+pub struct PointerWarpState {
+    pointer_warp: GlobalProxy<wp_pointer_warp_v1::WpPointerWarpV1>,
+}
+
+impl PointerWarpState {
+    /// Bind `wp_pointer_warp_v1` global, if it exists
+    pub fn bind<D>(globals: &GlobalList, qh: &QueueHandle<D>) -> Self
+    where
+        D: Dispatch<wp_pointer_warp_v1::WpPointerWarpV1, GlobalData> + 'static,
+    {
+        let pointer_warp = GlobalProxy::bind(globals, qh, 1..=1, GlobalData);
+        Self { pointer_warp }
+    }
+
+    /// Whether the compositor supports warping the pointer.
+    ///
+    /// When it does not, [`PointerConstraintsState`](super::pointer_constraints::PointerConstraintsState)
+    /// can be used to hint a cursor position to the compositor while the pointer is locked.
+    pub fn is_available(&self) -> bool {
+        self.pointer_warp.get().is_ok()
+    }
+
+    /// Request the pointer to be moved to a position relative to a surface.
+    ///
+    /// The serial must be the one of the latest enter event of the pointer on the surface. When
+    /// [`None`] is given, the latest enter serial tracked by the [`PointerData`] of the pointer is
+    /// used, which requires the pointer to have been created with that user data.
+    ///
+    /// The compositor may ignore the request, for example if the pointer is not over the surface.
+    pub fn warp(
+        &self,
+        surface: &wl_surface::WlSurface,
+        pointer: &wl_pointer::WlPointer,
+        x: f64,
+        y: f64,
+        serial: Option<u32>,
+    ) -> Result<(), PointerWarpError> {
+        let pointer_warp = self.pointer_warp.get()?;
+        let serial = serial
+            .or_else(|| pointer.data::<PointerData>().and_then(PointerData::latest_enter_serial))
+            .ok_or(PointerWarpError::NoEnterSerial)?;
+        pointer_warp.warp_pointer(surface, pointer, x, y, serial);
+        Ok(())
+    }
+}
+
+impl ProvidesBoundGlobal<wp_pointer_warp_v1::WpPointerWarpV1, 1> for PointerWarpState {
+    fn bound_global(&self) -> Result<wp_pointer_warp_v1::WpPointerWarpV1, GlobalError> {
+        self.pointer_warp.get().cloned()
+    }
+}
+
+impl<D> Dispatch<wp_pointer_warp_v1::WpPointerWarpV1, GlobalData, D> for PointerWarpState
+where
+    D: Dispatch<wp_pointer_warp_v1::WpPointerWarpV1, GlobalData>,
+{
+    fn event(
+        _data: &mut D,
+        _pointer_warp: &wp_pointer_warp_v1::WpPointerWarpV1,
+        _event: wp_pointer_warp_v1::Event,
+        _: &GlobalData,
+        _conn: &Connection,
+        _qh: &QueueHandle<D>,
+    ) {
+        unreachable!()
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_pointer_warp {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols::wp::pointer_warp::v1::client::wp_pointer_warp_v1::WpPointerWarpV1: $crate::globals::GlobalData
+            ] => $crate::seat::pointer_warp::PointerWarpState
+        );
+    };
+}
@@ -0,0 +1,288 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Weak,
+};
+
+use wayland_client::{
+    globals::GlobalList,
+    protocol::{wl_seat, wl_surface},
+    Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols::wp::keyboard_shortcuts_inhibit::zv1::client::{
+    zwp_keyboard_shortcuts_inhibit_manager_v1, zwp_keyboard_shortcuts_inhibitor_v1,
+};
+
+use crate::{
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+    registry::GlobalProxy,
+};
+
+/// An error that may occur when inhibiting keyboard shortcuts.
+#[derive(Debug, thiserror::Error)]
+pub enum ShortcutsInhibitError {
+    /// The `zwp_keyboard_shortcuts_inhibit_manager_v1` global is not bound.
+    #[error(transparent)]
+    Global(#[from] GlobalError),
+
+    /// An inhibitor already exists for this surface and seat.
+    #[error("keyboard shortcuts are already inhibited for this surface and seat")]
+    AlreadyInhibited,
+}
+
+#[derive(Debug)]
+pub struct ShortcutsInhibitState {
+    manager: GlobalProxy<
+        zwp_keyboard_shortcuts_inhibit_manager_v1::ZwpKeyboardShortcutsInhibitManagerV1,
+    >,
+    inhibitors: Vec<Weak<ShortcutsInhibitorInner>>,
+}
+
+impl ShortcutsInhibitState {
+    /// Bind `zwp_keyboard_shortcuts_inhibit_manager_v1` global, if it exists
+    pub fn bind<D>(globals: &GlobalList, qh: &QueueHandle<D>) -> Self
+    where
+        D: Dispatch<
+                zwp_keyboard_shortcuts_inhibit_manager_v1::ZwpKeyboardShortcutsInhibitManagerV1,
+                GlobalData,
+            > + 'static,
+    {
+        let manager = GlobalProxy::bind(globals, qh, 1..=1, GlobalData);
+        Self { manager, inhibitors: Vec::new() }
+    }
+
+    /// Request that the compositor stops handling its keyboard shortcuts for a surface and seat.
+    ///
+    /// Shortcuts are inhibited until the returned [`ShortcutsInhibitor`] and all its clones are dropped. The
+    /// compositor may deny or revoke the request at any time, which is reported through
+    /// [`ShortcutsInhibitHandler`].
+    ///
+    /// ## Errors
+    ///
+    /// This will return [`ShortcutsInhibitError::AlreadyInhibited`] if an inhibitor created by this state
+    /// is still alive for the same surface and seat.
+    pub fn inhibit_shortcuts<D>(
+        &mut self,
+        surface: &wl_surface::WlSurface,
+        seat: &wl_seat::WlSeat,
+        qh: &QueueHandle<D>,
+    ) -> Result<ShortcutsInhibitor, ShortcutsInhibitError>
+    where
+        D: Dispatch<
+                zwp_keyboard_shortcuts_inhibitor_v1::ZwpKeyboardShortcutsInhibitorV1,
+                ShortcutsInhibitorData,
+            > + 'static,
+    {
+        let manager = self.manager.get()?;
+
+        self.inhibitors.retain(|inner| inner.strong_count() > 0);
+        let exists = self
+            .inhibitors
+            .iter()
+            .filter_map(Weak::upgrade)
+            .any(|inner| &inner.surface == surface && &inner.seat == seat);
+
+        if exists {
+            return Err(ShortcutsInhibitError::AlreadyInhibited);
+        }
+
+        // Freeze the queue during the creation of the Arc to avoid a race between events on the
+        // new object being processed and the Weak in the ShortcutsInhibitorData becoming usable.
+        let freeze = qh.freeze();
+
+        let inner = Arc::new_cyclic(|weak| {
+            let inhibitor = manager.inhibit_shortcuts(
+                surface,
+                seat,
+                qh,
+                ShortcutsInhibitorData { inner: weak.clone() },
+            );
+
+            ShortcutsInhibitorInner {
+                inhibitor,
+                surface: surface.clone(),
+                seat: seat.clone(),
+                active: AtomicBool::new(false),
+            }
+        });
+        drop(freeze);
+
+        self.inhibitors.push(Arc::downgrade(&inner));
+
+        Ok(ShortcutsInhibitor(inner))
+    }
+}
+
+impl
+    ProvidesBoundGlobal<
+        zwp_keyboard_shortcuts_inhibit_manager_v1::ZwpKeyboardShortcutsInhibitManagerV1,
+        1,
+    > for ShortcutsInhibitState
+{
+    fn bound_global(
+        &self,
+    ) -> Result<
+        zwp_keyboard_shortcuts_inhibit_manager_v1::ZwpKeyboardShortcutsInhibitManagerV1,
+        GlobalError,
+    > {
+        self.manager.get().cloned()
+    }
+}
+
+/// Handler trait for the keyboard shortcuts inhibit protocol.
+pub trait ShortcutsInhibitHandler: Sized {
+    /// The compositor has stopped handling its keyboard shortcuts for the inhibitor's surface and seat.
+    fn shortcuts_inhibited(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        inhibitor: &ShortcutsInhibitor,
+    );
+
+    /// The compositor is handling its keyboard shortcuts again.
+    ///
+    /// This may happen if the user used an escape shortcut. The inhibitor remains valid and may become
+    /// active again later.
+    fn shortcuts_restored(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        inhibitor: &ShortcutsInhibitor,
+    );
+}
+
+#[derive(Debug)]
+struct ShortcutsInhibitorInner {
+    inhibitor: zwp_keyboard_shortcuts_inhibitor_v1::ZwpKeyboardShortcutsInhibitorV1,
+    surface: wl_surface::WlSurface,
+    seat: wl_seat::WlSeat,
+    active: AtomicBool,
+}
+
+impl Drop for ShortcutsInhibitorInner {
+    fn drop(&mut self) {
+        self.inhibitor.destroy();
+    }
+}
+
+/// An inhibitor of the compositor's keyboard shortcuts.
+///
+/// The inhibitor is destroyed when the last clone is dropped.
+#[must_use]
+#[derive(Debug, Clone)]
+pub struct ShortcutsInhibitor(Arc<ShortcutsInhibitorInner>);
+
+impl ShortcutsInhibitor {
+    pub fn from_inhibitor(
+        inhibitor: &zwp_keyboard_shortcuts_inhibitor_v1::ZwpKeyboardShortcutsInhibitorV1,
+    ) -> Option<Self> {
+        inhibitor.data::<ShortcutsInhibitorData>().and_then(|data| data.inner.upgrade()).map(Self)
+    }
+
+    pub fn inhibitor(
+        &self,
+    ) -> &zwp_keyboard_shortcuts_inhibitor_v1::ZwpKeyboardShortcutsInhibitorV1 {
+        &self.0.inhibitor
+    }
+
+    pub fn wl_surface(&self) -> &wl_surface::WlSurface {
+        &self.0.surface
+    }
+
+    pub fn seat(&self) -> &wl_seat::WlSeat {
+        &self.0.seat
+    }
+
+    /// Whether the compositor is currently inhibiting its shortcuts.
+    pub fn is_active(&self) -> bool {
+        self.0.active.load(Ordering::SeqCst)
+    }
+}
+
+impl PartialEq for ShortcutsInhibitor {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.inhibitor == other.0.inhibitor
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct ShortcutsInhibitorData {
+    inner: Weak<ShortcutsInhibitorInner>,
+}
+
+impl<D>
+    Dispatch<
+        zwp_keyboard_shortcuts_inhibit_manager_v1::ZwpKeyboardShortcutsInhibitManagerV1,
+        GlobalData,
+        D,
+    > for ShortcutsInhibitState
+where
+    D: Dispatch<
+        zwp_keyboard_shortcuts_inhibit_manager_v1::ZwpKeyboardShortcutsInhibitManagerV1,
+        GlobalData,
+    >,
+{
+    fn event(
+        _data: &mut D,
+        _manager: &zwp_keyboard_shortcuts_inhibit_manager_v1::ZwpKeyboardShortcutsInhibitManagerV1,
+        _event: zwp_keyboard_shortcuts_inhibit_manager_v1::Event,
+        _: &GlobalData,
+        _conn: &Connection,
+        _qh: &QueueHandle<D>,
+    ) {
+        unreachable!()
+    }
+}
+
+impl<D>
+    Dispatch<
+        zwp_keyboard_shortcuts_inhibitor_v1::ZwpKeyboardShortcutsInhibitorV1,
+        ShortcutsInhibitorData,
+        D,
+    > for ShortcutsInhibitState
+where
+    D: Dispatch<
+            zwp_keyboard_shortcuts_inhibitor_v1::ZwpKeyboardShortcutsInhibitorV1,
+            ShortcutsInhibitorData,
+        > + ShortcutsInhibitHandler,
+{
+    fn event(
+        data: &mut D,
+        proxy: &zwp_keyboard_shortcuts_inhibitor_v1::ZwpKeyboardShortcutsInhibitorV1,
+        event: zwp_keyboard_shortcuts_inhibitor_v1::Event,
+        _: &ShortcutsInhibitorData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        if let Some(inhibitor) = ShortcutsInhibitor::from_inhibitor(proxy) {
+            match event {
+                zwp_keyboard_shortcuts_inhibitor_v1::Event::Active => {
+                    inhibitor.0.active.store(true, Ordering::SeqCst);
+                    data.shortcuts_inhibited(conn, qh, &inhibitor);
+                }
+                zwp_keyboard_shortcuts_inhibitor_v1::Event::Inactive => {
+                    inhibitor.0.active.store(false, Ordering::SeqCst);
+                    data.shortcuts_restored(conn, qh, &inhibitor);
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_shortcuts_inhibit {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols::wp::keyboard_shortcuts_inhibit::zv1::client::zwp_keyboard_shortcuts_inhibit_manager_v1::ZwpKeyboardShortcutsInhibitManagerV1: $crate::globals::GlobalData
+            ] => $crate::seat::shortcuts_inhibit::ShortcutsInhibitState
+        );
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols::wp::keyboard_shortcuts_inhibit::zv1::client::zwp_keyboard_shortcuts_inhibitor_v1::ZwpKeyboardShortcutsInhibitorV1: $crate::seat::shortcuts_inhibit::ShortcutsInhibitorData
+            ] => $crate::seat::shortcuts_inhibit::ShortcutsInhibitState
+        );
+    };
+}
@@ -0,0 +1,454 @@
+//! Support for graphics tablets, using the `zwp_tablet_manager_v2` protocol.
+//!
+//! Tablets are not exposed through `wl_seat`: a tablet seat must be created with
+//! [`TabletState::get_tablet_seat`] for each seat of interest. The compositor then announces the
+//! tablets, tools and pads of the seat, which are tracked by [`TabletState`] and reported through
+//! [`TabletSeatHandler`] once their description is complete.
+//!
+//! Tool events are delivered per frame through [`TabletToolHandler`](tool::TabletToolHandler), while pad events are delivered
+//! through [`TabletPadHandler`](pad::TabletPadHandler).
+
+use std::sync::Mutex;
+
+use wayland_client::{
+    globals::GlobalList, protocol::wl_seat::WlSeat, Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols::wp::{
+    cursor_shape::v1::client::{
+        wp_cursor_shape_device_v1::WpCursorShapeDeviceV1,
+        wp_cursor_shape_manager_v1::WpCursorShapeManagerV1,
+    },
+    tablet::zv2::client::{
+        zwp_tablet_manager_v2::{self, ZwpTabletManagerV2},
+        zwp_tablet_pad_v2::ZwpTabletPadV2,
+        zwp_tablet_seat_v2::{self, ZwpTabletSeatV2},
+        zwp_tablet_tool_v2::ZwpTabletToolV2,
+        zwp_tablet_v2::{self, ZwpTabletV2},
+    },
+};
+
+use crate::{
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+    registry::GlobalProxy,
+};
+
+use super::pointer::cursor_shape::CursorShapeManager;
+
+pub mod pad;
+pub mod tool;
+
+use pad::{PadInfo, TabletPadData};
+use tool::{TabletToolData, ToolInfo};
+
+#[derive(Debug)]
+pub struct TabletState {
+    tablet_manager: GlobalProxy<ZwpTabletManagerV2>,
+    cursor_shape_manager: Option<CursorShapeManager>,
+    seats: Vec<TabletSeatInner>,
+}
+
+#[derive(Debug)]
+struct TabletSeatInner {
+    seat: WlSeat,
+    tablet_seat: ZwpTabletSeatV2,
+    tablets: Vec<ZwpTabletV2>,
+    tools: Vec<ZwpTabletToolV2>,
+    pads: Vec<ZwpTabletPadV2>,
+}
+
+impl TabletState {
+    /// Bind `zwp_tablet_manager_v2` global, if it exists.
+    ///
+    /// The `wp_cursor_shape_manager_v1` global is also bound if available, to support
+    /// [`TabletState::set_tool_cursor`].
+    pub fn bind<D>(globals: &GlobalList, qh: &QueueHandle<D>) -> Self
+    where
+        D: Dispatch<ZwpTabletManagerV2, GlobalData>
+            + Dispatch<WpCursorShapeManagerV1, GlobalData>
+            + 'static,
+    {
+        let tablet_manager = GlobalProxy::bind(globals, qh, 1..=1, GlobalData);
+        let cursor_shape_manager = CursorShapeManager::bind(globals, qh).ok();
+        Self { tablet_manager, cursor_shape_manager, seats: Vec::new() }
+    }
+
+    /// Get the tablet seat of a seat, creating it if needed.
+    ///
+    /// The tablets, tools and pads of the seat are announced once the tablet seat is created.
+    pub fn get_tablet_seat<D>(
+        &mut self,
+        seat: &WlSeat,
+        qh: &QueueHandle<D>,
+    ) -> Result<ZwpTabletSeatV2, GlobalError>
+    where
+        D: Dispatch<ZwpTabletSeatV2, TabletSeatData> + 'static,
+    {
+        if let Some(inner) = self.seats.iter().find(|inner| &inner.seat == seat) {
+            return Ok(inner.tablet_seat.clone());
+        }
+
+        let tablet_seat = self.tablet_manager.get()?.get_tablet_seat(
+            seat,
+            qh,
+            TabletSeatData { seat: seat.clone() },
+        );
+        self.seats.push(TabletSeatInner {
+            seat: seat.clone(),
+            tablet_seat: tablet_seat.clone(),
+            tablets: Vec::new(),
+            tools: Vec::new(),
+            pads: Vec::new(),
+        });
+        Ok(tablet_seat)
+    }
+
+    /// Destroy the tablet seat of a seat, along with its tablets, tools and pads.
+    ///
+    /// This should be called when the seat is removed.
+    pub fn release_tablet_seat(&mut self, seat: &WlSeat) {
+        if let Some(position) = self.seats.iter().position(|inner| &inner.seat == seat) {
+            let inner = self.seats.remove(position);
+            for tablet in inner.tablets {
+                tablet.destroy();
+            }
+            for tool in inner.tools {
+                tool::destroy_tool(&tool);
+            }
+            for pad in inner.pads {
+                pad::destroy_pad(&pad);
+            }
+            inner.tablet_seat.destroy();
+        }
+    }
+
+    /// Returns an iterator over the tablets of a seat.
+    pub fn tablets(&self, seat: &WlSeat) -> impl Iterator<Item = ZwpTabletV2> {
+        self.seat_inner(seat).map(|inner| inner.tablets.clone()).unwrap_or_default().into_iter()
+    }
+
+    /// Returns an iterator over the tools of a seat.
+    pub fn tools(&self, seat: &WlSeat) -> impl Iterator<Item = ZwpTabletToolV2> {
+        self.seat_inner(seat).map(|inner| inner.tools.clone()).unwrap_or_default().into_iter()
+    }
+
+    /// Returns an iterator over the pads of a seat.
+    pub fn pads(&self, seat: &WlSeat) -> impl Iterator<Item = ZwpTabletPadV2> {
+        self.seat_inner(seat).map(|inner| inner.pads.clone()).unwrap_or_default().into_iter()
+    }
+
+    /// Returns the description of a tablet.
+    ///
+    /// This returns [`None`] if the tablet was not created by sctk.
+    pub fn tablet_info(&self, tablet: &ZwpTabletV2) -> Option<TabletInfo> {
+        tablet.data::<TabletData>().map(|data| data.info.lock().unwrap().clone())
+    }
+
+    /// Returns the description of a tool.
+    ///
+    /// This returns [`None`] if the tool was not created by sctk.
+    pub fn tool_info(&self, tool: &ZwpTabletToolV2) -> Option<ToolInfo> {
+        tool.data::<TabletToolData>().map(TabletToolData::info)
+    }
+
+    /// Returns the description of a pad.
+    ///
+    /// This returns [`None`] if the pad was not created by sctk.
+    pub fn pad_info(&self, pad: &ZwpTabletPadV2) -> Option<PadInfo> {
+        pad.data::<TabletPadData>().map(TabletPadData::info)
+    }
+
+    fn seat_inner(&self, seat: &WlSeat) -> Option<&TabletSeatInner> {
+        self.seats.iter().find(|inner| &inner.seat == seat)
+    }
+
+    fn seat_inner_mut(&mut self, tablet_seat: &ZwpTabletSeatV2) -> Option<&mut TabletSeatInner> {
+        self.seats.iter_mut().find(|inner| &inner.tablet_seat == tablet_seat)
+    }
+
+    /// The seat a device was announced on.
+    fn seat_of<F>(&self, f: F) -> Option<WlSeat>
+    where
+        F: Fn(&TabletSeatInner) -> bool,
+    {
+        self.seats.iter().find(|inner| f(inner)).map(|inner| inner.seat.clone())
+    }
+}
+
+impl ProvidesBoundGlobal<ZwpTabletManagerV2, 1> for TabletState {
+    fn bound_global(&self) -> Result<ZwpTabletManagerV2, GlobalError> {
+        self.tablet_manager.get().cloned()
+    }
+}
+
+/// Handler for the tablets, tools and pads announced on tablet seats.
+pub trait TabletSeatHandler: Sized {
+    fn tablet_state(&mut self) -> &mut TabletState;
+
+    /// A tablet was added to the seat.
+    ///
+    /// The description of the tablet is available through [`TabletState::tablet_info`].
+    fn tablet_added(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        seat: &WlSeat,
+        tablet: &ZwpTabletV2,
+    );
+
+    /// A tablet was removed.
+    ///
+    /// The tablet is destroyed after this call.
+    fn tablet_removed(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        seat: &WlSeat,
+        tablet: &ZwpTabletV2,
+    );
+
+    /// A tool was added to the seat.
+    ///
+    /// Tools are usually announced the first time they come in proximity of a tablet. The description
+    /// of the tool is available through [`TabletState::tool_info`].
+    fn tool_added(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        seat: &WlSeat,
+        tool: &ZwpTabletToolV2,
+    );
+
+    /// A tool was removed.
+    ///
+    /// The tool is destroyed after this call.
+    fn tool_removed(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        seat: &WlSeat,
+        tool: &ZwpTabletToolV2,
+    );
+
+    /// A pad was added to the seat.
+    ///
+    /// The description of the pad is available through [`TabletState::pad_info`].
+    fn pad_added(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        seat: &WlSeat,
+        pad: &ZwpTabletPadV2,
+    );
+
+    /// A pad was removed.
+    ///
+    /// The pad is destroyed after this call.
+    fn pad_removed(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        seat: &WlSeat,
+        pad: &ZwpTabletPadV2,
+    );
+}
+
+/// Description of a tablet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TabletInfo {
+    /// Descriptive name of the tablet.
+    pub name: Option<String>,
+
+    /// USB vendor and product id of the tablet.
+    pub usb_id: Option<(u32, u32)>,
+
+    /// Device paths of the tablet, such as `/dev/input/event0`.
+    pub paths: Vec<String>,
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct TabletSeatData {
+    seat: WlSeat,
+}
+
+impl TabletSeatData {
+    pub fn seat(&self) -> &WlSeat {
+        &self.seat
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct TabletData {
+    info: Mutex<TabletInfo>,
+}
+
+impl<D> Dispatch<ZwpTabletManagerV2, GlobalData, D> for TabletState
+where
+    D: Dispatch<ZwpTabletManagerV2, GlobalData>,
+{
+    fn event(
+        _data: &mut D,
+        _manager: &ZwpTabletManagerV2,
+        _event: zwp_tablet_manager_v2::Event,
+        _: &GlobalData,
+        _conn: &Connection,
+        _qh: &QueueHandle<D>,
+    ) {
+        unreachable!()
+    }
+}
+
+impl<D> Dispatch<ZwpTabletSeatV2, TabletSeatData, D> for TabletState
+where
+    D: Dispatch<ZwpTabletSeatV2, TabletSeatData>
+        + Dispatch<ZwpTabletV2, TabletData>
+        + Dispatch<ZwpTabletToolV2, TabletToolData>
+        + Dispatch<ZwpTabletPadV2, TabletPadData>
+        + TabletSeatHandler
+        + 'static,
+{
+    fn event(
+        data: &mut D,
+        tablet_seat: &ZwpTabletSeatV2,
+        event: zwp_tablet_seat_v2::Event,
+        _: &TabletSeatData,
+        _conn: &Connection,
+        _qh: &QueueHandle<D>,
+    ) {
+        let inner = match data.tablet_state().seat_inner_mut(tablet_seat) {
+            Some(inner) => inner,
+            None => return,
+        };
+
+        // Devices are reported to the handler once their description is done.
+        match event {
+            zwp_tablet_seat_v2::Event::TabletAdded { id } => inner.tablets.push(id),
+            zwp_tablet_seat_v2::Event::ToolAdded { id } => inner.tools.push(id),
+            zwp_tablet_seat_v2::Event::PadAdded { id } => inner.pads.push(id),
+            _ => unreachable!(),
+        }
+    }
+
+    wayland_client::event_created_child!(D, ZwpTabletSeatV2, [
+        zwp_tablet_seat_v2::EVT_TABLET_ADDED_OPCODE => (ZwpTabletV2, Default::default()),
+        zwp_tablet_seat_v2::EVT_TOOL_ADDED_OPCODE => (ZwpTabletToolV2, Default::default()),
+        zwp_tablet_seat_v2::EVT_PAD_ADDED_OPCODE => (ZwpTabletPadV2, Default::default())
+    ]);
+}
+
+impl<D> Dispatch<ZwpTabletV2, TabletData, D> for TabletState
+where
+    D: Dispatch<ZwpTabletV2, TabletData> + TabletSeatHandler,
+{
+    fn event(
+        data: &mut D,
+        tablet: &ZwpTabletV2,
+        event: zwp_tablet_v2::Event,
+        udata: &TabletData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let mut info = udata.info.lock().unwrap();
+
+        match event {
+            zwp_tablet_v2::Event::Name { name } => {
+                info.name = Some(name);
+            }
+            zwp_tablet_v2::Event::Id { vid, pid } => {
+                info.usb_id = Some((vid, pid));
+            }
+            zwp_tablet_v2::Event::Path { path } => {
+                info.paths.push(path);
+            }
+            zwp_tablet_v2::Event::Done => {
+                drop(info);
+                if let Some(seat) =
+                    data.tablet_state().seat_of(|inner| inner.tablets.contains(tablet))
+                {
+                    data.tablet_added(conn, qh, &seat, tablet);
+                }
+            }
+            zwp_tablet_v2::Event::Removed => {
+                drop(info);
+                if let Some(seat) =
+                    data.tablet_state().seat_of(|inner| inner.tablets.contains(tablet))
+                {
+                    data.tablet_removed(conn, qh, &seat, tablet);
+                }
+
+                for inner in &mut data.tablet_state().seats {
+                    inner.tablets.retain(|t| t != tablet);
+                }
+                tablet.destroy();
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Cursor shape devices for tablet tools are dispatched like the ones of pointers.
+fn create_shape_device<D>(
+    state: &TabletState,
+    tool: &ZwpTabletToolV2,
+    qh: &QueueHandle<D>,
+) -> Option<WpCursorShapeDeviceV1>
+where
+    D: Dispatch<WpCursorShapeDeviceV1, GlobalData> + 'static,
+{
+    state
+        .cursor_shape_manager
+        .as_ref()
+        .map(|manager| manager.get_shape_device_for_tablet_tool(tool, qh))
+}
+
+/// Delegate the tablet objects to [`TabletState`].
+///
+/// The cursor shape objects used by tools are dispatched by [`delegate_pointer!`](crate::delegate_pointer),
+/// which must also be used.
+#[macro_export]
+macro_rules! delegate_tablet {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_manager_v2::ZwpTabletManagerV2: $crate::globals::GlobalData
+            ] => $crate::seat::tablet::TabletState
+        );
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_seat_v2::ZwpTabletSeatV2: $crate::seat::tablet::TabletSeatData
+            ] => $crate::seat::tablet::TabletState
+        );
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_v2::ZwpTabletV2: $crate::seat::tablet::TabletData
+            ] => $crate::seat::tablet::TabletState
+        );
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_tool_v2::ZwpTabletToolV2: $crate::seat::tablet::tool::TabletToolData
+            ] => $crate::seat::tablet::TabletState
+        );
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_pad_v2::ZwpTabletPadV2: $crate::seat::tablet::pad::TabletPadData
+            ] => $crate::seat::tablet::TabletState
+        );
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_pad_group_v2::ZwpTabletPadGroupV2: $crate::seat::tablet::pad::TabletPadGroupData
+            ] => $crate::seat::tablet::TabletState
+        );
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_pad_ring_v2::ZwpTabletPadRingV2: $crate::seat::tablet::pad::TabletPadRingData
+            ] => $crate::seat::tablet::TabletState
+        );
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_pad_strip_v2::ZwpTabletPadStripV2: $crate::seat::tablet::pad::TabletPadStripData
+            ] => $crate::seat::tablet::TabletState
+        );
+    };
+}
@@ -0,0 +1,448 @@
+use std::{mem, sync::Mutex};
+
+use wayland_client::{
+    protocol::wl_surface::WlSurface, Connection, Dispatch, Proxy, QueueHandle, WEnum,
+};
+use wayland_protocols::wp::tablet::zv2::client::{
+    zwp_tablet_pad_group_v2::{self, ZwpTabletPadGroupV2},
+    zwp_tablet_pad_ring_v2::{self, ZwpTabletPadRingV2},
+    zwp_tablet_pad_strip_v2::{self, ZwpTabletPadStripV2},
+    zwp_tablet_pad_v2::{self, ButtonState, ZwpTabletPadV2},
+    zwp_tablet_v2::ZwpTabletV2,
+};
+
+use super::{TabletSeatHandler, TabletState};
+
+/// Description of a pad.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PadInfo {
+    /// Device paths of the pad, such as `/dev/input/event0`.
+    pub paths: Vec<String>,
+
+    /// The number of buttons of the pad.
+    pub buttons: u32,
+
+    /// The groups of buttons, rings and strips of the pad.
+    pub groups: Vec<PadGroupInfo>,
+}
+
+/// Description of a group of pad controls sharing a mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PadGroupInfo {
+    pub group: ZwpTabletPadGroupV2,
+
+    /// The indices of the pad buttons in this group.
+    pub buttons: Vec<u32>,
+
+    pub rings: Vec<ZwpTabletPadRingV2>,
+
+    pub strips: Vec<ZwpTabletPadStripV2>,
+
+    /// The number of modes of the group, or zero if the group has no modes.
+    pub modes: u32,
+}
+
+/// A single pad event.
+#[derive(Debug, Clone)]
+pub enum TabletPadEvent {
+    /// The pad gained focus on a surface.
+    Enter { serial: u32, tablet: ZwpTabletV2, surface: WlSurface },
+
+    /// The pad lost focus on a surface.
+    Leave { serial: u32, surface: WlSurface },
+
+    /// A button of the pad was pressed or released.
+    Button { time: u32, button: u32, state: WEnum<ButtonState> },
+
+    /// The mode of a group changed.
+    ///
+    /// The feedback of the controls of the group should be updated with
+    /// [`ZwpTabletPadV2::set_feedback`], using this serial.
+    ModeSwitch { time: u32, serial: u32, group: ZwpTabletPadGroupV2, mode: u32 },
+
+    /// A ring of the pad was used.
+    Ring {
+        time: u32,
+        ring: ZwpTabletPadRingV2,
+        source: Option<WEnum<zwp_tablet_pad_ring_v2::Source>>,
+
+        /// The new angle of the ring in degrees clockwise from the logical north.
+        angle: Option<f64>,
+
+        /// The interaction with the ring stopped.
+        stop: bool,
+    },
+
+    /// A strip of the pad was used.
+    Strip {
+        time: u32,
+        strip: ZwpTabletPadStripV2,
+        source: Option<WEnum<zwp_tablet_pad_strip_v2::Source>>,
+
+        /// The new position on the strip, normalized between 0 and 65535.
+        position: Option<u32>,
+
+        /// The interaction with the strip stopped.
+        stop: bool,
+    },
+}
+
+pub trait TabletPadHandler: Sized {
+    /// A pad event is available.
+    ///
+    /// Ring and strip events are accumulated until the end of their frame.
+    fn tablet_pad_event(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        pad: &ZwpTabletPadV2,
+        event: TabletPadEvent,
+    );
+}
+
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct TabletPadData {
+    inner: Mutex<TabletPadDataInner>,
+}
+
+#[derive(Debug, Default)]
+struct TabletPadDataInner {
+    paths: Vec<String>,
+    buttons: u32,
+    groups: Vec<ZwpTabletPadGroupV2>,
+    focus: Option<(ZwpTabletV2, WlSurface)>,
+}
+
+impl TabletPadData {
+    pub fn info(&self) -> PadInfo {
+        let inner = self.inner.lock().unwrap();
+        PadInfo {
+            paths: inner.paths.clone(),
+            buttons: inner.buttons,
+            groups: inner
+                .groups
+                .iter()
+                .filter_map(|group| {
+                    let data = group.data::<TabletPadGroupData>()?;
+                    let info = data.info.lock().unwrap();
+                    Some(PadGroupInfo {
+                        group: group.clone(),
+                        buttons: info.buttons.clone(),
+                        rings: info.rings.clone(),
+                        strips: info.strips.clone(),
+                        modes: info.modes,
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    /// The tablet and surface the pad is focused on, if any.
+    pub fn focus(&self) -> Option<(ZwpTabletV2, WlSurface)> {
+        self.inner.lock().unwrap().focus.clone()
+    }
+}
+
+// The pad of groups, rings and strips is only known once the event creating them is dispatched.
+
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct TabletPadGroupData {
+    pad: Mutex<Option<ZwpTabletPadV2>>,
+    info: Mutex<PadGroupDataInner>,
+}
+
+#[derive(Debug, Default)]
+struct PadGroupDataInner {
+    buttons: Vec<u32>,
+    rings: Vec<ZwpTabletPadRingV2>,
+    strips: Vec<ZwpTabletPadStripV2>,
+    modes: u32,
+}
+
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct TabletPadRingData {
+    pad: Mutex<Option<ZwpTabletPadV2>>,
+    pending: Mutex<PendingAxis<zwp_tablet_pad_ring_v2::Source, f64>>,
+}
+
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct TabletPadStripData {
+    pad: Mutex<Option<ZwpTabletPadV2>>,
+    pending: Mutex<PendingAxis<zwp_tablet_pad_strip_v2::Source, u32>>,
+}
+
+#[derive(Debug)]
+struct PendingAxis<S, V> {
+    source: Option<WEnum<S>>,
+    value: Option<V>,
+    stop: bool,
+}
+
+impl<S, V> Default for PendingAxis<S, V> {
+    fn default() -> Self {
+        Self { source: None, value: None, stop: false }
+    }
+}
+
+pub(super) fn destroy_pad(pad: &ZwpTabletPadV2) {
+    if let Some(data) = pad.data::<TabletPadData>() {
+        for group in mem::take(&mut data.inner.lock().unwrap().groups) {
+            if let Some(data) = group.data::<TabletPadGroupData>() {
+                let info = mem::take(&mut *data.info.lock().unwrap());
+                for ring in info.rings {
+                    ring.destroy();
+                }
+                for strip in info.strips {
+                    strip.destroy();
+                }
+            }
+            group.destroy();
+        }
+    }
+    pad.destroy();
+}
+
+impl<D> Dispatch<ZwpTabletPadV2, TabletPadData, D> for TabletState
+where
+    D: Dispatch<ZwpTabletPadV2, TabletPadData>
+        + Dispatch<ZwpTabletPadGroupV2, TabletPadGroupData>
+        + TabletSeatHandler
+        + TabletPadHandler
+        + 'static,
+{
+    fn event(
+        data: &mut D,
+        pad: &ZwpTabletPadV2,
+        event: zwp_tablet_pad_v2::Event,
+        udata: &TabletPadData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let mut inner = udata.inner.lock().unwrap();
+
+        match event {
+            zwp_tablet_pad_v2::Event::Group { pad_group } => {
+                if let Some(data) = pad_group.data::<TabletPadGroupData>() {
+                    *data.pad.lock().unwrap() = Some(pad.clone());
+                }
+                inner.groups.push(pad_group);
+            }
+            zwp_tablet_pad_v2::Event::Path { path } => {
+                inner.paths.push(path);
+            }
+            zwp_tablet_pad_v2::Event::Buttons { buttons } => {
+                inner.buttons = buttons;
+            }
+            zwp_tablet_pad_v2::Event::Done => {
+                drop(inner);
+                if let Some(seat) = data.tablet_state().seat_of(|inner| inner.pads.contains(pad)) {
+                    data.pad_added(conn, qh, &seat, pad);
+                }
+            }
+            zwp_tablet_pad_v2::Event::Button { time, button, state } => {
+                drop(inner);
+                data.tablet_pad_event(
+                    conn,
+                    qh,
+                    pad,
+                    TabletPadEvent::Button { time, button, state },
+                );
+            }
+            zwp_tablet_pad_v2::Event::Enter { serial, tablet, surface } => {
+                inner.focus = Some((tablet.clone(), surface.clone()));
+                drop(inner);
+                data.tablet_pad_event(
+                    conn,
+                    qh,
+                    pad,
+                    TabletPadEvent::Enter { serial, tablet, surface },
+                );
+            }
+            zwp_tablet_pad_v2::Event::Leave { serial, surface } => {
+                inner.focus = None;
+                drop(inner);
+                data.tablet_pad_event(conn, qh, pad, TabletPadEvent::Leave { serial, surface });
+            }
+            zwp_tablet_pad_v2::Event::Removed => {
+                drop(inner);
+                if let Some(seat) = data.tablet_state().seat_of(|inner| inner.pads.contains(pad)) {
+                    data.pad_removed(conn, qh, &seat, pad);
+                }
+
+                for inner in &mut data.tablet_state().seats {
+                    inner.pads.retain(|p| p != pad);
+                }
+                destroy_pad(pad);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    wayland_client::event_created_child!(D, ZwpTabletPadV2, [
+        zwp_tablet_pad_v2::EVT_GROUP_OPCODE => (ZwpTabletPadGroupV2, Default::default())
+    ]);
+}
+
+impl<D> Dispatch<ZwpTabletPadGroupV2, TabletPadGroupData, D> for TabletState
+where
+    D: Dispatch<ZwpTabletPadGroupV2, TabletPadGroupData>
+        + Dispatch<ZwpTabletPadRingV2, TabletPadRingData>
+        + Dispatch<ZwpTabletPadStripV2, TabletPadStripData>
+        + TabletPadHandler
+        + 'static,
+{
+    fn event(
+        data: &mut D,
+        group: &ZwpTabletPadGroupV2,
+        event: zwp_tablet_pad_group_v2::Event,
+        udata: &TabletPadGroupData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let pad = udata.pad.lock().unwrap().clone();
+        let mut info = udata.info.lock().unwrap();
+
+        match event {
+            zwp_tablet_pad_group_v2::Event::Buttons { buttons } => {
+                info.buttons = buttons
+                    .chunks_exact(4)
+                    .map(|chunk| u32::from_ne_bytes(chunk.try_into().unwrap()))
+                    .collect();
+            }
+            zwp_tablet_pad_group_v2::Event::Ring { ring } => {
+                if let Some(data) = ring.data::<TabletPadRingData>() {
+                    *data.pad.lock().unwrap() = pad;
+                }
+                info.rings.push(ring);
+            }
+            zwp_tablet_pad_group_v2::Event::Strip { strip } => {
+                if let Some(data) = strip.data::<TabletPadStripData>() {
+                    *data.pad.lock().unwrap() = pad;
+                }
+                info.strips.push(strip);
+            }
+            zwp_tablet_pad_group_v2::Event::Modes { modes } => {
+                info.modes = modes;
+            }
+            // The group is reported as part of the pad description.
+            zwp_tablet_pad_group_v2::Event::Done => {}
+            zwp_tablet_pad_group_v2::Event::ModeSwitch { time, serial, mode } => {
+                drop(info);
+                if let Some(pad) = pad {
+                    data.tablet_pad_event(
+                        conn,
+                        qh,
+                        &pad,
+                        TabletPadEvent::ModeSwitch { time, serial, group: group.clone(), mode },
+                    );
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    wayland_client::event_created_child!(D, ZwpTabletPadGroupV2, [
+        zwp_tablet_pad_group_v2::EVT_RING_OPCODE => (ZwpTabletPadRingV2, Default::default()),
+        zwp_tablet_pad_group_v2::EVT_STRIP_OPCODE => (ZwpTabletPadStripV2, Default::default())
+    ]);
+}
+
+impl<D> Dispatch<ZwpTabletPadRingV2, TabletPadRingData, D> for TabletState
+where
+    D: Dispatch<ZwpTabletPadRingV2, TabletPadRingData> + TabletPadHandler,
+{
+    fn event(
+        data: &mut D,
+        ring: &ZwpTabletPadRingV2,
+        event: zwp_tablet_pad_ring_v2::Event,
+        udata: &TabletPadRingData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let mut pending = udata.pending.lock().unwrap();
+
+        match event {
+            zwp_tablet_pad_ring_v2::Event::Source { source } => {
+                pending.source = Some(source);
+            }
+            zwp_tablet_pad_ring_v2::Event::Angle { degrees } => {
+                pending.value = Some(degrees);
+            }
+            zwp_tablet_pad_ring_v2::Event::Stop => {
+                pending.stop = true;
+            }
+            zwp_tablet_pad_ring_v2::Event::Frame { time } => {
+                let PendingAxis { source, value, stop } = mem::take(&mut *pending);
+                drop(pending);
+
+                if let Some(pad) = udata.pad.lock().unwrap().clone() {
+                    data.tablet_pad_event(
+                        conn,
+                        qh,
+                        &pad,
+                        TabletPadEvent::Ring {
+                            time,
+                            ring: ring.clone(),
+                            source,
+                            angle: value,
+                            stop,
+                        },
+                    );
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ZwpTabletPadStripV2, TabletPadStripData, D> for TabletState
+where
+    D: Dispatch<ZwpTabletPadStripV2, TabletPadStripData> + TabletPadHandler,
+{
+    fn event(
+        data: &mut D,
+        strip: &ZwpTabletPadStripV2,
+        event: zwp_tablet_pad_strip_v2::Event,
+        udata: &TabletPadStripData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let mut pending = udata.pending.lock().unwrap();
+
+        match event {
+            zwp_tablet_pad_strip_v2::Event::Source { source } => {
+                pending.source = Some(source);
+            }
+            zwp_tablet_pad_strip_v2::Event::Position { position } => {
+                pending.value = Some(position);
+            }
+            zwp_tablet_pad_strip_v2::Event::Stop => {
+                pending.stop = true;
+            }
+            zwp_tablet_pad_strip_v2::Event::Frame { time } => {
+                let PendingAxis { source, value, stop } = mem::take(&mut *pending);
+                drop(pending);
+
+                if let Some(pad) = udata.pad.lock().unwrap().clone() {
+                    data.tablet_pad_event(
+                        conn,
+                        qh,
+                        &pad,
+                        TabletPadEvent::Strip {
+                            time,
+                            strip: strip.clone(),
+                            source,
+                            position: value,
+                            stop,
+                        },
+                    );
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+}
@@ -0,0 +1,287 @@
+use std::{mem, sync::Mutex};
+
+use cursor_icon::CursorIcon;
+use wayland_client::{
+    protocol::wl_surface::WlSurface, Connection, Dispatch, Proxy, QueueHandle, WEnum,
+};
+use wayland_protocols::wp::{
+    cursor_shape::v1::client::wp_cursor_shape_device_v1::WpCursorShapeDeviceV1,
+    tablet::zv2::client::{
+        zwp_tablet_tool_v2::{self, ButtonState, Capability, Type, ZwpTabletToolV2},
+        zwp_tablet_v2::ZwpTabletV2,
+    },
+};
+
+use crate::{globals::GlobalData, seat::pointer::cursor_shape::cursor_icon_to_shape};
+
+use super::{TabletSeatHandler, TabletState};
+
+/// An error that may occur when setting the cursor of a tool.
+#[derive(Debug, thiserror::Error)]
+pub enum TabletToolCursorError {
+    /// The tool is not in proximity of a surface.
+    #[error("the tool is not in proximity of a surface")]
+    NotInProximity,
+
+    /// The compositor does not support `wp_cursor_shape_manager_v1`.
+    #[error("cursor shapes are not supported by the compositor")]
+    CursorShapeUnsupported,
+}
+
+/// Description of a tool.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToolInfo {
+    /// The type of the tool.
+    pub tool_type: Option<WEnum<Type>>,
+
+    /// The unique serial number of the physical tool.
+    pub hardware_serial: Option<u64>,
+
+    /// The Wacom specific hardware id of the tool.
+    pub hardware_id_wacom: Option<u64>,
+
+    /// The axes supported by the tool, beside position and buttons.
+    pub capabilities: Vec<WEnum<Capability>>,
+}
+
+/// A single tool event.
+///
+/// Axis values are reported only when they change.
+#[derive(Debug, Clone)]
+pub enum TabletToolEvent {
+    /// The tool came in proximity of a surface.
+    ProximityIn { serial: u32, tablet: ZwpTabletV2, surface: WlSurface },
+
+    /// The tool left the proximity of the surface.
+    ProximityOut,
+
+    /// The tool made contact with the tablet.
+    Down { serial: u32 },
+
+    /// The tool stopped making contact with the tablet.
+    Up,
+
+    /// The tool moved, in surface local coordinates.
+    Motion { position: (f64, f64) },
+
+    /// The pressure of the tool, normalized between 0 and 65535.
+    Pressure(u32),
+
+    /// The distance of the tool from the tablet, normalized between 0 and 65535.
+    Distance(u32),
+
+    /// The tilt of the tool from the perpendicular of the tablet, in degrees.
+    Tilt { x: f64, y: f64 },
+
+    /// The rotation of the tool around its axis, in degrees clockwise.
+    Rotation(f64),
+
+    /// The position of the slider of the tool, normalized between -65535 and 65535.
+    Slider(i32),
+
+    /// The wheel of the tool was rotated.
+    Wheel { degrees: f64, clicks: i32 },
+
+    /// A button of the tool was pressed or released.
+    Button { serial: u32, button: u32, state: WEnum<ButtonState> },
+}
+
+pub trait TabletToolHandler: Sized {
+    /// One or more tool events are available.
+    ///
+    /// All the events of a frame describe a single logical hardware event, and should be applied
+    /// together. `time` is the timestamp of the frame in milliseconds.
+    fn tablet_tool_frame(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        tool: &ZwpTabletToolV2,
+        time: u32,
+        events: &[TabletToolEvent],
+    );
+}
+
+impl TabletState {
+    /// Set the cursor of a tool to the given [`CursorIcon`], using the cursor shape protocol.
+    ///
+    /// The cursor should be set on every [`TabletToolEvent::ProximityIn`] event.
+    pub fn set_tool_cursor(
+        &self,
+        tool: &ZwpTabletToolV2,
+        icon: CursorIcon,
+    ) -> Result<(), TabletToolCursorError> {
+        let data = tool.data::<TabletToolData>().ok_or(TabletToolCursorError::NotInProximity)?;
+        let inner = data.inner.lock().unwrap();
+        let serial = inner.proximity_serial.ok_or(TabletToolCursorError::NotInProximity)?;
+        let shape_device =
+            inner.shape_device.as_ref().ok_or(TabletToolCursorError::CursorShapeUnsupported)?;
+        shape_device.set_shape(serial, cursor_icon_to_shape(icon));
+        Ok(())
+    }
+
+    /// Set the cursor of a tool to a surface, or hide it with [`None`].
+    ///
+    /// The hotspot is given in surface local coordinates.
+    pub fn set_tool_cursor_surface(
+        &self,
+        tool: &ZwpTabletToolV2,
+        surface: Option<&WlSurface>,
+        hotspot_x: i32,
+        hotspot_y: i32,
+    ) -> Result<(), TabletToolCursorError> {
+        let serial = tool
+            .data::<TabletToolData>()
+            .and_then(TabletToolData::latest_proximity_serial)
+            .ok_or(TabletToolCursorError::NotInProximity)?;
+        tool.set_cursor(serial, surface, hotspot_x, hotspot_y);
+        Ok(())
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct TabletToolData {
+    inner: Mutex<TabletToolDataInner>,
+}
+
+#[derive(Debug, Default)]
+struct TabletToolDataInner {
+    info: ToolInfo,
+    focus: Option<(ZwpTabletV2, WlSurface)>,
+    proximity_serial: Option<u32>,
+    shape_device: Option<WpCursorShapeDeviceV1>,
+    pending: Vec<TabletToolEvent>,
+}
+
+impl TabletToolData {
+    pub fn info(&self) -> ToolInfo {
+        self.inner.lock().unwrap().info.clone()
+    }
+
+    /// The tablet and surface the tool is in proximity of, if any.
+    pub fn focus(&self) -> Option<(ZwpTabletV2, WlSurface)> {
+        self.inner.lock().unwrap().focus.clone()
+    }
+
+    /// Serial from the latest proximity in event, if the tool is in proximity of a surface.
+    pub fn latest_proximity_serial(&self) -> Option<u32> {
+        self.inner.lock().unwrap().proximity_serial
+    }
+}
+
+pub(super) fn destroy_tool(tool: &ZwpTabletToolV2) {
+    if let Some(data) = tool.data::<TabletToolData>() {
+        if let Some(shape_device) = data.inner.lock().unwrap().shape_device.take() {
+            shape_device.destroy();
+        }
+    }
+    tool.destroy();
+}
+
+impl<D> Dispatch<ZwpTabletToolV2, TabletToolData, D> for TabletState
+where
+    D: Dispatch<ZwpTabletToolV2, TabletToolData>
+        + Dispatch<WpCursorShapeDeviceV1, GlobalData>
+        + TabletSeatHandler
+        + TabletToolHandler
+        + 'static,
+{
+    fn event(
+        data: &mut D,
+        tool: &ZwpTabletToolV2,
+        event: zwp_tablet_tool_v2::Event,
+        udata: &TabletToolData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let mut inner = udata.inner.lock().unwrap();
+
+        let event = match event {
+            zwp_tablet_tool_v2::Event::Type { tool_type } => {
+                inner.info.tool_type = Some(tool_type);
+                return;
+            }
+            zwp_tablet_tool_v2::Event::HardwareSerial {
+                hardware_serial_hi,
+                hardware_serial_lo,
+            } => {
+                inner.info.hardware_serial =
+                    Some((hardware_serial_hi as u64) << 32 | hardware_serial_lo as u64);
+                return;
+            }
+            zwp_tablet_tool_v2::Event::HardwareIdWacom { hardware_id_hi, hardware_id_lo } => {
+                inner.info.hardware_id_wacom =
+                    Some((hardware_id_hi as u64) << 32 | hardware_id_lo as u64);
+                return;
+            }
+            zwp_tablet_tool_v2::Event::Capability { capability } => {
+                inner.info.capabilities.push(capability);
+                return;
+            }
+            zwp_tablet_tool_v2::Event::Done => {
+                if inner.shape_device.is_none() {
+                    inner.shape_device = super::create_shape_device(data.tablet_state(), tool, qh);
+                }
+                drop(inner);
+
+                if let Some(seat) = data.tablet_state().seat_of(|inner| inner.tools.contains(tool))
+                {
+                    data.tool_added(conn, qh, &seat, tool);
+                }
+                return;
+            }
+            zwp_tablet_tool_v2::Event::Removed => {
+                drop(inner);
+
+                if let Some(seat) = data.tablet_state().seat_of(|inner| inner.tools.contains(tool))
+                {
+                    data.tool_removed(conn, qh, &seat, tool);
+                }
+
+                for inner in &mut data.tablet_state().seats {
+                    inner.tools.retain(|t| t != tool);
+                }
+                destroy_tool(tool);
+                return;
+            }
+            zwp_tablet_tool_v2::Event::Frame { time } => {
+                let events = mem::take(&mut inner.pending);
+                drop(inner);
+                data.tablet_tool_frame(conn, qh, tool, time, &events);
+                return;
+            }
+
+            zwp_tablet_tool_v2::Event::ProximityIn { serial, tablet, surface } => {
+                inner.focus = Some((tablet.clone(), surface.clone()));
+                inner.proximity_serial = Some(serial);
+                TabletToolEvent::ProximityIn { serial, tablet, surface }
+            }
+            zwp_tablet_tool_v2::Event::ProximityOut => {
+                inner.focus = None;
+                inner.proximity_serial = None;
+                TabletToolEvent::ProximityOut
+            }
+            zwp_tablet_tool_v2::Event::Down { serial } => TabletToolEvent::Down { serial },
+            zwp_tablet_tool_v2::Event::Up => TabletToolEvent::Up,
+            zwp_tablet_tool_v2::Event::Motion { x, y } => {
+                TabletToolEvent::Motion { position: (x, y) }
+            }
+            zwp_tablet_tool_v2::Event::Pressure { pressure } => TabletToolEvent::Pressure(pressure),
+            zwp_tablet_tool_v2::Event::Distance { distance } => TabletToolEvent::Distance(distance),
+            zwp_tablet_tool_v2::Event::Tilt { tilt_x, tilt_y } => {
+                TabletToolEvent::Tilt { x: tilt_x, y: tilt_y }
+            }
+            zwp_tablet_tool_v2::Event::Rotation { degrees } => TabletToolEvent::Rotation(degrees),
+            zwp_tablet_tool_v2::Event::Slider { position } => TabletToolEvent::Slider(position),
+            zwp_tablet_tool_v2::Event::Wheel { degrees, clicks } => {
+                TabletToolEvent::Wheel { degrees, clicks }
+            }
+            zwp_tablet_tool_v2::Event::Button { serial, button, state } => {
+                TabletToolEvent::Button { serial, button, state }
+            }
+            _ => unreachable!(),
+        };
+
+        inner.pending.push(event);
+    }
+}
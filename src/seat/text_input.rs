@@ -0,0 +1,332 @@
+use std::sync::Mutex;
+
+use wayland_client::{
+    globals::GlobalList,
+    protocol::{wl_seat, wl_surface},
+    Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols::wp::text_input::zv3::client::{
+    zwp_text_input_manager_v3,
+    zwp_text_input_v3::{self, ChangeCause, ContentHint, ContentPurpose},
+};
+
+use crate::{
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+    registry::GlobalProxy,
+};
+
+#[derive(Debug)]
+pub struct TextInputState {
+    text_input_manager: GlobalProxy<zwp_text_input_manager_v3::ZwpTextInputManagerV3>,
+}
+
+impl TextInputState {
+    /// Bind `zwp_text_input_manager_v3` global, if it exists
+    pub fn bind<D>(globals: &GlobalList, qh: &QueueHandle<D>) -> Self
+    where
+        D: Dispatch<zwp_text_input_manager_v3::ZwpTextInputManagerV3, GlobalData> + 'static,
+    {
+        let text_input_manager = GlobalProxy::bind(globals, qh, 1..=1, GlobalData);
+        Self { text_input_manager }
+    }
+
+    /// Create a text input for a seat.
+    ///
+    /// The text input is disabled until [`TextInput::enable`] is called and committed.
+    pub fn get_text_input<D>(
+        &self,
+        seat: &wl_seat::WlSeat,
+        qh: &QueueHandle<D>,
+    ) -> Result<TextInput, GlobalError>
+    where
+        D: Dispatch<zwp_text_input_v3::ZwpTextInputV3, TextInputData> + 'static,
+    {
+        let udata = TextInputData { seat: seat.clone(), inner: Mutex::new(Default::default()) };
+        let text_input = self.text_input_manager.get()?.get_text_input(seat, qh, udata);
+        Ok(TextInput { text_input })
+    }
+}
+
+impl ProvidesBoundGlobal<zwp_text_input_manager_v3::ZwpTextInputManagerV3, 1> for TextInputState {
+    fn bound_global(
+        &self,
+    ) -> Result<zwp_text_input_manager_v3::ZwpTextInputManagerV3, GlobalError> {
+        self.text_input_manager.get().cloned()
+    }
+}
+
+/// A text input for a seat.
+///
+/// Requests changing the state of the text input are double-buffered and only take effect once
+/// [`TextInput::commit`] is called. The text input is destroyed on drop.
+#[derive(Debug)]
+pub struct TextInput {
+    text_input: zwp_text_input_v3::ZwpTextInputV3,
+}
+
+impl TextInput {
+    pub fn text_input(&self) -> &zwp_text_input_v3::ZwpTextInputV3 {
+        &self.text_input
+    }
+
+    pub fn seat(&self) -> &wl_seat::WlSeat {
+        &self.data().seat
+    }
+
+    /// The surface which currently has text input focus, if any.
+    pub fn focus(&self) -> Option<wl_surface::WlSurface> {
+        self.data().focus()
+    }
+
+    /// Request input method support for the focused surface.
+    ///
+    /// This resets all the pending state, so the surrounding text, content type and cursor rectangle
+    /// should be sent again before the next commit.
+    pub fn enable(&self) {
+        self.text_input.enable();
+    }
+
+    /// Disable input method support for the focused surface.
+    pub fn disable(&self) {
+        self.text_input.disable();
+    }
+
+    /// Set the text around the cursor.
+    ///
+    /// `cursor` and `anchor` are byte offsets into `text`, the anchor marking the other end of the selection.
+    /// If there is no selection, `anchor` is the same as `cursor`. `text` must not exceed 4000 bytes, so long
+    /// text should be truncated around the cursor.
+    pub fn set_surrounding_text(&self, text: String, cursor: i32, anchor: i32) {
+        self.text_input.set_surrounding_text(text, cursor, anchor);
+    }
+
+    /// Tell the input method what caused the latest change of the surrounding text.
+    pub fn set_text_change_cause(&self, cause: ChangeCause) {
+        self.text_input.set_text_change_cause(cause);
+    }
+
+    /// Set the type of content being edited, allowing the input method to adapt its behavior.
+    pub fn set_content_type(&self, hint: ContentHint, purpose: ContentPurpose) {
+        self.text_input.set_content_type(hint, purpose);
+    }
+
+    /// Set the area around the cursor in surface local coordinates.
+    ///
+    /// The input method may use this to position its popups, such as a candidate list.
+    pub fn set_cursor_rectangle(&self, x: i32, y: i32, width: i32, height: i32) {
+        self.text_input.set_cursor_rectangle(x, y, width, height);
+    }
+
+    /// Apply the pending state.
+    pub fn commit(&self) {
+        let mut inner = self.data().inner.lock().unwrap();
+        inner.commit_count = inner.commit_count.wrapping_add(1);
+        self.text_input.commit();
+    }
+
+    /// The number of commits made, which the compositor uses as the serial of `done` events.
+    pub fn commit_count(&self) -> u32 {
+        self.data().inner.lock().unwrap().commit_count
+    }
+
+    fn data(&self) -> &TextInputData {
+        self.text_input.data::<TextInputData>().unwrap()
+    }
+}
+
+impl Drop for TextInput {
+    fn drop(&mut self) {
+        self.text_input.destroy();
+    }
+}
+
+/// Text being composed by the input method.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preedit {
+    /// The pre-edit text.
+    pub text: String,
+
+    /// The cursor or selection inside the pre-edit text, as byte offsets.
+    ///
+    /// This is [`None`] when the cursor should be hidden.
+    pub cursor: Option<(i32, i32)>,
+}
+
+/// Text to delete around the cursor, in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeleteSurroundingText {
+    pub before_length: u32,
+    pub after_length: u32,
+}
+
+/// Changes sent by the input method, accumulated until a `done` event.
+///
+/// The changes should be applied in the following order:
+///
+/// 1. Remove the current pre-edit text.
+/// 2. Delete the text around the cursor given by [`delete_surrounding_text`](Self::delete_surrounding_text).
+/// 3. Insert the [`commit_string`](Self::commit_string) at the cursor.
+/// 4. Insert the new [`preedit`](Self::preedit) at the cursor and position the cursor inside it.
+#[non_exhaustive]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextInputUpdate {
+    /// The new pre-edit text, replacing the previous one.
+    ///
+    /// This is [`None`] if there is no pre-edit text anymore.
+    pub preedit: Option<Preedit>,
+
+    /// Text to insert at the cursor.
+    pub commit_string: Option<String>,
+
+    /// Text to delete around the cursor.
+    pub delete_surrounding_text: Option<DeleteSurroundingText>,
+
+    /// The serial of the `done` event.
+    pub serial: u32,
+
+    /// Whether the serial matches the number of commits made by the client.
+    ///
+    /// If this is `false`, the compositor has not seen the latest state sent by the client. The changes
+    /// should still be applied, but the client should wait for an up to date update before sending new state.
+    pub is_current: bool,
+}
+
+pub trait TextInputHandler: Sized {
+    /// The text input gained focus on a surface.
+    fn enter(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        text_input: &zwp_text_input_v3::ZwpTextInputV3,
+        surface: &wl_surface::WlSurface,
+    );
+
+    /// The text input lost focus on a surface.
+    fn leave(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        text_input: &zwp_text_input_v3::ZwpTextInputV3,
+        surface: &wl_surface::WlSurface,
+    );
+
+    /// The input method sent a set of changes which should be applied atomically.
+    fn done(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        text_input: &zwp_text_input_v3::ZwpTextInputV3,
+        update: TextInputUpdate,
+    );
+}
+
+#[derive(Debug)]
+pub struct TextInputData {
+    seat: wl_seat::WlSeat,
+    inner: Mutex<TextInputDataInner>,
+}
+
+#[derive(Debug, Default)]
+struct TextInputDataInner {
+    focus: Option<wl_surface::WlSurface>,
+    pending: TextInputUpdate,
+    commit_count: u32,
+}
+
+impl TextInputData {
+    pub fn seat(&self) -> &wl_seat::WlSeat {
+        &self.seat
+    }
+
+    /// The surface which currently has text input focus, if any.
+    pub fn focus(&self) -> Option<wl_surface::WlSurface> {
+        self.inner.lock().unwrap().focus.clone()
+    }
+}
+
+impl<D> Dispatch<zwp_text_input_manager_v3::ZwpTextInputManagerV3, GlobalData, D> for TextInputState
+where
+    D: Dispatch<zwp_text_input_manager_v3::ZwpTextInputManagerV3, GlobalData>,
+{
+    fn event(
+        _data: &mut D,
+        _manager: &zwp_text_input_manager_v3::ZwpTextInputManagerV3,
+        _event: zwp_text_input_manager_v3::Event,
+        _: &GlobalData,
+        _conn: &Connection,
+        _qh: &QueueHandle<D>,
+    ) {
+        unreachable!()
+    }
+}
+
+impl<D> Dispatch<zwp_text_input_v3::ZwpTextInputV3, TextInputData, D> for TextInputState
+where
+    D: Dispatch<zwp_text_input_v3::ZwpTextInputV3, TextInputData> + TextInputHandler,
+{
+    fn event(
+        data: &mut D,
+        text_input: &zwp_text_input_v3::ZwpTextInputV3,
+        event: zwp_text_input_v3::Event,
+        udata: &TextInputData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let mut inner = udata.inner.lock().unwrap();
+
+        match event {
+            zwp_text_input_v3::Event::Enter { surface } => {
+                inner.focus = Some(surface.clone());
+                drop(inner);
+                data.enter(conn, qh, text_input, &surface);
+            }
+            zwp_text_input_v3::Event::Leave { surface } => {
+                inner.focus = None;
+                drop(inner);
+                data.leave(conn, qh, text_input, &surface);
+            }
+            zwp_text_input_v3::Event::PreeditString { text, cursor_begin, cursor_end } => {
+                inner.pending.preedit = text.map(|text| Preedit {
+                    text,
+                    cursor: if cursor_begin == -1 && cursor_end == -1 {
+                        None
+                    } else {
+                        Some((cursor_begin, cursor_end))
+                    },
+                });
+            }
+            zwp_text_input_v3::Event::CommitString { text } => {
+                inner.pending.commit_string = text;
+            }
+            zwp_text_input_v3::Event::DeleteSurroundingText { before_length, after_length } => {
+                inner.pending.delete_surrounding_text =
+                    Some(DeleteSurroundingText { before_length, after_length });
+            }
+            zwp_text_input_v3::Event::Done { serial } => {
+                let mut update = std::mem::take(&mut inner.pending);
+                update.serial = serial;
+                update.is_current = serial == inner.commit_count;
+                drop(inner);
+                data.done(conn, qh, text_input, update);
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_text_input {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols::wp::text_input::zv3::client::zwp_text_input_manager_v3::ZwpTextInputManagerV3: $crate::globals::GlobalData
+            ] => $crate::seat::text_input::TextInputState
+        );
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols::wp::text_input::zv3::client::zwp_text_input_v3::ZwpTextInputV3: $crate::seat::text_input::TextInputData
+            ] => $crate::seat::text_input::TextInputState
+        );
+    };
+}
@@ -1,4 +1,4 @@
-use std::sync::Mutex;
+use std::{collections::HashMap, mem, sync::Mutex, time::Duration};
 
 use wayland_client::protocol::wl_seat::WlSeat;
 
@@ -6,42 +6,134 @@ use wayland_client::protocol::wl_surface::WlSurface;
 use wayland_client::protocol::wl_touch::{Event as TouchEvent, WlTouch};
 use wayland_client::{Connection, Dispatch, QueueHandle};
 
-use crate::seat::SeatState;
+use crate::seat::{input_timestamps::PendingTimestamp, SeatState};
 
 #[derive(Debug)]
 pub struct TouchData {
     seat: WlSeat,
 
     inner: Mutex<TouchDataInner>,
+
+    pub(crate) timestamp: PendingTimestamp,
 }
 
 impl TouchData {
     /// Create the new touch data associated with the given seat.
     pub fn new(seat: WlSeat) -> Self {
-        Self { seat, inner: Default::default() }
+        Self { seat, inner: Default::default(), timestamp: Default::default() }
     }
 
     /// Get the associated seat from the data.
     pub fn seat(&self) -> &WlSeat {
         &self.seat
     }
+
+    /// The active touch points, by ID.
+    ///
+    /// Points are updated before the [`TouchHandler`] callbacks of a frame run, and removed on touch
+    /// up or cancel.
+    pub fn points(&self) -> HashMap<i32, TouchPoint> {
+        self.inner.lock().unwrap().points.clone()
+    }
+
+    /// The active touch point with the given ID.
+    pub fn point(&self, id: i32) -> Option<TouchPoint> {
+        self.inner.lock().unwrap().points.get(&id).cloned()
+    }
+
+    /// The surface-local position of the active touch point with the given ID.
+    pub fn position(&self, id: i32) -> Option<(f64, f64)> {
+        self.inner.lock().unwrap().points.get(&id).map(|point| point.position)
+    }
+}
+
+/// An active touch point.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TouchPoint {
+    /// The surface the touch sequence started on.
+    pub surface: WlSurface,
+
+    /// The surface-local position.
+    pub position: (f64, f64),
+
+    /// Serial of the touch down event.
+    pub down_serial: u32,
+
+    /// Timestamp of the latest down or motion event, in milliseconds.
+    pub time: u32,
+
+    /// Major and minor axis length of the shape, if reported by the compositor.
+    pub shape: Option<(f64, f64)>,
+
+    /// Orientation of the shape in degrees, if reported by the compositor.
+    pub orientation: Option<f64>,
+
+    /// High-resolution timestamp of the latest down or motion event.
+    ///
+    /// This is only set for touch devices subscribed with
+    /// [`InputTimestampsState::get_touch_timestamps`](crate::seat::input_timestamps::InputTimestampsState::get_touch_timestamps).
+    pub timestamp: Option<Duration>,
 }
 
 #[derive(Debug, Default)]
 pub(crate) struct TouchDataInner {
-    events: Vec<TouchEvent>,
+    /// Events of the current frame, with their high-resolution timestamp.
+    events: Vec<(TouchEvent, Option<Duration>)>,
+    points: HashMap<i32, TouchPoint>,
+}
+
+impl TouchDataInner {
+    fn update_points(&mut self, event: &TouchEvent, timestamp: Option<Duration>) {
+        match event {
+            TouchEvent::Down { serial, time, surface, id, x, y } => {
+                self.points.insert(
+                    *id,
+                    TouchPoint {
+                        surface: surface.clone(),
+                        position: (*x, *y),
+                        down_serial: *serial,
+                        time: *time,
+                        shape: None,
+                        orientation: None,
+                        timestamp,
+                    },
+                );
+            }
+            TouchEvent::Up { id, .. } => {
+                self.points.remove(id);
+            }
+            TouchEvent::Motion { time, id, x, y } => {
+                if let Some(point) = self.points.get_mut(id) {
+                    point.position = (*x, *y);
+                    point.time = *time;
+                    point.timestamp = timestamp;
+                }
+            }
+            TouchEvent::Shape { id, major, minor } => {
+                if let Some(point) = self.points.get_mut(id) {
+                    point.shape = Some((*major, *minor));
+                }
+            }
+            TouchEvent::Orientation { id, orientation } => {
+                if let Some(point) = self.points.get_mut(id) {
+                    point.orientation = Some(*orientation);
+                }
+            }
+            _ => (),
+        }
+    }
 }
 
 #[macro_export]
 macro_rules! delegate_touch {
-    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
-        $crate::delegate_touch!(@{ $(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty }; touch: $crate::seat::touch::TouchData);
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::delegate_touch!(@{ $(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })? }; touch: $crate::seat::touch::TouchData);
     };
-    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty, touch: [$($td:ty),* $(,)?]) => {
-        $crate::delegate_touch!(@{ $(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty }; [ $($td),* ]);
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty, touch: [$($td:ty),* $(,)?] $(where $($bound:tt)+)?) => {
+        $crate::delegate_touch!(@{ $(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })? }; [ $($td),* ]);
     };
     (@{$($ty:tt)*}; touch: $td:ty) => {
-        $crate::reexports::client::delegate_dispatch!($($ty)*:
+        $crate::__delegate_dispatch!($($ty)*:
             [
                 $crate::reexports::client::protocol::wl_touch::WlTouch: $td
             ] => $crate::seat::SeatState
@@ -145,7 +237,8 @@ pub trait TouchHandler: Sized {
     /// Cancel active touch sequence.
     ///
     /// This indicates that the compositor has cancelled the active touch sequence, for example due
-    /// to detection of a touch gesture.
+    /// to detection of a touch gesture. All touch points are already removed from the [`TouchData`]
+    /// when this is called.
     fn cancel(&mut self, conn: &Connection, qh: &QueueHandle<Self>, touch: &WlTouch);
 }
 
@@ -170,19 +263,25 @@ where
             | TouchEvent::Motion { .. }
             | TouchEvent::Shape { .. }
             | TouchEvent::Orientation { .. } => {
+                let timestamp = udata.timestamp.take();
                 let mut guard = udata.inner.lock().unwrap();
-                guard.events.push(event);
+                guard.events.push((event, timestamp));
             }
             // Process all buffered events.
             TouchEvent::Frame => {
-                let mut guard = udata.inner.lock().unwrap();
-                for event in guard.events.drain(..) {
+                let events = mem::take(&mut udata.inner.lock().unwrap().events);
+                for (event, timestamp) in events {
+                    // The lock is not held during the callback, so the handler can query the points.
+                    udata.inner.lock().unwrap().update_points(&event, timestamp);
                     process_framed_event(data, touch, conn, qh, event);
                 }
             }
             TouchEvent::Cancel => {
-                let mut guard = udata.inner.lock().unwrap();
-                guard.events.clear();
+                {
+                    let mut guard = udata.inner.lock().unwrap();
+                    guard.events.clear();
+                    guard.points.clear();
+                }
 
                 data.cancel(conn, qh, touch);
             }
@@ -0,0 +1,334 @@
+use std::{fs::File, io, io::Write, os::unix::io::AsFd};
+
+use wayland_client::{
+    globals::GlobalList,
+    protocol::{wl_keyboard, wl_seat},
+    Connection, Dispatch, QueueHandle,
+};
+use wayland_protocols_misc::zwp_virtual_keyboard_v1::client::{
+    zwp_virtual_keyboard_manager_v1, zwp_virtual_keyboard_v1,
+};
+
+use crate::{
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+    registry::GlobalProxy,
+    shm::raw::RawPool,
+};
+
+/// An error that may occur when using a virtual keyboard.
+#[derive(Debug, thiserror::Error)]
+pub enum VirtualKeyboardError {
+    /// Error while uploading the keymap.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    /// The keymap could not be compiled.
+    #[error("invalid keymap was specified")]
+    InvalidKeymap,
+
+    /// No keymap was set on the virtual keyboard.
+    #[error("no keymap was set")]
+    NoKeymap,
+
+    /// The character is not produced by any key of the keymap.
+    #[error("the character {0:?} can not be typed with the current keymap")]
+    UnmappedCharacter(char),
+}
+
+#[derive(Debug)]
+pub struct VirtualKeyboardState {
+    virtual_keyboard_manager:
+        GlobalProxy<zwp_virtual_keyboard_manager_v1::ZwpVirtualKeyboardManagerV1>,
+}
+
+impl VirtualKeyboardState {
+    /// Bind `zwp_virtual_keyboard_manager_v1` global, if it exists
+    pub fn bind<D>(globals: &GlobalList, qh: &QueueHandle<D>) -> Self
+    where
+        D: Dispatch<zwp_virtual_keyboard_manager_v1::ZwpVirtualKeyboardManagerV1, GlobalData>
+            + 'static,
+    {
+        let virtual_keyboard_manager = GlobalProxy::bind(globals, qh, 1..=1, GlobalData);
+        Self { virtual_keyboard_manager }
+    }
+
+    /// Create a virtual keyboard for a seat.
+    ///
+    /// A keymap must be set with [`VirtualKeyboard::set_keymap`] before sending any key. Compositors may
+    /// refuse to create virtual keyboards for untrusted clients, which results in a protocol error.
+    pub fn create_virtual_keyboard<D>(
+        &self,
+        seat: &wl_seat::WlSeat,
+        qh: &QueueHandle<D>,
+    ) -> Result<VirtualKeyboard, GlobalError>
+    where
+        D: Dispatch<zwp_virtual_keyboard_v1::ZwpVirtualKeyboardV1, GlobalData> + 'static,
+    {
+        let keyboard =
+            self.virtual_keyboard_manager.get()?.create_virtual_keyboard(seat, qh, GlobalData);
+        Ok(VirtualKeyboard { keyboard, keymap: None })
+    }
+}
+
+impl ProvidesBoundGlobal<zwp_virtual_keyboard_manager_v1::ZwpVirtualKeyboardManagerV1, 1>
+    for VirtualKeyboardState
+{
+    fn bound_global(
+        &self,
+    ) -> Result<zwp_virtual_keyboard_manager_v1::ZwpVirtualKeyboardManagerV1, GlobalError> {
+        self.virtual_keyboard_manager.get().cloned()
+    }
+}
+
+/// A virtual keyboard, emulating a physical keyboard of a seat.
+///
+/// The virtual keyboard is destroyed on drop.
+#[derive(Debug)]
+pub struct VirtualKeyboard {
+    keyboard: zwp_virtual_keyboard_v1::ZwpVirtualKeyboardV1,
+    keymap: Option<String>,
+}
+
+impl VirtualKeyboard {
+    pub fn virtual_keyboard(&self) -> &zwp_virtual_keyboard_v1::ZwpVirtualKeyboardV1 {
+        &self.keyboard
+    }
+
+    /// Upload an xkb keymap in the text format.
+    ///
+    /// Keys sent afterwards are interpreted using this keymap.
+    pub fn set_keymap(&mut self, keymap: &str) -> Result<(), VirtualKeyboardError> {
+        #[cfg(feature = "xkbcommon")]
+        {
+            let context = xkbcommon::xkb::Context::new(xkbcommon::xkb::CONTEXT_NO_FLAGS);
+            xkbcommon::xkb::Keymap::new_from_string(
+                &context,
+                keymap.to_owned(),
+                xkbcommon::xkb::KEYMAP_FORMAT_TEXT_V1,
+                xkbcommon::xkb::COMPILE_NO_FLAGS,
+            )
+            .ok_or(VirtualKeyboardError::InvalidKeymap)?;
+        }
+
+        // The keymap is sent with a trailing nul byte, like compositors do.
+        let mut file = File::from(RawPool::create_shm_fd()?);
+        file.write_all(keymap.as_bytes())?;
+        file.write_all(b"\0")?;
+        file.flush()?;
+
+        self.keyboard.keymap(
+            wl_keyboard::KeymapFormat::XkbV1 as u32,
+            file.as_fd(),
+            keymap.len() as u32 + 1,
+        );
+        self.keymap = Some(keymap.to_owned());
+        Ok(())
+    }
+
+    /// Compile a keymap from its RMLVO description and upload it.
+    #[cfg(feature = "xkbcommon")]
+    pub fn set_keymap_from_rmlvo(
+        &mut self,
+        rmlvo: super::keyboard::RMLVO,
+    ) -> Result<(), VirtualKeyboardError> {
+        use xkbcommon::xkb;
+
+        let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
+        let keymap = xkb::Keymap::new_from_names(
+            &context,
+            &rmlvo.rules.unwrap_or_default(),
+            &rmlvo.model.unwrap_or_default(),
+            &rmlvo.layout.unwrap_or_default(),
+            &rmlvo.variant.unwrap_or_default(),
+            rmlvo.options,
+            xkb::COMPILE_NO_FLAGS,
+        )
+        .ok_or(VirtualKeyboardError::InvalidKeymap)?;
+
+        self.set_keymap(&keymap.get_as_string(xkb::KEYMAP_FORMAT_TEXT_V1))
+    }
+
+    /// The raw code of the first key producing the keysym in the keymap, in any layout and level.
+    ///
+    /// The code can be sent with [`VirtualKeyboard::key`].
+    #[cfg(feature = "xkbcommon")]
+    pub fn keycode_for_keysym(
+        &self,
+        keysym: super::keyboard::Keysym,
+    ) -> Result<Option<u32>, VirtualKeyboardError> {
+        use xkbcommon::xkb;
+
+        let keymap = self.keymap.as_ref().ok_or(VirtualKeyboardError::NoKeymap)?;
+        let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
+        let keymap = xkb::Keymap::new_from_string(
+            &context,
+            keymap.clone(),
+            xkb::KEYMAP_FORMAT_TEXT_V1,
+            xkb::COMPILE_NO_FLAGS,
+        )
+        .ok_or(VirtualKeyboardError::InvalidKeymap)?;
+
+        Ok(super::keyboard::Keymap(&keymap).keycode_for_keysym(keysym))
+    }
+
+    /// Whether a keymap was set.
+    pub fn has_keymap(&self) -> bool {
+        self.keymap.is_some()
+    }
+
+    /// Send a key press or release.
+    ///
+    /// `key` is a raw evdev key code, as in [`wl_keyboard::Event::Key`]. `time` is a timestamp in
+    /// milliseconds, which must use the same clock for all events of the virtual keyboard.
+    pub fn key(
+        &self,
+        time: u32,
+        key: u32,
+        state: wl_keyboard::KeyState,
+    ) -> Result<(), VirtualKeyboardError> {
+        if self.keymap.is_none() {
+            return Err(VirtualKeyboardError::NoKeymap);
+        }
+
+        self.keyboard.key(time, key, state.into());
+        Ok(())
+    }
+
+    /// Send the serialized modifier state, as in [`wl_keyboard::Event::Modifiers`].
+    pub fn modifiers(
+        &self,
+        mods_depressed: u32,
+        mods_latched: u32,
+        mods_locked: u32,
+        group: u32,
+    ) -> Result<(), VirtualKeyboardError> {
+        if self.keymap.is_none() {
+            return Err(VirtualKeyboardError::NoKeymap);
+        }
+
+        self.keyboard.modifiers(mods_depressed, mods_latched, mods_locked, group);
+        Ok(())
+    }
+
+    /// Type a string by pressing and releasing the keys producing each character.
+    ///
+    /// Keys are looked up in the current keymap, holding Shift and AltGr when needed. Nothing is sent if
+    /// any character can not be typed with the keymap, in which case
+    /// [`VirtualKeyboardError::UnmappedCharacter`] is returned.
+    #[cfg(feature = "xkbcommon")]
+    pub fn type_str(&self, time: u32, text: &str) -> Result<(), VirtualKeyboardError> {
+        use xkbcommon::xkb;
+
+        let keymap = self.keymap.as_ref().ok_or(VirtualKeyboardError::NoKeymap)?;
+        let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
+        let keymap = xkb::Keymap::new_from_string(
+            &context,
+            keymap.clone(),
+            xkb::KEYMAP_FORMAT_TEXT_V1,
+            xkb::COMPILE_NO_FLAGS,
+        )
+        .ok_or(VirtualKeyboardError::InvalidKeymap)?;
+
+        let mod_mask = |name: &str| match keymap.mod_get_index(name) {
+            xkb::MOD_INVALID => None,
+            index => Some(1 << index),
+        };
+        let shift = mod_mask(xkb::MOD_NAME_SHIFT);
+        let level3 = mod_mask(xkb::MOD_NAME_ISO_LEVEL3_SHIFT);
+        let masks =
+            [Some(0), shift, level3, shift.zip(level3).map(|(shift, level3)| shift | level3)];
+
+        let mut state = xkb::State::new(&keymap);
+        let mut find_key = |keysym: xkb::Keysym| {
+            for mask in masks.iter().flatten().copied() {
+                state.update_mask(mask, 0, 0, 0, 0, 0);
+                for code in keymap.min_keycode().raw()..=keymap.max_keycode().raw() {
+                    if state.key_get_one_sym(code.into()) == keysym {
+                        // Keycodes of the xkb keymap are offset by 8 from evdev key codes.
+                        return Some((code - 8, mask));
+                    }
+                }
+            }
+
+            None
+        };
+
+        let keys = text
+            .chars()
+            .map(|c| {
+                find_key(xkb::utf32_to_keysym(c as u32))
+                    .ok_or(VirtualKeyboardError::UnmappedCharacter(c))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (key, mask) in keys {
+            if mask != 0 {
+                self.keyboard.modifiers(mask, 0, 0, 0);
+            }
+            self.keyboard.key(time, key, wl_keyboard::KeyState::Pressed.into());
+            self.keyboard.key(time, key, wl_keyboard::KeyState::Released.into());
+            if mask != 0 {
+                self.keyboard.modifiers(0, 0, 0, 0);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for VirtualKeyboard {
+    fn drop(&mut self) {
+        self.keyboard.destroy();
+    }
+}
+
+impl<D> Dispatch<zwp_virtual_keyboard_manager_v1::ZwpVirtualKeyboardManagerV1, GlobalData, D>
+    for VirtualKeyboardState
+where
+    D: Dispatch<zwp_virtual_keyboard_manager_v1::ZwpVirtualKeyboardManagerV1, GlobalData>,
+{
+    fn event(
+        _data: &mut D,
+        _manager: &zwp_virtual_keyboard_manager_v1::ZwpVirtualKeyboardManagerV1,
+        _event: zwp_virtual_keyboard_manager_v1::Event,
+        _: &GlobalData,
+        _conn: &Connection,
+        _qh: &QueueHandle<D>,
+    ) {
+        unreachable!()
+    }
+}
+
+impl<D> Dispatch<zwp_virtual_keyboard_v1::ZwpVirtualKeyboardV1, GlobalData, D>
+    for VirtualKeyboardState
+where
+    D: Dispatch<zwp_virtual_keyboard_v1::ZwpVirtualKeyboardV1, GlobalData>,
+{
+    fn event(
+        _data: &mut D,
+        _keyboard: &zwp_virtual_keyboard_v1::ZwpVirtualKeyboardV1,
+        _event: zwp_virtual_keyboard_v1::Event,
+        _: &GlobalData,
+        _conn: &Connection,
+        _qh: &QueueHandle<D>,
+    ) {
+        unreachable!()
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_virtual_keyboard {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols_misc::zwp_virtual_keyboard_v1::client::zwp_virtual_keyboard_manager_v1::ZwpVirtualKeyboardManagerV1: $crate::globals::GlobalData
+            ] => $crate::seat::virtual_keyboard::VirtualKeyboardState
+        );
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?:
+            [
+                $crate::reexports::protocols_misc::zwp_virtual_keyboard_v1::client::zwp_virtual_keyboard_v1::ZwpVirtualKeyboardV1: $crate::globals::GlobalData
+            ] => $crate::seat::virtual_keyboard::VirtualKeyboardState
+        );
+    };
+}
@@ -0,0 +1,208 @@
+//! ## Security context
+//!
+//! The `wp_security_context_manager_v1` global is used by sandbox launchers, such as Flatpak, to let
+//! the compositor identify the clients running in a sandbox. The launcher creates a listening socket,
+//! attaches metadata to it with a [`SecurityContext`], and makes the sandboxed client connect to the
+//! compositor through this socket.
+//!
+//! [`SecurityContextState::create_listener_socket`] creates the socket in `XDG_RUNTIME_DIR`, whose path
+//! may be used as `WAYLAND_DISPLAY` by the sandboxed client.
+
+use std::{
+    env, io,
+    os::unix::{
+        io::{AsFd, OwnedFd},
+        net::UnixListener,
+    },
+    path::{Path, PathBuf},
+};
+
+use rustix::pipe::{pipe_with, PipeFlags};
+use wayland_client::{
+    globals::{BindError, GlobalList},
+    Connection, Dispatch, QueueHandle,
+};
+use wayland_protocols::wp::security_context::v1::client::{
+    wp_security_context_manager_v1, wp_security_context_v1,
+};
+
+use crate::globals::GlobalData;
+
+/// The security context manager global.
+#[derive(Debug)]
+pub struct SecurityContextState {
+    manager: wp_security_context_manager_v1::WpSecurityContextManagerV1,
+}
+
+impl SecurityContextState {
+    /// Binds the `wp_security_context_manager_v1` global.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support security contexts, or
+    /// does not expose them to this client, which is the case for clients already in a sandbox.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State: Dispatch<wp_security_context_manager_v1::WpSecurityContextManagerV1, GlobalData>
+            + 'static,
+    {
+        let manager = globals.bind(qh, 1..=1, GlobalData)?;
+        Ok(Self { manager })
+    }
+
+    /// Create a security context for clients connecting to a listening socket.
+    ///
+    /// The compositor stops listening on the socket once `close_fd` becomes readable, usually when the
+    /// write end of a pipe whose read end is `close_fd` is closed.
+    pub fn create_listener<D>(
+        &self,
+        listen_fd: OwnedFd,
+        close_fd: OwnedFd,
+        qh: &QueueHandle<D>,
+    ) -> SecurityContext
+    where
+        D: Dispatch<wp_security_context_v1::WpSecurityContextV1, GlobalData> + 'static,
+    {
+        let context =
+            self.manager.create_listener(listen_fd.as_fd(), close_fd.as_fd(), qh, GlobalData);
+        SecurityContext { context }
+    }
+
+    /// Create a listening socket named `name` in `XDG_RUNTIME_DIR`, and a security context for the
+    /// clients connecting to it.
+    ///
+    /// The compositor stops listening once the returned [`SecurityContextSocket`] is dropped.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if `XDG_RUNTIME_DIR` is not set, or the socket or the pipe
+    /// closing it could not be created.
+    pub fn create_listener_socket<D>(
+        &self,
+        name: &str,
+        qh: &QueueHandle<D>,
+    ) -> io::Result<(SecurityContext, SecurityContextSocket)>
+    where
+        D: Dispatch<wp_security_context_v1::WpSecurityContextV1, GlobalData> + 'static,
+    {
+        let runtime_dir = env::var_os("XDG_RUNTIME_DIR")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "XDG_RUNTIME_DIR is not set"))?;
+        let path = Path::new(&runtime_dir).join(name);
+
+        let listener = UnixListener::bind(&path)?;
+        // Removes the socket if the pipe can not be created.
+        let mut socket = SecurityContextSocket { path, close_fd: None };
+        let (close_fd, close_fd_write) = pipe_with(PipeFlags::CLOEXEC)?;
+
+        let context = self.create_listener(listener.into(), close_fd, qh);
+        socket.close_fd = Some(close_fd_write);
+        Ok((context, socket))
+    }
+}
+
+/// Builder for the metadata of a security context.
+///
+/// The compositor only starts listening once the context is committed.
+#[derive(Debug)]
+pub struct SecurityContext {
+    context: wp_security_context_v1::WpSecurityContextV1,
+}
+
+impl SecurityContext {
+    /// Set the name of the sandbox engine, such as `org.flatpak`.
+    ///
+    /// It is a protocol error to set it more than once.
+    pub fn set_sandbox_engine(&self, name: &str) {
+        self.context.set_sandbox_engine(name.to_owned());
+    }
+
+    /// Set the application id of the sandboxed clients, as understood by the sandbox engine.
+    ///
+    /// It is a protocol error to set it more than once.
+    pub fn set_app_id(&self, app_id: &str) {
+        self.context.set_app_id(app_id.to_owned());
+    }
+
+    /// Set the id of this instance of the sandbox, as understood by the sandbox engine.
+    ///
+    /// It is a protocol error to set it more than once.
+    pub fn set_instance_id(&self, instance_id: &str) {
+        self.context.set_instance_id(instance_id.to_owned());
+    }
+
+    /// Attach the metadata to the listening socket, and start listening.
+    pub fn commit(self) {
+        self.context.commit();
+        self.context.destroy();
+    }
+}
+
+/// A listening socket created by [`SecurityContextState::create_listener_socket`].
+///
+/// Dropping it makes the compositor stop listening, and removes the socket.
+#[derive(Debug)]
+pub struct SecurityContextSocket {
+    path: PathBuf,
+    /// The write end of the pipe closing the socket.
+    close_fd: Option<OwnedFd>,
+}
+
+impl SecurityContextSocket {
+    /// The path of the socket, which may be used as `WAYLAND_DISPLAY`.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for SecurityContextSocket {
+    fn drop(&mut self) {
+        drop(self.close_fd.take());
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+impl<D> Dispatch<wp_security_context_manager_v1::WpSecurityContextManagerV1, GlobalData, D>
+    for SecurityContextState
+where
+    D: Dispatch<wp_security_context_manager_v1::WpSecurityContextManagerV1, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &wp_security_context_manager_v1::WpSecurityContextManagerV1,
+        _: wp_security_context_manager_v1::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("wp_security_context_manager_v1 has no events")
+    }
+}
+
+impl<D> Dispatch<wp_security_context_v1::WpSecurityContextV1, GlobalData, D>
+    for SecurityContextState
+where
+    D: Dispatch<wp_security_context_v1::WpSecurityContextV1, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &wp_security_context_v1::WpSecurityContextV1,
+        _: wp_security_context_v1::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("wp_security_context_v1 has no events")
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_security_context {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::security_context::v1::client::wp_security_context_manager_v1::WpSecurityContextManagerV1: $crate::globals::GlobalData
+        ] => $crate::security_context::SecurityContextState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::security_context::v1::client::wp_security_context_v1::WpSecurityContextV1: $crate::globals::GlobalData
+        ] => $crate::security_context::SecurityContextState);
+    };
+}
@@ -0,0 +1,425 @@
+//! ## Session management
+//!
+//! The experimental `xx_session_manager_v1` global lets the compositor restore the state of
+//! toplevels, such as their position and size, when an application is started again, even after
+//! the compositor itself was restarted.
+//!
+//! A client gets a [`Session`] with [`SessionState::get_session`], passing the id of the previous
+//! session if it has one. The compositor answers with [`SessionHandler::created`] and a new id the
+//! client should store to restore the session later, or with [`SessionHandler::restored`]. Windows
+//! are then associated with the session by a name unique within it, with
+//! [`Session::restore_toplevel`] before their initial commit to get their stored state back, or
+//! with [`Session::add_toplevel`] to start storing it.
+//!
+//! [`SessionState::bind`] does not fail if the compositor does not support session management, in
+//! which case [`SessionState::get_session`] returns an error and windows are simply placed by the
+//! compositor as usual.
+//!
+//! This protocol is not yet part of `wayland-protocols` and may change in incompatible ways, so
+//! this module requires the `unstable-protocols` feature.
+
+use std::sync::{Arc, Mutex, Weak};
+
+use wayland_client::{globals::GlobalList, Connection, Dispatch, Proxy, QueueHandle};
+
+use crate::{
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+    registry::GlobalProxy,
+    shell::xdg::window::Window,
+};
+
+use self::protocol::{xx_session_manager_v1, xx_session_v1, xx_toplevel_session_v1};
+
+pub use self::protocol::xx_session_manager_v1::Reason as SessionReason;
+
+/// Client side code of the `xx-session-management-v1` protocol.
+#[allow(
+    dead_code,
+    non_camel_case_types,
+    unused_unsafe,
+    unused_variables,
+    non_upper_case_globals,
+    non_snake_case,
+    unused_imports,
+    missing_docs,
+    clippy::all
+)]
+pub mod protocol {
+    use wayland_client;
+    use wayland_client::protocol::*;
+    use wayland_protocols::xdg::shell::client::*;
+
+    #[doc(hidden)]
+    pub mod __interfaces {
+        use wayland_client::protocol::__interfaces::*;
+        use wayland_protocols::xdg::shell::client::__interfaces::*;
+        wayland_scanner::generate_interfaces!("protocols/xx-session-management-v1.xml");
+    }
+    use self::__interfaces::*;
+
+    wayland_scanner::generate_client_code!("protocols/xx-session-management-v1.xml");
+}
+
+/// Handler for session management.
+pub trait SessionHandler: Sized {
+    /// A new session was created, because no id was given or the session could not be restored.
+    ///
+    /// The client should store `id` and pass it to [`SessionState::get_session`] the next time it
+    /// is started.
+    fn created(&mut self, conn: &Connection, qh: &QueueHandle<Self>, session: Session, id: String);
+
+    /// The session with the id given to [`SessionState::get_session`] was restored.
+    fn restored(&mut self, conn: &Connection, qh: &QueueHandle<Self>, session: Session);
+
+    /// The session was taken over by another client.
+    ///
+    /// The session and its toplevels are no longer managed and should be dropped.
+    fn replaced(&mut self, conn: &Connection, qh: &QueueHandle<Self>, session: Session);
+
+    /// The stored state of a toplevel was restored, before its initial configure.
+    fn toplevel_restored(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        toplevel_session: ToplevelSession,
+    ) {
+        let _ = (conn, qh, toplevel_session);
+    }
+}
+
+/// The session manager global.
+#[derive(Debug)]
+pub struct SessionState {
+    session_manager: GlobalProxy<xx_session_manager_v1::XxSessionManagerV1>,
+}
+
+impl SessionState {
+    /// Bind the `xx_session_manager_v1` global, if it exists.
+    pub fn bind<D>(globals: &GlobalList, qh: &QueueHandle<D>) -> Self
+    where
+        D: Dispatch<xx_session_manager_v1::XxSessionManagerV1, GlobalData> + 'static,
+    {
+        let session_manager = GlobalProxy::bind(globals, qh, 1..=1, GlobalData);
+        Self { session_manager }
+    }
+
+    /// Whether the compositor supports session management.
+    pub fn is_available(&self) -> bool {
+        self.session_manager.get().is_ok()
+    }
+
+    /// Create a new session, or restore the session with the given `id`.
+    ///
+    /// The compositor answers with [`SessionHandler::created`] or [`SessionHandler::restored`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support session management.
+    ///
+    /// # Protocol errors
+    ///
+    /// A session may only be used by one [`Session`] at a time.
+    pub fn get_session<D>(
+        &self,
+        reason: SessionReason,
+        id: Option<&str>,
+        qh: &QueueHandle<D>,
+    ) -> Result<Session, GlobalError>
+    where
+        D: Dispatch<xx_session_v1::XxSessionV1, SessionData> + 'static,
+    {
+        let session_manager = self.session_manager.get()?;
+
+        // Freeze the queue during the creation of the Arc to avoid a race between events on the
+        // new objects being processed and the Weak in the SessionData becoming usable.
+        let freeze = qh.freeze();
+
+        let inner = Arc::new_cyclic(|weak| {
+            let session = session_manager.get_session(
+                reason,
+                id.map(str::to_owned),
+                qh,
+                SessionData { inner: weak.clone() },
+            );
+
+            SessionInner { session, id: Mutex::new(id.map(str::to_owned)) }
+        });
+        drop(freeze);
+
+        Ok(Session(inner))
+    }
+}
+
+impl ProvidesBoundGlobal<xx_session_manager_v1::XxSessionManagerV1, 1> for SessionState {
+    fn bound_global(&self) -> Result<xx_session_manager_v1::XxSessionManagerV1, GlobalError> {
+        self.session_manager.get().cloned()
+    }
+}
+
+#[derive(Debug)]
+struct SessionInner {
+    session: xx_session_v1::XxSessionV1,
+    id: Mutex<Option<String>>,
+}
+
+impl Drop for SessionInner {
+    fn drop(&mut self) {
+        self.session.destroy();
+    }
+}
+
+/// A session of the application.
+///
+/// The session is destroyed when the last clone is dropped, but stays stored by the compositor so
+/// it can be restored later, unless [`Session::remove`] is used.
+#[must_use]
+#[derive(Debug, Clone)]
+pub struct Session(Arc<SessionInner>);
+
+impl Session {
+    pub fn from_xx_session(session: &xx_session_v1::XxSessionV1) -> Option<Self> {
+        session.data::<SessionData>().and_then(|data| data.inner.upgrade()).map(Self)
+    }
+
+    pub fn xx_session(&self) -> &xx_session_v1::XxSessionV1 {
+        &self.0.session
+    }
+
+    /// The id of the session, to restore it later.
+    ///
+    /// This is the id given to [`SessionState::get_session`] until the compositor creates a new
+    /// session.
+    pub fn id(&self) -> Option<String> {
+        self.0.id.lock().unwrap().clone()
+    }
+
+    /// Start storing the state of a window in the session, under the given name.
+    ///
+    /// This replaces any state previously stored under this name.
+    ///
+    /// # Protocol errors
+    ///
+    /// The name must not already be used by another toplevel of the session.
+    pub fn add_toplevel<D>(
+        &self,
+        window: &Window,
+        name: impl Into<String>,
+        qh: &QueueHandle<D>,
+    ) -> ToplevelSession
+    where
+        D: Dispatch<xx_toplevel_session_v1::XxToplevelSessionV1, ToplevelSessionData> + 'static,
+    {
+        self.create_toplevel_session(window, qh, |data| {
+            self.0.session.add_toplevel(window.xdg_toplevel(), name.into(), qh, data)
+        })
+    }
+
+    /// Restore the state of a window stored in the session under the given name, and keep storing
+    /// it.
+    ///
+    /// If the state is restored, [`SessionHandler::toplevel_restored`] is called before the initial
+    /// configure of the window.
+    ///
+    /// # Protocol errors
+    ///
+    /// This must be called before the initial commit of the window, and the name must not already
+    /// be used by another toplevel of the session.
+    pub fn restore_toplevel<D>(
+        &self,
+        window: &Window,
+        name: impl Into<String>,
+        qh: &QueueHandle<D>,
+    ) -> ToplevelSession
+    where
+        D: Dispatch<xx_toplevel_session_v1::XxToplevelSessionV1, ToplevelSessionData> + 'static,
+    {
+        self.create_toplevel_session(window, qh, |data| {
+            self.0.session.restore_toplevel(window.xdg_toplevel(), name.into(), qh, data)
+        })
+    }
+
+    fn create_toplevel_session<D>(
+        &self,
+        window: &Window,
+        qh: &QueueHandle<D>,
+        create: impl FnOnce(ToplevelSessionData) -> xx_toplevel_session_v1::XxToplevelSessionV1,
+    ) -> ToplevelSession
+    where
+        D: Dispatch<xx_toplevel_session_v1::XxToplevelSessionV1, ToplevelSessionData> + 'static,
+    {
+        // Freeze the queue during the creation of the Arc to avoid a race between events on the
+        // new objects being processed and the Weak in the ToplevelSessionData becoming usable.
+        let freeze = qh.freeze();
+        let inner = Arc::new_cyclic(|weak| {
+            let toplevel_session = create(ToplevelSessionData { inner: weak.clone() });
+            ToplevelSessionInner { toplevel_session, window: window.clone() }
+        });
+        drop(freeze);
+
+        ToplevelSession(inner)
+    }
+
+    /// Remove the session from the storage of the compositor, so it can no longer be restored.
+    ///
+    /// This destroys the session, other clones of it become inert.
+    pub fn remove(&self) {
+        self.0.session.remove();
+    }
+}
+
+impl PartialEq for Session {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.session == other.0.session
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct SessionData {
+    inner: Weak<SessionInner>,
+}
+
+#[derive(Debug)]
+struct ToplevelSessionInner {
+    toplevel_session: xx_toplevel_session_v1::XxToplevelSessionV1,
+    window: Window,
+}
+
+impl Drop for ToplevelSessionInner {
+    fn drop(&mut self) {
+        self.toplevel_session.destroy();
+    }
+}
+
+/// A window associated with a [`Session`].
+///
+/// Its state keeps being stored until the last clone is dropped, which should happen before the
+/// window is destroyed.
+#[must_use]
+#[derive(Debug, Clone)]
+pub struct ToplevelSession(Arc<ToplevelSessionInner>);
+
+impl ToplevelSession {
+    pub fn from_xx_toplevel_session(
+        toplevel_session: &xx_toplevel_session_v1::XxToplevelSessionV1,
+    ) -> Option<Self> {
+        toplevel_session
+            .data::<ToplevelSessionData>()
+            .and_then(|data| data.inner.upgrade())
+            .map(Self)
+    }
+
+    pub fn xx_toplevel_session(&self) -> &xx_toplevel_session_v1::XxToplevelSessionV1 {
+        &self.0.toplevel_session
+    }
+
+    /// The window of this toplevel session.
+    pub fn window(&self) -> &Window {
+        &self.0.window
+    }
+
+    /// Remove the window and its stored state from the session.
+    ///
+    /// This destroys the toplevel session, other clones of it become inert.
+    pub fn remove(&self) {
+        self.0.toplevel_session.remove();
+    }
+}
+
+impl PartialEq for ToplevelSession {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.toplevel_session == other.0.toplevel_session
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct ToplevelSessionData {
+    inner: Weak<ToplevelSessionInner>,
+}
+
+impl<D> Dispatch<xx_session_manager_v1::XxSessionManagerV1, GlobalData, D> for SessionState
+where
+    D: Dispatch<xx_session_manager_v1::XxSessionManagerV1, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &xx_session_manager_v1::XxSessionManagerV1,
+        _: xx_session_manager_v1::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("xx_session_manager_v1 has no events")
+    }
+}
+
+impl<D> Dispatch<xx_session_v1::XxSessionV1, SessionData, D> for SessionState
+where
+    D: Dispatch<xx_session_v1::XxSessionV1, SessionData> + SessionHandler,
+{
+    fn event(
+        data: &mut D,
+        _: &xx_session_v1::XxSessionV1,
+        event: xx_session_v1::Event,
+        session_data: &SessionData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let session = match session_data.inner.upgrade() {
+            Some(inner) => Session(inner),
+            None => return,
+        };
+
+        match event {
+            xx_session_v1::Event::Created { id } => {
+                *session.0.id.lock().unwrap() = Some(id.clone());
+                data.created(conn, qh, session, id);
+            }
+            xx_session_v1::Event::Restored => data.restored(conn, qh, session),
+            xx_session_v1::Event::Replaced => data.replaced(conn, qh, session),
+        }
+    }
+}
+
+impl<D> Dispatch<xx_toplevel_session_v1::XxToplevelSessionV1, ToplevelSessionData, D>
+    for SessionState
+where
+    D: Dispatch<xx_toplevel_session_v1::XxToplevelSessionV1, ToplevelSessionData> + SessionHandler,
+{
+    fn event(
+        data: &mut D,
+        _: &xx_toplevel_session_v1::XxToplevelSessionV1,
+        event: xx_toplevel_session_v1::Event,
+        toplevel_session_data: &ToplevelSessionData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let toplevel_session = match toplevel_session_data.inner.upgrade() {
+            Some(inner) => ToplevelSession(inner),
+            None => return,
+        };
+
+        match event {
+            xx_toplevel_session_v1::Event::Restored { .. } => {
+                data.toplevel_restored(conn, qh, toplevel_session);
+            }
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_session_management {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::session_management::protocol::xx_session_manager_v1::XxSessionManagerV1: $crate::globals::GlobalData
+        ] => $crate::session_management::SessionState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::session_management::protocol::xx_session_v1::XxSessionV1: $crate::session_management::SessionData
+        ] => $crate::session_management::SessionState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::session_management::protocol::xx_toplevel_session_v1::XxToplevelSessionV1: $crate::session_management::ToplevelSessionData
+        ] => $crate::session_management::SessionState);
+    };
+}
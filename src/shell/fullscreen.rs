@@ -0,0 +1,225 @@
+//! ## Fullscreen shell
+//!
+//! The `zwp_fullscreen_shell_v1` global is exposed by kiosk compositors, often instead of the xdg shell.
+//! Surfaces are presented fullscreen on an output, without any window management.
+//!
+//! A surface is shown with [`FullscreenShellState::present_surface`], which lets the compositor scale
+//! or center it, or with [`FullscreenShellState::present_surface_for_mode`], which asks the compositor
+//! to switch the output to a mode matching the surface. The result of a mode switch is reported to
+//! [`FullscreenShellHandler`].
+
+use wayland_client::{
+    globals::{BindError, GlobalList},
+    protocol::{wl_output, wl_surface},
+    Connection, Dispatch, QueueHandle, WEnum,
+};
+use wayland_protocols::wp::fullscreen_shell::zv1::client::{
+    zwp_fullscreen_shell_mode_feedback_v1, zwp_fullscreen_shell_v1,
+};
+
+use crate::{
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+};
+
+pub use zwp_fullscreen_shell_v1::PresentMethod;
+
+/// Handler for the fullscreen shell.
+pub trait FullscreenShellHandler: Sized {
+    fn fullscreen_shell_state(&mut self) -> &mut FullscreenShellState;
+
+    /// The output switched to a mode matching the surface, which is now presented.
+    fn mode_successful(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        surface: &wl_surface::WlSurface,
+    );
+
+    /// The output could not switch to a mode matching the surface, which is not presented.
+    fn mode_failed(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        surface: &wl_surface::WlSurface,
+    );
+
+    /// Another surface was presented on the output before the mode switch completed.
+    fn present_cancelled(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        surface: &wl_surface::WlSurface,
+    );
+}
+
+/// The fullscreen shell global.
+#[derive(Debug)]
+pub struct FullscreenShellState {
+    fullscreen_shell: zwp_fullscreen_shell_v1::ZwpFullscreenShellV1,
+    arbitrary_modes: bool,
+    cursor_plane: bool,
+}
+
+impl FullscreenShellState {
+    /// Binds the `zwp_fullscreen_shell_v1` global.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support the fullscreen shell.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State: Dispatch<zwp_fullscreen_shell_v1::ZwpFullscreenShellV1, GlobalData>
+            + FullscreenShellHandler
+            + 'static,
+    {
+        let fullscreen_shell = globals.bind(qh, 1..=1, GlobalData)?;
+        Ok(Self { fullscreen_shell, arbitrary_modes: false, cursor_plane: false })
+    }
+
+    /// Whether the compositor can set arbitrary modes on outputs, rather than only the modes of the
+    /// output, for [`FullscreenShellState::present_surface_for_mode`].
+    ///
+    /// The capabilities are sent by the compositor right after the global is bound.
+    pub fn arbitrary_modes(&self) -> bool {
+        self.arbitrary_modes
+    }
+
+    /// Whether the compositor has a cursor plane, so cursor surfaces are shown without compositing.
+    ///
+    /// The capabilities are sent by the compositor right after the global is bound.
+    pub fn cursor_plane(&self) -> bool {
+        self.cursor_plane
+    }
+
+    /// Present a surface on an output, or on an output chosen by the compositor.
+    ///
+    /// The method tells the compositor how to show a surface whose size does not match the output.
+    /// Passing [`None`] as the surface hides the surface presented on the output.
+    pub fn present_surface(
+        &self,
+        surface: Option<&wl_surface::WlSurface>,
+        method: PresentMethod,
+        output: Option<&wl_output::WlOutput>,
+    ) {
+        self.fullscreen_shell.present_surface(surface, method, output);
+    }
+
+    /// Present a surface on an output, switching the output to a mode matching the size of the surface
+    /// and a refresh rate in mHz, or 0 to let the compositor choose it.
+    ///
+    /// The result of the mode switch is reported to [`FullscreenShellHandler`], after the surface is
+    /// committed with a buffer.
+    pub fn present_surface_for_mode<D>(
+        &self,
+        surface: &wl_surface::WlSurface,
+        output: &wl_output::WlOutput,
+        framerate: i32,
+        qh: &QueueHandle<D>,
+    ) where
+        D: Dispatch<
+                zwp_fullscreen_shell_mode_feedback_v1::ZwpFullscreenShellModeFeedbackV1,
+                FullscreenModeFeedbackData,
+            > + 'static,
+    {
+        let data = FullscreenModeFeedbackData { surface: surface.clone() };
+        self.fullscreen_shell.present_surface_for_mode(surface, output, framerate, qh, data);
+    }
+}
+
+impl ProvidesBoundGlobal<zwp_fullscreen_shell_v1::ZwpFullscreenShellV1, 1>
+    for FullscreenShellState
+{
+    fn bound_global(&self) -> Result<zwp_fullscreen_shell_v1::ZwpFullscreenShellV1, GlobalError> {
+        Ok(self.fullscreen_shell.clone())
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct FullscreenModeFeedbackData {
+    surface: wl_surface::WlSurface,
+}
+
+impl<D> Dispatch<zwp_fullscreen_shell_v1::ZwpFullscreenShellV1, GlobalData, D>
+    for FullscreenShellState
+where
+    D: Dispatch<zwp_fullscreen_shell_v1::ZwpFullscreenShellV1, GlobalData> + FullscreenShellHandler,
+{
+    fn event(
+        state: &mut D,
+        _: &zwp_fullscreen_shell_v1::ZwpFullscreenShellV1,
+        event: zwp_fullscreen_shell_v1::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        match event {
+            zwp_fullscreen_shell_v1::Event::Capability { capability } => {
+                let fullscreen_shell_state = state.fullscreen_shell_state();
+                match capability {
+                    WEnum::Value(zwp_fullscreen_shell_v1::Capability::ArbitraryModes) => {
+                        fullscreen_shell_state.arbitrary_modes = true;
+                    }
+                    WEnum::Value(zwp_fullscreen_shell_v1::Capability::CursorPlane) => {
+                        fullscreen_shell_state.cursor_plane = true;
+                    }
+                    _ => (),
+                }
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D>
+    Dispatch<
+        zwp_fullscreen_shell_mode_feedback_v1::ZwpFullscreenShellModeFeedbackV1,
+        FullscreenModeFeedbackData,
+        D,
+    > for FullscreenShellState
+where
+    D: Dispatch<
+            zwp_fullscreen_shell_mode_feedback_v1::ZwpFullscreenShellModeFeedbackV1,
+            FullscreenModeFeedbackData,
+        > + FullscreenShellHandler,
+{
+    fn event(
+        state: &mut D,
+        _: &zwp_fullscreen_shell_mode_feedback_v1::ZwpFullscreenShellModeFeedbackV1,
+        event: zwp_fullscreen_shell_mode_feedback_v1::Event,
+        data: &FullscreenModeFeedbackData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        // The feedback object is destroyed by the compositor after any of its events.
+        match event {
+            zwp_fullscreen_shell_mode_feedback_v1::Event::ModeSuccessful => {
+                state.mode_successful(conn, qh, &data.surface);
+            }
+
+            zwp_fullscreen_shell_mode_feedback_v1::Event::ModeFailed => {
+                state.mode_failed(conn, qh, &data.surface);
+            }
+
+            zwp_fullscreen_shell_mode_feedback_v1::Event::PresentCancelled => {
+                state.present_cancelled(conn, qh, &data.surface);
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_fullscreen_shell {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::fullscreen_shell::zv1::client::zwp_fullscreen_shell_v1::ZwpFullscreenShellV1: $crate::globals::GlobalData
+        ] => $crate::shell::fullscreen::FullscreenShellState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::fullscreen_shell::zv1::client::zwp_fullscreen_shell_mode_feedback_v1::ZwpFullscreenShellModeFeedbackV1: $crate::shell::fullscreen::FullscreenModeFeedbackData
+        ] => $crate::shell::fullscreen::FullscreenShellState);
+    };
+}
@@ -33,6 +33,11 @@
 //! The layer shell may be used to implement many desktop shell components, such as backgrounds, docks and
 //! launchers.
 //!
+//! ## Fullscreen shell
+//!
+//! Kiosk and embedded compositors may only expose the fullscreen shell, which presents surfaces fullscreen
+//! on an output. See the [`fullscreen`] module.
+//!
 //! [^window]: The XDG shell protocol actually refers to a window as a toplevel surface, but we use the more
 //! familiar term "window" for the sake of clarity.
 //!
@@ -42,11 +47,17 @@
 //!
 //! [`Layer`]: self::layer::LayerSurface
 
+use std::num::NonZeroU32;
+
 use wayland_client::{
-    protocol::{wl_buffer, wl_output, wl_region, wl_surface},
-    Proxy,
+    protocol::{wl_buffer, wl_callback, wl_output, wl_surface},
+    Dispatch, Proxy, QueueHandle,
 };
 
+use crate::compositor::{Region, SurfaceData};
+
+pub mod fullscreen;
+pub mod plasma;
 pub mod wlr_layer;
 pub mod xdg;
 
@@ -74,34 +85,139 @@ pub trait WaylandSurface: Sized {
 
     // TODO: Damage (Buffer and Surface-local)
 
-    // TODO: Frame (a nice helper for this could exist).
+    /// Requests a frame callback, unless one was already requested since the last commit.
+    ///
+    /// The callback is delivered to [`CompositorHandler::frame`](crate::compositor::CompositorHandler::frame)
+    /// once the compositor would like a new frame, after the next commit. Requesting a callback once per
+    /// commit avoids drawing twice for the same frame, for example when both a resize and the drawing code
+    /// request one.
+    ///
+    /// Returns `true` if a new callback was requested.
+    ///
+    /// The requested callback is forgotten by [`WaylandSurface::commit`], so a callback which never fires,
+    /// for example on an occluded surface, does not prevent requesting one for the next commit. The pending
+    /// callback is only tracked for surfaces created with [`SurfaceData`] as their user data, other
+    /// surfaces always get a new callback.
+    fn request_frame<D>(&self, qh: &QueueHandle<D>) -> bool
+    where
+        D: Dispatch<wl_callback::WlCallback, wl_surface::WlSurface> + 'static,
+    {
+        let surface = self.wl_surface();
+        match surface.data::<SurfaceData>() {
+            Some(data) => data.request_frame(surface, qh),
+            None => {
+                surface.frame(qh, surface.clone());
+                true
+            }
+        }
+    }
 
-    fn set_opaque_region(&self, region: Option<&wl_region::WlRegion>) {
-        self.wl_surface().set_opaque_region(region);
+    /// Whether a frame callback was requested with [`WaylandSurface::request_frame`] since the last
+    /// [`WaylandSurface::commit`], and has not fired yet.
+    fn has_pending_frame(&self) -> bool {
+        match self.wl_surface().data::<SurfaceData>() {
+            Some(data) => data.has_pending_frame(),
+            None => false,
+        }
     }
 
-    fn set_input_region(&self, region: Option<&wl_region::WlRegion>) {
-        self.wl_surface().set_input_region(region);
+    /// Sets the region of the surface whose content is fully opaque, or an empty region with [`None`].
+    ///
+    /// The compositor may skip drawing what is behind the opaque region. This state is double buffered
+    /// and applied on the next commit. The region is copied, so it may be dropped right away.
+    fn set_opaque_region(&self, region: Option<&Region>) {
+        self.wl_surface().set_opaque_region(region.map(Region::wl_region));
     }
 
+    /// Sets the region of the surface that accepts pointer and touch input, or the whole surface with
+    /// [`None`].
+    ///
+    /// Input outside of the region goes to the surfaces below, which makes these parts of the surface
+    /// click-through. This state is double buffered and applied on the next commit. The region is
+    /// copied, so it may be dropped right away.
+    fn set_input_region(&self, region: Option<&Region>) {
+        self.wl_surface().set_input_region(region.map(Region::wl_region));
+    }
+
+    /// Sets the transform applied to the buffers attached to the surface.
+    ///
+    /// The buffer must be drawn with the transform applied, so with a 90 or 270 degrees rotation its
+    /// width and height are swapped, see [`WaylandSurface::buffer_size_for_logical`]. This state is double
+    /// buffered and applied on the next commit.
+    ///
+    /// If the surface was created with [`SurfaceData`], the transform is recorded and available from
+    /// [`SurfaceData::buffer_transform`].
     fn set_buffer_transform(&self, transform: wl_output::Transform) -> Result<(), Unsupported> {
         if self.wl_surface().version() < 2 {
             return Err(Unsupported);
         }
 
         self.wl_surface().set_buffer_transform(transform);
+        if let Some(data) = self.wl_surface().data::<SurfaceData>() {
+            data.set_buffer_transform(transform);
+        }
         Ok(())
     }
 
-    fn set_buffer_scale(&self, scale: u32) -> Result<(), Unsupported> {
+    /// Sets the scale of the buffers attached to the surface.
+    ///
+    /// The size of the buffers must be a multiple of the scale, see
+    /// [`WaylandSurface::buffer_size_for_logical`]. This state is double buffered and applied on the next
+    /// commit.
+    ///
+    /// If the surface was created with [`SurfaceData`], the scale is recorded and available from
+    /// [`SurfaceData::buffer_scale`]. Scales which do not fit in an [`i32`] are clamped to
+    /// [`i32::MAX`].
+    fn set_buffer_scale(&self, scale: NonZeroU32) -> Result<(), Unsupported> {
         if self.wl_surface().version() < 3 {
             return Err(Unsupported);
         }
 
-        self.wl_surface().set_buffer_scale(scale as i32);
+        let scale = i32::try_from(scale.get()).unwrap_or(i32::MAX);
+        self.wl_surface().set_buffer_scale(scale);
+        if let Some(data) = self.wl_surface().data::<SurfaceData>() {
+            data.set_buffer_scale(scale);
+        }
         Ok(())
     }
 
+    /// The size of the buffer to draw for the given logical size of the surface.
+    ///
+    /// This accounts for the scale and transform set with [`WaylandSurface::set_buffer_scale`] and
+    /// [`WaylandSurface::set_buffer_transform`], swapping the width and height for 90 and 270 degrees
+    /// rotations.
+    ///
+    /// If the surface has a [`viewport`](SurfaceData::viewport) and a
+    /// [`fractional scale`](SurfaceData::fractional_scale), the logical size is instead multiplied by the
+    /// fractional scale and rounded to the nearest integer, since the buffer scale stays at 1 with
+    /// fractional scaling.
+    ///
+    /// Surfaces which were not created with [`SurfaceData`] are assumed to have a scale of 1 and no
+    /// transform.
+    fn buffer_size_for_logical(&self, (width, height): (u32, u32)) -> (u32, u32) {
+        let Some(data) = self.wl_surface().data::<SurfaceData>() else {
+            return (width, height);
+        };
+
+        let (width, height) = match data.fractional_scale().filter(|_| data.viewport().is_some()) {
+            Some(scale) => {
+                ((width as f64 * scale).round() as u32, (height as f64 * scale).round() as u32)
+            }
+            None => {
+                let scale = data.buffer_scale() as u32;
+                (width * scale, height * scale)
+            }
+        };
+
+        match data.buffer_transform() {
+            wl_output::Transform::_90
+            | wl_output::Transform::_270
+            | wl_output::Transform::Flipped90
+            | wl_output::Transform::Flipped270 => (height, width),
+            _ => (width, height),
+        }
+    }
+
     fn offset(&self, x: u32, y: u32) -> Result<(), Unsupported> {
         if self.wl_surface().version() < 5 {
             return Err(Unsupported);
@@ -129,6 +245,76 @@ pub trait WaylandSurface: Sized {
     /// via window system integration in graphics APIs such as Vulkan (using `vkQueuePresentKHR`) and EGL
     /// (using `eglSwapBuffers`).
     fn commit(&self) {
+        // A frame callback requested after this commit is for the next one.
+        if let Some(data) = self.wl_surface().data::<SurfaceData>() {
+            data.frame_committed();
+        }
         self.wl_surface().commit();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use wayland_backend::protocol::Argument;
+    use wayland_client::{
+        protocol::{wl_compositor::WlCompositor, wl_surface::WlSurface},
+        Proxy,
+    };
+
+    use super::WaylandSurface;
+    use crate::testing::{Call, TestClient, TestServer};
+
+    struct TestSurface(WlSurface);
+
+    impl WaylandSurface for TestSurface {
+        fn wl_surface(&self) -> &WlSurface {
+            &self.0
+        }
+    }
+
+    fn connect() -> (TestServer, TestClient, TestSurface) {
+        let mut server = TestServer::new();
+        server.create_global(WlCompositor::interface(), 6);
+        let mut client = server.connect();
+        let surface = TestSurface(client.create_surface());
+        server.take_requests();
+        (server, client, surface)
+    }
+
+    #[test]
+    fn request_frame_once_per_commit() {
+        let (mut server, mut client, surface) = connect();
+
+        assert!(surface.request_frame(&client.qh));
+        assert!(!surface.request_frame(&client.qh));
+        assert!(surface.has_pending_frame());
+        surface.commit();
+        assert!(!surface.has_pending_frame());
+        client.roundtrip();
+        assert_eq!(server.take_request_names(), ["wl_surface.frame", "wl_surface.commit"]);
+
+        // The callback of the previous commit never fired, which does not prevent a new one.
+        assert!(surface.request_frame(&client.qh));
+        surface.commit();
+        client.roundtrip();
+        assert_eq!(server.take_request_names(), ["wl_surface.frame", "wl_surface.commit"]);
+        assert_eq!(server.objects("wl_callback").len(), 2);
+    }
+
+    #[test]
+    fn request_frame_after_done() {
+        let (mut server, mut client, surface) = connect();
+
+        // Committing without `WaylandSurface::commit` keeps the callback pending until it fires.
+        assert!(surface.request_frame(&client.qh));
+        surface.wl_surface().commit();
+        client.roundtrip();
+        assert!(!surface.request_frame(&client.qh));
+
+        server.send(&server.last_object("wl_callback"), "done", vec![Argument::Uint(16)]);
+        client.roundtrip();
+        assert!(matches!(client.state.take_calls()[..], [Call::Frame(_)]));
+        assert!(!surface.has_pending_frame());
+        assert!(surface.request_frame(&client.qh));
+    }
+}
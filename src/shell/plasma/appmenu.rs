@@ -0,0 +1,147 @@
+//! ## KDE application menu
+//!
+//! The `org_kde_kwin_appmenu_manager` global links a surface to a `com.canonical.dbusmenu` menu
+//! exported on the session bus, so that the global menu of KDE Plasma can show it.
+//!
+//! The menu itself is exported over D-Bus by the application, usually with a dbusmenu library, and
+//! must be registered before its address is sent:
+//!
+//! ```no_run
+//! # use smithay_client_toolkit::shell::{plasma::appmenu::AppMenuManagerState, xdg::window::Window};
+//! # use wayland_client::QueueHandle;
+//! # struct State;
+//! # smithay_client_toolkit::delegate_appmenu!(State);
+//! # /// Exports the menu with a dbusmenu implementation, returning its service name and object path.
+//! # fn export_dbusmenu() -> (String, String) { unimplemented!() }
+//! # fn example(appmenu: &AppMenuManagerState, window: &Window, qh: &QueueHandle<State>) {
+//! let (service_name, object_path) = export_dbusmenu();
+//! window.set_app_menu(appmenu, service_name, object_path, qh);
+//! # }
+//! ```
+
+use wayland_client::{
+    globals::{BindError, GlobalList},
+    protocol::wl_surface,
+    Connection, Dispatch, QueueHandle,
+};
+use wayland_protocols_plasma::appmenu::client::{
+    org_kde_kwin_appmenu, org_kde_kwin_appmenu_manager,
+};
+
+use crate::{
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+};
+
+/// The application menu manager global.
+#[derive(Debug)]
+pub struct AppMenuManagerState {
+    manager: org_kde_kwin_appmenu_manager::OrgKdeKwinAppmenuManager,
+}
+
+impl AppMenuManagerState {
+    /// Binds the `org_kde_kwin_appmenu_manager` global.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support KDE application menus.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State:
+            Dispatch<org_kde_kwin_appmenu_manager::OrgKdeKwinAppmenuManager, GlobalData> + 'static,
+    {
+        let manager = globals.bind(qh, 1..=2, GlobalData)?;
+        Ok(Self { manager })
+    }
+
+    /// Create the application menu of a surface.
+    ///
+    /// For windows, [`Window::set_app_menu`](crate::shell::xdg::window::Window::set_app_menu) keeps the
+    /// menu alive as long as the window.
+    pub fn create<D>(&self, surface: &wl_surface::WlSurface, qh: &QueueHandle<D>) -> AppMenu
+    where
+        D: Dispatch<org_kde_kwin_appmenu::OrgKdeKwinAppmenu, GlobalData> + 'static,
+    {
+        AppMenu(self.manager.create(surface, qh, GlobalData))
+    }
+}
+
+impl ProvidesBoundGlobal<org_kde_kwin_appmenu_manager::OrgKdeKwinAppmenuManager, 2>
+    for AppMenuManagerState
+{
+    fn bound_global(
+        &self,
+    ) -> Result<org_kde_kwin_appmenu_manager::OrgKdeKwinAppmenuManager, GlobalError> {
+        Ok(self.manager.clone())
+    }
+}
+
+/// The application menu of a surface.
+///
+/// The menu is removed from the surface when this is dropped.
+#[derive(Debug)]
+pub struct AppMenu(org_kde_kwin_appmenu::OrgKdeKwinAppmenu);
+
+impl AppMenu {
+    pub fn appmenu(&self) -> &org_kde_kwin_appmenu::OrgKdeKwinAppmenu {
+        &self.0
+    }
+
+    /// Set the D-Bus service name and object path the menu is exported at.
+    ///
+    /// The menu must already be registered on the session bus.
+    pub fn set_address(&self, service_name: String, object_path: String) {
+        self.0.set_address(service_name, object_path);
+    }
+}
+
+impl Drop for AppMenu {
+    fn drop(&mut self) {
+        self.0.release();
+    }
+}
+
+impl<D> Dispatch<org_kde_kwin_appmenu_manager::OrgKdeKwinAppmenuManager, GlobalData, D>
+    for AppMenuManagerState
+where
+    D: Dispatch<org_kde_kwin_appmenu_manager::OrgKdeKwinAppmenuManager, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &org_kde_kwin_appmenu_manager::OrgKdeKwinAppmenuManager,
+        _: org_kde_kwin_appmenu_manager::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("org_kde_kwin_appmenu_manager has no events")
+    }
+}
+
+impl<D> Dispatch<org_kde_kwin_appmenu::OrgKdeKwinAppmenu, GlobalData, D> for AppMenuManagerState
+where
+    D: Dispatch<org_kde_kwin_appmenu::OrgKdeKwinAppmenu, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &org_kde_kwin_appmenu::OrgKdeKwinAppmenu,
+        _: org_kde_kwin_appmenu::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("org_kde_kwin_appmenu has no events")
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_appmenu {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_plasma::appmenu::client::org_kde_kwin_appmenu_manager::OrgKdeKwinAppmenuManager: $crate::globals::GlobalData
+        ] => $crate::shell::plasma::appmenu::AppMenuManagerState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_plasma::appmenu::client::org_kde_kwin_appmenu::OrgKdeKwinAppmenu: $crate::globals::GlobalData
+        ] => $crate::shell::plasma::appmenu::AppMenuManagerState);
+    };
+}
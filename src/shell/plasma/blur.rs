@@ -0,0 +1,286 @@
+//! ## KDE blur
+//!
+//! The `org_kde_kwin_blur_manager` global lets KWin blur what is behind a translucent region of a
+//! surface, such as the background of a panel.
+//!
+//! ```no_run
+//! # use smithay_client_toolkit::{compositor::CompositorState, shell::plasma::blur::KdeBlur};
+//! # fn example(blur: &KdeBlur, compositor: &CompositorState) {
+//! // Only blur behind the 32 pixels high bar at the top of the surface.
+//! blur.set_rects(compositor, &[(0, 0, 1920, 32)]).unwrap();
+//! blur.commit();
+//! # }
+//! ```
+
+use wayland_client::{
+    globals::GlobalList,
+    protocol::{wl_compositor, wl_region, wl_surface},
+    Connection, Dispatch, QueueHandle,
+};
+use wayland_protocols_plasma::blur::client::{org_kde_kwin_blur, org_kde_kwin_blur_manager};
+
+use crate::{
+    compositor::{CompositorState, Region},
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+    registry::{GlobalProxy, ProvidesRegistryState, RegistryHandler},
+};
+
+/// The blur manager global.
+///
+/// The global is only bound when the first blur is created. List this type in
+/// [`registry_handlers!`](crate::registry_handlers) to use a blur manager advertised after startup,
+/// and to forget one removed before it is bound.
+#[derive(Debug)]
+pub struct KdeBlurManagerState {
+    manager: GlobalProxy<org_kde_kwin_blur_manager::OrgKdeKwinBlurManager>,
+}
+
+impl KdeBlurManagerState {
+    /// Finds the `org_kde_kwin_blur_manager` global, without binding it yet.
+    pub fn new(globals: &GlobalList) -> Self {
+        Self { manager: GlobalProxy::lazy(globals, 1..=1) }
+    }
+
+    /// Whether the compositor advertises the blur manager.
+    pub fn is_available(&self) -> bool {
+        matches!(self.manager, GlobalProxy::Lazy { .. } | GlobalProxy::Bound(_))
+    }
+
+    /// Create the blur of a surface, binding the blur manager first if needed.
+    ///
+    /// The blur is applied once [`KdeBlur::commit`] is followed by a commit of the surface.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support KDE blur.
+    pub fn blur<D>(
+        &mut self,
+        surface: &wl_surface::WlSurface,
+        qh: &QueueHandle<D>,
+    ) -> Result<KdeBlur, GlobalError>
+    where
+        D: Dispatch<org_kde_kwin_blur_manager::OrgKdeKwinBlurManager, GlobalData>
+            + Dispatch<org_kde_kwin_blur::OrgKdeKwinBlur, GlobalData>
+            + 'static,
+    {
+        let manager = self.manager.get_or_bind(qh, GlobalData)?;
+        Ok(KdeBlur(manager.create(surface, qh, GlobalData)))
+    }
+
+    /// Remove the blur of a surface, on the next commit of the surface.
+    ///
+    /// The blur is also removed when the surface is destroyed. This does nothing if no blur was
+    /// created yet, since the surface cannot have one.
+    pub fn unset(&self, surface: &wl_surface::WlSurface) {
+        if let Ok(manager) = self.manager.get() {
+            manager.unset(surface);
+        }
+    }
+}
+
+/// Provides access to the [`KdeBlurManagerState`], so it can track the blur manager global.
+pub trait KdeBlurHandler: Sized {
+    fn kde_blur_state(&mut self) -> &mut KdeBlurManagerState;
+}
+
+impl<D> RegistryHandler<D> for KdeBlurManagerState
+where
+    D: KdeBlurHandler + ProvidesRegistryState,
+{
+    fn new_global(
+        data: &mut D,
+        _: &Connection,
+        _: &QueueHandle<D>,
+        name: u32,
+        interface: &str,
+        version: u32,
+    ) {
+        let registry = data.registry().registry().clone();
+        data.kde_blur_state().manager.global_added(&registry, name, interface, version, 1..=1);
+    }
+
+    fn remove_global(data: &mut D, _: &Connection, _: &QueueHandle<D>, name: u32, _: &str) {
+        data.kde_blur_state().manager.global_removed(name);
+    }
+}
+
+impl ProvidesBoundGlobal<org_kde_kwin_blur_manager::OrgKdeKwinBlurManager, 1>
+    for KdeBlurManagerState
+{
+    fn bound_global(
+        &self,
+    ) -> Result<org_kde_kwin_blur_manager::OrgKdeKwinBlurManager, GlobalError> {
+        self.manager.get().cloned()
+    }
+}
+
+/// The blur of a surface.
+///
+/// The blur is released when this is dropped. The compositor keeps applying it until it is unset with
+/// [`KdeBlurManagerState::unset`].
+#[derive(Debug)]
+pub struct KdeBlur(org_kde_kwin_blur::OrgKdeKwinBlur);
+
+impl KdeBlur {
+    pub fn blur(&self) -> &org_kde_kwin_blur::OrgKdeKwinBlur {
+        &self.0
+    }
+
+    /// Set the region of the surface to blur behind, or [`None`] for the whole surface.
+    ///
+    /// The region can be destroyed once this is called. This state is double buffered and applied by
+    /// [`KdeBlur::commit`].
+    pub fn set_region(&self, region: Option<&wl_region::WlRegion>) {
+        self.0.set_region(region);
+    }
+
+    /// Set the region of the surface to blur behind to a list of `(x, y, width, height)` rectangles, in
+    /// surface coordinates.
+    ///
+    /// This state is double buffered and applied by [`KdeBlur::commit`].
+    pub fn set_rects(
+        &self,
+        compositor: &impl ProvidesBoundGlobal<
+            wl_compositor::WlCompositor,
+            { CompositorState::API_VERSION_MAX },
+        >,
+        rects: &[(i32, i32, i32, i32)],
+    ) -> Result<(), GlobalError> {
+        let region = Region::new(compositor)?;
+        for &(x, y, width, height) in rects {
+            region.add(x, y, width, height);
+        }
+
+        self.set_region(Some(region.wl_region()));
+        Ok(())
+    }
+
+    /// Apply the pending region, on the next commit of the surface.
+    pub fn commit(&self) {
+        self.0.commit();
+    }
+}
+
+impl Drop for KdeBlur {
+    fn drop(&mut self) {
+        self.0.release();
+    }
+}
+
+impl<D> Dispatch<org_kde_kwin_blur_manager::OrgKdeKwinBlurManager, GlobalData, D>
+    for KdeBlurManagerState
+where
+    D: Dispatch<org_kde_kwin_blur_manager::OrgKdeKwinBlurManager, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &org_kde_kwin_blur_manager::OrgKdeKwinBlurManager,
+        _: org_kde_kwin_blur_manager::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("org_kde_kwin_blur_manager has no events")
+    }
+}
+
+impl<D> Dispatch<org_kde_kwin_blur::OrgKdeKwinBlur, GlobalData, D> for KdeBlurManagerState
+where
+    D: Dispatch<org_kde_kwin_blur::OrgKdeKwinBlur, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &org_kde_kwin_blur::OrgKdeKwinBlur,
+        _: org_kde_kwin_blur::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("org_kde_kwin_blur has no events")
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_kde_blur {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_plasma::blur::client::org_kde_kwin_blur_manager::OrgKdeKwinBlurManager: $crate::globals::GlobalData
+        ] => $crate::shell::plasma::blur::KdeBlurManagerState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_plasma::blur::client::org_kde_kwin_blur::OrgKdeKwinBlur: $crate::globals::GlobalData
+        ] => $crate::shell::plasma::blur::KdeBlurManagerState);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use wayland_client::{protocol::wl_compositor::WlCompositor, Proxy};
+    use wayland_protocols_plasma::blur::client::org_kde_kwin_blur_manager::OrgKdeKwinBlurManager;
+
+    use crate::{error::GlobalError, testing::TestServer};
+
+    #[test]
+    fn bound_on_first_use() {
+        let mut server = TestServer::new();
+        server.create_global(WlCompositor::interface(), 6);
+        server.create_global(OrgKdeKwinBlurManager::interface(), 1);
+        let mut client = server.connect();
+        let surface = client.create_surface();
+        server.take_requests();
+
+        assert!(client.state.kde_blur.is_available());
+        assert!(server.objects("org_kde_kwin_blur_manager").is_empty());
+        assert!(matches!(
+            client.state.kde_blur.manager.get(),
+            Err(GlobalError::NotBound("org_kde_kwin_blur_manager"))
+        ));
+
+        let blur = client.state.kde_blur.blur(&surface, &client.qh).unwrap();
+        blur.commit();
+        client.roundtrip();
+        assert_eq!(server.objects("org_kde_kwin_blur_manager").len(), 1);
+        assert_eq!(
+            server.take_request_names(),
+            ["org_kde_kwin_blur_manager.create", "org_kde_kwin_blur.commit"]
+        );
+    }
+
+    #[test]
+    fn advertised_later() {
+        let mut server = TestServer::new();
+        server.create_global(WlCompositor::interface(), 6);
+        let mut client = server.connect();
+        let surface = client.create_surface();
+        assert!(!client.state.kde_blur.is_available());
+        assert!(matches!(
+            client.state.kde_blur.blur(&surface, &client.qh),
+            Err(GlobalError::MissingGlobal("org_kde_kwin_blur_manager"))
+        ));
+
+        server.create_global(OrgKdeKwinBlurManager::interface(), 1);
+        client.roundtrip();
+        assert!(client.state.kde_blur.is_available());
+        client.state.kde_blur.blur(&surface, &client.qh).unwrap();
+        client.roundtrip();
+        assert_eq!(server.objects("org_kde_kwin_blur_manager").len(), 1);
+    }
+
+    #[test]
+    fn removed_before_use() {
+        let mut server = TestServer::new();
+        server.create_global(WlCompositor::interface(), 6);
+        let global = server.create_global(OrgKdeKwinBlurManager::interface(), 1);
+        let mut client = server.connect();
+        let surface = client.create_surface();
+
+        server.remove_global(global);
+        client.roundtrip();
+        assert!(!client.state.kde_blur.is_available());
+        assert!(matches!(
+            client.state.kde_blur.blur(&surface, &client.qh),
+            Err(GlobalError::MissingGlobal("org_kde_kwin_blur_manager"))
+        ));
+        assert!(server.objects("org_kde_kwin_blur_manager").is_empty());
+    }
+}
@@ -0,0 +1,184 @@
+//! ## KDE background contrast
+//!
+//! The `org_kde_kwin_contrast_manager` global lets KWin change the contrast, intensity and saturation of
+//! what is behind a translucent region of a surface, so that text drawn over it stays readable.
+
+use wayland_client::{
+    globals::{BindError, GlobalList},
+    protocol::{wl_region, wl_surface},
+    Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols_plasma::contrast::client::{
+    org_kde_kwin_contrast, org_kde_kwin_contrast_manager,
+};
+
+use crate::{
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+};
+
+/// The contrast manager global.
+#[derive(Debug)]
+pub struct ContrastManagerState {
+    manager: org_kde_kwin_contrast_manager::OrgKdeKwinContrastManager,
+}
+
+impl ContrastManagerState {
+    /// Binds the `org_kde_kwin_contrast_manager` global.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support KDE background contrast.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State: Dispatch<org_kde_kwin_contrast_manager::OrgKdeKwinContrastManager, GlobalData>
+            + 'static,
+    {
+        let manager = globals.bind(qh, 1..=2, GlobalData)?;
+        Ok(Self { manager })
+    }
+
+    /// Create the background contrast of a surface.
+    ///
+    /// The contrast is applied once [`Contrast::commit`] is followed by a commit of the surface.
+    pub fn create<D>(&self, surface: &wl_surface::WlSurface, qh: &QueueHandle<D>) -> Contrast
+    where
+        D: Dispatch<org_kde_kwin_contrast::OrgKdeKwinContrast, GlobalData> + 'static,
+    {
+        Contrast(self.manager.create(surface, qh, GlobalData))
+    }
+
+    /// Remove the background contrast of a surface, on the next commit of the surface.
+    ///
+    /// The contrast is also removed when the surface is destroyed.
+    pub fn unset(&self, surface: &wl_surface::WlSurface) {
+        self.manager.unset(surface);
+    }
+}
+
+impl ProvidesBoundGlobal<org_kde_kwin_contrast_manager::OrgKdeKwinContrastManager, 2>
+    for ContrastManagerState
+{
+    fn bound_global(
+        &self,
+    ) -> Result<org_kde_kwin_contrast_manager::OrgKdeKwinContrastManager, GlobalError> {
+        Ok(self.manager.clone())
+    }
+}
+
+/// The background contrast of a surface.
+///
+/// The contrast is released when this is dropped. The compositor keeps applying it until it is unset
+/// with [`ContrastManagerState::unset`].
+#[derive(Debug)]
+pub struct Contrast(org_kde_kwin_contrast::OrgKdeKwinContrast);
+
+impl Contrast {
+    pub fn contrast(&self) -> &org_kde_kwin_contrast::OrgKdeKwinContrast {
+        &self.0
+    }
+
+    /// Set the region of the surface the contrast is applied behind, or [`None`] for the whole surface.
+    ///
+    /// The region can be destroyed once this is called. This state is double buffered and applied by
+    /// [`Contrast::commit`].
+    pub fn set_region(&self, region: Option<&wl_region::WlRegion>) {
+        self.0.set_region(region);
+    }
+
+    /// Set the contrast of the background, where `1.0` leaves it unchanged.
+    ///
+    /// This state is double buffered and applied by [`Contrast::commit`].
+    pub fn set_contrast(&self, contrast: f64) {
+        self.0.set_contrast(contrast);
+    }
+
+    /// Set the intensity of the background, where `1.0` leaves it unchanged.
+    ///
+    /// This state is double buffered and applied by [`Contrast::commit`].
+    pub fn set_intensity(&self, intensity: f64) {
+        self.0.set_intensity(intensity);
+    }
+
+    /// Set the saturation of the background, where `1.0` leaves it unchanged.
+    ///
+    /// This state is double buffered and applied by [`Contrast::commit`].
+    pub fn set_saturation(&self, saturation: f64) {
+        self.0.set_saturation(saturation);
+    }
+
+    /// Enable the frost variant of the effect with the main background color of the surface, as
+    /// `(red, green, blue, alpha)`, or disable it with [`None`].
+    ///
+    /// Frost makes the background both more contrasted and more transparent. This does nothing if the
+    /// compositor only supports version 1 of the protocol. This state is double buffered and applied by
+    /// [`Contrast::commit`].
+    pub fn set_frost(&self, color: Option<(u8, u8, u8, u8)>) {
+        if self.0.version() < 2 {
+            return;
+        }
+
+        match color {
+            Some((red, green, blue, alpha)) => {
+                self.0.set_frost(red.into(), green.into(), blue.into(), alpha.into())
+            }
+            None => self.0.unset_frost(),
+        }
+    }
+
+    /// Apply the pending state, on the next commit of the surface.
+    pub fn commit(&self) {
+        self.0.commit();
+    }
+}
+
+impl Drop for Contrast {
+    fn drop(&mut self) {
+        self.0.release();
+    }
+}
+
+impl<D> Dispatch<org_kde_kwin_contrast_manager::OrgKdeKwinContrastManager, GlobalData, D>
+    for ContrastManagerState
+where
+    D: Dispatch<org_kde_kwin_contrast_manager::OrgKdeKwinContrastManager, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &org_kde_kwin_contrast_manager::OrgKdeKwinContrastManager,
+        _: org_kde_kwin_contrast_manager::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("org_kde_kwin_contrast_manager has no events")
+    }
+}
+
+impl<D> Dispatch<org_kde_kwin_contrast::OrgKdeKwinContrast, GlobalData, D> for ContrastManagerState
+where
+    D: Dispatch<org_kde_kwin_contrast::OrgKdeKwinContrast, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &org_kde_kwin_contrast::OrgKdeKwinContrast,
+        _: org_kde_kwin_contrast::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("org_kde_kwin_contrast has no events")
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_contrast {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_plasma::contrast::client::org_kde_kwin_contrast_manager::OrgKdeKwinContrastManager: $crate::globals::GlobalData
+        ] => $crate::shell::plasma::contrast::ContrastManagerState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_plasma::contrast::client::org_kde_kwin_contrast::OrgKdeKwinContrast: $crate::globals::GlobalData
+        ] => $crate::shell::plasma::contrast::ContrastManagerState);
+    };
+}
@@ -0,0 +1,13 @@
+//! ## KDE Plasma protocols
+//!
+//! Protocols specific to KWin, the compositor of KDE Plasma. They are usually not available on other
+//! compositors.
+
+pub mod appmenu;
+pub mod blur;
+pub mod contrast;
+pub mod plasma_shell;
+pub mod server_decoration;
+pub mod shadow;
+pub mod slide;
+pub mod window_management;
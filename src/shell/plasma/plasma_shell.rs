@@ -0,0 +1,301 @@
+//! ## Plasma shell
+//!
+//! The `org_kde_plasma_shell` global gives the surfaces of the KDE Plasma desktop shell a role such as
+//! panel, on screen display or notification, which KWin uses to place and stack them, and to exclude
+//! them from taskbars and window switchers.
+//!
+//! This is an implementation detail of KDE Plasma, and only one client can bind it. Clients that are
+//! not part of Plasma should use [layer shell](crate::shell::wlr_layer) instead, which KWin also
+//! supports. Plasma shell is still needed for the behavior layer shell does not cover, such as
+//! auto-hiding panels, or placing a surface at a position in global coordinates.
+//!
+//! A [`PlasmaSurface`] is created for a surface which already has a role, usually an xdg toplevel,
+//! before its first commit.
+
+use std::sync::{Arc, Weak};
+
+use wayland_client::{
+    globals::{BindError, GlobalList},
+    protocol::{wl_output, wl_surface},
+    Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols_plasma::plasma_shell::client::{
+    org_kde_plasma_shell, org_kde_plasma_surface,
+};
+
+use crate::{
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+};
+
+pub use org_kde_plasma_surface::{PanelBehavior, Role};
+
+/// Handler for Plasma shell surfaces.
+pub trait PlasmaShellHandler: Sized {
+    /// An auto-hiding panel was hidden by the compositor.
+    fn auto_hidden_panel_hidden(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        surface: &PlasmaSurface,
+    );
+
+    /// An auto-hiding panel was shown by the compositor, for example because the pointer touched the
+    /// edge of the screen.
+    ///
+    /// This is also sent if the compositor could not hide the panel.
+    fn auto_hidden_panel_shown(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        surface: &PlasmaSurface,
+    );
+}
+
+/// The Plasma shell global.
+#[derive(Debug)]
+pub struct PlasmaShellState {
+    plasma_shell: org_kde_plasma_shell::OrgKdePlasmaShell,
+}
+
+impl PlasmaShellState {
+    /// Binds the `org_kde_plasma_shell` global.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support Plasma shell, which is also
+    /// the case if another client already bound it.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State: Dispatch<org_kde_plasma_shell::OrgKdePlasmaShell, GlobalData> + 'static,
+    {
+        let plasma_shell = globals.bind(qh, 1..=8, GlobalData)?;
+        Ok(Self { plasma_shell })
+    }
+
+    /// Create the Plasma shell surface of a surface.
+    ///
+    /// # Protocol errors
+    ///
+    /// A surface can only have one Plasma shell surface at a time.
+    pub fn get_surface<D>(
+        &self,
+        surface: &wl_surface::WlSurface,
+        qh: &QueueHandle<D>,
+    ) -> PlasmaSurface
+    where
+        D: Dispatch<org_kde_plasma_surface::OrgKdePlasmaSurface, PlasmaSurfaceData> + 'static,
+    {
+        // Freeze the queue so events can not be dispatched before the Weak is usable.
+        let freeze = qh.freeze();
+        let inner = Arc::new_cyclic(|weak| PlasmaSurfaceInner {
+            plasma_surface: self.plasma_shell.get_surface(
+                surface,
+                qh,
+                PlasmaSurfaceData(weak.clone()),
+            ),
+            surface: surface.clone(),
+        });
+        drop(freeze);
+
+        PlasmaSurface(inner)
+    }
+}
+
+impl ProvidesBoundGlobal<org_kde_plasma_shell::OrgKdePlasmaShell, 8> for PlasmaShellState {
+    fn bound_global(&self) -> Result<org_kde_plasma_shell::OrgKdePlasmaShell, GlobalError> {
+        Ok(self.plasma_shell.clone())
+    }
+}
+
+/// The Plasma shell surface of a surface.
+///
+/// The Plasma shell surface is destroyed when the last clone of this is dropped, which unmaps the
+/// surface. It must be dropped before the surface is destroyed.
+///
+/// Requests which need a newer version of the protocol than the compositor supports do nothing.
+#[derive(Debug, Clone)]
+pub struct PlasmaSurface(Arc<PlasmaSurfaceInner>);
+
+impl PlasmaSurface {
+    pub fn plasma_surface(&self) -> &org_kde_plasma_surface::OrgKdePlasmaSurface {
+        &self.0.plasma_surface
+    }
+
+    pub fn wl_surface(&self) -> &wl_surface::WlSurface {
+        &self.0.surface
+    }
+
+    /// Set the role of the surface, which KWin uses to place and stack it.
+    pub fn set_role(&self, role: Role) {
+        self.0.plasma_surface.set_role(role as u32);
+    }
+
+    /// Set the output the surface is shown on.
+    pub fn set_output(&self, output: &wl_output::WlOutput) {
+        self.0.plasma_surface.set_output(output);
+    }
+
+    /// Set the position of the surface, in global compositor space.
+    pub fn set_position(&self, x: i32, y: i32) {
+        self.0.plasma_surface.set_position(x, y);
+    }
+
+    /// Request the surface to open under the pointer. This must be called before a buffer is attached.
+    ///
+    /// This requires version 7.
+    pub fn open_under_cursor(&self) {
+        if self.version() >= 7 {
+            self.0.plasma_surface.open_under_cursor();
+        }
+    }
+
+    /// Set whether the surface is listed in taskbars.
+    ///
+    /// This requires version 2.
+    pub fn set_skip_taskbar(&self, skip: bool) {
+        if self.version() >= 2 {
+            self.0.plasma_surface.set_skip_taskbar(skip.into());
+        }
+    }
+
+    /// Set whether the surface is listed in window switchers.
+    ///
+    /// This requires version 5.
+    pub fn set_skip_switcher(&self, skip: bool) {
+        if self.version() >= 5 {
+            self.0.plasma_surface.set_skip_switcher(skip.into());
+        }
+    }
+
+    // Panels
+
+    /// Set how a surface with the [`Role::Panel`] role interacts with windows.
+    pub fn set_panel_behavior(&self, behavior: PanelBehavior) {
+        self.0.plasma_surface.set_panel_behavior(behavior as u32);
+    }
+
+    /// Set whether a panel can be focused, which surfaces with a Plasma role can not by default.
+    ///
+    /// This requires version 4.
+    pub fn set_panel_takes_focus(&self, takes_focus: bool) {
+        if self.version() >= 4 {
+            self.0.plasma_surface.set_panel_takes_focus(takes_focus.into());
+        }
+    }
+
+    /// Hide a panel with the [`PanelBehavior::AutoHide`] behavior, without unmapping it.
+    ///
+    /// The panel is shown again when the pointer touches its edge of the screen, or with
+    /// [`PlasmaSurface::show_auto_hidden_panel`]. This requires version 4.
+    ///
+    /// # Protocol errors
+    ///
+    /// The surface must be a panel with the [`PanelBehavior::AutoHide`] behavior.
+    pub fn hide_auto_hidden_panel(&self) {
+        if self.version() >= 4 {
+            self.0.plasma_surface.panel_auto_hide_hide();
+        }
+    }
+
+    /// Show a panel hidden with [`PlasmaSurface::hide_auto_hidden_panel`].
+    ///
+    /// This requires version 4.
+    ///
+    /// # Protocol errors
+    ///
+    /// The surface must be a panel with the [`PanelBehavior::AutoHide`] behavior.
+    pub fn show_auto_hidden_panel(&self) {
+        if self.version() >= 4 {
+            self.0.plasma_surface.panel_auto_hide_show();
+        }
+    }
+
+    fn version(&self) -> u32 {
+        self.0.plasma_surface.version()
+    }
+}
+
+impl PartialEq for PlasmaSurface {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[derive(Debug)]
+struct PlasmaSurfaceInner {
+    plasma_surface: org_kde_plasma_surface::OrgKdePlasmaSurface,
+    surface: wl_surface::WlSurface,
+}
+
+impl Drop for PlasmaSurfaceInner {
+    fn drop(&mut self) {
+        self.plasma_surface.destroy();
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct PlasmaSurfaceData(Weak<PlasmaSurfaceInner>);
+
+impl<D> Dispatch<org_kde_plasma_shell::OrgKdePlasmaShell, GlobalData, D> for PlasmaShellState
+where
+    D: Dispatch<org_kde_plasma_shell::OrgKdePlasmaShell, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &org_kde_plasma_shell::OrgKdePlasmaShell,
+        _: org_kde_plasma_shell::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("org_kde_plasma_shell has no events")
+    }
+}
+
+impl<D> Dispatch<org_kde_plasma_surface::OrgKdePlasmaSurface, PlasmaSurfaceData, D>
+    for PlasmaShellState
+where
+    D: Dispatch<org_kde_plasma_surface::OrgKdePlasmaSurface, PlasmaSurfaceData>
+        + PlasmaShellHandler,
+{
+    fn event(
+        state: &mut D,
+        _: &org_kde_plasma_surface::OrgKdePlasmaSurface,
+        event: org_kde_plasma_surface::Event,
+        data: &PlasmaSurfaceData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        // The surface was dropped while the event was in flight.
+        let surface = match data.0.upgrade() {
+            Some(inner) => PlasmaSurface(inner),
+            None => return,
+        };
+
+        match event {
+            org_kde_plasma_surface::Event::AutoHiddenPanelHidden => {
+                state.auto_hidden_panel_hidden(conn, qh, &surface);
+            }
+
+            org_kde_plasma_surface::Event::AutoHiddenPanelShown => {
+                state.auto_hidden_panel_shown(conn, qh, &surface);
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_plasma_shell {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_plasma::plasma_shell::client::org_kde_plasma_shell::OrgKdePlasmaShell: $crate::globals::GlobalData
+        ] => $crate::shell::plasma::plasma_shell::PlasmaShellState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_plasma::plasma_shell::client::org_kde_plasma_surface::OrgKdePlasmaSurface: $crate::shell::plasma::plasma_shell::PlasmaSurfaceData
+        ] => $crate::shell::plasma::plasma_shell::PlasmaShellState);
+    };
+}
@@ -0,0 +1,233 @@
+//! ## KDE server decorations
+//!
+//! The `org_kde_kwin_server_decoration_manager` global negotiates server side decorations on versions
+//! of KDE Plasma which do not support xdg-decoration.
+//!
+//! [`Window`](crate::shell::xdg::window::Window) already falls back to this protocol when xdg-decoration
+//! is not available, so this is only needed for surfaces which are not created through
+//! [`XdgShell`](crate::shell::xdg::XdgShell).
+
+use std::{convert::TryFrom, sync::Mutex};
+
+use wayland_client::{
+    globals::{BindError, GlobalList},
+    protocol::wl_surface,
+    Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols_plasma::server_decoration::client::{
+    org_kde_kwin_server_decoration, org_kde_kwin_server_decoration_manager,
+};
+
+use crate::{error::GlobalError, globals::ProvidesBoundGlobal};
+
+pub use org_kde_kwin_server_decoration::Mode;
+
+/// Handler for KDE server decorations.
+pub trait KdeDecorationHandler: Sized {
+    /// The compositor announced the mode used by decorations that don't request a mode.
+    fn default_mode(&mut self, conn: &Connection, qh: &QueueHandle<Self>, mode: Mode);
+
+    /// The compositor decided the decoration mode of a surface.
+    ///
+    /// This is sent when the decoration is created and after every [`KdeDecoration::request_mode`].
+    fn mode(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        surface: &wl_surface::WlSurface,
+        mode: Mode,
+    );
+}
+
+/// The KDE server decoration global.
+#[derive(Debug)]
+pub struct KdeDecorationState {
+    manager: org_kde_kwin_server_decoration_manager::OrgKdeKwinServerDecorationManager,
+}
+
+impl KdeDecorationState {
+    /// Binds the `org_kde_kwin_server_decoration_manager` global.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support KDE server decorations.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State: Dispatch<
+                org_kde_kwin_server_decoration_manager::OrgKdeKwinServerDecorationManager,
+                KdeDecorationManagerData,
+            > + 'static,
+    {
+        let manager =
+            globals.bind(qh, 1..=1, KdeDecorationManagerData { default_mode: Mutex::new(None) })?;
+        Ok(Self { manager })
+    }
+
+    /// The mode used by decorations that don't request a mode, or [`None`] if the compositor did not
+    /// announce it yet.
+    pub fn default_mode(&self) -> Option<Mode> {
+        let data = self.manager.data::<KdeDecorationManagerData>().unwrap();
+        *data.default_mode.lock().unwrap()
+    }
+
+    /// Create the decoration of a surface.
+    ///
+    /// The compositor uses the [default mode](Self::default_mode) until another mode is requested.
+    pub fn create<D>(&self, surface: &wl_surface::WlSurface, qh: &QueueHandle<D>) -> KdeDecoration
+    where
+        D: Dispatch<org_kde_kwin_server_decoration::OrgKdeKwinServerDecoration, KdeDecorationData>
+            + 'static,
+    {
+        let data = KdeDecorationData { surface: surface.clone(), mode: Mutex::new(None) };
+        KdeDecoration(self.manager.create(surface, qh, data))
+    }
+}
+
+impl
+    ProvidesBoundGlobal<
+        org_kde_kwin_server_decoration_manager::OrgKdeKwinServerDecorationManager,
+        1,
+    > for KdeDecorationState
+{
+    fn bound_global(
+        &self,
+    ) -> Result<
+        org_kde_kwin_server_decoration_manager::OrgKdeKwinServerDecorationManager,
+        GlobalError,
+    > {
+        Ok(self.manager.clone())
+    }
+}
+
+/// The server decoration of a surface.
+///
+/// The decoration is released when this is dropped.
+#[derive(Debug)]
+pub struct KdeDecoration(org_kde_kwin_server_decoration::OrgKdeKwinServerDecoration);
+
+impl KdeDecoration {
+    pub fn decoration(&self) -> &org_kde_kwin_server_decoration::OrgKdeKwinServerDecoration {
+        &self.0
+    }
+
+    /// The decorated surface.
+    pub fn surface(&self) -> &wl_surface::WlSurface {
+        &self.data().surface
+    }
+
+    /// Request a decoration mode.
+    ///
+    /// The compositor answers with [`KdeDecorationHandler::mode`], which may not be the requested mode.
+    pub fn request_mode(&self, mode: Mode) {
+        self.0.request_mode(mode as u32);
+    }
+
+    /// The mode most recently decided by the compositor, or [`None`] if it was not sent yet.
+    pub fn mode(&self) -> Option<Mode> {
+        *self.data().mode.lock().unwrap()
+    }
+
+    fn data(&self) -> &KdeDecorationData {
+        self.0.data::<KdeDecorationData>().unwrap()
+    }
+}
+
+impl Drop for KdeDecoration {
+    fn drop(&mut self) {
+        self.0.release();
+    }
+}
+
+/// Parse a mode sent by the compositor, which is not typed in the protocol.
+pub(crate) fn parse_mode(raw: u32) -> Option<Mode> {
+    let mode = Mode::try_from(raw).ok();
+    if mode.is_none() {
+        log::error!(target: "sctk", "unknown KDE decoration mode 0x{:x}", raw);
+    }
+    mode
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct KdeDecorationManagerData {
+    default_mode: Mutex<Option<Mode>>,
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct KdeDecorationData {
+    surface: wl_surface::WlSurface,
+    mode: Mutex<Option<Mode>>,
+}
+
+impl<D>
+    Dispatch<
+        org_kde_kwin_server_decoration_manager::OrgKdeKwinServerDecorationManager,
+        KdeDecorationManagerData,
+        D,
+    > for KdeDecorationState
+where
+    D: Dispatch<
+            org_kde_kwin_server_decoration_manager::OrgKdeKwinServerDecorationManager,
+            KdeDecorationManagerData,
+        > + KdeDecorationHandler,
+{
+    fn event(
+        state: &mut D,
+        _: &org_kde_kwin_server_decoration_manager::OrgKdeKwinServerDecorationManager,
+        event: org_kde_kwin_server_decoration_manager::Event,
+        data: &KdeDecorationManagerData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        match event {
+            org_kde_kwin_server_decoration_manager::Event::DefaultMode { mode } => {
+                if let Some(mode) = parse_mode(mode) {
+                    *data.default_mode.lock().unwrap() = Some(mode);
+                    state.default_mode(conn, qh, mode);
+                }
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<org_kde_kwin_server_decoration::OrgKdeKwinServerDecoration, KdeDecorationData, D>
+    for KdeDecorationState
+where
+    D: Dispatch<org_kde_kwin_server_decoration::OrgKdeKwinServerDecoration, KdeDecorationData>
+        + KdeDecorationHandler,
+{
+    fn event(
+        state: &mut D,
+        _: &org_kde_kwin_server_decoration::OrgKdeKwinServerDecoration,
+        event: org_kde_kwin_server_decoration::Event,
+        data: &KdeDecorationData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        match event {
+            org_kde_kwin_server_decoration::Event::Mode { mode } => {
+                if let Some(mode) = parse_mode(mode) {
+                    *data.mode.lock().unwrap() = Some(mode);
+                    state.mode(conn, qh, &data.surface, mode);
+                }
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_kde_decoration {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_plasma::server_decoration::client::org_kde_kwin_server_decoration_manager::OrgKdeKwinServerDecorationManager: $crate::shell::plasma::server_decoration::KdeDecorationManagerData
+        ] => $crate::shell::plasma::server_decoration::KdeDecorationState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_plasma::server_decoration::client::org_kde_kwin_server_decoration::OrgKdeKwinServerDecoration: $crate::shell::plasma::server_decoration::KdeDecorationData
+        ] => $crate::shell::plasma::server_decoration::KdeDecorationState);
+    };
+}
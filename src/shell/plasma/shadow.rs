@@ -0,0 +1,288 @@
+//! ## KDE shadows
+//!
+//! The `org_kde_kwin_shadow_manager` global lets KWin draw a shadow around a surface, so that clients
+//! drawing their own decorations don't have to draw the shadow inside of their surface.
+//!
+//! A shadow is a nine-patch without its center: a buffer for each edge and corner of the surface,
+//! with the edges stretched along the surface. [`ShadowBuffers`] can draw a simple shadow of this kind.
+
+use wayland_client::{
+    globals::{BindError, GlobalList},
+    protocol::{wl_buffer, wl_shm, wl_surface},
+    Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols_plasma::shadow::client::{org_kde_kwin_shadow, org_kde_kwin_shadow_manager};
+
+use crate::{
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+    shm::slot::{Buffer, CreateBufferError, SlotPool},
+};
+
+/// The shadow manager global.
+#[derive(Debug)]
+pub struct ShadowManagerState {
+    manager: org_kde_kwin_shadow_manager::OrgKdeKwinShadowManager,
+}
+
+impl ShadowManagerState {
+    /// Binds the `org_kde_kwin_shadow_manager` global.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support KDE shadows.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State: Dispatch<org_kde_kwin_shadow_manager::OrgKdeKwinShadowManager, GlobalData> + 'static,
+    {
+        let manager = globals.bind(qh, 1..=2, GlobalData)?;
+        Ok(Self { manager })
+    }
+
+    /// Create the shadow of a surface.
+    ///
+    /// The shadow is shown once its buffers are attached and [`Shadow::commit`] is followed by a commit
+    /// of the surface.
+    pub fn create_shadow<D>(&self, surface: &wl_surface::WlSurface, qh: &QueueHandle<D>) -> Shadow
+    where
+        D: Dispatch<org_kde_kwin_shadow::OrgKdeKwinShadow, GlobalData> + 'static,
+    {
+        Shadow(self.manager.create(surface, qh, GlobalData))
+    }
+
+    /// Remove the shadow of a surface, on the next commit of the surface.
+    pub fn unset_shadow(&self, surface: &wl_surface::WlSurface) {
+        self.manager.unset(surface);
+    }
+}
+
+impl ProvidesBoundGlobal<org_kde_kwin_shadow_manager::OrgKdeKwinShadowManager, 2>
+    for ShadowManagerState
+{
+    fn bound_global(
+        &self,
+    ) -> Result<org_kde_kwin_shadow_manager::OrgKdeKwinShadowManager, GlobalError> {
+        Ok(self.manager.clone())
+    }
+}
+
+/// A part of a shadow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShadowPart {
+    Left,
+    TopLeft,
+    Top,
+    TopRight,
+    Right,
+    BottomRight,
+    Bottom,
+    BottomLeft,
+}
+
+impl ShadowPart {
+    /// All the parts of a shadow.
+    pub const ALL: [ShadowPart; 8] = [
+        ShadowPart::Left,
+        ShadowPart::TopLeft,
+        ShadowPart::Top,
+        ShadowPart::TopRight,
+        ShadowPart::Right,
+        ShadowPart::BottomRight,
+        ShadowPart::Bottom,
+        ShadowPart::BottomLeft,
+    ];
+}
+
+/// The shadow of a surface.
+///
+/// The shadow is destroyed when this is dropped. The compositor keeps showing it until it is unset
+/// with [`ShadowManagerState::unset_shadow`].
+#[derive(Debug)]
+pub struct Shadow(org_kde_kwin_shadow::OrgKdeKwinShadow);
+
+impl Shadow {
+    pub fn shadow(&self) -> &org_kde_kwin_shadow::OrgKdeKwinShadow {
+        &self.0
+    }
+
+    /// Attach the buffer of a part of the shadow.
+    ///
+    /// This state is double buffered and applied by [`Shadow::commit`].
+    pub fn attach(&self, part: ShadowPart, buffer: &wl_buffer::WlBuffer) {
+        match part {
+            ShadowPart::Left => self.0.attach_left(buffer),
+            ShadowPart::TopLeft => self.0.attach_top_left(buffer),
+            ShadowPart::Top => self.0.attach_top(buffer),
+            ShadowPart::TopRight => self.0.attach_top_right(buffer),
+            ShadowPart::Right => self.0.attach_right(buffer),
+            ShadowPart::BottomRight => self.0.attach_bottom_right(buffer),
+            ShadowPart::Bottom => self.0.attach_bottom(buffer),
+            ShadowPart::BottomLeft => self.0.attach_bottom_left(buffer),
+        }
+    }
+
+    /// Set how far the shadow extends outside of each edge of the surface, as
+    /// `(left, top, right, bottom)` in surface coordinates.
+    ///
+    /// This state is double buffered and applied by [`Shadow::commit`].
+    pub fn set_offsets(&self, (left, top, right, bottom): (f64, f64, f64, f64)) {
+        self.0.set_left_offset(left);
+        self.0.set_top_offset(top);
+        self.0.set_right_offset(right);
+        self.0.set_bottom_offset(bottom);
+    }
+
+    /// Attach all the buffers of a [`ShadowBuffers`] and set the offsets to its radius.
+    ///
+    /// This state is double buffered and applied by [`Shadow::commit`].
+    pub fn attach_buffers(&self, buffers: &ShadowBuffers) {
+        for (part, buffer) in ShadowPart::ALL.iter().zip(&buffers.buffers) {
+            self.attach(*part, buffer.wl_buffer());
+        }
+
+        let radius = buffers.radius as f64;
+        self.set_offsets((radius, radius, radius, radius));
+    }
+
+    /// Apply the pending buffers and offsets, on the next commit of the surface.
+    pub fn commit(&self) {
+        self.0.commit();
+    }
+}
+
+impl Drop for Shadow {
+    fn drop(&mut self) {
+        if self.0.version() >= 2 {
+            self.0.destroy();
+        }
+    }
+}
+
+/// The buffers of a shadow which fades out from a color over a radius, drawn in a [`SlotPool`].
+///
+/// The buffers must be kept alive while they are attached to a shadow.
+#[derive(Debug)]
+pub struct ShadowBuffers {
+    radius: u32,
+    /// In the order of [`ShadowPart::ALL`].
+    buffers: Vec<Buffer>,
+}
+
+impl ShadowBuffers {
+    /// Draw a shadow of `radius` pixels, starting at `color` along the surface and fading out.
+    ///
+    /// The color is in the `ARGB8888` format, as in `0xAARRGGBB`, and should usually be a translucent
+    /// black such as `0x80000000`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `radius` is zero.
+    pub fn new(pool: &mut SlotPool, radius: u32, color: u32) -> Result<Self, CreateBufferError> {
+        assert!(radius > 0, "the radius of a shadow must be positive");
+
+        let r = radius as f64;
+        let mut buffers = Vec::with_capacity(ShadowPart::ALL.len());
+
+        for part in ShadowPart::ALL {
+            // The size of the part, and the position of the closest point of the surface relative to
+            // the part, along each axis. Edges are one pixel long, since they are stretched.
+            let ((width, x0), (height, y0)) = match part {
+                ShadowPart::Left => ((radius, Some(r)), (1, None)),
+                ShadowPart::TopLeft => ((radius, Some(r)), (radius, Some(r))),
+                ShadowPart::Top => ((1, None), (radius, Some(r))),
+                ShadowPart::TopRight => ((radius, Some(0.)), (radius, Some(r))),
+                ShadowPart::Right => ((radius, Some(0.)), (1, None)),
+                ShadowPart::BottomRight => ((radius, Some(0.)), (radius, Some(0.))),
+                ShadowPart::Bottom => ((1, None), (radius, Some(0.))),
+                ShadowPart::BottomLeft => ((radius, Some(r)), (radius, Some(0.))),
+            };
+
+            let (buffer, canvas) = pool.create_buffer(
+                width as i32,
+                height as i32,
+                width as i32 * 4,
+                wl_shm::Format::Argb8888,
+            )?;
+
+            canvas.chunks_exact_mut(4).enumerate().for_each(|(index, chunk)| {
+                let x = (index as u32 % width) as f64 + 0.5;
+                let y = (index as u32 / width) as f64 + 0.5;
+                let dx = x0.map_or(0., |x0| x - x0);
+                let dy = y0.map_or(0., |y0| y - y0);
+                let distance = (dx * dx + dy * dy).sqrt() / r;
+
+                let pixel = scale_premultiplied(color, falloff(distance));
+                chunk.copy_from_slice(&pixel.to_le_bytes());
+            });
+
+            buffers.push(buffer);
+        }
+
+        Ok(Self { radius, buffers })
+    }
+
+    /// The radius of the shadow, in pixels.
+    pub fn radius(&self) -> u32 {
+        self.radius
+    }
+}
+
+/// The opacity of a shadow at a distance from the surface, relative to the radius of the shadow.
+///
+/// This is a smoothstep, close enough to a gaussian blur for a shadow.
+fn falloff(distance: f64) -> f64 {
+    let t = 1. - distance.min(1.);
+    t * t * (3. - 2. * t)
+}
+
+/// Premultiply an `ARGB8888` color by its alpha, scaled by `factor`.
+fn scale_premultiplied(color: u32, factor: f64) -> u32 {
+    let alpha = ((color >> 24) as f64 / 255.) * factor;
+    let channel = |shift: u32| (((color >> shift) & 0xFF) as f64 * alpha).round() as u32;
+    ((alpha * 255.).round() as u32) << 24 | channel(16) << 16 | channel(8) << 8 | channel(0)
+}
+
+impl<D> Dispatch<org_kde_kwin_shadow_manager::OrgKdeKwinShadowManager, GlobalData, D>
+    for ShadowManagerState
+where
+    D: Dispatch<org_kde_kwin_shadow_manager::OrgKdeKwinShadowManager, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &org_kde_kwin_shadow_manager::OrgKdeKwinShadowManager,
+        _: org_kde_kwin_shadow_manager::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("org_kde_kwin_shadow_manager has no events")
+    }
+}
+
+impl<D> Dispatch<org_kde_kwin_shadow::OrgKdeKwinShadow, GlobalData, D> for ShadowManagerState
+where
+    D: Dispatch<org_kde_kwin_shadow::OrgKdeKwinShadow, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &org_kde_kwin_shadow::OrgKdeKwinShadow,
+        _: org_kde_kwin_shadow::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("org_kde_kwin_shadow has no events")
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_shadow {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_plasma::shadow::client::org_kde_kwin_shadow_manager::OrgKdeKwinShadowManager: $crate::globals::GlobalData
+        ] => $crate::shell::plasma::shadow::ShadowManagerState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_plasma::shadow::client::org_kde_kwin_shadow::OrgKdeKwinShadow: $crate::globals::GlobalData
+        ] => $crate::shell::plasma::shadow::ShadowManagerState);
+    };
+}
@@ -0,0 +1,151 @@
+//! ## KDE slide
+//!
+//! The `org_kde_kwin_slide_manager` global lets KWin slide a surface in from an edge of the screen when
+//! it is mapped and out when it is unmapped, as done for panels and their popups.
+
+use wayland_client::{
+    globals::{BindError, GlobalList},
+    protocol::wl_surface,
+    Connection, Dispatch, QueueHandle,
+};
+use wayland_protocols_plasma::slide::client::{org_kde_kwin_slide, org_kde_kwin_slide_manager};
+
+use crate::{
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+};
+
+pub use org_kde_kwin_slide::Location;
+
+/// The slide manager global.
+#[derive(Debug)]
+pub struct SlideManagerState {
+    manager: org_kde_kwin_slide_manager::OrgKdeKwinSlideManager,
+}
+
+impl SlideManagerState {
+    /// Binds the `org_kde_kwin_slide_manager` global.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support KDE slides.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State: Dispatch<org_kde_kwin_slide_manager::OrgKdeKwinSlideManager, GlobalData> + 'static,
+    {
+        let manager = globals.bind(qh, 1..=1, GlobalData)?;
+        Ok(Self { manager })
+    }
+
+    /// Create the slide of a surface.
+    ///
+    /// The slide is used once its location is set and [`Slide::commit`] is followed by a commit of the
+    /// surface.
+    pub fn create<D>(&self, surface: &wl_surface::WlSurface, qh: &QueueHandle<D>) -> Slide
+    where
+        D: Dispatch<org_kde_kwin_slide::OrgKdeKwinSlide, GlobalData> + 'static,
+    {
+        Slide(self.manager.create(surface, qh, GlobalData))
+    }
+
+    /// Remove the slide of a surface, on the next commit of the surface.
+    ///
+    /// The slide is also removed when the surface is destroyed.
+    pub fn unset(&self, surface: &wl_surface::WlSurface) {
+        self.manager.unset(surface);
+    }
+}
+
+impl ProvidesBoundGlobal<org_kde_kwin_slide_manager::OrgKdeKwinSlideManager, 1>
+    for SlideManagerState
+{
+    fn bound_global(
+        &self,
+    ) -> Result<org_kde_kwin_slide_manager::OrgKdeKwinSlideManager, GlobalError> {
+        Ok(self.manager.clone())
+    }
+}
+
+/// The slide of a surface.
+///
+/// The slide is released when this is dropped. The compositor keeps using it until it is unset with
+/// [`SlideManagerState::unset`].
+#[derive(Debug)]
+pub struct Slide(org_kde_kwin_slide::OrgKdeKwinSlide);
+
+impl Slide {
+    pub fn slide(&self) -> &org_kde_kwin_slide::OrgKdeKwinSlide {
+        &self.0
+    }
+
+    /// Set the edge of the screen the surface slides from.
+    ///
+    /// This state is double buffered and applied by [`Slide::commit`].
+    pub fn set_location(&self, location: Location) {
+        self.0.set_location(location as u32);
+    }
+
+    /// Set the distance from the edge of the screen at which the slide starts, in surface coordinates.
+    ///
+    /// This is usually the distance between the edge and the surface, such as the size of the panel
+    /// of a popup. This state is double buffered and applied by [`Slide::commit`].
+    pub fn set_offset(&self, offset: i32) {
+        self.0.set_offset(offset);
+    }
+
+    /// Apply the pending location and offset, on the next commit of the surface.
+    pub fn commit(&self) {
+        self.0.commit();
+    }
+}
+
+impl Drop for Slide {
+    fn drop(&mut self) {
+        self.0.release();
+    }
+}
+
+impl<D> Dispatch<org_kde_kwin_slide_manager::OrgKdeKwinSlideManager, GlobalData, D>
+    for SlideManagerState
+where
+    D: Dispatch<org_kde_kwin_slide_manager::OrgKdeKwinSlideManager, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &org_kde_kwin_slide_manager::OrgKdeKwinSlideManager,
+        _: org_kde_kwin_slide_manager::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("org_kde_kwin_slide_manager has no events")
+    }
+}
+
+impl<D> Dispatch<org_kde_kwin_slide::OrgKdeKwinSlide, GlobalData, D> for SlideManagerState
+where
+    D: Dispatch<org_kde_kwin_slide::OrgKdeKwinSlide, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &org_kde_kwin_slide::OrgKdeKwinSlide,
+        _: org_kde_kwin_slide::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("org_kde_kwin_slide has no events")
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_slide {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_plasma::slide::client::org_kde_kwin_slide_manager::OrgKdeKwinSlideManager: $crate::globals::GlobalData
+        ] => $crate::shell::plasma::slide::SlideManagerState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_plasma::slide::client::org_kde_kwin_slide::OrgKdeKwinSlide: $crate::globals::GlobalData
+        ] => $crate::shell::plasma::slide::SlideManagerState);
+    };
+}
@@ -0,0 +1,634 @@
+//! ## Plasma window management
+//!
+//! The `org_kde_plasma_window_management` global lists the windows of all clients with their title,
+//! application id, icon and state, and lets the client activate, minimize or close them. It is meant
+//! for taskbars and docks of KDE Plasma, and only one client can bind it at a time.
+//!
+//! [`PlasmaWindowManagementState`] keeps track of the windows and caches their metadata. Changes are
+//! reported to [`PlasmaWindowManagementHandler`] once the compositor sent the initial state of a window.
+
+use std::{
+    os::unix::io::BorrowedFd,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use bitflags::bitflags;
+use wayland_client::{
+    globals::{BindError, GlobalList},
+    protocol::{wl_output, wl_surface},
+    Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols_plasma::plasma_window_management::client::{
+    org_kde_plasma_stacking_order, org_kde_plasma_window, org_kde_plasma_window_management,
+};
+
+use crate::{
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+};
+
+/// Handler for Plasma window management.
+pub trait PlasmaWindowManagementHandler: Sized {
+    fn plasma_window_management_state(&mut self) -> &mut PlasmaWindowManagementState;
+
+    /// A window was mapped, and its initial state is known.
+    fn window_created(&mut self, conn: &Connection, qh: &QueueHandle<Self>, window: &PlasmaWindow);
+
+    /// A window was unmapped.
+    ///
+    /// The window is no longer listed by [`PlasmaWindowManagementState::windows`], and requests on it
+    /// are ignored.
+    fn window_removed(&mut self, conn: &Connection, qh: &QueueHandle<Self>, window: &PlasmaWindow);
+
+    /// The [`PlasmaWindowState`] of a window changed.
+    fn window_state_changed(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        window: &PlasmaWindow,
+    );
+
+    /// The title of a window changed.
+    fn window_title_changed(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        window: &PlasmaWindow,
+    );
+
+    /// Other metadata of a window changed, such as its application id, icon or geometry.
+    fn window_changed(&mut self, conn: &Connection, qh: &QueueHandle<Self>, window: &PlasmaWindow);
+
+    /// The stacking order of the windows changed.
+    ///
+    /// The new order is available from [`PlasmaWindowManagementState::stacking_order`].
+    fn stacking_order_changed(&mut self, conn: &Connection, qh: &QueueHandle<Self>);
+
+    /// The show desktop mode was entered or left.
+    fn show_desktop_changed(&mut self, conn: &Connection, qh: &QueueHandle<Self>, active: bool);
+}
+
+/// The Plasma window management global and the windows it lists.
+#[derive(Debug)]
+pub struct PlasmaWindowManagementState {
+    manager: org_kde_plasma_window_management::OrgKdePlasmaWindowManagement,
+    windows: Vec<PlasmaWindow>,
+    stacking_order: Vec<WindowKey>,
+    show_desktop: bool,
+}
+
+impl PlasmaWindowManagementState {
+    /// Binds the `org_kde_plasma_window_management` global.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support Plasma window management,
+    /// which is also the case if another client already bound it.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State: Dispatch<org_kde_plasma_window_management::OrgKdePlasmaWindowManagement, GlobalData>
+            + PlasmaWindowManagementHandler
+            + 'static,
+    {
+        let manager = globals.bind(qh, 1..=18, GlobalData)?;
+        Ok(Self { manager, windows: Vec::new(), stacking_order: Vec::new(), show_desktop: false })
+    }
+
+    /// The mapped windows whose initial state is known.
+    pub fn windows(&self) -> impl Iterator<Item = &PlasmaWindow> {
+        self.windows.iter().filter(|window| window.data().announced.load(Ordering::Relaxed))
+    }
+
+    /// The mapped windows from bottom to top, or an empty list if the compositor does not report the
+    /// stacking order.
+    pub fn stacking_order(&self) -> Vec<PlasmaWindow> {
+        self.stacking_order
+            .iter()
+            .filter_map(|key| self.windows().find(|window| window.data().matches(key)))
+            .cloned()
+            .collect()
+    }
+
+    /// Whether the show desktop mode is active.
+    pub fn show_desktop(&self) -> bool {
+        self.show_desktop
+    }
+
+    /// Enter or leave the show desktop mode.
+    pub fn set_show_desktop(&self, active: bool) {
+        let state = if active {
+            org_kde_plasma_window_management::ShowDesktop::Enabled
+        } else {
+            org_kde_plasma_window_management::ShowDesktop::Disabled
+        };
+        self.manager.show_desktop(state as u32);
+    }
+}
+
+impl ProvidesBoundGlobal<org_kde_plasma_window_management::OrgKdePlasmaWindowManagement, 18>
+    for PlasmaWindowManagementState
+{
+    fn bound_global(
+        &self,
+    ) -> Result<org_kde_plasma_window_management::OrgKdePlasmaWindowManagement, GlobalError> {
+        Ok(self.manager.clone())
+    }
+}
+
+bitflags! {
+    /// The state of a window, and the actions the compositor allows on it.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct PlasmaWindowState: u32 {
+        const ACTIVE = 0x1;
+        const MINIMIZED = 0x2;
+        const MAXIMIZED = 0x4;
+        const FULLSCREEN = 0x8;
+        const KEEP_ABOVE = 0x10;
+        const KEEP_BELOW = 0x20;
+        const ON_ALL_DESKTOPS = 0x40;
+        const DEMANDS_ATTENTION = 0x80;
+        const CLOSEABLE = 0x100;
+        const MINIMIZABLE = 0x200;
+        const MAXIMIZABLE = 0x400;
+        const FULLSCREENABLE = 0x800;
+        /// The window should not be shown in taskbars.
+        const SKIP_TASKBAR = 0x1000;
+        const SHADEABLE = 0x2000;
+        const SHADED = 0x4000;
+        const MOVABLE = 0x8000;
+        const RESIZABLE = 0x10000;
+        const VIRTUAL_DESKTOP_CHANGEABLE = 0x20000;
+        /// The window should not be shown in window switchers.
+        const SKIP_SWITCHER = 0x40000;
+    }
+}
+
+/// The cached metadata of a window.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct PlasmaWindowInfo {
+    /// The unique id of the window, if the compositor supports version 13 of the protocol.
+    pub uuid: Option<String>,
+    pub title: String,
+    pub app_id: String,
+    /// The resource name of the window, which is the name of the X11 window class for X11 windows.
+    pub resource_name: Option<String>,
+    pub state: PlasmaWindowState,
+    /// The name of the icon of the window in the icon theme.
+    ///
+    /// If this is empty, the icon can be requested with [`PlasmaWindow::request_icon`].
+    pub themed_icon_name: String,
+    pub pid: Option<u32>,
+    /// The position and size of the window, including its decorations, in global compositor space.
+    pub geometry: Option<(i32, i32, u32, u32)>,
+    /// The position and size of the window, without its decorations.
+    pub client_geometry: Option<(i32, i32, u32, u32)>,
+    /// The window this window is a transient of, such as a dialog of the window.
+    pub parent: Option<PlasmaWindow>,
+    /// The ids of the virtual desktops the window is on.
+    pub virtual_desktops: Vec<String>,
+    /// The ids of the activities the window is on.
+    pub activities: Vec<String>,
+    /// The D-Bus service name and object path of the application menu of the window.
+    pub application_menu: Option<(String, String)>,
+}
+
+impl PlasmaWindowInfo {
+    fn new(uuid: Option<String>) -> Self {
+        Self {
+            uuid,
+            title: String::new(),
+            app_id: String::new(),
+            resource_name: None,
+            state: PlasmaWindowState::empty(),
+            themed_icon_name: String::new(),
+            pid: None,
+            geometry: None,
+            client_geometry: None,
+            parent: None,
+            virtual_desktops: Vec::new(),
+            activities: Vec::new(),
+            application_menu: None,
+        }
+    }
+}
+
+/// A window listed by Plasma window management.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlasmaWindow(org_kde_plasma_window::OrgKdePlasmaWindow);
+
+impl PlasmaWindow {
+    pub fn plasma_window(&self) -> &org_kde_plasma_window::OrgKdePlasmaWindow {
+        &self.0
+    }
+
+    /// The cached metadata of the window.
+    pub fn info(&self) -> PlasmaWindowInfo {
+        self.data().info.lock().unwrap().clone()
+    }
+
+    pub fn title(&self) -> String {
+        self.data().info.lock().unwrap().title.clone()
+    }
+
+    pub fn app_id(&self) -> String {
+        self.data().info.lock().unwrap().app_id.clone()
+    }
+
+    pub fn state(&self) -> PlasmaWindowState {
+        self.data().info.lock().unwrap().state
+    }
+
+    /// Request to change the states set in `mask` to their value in `state`.
+    ///
+    /// For example, `set_state(PlasmaWindowState::MINIMIZED, PlasmaWindowState::empty())` unminimizes
+    /// the window.
+    pub fn set_state(&self, mask: PlasmaWindowState, state: PlasmaWindowState) {
+        self.0.set_state(mask.bits(), state.bits());
+    }
+
+    /// Request to activate the window, unminimizing it if needed.
+    pub fn activate(&self) {
+        self.set_state(PlasmaWindowState::ACTIVE, PlasmaWindowState::ACTIVE);
+    }
+
+    /// Request to minimize or unminimize the window.
+    pub fn set_minimized(&self, minimized: bool) {
+        let state =
+            if minimized { PlasmaWindowState::MINIMIZED } else { PlasmaWindowState::empty() };
+        self.set_state(PlasmaWindowState::MINIMIZED, state);
+    }
+
+    /// Set the area of a surface of this client the window minimizes to, such as its taskbar entry,
+    /// relative to the surface.
+    pub fn set_minimized_geometry(
+        &self,
+        panel: &wl_surface::WlSurface,
+        (x, y, width, height): (u32, u32, u32, u32),
+    ) {
+        self.0.set_minimized_geometry(panel, x, y, width, height);
+    }
+
+    /// Remove the area set with [`PlasmaWindow::set_minimized_geometry`] for a surface.
+    pub fn unset_minimized_geometry(&self, panel: &wl_surface::WlSurface) {
+        self.0.unset_minimized_geometry(panel);
+    }
+
+    /// Request to close the window.
+    pub fn close(&self) {
+        self.0.close();
+    }
+
+    /// Request an interactive move of the window.
+    ///
+    /// Does nothing if the compositor only supports a version older than 3.
+    pub fn request_move(&self) {
+        if self.0.version() >= 3 {
+            self.0.request_move();
+        }
+    }
+
+    /// Request an interactive resize of the window.
+    ///
+    /// Does nothing if the compositor only supports a version older than 3.
+    pub fn request_resize(&self) {
+        if self.0.version() >= 3 {
+            self.0.request_resize();
+        }
+    }
+
+    /// Request to move the window to an output.
+    ///
+    /// Does nothing if the compositor only supports a version older than 15.
+    pub fn send_to_output(&self, output: &wl_output::WlOutput) {
+        if self.0.version() >= 15 {
+            self.0.send_to_output(output);
+        }
+    }
+
+    /// Request the compositor to write the icon of the window to a file descriptor, usually the
+    /// writing end of a pipe.
+    ///
+    /// The icon is a `QIcon` serialized with `QDataStream`. Does nothing if the compositor only
+    /// supports a version older than 7.
+    pub fn request_icon(&self, fd: BorrowedFd<'_>) {
+        if self.0.version() >= 7 {
+            self.0.get_icon(fd);
+        }
+    }
+
+    fn data(&self) -> &PlasmaWindowData {
+        self.0.data::<PlasmaWindowData>().unwrap()
+    }
+}
+
+/// How the compositor identifies a window in the stacking order.
+#[derive(Debug)]
+enum WindowKey {
+    Id(u32),
+    Uuid(String),
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct PlasmaWindowData {
+    id: u32,
+    info: Mutex<PlasmaWindowInfo>,
+    /// Whether the initial state was received and the window reported to the handler.
+    announced: AtomicBool,
+}
+
+impl PlasmaWindowData {
+    fn matches(&self, key: &WindowKey) -> bool {
+        match key {
+            WindowKey::Id(id) => self.id == *id,
+            WindowKey::Uuid(uuid) => self.info.lock().unwrap().uuid.as_ref() == Some(uuid),
+        }
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct StackingOrderData {
+    uuids: Mutex<Vec<String>>,
+}
+
+impl PlasmaWindowManagementState {
+    fn add_window<D>(&mut self, id: u32, uuid: Option<String>, qh: &QueueHandle<D>) -> PlasmaWindow
+    where
+        D: Dispatch<org_kde_plasma_window::OrgKdePlasmaWindow, PlasmaWindowData> + 'static,
+    {
+        let data = PlasmaWindowData {
+            id,
+            info: Mutex::new(PlasmaWindowInfo::new(uuid.clone())),
+            announced: AtomicBool::new(false),
+        };
+
+        let window = match uuid {
+            Some(uuid) => self.manager.get_window_by_uuid(uuid, qh, data),
+            None => self.manager.get_window(id, qh, data),
+        };
+
+        let window = PlasmaWindow(window);
+        self.windows.push(window.clone());
+        window
+    }
+}
+
+impl<D> Dispatch<org_kde_plasma_window_management::OrgKdePlasmaWindowManagement, GlobalData, D>
+    for PlasmaWindowManagementState
+where
+    D: Dispatch<org_kde_plasma_window_management::OrgKdePlasmaWindowManagement, GlobalData>
+        + Dispatch<org_kde_plasma_window::OrgKdePlasmaWindow, PlasmaWindowData>
+        + Dispatch<org_kde_plasma_stacking_order::OrgKdePlasmaStackingOrder, StackingOrderData>
+        + PlasmaWindowManagementHandler
+        + 'static,
+{
+    fn event(
+        state: &mut D,
+        manager: &org_kde_plasma_window_management::OrgKdePlasmaWindowManagement,
+        event: org_kde_plasma_window_management::Event,
+        _: &GlobalData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        match event {
+            org_kde_plasma_window_management::Event::ShowDesktopChanged { state: active } => {
+                let active =
+                    active == org_kde_plasma_window_management::ShowDesktop::Enabled as u32;
+                state.plasma_window_management_state().show_desktop = active;
+                state.show_desktop_changed(conn, qh, active);
+            }
+
+            org_kde_plasma_window_management::Event::Window { id } => {
+                let window = state.plasma_window_management_state().add_window(id, None, qh);
+
+                // Before version 4, there is no event marking the end of the initial state.
+                if manager.version() < 4 {
+                    window.data().announced.store(true, Ordering::Relaxed);
+                    state.window_created(conn, qh, &window);
+                }
+            }
+
+            org_kde_plasma_window_management::Event::WindowWithUuid { id, uuid } => {
+                state.plasma_window_management_state().add_window(id, Some(uuid), qh);
+            }
+
+            org_kde_plasma_window_management::Event::StackingOrderChanged { ids } => {
+                state.plasma_window_management_state().stacking_order = ids
+                    .chunks_exact(4)
+                    .map(|id| WindowKey::Id(u32::from_ne_bytes(id.try_into().unwrap())))
+                    .collect();
+                state.stacking_order_changed(conn, qh);
+            }
+
+            org_kde_plasma_window_management::Event::StackingOrderUuidChanged { uuids } => {
+                state.plasma_window_management_state().stacking_order = uuids
+                    .split(';')
+                    .filter(|uuid| !uuid.is_empty())
+                    .map(|uuid| WindowKey::Uuid(uuid.to_owned()))
+                    .collect();
+                state.stacking_order_changed(conn, qh);
+            }
+
+            // Since version 17, the order is sent through a new object each time it changes.
+            org_kde_plasma_window_management::Event::StackingOrderChanged2 => {
+                manager.get_stacking_order(qh, StackingOrderData::default());
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<org_kde_plasma_window::OrgKdePlasmaWindow, PlasmaWindowData, D>
+    for PlasmaWindowManagementState
+where
+    D: Dispatch<org_kde_plasma_window::OrgKdePlasmaWindow, PlasmaWindowData>
+        + PlasmaWindowManagementHandler,
+{
+    fn event(
+        state: &mut D,
+        proxy: &org_kde_plasma_window::OrgKdePlasmaWindow,
+        event: org_kde_plasma_window::Event,
+        data: &PlasmaWindowData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let window = PlasmaWindow(proxy.clone());
+
+        enum Change {
+            State,
+            Title,
+            Other,
+        }
+
+        let change = {
+            let mut info = data.info.lock().unwrap();
+
+            match event {
+                org_kde_plasma_window::Event::TitleChanged { title } => {
+                    info.title = title;
+                    Change::Title
+                }
+
+                org_kde_plasma_window::Event::AppIdChanged { app_id } => {
+                    info.app_id = app_id;
+                    Change::Other
+                }
+
+                org_kde_plasma_window::Event::StateChanged { flags } => {
+                    info.state = PlasmaWindowState::from_bits_truncate(flags);
+                    Change::State
+                }
+
+                org_kde_plasma_window::Event::ThemedIconNameChanged { name } => {
+                    info.themed_icon_name = name;
+                    Change::Other
+                }
+
+                org_kde_plasma_window::Event::IconChanged => Change::Other,
+
+                org_kde_plasma_window::Event::PidChanged { pid } => {
+                    info.pid = Some(pid);
+                    Change::Other
+                }
+
+                org_kde_plasma_window::Event::ParentWindow { parent } => {
+                    info.parent = parent.map(PlasmaWindow);
+                    Change::Other
+                }
+
+                org_kde_plasma_window::Event::Geometry { x, y, width, height } => {
+                    info.geometry = Some((x, y, width, height));
+                    Change::Other
+                }
+
+                org_kde_plasma_window::Event::ClientGeometry { x, y, width, height } => {
+                    info.client_geometry = Some((x, y, width, height));
+                    Change::Other
+                }
+
+                org_kde_plasma_window::Event::ResourceNameChanged { resource_name } => {
+                    info.resource_name = Some(resource_name);
+                    Change::Other
+                }
+
+                org_kde_plasma_window::Event::ApplicationMenu { service_name, object_path } => {
+                    info.application_menu = Some((service_name, object_path));
+                    Change::Other
+                }
+
+                org_kde_plasma_window::Event::VirtualDesktopEntered { id } => {
+                    if !info.virtual_desktops.contains(&id) {
+                        info.virtual_desktops.push(id);
+                    }
+                    Change::Other
+                }
+
+                org_kde_plasma_window::Event::VirtualDesktopLeft { is } => {
+                    info.virtual_desktops.retain(|id| *id != is);
+                    Change::Other
+                }
+
+                org_kde_plasma_window::Event::ActivityEntered { id } => {
+                    if !info.activities.contains(&id) {
+                        info.activities.push(id);
+                    }
+                    Change::Other
+                }
+
+                org_kde_plasma_window::Event::ActivityLeft { id } => {
+                    info.activities.retain(|activity| *activity != id);
+                    Change::Other
+                }
+
+                // Numbered virtual desktops are deprecated in favor of the desktop ids.
+                org_kde_plasma_window::Event::VirtualDesktopChanged { .. } => return,
+
+                org_kde_plasma_window::Event::InitialState => {
+                    drop(info);
+                    data.announced.store(true, Ordering::Relaxed);
+                    state.window_created(conn, qh, &window);
+                    return;
+                }
+
+                org_kde_plasma_window::Event::Unmapped => {
+                    drop(info);
+                    state.plasma_window_management_state().windows.retain(|w| w != &window);
+
+                    if data.announced.swap(false, Ordering::Relaxed) {
+                        state.window_removed(conn, qh, &window);
+                    }
+
+                    if proxy.version() >= 4 {
+                        proxy.destroy();
+                    }
+                    return;
+                }
+
+                _ => unreachable!(),
+            }
+        };
+
+        // Changes that are part of the initial state are reported by window_created.
+        if !data.announced.load(Ordering::Relaxed) {
+            return;
+        }
+
+        match change {
+            Change::State => state.window_state_changed(conn, qh, &window),
+            Change::Title => state.window_title_changed(conn, qh, &window),
+            Change::Other => state.window_changed(conn, qh, &window),
+        }
+    }
+}
+
+impl<D> Dispatch<org_kde_plasma_stacking_order::OrgKdePlasmaStackingOrder, StackingOrderData, D>
+    for PlasmaWindowManagementState
+where
+    D: Dispatch<org_kde_plasma_stacking_order::OrgKdePlasmaStackingOrder, StackingOrderData>
+        + PlasmaWindowManagementHandler,
+{
+    fn event(
+        state: &mut D,
+        _: &org_kde_plasma_stacking_order::OrgKdePlasmaStackingOrder,
+        event: org_kde_plasma_stacking_order::Event,
+        data: &StackingOrderData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        match event {
+            org_kde_plasma_stacking_order::Event::Window { uuid } => {
+                data.uuids.lock().unwrap().push(uuid);
+            }
+
+            org_kde_plasma_stacking_order::Event::Done => {
+                let uuids = std::mem::take(&mut *data.uuids.lock().unwrap());
+                state.plasma_window_management_state().stacking_order =
+                    uuids.into_iter().map(WindowKey::Uuid).collect();
+                state.stacking_order_changed(conn, qh);
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_plasma_window_management {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_plasma::plasma_window_management::client::org_kde_plasma_window_management::OrgKdePlasmaWindowManagement: $crate::globals::GlobalData
+        ] => $crate::shell::plasma::window_management::PlasmaWindowManagementState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_plasma::plasma_window_management::client::org_kde_plasma_window::OrgKdePlasmaWindow: $crate::shell::plasma::window_management::PlasmaWindowData
+        ] => $crate::shell::plasma::window_management::PlasmaWindowManagementState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_plasma::plasma_window_management::client::org_kde_plasma_stacking_order::OrgKdePlasmaStackingOrder: $crate::shell::plasma::window_management::StackingOrderData
+        ] => $crate::shell::plasma::window_management::PlasmaWindowManagementState);
+    };
+}
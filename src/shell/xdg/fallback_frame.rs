@@ -41,6 +41,30 @@ const BTN_HOVER_BG: u32 = 0xFF808080;
 const PRIMARY_COLOR_ACTIVE: u32 = 0xFF3A3A3A;
 const PRIMARY_COLOR_INACTIVE: u32 = 0xFF242424;
 
+/// The colors of a [`FallbackFrame`], in ARGB8888.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameTheme {
+    /// The color of the header and borders when the window is active.
+    pub active: u32,
+    /// The color of the header and borders when the window is inactive.
+    pub inactive: u32,
+    /// The color of the button icons.
+    pub button_icon: u32,
+    /// The background color of a hovered button.
+    pub button_hover: u32,
+}
+
+impl Default for FrameTheme {
+    fn default() -> Self {
+        Self {
+            active: PRIMARY_COLOR_ACTIVE,
+            inactive: PRIMARY_COLOR_INACTIVE,
+            button_icon: BTN_ICON_COLOR,
+            button_hover: BTN_HOVER_BG,
+        }
+    }
+}
+
 /// The default ugly frame.
 #[derive(Debug)]
 pub struct FallbackFrame<State> {
@@ -87,6 +111,9 @@ pub struct FallbackFrame<State> {
 
     /// Buttons state.
     buttons: [Option<UIButton>; 3],
+
+    /// The colors of the frame.
+    theme: FrameTheme,
 }
 
 impl<State> FallbackFrame<State>
@@ -119,9 +146,21 @@ where
             mouse_location: Location::None,
             mouse_coords: (0, 0),
             buttons: Self::supported_buttons(wm_capabilities),
+            theme: FrameTheme::default(),
         })
     }
 
+    /// The colors of the frame.
+    pub fn theme(&self) -> FrameTheme {
+        self.theme
+    }
+
+    /// Change the colors of the frame.
+    pub fn set_theme(&mut self, theme: FrameTheme) {
+        self.dirty |= self.theme != theme;
+        self.theme = theme;
+    }
+
     fn supported_buttons(wm_capabilities: WindowManagerCapabilities) -> [Option<UIButton>; 3] {
         let maximize = wm_capabilities
             .contains(WindowManagerCapabilities::MAXIMIZE)
@@ -194,6 +233,7 @@ where
         scale: u32,
         is_active: bool,
         mouse_location: &Location,
+        theme: &FrameTheme,
     ) {
         let scale = scale as usize;
         for (idx, &button) in buttons.iter().flatten().enumerate() {
@@ -204,7 +244,7 @@ where
                         idx * HEADER_SIZE as usize,
                         scale,
                         width as usize,
-                        BTN_HOVER_BG.to_le_bytes(),
+                        theme.button_hover.to_le_bytes(),
                     );
                 }
                 Self::draw_icon(
@@ -212,7 +252,7 @@ where
                     width as usize,
                     idx * HEADER_SIZE as usize,
                     scale,
-                    BTN_ICON_COLOR.to_le_bytes(),
+                    theme.button_icon.to_le_bytes(),
                     button,
                 );
             }
@@ -539,7 +579,7 @@ where
 
         let is_active = self.state.contains(WindowState::ACTIVATED);
         let fill_color =
-            if is_active { PRIMARY_COLOR_ACTIVE } else { PRIMARY_COLOR_INACTIVE }.to_le_bytes();
+            if is_active { self.theme.active } else { self.theme.inactive }.to_le_bytes();
 
         for (idx, part) in render_data.parts.iter().enumerate() {
             // We don't support fractinal scaling here, so round up.
@@ -572,6 +612,7 @@ where
                     scale as u32,
                     is_active,
                     &self.mouse_location,
+                    &self.theme,
                 );
             }
 
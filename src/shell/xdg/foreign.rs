@@ -0,0 +1,340 @@
+//! ## xdg-foreign
+//!
+//! Lets a toplevel of one client be the parent of a toplevel of another client, such as a file dialog
+//! shown by a portal for an application.
+//!
+//! The application exports its toplevel with [`ForeignState::export_toplevel`] and passes the handle
+//! it receives in [`ForeignHandler::exported`] to the other client, usually over D-Bus. The other client
+//! imports it with [`ForeignState::import_toplevel`] and uses [`ImportedToplevel::set_parent_of`] on its
+//! own toplevel.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex, Weak,
+};
+
+use wayland_client::{
+    globals::GlobalList, protocol::wl_surface, Connection, Dispatch, QueueHandle,
+};
+use wayland_protocols::xdg::foreign::zv2::client::{
+    zxdg_exported_v2, zxdg_exporter_v2, zxdg_imported_v2, zxdg_importer_v2,
+};
+
+use crate::{error::GlobalError, globals::GlobalData, registry::GlobalProxy};
+
+/// Handler for exported and imported toplevels.
+pub trait ForeignHandler: Sized {
+    /// The compositor sent the handle of an exported toplevel.
+    ///
+    /// The handle can be passed to another client to import the toplevel, and stays valid until the
+    /// [`ExportedToplevel`] is dropped.
+    fn exported(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        exported: &ExportedToplevel,
+        handle: String,
+    );
+
+    /// An imported toplevel is no longer valid, because the handle was invalid or the exporting client
+    /// stopped exporting it.
+    ///
+    /// Surfaces that were made children of the imported toplevel lose their parent.
+    fn imported_destroyed(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        imported: &ImportedToplevel,
+    );
+}
+
+/// The xdg-foreign globals.
+#[derive(Debug)]
+pub struct ForeignState {
+    exporter: GlobalProxy<zxdg_exporter_v2::ZxdgExporterV2>,
+    importer: GlobalProxy<zxdg_importer_v2::ZxdgImporterV2>,
+}
+
+impl ForeignState {
+    /// Binds the `zxdg_exporter_v2` and `zxdg_importer_v2` globals.
+    ///
+    /// A client usually only needs one of them, so a missing global is only reported when it is used.
+    pub fn new<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Self
+    where
+        State: Dispatch<zxdg_exporter_v2::ZxdgExporterV2, GlobalData>
+            + Dispatch<zxdg_importer_v2::ZxdgImporterV2, GlobalData>
+            + 'static,
+    {
+        let exporter = GlobalProxy::bind(globals, qh, 1..=1, GlobalData);
+        let importer = GlobalProxy::bind(globals, qh, 1..=1, GlobalData);
+        Self { exporter, importer }
+    }
+
+    /// Export a toplevel, so that another client can use it as parent.
+    ///
+    /// The handle is delivered to [`ForeignHandler::exported`].
+    ///
+    /// # Protocol errors
+    ///
+    /// The surface must have the xdg_toplevel role.
+    pub fn export_toplevel<D>(
+        &self,
+        surface: &wl_surface::WlSurface,
+        qh: &QueueHandle<D>,
+    ) -> Result<ExportedToplevel, GlobalError>
+    where
+        D: Dispatch<zxdg_exported_v2::ZxdgExportedV2, ExportedData> + 'static,
+    {
+        let exporter = self.exporter.get()?;
+
+        // Freeze the queue so the handle can not be dispatched before the Weak is usable.
+        let freeze = qh.freeze();
+        let inner = Arc::new_cyclic(|weak| ExportedInner {
+            exported: exporter.export_toplevel(surface, qh, ExportedData(weak.clone())),
+            surface: surface.clone(),
+            handle: Mutex::new(None),
+        });
+        drop(freeze);
+
+        Ok(ExportedToplevel(inner))
+    }
+
+    /// Import a toplevel exported by another client.
+    ///
+    /// If the handle is invalid, [`ForeignHandler::imported_destroyed`] is called.
+    pub fn import_toplevel<D>(
+        &self,
+        handle: String,
+        qh: &QueueHandle<D>,
+    ) -> Result<ImportedToplevel, GlobalError>
+    where
+        D: Dispatch<zxdg_imported_v2::ZxdgImportedV2, ImportedData> + 'static,
+    {
+        let importer = self.importer.get()?;
+
+        let freeze = qh.freeze();
+        let inner = Arc::new_cyclic(|weak| ImportedInner {
+            imported: importer.import_toplevel(handle.clone(), qh, ImportedData(weak.clone())),
+            handle,
+            destroyed: AtomicBool::new(false),
+        });
+        drop(freeze);
+
+        Ok(ImportedToplevel(inner))
+    }
+}
+
+/// A toplevel exported to other clients.
+///
+/// The toplevel stops being exported when the last clone of this is dropped, after which other clients
+/// can no longer import it, and their imported toplevels are destroyed.
+#[derive(Debug, Clone)]
+pub struct ExportedToplevel(Arc<ExportedInner>);
+
+impl ExportedToplevel {
+    pub fn exported(&self) -> &zxdg_exported_v2::ZxdgExportedV2 {
+        &self.0.exported
+    }
+
+    /// The exported surface.
+    pub fn wl_surface(&self) -> &wl_surface::WlSurface {
+        &self.0.surface
+    }
+
+    /// The handle of the exported toplevel, or [`None`] if the compositor did not send it yet.
+    pub fn handle(&self) -> Option<String> {
+        self.0.handle.lock().unwrap().clone()
+    }
+}
+
+impl PartialEq for ExportedToplevel {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// A toplevel of another client.
+///
+/// The imported toplevel is destroyed when the last clone of this is dropped, and surfaces made its
+/// children lose their parent.
+#[derive(Debug, Clone)]
+pub struct ImportedToplevel(Arc<ImportedInner>);
+
+impl ImportedToplevel {
+    pub fn imported(&self) -> &zxdg_imported_v2::ZxdgImportedV2 {
+        &self.0.imported
+    }
+
+    /// The handle this toplevel was imported with.
+    pub fn handle(&self) -> &str {
+        &self.0.handle
+    }
+
+    /// Whether the imported toplevel is no longer valid.
+    ///
+    /// This is set before [`ForeignHandler::imported_destroyed`] is called.
+    pub fn is_destroyed(&self) -> bool {
+        self.0.destroyed.load(Ordering::Relaxed)
+    }
+
+    /// Make a surface of this client a child of the imported toplevel, as with
+    /// [`Window::set_parent`](crate::shell::xdg::window::Window::set_parent).
+    ///
+    /// Does nothing if the imported toplevel was destroyed.
+    ///
+    /// # Protocol errors
+    ///
+    /// The surface must have the xdg_toplevel role.
+    pub fn set_parent_of(&self, surface: &wl_surface::WlSurface) {
+        if !self.is_destroyed() {
+            self.0.imported.set_parent_of(surface);
+        }
+    }
+}
+
+impl PartialEq for ImportedToplevel {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[derive(Debug)]
+struct ExportedInner {
+    exported: zxdg_exported_v2::ZxdgExportedV2,
+    surface: wl_surface::WlSurface,
+    handle: Mutex<Option<String>>,
+}
+
+impl Drop for ExportedInner {
+    fn drop(&mut self) {
+        self.exported.destroy();
+    }
+}
+
+#[derive(Debug)]
+struct ImportedInner {
+    imported: zxdg_imported_v2::ZxdgImportedV2,
+    handle: String,
+    destroyed: AtomicBool,
+}
+
+impl Drop for ImportedInner {
+    fn drop(&mut self) {
+        self.imported.destroy();
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct ExportedData(Weak<ExportedInner>);
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct ImportedData(Weak<ImportedInner>);
+
+impl<D> Dispatch<zxdg_exporter_v2::ZxdgExporterV2, GlobalData, D> for ForeignState
+where
+    D: Dispatch<zxdg_exporter_v2::ZxdgExporterV2, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &zxdg_exporter_v2::ZxdgExporterV2,
+        _: zxdg_exporter_v2::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("zxdg_exporter_v2 has no events")
+    }
+}
+
+impl<D> Dispatch<zxdg_importer_v2::ZxdgImporterV2, GlobalData, D> for ForeignState
+where
+    D: Dispatch<zxdg_importer_v2::ZxdgImporterV2, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &zxdg_importer_v2::ZxdgImporterV2,
+        _: zxdg_importer_v2::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("zxdg_importer_v2 has no events")
+    }
+}
+
+impl<D> Dispatch<zxdg_exported_v2::ZxdgExportedV2, ExportedData, D> for ForeignState
+where
+    D: Dispatch<zxdg_exported_v2::ZxdgExportedV2, ExportedData> + ForeignHandler,
+{
+    fn event(
+        state: &mut D,
+        _: &zxdg_exported_v2::ZxdgExportedV2,
+        event: zxdg_exported_v2::Event,
+        data: &ExportedData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        // The toplevel was dropped while the event was in flight.
+        let exported = match data.0.upgrade() {
+            Some(inner) => ExportedToplevel(inner),
+            None => return,
+        };
+
+        match event {
+            zxdg_exported_v2::Event::Handle { handle } => {
+                *exported.0.handle.lock().unwrap() = Some(handle.clone());
+                state.exported(conn, qh, &exported, handle);
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<zxdg_imported_v2::ZxdgImportedV2, ImportedData, D> for ForeignState
+where
+    D: Dispatch<zxdg_imported_v2::ZxdgImportedV2, ImportedData> + ForeignHandler,
+{
+    fn event(
+        state: &mut D,
+        _: &zxdg_imported_v2::ZxdgImportedV2,
+        event: zxdg_imported_v2::Event,
+        data: &ImportedData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let imported = match data.0.upgrade() {
+            Some(inner) => ImportedToplevel(inner),
+            None => return,
+        };
+
+        match event {
+            zxdg_imported_v2::Event::Destroyed => {
+                imported.0.destroyed.store(true, Ordering::Relaxed);
+                state.imported_destroyed(conn, qh, &imported);
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_xdg_foreign {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::xdg::foreign::zv2::client::zxdg_exporter_v2::ZxdgExporterV2: $crate::globals::GlobalData
+        ] => $crate::shell::xdg::foreign::ForeignState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::xdg::foreign::zv2::client::zxdg_importer_v2::ZxdgImporterV2: $crate::globals::GlobalData
+        ] => $crate::shell::xdg::foreign::ForeignState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::xdg::foreign::zv2::client::zxdg_exported_v2::ZxdgExportedV2: $crate::shell::xdg::foreign::ExportedData
+        ] => $crate::shell::xdg::foreign::ForeignState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::xdg::foreign::zv2::client::zxdg_imported_v2::ZxdgImportedV2: $crate::shell::xdg::foreign::ImportedData
+        ] => $crate::shell::xdg::foreign::ForeignState);
+    };
+}
@@ -0,0 +1,244 @@
+//! ## Window frame
+//!
+//! Some compositors, such as GNOME's, never draw decorations for windows. [`WindowFrame`] is a
+//! ready-made fallback for them: it draws a titlebar with close, maximize and minimize buttons and
+//! resize borders with a [`FallbackFrame`] in subsurfaces around a [`Window`], and turns the pointer
+//! input on them into requests to the compositor, such as moving or resizing the window.
+//!
+//! The frame follows the decoration mode negotiated with the compositor: it is only shown while the
+//! window uses [`DecorationMode::Client`].
+//!
+//! This module requires the `frame` feature.
+
+use std::{error::Error, num::NonZeroU32, sync::Arc, time::Duration};
+
+use crate::reexports::client::{
+    protocol::{wl_seat, wl_subsurface::WlSubsurface, wl_surface::WlSurface},
+    Dispatch, Proxy, QueueHandle,
+};
+use crate::reexports::csd_frame::{DecorationsFrame, FrameAction, FrameClick, ResizeEdge};
+use crate::reexports::protocols::xdg::shell::client::xdg_toplevel;
+
+use crate::{
+    compositor::SurfaceData,
+    seat::pointer::{CursorIcon, PointerButton, PointerEvent, PointerEventKind},
+    shell::WaylandSurface,
+    shm::Shm,
+    subcompositor::{SubcompositorState, SubsurfaceData},
+};
+
+use super::{
+    fallback_frame::FallbackFrame,
+    window::{DecorationMode, Window, WindowConfigure},
+    XdgSurface,
+};
+
+pub use super::fallback_frame::FrameTheme;
+
+/// What the application should do after pointer input on a [`WindowFrame`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameRequest {
+    /// The close button was clicked.
+    Close,
+    /// The pointer should show this cursor.
+    Cursor(CursorIcon),
+}
+
+/// Client side decorations of a [`Window`].
+#[derive(Debug)]
+pub struct WindowFrame<State> {
+    // Dropped before the window, so the subsurfaces are destroyed before their parent.
+    frame: FallbackFrame<State>,
+    window: Window,
+}
+
+impl<State> WindowFrame<State>
+where
+    State: Dispatch<WlSurface, SurfaceData> + Dispatch<WlSubsurface, SubsurfaceData> + 'static,
+{
+    /// Create the frame of a window.
+    ///
+    /// The frame is hidden until a configure with [`DecorationMode::Client`] is passed to
+    /// [`WindowFrame::configure`].
+    pub fn new(
+        window: &Window,
+        shm: &Shm,
+        subcompositor: Arc<SubcompositorState>,
+        queue_handle: QueueHandle<State>,
+        theme: FrameTheme,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut frame = FallbackFrame::new(window, shm, subcompositor, queue_handle)?;
+        frame.set_hidden(true);
+        frame.set_theme(theme);
+        Ok(Self { frame, window: window.clone() })
+    }
+
+    /// The window of this frame.
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    /// The colors of the frame.
+    pub fn theme(&self) -> FrameTheme {
+        self.frame.theme()
+    }
+
+    /// Change the colors of the frame.
+    pub fn set_theme(&mut self, theme: FrameTheme) {
+        self.frame.set_theme(theme);
+    }
+
+    /// Whether the frame is hidden, because the compositor draws the decorations.
+    pub fn is_hidden(&self) -> bool {
+        self.frame.is_hidden()
+    }
+
+    /// Set whether the borders of the frame resize the window.
+    pub fn set_resizable(&mut self, resizable: bool) {
+        self.frame.set_resizable(resizable);
+    }
+
+    /// Set the scale factor of the window, the frame is drawn at the next integer scale.
+    pub fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.frame.set_scaling_factor(scale_factor);
+    }
+
+    /// Apply a configure of the window, and set the window geometry to include the frame.
+    ///
+    /// Returns the size of the content of the window, without the frame. `default_size` is used if
+    /// the compositor lets the client choose the size.
+    pub fn configure(
+        &mut self,
+        configure: &WindowConfigure,
+        default_size: (NonZeroU32, NonZeroU32),
+    ) -> (NonZeroU32, NonZeroU32) {
+        let one = NonZeroU32::new(1).unwrap();
+
+        if configure.decoration_mode != DecorationMode::Client {
+            self.frame.set_hidden(true);
+
+            let width = configure.new_size.0.unwrap_or(default_size.0);
+            let height = configure.new_size.1.unwrap_or(default_size.1);
+            self.window.xdg_surface().set_window_geometry(
+                0,
+                0,
+                width.get() as i32,
+                height.get() as i32,
+            );
+            return (width, height);
+        }
+
+        self.frame.set_hidden(false);
+        // The state decides whether the borders are drawn, so it is updated before resizing.
+        self.frame.update_state(configure.state);
+        self.frame.update_wm_capabilities(configure.capabilities);
+
+        let (width, height) = match configure.new_size {
+            (Some(width), Some(height)) => self.frame.subtract_borders(width, height),
+            _ => (Some(default_size.0), Some(default_size.1)),
+        };
+        let (width, height) = (width.unwrap_or(one), height.unwrap_or(one));
+        self.frame.resize(width, height);
+
+        let (x, y) = self.frame.location();
+        let (outer_width, outer_height) = self.frame.add_borders(width.get(), height.get());
+        self.window.xdg_surface().set_window_geometry(
+            x,
+            y,
+            outer_width as i32,
+            outer_height as i32,
+        );
+
+        (width, height)
+    }
+
+    /// Whether the frame needs to be drawn again.
+    pub fn is_dirty(&self) -> bool {
+        self.frame.is_dirty() && !self.frame.is_hidden()
+    }
+
+    /// Draw the frame if it changed.
+    ///
+    /// Returns `true` if the frame is synchronized with the window, in which case it is only shown
+    /// after the next commit of the window.
+    pub fn draw(&mut self) -> bool {
+        self.is_dirty() && self.frame.draw()
+    }
+
+    /// Handle a pointer event, moving or resizing the window or applying the buttons as needed.
+    ///
+    /// Events on surfaces other than the frame, such as the window itself, are ignored. The frame
+    /// may need to be drawn again afterwards, to show the hovered button.
+    pub fn handle_pointer_event(
+        &mut self,
+        seat: &wl_seat::WlSeat,
+        event: &PointerEvent,
+    ) -> Option<FrameRequest> {
+        if &event.surface == self.window.wl_surface() || self.frame.is_hidden() {
+            return None;
+        }
+
+        let (x, y) = event.position;
+        match event.kind {
+            PointerEventKind::Enter { .. } => self
+                .frame
+                .click_point_moved(Duration::ZERO, &event.surface.id(), x, y)
+                .map(FrameRequest::Cursor),
+            PointerEventKind::Motion { time } => self
+                .frame
+                .click_point_moved(Duration::from_millis(time.into()), &event.surface.id(), x, y)
+                .map(FrameRequest::Cursor),
+            PointerEventKind::Leave { .. } => {
+                self.frame.click_point_left();
+                None
+            }
+            PointerEventKind::Press { time, serial, .. }
+            | PointerEventKind::Release { time, serial, .. } => {
+                let pressed = matches!(event.kind, PointerEventKind::Press { .. });
+                let click = match event.kind.button()? {
+                    PointerButton::Left => FrameClick::Normal,
+                    PointerButton::Right => FrameClick::Alternate,
+                    _ => return None,
+                };
+
+                let action =
+                    self.frame.on_click(Duration::from_millis(time.into()), click, pressed)?;
+                self.frame_action(seat, serial, action)
+            }
+            PointerEventKind::Axis { .. } => None,
+        }
+    }
+
+    fn frame_action(
+        &self,
+        seat: &wl_seat::WlSeat,
+        serial: u32,
+        action: FrameAction,
+    ) -> Option<FrameRequest> {
+        match action {
+            FrameAction::Close => return Some(FrameRequest::Close),
+            FrameAction::Minimize => self.window.set_minimized(),
+            FrameAction::Maximize => self.window.set_maximized(),
+            FrameAction::UnMaximize => self.window.unset_maximized(),
+            FrameAction::ShowMenu(x, y) => self.window.show_window_menu(seat, serial, (x, y)),
+            FrameAction::Move => self.window.move_(seat, serial),
+            FrameAction::Resize(edge) => {
+                let edge = match edge {
+                    ResizeEdge::Top => xdg_toplevel::ResizeEdge::Top,
+                    ResizeEdge::Bottom => xdg_toplevel::ResizeEdge::Bottom,
+                    ResizeEdge::Left => xdg_toplevel::ResizeEdge::Left,
+                    ResizeEdge::TopLeft => xdg_toplevel::ResizeEdge::TopLeft,
+                    ResizeEdge::BottomLeft => xdg_toplevel::ResizeEdge::BottomLeft,
+                    ResizeEdge::Right => xdg_toplevel::ResizeEdge::Right,
+                    ResizeEdge::TopRight => xdg_toplevel::ResizeEdge::TopRight,
+                    ResizeEdge::BottomRight => xdg_toplevel::ResizeEdge::BottomRight,
+                    _ => return None,
+                };
+                self.window.resize(seat, serial, edge);
+            }
+            _ => (),
+        }
+
+        None
+    }
+}
@@ -2,7 +2,7 @@
 // TODO: Examples
 
 use std::os::unix::io::OwnedFd;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, Weak};
 
 use crate::reexports::client::globals::{BindError, GlobalList};
 use crate::reexports::client::Connection;
@@ -12,9 +12,13 @@ use crate::reexports::protocols::xdg::decoration::zv1::client::zxdg_toplevel_dec
 use crate::reexports::protocols::xdg::decoration::zv1::client::{
     zxdg_decoration_manager_v1, zxdg_toplevel_decoration_v1,
 };
+use crate::reexports::protocols::xdg::dialog::v1::client::xdg_wm_dialog_v1;
 use crate::reexports::protocols::xdg::shell::client::{
     xdg_positioner, xdg_surface, xdg_toplevel, xdg_wm_base,
 };
+use crate::reexports::protocols_plasma::server_decoration::client::{
+    org_kde_kwin_server_decoration, org_kde_kwin_server_decoration_manager,
+};
 
 use crate::compositor::Surface;
 use crate::error::GlobalError;
@@ -23,12 +27,16 @@ use crate::registry::GlobalProxy;
 
 use self::window::inner::WindowInner;
 use self::window::{
-    DecorationMode, Window, WindowConfigure, WindowData, WindowDecorations, WindowHandler,
+    ConstrainedEdges, DecorationMode, Window, WindowConfigure, WindowData, WindowDecorations,
+    WindowHandler,
 };
 
 use super::WaylandSurface;
 
 pub mod fallback_frame;
+pub mod foreign;
+#[cfg(feature = "frame")]
+pub mod frame;
 pub mod popup;
 pub mod window;
 
@@ -37,6 +45,9 @@ pub mod window;
 pub struct XdgShell {
     xdg_wm_base: xdg_wm_base::XdgWmBase,
     xdg_decoration_manager: GlobalProxy<zxdg_decoration_manager_v1::ZxdgDecorationManagerV1>,
+    xdg_wm_dialog: GlobalProxy<xdg_wm_dialog_v1::XdgWmDialogV1>,
+    kde_decoration_manager:
+        GlobalProxy<org_kde_kwin_server_decoration_manager::OrgKdeKwinServerDecorationManager>,
 }
 
 impl XdgShell {
@@ -44,12 +55,14 @@ impl XdgShell {
     // Note: if bumping this version number, check if the changes to the wayland XML cause an API
     // break in the rust interfaces.  If it does, be sure to remove other ProvidesBoundGlobal
     // impls; if it does not, consider adding one for the previous (compatible) version.
-    pub const API_VERSION_MAX: u32 = 6;
+    pub const API_VERSION_MAX: u32 = 7;
 
     /// Binds the xdg shell global, `xdg_wm_base`.
     ///
     /// If available, the `zxdg_decoration_manager_v1` global will be bound to allow server side decorations
-    /// for windows.
+    /// for windows, and the `xdg_wm_dialog_v1` global to allow marking windows as modal dialogs. The
+    /// `org_kde_kwin_server_decoration_manager` global is bound as a fallback for server side decorations
+    /// on compositors which only support the KDE protocol.
     ///
     /// # Errors
     ///
@@ -58,11 +71,18 @@ impl XdgShell {
     where
         State: Dispatch<xdg_wm_base::XdgWmBase, GlobalData, State>
             + Dispatch<zxdg_decoration_manager_v1::ZxdgDecorationManagerV1, GlobalData, State>
-            + 'static,
+            + Dispatch<xdg_wm_dialog_v1::XdgWmDialogV1, GlobalData, State>
+            + Dispatch<
+                org_kde_kwin_server_decoration_manager::OrgKdeKwinServerDecorationManager,
+                GlobalData,
+                State,
+            > + 'static,
     {
         let xdg_wm_base = globals.bind(qh, 1..=Self::API_VERSION_MAX, GlobalData)?;
-        let xdg_decoration_manager = GlobalProxy::from(globals.bind(qh, 1..=1, GlobalData));
-        Ok(Self { xdg_wm_base, xdg_decoration_manager })
+        let xdg_decoration_manager = GlobalProxy::bind(globals, qh, 1..=1, GlobalData);
+        let xdg_wm_dialog = GlobalProxy::bind(globals, qh, 1..=1, GlobalData);
+        let kde_decoration_manager = GlobalProxy::bind(globals, qh, 1..=1, GlobalData);
+        Ok(Self { xdg_wm_base, xdg_decoration_manager, xdg_wm_dialog, kde_decoration_manager })
     }
 
     /// Creates a new, unmapped window.
@@ -89,10 +109,13 @@ impl XdgShell {
         State: Dispatch<xdg_surface::XdgSurface, WindowData>
             + Dispatch<xdg_toplevel::XdgToplevel, WindowData>
             + Dispatch<zxdg_toplevel_decoration_v1::ZxdgToplevelDecorationV1, WindowData>
+            + Dispatch<org_kde_kwin_server_decoration::OrgKdeKwinServerDecoration, WindowData>
             + WindowHandler
             + 'static,
     {
         let decoration_manager = self.xdg_decoration_manager.get().ok();
+        let kde_decoration_manager = self.kde_decoration_manager.get().ok();
+        let wm_dialog = self.xdg_wm_dialog.get().ok().cloned();
         let surface = surface.into();
 
         // Freeze the queue during the creation of the Arc to avoid a race between events on the
@@ -138,19 +161,61 @@ impl XdgShell {
                 }
             });
 
+            // Fall back to KDE server decorations if xdg-decoration is not available.
+            let kde_decoration = match (&toplevel_decoration, kde_decoration_manager) {
+                (None, Some(kde_decoration_manager)) => match decorations {
+                    WindowDecorations::ClientOnly | WindowDecorations::None => None,
+
+                    _ => {
+                        let kde_decoration = kde_decoration_manager.create(
+                            xdg_surface.wl_surface(),
+                            qh,
+                            WindowData(weak.clone()),
+                        );
+
+                        let mode = match decorations {
+                            WindowDecorations::RequestServer => {
+                                Some(org_kde_kwin_server_decoration::Mode::Server)
+                            }
+                            WindowDecorations::RequestClient => {
+                                Some(org_kde_kwin_server_decoration::Mode::Client)
+                            }
+                            _ => None,
+                        };
+
+                        if let Some(mode) = mode {
+                            kde_decoration.request_mode(mode as u32);
+                        }
+
+                        Some(kde_decoration)
+                    }
+                },
+
+                _ => None,
+            };
+
             WindowInner {
                 xdg_surface,
                 xdg_toplevel,
                 toplevel_decoration,
+                kde_decoration,
                 pending_configure: Mutex::new(WindowConfigure {
                     new_size: (None, None),
                     suggested_bounds: None,
                     // Initial configure will indicate whether there are server side decorations.
                     decoration_mode: DecorationMode::Client,
                     state: WindowState::empty(),
+                    constrained: ConstrainedEdges::empty(),
                     // XXX by default we assume that everything is supported.
                     capabilities: WindowManagerCapabilities::all(),
                 }),
+                shortcuts_inhibitors: Mutex::new(Vec::new()),
+                app_menu: Mutex::new(None),
+                content_type: Mutex::new(None),
+                parent: Mutex::new(Weak::new()),
+                children: Mutex::new(Vec::new()),
+                wm_dialog,
+                dialog: Mutex::new(None),
             }
         });
 
@@ -174,7 +239,7 @@ pub struct XdgPositioner(xdg_positioner::XdgPositioner);
 
 impl XdgPositioner {
     pub fn new(
-        wm_base: &impl ProvidesBoundGlobal<xdg_wm_base::XdgWmBase, { XdgShell::API_VERSION_MAX }>,
+        wm_base: &impl ProvidesBoundGlobal<xdg_wm_base::XdgWmBase, 6>,
     ) -> Result<Self, GlobalError> {
         wm_base
             .bound_global()
@@ -188,6 +253,83 @@ impl XdgPositioner {
             })
             .map(XdgPositioner)
     }
+
+    /// Set the size of the surface that is to be positioned.
+    ///
+    /// This must be set, with a non-zero size, before the positioner is used.
+    pub fn with_size(self, width: i32, height: i32) -> Self {
+        self.0.set_size(width, height);
+        self
+    }
+
+    /// Set the anchor rectangle, in window geometry coordinates of the parent surface.
+    ///
+    /// This must be set before the positioner is used.
+    pub fn with_anchor_rect(self, x: i32, y: i32, width: i32, height: i32) -> Self {
+        self.0.set_anchor_rect(x, y, width, height);
+        self
+    }
+
+    /// Set the edge or corner of the anchor rectangle the surface is anchored to.
+    pub fn with_anchor(self, anchor: xdg_positioner::Anchor) -> Self {
+        self.0.set_anchor(anchor);
+        self
+    }
+
+    /// Set the direction in which the surface should be positioned relative to the anchor point.
+    pub fn with_gravity(self, gravity: xdg_positioner::Gravity) -> Self {
+        self.0.set_gravity(gravity);
+        self
+    }
+
+    /// Set how the compositor may adjust the position of the surface if it would be constrained,
+    /// for example by the edges of an output.
+    pub fn with_constraint_adjustment(
+        self,
+        constraint_adjustment: xdg_positioner::ConstraintAdjustment,
+    ) -> Self {
+        self.0.set_constraint_adjustment(constraint_adjustment);
+        self
+    }
+
+    /// Set an offset of the surface from the anchor point.
+    pub fn with_offset(self, x: i32, y: i32) -> Self {
+        self.0.set_offset(x, y);
+        self
+    }
+
+    /// Ask the compositor to reposition the surface when the parent surface moves or changes.
+    ///
+    /// The popup will then receive a configure of kind [`Reactive`](popup::ConfigureKind::Reactive).
+    ///
+    /// This requires `xdg_wm_base` version 3 and is ignored on older versions.
+    pub fn with_reactive(self) -> Self {
+        if self.0.version() >= 3 {
+            self.0.set_reactive();
+        }
+        self
+    }
+
+    /// Set the size the parent surface is expected to have, for positioning against a parent that
+    /// is being resized.
+    ///
+    /// This requires `xdg_wm_base` version 3 and is ignored on older versions.
+    pub fn with_parent_size(self, width: i32, height: i32) -> Self {
+        if self.0.version() >= 3 {
+            self.0.set_parent_size(width, height);
+        }
+        self
+    }
+
+    /// Set the serial of the parent configure event this positioner was computed for.
+    ///
+    /// This requires `xdg_wm_base` version 3 and is ignored on older versions.
+    pub fn with_parent_configure(self, serial: u32) -> Self {
+        if self.0.version() >= 3 {
+            self.0.set_parent_configure(serial);
+        }
+        self
+    }
 }
 
 impl std::ops::Deref for XdgPositioner {
@@ -246,7 +388,7 @@ impl XdgShellSurface {
     /// [`XdgSurface`]: xdg_surface::XdgSurface
     /// [`WlSurface`]: wl_surface::WlSurface
     pub fn new<U, D>(
-        wm_base: &impl ProvidesBoundGlobal<xdg_wm_base::XdgWmBase, { XdgShell::API_VERSION_MAX }>,
+        wm_base: &impl ProvidesBoundGlobal<xdg_wm_base::XdgWmBase, 6>,
         qh: &QueueHandle<D>,
         surface: impl Into<Surface>,
         udata: U,
@@ -270,6 +412,19 @@ impl XdgShellSurface {
     }
 }
 
+/// Handler for the events of the `xdg_wm_base` global.
+pub trait XdgShellHandler: Sized {
+    /// The compositor pinged the client to check that it is still responsive.
+    ///
+    /// The pong is sent and flushed before this is called, whatever this function does. Pings are only
+    /// answered when the event queue is dispatched, and the compositor may tell the user that a client is
+    /// not responding if the pong takes too long, so an application blocking its main thread for a long
+    /// time should dispatch the queue in between.
+    ///
+    /// This does nothing by default.
+    fn ping(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _serial: u32) {}
+}
+
 pub trait XdgSurface: WaylandSurface + Sized {
     /// The underlying [`XdgSurface`](xdg_surface::XdgSurface).
     fn xdg_surface(&self) -> &xdg_surface::XdgSurface;
@@ -293,16 +448,25 @@ impl XdgSurface for XdgShellSurface {
 
 #[macro_export]
 macro_rules! delegate_xdg_shell {
-    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
-        $crate::reexports::client::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
             $crate::reexports::protocols::xdg::shell::client::xdg_wm_base::XdgWmBase: $crate::globals::GlobalData
         ] => $crate::shell::xdg::XdgShell);
-        $crate::reexports::client::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
         $crate::reexports::protocols::xdg::decoration::zv1::client::zxdg_decoration_manager_v1::ZxdgDecorationManagerV1: $crate::globals::GlobalData
         ] => $crate::shell::xdg::XdgShell);
-        $crate::reexports::client::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
             $crate::reexports::protocols::xdg::decoration::zv1::client::zxdg_toplevel_decoration_v1::ZxdgToplevelDecorationV1: $crate::shell::xdg::window::WindowData
         ] => $crate::shell::xdg::XdgShell);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::xdg::dialog::v1::client::xdg_wm_dialog_v1::XdgWmDialogV1: $crate::globals::GlobalData
+        ] => $crate::shell::xdg::XdgShell);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_plasma::server_decoration::client::org_kde_kwin_server_decoration_manager::OrgKdeKwinServerDecorationManager: $crate::globals::GlobalData
+        ] => $crate::shell::xdg::XdgShell);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols_plasma::server_decoration::client::org_kde_kwin_server_decoration::OrgKdeKwinServerDecoration: $crate::shell::xdg::window::WindowData
+        ] => $crate::shell::xdg::XdgShell);
     };
 }
 
@@ -320,6 +484,13 @@ impl ProvidesBoundGlobal<xdg_wm_base::XdgWmBase, 5> for XdgShell {
     }
 }
 
+// Version 7 only adds the constrained toplevel states
+impl ProvidesBoundGlobal<xdg_wm_base::XdgWmBase, 6> for XdgShell {
+    fn bound_global(&self) -> Result<xdg_wm_base::XdgWmBase, GlobalError> {
+        <Self as ProvidesBoundGlobal<xdg_wm_base::XdgWmBase, 7>>::bound_global(self)
+    }
+}
+
 impl ProvidesBoundGlobal<xdg_wm_base::XdgWmBase, { XdgShell::API_VERSION_MAX }> for XdgShell {
     fn bound_global(&self) -> Result<xdg_wm_base::XdgWmBase, GlobalError> {
         Ok(self.xdg_wm_base.clone())
@@ -328,22 +499,54 @@ impl ProvidesBoundGlobal<xdg_wm_base::XdgWmBase, { XdgShell::API_VERSION_MAX }>
 
 impl<D> Dispatch<xdg_wm_base::XdgWmBase, GlobalData, D> for XdgShell
 where
-    D: Dispatch<xdg_wm_base::XdgWmBase, GlobalData>,
+    D: Dispatch<xdg_wm_base::XdgWmBase, GlobalData> + XdgShellHandler,
 {
     fn event(
-        _state: &mut D,
+        state: &mut D,
         xdg_wm_base: &xdg_wm_base::XdgWmBase,
         event: xdg_wm_base::Event,
         _data: &GlobalData,
-        _conn: &Connection,
-        _qh: &QueueHandle<D>,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
     ) {
         match event {
             xdg_wm_base::Event::Ping { serial } => {
+                // Answer right away, even if the application does not flush before doing a lot of
+                // work after dispatching. Errors are reported by the next dispatch.
                 xdg_wm_base.pong(serial);
+                let _ = conn.flush();
+
+                state.ping(conn, qh, serial);
             }
 
             _ => unreachable!(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use wayland_backend::protocol::Argument;
+    use wayland_client::Proxy;
+
+    use crate::{
+        reexports::protocols::xdg::shell::client::xdg_wm_base::XdgWmBase,
+        testing::{Call, TestServer},
+    };
+
+    #[test]
+    fn ping() {
+        let mut server = TestServer::new();
+        server.create_global(XdgWmBase::interface(), 6);
+        let mut client = server.connect();
+        client.roundtrip();
+
+        let wm_base = server.last_object("xdg_wm_base");
+        server.send(&wm_base, "ping", vec![Argument::Uint(42)]);
+        // The pong is flushed while the ping is dispatched, without waiting for the next flush.
+        client.queue.blocking_dispatch(&mut client.state).unwrap();
+        let pong = server.wait_for_request("xdg_wm_base.pong");
+        assert!(matches!(pong.args[..], [Argument::Uint(42)]));
+        assert!(matches!(client.state.take_calls()[..], [Call::Ping(42)]));
+    }
+}
@@ -2,15 +2,18 @@ use crate::{
     compositor::{Surface, SurfaceData},
     error::GlobalError,
     globals::ProvidesBoundGlobal,
-    shell::xdg::XdgShellSurface,
+    shell::{
+        xdg::{XdgShellSurface, XdgSurface},
+        WaylandSurface,
+    },
 };
 use std::sync::{
     atomic::{AtomicI32, AtomicU32, Ordering::Relaxed},
     Arc, Weak,
 };
 use wayland_client::{
-    protocol::{wl_compositor::WlCompositor, wl_surface},
-    Connection, Dispatch, QueueHandle,
+    protocol::{wl_compositor::WlCompositor, wl_seat, wl_surface},
+    Connection, Dispatch, Proxy, QueueHandle,
 };
 use wayland_protocols::xdg::shell::client::{xdg_popup, xdg_positioner, xdg_surface, xdg_wm_base};
 
@@ -133,8 +136,44 @@ impl Popup {
         self.inner.surface.wl_surface()
     }
 
-    pub fn reposition(&self, position: &xdg_positioner::XdgPositioner, token: u32) {
+    /// Reposition the popup using a new positioner.
+    ///
+    /// The compositor answers with [`PopupHandler::repositioned`] and then a configure of kind
+    /// [`ConfigureKind::Reposition`] carrying the same `token`, with the position and size computed
+    /// again from the positioner, including its constraint adjustments.  The popup must be redrawn
+    /// at the new size before the next commit.  If several reposition requests are sent before the
+    /// compositor handles them, it may only answer the last one.
+    ///
+    /// This requires `xdg_wm_base` version 3.  On older versions nothing is sent and `false` is
+    /// returned, the popup must then be destroyed and created again to move it.
+    pub fn reposition(&self, position: &xdg_positioner::XdgPositioner, token: u32) -> bool {
+        if self.xdg_popup().version() < 3 {
+            return false;
+        }
         self.xdg_popup().reposition(position, token);
+        true
+    }
+
+    /// Grab the input of the seat, so the popup is dismissed when the user clicks outside of it.
+    ///
+    /// The `serial` must be the serial of a user input event, such as a pointer button press, on
+    /// the parent surface.  This must be called before the initial commit of the popup, and the
+    /// parent must itself be a grabbing popup or a toplevel with input focus, otherwise the
+    /// compositor will immediately send [`PopupHandler::done`].
+    pub fn grab(&self, seat: &wl_seat::WlSeat, serial: u32) {
+        self.xdg_popup().grab(seat, serial);
+    }
+}
+
+impl WaylandSurface for Popup {
+    fn wl_surface(&self) -> &wl_surface::WlSurface {
+        self.inner.surface.wl_surface()
+    }
+}
+
+impl XdgSurface for Popup {
+    fn xdg_surface(&self) -> &xdg_surface::XdgSurface {
+        self.inner.surface.xdg_surface()
     }
 }
 
@@ -194,6 +233,20 @@ pub trait PopupHandler: Sized {
 
     /// The popup was dismissed by the compositor and should be destroyed.
     fn done(&mut self, conn: &Connection, qh: &QueueHandle<Self>, popup: &Popup);
+
+    /// The compositor has handled a [`Popup::reposition`] request with the given `token`.
+    ///
+    /// The new position and size follow in the next [`configure`](Self::configure), which has
+    /// the kind [`ConfigureKind::Reposition`].
+    fn repositioned(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        popup: &Popup,
+        token: u32,
+    ) {
+        let _ = (conn, qh, popup, token);
+    }
 }
 
 impl<D> Dispatch<xdg_surface::XdgSurface, PopupData, D> for PopupData
@@ -269,6 +322,7 @@ where
             xdg_popup::Event::Repositioned { token } => {
                 inner.pending_token.store(token, Relaxed);
                 inner.configure_state.store(PopupConfigure::STATE_REPOSITION_ACK, Relaxed);
+                data.repositioned(conn, qh, &popup, token);
             }
             _ => unreachable!(),
         }
@@ -277,11 +331,11 @@ where
 
 #[macro_export]
 macro_rules! delegate_xdg_popup {
-    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
-        $crate::reexports::client::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
             $crate::reexports::protocols::xdg::shell::client::xdg_popup::XdgPopup: $crate::shell::xdg::popup::PopupData
         ] => $crate::shell::xdg::popup::PopupData);
-        $crate::reexports::client::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
             $crate::reexports::protocols::xdg::shell::client::xdg_surface::XdgSurface: $crate::shell::xdg::popup::PopupData
         ] => $crate::shell::xdg::popup::PopupData);
     };
@@ -1,7 +1,8 @@
 use std::{
     convert::{TryFrom, TryInto},
     num::NonZeroU32,
-    sync::Mutex,
+    os::unix::io::OwnedFd,
+    sync::{Arc, Mutex, Weak},
 };
 
 use wayland_client::{Connection, Dispatch, QueueHandle};
@@ -10,30 +11,64 @@ use wayland_protocols::{
         zxdg_decoration_manager_v1,
         zxdg_toplevel_decoration_v1::{self, Mode},
     },
+    xdg::dialog::v1::client::{xdg_dialog_v1, xdg_wm_dialog_v1},
     xdg::shell::client::{
         xdg_surface,
         xdg_toplevel::{self, State, WmCapabilities},
     },
 };
 
+use wayland_protocols_plasma::server_decoration::client::{
+    org_kde_kwin_server_decoration, org_kde_kwin_server_decoration_manager,
+};
+
 use crate::{
+    content_type::SurfaceContentType,
     error::GlobalError,
     globals::{GlobalData, ProvidesBoundGlobal},
+    seat::shortcuts_inhibit::ShortcutsInhibitor,
+    shell::plasma::appmenu::AppMenu,
+    shell::plasma::server_decoration::parse_mode,
     shell::xdg::{XdgShell, XdgShellSurface},
 };
 
 use super::{
-    DecorationMode, Window, WindowConfigure, WindowData, WindowHandler, WindowManagerCapabilities,
-    WindowState,
+    ConstrainedEdges, DecorationMode, Window, WindowConfigure, WindowData, WindowHandler,
+    WindowManagerCapabilities, WindowState,
 };
 
 impl Drop for WindowInner {
     fn drop(&mut self) {
+        // Destroy the inhibitors while the surface is still alive.
+        self.shortcuts_inhibitors.get_mut().unwrap().clear();
+        self.app_menu.get_mut().unwrap().take();
+        self.content_type.get_mut().unwrap().take();
+
+        // Children still referring to this window become parentless, as if the parent was unset.
+        let this: *const WindowInner = self;
+        for child in self.children.get_mut().unwrap().drain(..) {
+            if let Some(child) = child.upgrade() {
+                let mut parent = child.parent.lock().unwrap();
+                if std::ptr::eq(parent.as_ptr(), this) {
+                    child.xdg_toplevel.set_parent(None);
+                    *parent = Weak::new();
+                }
+            }
+        }
+
+        if let Some(dialog) = self.dialog.get_mut().unwrap().take() {
+            dialog.destroy();
+        }
+
         // XDG decoration says we must destroy the decoration object before the toplevel
         if let Some(toplevel_decoration) = self.toplevel_decoration.as_ref() {
             toplevel_decoration.destroy();
         }
 
+        if let Some(kde_decoration) = self.kde_decoration.as_ref() {
+            kde_decoration.release();
+        }
+
         // XDG Shell protocol dictates we must destroy the role object before the xdg surface.
         self.xdg_toplevel.destroy();
         // XdgShellSurface will do it's own drop
@@ -46,7 +81,35 @@ pub struct WindowInner {
     pub xdg_surface: XdgShellSurface,
     pub xdg_toplevel: xdg_toplevel::XdgToplevel,
     pub toplevel_decoration: Option<zxdg_toplevel_decoration_v1::ZxdgToplevelDecorationV1>,
+    pub kde_decoration: Option<org_kde_kwin_server_decoration::OrgKdeKwinServerDecoration>,
     pub pending_configure: Mutex<WindowConfigure>,
+    pub shortcuts_inhibitors: Mutex<Vec<ShortcutsInhibitor>>,
+    pub app_menu: Mutex<Option<AppMenu>>,
+    pub content_type: Mutex<Option<SurfaceContentType>>,
+    pub parent: Mutex<Weak<WindowInner>>,
+    pub children: Mutex<Vec<Weak<WindowInner>>>,
+    pub wm_dialog: Option<xdg_wm_dialog_v1::XdgWmDialogV1>,
+    pub dialog: Mutex<Option<xdg_dialog_v1::XdgDialogV1>>,
+}
+
+impl ProvidesBoundGlobal<xdg_wm_dialog_v1::XdgWmDialogV1, 1> for XdgShell {
+    fn bound_global(&self) -> Result<xdg_wm_dialog_v1::XdgWmDialogV1, GlobalError> {
+        self.xdg_wm_dialog.get().cloned()
+    }
+}
+
+/// Object data of `xdg_dialog_v1`, which has no events.
+pub(crate) struct DialogData;
+
+impl wayland_client::backend::ObjectData for DialogData {
+    fn event(
+        self: Arc<Self>,
+        _: &wayland_client::backend::Backend,
+        _: wayland_client::backend::protocol::Message<wayland_client::backend::ObjectId, OwnedFd>,
+    ) -> Option<Arc<dyn wayland_client::backend::ObjectData>> {
+        unreachable!("xdg_dialog_v1 has no events");
+    }
+    fn destroyed(&self, _: wayland_client::backend::ObjectId) {}
 }
 
 impl ProvidesBoundGlobal<zxdg_decoration_manager_v1::ZxdgDecorationManagerV1, 1> for XdgShell {
@@ -101,27 +164,37 @@ where
             match event {
                 xdg_toplevel::Event::Configure { width, height, states } => {
                     // The states are encoded as a bunch of u32 of native endian, but are encoded in an array of
-                    // bytes.
-                    let new_state = states
+                    // bytes. Unknown states from newer protocol versions are skipped.
+                    let states = states
                         .chunks_exact(4)
                         .flat_map(TryInto::<[u8; 4]>::try_into)
                         .map(u32::from_ne_bytes)
-                        .flat_map(State::try_from)
-                        .fold(WindowState::empty(), |mut acc, state| {
-                            match state {
-                                State::Maximized => acc.set(WindowState::MAXIMIZED, true),
-                                State::Fullscreen => acc.set(WindowState::FULLSCREEN, true),
-                                State::Resizing => acc.set(WindowState::RESIZING, true),
-                                State::Activated => acc.set(WindowState::ACTIVATED, true),
-                                State::TiledLeft => acc.set(WindowState::TILED_LEFT, true),
-                                State::TiledRight => acc.set(WindowState::TILED_RIGHT, true),
-                                State::TiledTop => acc.set(WindowState::TILED_TOP, true),
-                                State::TiledBottom => acc.set(WindowState::TILED_BOTTOM, true),
-                                State::Suspended => acc.set(WindowState::SUSPENDED, true),
-                                _ => (),
-                            }
-                            acc
-                        });
+                        .flat_map(State::try_from);
+                    let new_state = states.clone().fold(WindowState::empty(), |mut acc, state| {
+                        match state {
+                            State::Maximized => acc.set(WindowState::MAXIMIZED, true),
+                            State::Fullscreen => acc.set(WindowState::FULLSCREEN, true),
+                            State::Resizing => acc.set(WindowState::RESIZING, true),
+                            State::Activated => acc.set(WindowState::ACTIVATED, true),
+                            State::TiledLeft => acc.set(WindowState::TILED_LEFT, true),
+                            State::TiledRight => acc.set(WindowState::TILED_RIGHT, true),
+                            State::TiledTop => acc.set(WindowState::TILED_TOP, true),
+                            State::TiledBottom => acc.set(WindowState::TILED_BOTTOM, true),
+                            State::Suspended => acc.set(WindowState::SUSPENDED, true),
+                            _ => (),
+                        }
+                        acc
+                    });
+                    let constrained = states.fold(ConstrainedEdges::empty(), |mut acc, state| {
+                        match state {
+                            State::ConstrainedLeft => acc.set(ConstrainedEdges::LEFT, true),
+                            State::ConstrainedRight => acc.set(ConstrainedEdges::RIGHT, true),
+                            State::ConstrainedTop => acc.set(ConstrainedEdges::TOP, true),
+                            State::ConstrainedBottom => acc.set(ConstrainedEdges::BOTTOM, true),
+                            _ => (),
+                        }
+                        acc
+                    });
 
                     // XXX we do explicit convertion and sanity checking because compositor
                     // could pass negative values which we should ignore all together.
@@ -131,6 +204,7 @@ where
                     let pending_configure = &mut window.0.pending_configure.lock().unwrap();
                     pending_configure.new_size = (width, height);
                     pending_configure.state = new_state;
+                    pending_configure.constrained = constrained;
                 }
 
                 xdg_toplevel::Event::Close => {
@@ -177,6 +251,24 @@ where
     }
 }
 
+// XDG dialog
+
+impl<D> Dispatch<xdg_wm_dialog_v1::XdgWmDialogV1, GlobalData, D> for XdgShell
+where
+    D: Dispatch<xdg_wm_dialog_v1::XdgWmDialogV1, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &xdg_wm_dialog_v1::XdgWmDialogV1,
+        _: xdg_wm_dialog_v1::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("xdg_wm_dialog_v1 has no events")
+    }
+}
+
 // XDG decoration
 
 impl<D> Dispatch<zxdg_decoration_manager_v1::ZxdgDecorationManagerV1, GlobalData, D> for XdgShell
@@ -231,3 +323,63 @@ where
         }
     }
 }
+
+// KDE server decoration
+
+impl<D>
+    Dispatch<
+        org_kde_kwin_server_decoration_manager::OrgKdeKwinServerDecorationManager,
+        GlobalData,
+        D,
+    > for XdgShell
+where
+    D: Dispatch<
+        org_kde_kwin_server_decoration_manager::OrgKdeKwinServerDecorationManager,
+        GlobalData,
+    >,
+{
+    fn event(
+        _: &mut D,
+        _: &org_kde_kwin_server_decoration_manager::OrgKdeKwinServerDecorationManager,
+        _: org_kde_kwin_server_decoration_manager::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        // The default mode is followed by a mode event for every decoration, which is all windows need.
+    }
+}
+
+impl<D> Dispatch<org_kde_kwin_server_decoration::OrgKdeKwinServerDecoration, WindowData, D>
+    for XdgShell
+where
+    D: Dispatch<org_kde_kwin_server_decoration::OrgKdeKwinServerDecoration, WindowData>
+        + WindowHandler,
+{
+    fn event(
+        _: &mut D,
+        _: &org_kde_kwin_server_decoration::OrgKdeKwinServerDecoration,
+        event: org_kde_kwin_server_decoration::Event,
+        data: &WindowData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        if let Some(window) = data.0.upgrade().map(Window) {
+            match event {
+                org_kde_kwin_server_decoration::Event::Mode { mode } => {
+                    let mode = match parse_mode(mode) {
+                        Some(org_kde_kwin_server_decoration::Mode::Server) => {
+                            DecorationMode::Server
+                        }
+                        Some(_) => DecorationMode::Client,
+                        None => return,
+                    };
+
+                    window.0.pending_configure.lock().unwrap().decoration_mode = mode;
+                }
+
+                _ => unreachable!(),
+            }
+        }
+    }
+}
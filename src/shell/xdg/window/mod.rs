@@ -5,21 +5,37 @@ use std::{
     sync::{Arc, Weak},
 };
 
+use bitflags::bitflags;
+
 use crate::reexports::client::{
     protocol::{wl_output, wl_seat, wl_surface},
-    Connection, Proxy, QueueHandle,
+    Connection, Dispatch, Proxy, QueueHandle,
 };
 use crate::reexports::csd_frame::{WindowManagerCapabilities, WindowState};
 use crate::reexports::protocols::{
     xdg::decoration::zv1::client::zxdg_toplevel_decoration_v1::{self, Mode},
+    xdg::dialog::v1::client::xdg_wm_dialog_v1,
     xdg::shell::client::{xdg_surface, xdg_toplevel},
 };
-
+use crate::reexports::protocols_plasma::appmenu::client::org_kde_kwin_appmenu::OrgKdeKwinAppmenu;
+use crate::reexports::protocols_plasma::server_decoration::client::org_kde_kwin_server_decoration;
+
+use crate::content_type::{ContentType, ContentTypeData, ContentTypeState};
+use crate::reexports::protocols::wp::content_type::v1::client::wp_content_type_v1::WpContentTypeV1;
+use crate::reexports::protocols::wp::keyboard_shortcuts_inhibit::zv1::client::zwp_keyboard_shortcuts_inhibitor_v1::ZwpKeyboardShortcutsInhibitorV1;
+use crate::seat::shortcuts_inhibit::{
+    ShortcutsInhibitError, ShortcutsInhibitState, ShortcutsInhibitor, ShortcutsInhibitorData,
+};
+use crate::compositor::Surface;
+use crate::shell::plasma::appmenu::AppMenuManagerState;
 use crate::shell::WaylandSurface;
 
-use self::inner::WindowInner;
+use crate::error::GlobalError;
+use crate::globals::GlobalData;
 
-use super::XdgSurface;
+use self::inner::{DialogData, WindowInner};
+
+use super::{XdgShell, XdgSurface};
 
 pub(super) mod inner;
 
@@ -63,6 +79,27 @@ pub enum DecorationMode {
     Server,
 }
 
+bitflags! {
+    /// Edges of a window that can not be resized by the user, for example because they touch a
+    /// monitor edge or another tiled window.
+    ///
+    /// Resize handles should not be drawn on these edges.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct ConstrainedEdges: u32 {
+        /// The left edge of the window is constrained.
+        const LEFT = 1;
+
+        /// The right edge of the window is constrained.
+        const RIGHT = 2;
+
+        /// The top edge of the window is constrained.
+        const TOP = 4;
+
+        /// The bottom edge of the window is constrained.
+        const BOTTOM = 8;
+    }
+}
+
 /// A window configure.
 ///
 /// A configure describes a compositor request to resize the window or change it's state.
@@ -92,8 +129,18 @@ pub struct WindowConfigure {
     /// For more see [`WindowState`] documentation on the flag values.
     pub state: WindowState,
 
+    /// The edges of the window the user can not resize.
+    ///
+    /// If xdg-shell is version 6 or lower, this will always be empty.
+    pub constrained: ConstrainedEdges,
+
     /// The capabilities supported by the compositor.
     ///
+    /// Client side decorations should hide the controls for unsupported actions, such as the maximize
+    /// button.
+    ///
+    /// If xdg-shell is version 4 or lower, all capabilities are assumed to be supported.
+    ///
     /// For more see [`WindowManagerCapabilities`] documentation on the flag values.
     pub capabilities: WindowManagerCapabilities,
 }
@@ -152,6 +199,14 @@ impl WindowConfigure {
     pub fn is_tiled_bottom(&self) -> bool {
         self.state.contains(WindowState::TILED_BOTTOM)
     }
+
+    /// Is [`WindowState::SUSPENDED`] state is set.
+    ///
+    /// A suspended window is not visible, so it should stop rendering until it is configured again.
+    #[inline]
+    pub fn is_suspended(&self) -> bool {
+        self.state.contains(WindowState::SUSPENDED)
+    }
 }
 
 /// Decorations a window is created with.
@@ -182,9 +237,152 @@ pub enum WindowDecorations {
     None,
 }
 
+/// The edge to resize a window from, for a pointer position within `border` of the edges.
+///
+/// `position` is in window geometry coordinates, as are the `width` and `height` of the window. Corners
+/// take precedence over edges, and [`None`] is returned for positions outside of the border.
+///
+/// The edge can be passed to [`Window::resize`] on a button press.
+pub fn resize_edge_at(
+    position: (f64, f64),
+    width: u32,
+    height: u32,
+    border: u32,
+) -> Option<xdg_toplevel::ResizeEdge> {
+    let (x, y) = position;
+    let border = border as f64;
+    if x < 0. || y < 0. || x >= width as f64 || y >= height as f64 {
+        return None;
+    }
+
+    let left = x < border;
+    let right = x >= width as f64 - border;
+    let top = y < border;
+    let bottom = y >= height as f64 - border;
+
+    let edge = match (left, right, top, bottom) {
+        (true, _, true, _) => xdg_toplevel::ResizeEdge::TopLeft,
+        (_, true, true, _) => xdg_toplevel::ResizeEdge::TopRight,
+        (true, _, _, true) => xdg_toplevel::ResizeEdge::BottomLeft,
+        (_, true, _, true) => xdg_toplevel::ResizeEdge::BottomRight,
+        (true, _, _, _) => xdg_toplevel::ResizeEdge::Left,
+        (_, true, _, _) => xdg_toplevel::ResizeEdge::Right,
+        (_, _, true, _) => xdg_toplevel::ResizeEdge::Top,
+        (_, _, _, true) => xdg_toplevel::ResizeEdge::Bottom,
+        _ => return None,
+    };
+
+    Some(edge)
+}
+
 #[derive(Debug, Clone)]
 pub struct Window(pub(super) Arc<WindowInner>);
 
+/// A builder for a [`Window`], setting its initial state.
+///
+/// Every property of the builder can also be changed after the window is mapped, with the matching
+/// setter of [`Window`].
+///
+/// [`WindowBuilder::build`] creates the window and sends its state, but does not commit it. The
+/// window must then be committed to receive its initial [`configure`](WindowHandler::configure),
+/// which is also when the double buffered state, such as the minimum size, applies.
+#[must_use]
+#[derive(Debug, Clone)]
+pub struct WindowBuilder {
+    decorations: WindowDecorations,
+    title: Option<String>,
+    app_id: Option<String>,
+    min_size: Option<(u32, u32)>,
+    max_size: Option<(u32, u32)>,
+}
+
+impl WindowBuilder {
+    /// Creates a builder for a window requesting server side decorations, with no other state set.
+    pub fn new() -> Self {
+        Self {
+            decorations: WindowDecorations::RequestServer,
+            title: None,
+            app_id: None,
+            min_size: None,
+            max_size: None,
+        }
+    }
+
+    /// Sets the decorations the window is created with.
+    pub fn decorations(mut self, decorations: WindowDecorations) -> Self {
+        self.decorations = decorations;
+        self
+    }
+
+    /// Sets the title of the window, see [`Window::set_title`].
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Sets the application id of the window, see [`Window::set_app_id`].
+    pub fn app_id(mut self, app_id: impl Into<String>) -> Self {
+        self.app_id = Some(app_id.into());
+        self
+    }
+
+    /// Sets the minimum size of the window, see [`Window::set_min_size`].
+    pub fn min_size(mut self, min_size: Option<(u32, u32)>) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    /// Sets the maximum size of the window, see [`Window::set_max_size`].
+    pub fn max_size(mut self, max_size: Option<(u32, u32)>) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Creates the window with [`XdgShell::create_window`] and sends its initial state.
+    ///
+    /// The window is not committed, so other state that must be set before the initial commit can
+    /// still be added.
+    #[must_use = "Dropping all window handles will destroy the window"]
+    pub fn build<State>(
+        self,
+        xdg_shell: &XdgShell,
+        surface: impl Into<Surface>,
+        qh: &QueueHandle<State>,
+    ) -> Window
+    where
+        State: Dispatch<xdg_surface::XdgSurface, WindowData>
+            + Dispatch<xdg_toplevel::XdgToplevel, WindowData>
+            + Dispatch<zxdg_toplevel_decoration_v1::ZxdgToplevelDecorationV1, WindowData>
+            + Dispatch<org_kde_kwin_server_decoration::OrgKdeKwinServerDecoration, WindowData>
+            + WindowHandler
+            + 'static,
+    {
+        let window = xdg_shell.create_window(surface, self.decorations, qh);
+
+        if let Some(title) = self.title {
+            window.set_title(title);
+        }
+        if let Some(app_id) = self.app_id {
+            window.set_app_id(app_id);
+        }
+        // No size constraint is the initial state, so there is nothing to send without one.
+        if self.min_size.is_some() {
+            window.set_min_size(self.min_size);
+        }
+        if self.max_size.is_some() {
+            window.set_max_size(self.max_size);
+        }
+
+        window
+    }
+}
+
+impl Default for WindowBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Window {
     pub fn from_xdg_toplevel(toplevel: &xdg_toplevel::XdgToplevel) -> Option<Window> {
         toplevel.data::<WindowData>().and_then(|data| data.0.upgrade()).map(Window)
@@ -200,38 +398,132 @@ impl Window {
         decoration.data::<WindowData>().and_then(|data| data.0.upgrade()).map(Window)
     }
 
+    /// Shows the window menu of the compositor at the given position, in window geometry coordinates.
+    ///
+    /// The `serial` must be the serial of the user input event, usually a right click on the
+    /// titlebar, requesting the menu.
     pub fn show_window_menu(&self, seat: &wl_seat::WlSeat, serial: u32, position: (i32, i32)) {
         self.xdg_toplevel().show_window_menu(seat, serial, position.0, position.1);
     }
 
+    /// Creates a builder to set the initial state of a window.
+    pub fn builder() -> WindowBuilder {
+        WindowBuilder::new()
+    }
+
+    /// Sets the title of the window, shown by the compositor in the titlebar or task switcher.
+    ///
+    /// This state is not double buffered: it may be changed at any time and applies without a commit.
     pub fn set_title(&self, title: impl Into<String>) {
         self.xdg_toplevel().set_title(title.into());
     }
 
+    /// Sets the application id of the window, usually the name of the desktop file of the application.
+    ///
+    /// The compositor uses it to group windows and pick their icon. This state is not double buffered:
+    /// it may be changed at any time and applies without a commit.
     pub fn set_app_id(&self, app_id: impl Into<String>) {
         self.xdg_toplevel().set_app_id(app_id.into());
     }
 
+    /// Sets the parent of the window, or unsets it with [`None`].
+    ///
+    /// A window with a parent, such as a dialog, is stacked above its parent and usually minimized
+    /// together with it. If the parent is destroyed first, the window is made parentless.
     pub fn set_parent(&self, parent: Option<&Window>) {
         self.xdg_toplevel().set_parent(parent.map(Window::xdg_toplevel));
+
+        let mut current = self.0.parent.lock().unwrap();
+        *current = match parent {
+            Some(parent) => {
+                let mut children = parent.0.children.lock().unwrap();
+                children.retain(|child| child.strong_count() > 0);
+                if !children.iter().any(|child| child.as_ptr() == Arc::as_ptr(&self.0)) {
+                    children.push(Arc::downgrade(&self.0));
+                }
+                Arc::downgrade(&parent.0)
+            }
+            None => Weak::new(),
+        };
+    }
+
+    /// Marks the window as a modal dialog of its parent, or not.
+    ///
+    /// The compositor may use this to prevent interaction with the parent, but the application must
+    /// still ignore input on the parent itself while the dialog is modal. This should be used with
+    /// [`Window::set_parent`].
+    ///
+    /// # Errors
+    ///
+    /// This returns an error if the compositor does not support `xdg_wm_dialog_v1` and `modal` is
+    /// `true`.
+    pub fn set_modal(&self, modal: bool) -> Result<(), GlobalError> {
+        let mut dialog = self.0.dialog.lock().unwrap();
+
+        if dialog.is_none() {
+            if !modal {
+                return Ok(());
+            }
+
+            let wm_dialog =
+                self.0.wm_dialog.as_ref().ok_or(GlobalError::MissingGlobal("xdg_wm_dialog_v1"))?;
+            let new_dialog = wm_dialog
+                .send_constructor(
+                    xdg_wm_dialog_v1::Request::GetXdgDialog {
+                        toplevel: self.xdg_toplevel().clone(),
+                    },
+                    Arc::new(DialogData),
+                )
+                .unwrap_or_else(|_| Proxy::inert(wm_dialog.backend().clone()));
+            *dialog = Some(new_dialog);
+        }
+
+        let dialog = dialog.as_ref().unwrap();
+        if modal {
+            dialog.set_modal();
+        } else {
+            dialog.unset_modal();
+        }
+
+        Ok(())
     }
 
+    /// Requests the window to be maximized.
+    ///
+    /// This request is not double buffered and does not need a commit. The compositor answers with a
+    /// [`configure`](WindowHandler::configure) with the [`WindowState::MAXIMIZED`] state and the new size,
+    /// which takes effect once a buffer of that size is committed.
     pub fn set_maximized(&self) {
         self.xdg_toplevel().set_maximized()
     }
 
+    /// Requests the window to no longer be maximized.
+    ///
+    /// Like [`Window::set_maximized`], this does not need a commit and is answered with a configure.
     pub fn unset_maximized(&self) {
         self.xdg_toplevel().unset_maximized()
     }
 
+    /// Requests the window to be minimized.
+    ///
+    /// This request does not need a commit. There is no configure and no way to know whether the window
+    /// was minimized, nor a request to unminimize it: this is left to the compositor and the user.
     pub fn set_minimized(&self) {
         self.xdg_toplevel().set_minimized()
     }
 
+    /// Requests the window to be fullscreen, on the given output or one picked by the compositor.
+    ///
+    /// This request does not need a commit. The compositor answers with a
+    /// [`configure`](WindowHandler::configure) with the [`WindowState::FULLSCREEN`] state and the size of
+    /// the output, which takes effect once a buffer of that size is committed.
     pub fn set_fullscreen(&self, output: Option<&wl_output::WlOutput>) {
         self.xdg_toplevel().set_fullscreen(output)
     }
 
+    /// Requests the window to leave fullscreen.
+    ///
+    /// Like [`Window::set_fullscreen`], this does not need a commit and is answered with a configure.
     pub fn unset_fullscreen(&self) {
         self.xdg_toplevel().unset_fullscreen()
     }
@@ -246,6 +538,20 @@ impl Window {
     /// # Configure loops
     ///
     /// You should avoid sending multiple decoration mode requests to ensure you do not enter a configure loop.
+    ///
+    /// # Without server side decorations
+    ///
+    /// If the compositor supports neither `zxdg_decoration_manager_v1` nor
+    /// `org_kde_kwin_server_decoration_manager`, or the window was created with
+    /// [`WindowDecorations::ClientOnly`] or [`WindowDecorations::None`], this does nothing and the window
+    /// stays in [`DecorationMode::Client`]. Use [`Window::server_decorations_available`] to check this
+    /// before offering a choice to the user.
+    ///
+    /// # KDE server decorations
+    ///
+    /// When only `org_kde_kwin_server_decoration_manager` is available, a mode of [`None`] does nothing,
+    /// and the compositor does not send a configure for the new mode: it is reported by the next
+    /// configure of the window, and by [`Window::decoration_mode`] once the compositor answered.
     pub fn request_decoration_mode(&self, mode: Option<DecorationMode>) {
         if let Some(toplevel_decoration) = &self.0.toplevel_decoration {
             match mode {
@@ -253,24 +559,65 @@ impl Window {
                 Some(DecorationMode::Server) => toplevel_decoration.set_mode(Mode::ServerSide),
                 None => toplevel_decoration.unset_mode(),
             }
+        } else if let Some(kde_decoration) = &self.0.kde_decoration {
+            let mode = match mode {
+                Some(DecorationMode::Client) => org_kde_kwin_server_decoration::Mode::Client,
+                Some(DecorationMode::Server) => org_kde_kwin_server_decoration::Mode::Server,
+                None => return,
+            };
+            kde_decoration.request_mode(mode as u32);
         }
     }
 
+    /// Whether the decoration mode of the window can be negotiated with the compositor.
+    ///
+    /// This is `false` if the compositor supports neither `zxdg_decoration_manager_v1` nor
+    /// `org_kde_kwin_server_decoration_manager`, or if the window was created with
+    /// [`WindowDecorations::ClientOnly`] or [`WindowDecorations::None`].
+    pub fn server_decorations_available(&self) -> bool {
+        self.0.toplevel_decoration.is_some() || self.0.kde_decoration.is_some()
+    }
+
+    /// The decoration mode most recently decided by the compositor.
+    ///
+    /// This is [`DecorationMode::Client`] until the compositor configures the decoration, and always if
+    /// [`Window::server_decorations_available`] is `false`.
+    pub fn decoration_mode(&self) -> DecorationMode {
+        self.0.pending_configure.lock().unwrap().decoration_mode
+    }
+
+    /// Starts an interactive move of the window, usually when the user drags a titlebar.
+    ///
+    /// The `serial` must be the serial of the pointer button press or touch down event starting the
+    /// move, otherwise the compositor may ignore the request.
     pub fn move_(&self, seat: &wl_seat::WlSeat, serial: u32) {
         self.xdg_toplevel()._move(seat, serial)
     }
 
+    /// Starts an interactive resize of the window from the given edge.
+    ///
+    /// The `serial` must be the serial of the pointer button press or touch down event starting the
+    /// resize. [`resize_edge_at`] finds the edge under the pointer for client side decorations.
     pub fn resize(&self, seat: &wl_seat::WlSeat, serial: u32, edges: xdg_toplevel::ResizeEdge) {
         self.xdg_toplevel().resize(seat, serial, edges)
     }
 
     // Double buffered window state
 
+    /// Sets the minimum size of the window, or removes it with [`None`].
+    ///
+    /// This state is double buffered and applied on the next commit of the window. Removing the
+    /// minimum size sends a size of 0 by 0, which means no constraint.
     pub fn set_min_size(&self, min_size: Option<(u32, u32)>) {
         let min_size = min_size.unwrap_or_default();
         self.xdg_toplevel().set_min_size(min_size.0 as i32, min_size.1 as i32);
     }
 
+    /// Sets the maximum size of the window, or removes it with [`None`].
+    ///
+    /// This state is double buffered and applied on the next commit of the window. Removing the
+    /// maximum size sends a size of 0 by 0, which means no constraint.
+    ///
     /// # Protocol errors
     ///
     /// The maximum size of the window may not be smaller than the minimum size.
@@ -279,6 +626,103 @@ impl Window {
         self.xdg_toplevel().set_max_size(max_size.0 as i32, max_size.1 as i32);
     }
 
+    // Keyboard shortcuts
+
+    /// Request that the compositor stops handling its keyboard shortcuts for this window and seat.
+    ///
+    /// The inhibitor is kept by the window until [`Window::uninhibit_shortcuts`] is called or the window
+    /// is dropped. It stays valid when the keyboard capability of the seat changes or the window is
+    /// unmapped, the compositor reports whether it is active through
+    /// [`ShortcutsInhibitHandler`](crate::seat::shortcuts_inhibit::ShortcutsInhibitHandler).
+    ///
+    /// Calling this again for the same seat returns the existing inhibitor.
+    pub fn inhibit_shortcuts<D>(
+        &self,
+        shortcuts_inhibit_state: &mut ShortcutsInhibitState,
+        seat: &wl_seat::WlSeat,
+        qh: &QueueHandle<D>,
+    ) -> Result<ShortcutsInhibitor, ShortcutsInhibitError>
+    where
+        D: Dispatch<ZwpKeyboardShortcutsInhibitorV1, ShortcutsInhibitorData> + 'static,
+    {
+        let mut inhibitors = self.0.shortcuts_inhibitors.lock().unwrap();
+        if let Some(inhibitor) = inhibitors.iter().find(|inhibitor| inhibitor.seat() == seat) {
+            return Ok(inhibitor.clone());
+        }
+
+        let inhibitor = shortcuts_inhibit_state.inhibit_shortcuts(self.wl_surface(), seat, qh)?;
+        inhibitors.push(inhibitor.clone());
+        Ok(inhibitor)
+    }
+
+    /// Release the keyboard shortcut inhibitors of this window.
+    ///
+    /// Shortcuts are restored once all clones of the inhibitors returned by
+    /// [`Window::inhibit_shortcuts`] are dropped.
+    pub fn uninhibit_shortcuts(&self) {
+        self.0.shortcuts_inhibitors.lock().unwrap().clear();
+    }
+
+    /// The keyboard shortcut inhibitor of this window for a seat, if any.
+    pub fn shortcuts_inhibitor(&self, seat: &wl_seat::WlSeat) -> Option<ShortcutsInhibitor> {
+        self.0
+            .shortcuts_inhibitors
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|inhibitor| inhibitor.seat() == seat)
+            .cloned()
+    }
+
+    /// Link the window to its application menu, exported over D-Bus at `service_name` and
+    /// `object_path`, so that the global menu of KDE Plasma can show it.
+    ///
+    /// The menu is kept by the window until [`Window::unset_app_menu`] is called or the window is
+    /// dropped. Calling this again updates the address of the menu.
+    pub fn set_app_menu<D>(
+        &self,
+        appmenu_manager: &AppMenuManagerState,
+        service_name: String,
+        object_path: String,
+        qh: &QueueHandle<D>,
+    ) where
+        D: Dispatch<OrgKdeKwinAppmenu, GlobalData> + 'static,
+    {
+        let mut app_menu = self.0.app_menu.lock().unwrap();
+        let app_menu =
+            app_menu.get_or_insert_with(|| appmenu_manager.create(self.wl_surface(), qh));
+        app_menu.set_address(service_name, object_path);
+    }
+
+    /// Remove the application menu set with [`Window::set_app_menu`].
+    pub fn unset_app_menu(&self) {
+        self.0.app_menu.lock().unwrap().take();
+    }
+
+    /// Tell the compositor what kind of content the window shows.
+    ///
+    /// The content type is kept by the window until [`Window::unset_content_type`] is called or the
+    /// window is dropped. Like other surface state, it is applied on the next commit.
+    pub fn set_content_type<D>(
+        &self,
+        content_type_state: &ContentTypeState,
+        content_type: ContentType,
+        qh: &QueueHandle<D>,
+    ) where
+        D: Dispatch<WpContentTypeV1, ContentTypeData> + 'static,
+    {
+        let mut surface_content_type = self.0.content_type.lock().unwrap();
+        let surface_content_type = surface_content_type.get_or_insert_with(|| {
+            content_type_state.get_surface_content_type(self.wl_surface(), qh)
+        });
+        surface_content_type.set_content_type(content_type);
+    }
+
+    /// Remove the content type set with [`Window::set_content_type`].
+    pub fn unset_content_type(&self) {
+        self.0.content_type.lock().unwrap().take();
+    }
+
     // Other
 
     /// Returns the underlying xdg toplevel wrapped by this window.
@@ -310,12 +754,143 @@ pub struct WindowData(pub(crate) Weak<WindowInner>);
 
 #[macro_export]
 macro_rules! delegate_xdg_window {
-    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
-        $crate::reexports::client::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
             $crate::reexports::protocols::xdg::shell::client::xdg_surface::XdgSurface: $crate::shell::xdg::window::WindowData
         ] => $crate::shell::xdg::XdgShell);
-        $crate::reexports::client::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
             $crate::reexports::protocols::xdg::shell::client::xdg_toplevel::XdgToplevel: $crate::shell::xdg::window::WindowData
         ] => $crate::shell::xdg::XdgShell);
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use wayland_backend::protocol::Argument;
+    use wayland_client::{protocol::wl_compositor::WlCompositor, Proxy};
+
+    use super::{Window, WindowDecorations};
+    use crate::{
+        reexports::protocols::xdg::shell::client::xdg_wm_base::XdgWmBase,
+        shell::{xdg::XdgSurface, WaylandSurface},
+        testing::{Call, TestClient, TestServer},
+    };
+
+    fn connect() -> (TestServer, TestClient) {
+        let mut server = TestServer::new();
+        server.create_global(WlCompositor::interface(), 6);
+        server.create_global(XdgWmBase::interface(), 6);
+        let client = server.connect();
+        (server, client)
+    }
+
+    /// The requests sent by the client, with their arguments, without the surface creation.
+    fn requests(server: &mut TestServer) -> Vec<String> {
+        server
+            .take_requests()
+            .into_iter()
+            .map(|request| {
+                let args = request
+                    .args
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        Argument::Int(value) => Some(value.to_string()),
+                        Argument::Str(Some(value)) => Some(value.to_string_lossy().into_owned()),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                format!("{}({})", request.name, args.join(", "))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn builder_before_initial_commit() {
+        let (mut server, mut client) = connect();
+        let surface = client.create_surface();
+        server.take_requests();
+
+        let window = Window::builder()
+            .decorations(WindowDecorations::ClientOnly)
+            .title("title")
+            .app_id("app.id")
+            .min_size(Some((100, 50)))
+            .max_size(Some((800, 600)))
+            .build(client.state.xdg_shell.as_ref().unwrap(), surface, &client.qh);
+        window.commit();
+        client.roundtrip();
+
+        assert_eq!(
+            requests(&mut server),
+            [
+                "xdg_wm_base.get_xdg_surface()",
+                "xdg_surface.get_toplevel()",
+                "xdg_toplevel.set_title(title)",
+                "xdg_toplevel.set_app_id(app.id)",
+                "xdg_toplevel.set_min_size(100, 50)",
+                "xdg_toplevel.set_max_size(800, 600)",
+                "wl_surface.commit()",
+            ]
+        );
+    }
+
+    #[test]
+    fn builder_without_state() {
+        let (mut server, mut client) = connect();
+        let surface = client.create_surface();
+        server.take_requests();
+
+        let window = Window::builder().decorations(WindowDecorations::ClientOnly).build(
+            client.state.xdg_shell.as_ref().unwrap(),
+            surface,
+            &client.qh,
+        );
+        window.commit();
+        client.roundtrip();
+
+        assert_eq!(
+            requests(&mut server),
+            ["xdg_wm_base.get_xdg_surface()", "xdg_surface.get_toplevel()", "wl_surface.commit()"]
+        );
+    }
+
+    #[test]
+    fn setters_after_map() {
+        let (mut server, mut client) = connect();
+        let surface = client.create_surface();
+        let window = Window::builder()
+            .decorations(WindowDecorations::ClientOnly)
+            .min_size(Some((100, 50)))
+            .build(client.state.xdg_shell.as_ref().unwrap(), surface, &client.qh);
+        window.commit();
+        client.roundtrip();
+
+        server.send(
+            &server.object(window.xdg_toplevel()),
+            "configure",
+            vec![Argument::Int(0), Argument::Int(0), Argument::Array(Box::default())],
+        );
+        server.send(&server.object(window.xdg_surface()), "configure", vec![Argument::Uint(1)]);
+        client.roundtrip();
+        assert!(matches!(client.state.take_calls()[..], [Call::Configure(..)]));
+        server.take_requests();
+
+        window.set_title("other title");
+        window.set_app_id("other.app.id");
+        window.set_min_size(None);
+        window.set_max_size(Some((400, 300)));
+        window.commit();
+        client.roundtrip();
+
+        assert_eq!(
+            requests(&mut server),
+            [
+                "xdg_toplevel.set_title(other title)",
+                "xdg_toplevel.set_app_id(other.app.id)",
+                "xdg_toplevel.set_min_size(0, 0)",
+                "xdg_toplevel.set_max_size(400, 300)",
+                "wl_surface.commit()",
+            ]
+        );
+    }
+}
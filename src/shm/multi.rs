@@ -93,6 +93,9 @@ pub enum PoolError {
 
 /// This pool manages buffers associated with keys.
 /// Only one buffer can be attributed to a given key.
+///
+/// The format and layout of a buffer are tracked per key, so buffers of different formats
+/// (for example `Xrgb8888` and `Argb8888`) can share a single pool.
 #[derive(Debug)]
 pub struct MultiPool<K> {
     buffer_list: Vec<BufferSlot<K>>,
@@ -105,6 +108,10 @@ pub struct BufferSlot<K> {
     size: usize,
     used: usize,
     offset: usize,
+    width: i32,
+    stride: i32,
+    height: i32,
+    format: wl_shm::Format,
     buffer: Option<wl_buffer::WlBuffer>,
     key: K,
 }
@@ -121,6 +128,19 @@ impl<K> BufferSlot<K> {
             self.free.load(Ordering::Relaxed).then(|| buffer.destroy()).ok_or(PoolError::InUse)
         })
     }
+
+    /// Returns the format of the buffer last created for this slot.
+    pub fn format(&self) -> wl_shm::Format {
+        self.format
+    }
+
+    /// Returns whether the buffer of this slot was created with the given layout.
+    fn matches(&self, width: i32, stride: i32, height: i32, format: wl_shm::Format) -> bool {
+        self.width == width
+            && self.stride == stride
+            && self.height == height
+            && self.format == format
+    }
 }
 
 impl<K> MultiPool<K> {
@@ -180,8 +200,9 @@ impl<K> MultiPool<K> {
             if buf_slot.key.borrow().eq(key) {
                 found_key = true;
                 if buf_slot.free.load(Ordering::Relaxed) {
-                    // Destroys the buffer if it's resized
-                    if size != buf_slot.used {
+                    // Destroys the buffer if it's resized or if its format changed.
+                    // Each key may use its own format, so the layout is tracked per slot.
+                    if size != buf_slot.used || !buf_slot.matches(width, stride, height, format) {
                         if let Some(buffer) = buf_slot.buffer.take() {
                             buffer.destroy();
                         }
@@ -253,10 +274,19 @@ impl<K> MultiPool<K> {
         let buf_slot =
             self.buffer_list.iter_mut().find(|buf_slot| buf_slot.key.borrow().eq(key))?;
 
-        if buf_slot.size >= size {
+        if size > buf_slot.size {
             return None;
         }
 
+        if !buf_slot.matches(width, stride, height, format) {
+            if !buf_slot.free.load(Ordering::Relaxed) {
+                return None;
+            }
+            if let Some(buffer) = buf_slot.buffer.take() {
+                buffer.destroy();
+            }
+        }
+
         buf_slot.used = size;
         let offset = buf_slot.offset;
         if buf_slot.buffer.is_none() {
@@ -275,6 +305,10 @@ impl<K> MultiPool<K> {
             );
             buf_slot.free = free;
             buf_slot.buffer = Some(buffer);
+            buf_slot.width = width;
+            buf_slot.stride = stride;
+            buf_slot.height = height;
+            buf_slot.format = format;
         }
         let buf = buf_slot.buffer.as_ref()?;
         buf_slot.free.store(false, Ordering::Relaxed);
@@ -344,6 +378,10 @@ impl<K> MultiPool<K> {
             );
             buf_slot.free = free;
             buf_slot.buffer = Some(buffer);
+            buf_slot.width = width;
+            buf_slot.stride = stride;
+            buf_slot.height = height;
+            buf_slot.format = format;
         }
         buf_slot.free.store(false, Ordering::Relaxed);
         let buf = buf_slot.buffer.as_ref().unwrap();
@@ -388,6 +426,10 @@ impl<K> MultiPool<K> {
         self.buffer_list.push(BufferSlot {
             offset,
             used: 0,
+            width,
+            stride,
+            height,
+            format,
             free,
             buffer: Some(buffer),
             size,
@@ -419,3 +461,80 @@ impl wayland_client::backend::ObjectData for BufferObjectData {
 
     fn destroyed(&self, _: wayland_backend::client::ObjectId) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use wayland_backend::protocol::Argument;
+    use wayland_client::{
+        protocol::{wl_buffer::WlBuffer, wl_shm},
+        Proxy,
+    };
+
+    use super::MultiPool;
+    use crate::testing::{Request, TestServer};
+
+    /// The formats of the buffers created by the requests.
+    fn created_formats(requests: &[Request]) -> Vec<u32> {
+        requests
+            .iter()
+            .filter(|request| request.name == "wl_shm_pool.create_buffer")
+            .map(|request| match request.args[5] {
+                Argument::Uint(format) => format,
+                _ => panic!("wl_shm_pool.create_buffer has no format"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn mixed_formats() {
+        let mut server = TestServer::new();
+        server.create_global(wl_shm::WlShm::interface(), 1);
+        let mut client = server.connect();
+        let mut pool = MultiPool::new(client.state.shm.as_ref().unwrap()).unwrap();
+
+        let argb = pool.create_buffer(4, 16, 4, &0, wl_shm::Format::Argb8888).unwrap().1.clone();
+        let xbgr = pool.create_buffer(4, 16, 4, &1, wl_shm::Format::Xbgr2101010).unwrap().1.clone();
+        client.roundtrip();
+        assert_eq!(
+            created_formats(&server.take_requests()),
+            [u32::from(wl_shm::Format::Argb8888), u32::from(wl_shm::Format::Xbgr2101010)]
+        );
+        assert_eq!(pool.buffer_list[0].format(), wl_shm::Format::Argb8888);
+        assert_eq!(pool.buffer_list[1].format(), wl_shm::Format::Xbgr2101010);
+
+        // A buffer still used by the compositor cannot be replaced by one of another format.
+        assert!(pool.get(4, 16, 4, &0, wl_shm::Format::Xbgr2101010).is_none());
+        assert!(pool.get(4, 16, 4, &1, wl_shm::Format::Argb8888).is_none());
+
+        release(&mut server, &argb);
+        client.roundtrip();
+
+        // A buffer that fits the requested size is reused as is.
+        let (_, buffer, _) = pool.get(4, 16, 4, &0, wl_shm::Format::Argb8888).unwrap();
+        assert_eq!(buffer, &argb);
+        client.roundtrip();
+        assert!(server.take_requests().is_empty());
+
+        release(&mut server, &xbgr);
+        client.roundtrip();
+
+        // Reusing a key with another format replaces its buffer.
+        let (_, buffer, _) = pool.get(4, 16, 4, &1, wl_shm::Format::Argb8888).unwrap();
+        assert_ne!(buffer, &xbgr);
+        client.roundtrip();
+        let requests = server.take_requests();
+        assert_eq!(requests[0].name, "wl_buffer.destroy");
+        assert_eq!(created_formats(&requests), [u32::from(wl_shm::Format::Argb8888)]);
+        assert_eq!(pool.buffer_list[1].format(), wl_shm::Format::Argb8888);
+
+        // A buffer too small for the requested size is not returned.
+        release(&mut server, &argb);
+        client.roundtrip();
+        assert!(pool.get(8, 32, 8, &0, wl_shm::Format::Argb8888).is_none());
+    }
+
+    fn release(server: &mut TestServer, buffer: &WlBuffer) {
+        let object = server.object(buffer);
+        server.send(&object, "release", Vec::new());
+    }
+}
@@ -168,7 +168,7 @@ impl io::Seek for RawPool {
 }
 
 impl RawPool {
-    fn create_shm_fd() -> io::Result<OwnedFd> {
+    pub(crate) fn create_shm_fd() -> io::Result<OwnedFd> {
         #[cfg(target_os = "linux")]
         {
             match RawPool::create_memfd() {
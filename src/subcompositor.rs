@@ -1,3 +1,19 @@
+//! ## Subsurfaces
+//!
+//! A subsurface is a surface placed relative to a parent surface, and shown along with it. They are
+//! used to show content that updates independently of the rest of the window, such as a video or an
+//! embedded widget, or that uses another buffer format, without drawing it into the buffer of the
+//! parent.
+//!
+//! A [`Subsurface`] owns its surface and is created with
+//! [`SubcompositorState::subsurface_from_surface`]. Subsurfaces follow the scale factor and
+//! transform of their parent until the compositor sends their own, which
+//! [`CompositorHandler`](crate::compositor::CompositorHandler) reports for them as for any surface.
+//!
+//! By default a subsurface is synchronized: its commits are applied with the next commit of its
+//! parent. A desynchronized subsurface, see [`Subsurface::set_desync`], applies its commits right
+//! away, for example to show video frames without redrawing the window.
+
 use crate::reexports::client::globals::{BindError, GlobalList};
 use crate::reexports::client::protocol::wl_compositor::WlCompositor;
 use crate::reexports::client::protocol::wl_subcompositor::WlSubcompositor;
@@ -5,8 +21,9 @@ use crate::reexports::client::protocol::wl_subsurface::WlSubsurface;
 use crate::reexports::client::protocol::wl_surface::WlSurface;
 use crate::reexports::client::{Connection, Dispatch, Proxy, QueueHandle};
 
-use crate::compositor::SurfaceData;
+use crate::compositor::{Surface, SurfaceData};
 use crate::globals::GlobalData;
+use crate::shell::WaylandSurface;
 
 #[derive(Debug)]
 pub struct SubcompositorState {
@@ -37,11 +54,132 @@ impl SubcompositorState {
     {
         let surface_data = SurfaceData::new(Some(parent.clone()), 1);
         let surface = self.compositor.create_surface(queue_handle, surface_data);
+        if let Some(parent_data) = parent.data::<SurfaceData>() {
+            parent_data.add_child(&surface);
+        }
         let subsurface_data = SubsurfaceData::new(surface.clone());
         let subsurface =
             self.subcompositor.get_subsurface(&surface, &parent, queue_handle, subsurface_data);
         (subsurface, surface)
     }
+
+    /// Make a surface a subsurface of `parent`.
+    ///
+    /// The subsurface is placed at the top left corner of the parent, above it, and is synchronized.
+    /// Like all double buffered state of a subsurface, it is only shown once the parent is committed.
+    ///
+    /// # Protocol errors
+    ///
+    /// The surface must not already have a role, and must not be an ancestor of `parent`.
+    pub fn subsurface_from_surface<State>(
+        &self,
+        surface: impl Into<Surface>,
+        parent: &WlSurface,
+        queue_handle: &QueueHandle<State>,
+    ) -> Subsurface
+    where
+        State: Dispatch<WlSubsurface, SubsurfaceData> + 'static,
+    {
+        let surface = surface.into();
+        if let Some(surface_data) = surface.wl_surface().data::<SurfaceData>() {
+            surface_data.set_parent_surface(Some(parent.clone()));
+        }
+        if let Some(parent_data) = parent.data::<SurfaceData>() {
+            parent_data.add_child(surface.wl_surface());
+        }
+
+        let subsurface_data = SubsurfaceData::new(surface.wl_surface().clone());
+        let subsurface = self.subcompositor.get_subsurface(
+            surface.wl_surface(),
+            parent,
+            queue_handle,
+            subsurface_data,
+        );
+
+        Subsurface { subsurface, parent: parent.clone(), surface }
+    }
+}
+
+/// A subsurface, which owns its surface.
+///
+/// The subsurface and then its surface are destroyed on drop, which unmaps them with the next commit
+/// of the parent.
+#[derive(Debug)]
+pub struct Subsurface {
+    subsurface: WlSubsurface,
+    parent: WlSurface,
+    surface: Surface,
+}
+
+impl Subsurface {
+    pub fn wl_subsurface(&self) -> &WlSubsurface {
+        &self.subsurface
+    }
+
+    /// The parent of this subsurface.
+    pub fn parent(&self) -> &WlSurface {
+        &self.parent
+    }
+
+    /// Set the position of the subsurface, relative to the top left corner of the parent surface.
+    ///
+    /// The position applies with the next commit of the parent.
+    pub fn set_position(&self, x: i32, y: i32) {
+        self.subsurface.set_position(x, y);
+    }
+
+    /// Stack the subsurface right above `sibling`, which is its parent or another subsurface of the
+    /// same parent.
+    ///
+    /// The stacking order applies with the next commit of the parent.
+    pub fn place_above(&self, sibling: &WlSurface) {
+        self.subsurface.place_above(sibling);
+    }
+
+    /// Stack the subsurface right below `sibling`, which is its parent or another subsurface of the
+    /// same parent.
+    ///
+    /// The stacking order applies with the next commit of the parent.
+    pub fn place_below(&self, sibling: &WlSurface) {
+        self.subsurface.place_below(sibling);
+    }
+
+    /// Make the commits of the subsurface apply with the next commit of its parent.
+    ///
+    /// This is the default.
+    pub fn set_sync(&self) {
+        self.subsurface.set_sync();
+    }
+
+    /// Make the commits of the subsurface apply right away.
+    ///
+    /// The subsurface still behaves as synchronized while its parent, or an ancestor of it, is
+    /// synchronized.
+    pub fn set_desync(&self) {
+        self.subsurface.set_desync();
+    }
+}
+
+impl WaylandSurface for Subsurface {
+    fn wl_surface(&self) -> &WlSurface {
+        self.surface.wl_surface()
+    }
+}
+
+impl PartialEq for Subsurface {
+    fn eq(&self, other: &Self) -> bool {
+        self.subsurface == other.subsurface
+    }
+}
+
+impl Drop for Subsurface {
+    fn drop(&mut self) {
+        if let Some(parent_data) = self.parent.data::<SurfaceData>() {
+            parent_data.remove_child(self.surface.wl_surface());
+        }
+        // The surface is destroyed after the subsurface, when the field is dropped.
+        self.subsurface.destroy();
+    }
 }
 
 impl<D> Dispatch<WlSubsurface, SubsurfaceData, D> for SubcompositorState
@@ -96,22 +234,22 @@ impl SubsurfaceData {
 
 #[macro_export]
 macro_rules! delegate_subcompositor {
-    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
-        $crate::delegate_subcompositor!(@{ $(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty }; subsurface: []);
-        $crate::delegate_subcompositor!(@{ $(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty }; subsurface-only: $crate::subcompositor::SubsurfaceData);
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::delegate_subcompositor!(@{ $(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })? }; subsurface: []);
+        $crate::delegate_subcompositor!(@{ $(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })? }; subsurface-only: $crate::subcompositor::SubsurfaceData);
     };
-    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty, subsurface: [$($subsurface: ty),*$(,)?]) => {
-        $crate::delegate_subcompositor!(@{ $(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty }; subsurface: [ $($subsurface),* ]);
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty, subsurface: [$($subsurface: ty),*$(,)?] $(where $($bound:tt)+)?) => {
+        $crate::delegate_subcompositor!(@{ $(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })? }; subsurface: [ $($subsurface),* ]);
     };
     (@{$($ty:tt)*}; subsurface: []) => {
-        $crate::reexports::client::delegate_dispatch!($($ty)*:
+        $crate::__delegate_dispatch!($($ty)*:
             [
                 $crate::reexports::client::protocol::wl_subcompositor::WlSubcompositor: $crate::globals::GlobalData
             ] => $crate::subcompositor::SubcompositorState
         );
     };
     (@{$($ty:tt)*}; subsurface-only: $subsurface:ty) => {
-        $crate::reexports::client::delegate_dispatch!($($ty)*:
+        $crate::__delegate_dispatch!($($ty)*:
             [
                     $crate::reexports::client::protocol::wl_subsurface::WlSubsurface: $subsurface
             ] => $crate::subcompositor::SubcompositorState
@@ -0,0 +1,85 @@
+//! ## System bell
+//!
+//! The `xdg_system_bell_v1` global lets clients such as terminal emulators ring the system bell, so
+//! the compositor notifies the user the way it is configured to, for example with a sound or by
+//! flashing the window.
+//!
+//! [`SystemBellState::bind`] does not fail if the compositor does not support the system bell. In
+//! this case [`SystemBellState::ring`] returns an error, and the client should fall back to its own
+//! notification, which [`SystemBellState::is_available`] tells in advance.
+
+use wayland_client::{
+    globals::GlobalList, protocol::wl_surface, Connection, Dispatch, QueueHandle,
+};
+use wayland_protocols::xdg::system_bell::v1::client::xdg_system_bell_v1;
+
+use crate::{
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+    registry::GlobalProxy,
+};
+
+/// The system bell global.
+#[derive(Debug)]
+pub struct SystemBellState {
+    system_bell: GlobalProxy<xdg_system_bell_v1::XdgSystemBellV1>,
+}
+
+impl SystemBellState {
+    /// Bind the `xdg_system_bell_v1` global, if it exists.
+    pub fn bind<D>(globals: &GlobalList, qh: &QueueHandle<D>) -> Self
+    where
+        D: Dispatch<xdg_system_bell_v1::XdgSystemBellV1, GlobalData> + 'static,
+    {
+        let system_bell = GlobalProxy::bind(globals, qh, 1..=1, GlobalData);
+        Self { system_bell }
+    }
+
+    /// Whether the compositor supports the system bell.
+    pub fn is_available(&self) -> bool {
+        self.system_bell.get().is_ok()
+    }
+
+    /// Ring the system bell, for a surface if it is related to one.
+    ///
+    /// The compositor may use the surface to notify the user, for example by flashing it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support the system bell.
+    pub fn ring(&self, surface: Option<&wl_surface::WlSurface>) -> Result<(), GlobalError> {
+        self.system_bell.get()?.ring(surface);
+        Ok(())
+    }
+}
+
+impl ProvidesBoundGlobal<xdg_system_bell_v1::XdgSystemBellV1, 1> for SystemBellState {
+    fn bound_global(&self) -> Result<xdg_system_bell_v1::XdgSystemBellV1, GlobalError> {
+        self.system_bell.get().cloned()
+    }
+}
+
+impl<D> Dispatch<xdg_system_bell_v1::XdgSystemBellV1, GlobalData, D> for SystemBellState
+where
+    D: Dispatch<xdg_system_bell_v1::XdgSystemBellV1, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &xdg_system_bell_v1::XdgSystemBellV1,
+        _: xdg_system_bell_v1::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("xdg_system_bell_v1 has no events")
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_system_bell {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::xdg::system_bell::v1::client::xdg_system_bell_v1::XdgSystemBellV1: $crate::globals::GlobalData
+        ] => $crate::system_bell::SystemBellState);
+    };
+}
@@ -0,0 +1,210 @@
+//! ## Tearing control
+//!
+//! The `wp_tearing_control_manager_v1` global lets clients such as games hint that the buffers of a
+//! surface may be presented as soon as they are committed, even if this causes tearing, to reduce the
+//! latency.
+//!
+//! The hint only changes how the compositor presents the buffers, it does not make frame callbacks
+//! arrive sooner. Clients should still throttle their rendering, with frame callbacks or presentation
+//! feedback, rather than committing as fast as they can.
+
+use std::sync::{Arc, Weak};
+
+use wayland_client::{
+    globals::{BindError, GlobalList},
+    protocol::wl_surface,
+    Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols::wp::tearing_control::v1::client::{
+    wp_tearing_control_manager_v1, wp_tearing_control_v1,
+};
+
+use crate::{
+    compositor::SurfaceData,
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+};
+
+pub use wp_tearing_control_v1::PresentationHint;
+
+/// The tearing control manager global.
+#[derive(Debug)]
+pub struct TearingControlState {
+    manager: wp_tearing_control_manager_v1::WpTearingControlManagerV1,
+}
+
+impl TearingControlState {
+    /// Binds the `wp_tearing_control_manager_v1` global.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support tearing control.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State: Dispatch<wp_tearing_control_manager_v1::WpTearingControlManagerV1, GlobalData>
+            + 'static,
+    {
+        let manager = globals.bind(qh, 1..=1, GlobalData)?;
+        Ok(Self { manager })
+    }
+
+    /// Get the tearing control of a surface.
+    ///
+    /// If the surface was created with [`SurfaceData`], the tearing control is stored in it, and this
+    /// returns the same tearing control until all its clones are dropped.
+    ///
+    /// # Protocol errors
+    ///
+    /// Only one tearing control may exist for a surface at a time.
+    pub fn get_tearing_control<D>(
+        &self,
+        surface: &wl_surface::WlSurface,
+        qh: &QueueHandle<D>,
+    ) -> TearingControl
+    where
+        D: Dispatch<wp_tearing_control_v1::WpTearingControlV1, TearingControlData> + 'static,
+    {
+        let surface_data = surface.data::<SurfaceData>();
+
+        if let Some(tearing_control) = surface_data
+            .and_then(SurfaceData::tearing_control)
+            .and_then(|tearing_control| TearingControl::from_tearing_control(&tearing_control))
+        {
+            return tearing_control;
+        }
+
+        let inner = Arc::new_cyclic(|weak| {
+            let tearing_control = self.manager.get_tearing_control(
+                surface,
+                qh,
+                TearingControlData { inner: weak.clone() },
+            );
+
+            TearingControlInner { tearing_control, surface: surface.clone() }
+        });
+
+        if let Some(surface_data) = surface_data {
+            surface_data.set_tearing_control(Some(inner.tearing_control.clone()));
+        }
+
+        TearingControl(inner)
+    }
+}
+
+impl ProvidesBoundGlobal<wp_tearing_control_manager_v1::WpTearingControlManagerV1, 1>
+    for TearingControlState
+{
+    fn bound_global(
+        &self,
+    ) -> Result<wp_tearing_control_manager_v1::WpTearingControlManagerV1, GlobalError> {
+        Ok(self.manager.clone())
+    }
+}
+
+#[derive(Debug)]
+struct TearingControlInner {
+    tearing_control: wp_tearing_control_v1::WpTearingControlV1,
+    surface: wl_surface::WlSurface,
+}
+
+impl Drop for TearingControlInner {
+    fn drop(&mut self) {
+        if let Some(surface_data) = self.surface.data::<SurfaceData>() {
+            surface_data.set_tearing_control(None);
+        }
+        self.tearing_control.destroy();
+    }
+}
+
+/// The tearing control of a surface.
+///
+/// The tearing control is destroyed when the last clone is dropped, which must happen before the
+/// surface is destroyed. The surface is then presented with [`PresentationHint::Vsync`] after its next
+/// commit.
+#[derive(Debug, Clone)]
+pub struct TearingControl(Arc<TearingControlInner>);
+
+impl TearingControl {
+    pub fn from_tearing_control(
+        tearing_control: &wp_tearing_control_v1::WpTearingControlV1,
+    ) -> Option<Self> {
+        tearing_control.data::<TearingControlData>().and_then(|data| data.inner.upgrade()).map(Self)
+    }
+
+    pub fn tearing_control(&self) -> &wp_tearing_control_v1::WpTearingControlV1 {
+        &self.0.tearing_control
+    }
+
+    /// The surface of this tearing control.
+    pub fn wl_surface(&self) -> &wl_surface::WlSurface {
+        &self.0.surface
+    }
+
+    /// Set how the buffers of the surface should be presented.
+    ///
+    /// With [`PresentationHint::Async`], the compositor may present them as soon as they are
+    /// committed, which may cause tearing.
+    ///
+    /// This state is double buffered and applied on the next commit of the surface.
+    pub fn set_presentation_hint(&self, hint: PresentationHint) {
+        self.0.tearing_control.set_presentation_hint(hint);
+    }
+}
+
+impl PartialEq for TearingControl {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.tearing_control == other.0.tearing_control
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct TearingControlData {
+    inner: Weak<TearingControlInner>,
+}
+
+impl<D> Dispatch<wp_tearing_control_manager_v1::WpTearingControlManagerV1, GlobalData, D>
+    for TearingControlState
+where
+    D: Dispatch<wp_tearing_control_manager_v1::WpTearingControlManagerV1, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &wp_tearing_control_manager_v1::WpTearingControlManagerV1,
+        _: wp_tearing_control_manager_v1::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("wp_tearing_control_manager_v1 has no events")
+    }
+}
+
+impl<D> Dispatch<wp_tearing_control_v1::WpTearingControlV1, TearingControlData, D>
+    for TearingControlState
+where
+    D: Dispatch<wp_tearing_control_v1::WpTearingControlV1, TearingControlData>,
+{
+    fn event(
+        _: &mut D,
+        _: &wp_tearing_control_v1::WpTearingControlV1,
+        _: wp_tearing_control_v1::Event,
+        _: &TearingControlData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("wp_tearing_control_v1 has no events")
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_tearing_control {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::tearing_control::v1::client::wp_tearing_control_manager_v1::WpTearingControlManagerV1: $crate::globals::GlobalData
+        ] => $crate::tearing_control::TearingControlState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::tearing_control::v1::client::wp_tearing_control_v1::WpTearingControlV1: $crate::tearing_control::TearingControlData
+        ] => $crate::tearing_control::TearingControlState);
+    };
+}
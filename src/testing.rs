@@ -0,0 +1,807 @@
+//! An in-process compositor for the unit tests.
+//!
+//! [`TestServer`] advertises the globals a test creates, records every request sent by the client and
+//! lets the test send arbitrary events, so the dispatch code of the toolkit can be driven with
+//! synthetic event sequences. [`TestClient`] is the client side, whose [`TestState`] records the
+//! calls made to its handlers.
+
+// Each test only uses the parts of the harness it needs.
+#![allow(dead_code)]
+
+use std::{
+    ffi::CString,
+    os::unix::{
+        io::{OwnedFd, RawFd},
+        net::UnixStream,
+    },
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use wayland_backend::{
+    protocol::{Argument, Interface, Message},
+    server::{
+        self, ClientData, ClientId, DisconnectReason, GlobalHandler, GlobalId, Handle, ObjectData,
+        ObjectId,
+    },
+    smallvec::SmallVec,
+};
+#[cfg(feature = "xkbcommon")]
+use wayland_client::protocol::wl_keyboard::WlKeyboard;
+use wayland_client::{
+    globals::{registry_queue_init, GlobalList},
+    protocol::{
+        wl_output::{self, WlOutput},
+        wl_pointer::WlPointer,
+        wl_seat::{self, WlSeat},
+        wl_surface::WlSurface,
+    },
+    Connection, EventQueue, Proxy, QueueHandle,
+};
+use wayland_protocols::wp::pointer_gestures::zv1::client::{
+    zwp_pointer_gesture_hold_v1::ZwpPointerGestureHoldV1,
+    zwp_pointer_gesture_pinch_v1::ZwpPointerGesturePinchV1,
+    zwp_pointer_gesture_swipe_v1::ZwpPointerGestureSwipeV1,
+};
+
+use crate::{
+    compositor::{CompositorHandler, CompositorState},
+    delegate_compositor, delegate_kde_blur, delegate_output, delegate_pointer,
+    delegate_pointer_gestures, delegate_registry, delegate_seat, delegate_shm, delegate_xdg_shell,
+    delegate_xdg_window,
+    output::{OutputHandler, OutputInfo, OutputState},
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    seat::{
+        pointer::{PointerEvent, PointerHandler},
+        pointer_gestures::{PointerGestureKind, PointerGesturesHandler, PointerGesturesState},
+        Capability, SeatHandler, SeatState,
+    },
+    shell::{
+        plasma::blur::{KdeBlurHandler, KdeBlurManagerState},
+        xdg::{
+            window::{Window, WindowConfigure, WindowHandler},
+            XdgShell, XdgShellHandler,
+        },
+    },
+    shm::{Shm, ShmHandler},
+};
+#[cfg(feature = "xkbcommon")]
+use crate::{
+    delegate_keyboard,
+    seat::keyboard::{KeyEvent, KeyboardHandler, Keysym, Modifiers},
+};
+
+/// A request sent by the client.
+#[derive(Debug)]
+pub(crate) struct Request {
+    /// The object the request was sent to.
+    pub object: ObjectId,
+    /// The name of the request, such as `wl_surface.commit`.
+    pub name: String,
+    pub args: Vec<Argument<ObjectId, OwnedFd>>,
+}
+
+impl Request {
+    /// The object created by the request.
+    pub fn new_id(&self) -> Option<&ObjectId> {
+        self.args.iter().find_map(|arg| match arg {
+            Argument::NewId(id) => Some(id),
+            _ => None,
+        })
+    }
+}
+
+/// The requests and objects of the client.
+#[derive(Debug, Default)]
+struct Log {
+    requests: Vec<Request>,
+    /// The objects created by the client, in order.
+    objects: Vec<ObjectId>,
+}
+
+/// Records the requests sent to every object and global.
+#[derive(Debug, Default)]
+struct Recorder {
+    log: Mutex<Log>,
+}
+
+impl ObjectData<()> for Recorder {
+    fn request(
+        self: Arc<Self>,
+        _: &Handle,
+        _: &mut (),
+        _: ClientId,
+        msg: Message<ObjectId, OwnedFd>,
+    ) -> Option<Arc<dyn ObjectData<()>>> {
+        let interface = msg.sender_id.interface();
+        let name = format!("{}.{}", interface.name, interface.requests[msg.opcode as usize].name);
+        let request = Request { object: msg.sender_id, name, args: msg.args.into_vec() };
+
+        let mut log = self.log.lock().unwrap();
+        let new_id = request.new_id().cloned();
+        if let Some(id) = &new_id {
+            log.objects.push(id.clone());
+        }
+        log.requests.push(request);
+        drop(log);
+
+        new_id.map(|_| self as Arc<dyn ObjectData<()>>)
+    }
+
+    fn destroyed(self: Arc<Self>, _: &Handle, _: &mut (), _: ClientId, _: ObjectId) {}
+}
+
+impl GlobalHandler<()> for Recorder {
+    fn bind(
+        self: Arc<Self>,
+        _: &Handle,
+        _: &mut (),
+        _: ClientId,
+        _: GlobalId,
+        object_id: ObjectId,
+    ) -> Arc<dyn ObjectData<()>> {
+        self.log.lock().unwrap().objects.push(object_id);
+        self
+    }
+}
+
+#[derive(Debug)]
+struct TestClientData;
+
+impl ClientData for TestClientData {
+    fn disconnected(&self, _: server::ClientId, _: DisconnectReason) {}
+}
+
+/// A compositor dispatching the requests of a single client from a background thread.
+#[derive(Debug)]
+pub(crate) struct TestServer {
+    handle: Handle,
+    client: Option<ClientId>,
+    recorder: Arc<Recorder>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl TestServer {
+    pub fn new() -> Self {
+        let mut backend = server::Backend::<()>::new().unwrap();
+        let handle = backend.handle();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread = thread::spawn({
+            let stop = stop.clone();
+            move || {
+                while !stop.load(Ordering::SeqCst) {
+                    let _ = backend.dispatch_all_clients(&mut ());
+                    let _ = backend.flush(None);
+                    thread::sleep(Duration::from_millis(1));
+                }
+            }
+        });
+
+        TestServer { handle, client: None, recorder: Arc::default(), stop, thread: Some(thread) }
+    }
+
+    /// Connects the client, which binds the globals created so far.
+    pub fn connect(&mut self) -> TestClient {
+        let (client_stream, server_stream) = UnixStream::pair().unwrap();
+        self.client =
+            Some(self.handle.insert_client(server_stream, Arc::new(TestClientData)).unwrap());
+
+        let conn = Connection::from_socket(client_stream).unwrap();
+        let (globals, queue) = registry_queue_init(&conn).unwrap();
+        let qh = queue.handle();
+        let state = TestState::new(&globals, &qh);
+        TestClient { conn, queue, qh, state }
+    }
+
+    /// Advertises a global.
+    pub fn create_global(&mut self, interface: &'static Interface, version: u32) -> GlobalId {
+        let global = self.handle.create_global::<()>(interface, version, self.recorder.clone());
+        self.flush();
+        global
+    }
+
+    /// Removes a global, like a compositor does once it knows clients are done with it.
+    pub fn remove_global(&mut self, global: GlobalId) {
+        self.handle.remove_global::<()>(global);
+        self.flush();
+    }
+
+    /// Tells the client a global is removed, while still allowing it to be bound.
+    pub fn disable_global(&mut self, global: GlobalId) {
+        self.handle.disable_global::<()>(global);
+        self.flush();
+    }
+
+    /// Sends an event to an object of the client.
+    pub fn send(&mut self, object: &ObjectId, event: &str, args: Vec<Argument<ObjectId, RawFd>>) {
+        let opcode = object
+            .interface()
+            .events
+            .iter()
+            .position(|desc| desc.name == event)
+            .unwrap_or_else(|| panic!("{} has no {} event", object.interface().name, event));
+        let msg = Message {
+            sender_id: object.clone(),
+            opcode: opcode as u16,
+            args: SmallVec::from(args),
+        };
+        self.handle.send_event(msg).unwrap();
+        self.flush();
+    }
+
+    /// Sends the info of an output followed by `wl_output.done`, as a compositor does when the
+    /// output is bound or changes.
+    pub fn send_output_info(
+        &mut self,
+        output: &ObjectId,
+        location: (i32, i32),
+        mode: (i32, i32),
+        scale: i32,
+    ) {
+        let geometry = vec![
+            Argument::Int(location.0),
+            Argument::Int(location.1),
+            Argument::Int(600),
+            Argument::Int(340),
+            Argument::Int(wl_output::Subpixel::Unknown as i32),
+            string("make"),
+            string("model"),
+            Argument::Int(wl_output::Transform::Normal as i32),
+        ];
+        self.send(output, "geometry", geometry);
+        let current = wl_output::Mode::Current.bits();
+        let mode = vec![
+            Argument::Uint(current),
+            Argument::Int(mode.0),
+            Argument::Int(mode.1),
+            Argument::Int(60000),
+        ];
+        self.send(output, "mode", mode);
+        self.send(output, "scale", vec![Argument::Int(scale)]);
+        self.send(output, "done", vec![]);
+    }
+
+    /// The server side of a proxy of the client.
+    ///
+    /// The client must have done a roundtrip since the proxy was created.
+    pub fn object<I: Proxy>(&self, proxy: &I) -> ObjectId {
+        self.handle
+            .object_for_protocol_id(
+                self.client.clone().unwrap(),
+                I::interface(),
+                proxy.id().protocol_id(),
+            )
+            .unwrap()
+    }
+
+    /// The objects created by the client with the given interface, in order.
+    pub fn objects(&self, interface: &str) -> Vec<ObjectId> {
+        let log = self.recorder.log.lock().unwrap();
+        log.objects.iter().filter(|id| id.interface().name == interface).cloned().collect()
+    }
+
+    /// The last object created by the client with the given interface.
+    pub fn last_object(&self, interface: &str) -> ObjectId {
+        self.objects(interface).pop().unwrap_or_else(|| panic!("no {} was created", interface))
+    }
+
+    /// Takes the requests the client sent since this was last called.
+    pub fn take_requests(&mut self) -> Vec<Request> {
+        std::mem::take(&mut self.recorder.log.lock().unwrap().requests)
+    }
+
+    /// Waits for the client to send a request, without the client doing a roundtrip.
+    ///
+    /// The requests sent before it are discarded.
+    pub fn wait_for_request(&mut self, name: &str) -> Request {
+        for _ in 0..1000 {
+            let mut log = self.recorder.log.lock().unwrap();
+            if let Some(i) = log.requests.iter().position(|request| request.name == name) {
+                return log.requests.drain(..=i).next_back().unwrap();
+            }
+            drop(log);
+            thread::sleep(Duration::from_millis(1));
+        }
+        panic!("the client did not send {}", name)
+    }
+
+    /// Takes the names of the requests the client sent since this was last called.
+    pub fn take_request_names(&mut self) -> Vec<String> {
+        self.take_requests().into_iter().map(|request| request.name).collect()
+    }
+
+    fn flush(&mut self) {
+        let _ = self.handle.flush(None);
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// A string argument of an event.
+pub(crate) fn string(value: &str) -> Argument<ObjectId, RawFd> {
+    Argument::Str(Some(Box::new(CString::new(value).unwrap())))
+}
+
+/// The client connected to a [`TestServer`].
+#[derive(Debug)]
+pub(crate) struct TestClient {
+    pub conn: Connection,
+    pub queue: EventQueue<TestState>,
+    pub qh: QueueHandle<TestState>,
+    pub state: TestState,
+}
+
+impl TestClient {
+    /// Dispatches the events sent by the server until it processed every request sent so far.
+    pub fn roundtrip(&mut self) {
+        self.queue.roundtrip(&mut self.state).unwrap();
+    }
+
+    /// The first seat, once the server advertised the given capabilities for it.
+    pub fn seat(&mut self, server: &mut TestServer, capabilities: wl_seat::Capability) -> WlSeat {
+        self.roundtrip();
+        let seat = self.state.seat_state.seats().next().expect("no seat was advertised");
+        server.send(
+            &server.object(&seat),
+            "capabilities",
+            vec![Argument::Uint(capabilities.bits())],
+        );
+        self.roundtrip();
+        self.state.take_calls();
+        seat
+    }
+
+    /// Creates a surface.
+    pub fn create_surface(&mut self) -> WlSurface {
+        let surface = self.state.compositor.as_ref().unwrap().create_surface(&self.qh);
+        self.roundtrip();
+        surface
+    }
+}
+
+/// A call made to a handler of [`TestState`].
+#[derive(Debug, Clone)]
+pub(crate) enum Call {
+    ScaleFactorChanged(WlSurface, i32),
+    TransformChanged(WlSurface, wl_output::Transform),
+    Frame(WlSurface),
+    SurfaceEnter(WlSurface, WlOutput),
+    SurfaceLeave(WlSurface, WlOutput),
+    NewOutput(WlOutput),
+    UpdateOutput(WlOutput),
+    ModeChanged(WlOutput, OutputInfo),
+    ScaleChanged(WlOutput, OutputInfo),
+    GeometryChanged(WlOutput, OutputInfo),
+    /// The info of the output as available from [`OutputState::info`] during the call.
+    OutputDestroyed(WlOutput, Option<OutputInfo>),
+    NewCapability(Capability),
+    RemoveCapability(Capability),
+    PointerFrame(Vec<PointerEvent>),
+    GestureBegin(PointerGestureKind, u32),
+    GestureEnd(PointerGestureKind, bool),
+    Ping(u32),
+    RequestClose(Window),
+    Configure(Window, WindowConfigure),
+    #[cfg(feature = "xkbcommon")]
+    KeyboardEnter(Vec<u32>, Vec<Keysym>),
+    #[cfg(feature = "xkbcommon")]
+    KeyboardLeave,
+    #[cfg(feature = "xkbcommon")]
+    PressKey(KeyEvent),
+    #[cfg(feature = "xkbcommon")]
+    ReleaseKey(KeyEvent),
+    #[cfg(feature = "xkbcommon")]
+    UpdateModifiers(Modifiers),
+}
+
+/// The state of the test client, which binds the globals it knows when they are advertised.
+#[derive(Debug)]
+pub(crate) struct TestState {
+    pub registry_state: RegistryState,
+    pub compositor: Option<CompositorState>,
+    pub output_state: OutputState,
+    pub seat_state: SeatState,
+    pub pointer_gestures: PointerGesturesState,
+    pub shm: Option<Shm>,
+    pub xdg_shell: Option<XdgShell>,
+    pub kde_blur: KdeBlurManagerState,
+    pub calls: Vec<Call>,
+}
+
+impl TestState {
+    fn new(globals: &GlobalList, qh: &QueueHandle<Self>) -> Self {
+        TestState {
+            registry_state: RegistryState::new(globals),
+            compositor: CompositorState::bind(globals, qh).ok(),
+            output_state: OutputState::new(globals, qh),
+            seat_state: SeatState::new(globals, qh),
+            pointer_gestures: PointerGesturesState::bind(globals, qh),
+            shm: Shm::bind(globals, qh).ok(),
+            xdg_shell: XdgShell::bind(globals, qh).ok(),
+            kde_blur: KdeBlurManagerState::new(globals),
+            calls: Vec::new(),
+        }
+    }
+
+    /// Takes the calls made to the handlers since this was last called.
+    pub fn take_calls(&mut self) -> Vec<Call> {
+        std::mem::take(&mut self.calls)
+    }
+}
+
+impl CompositorHandler for TestState {
+    fn scale_factor_changed(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        surface: &WlSurface,
+        new_factor: i32,
+    ) {
+        self.calls.push(Call::ScaleFactorChanged(surface.clone(), new_factor));
+    }
+
+    fn transform_changed(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        surface: &WlSurface,
+        new_transform: wl_output::Transform,
+    ) {
+        self.calls.push(Call::TransformChanged(surface.clone(), new_transform));
+    }
+
+    fn frame(&mut self, _: &Connection, _: &QueueHandle<Self>, surface: &WlSurface, _: u32) {
+        self.calls.push(Call::Frame(surface.clone()));
+    }
+
+    fn surface_enter(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        surface: &WlSurface,
+        output: &WlOutput,
+    ) {
+        self.calls.push(Call::SurfaceEnter(surface.clone(), output.clone()));
+    }
+
+    fn surface_leave(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        surface: &WlSurface,
+        output: &WlOutput,
+    ) {
+        self.calls.push(Call::SurfaceLeave(surface.clone(), output.clone()));
+    }
+}
+
+impl OutputHandler for TestState {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(&mut self, _: &Connection, _: &QueueHandle<Self>, output: WlOutput) {
+        self.calls.push(Call::NewOutput(output));
+    }
+
+    fn update_output(&mut self, _: &Connection, _: &QueueHandle<Self>, output: WlOutput) {
+        self.calls.push(Call::UpdateOutput(output));
+    }
+
+    fn mode_changed(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        output: WlOutput,
+        previous: &OutputInfo,
+    ) {
+        self.calls.push(Call::ModeChanged(output, previous.clone()));
+    }
+
+    fn scale_changed(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        output: WlOutput,
+        previous: &OutputInfo,
+    ) {
+        self.calls.push(Call::ScaleChanged(output, previous.clone()));
+    }
+
+    fn geometry_changed(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        output: WlOutput,
+        previous: &OutputInfo,
+    ) {
+        self.calls.push(Call::GeometryChanged(output, previous.clone()));
+    }
+
+    fn output_destroyed(&mut self, _: &Connection, _: &QueueHandle<Self>, output: WlOutput) {
+        let info = self.output_state.info(&output);
+        self.calls.push(Call::OutputDestroyed(output, info));
+    }
+}
+
+impl SeatHandler for TestState {
+    fn seat_state(&mut self) -> &mut SeatState {
+        &mut self.seat_state
+    }
+
+    fn new_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: WlSeat) {}
+
+    fn new_capability(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: WlSeat,
+        capability: Capability,
+    ) {
+        self.calls.push(Call::NewCapability(capability));
+    }
+
+    fn remove_capability(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: WlSeat,
+        capability: Capability,
+    ) {
+        self.calls.push(Call::RemoveCapability(capability));
+    }
+
+    fn remove_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: WlSeat) {}
+}
+
+impl PointerHandler for TestState {
+    fn pointer_frame(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &WlPointer,
+        events: &[PointerEvent],
+    ) {
+        self.calls.push(Call::PointerFrame(events.to_vec()));
+    }
+}
+
+impl PointerGesturesHandler for TestState {
+    fn swipe_begin(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &ZwpPointerGestureSwipeV1,
+        _: &WlPointer,
+        _: u32,
+        _: u32,
+        _: &WlSurface,
+        fingers: u32,
+    ) {
+        self.calls.push(Call::GestureBegin(PointerGestureKind::Swipe, fingers));
+    }
+
+    fn swipe_update(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &ZwpPointerGestureSwipeV1,
+        _: &WlPointer,
+        _: u32,
+        _: f64,
+        _: f64,
+    ) {
+    }
+
+    fn swipe_end(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &ZwpPointerGestureSwipeV1,
+        _: &WlPointer,
+        _: u32,
+        _: u32,
+        cancelled: bool,
+    ) {
+        self.calls.push(Call::GestureEnd(PointerGestureKind::Swipe, cancelled));
+    }
+
+    fn pinch_begin(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &ZwpPointerGesturePinchV1,
+        _: &WlPointer,
+        _: u32,
+        _: u32,
+        _: &WlSurface,
+        fingers: u32,
+    ) {
+        self.calls.push(Call::GestureBegin(PointerGestureKind::Pinch, fingers));
+    }
+
+    fn pinch_update(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &ZwpPointerGesturePinchV1,
+        _: &WlPointer,
+        _: u32,
+        _: f64,
+        _: f64,
+        _: f64,
+        _: f64,
+    ) {
+    }
+
+    fn pinch_end(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &ZwpPointerGesturePinchV1,
+        _: &WlPointer,
+        _: u32,
+        _: u32,
+        cancelled: bool,
+    ) {
+        self.calls.push(Call::GestureEnd(PointerGestureKind::Pinch, cancelled));
+    }
+
+    fn hold_begin(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &ZwpPointerGestureHoldV1,
+        _: &WlPointer,
+        _: u32,
+        _: u32,
+        _: &WlSurface,
+        fingers: u32,
+    ) {
+        self.calls.push(Call::GestureBegin(PointerGestureKind::Hold, fingers));
+    }
+
+    fn hold_end(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &ZwpPointerGestureHoldV1,
+        _: &WlPointer,
+        _: u32,
+        _: u32,
+        cancelled: bool,
+    ) {
+        self.calls.push(Call::GestureEnd(PointerGestureKind::Hold, cancelled));
+    }
+}
+
+#[cfg(feature = "xkbcommon")]
+impl KeyboardHandler for TestState {
+    fn enter(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &WlKeyboard,
+        _: &WlSurface,
+        _: u32,
+        raw: &[u32],
+        keysyms: &[Keysym],
+    ) {
+        self.calls.push(Call::KeyboardEnter(raw.to_vec(), keysyms.to_vec()));
+    }
+
+    fn leave(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &WlKeyboard,
+        _: &WlSurface,
+        _: u32,
+    ) {
+        self.calls.push(Call::KeyboardLeave);
+    }
+
+    fn press_key(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &WlKeyboard,
+        _: u32,
+        event: KeyEvent,
+    ) {
+        self.calls.push(Call::PressKey(event));
+    }
+
+    fn release_key(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &WlKeyboard,
+        _: u32,
+        event: KeyEvent,
+    ) {
+        self.calls.push(Call::ReleaseKey(event));
+    }
+
+    fn update_modifiers(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &WlKeyboard,
+        _: u32,
+        modifiers: Modifiers,
+        _: u32,
+    ) {
+        self.calls.push(Call::UpdateModifiers(modifiers));
+    }
+}
+
+impl XdgShellHandler for TestState {
+    fn ping(&mut self, _: &Connection, _: &QueueHandle<Self>, serial: u32) {
+        self.calls.push(Call::Ping(serial));
+    }
+}
+
+impl WindowHandler for TestState {
+    fn request_close(&mut self, _: &Connection, _: &QueueHandle<Self>, window: &Window) {
+        self.calls.push(Call::RequestClose(window.clone()));
+    }
+
+    fn configure(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        window: &Window,
+        configure: WindowConfigure,
+        _: u32,
+    ) {
+        self.calls.push(Call::Configure(window.clone(), configure));
+    }
+}
+
+impl ShmHandler for TestState {
+    fn shm_state(&mut self) -> &mut Shm {
+        self.shm.as_mut().unwrap()
+    }
+}
+
+impl KdeBlurHandler for TestState {
+    fn kde_blur_state(&mut self) -> &mut KdeBlurManagerState {
+        &mut self.kde_blur
+    }
+}
+
+impl ProvidesRegistryState for TestState {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers!(OutputState, SeatState, KdeBlurManagerState);
+}
+
+delegate_compositor!(TestState);
+delegate_kde_blur!(TestState);
+delegate_output!(TestState);
+delegate_seat!(TestState);
+delegate_pointer!(TestState);
+delegate_pointer_gestures!(TestState);
+#[cfg(feature = "xkbcommon")]
+delegate_keyboard!(TestState);
+delegate_registry!(TestState);
+delegate_shm!(TestState);
+delegate_xdg_shell!(TestState);
+delegate_xdg_window!(TestState);
@@ -0,0 +1,310 @@
+//! ## Viewporter
+//!
+//! A `wp_viewport` lets the compositor crop and scale the buffer of a surface: the source rectangle
+//! selects a part of the buffer, and the destination size sets the size of the surface, independently
+//! of the size of the buffer.
+//!
+//! This is used to draw at a [fractional scale](crate::fractional_scale), or to let the compositor
+//! scale images and videos.
+
+use wayland_client::{
+    globals::{BindError, GlobalList},
+    protocol::wl_surface,
+    Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols::wp::viewporter::client::{wp_viewport, wp_viewporter};
+
+use crate::{
+    compositor::SurfaceData,
+    error::GlobalError,
+    globals::{GlobalData, ProvidesBoundGlobal},
+};
+
+/// An error when setting the source or destination of a [`Viewport`].
+#[derive(Debug, thiserror::Error)]
+pub enum ViewportError {
+    /// The source rectangle has a negative position, or a size that is not positive once rounded to
+    /// 1/256.
+    #[error("the source rectangle must have a non-negative position and a positive size")]
+    InvalidSource,
+
+    /// The destination size is zero.
+    #[error("the destination size must be positive")]
+    InvalidDestination,
+}
+
+/// The viewporter global.
+#[derive(Debug)]
+pub struct ViewporterState {
+    viewporter: wp_viewporter::WpViewporter,
+}
+
+impl ViewporterState {
+    /// Binds the `wp_viewporter` global.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support viewports.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State: Dispatch<wp_viewporter::WpViewporter, GlobalData> + 'static,
+    {
+        let viewporter = globals.bind(qh, 1..=1, GlobalData)?;
+        Ok(Self { viewporter })
+    }
+
+    /// Create the viewport of a surface.
+    ///
+    /// If the surface was created with [`SurfaceData`], the viewport is also available from
+    /// [`SurfaceData::viewport`] until it is dropped.
+    ///
+    /// # Protocol errors
+    ///
+    /// Only one [`Viewport`] may exist for a surface at a time.
+    pub fn get_viewport<D>(&self, surface: &wl_surface::WlSurface, qh: &QueueHandle<D>) -> Viewport
+    where
+        D: Dispatch<wp_viewport::WpViewport, ViewportData> + 'static,
+    {
+        let data = ViewportData { surface: surface.clone() };
+        let viewport = self.viewporter.get_viewport(surface, qh, data);
+        if let Some(surface_data) = surface.data::<SurfaceData>() {
+            surface_data.set_viewport(Some(viewport.clone()));
+        }
+        Viewport(viewport)
+    }
+}
+
+impl ProvidesBoundGlobal<wp_viewporter::WpViewporter, 1> for ViewporterState {
+    fn bound_global(&self) -> Result<wp_viewporter::WpViewporter, GlobalError> {
+        Ok(self.viewporter.clone())
+    }
+}
+
+/// The viewport of a surface.
+///
+/// The viewport is destroyed when this is dropped, which must happen before the surface is destroyed.
+/// The source and destination of the surface are then unset on its next commit.
+#[derive(Debug)]
+pub struct Viewport(wp_viewport::WpViewport);
+
+impl Viewport {
+    pub fn viewport(&self) -> &wp_viewport::WpViewport {
+        &self.0
+    }
+
+    /// The surface of this viewport.
+    pub fn surface(&self) -> &wl_surface::WlSurface {
+        &self.0.data::<ViewportData>().unwrap().surface
+    }
+
+    /// Sets the rectangle of the buffer to show, as `(x, y, width, height)` in buffer coordinates
+    /// after the buffer transform and scale are applied, or shows the whole buffer with [`None`].
+    ///
+    /// The values are rounded to the nearest 1/256, the precision of the protocol.
+    ///
+    /// This state is double buffered and applied on the next commit of the surface.
+    ///
+    /// # Protocol errors
+    ///
+    /// The rectangle must be inside of the buffer when the surface is committed.
+    pub fn set_source(&self, source: Option<(f64, f64, f64, f64)>) -> Result<(), ViewportError> {
+        let (x, y, width, height) = source_to_raw(source)?;
+        self.0.set_source(x, y, width, height);
+        Ok(())
+    }
+
+    /// Sets the size of the surface, in surface coordinates, or uses the size of the source with
+    /// [`None`].
+    ///
+    /// This state is double buffered and applied on the next commit of the surface.
+    ///
+    /// # Protocol errors
+    ///
+    /// Without a destination, the size of the source must be integer.
+    pub fn set_destination(&self, destination: Option<(u32, u32)>) -> Result<(), ViewportError> {
+        let (width, height) = destination_to_raw(destination)?;
+        self.0.set_destination(width, height);
+        Ok(())
+    }
+}
+
+impl Drop for Viewport {
+    fn drop(&mut self) {
+        if let Some(surface_data) = self.surface().data::<SurfaceData>() {
+            surface_data.set_viewport(None);
+        }
+        self.0.destroy();
+    }
+}
+
+/// Convert a source rectangle to the arguments of `wp_viewport::set_source`, where -1 everywhere
+/// unsets the source.
+///
+/// The values are rounded to the precision of `wl_fixed`, so a size rounded to zero is rejected
+/// instead of causing a protocol error.
+fn source_to_raw(
+    source: Option<(f64, f64, f64, f64)>,
+) -> Result<(f64, f64, f64, f64), ViewportError> {
+    match source {
+        None => Ok((-1., -1., -1., -1.)),
+        Some((x, y, width, height)) => {
+            let rounded = (round_fixed(x), round_fixed(y), round_fixed(width), round_fixed(height));
+            match rounded {
+                (Some(x), Some(y), Some(width), Some(height))
+                    if x >= 0. && y >= 0. && width > 0. && height > 0. =>
+                {
+                    Ok((x, y, width, height))
+                }
+                _ => Err(ViewportError::InvalidSource),
+            }
+        }
+    }
+}
+
+/// Round a value to the nearest multiple of 1/256 representable as a `wl_fixed`, or [`None`] if it is
+/// not finite or out of range.
+fn round_fixed(value: f64) -> Option<f64> {
+    let fixed = (value * 256.).round();
+    (i32::MIN as f64..=i32::MAX as f64).contains(&fixed).then(|| fixed / 256.)
+}
+
+/// Convert a destination size to the arguments of `wp_viewport::set_destination`, where -1 for both
+/// unsets the destination.
+fn destination_to_raw(destination: Option<(u32, u32)>) -> Result<(i32, i32), ViewportError> {
+    match destination {
+        None => Ok((-1, -1)),
+        Some((width, height)) => {
+            let width = i32::try_from(width).map_err(|_| ViewportError::InvalidDestination)?;
+            let height = i32::try_from(height).map_err(|_| ViewportError::InvalidDestination)?;
+            if width > 0 && height > 0 {
+                Ok((width, height))
+            } else {
+                Err(ViewportError::InvalidDestination)
+            }
+        }
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct ViewportData {
+    surface: wl_surface::WlSurface,
+}
+
+impl<D> Dispatch<wp_viewporter::WpViewporter, GlobalData, D> for ViewporterState
+where
+    D: Dispatch<wp_viewporter::WpViewporter, GlobalData>,
+{
+    fn event(
+        _: &mut D,
+        _: &wp_viewporter::WpViewporter,
+        _: wp_viewporter::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("wp_viewporter has no events")
+    }
+}
+
+impl<D> Dispatch<wp_viewport::WpViewport, ViewportData, D> for ViewporterState
+where
+    D: Dispatch<wp_viewport::WpViewport, ViewportData>,
+{
+    fn event(
+        _: &mut D,
+        _: &wp_viewport::WpViewport,
+        _: wp_viewport::Event,
+        _: &ViewportData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        unreachable!("wp_viewport has no events")
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_viewporter {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::viewporter::client::wp_viewporter::WpViewporter: $crate::globals::GlobalData
+        ] => $crate::viewporter::ViewporterState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::wp::viewporter::client::wp_viewport::WpViewport: $crate::viewporter::ViewportData
+        ] => $crate::viewporter::ViewporterState);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{destination_to_raw, source_to_raw, ViewportError};
+
+    #[test]
+    fn source() {
+        assert_eq!(source_to_raw(None).unwrap(), (-1., -1., -1., -1.));
+        assert_eq!(source_to_raw(Some((0., 0., 1., 1.))).unwrap(), (0., 0., 1., 1.));
+        assert_eq!(
+            source_to_raw(Some((10.5, 2.25, 640., 480.125))).unwrap(),
+            (10.5, 2.25, 640., 480.125)
+        );
+    }
+
+    #[test]
+    fn source_rounding() {
+        // Values are rounded to the nearest 1/256, rather than truncated by the protocol.
+        assert_eq!(
+            source_to_raw(Some((0.1, 0.999, 100.3, 0.002))).unwrap(),
+            (26. / 256., 1., 25677. / 256., 1. / 256.)
+        );
+        // A size smaller than the precision of the protocol would be sent as zero.
+        assert!(matches!(
+            source_to_raw(Some((0., 0., 0.001, 1.))),
+            Err(ViewportError::InvalidSource)
+        ));
+        // A position slightly below zero is rounded to zero.
+        assert_eq!(source_to_raw(Some((-0.001, -0., 1., 1.))).unwrap(), (0., 0., 1., 1.));
+    }
+
+    #[test]
+    fn source_invalid() {
+        for source in [
+            (-1., 0., 1., 1.),
+            (0., -1., 1., 1.),
+            (0., 0., 0., 1.),
+            (0., 0., 1., 0.),
+            (0., 0., -1., 1.),
+            (-1., -1., -1., 1.),
+            (f64::NAN, 0., 1., 1.),
+            (0., 0., f64::INFINITY, 1.),
+            (0., 0., 1., 1e10),
+        ] {
+            assert!(
+                matches!(source_to_raw(Some(source)), Err(ViewportError::InvalidSource)),
+                "{:?}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn destination() {
+        assert_eq!(destination_to_raw(None).unwrap(), (-1, -1));
+        assert_eq!(destination_to_raw(Some((1, 1))).unwrap(), (1, 1));
+        assert_eq!(destination_to_raw(Some((1920, 1080))).unwrap(), (1920, 1080));
+        assert_eq!(destination_to_raw(Some((i32::MAX as u32, 1))).unwrap(), (i32::MAX, 1));
+    }
+
+    #[test]
+    fn destination_invalid() {
+        for destination in [(0, 1), (1, 0), (0, 0), (i32::MAX as u32 + 1, 1), (1, u32::MAX)] {
+            assert!(
+                matches!(
+                    destination_to_raw(Some(destination)),
+                    Err(ViewportError::InvalidDestination)
+                ),
+                "{:?}",
+                destination
+            );
+        }
+    }
+}
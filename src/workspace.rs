@@ -0,0 +1,526 @@
+//! ## ext workspace
+//!
+//! The `ext_workspace_manager_v1` global lets pagers and bars list the workspaces, or virtual desktops,
+//! of the compositor, and switch between them.
+//!
+//! Workspaces are organized in groups, usually one group per output. [`WorkspaceState`] keeps track of
+//! the groups and workspaces, whose properties are applied atomically when the compositor is done
+//! sending a change, and reported to [`WorkspaceHandler`].
+//!
+//! Requests such as [`Workspace::activate`] are only applied once [`WorkspaceState::commit`] is
+//! called, so several of them can be applied at once.
+
+use std::sync::Mutex;
+
+use wayland_client::{
+    event_created_child,
+    globals::{BindError, GlobalList},
+    protocol::wl_output,
+    Connection, Dispatch, Proxy, QueueHandle, WEnum,
+};
+use wayland_protocols::ext::workspace::v1::client::{
+    ext_workspace_group_handle_v1, ext_workspace_handle_v1, ext_workspace_manager_v1,
+};
+
+use crate::globals::GlobalData;
+
+pub use ext_workspace_group_handle_v1::GroupCapabilities;
+pub use ext_workspace_handle_v1::{State, WorkspaceCapabilities};
+
+/// Handler for workspace groups and workspaces.
+pub trait WorkspaceHandler: Sized {
+    fn workspace_state(&mut self) -> &mut WorkspaceState;
+
+    /// A workspace group was created, and its properties are known.
+    fn new_workspace_group(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        group: &WorkspaceGroup,
+    );
+
+    /// The properties of a workspace group changed, such as its outputs or workspaces.
+    fn update_workspace_group(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        group: &WorkspaceGroup,
+    );
+
+    /// A workspace group was removed.
+    ///
+    /// The group is destroyed after this returns.
+    fn workspace_group_removed(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        group: &WorkspaceGroup,
+    );
+
+    /// A workspace was created, and its properties are known.
+    fn new_workspace(&mut self, conn: &Connection, qh: &QueueHandle<Self>, workspace: &Workspace);
+
+    /// The properties of a workspace changed, such as its name or state.
+    fn update_workspace(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        workspace: &Workspace,
+    );
+
+    /// A workspace was removed.
+    ///
+    /// The workspace is destroyed after this returns.
+    fn workspace_removed(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        workspace: &Workspace,
+    );
+}
+
+/// The workspace manager global, and the workspace groups and workspaces it reported.
+#[derive(Debug)]
+pub struct WorkspaceState {
+    manager: ext_workspace_manager_v1::ExtWorkspaceManagerV1,
+    groups: Vec<WorkspaceGroup>,
+    workspaces: Vec<Workspace>,
+    finished: bool,
+}
+
+impl WorkspaceState {
+    /// Binds the `ext_workspace_manager_v1` global.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Err`] if the compositor does not support ext workspace.
+    pub fn bind<State>(globals: &GlobalList, qh: &QueueHandle<State>) -> Result<Self, BindError>
+    where
+        State: Dispatch<ext_workspace_manager_v1::ExtWorkspaceManagerV1, GlobalData> + 'static,
+    {
+        let manager = globals.bind(qh, 1..=1, GlobalData)?;
+        Ok(Self { manager, groups: Vec::new(), workspaces: Vec::new(), finished: false })
+    }
+
+    /// The workspace groups whose properties are known.
+    pub fn groups(&self) -> impl Iterator<Item = &WorkspaceGroup> {
+        self.groups.iter().filter(|group| group.info().is_some())
+    }
+
+    /// The workspaces whose properties are known, including the ones without a group.
+    pub fn workspaces(&self) -> impl Iterator<Item = &Workspace> {
+        self.workspaces.iter().filter(|workspace| workspace.info().is_some())
+    }
+
+    /// Apply the requests made since the last commit, such as [`Workspace::activate`], at once.
+    pub fn commit(&self) {
+        if !self.finished {
+            self.manager.commit();
+        }
+    }
+
+    /// Whether the compositor stopped sending workspace events.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Stop receiving workspace events.
+    ///
+    /// The workspace groups and workspaces are removed once the compositor acknowledged it.
+    pub fn stop(&self) {
+        if !self.finished {
+            self.manager.stop();
+        }
+    }
+}
+
+/// A group of workspaces, usually shown on the same outputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceGroup(ext_workspace_group_handle_v1::ExtWorkspaceGroupHandleV1);
+
+impl WorkspaceGroup {
+    pub fn group(&self) -> &ext_workspace_group_handle_v1::ExtWorkspaceGroupHandleV1 {
+        &self.0
+    }
+
+    /// The properties of the group, or [`None`] until the compositor sent them.
+    pub fn info(&self) -> Option<WorkspaceGroupInfo> {
+        self.data().inner.lock().unwrap().current.clone()
+    }
+
+    /// Request to create a workspace in this group.
+    ///
+    /// This is applied once [`WorkspaceState::commit`] is called. The compositor may ignore it,
+    /// unless the group has [`GroupCapabilities::CreateWorkspace`].
+    pub fn create_workspace(&self, name: &str) {
+        self.0.create_workspace(name.to_owned());
+    }
+
+    fn data(&self) -> &WorkspaceGroupData {
+        self.0.data::<WorkspaceGroupData>().unwrap()
+    }
+}
+
+/// The properties of a workspace group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct WorkspaceGroupInfo {
+    pub capabilities: GroupCapabilities,
+    /// The outputs the workspaces of the group are shown on.
+    pub outputs: Vec<wl_output::WlOutput>,
+    /// The workspaces of the group.
+    pub workspaces: Vec<Workspace>,
+}
+
+impl Default for WorkspaceGroupInfo {
+    fn default() -> Self {
+        Self {
+            capabilities: GroupCapabilities::empty(),
+            outputs: Vec::new(),
+            workspaces: Vec::new(),
+        }
+    }
+}
+
+/// A workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workspace(ext_workspace_handle_v1::ExtWorkspaceHandleV1);
+
+impl Workspace {
+    pub fn workspace(&self) -> &ext_workspace_handle_v1::ExtWorkspaceHandleV1 {
+        &self.0
+    }
+
+    /// The properties of the workspace, or [`None`] until the compositor sent them.
+    pub fn info(&self) -> Option<WorkspaceInfo> {
+        self.data().inner.lock().unwrap().current.clone()
+    }
+
+    /// Request to activate the workspace.
+    ///
+    /// This is applied once [`WorkspaceState::commit`] is called.
+    pub fn activate(&self) {
+        self.0.activate();
+    }
+
+    /// Request to deactivate the workspace.
+    ///
+    /// This is applied once [`WorkspaceState::commit`] is called.
+    pub fn deactivate(&self) {
+        self.0.deactivate();
+    }
+
+    /// Request to move the workspace to another group.
+    ///
+    /// This is applied once [`WorkspaceState::commit`] is called.
+    pub fn assign(&self, group: &WorkspaceGroup) {
+        self.0.assign(&group.0);
+    }
+
+    /// Request to remove the workspace.
+    ///
+    /// This is applied once [`WorkspaceState::commit`] is called.
+    pub fn remove(&self) {
+        self.0.remove();
+    }
+
+    fn data(&self) -> &WorkspaceData {
+        self.0.data::<WorkspaceData>().unwrap()
+    }
+}
+
+/// The properties of a workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct WorkspaceInfo {
+    /// An identifier which stays the same across sessions, if the compositor has one.
+    pub id: Option<String>,
+    pub name: String,
+    /// The position of the workspace in a grid of any number of dimensions, if the compositor lays out
+    /// workspaces this way.
+    pub coordinates: Vec<u32>,
+    pub state: State,
+    pub capabilities: WorkspaceCapabilities,
+    /// The group of the workspace, if it has one.
+    pub group: Option<WorkspaceGroup>,
+}
+
+impl Default for WorkspaceInfo {
+    fn default() -> Self {
+        Self {
+            id: None,
+            name: String::new(),
+            coordinates: Vec::new(),
+            state: State::empty(),
+            capabilities: WorkspaceCapabilities::empty(),
+            group: None,
+        }
+    }
+}
+
+impl WorkspaceInfo {
+    /// Whether the workspace is shown.
+    pub fn is_active(&self) -> bool {
+        self.state.contains(State::Active)
+    }
+}
+
+/// The change of a group or workspace applied on a `done` event.
+enum Change {
+    New,
+    Update,
+    Removed,
+}
+
+#[derive(Debug)]
+struct Inner<T> {
+    pending: T,
+    current: Option<T>,
+    removed: bool,
+}
+
+impl<T: Default> Default for Inner<T> {
+    fn default() -> Self {
+        Self { pending: T::default(), current: None, removed: false }
+    }
+}
+
+impl<T: Clone + PartialEq> Inner<T> {
+    fn apply(&mut self) -> Option<Change> {
+        if self.removed {
+            return Some(Change::Removed);
+        }
+
+        match &self.current {
+            Some(current) if current == &self.pending => None,
+            current => {
+                let change = if current.is_some() { Change::Update } else { Change::New };
+                self.current = Some(self.pending.clone());
+                Some(change)
+            }
+        }
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct WorkspaceGroupData {
+    inner: Mutex<Inner<WorkspaceGroupInfo>>,
+}
+
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct WorkspaceData {
+    inner: Mutex<Inner<WorkspaceInfo>>,
+}
+
+impl<D> Dispatch<ext_workspace_manager_v1::ExtWorkspaceManagerV1, GlobalData, D> for WorkspaceState
+where
+    D: Dispatch<ext_workspace_manager_v1::ExtWorkspaceManagerV1, GlobalData>
+        + Dispatch<ext_workspace_group_handle_v1::ExtWorkspaceGroupHandleV1, WorkspaceGroupData>
+        + Dispatch<ext_workspace_handle_v1::ExtWorkspaceHandleV1, WorkspaceData>
+        + WorkspaceHandler
+        + 'static,
+{
+    event_created_child!(D, ext_workspace_manager_v1::ExtWorkspaceManagerV1, [
+        ext_workspace_manager_v1::EVT_WORKSPACE_GROUP_OPCODE => (ext_workspace_group_handle_v1::ExtWorkspaceGroupHandleV1, Default::default()),
+        ext_workspace_manager_v1::EVT_WORKSPACE_OPCODE => (ext_workspace_handle_v1::ExtWorkspaceHandleV1, Default::default())
+    ]);
+
+    fn event(
+        state: &mut D,
+        _: &ext_workspace_manager_v1::ExtWorkspaceManagerV1,
+        event: ext_workspace_manager_v1::Event,
+        _: &GlobalData,
+        conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        match event {
+            ext_workspace_manager_v1::Event::WorkspaceGroup { workspace_group } => {
+                state.workspace_state().groups.push(WorkspaceGroup(workspace_group));
+            }
+
+            ext_workspace_manager_v1::Event::Workspace { workspace } => {
+                state.workspace_state().workspaces.push(Workspace(workspace));
+            }
+
+            ext_workspace_manager_v1::Event::Done => {
+                // Report the workspaces first, so the workspaces of a group are known when the group
+                // is reported.
+                for workspace in state.workspace_state().workspaces.clone() {
+                    let change = workspace.data().inner.lock().unwrap().apply();
+                    match change {
+                        Some(Change::New) => state.new_workspace(conn, qh, &workspace),
+                        Some(Change::Update) => state.update_workspace(conn, qh, &workspace),
+                        Some(Change::Removed) => {
+                            state.workspace_state().workspaces.retain(|w| w != &workspace);
+                            state.workspace_removed(conn, qh, &workspace);
+                            workspace.0.destroy();
+                        }
+                        None => (),
+                    }
+                }
+
+                for group in state.workspace_state().groups.clone() {
+                    let change = group.data().inner.lock().unwrap().apply();
+                    match change {
+                        Some(Change::New) => state.new_workspace_group(conn, qh, &group),
+                        Some(Change::Update) => state.update_workspace_group(conn, qh, &group),
+                        Some(Change::Removed) => {
+                            state.workspace_state().groups.retain(|g| g != &group);
+                            state.workspace_group_removed(conn, qh, &group);
+                            group.0.destroy();
+                        }
+                        None => (),
+                    }
+                }
+            }
+
+            ext_workspace_manager_v1::Event::Finished => {
+                let workspace_state = state.workspace_state();
+                workspace_state.finished = true;
+
+                for workspace in std::mem::take(&mut workspace_state.workspaces) {
+                    state.workspace_removed(conn, qh, &workspace);
+                    workspace.0.destroy();
+                }
+
+                for group in std::mem::take(&mut state.workspace_state().groups) {
+                    state.workspace_group_removed(conn, qh, &group);
+                    group.0.destroy();
+                }
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ext_workspace_group_handle_v1::ExtWorkspaceGroupHandleV1, WorkspaceGroupData, D>
+    for WorkspaceState
+where
+    D: Dispatch<ext_workspace_group_handle_v1::ExtWorkspaceGroupHandleV1, WorkspaceGroupData>
+        + WorkspaceHandler,
+{
+    fn event(
+        _: &mut D,
+        group: &ext_workspace_group_handle_v1::ExtWorkspaceGroupHandleV1,
+        event: ext_workspace_group_handle_v1::Event,
+        data: &WorkspaceGroupData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        let mut inner = data.inner.lock().unwrap();
+
+        match event {
+            ext_workspace_group_handle_v1::Event::Capabilities { capabilities } => {
+                inner.pending.capabilities = match capabilities {
+                    WEnum::Value(capabilities) => capabilities,
+                    WEnum::Unknown(raw) => GroupCapabilities::from_bits_truncate(raw),
+                };
+            }
+
+            ext_workspace_group_handle_v1::Event::OutputEnter { output } => {
+                inner.pending.outputs.push(output);
+            }
+
+            ext_workspace_group_handle_v1::Event::OutputLeave { output } => {
+                inner.pending.outputs.retain(|o| o != &output);
+            }
+
+            ext_workspace_group_handle_v1::Event::WorkspaceEnter { workspace } => {
+                if let Some(workspace_data) = workspace.data::<WorkspaceData>() {
+                    workspace_data.inner.lock().unwrap().pending.group =
+                        Some(WorkspaceGroup(group.clone()));
+                }
+                inner.pending.workspaces.push(Workspace(workspace));
+            }
+
+            ext_workspace_group_handle_v1::Event::WorkspaceLeave { workspace } => {
+                if let Some(workspace_data) = workspace.data::<WorkspaceData>() {
+                    let mut workspace_inner = workspace_data.inner.lock().unwrap();
+                    if workspace_inner.pending.group.as_ref().map(WorkspaceGroup::group)
+                        == Some(group)
+                    {
+                        workspace_inner.pending.group = None;
+                    }
+                }
+                inner.pending.workspaces.retain(|w| w.0 != workspace);
+            }
+
+            ext_workspace_group_handle_v1::Event::Removed => {
+                inner.removed = true;
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ext_workspace_handle_v1::ExtWorkspaceHandleV1, WorkspaceData, D> for WorkspaceState
+where
+    D: Dispatch<ext_workspace_handle_v1::ExtWorkspaceHandleV1, WorkspaceData> + WorkspaceHandler,
+{
+    fn event(
+        _: &mut D,
+        _: &ext_workspace_handle_v1::ExtWorkspaceHandleV1,
+        event: ext_workspace_handle_v1::Event,
+        data: &WorkspaceData,
+        _: &Connection,
+        _: &QueueHandle<D>,
+    ) {
+        let mut inner = data.inner.lock().unwrap();
+
+        match event {
+            ext_workspace_handle_v1::Event::Id { id } => {
+                inner.pending.id = Some(id);
+            }
+
+            ext_workspace_handle_v1::Event::Name { name } => {
+                inner.pending.name = name;
+            }
+
+            ext_workspace_handle_v1::Event::Coordinates { coordinates } => {
+                inner.pending.coordinates = coordinates
+                    .chunks_exact(4)
+                    .map(|c| u32::from_ne_bytes([c[0], c[1], c[2], c[3]]))
+                    .collect();
+            }
+
+            ext_workspace_handle_v1::Event::State { state } => {
+                inner.pending.state = match state {
+                    WEnum::Value(state) => state,
+                    WEnum::Unknown(raw) => State::from_bits_truncate(raw),
+                };
+            }
+
+            ext_workspace_handle_v1::Event::Capabilities { capabilities } => {
+                inner.pending.capabilities = match capabilities {
+                    WEnum::Value(capabilities) => capabilities,
+                    WEnum::Unknown(raw) => WorkspaceCapabilities::from_bits_truncate(raw),
+                };
+            }
+
+            ext_workspace_handle_v1::Event::Removed => {
+                inner.removed = true;
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_workspace {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty $(where $($bound:tt)+)?) => {
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::ext::workspace::v1::client::ext_workspace_manager_v1::ExtWorkspaceManagerV1: $crate::globals::GlobalData
+        ] => $crate::workspace::WorkspaceState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::ext::workspace::v1::client::ext_workspace_group_handle_v1::ExtWorkspaceGroupHandleV1: $crate::workspace::WorkspaceGroupData
+        ] => $crate::workspace::WorkspaceState);
+        $crate::__delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty $(where { $($bound)+ })?: [
+            $crate::reexports::protocols::ext::workspace::v1::client::ext_workspace_handle_v1::ExtWorkspaceHandleV1: $crate::workspace::WorkspaceData
+        ] => $crate::workspace::WorkspaceState);
+    };
+}